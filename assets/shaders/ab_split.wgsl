@@ -0,0 +1,37 @@
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+
+struct CompareSplit {
+    // The view's area in the target, in UV: min x, min y, max x, max y
+    viewport: vec4<f32>,
+    divider: f32,      // 0 left .. 1 right of the view
+    line_width: f32,   // divider width in UV
+    _padding: vec2<f32>,
+};
+@group(0) @binding(2) var<uniform> split: CompareSplit;
+
+// Snapshot B, drawn by the compare camera at the same size and viewport
+@group(0) @binding(3) var b_texture: texture_2d<f32>;
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let a = textureSample(screen_texture, texture_sampler, in.uv);
+    let b = textureSample(b_texture, texture_sampler, in.uv);
+
+    let v = split.viewport;
+    let inside = in.uv.x >= v.x && in.uv.x <= v.z && in.uv.y >= v.y && in.uv.y <= v.w;
+    if (!inside) {
+        return a;
+    }
+
+    let x = mix(v.x, v.z, split.divider);
+    if (abs(in.uv.x - x) <= split.line_width * 0.5) {
+        return vec4<f32>(1.0, 1.0, 1.0, 1.0);
+    }
+    if (in.uv.x < x) {
+        return a;
+    }
+    return b;
+}
@@ -25,6 +25,10 @@
 struct PostProcessSettings {
     enabled: u32,
     intensity: f32,
+#ifdef SIXTEEN_BYTE_ALIGNMENT
+    // WebGL2 structs must be 16 byte aligned.
+    _webgl2_padding: vec2<f32>,
+#endif
 }
 @group(0) @binding(2) var<uniform> settings: PostProcessSettings;
 
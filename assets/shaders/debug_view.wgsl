@@ -0,0 +1,96 @@
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+
+struct DebugViewSettings {
+    mode: u32,           // 1 depth, 2 normals, 3 overdraw, 4 shadow cascades
+    depth_range: f32,    // distance shown as black in the depth view
+    overdraw_step: f32,  // what one layer adds to the overdraw image
+    _padding: f32,
+    cascades: vec4<f32>, // far bound of each shadow cascade, 0 if there's none
+    view_from_clip: mat4x4<f32>,
+};
+@group(0) @binding(2) var<uniform> settings: DebugViewSettings;
+
+// Main view depth (reverse-Z: 0 is the far plane / sky)
+@group(0) @binding(3) var depth_texture: texture_depth_2d;
+// World normals packed into 0..1
+@group(0) @binding(4) var normal_texture: texture_2d<f32>;
+// Sum of the additive overdraw copies
+@group(0) @binding(5) var overdraw_texture: texture_2d<f32>;
+
+// Distance along the view direction for a depth buffer value (either projection)
+fn view_distance(depth: f32) -> f32 {
+    let p = settings.view_from_clip * vec4<f32>(0.0, 0.0, depth, 1.0);
+    return -p.z / p.w;
+}
+
+// Black, blue, green, yellow, red, white as the layer count goes up
+fn heat(layers: f32) -> vec3<f32> {
+    var stops = array<vec3<f32>, 6>(
+        vec3<f32>(0.0, 0.0, 0.0),
+        vec3<f32>(0.1, 0.2, 0.9),
+        vec3<f32>(0.1, 0.8, 0.2),
+        vec3<f32>(0.95, 0.85, 0.1),
+        vec3<f32>(0.95, 0.15, 0.1),
+        vec3<f32>(1.0, 1.0, 1.0),
+    );
+    // 0, 1, 2, 3, 4 and 8 layers
+    let t = select(layers, 4.0 + (layers - 4.0) / 4.0, layers > 4.0);
+    let i = u32(clamp(floor(t), 0.0, 4.0));
+    return mix(stops[i], stops[i + 1u], clamp(t - f32(i), 0.0, 1.0));
+}
+
+fn cascade_tint(index: u32) -> vec3<f32> {
+    var tints = array<vec3<f32>, 4>(
+        vec3<f32>(1.0, 0.25, 0.25),
+        vec3<f32>(0.25, 1.0, 0.25),
+        vec3<f32>(0.3, 0.45, 1.0),
+        vec3<f32>(1.0, 0.95, 0.25),
+    );
+    return tints[index];
+}
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let base = textureSample(screen_texture, texture_sampler, in.uv);
+    let coords = vec2<i32>(in.position.xy);
+    let depth = textureLoad(depth_texture, coords, 0);
+
+    switch settings.mode {
+        case 1u: {
+            if (depth <= 0.0) {
+                return vec4<f32>(0.0, 0.0, 0.0, 1.0);
+            }
+            let shade = 1.0 - clamp(view_distance(depth) / settings.depth_range, 0.0, 1.0);
+            return vec4<f32>(vec3<f32>(shade), 1.0);
+        }
+        case 2u: {
+            if (depth <= 0.0) {
+                return vec4<f32>(0.0, 0.0, 0.0, 1.0);
+            }
+            return vec4<f32>(textureLoad(normal_texture, coords, 0).rgb, 1.0);
+        }
+        case 3u: {
+            let sum = textureSample(overdraw_texture, texture_sampler, in.uv).r;
+            return vec4<f32>(heat(sum / settings.overdraw_step), 1.0);
+        }
+        case 4u: {
+            if (depth <= 0.0) {
+                return base;
+            }
+            let z = view_distance(depth);
+            for (var i = 0u; i < 4u; i++) {
+                let far = settings.cascades[i];
+                if (far > 0.0 && z <= far) {
+                    return vec4<f32>(mix(base.rgb, base.rgb * cascade_tint(i), 0.7), base.a);
+                }
+            }
+            return base;
+        }
+        default: {
+            return base;
+        }
+    }
+}
@@ -0,0 +1,68 @@
+#import bevy_pbr::{
+    pbr_fragment::pbr_input_from_standard_material,
+    pbr_functions::alpha_discard,
+}
+
+#ifdef PREPASS_PIPELINE
+#import bevy_pbr::{
+    prepass_io::{VertexOutput, FragmentOutput},
+    pbr_deferred_functions::deferred_output,
+}
+#else
+#import bevy_pbr::{
+    forward_io::{VertexOutput, FragmentOutput},
+    pbr_functions::{apply_pbr_lighting, main_pass_post_lighting_processing},
+}
+#endif
+
+struct DissolveParams {
+    amount: f32,       // 0 = whole, 1 = gone
+    edge_width: f32,   // noise range that glows just before it's cut away
+    noise_scale: f32,  // noise cells per world unit
+    _pad: f32,
+};
+@group(#{MATERIAL_BIND_GROUP}) @binding(100) var<uniform> dissolve: DissolveParams;
+@group(#{MATERIAL_BIND_GROUP}) @binding(101) var<uniform> edge_color: vec4<f32>;
+
+fn hash(p: vec3<f32>) -> f32 {
+    let q = fract(p * 0.3183099 + vec3<f32>(0.1, 0.2, 0.3)) * 17.0;
+    return fract(q.x * q.y * q.z * (q.x + q.y + q.z));
+}
+
+fn value_noise(p: vec3<f32>) -> f32 {
+    let i = floor(p);
+    let f = fract(p);
+    let u = f * f * (3.0 - 2.0 * f);
+    let x00 = mix(hash(i), hash(i + vec3<f32>(1.0, 0.0, 0.0)), u.x);
+    let x10 = mix(hash(i + vec3<f32>(0.0, 1.0, 0.0)), hash(i + vec3<f32>(1.0, 1.0, 0.0)), u.x);
+    let x01 = mix(hash(i + vec3<f32>(0.0, 0.0, 1.0)), hash(i + vec3<f32>(1.0, 0.0, 1.0)), u.x);
+    let x11 = mix(hash(i + vec3<f32>(0.0, 1.0, 1.0)), hash(i + vec3<f32>(1.0, 1.0, 1.0)), u.x);
+    return mix(mix(x00, x10, u.y), mix(x01, x11, u.y), u.z);
+}
+
+@fragment
+fn fragment(in: VertexOutput, @builtin(front_facing) is_front: bool) -> FragmentOutput {
+    // World-space noise, so neighbouring blocks break up as one surface
+    let p = in.world_position.xyz * dissolve.noise_scale;
+    let n = value_noise(p) * 0.65 + value_noise(p * 2.7) * 0.35;
+    // Stretch the cut so amount 0 keeps everything and 1 removes everything, glow included
+    let cut = dissolve.amount * (1.0 + dissolve.edge_width);
+    if n < cut - dissolve.edge_width {
+        discard;
+    }
+
+    var pbr_input = pbr_input_from_standard_material(in, is_front);
+    pbr_input.material.base_color = alpha_discard(pbr_input.material, pbr_input.material.base_color);
+    if n < cut {
+        pbr_input.material.emissive = vec4<f32>(pbr_input.material.emissive.rgb + edge_color.rgb, 1.0);
+    }
+
+#ifdef PREPASS_PIPELINE
+    let out = deferred_output(in, pbr_input);
+#else
+    var out: FragmentOutput;
+    out.color = apply_pbr_lighting(pbr_input);
+    out.color = main_pass_post_lighting_processing(pbr_input, out.color);
+#endif
+    return out;
+}
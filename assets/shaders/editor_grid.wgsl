@@ -0,0 +1,55 @@
+#import bevy_pbr::{
+    forward_io::VertexOutput,
+    mesh_view_bindings::view,
+}
+
+struct GridParams {
+    cell_size: f32,     // world units between minor lines
+    major_every: f32,   // minor cells per major line
+    fade_factor: f32,   // lines fade out at this many camera heights away
+    opacity: f32,
+};
+@group(#{MATERIAL_BIND_GROUP}) @binding(0) var<uniform> params: GridParams;
+@group(#{MATERIAL_BIND_GROUP}) @binding(1) var<uniform> line_color: vec4<f32>;
+
+// 1 on a line of the grid with the given spacing, antialiased to about a pixel
+fn grid_lines(coord: vec2<f32>) -> f32 {
+    let width = fwidth(coord);
+    let dist = abs(fract(coord - 0.5) - 0.5) / max(width, vec2<f32>(1e-5));
+    return 1.0 - min(min(dist.x, dist.y), 1.0);
+}
+
+@fragment
+fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    let p = in.world_position.xz;
+    let minor_coord = p / params.cell_size;
+    let major_coord = minor_coord / params.major_every;
+
+    // Drop the minor lines where they get closer than a few pixels apart
+    let density = max(fwidth(minor_coord).x, fwidth(minor_coord).y);
+    let minor = grid_lines(minor_coord) * (1.0 - smoothstep(0.15, 0.4, density)) * 0.35;
+    let major = grid_lines(major_coord) * 0.8;
+
+    var color = line_color.rgb;
+    var alpha = max(minor, major);
+
+    // World axes: X (z = 0) in red, Z (x = 0) in blue
+    let axis_width = fwidth(p);
+    let on_x = 1.0 - min(abs(p.y) / max(axis_width.y, 1e-5), 1.0);
+    let on_z = 1.0 - min(abs(p.x) / max(axis_width.x, 1e-5), 1.0);
+    if (on_x > 0.0) {
+        color = mix(color, vec3<f32>(0.9, 0.2, 0.2), on_x);
+        alpha = max(alpha, on_x);
+    }
+    if (on_z > 0.0) {
+        color = mix(color, vec3<f32>(0.2, 0.4, 0.95), on_z);
+        alpha = max(alpha, on_z);
+    }
+
+    // Fade with distance, scaled by how high the camera is so zooming out shows more grid
+    let fade_dist = params.fade_factor * max(abs(view.world_position.y), 1.0);
+    let dist = length(view.world_position.xz - p);
+    let fade = 1.0 - smoothstep(fade_dist * 0.4, fade_dist, dist);
+
+    return vec4<f32>(color, alpha * fade * params.opacity * line_color.a);
+}
@@ -0,0 +1,94 @@
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+
+struct LensFlareSettings {
+    enabled: u32,
+    emissive_sources: u32,
+    intensity: f32,
+    ghost_intensity: f32,
+    halo_intensity: f32,
+    emissive_threshold: f32,
+    source_count: u32,
+    // xy = source uv, z = strength (0..1), w = NDC depth of the source (0 = sky)
+    sources: array<vec4<f32>, 4>,
+};
+@group(0) @binding(2) var<uniform> settings: LensFlareSettings;
+@group(0) @binding(3) var depth_texture: texture_depth_2d;
+
+// Fraction of a small kernel around the source that isn't covered by closer geometry.
+fn visibility(src: vec4<f32>) -> f32 {
+    let dims = vec2<f32>(textureDimensions(depth_texture));
+    let center = src.xy * dims;
+    var visible = 0.0;
+    for (var y = -2; y <= 2; y++) {
+        for (var x = -2; x <= 2; x++) {
+            let p = clamp(center + vec2<f32>(f32(x), f32(y)) * 3.0, vec2<f32>(0.0), dims - 1.0);
+            let d = textureLoad(depth_texture, vec2<i32>(p), 0);
+            // Reverse-Z: a larger depth is closer to the camera than the source
+            if (d <= src.w + 0.0005) {
+                visible += 1.0;
+            }
+        }
+    }
+    return visible / 25.0;
+}
+
+// Soft disc of `radius` (in aspect-corrected uv units)
+fn disc(uv: vec2<f32>, center: vec2<f32>, radius: f32, aspect: f32) -> f32 {
+    let d = length((uv - center) * vec2<f32>(aspect, 1.0));
+    return 1.0 - smoothstep(radius * 0.3, radius, d);
+}
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let base = textureSample(screen_texture, texture_sampler, in.uv);
+
+    if (settings.enabled == 0u || settings.source_count == 0u) {
+        return base;
+    }
+
+    let dims = vec2<f32>(textureDimensions(screen_texture));
+    let aspect = dims.x / dims.y;
+
+    // Ghost placement along the source -> center axis (1.0 = mirrored through the center)
+    var ghost_pos = array<f32, 4>(0.35, 0.7, 1.15, 1.5);
+    var ghost_size = array<f32, 4>(0.05, 0.025, 0.08, 0.035);
+    var ghost_tint = array<vec3<f32>, 4>(
+        vec3<f32>(1.0, 0.6, 0.3),
+        vec3<f32>(0.4, 0.8, 1.0),
+        vec3<f32>(0.6, 1.0, 0.6),
+        vec3<f32>(1.0, 0.45, 0.8),
+    );
+
+    var flare = vec3<f32>(0.0);
+    for (var i = 0u; i < min(settings.source_count, 4u); i++) {
+        let src = settings.sources[i];
+        let vis = visibility(src) * src.z;
+        if (vis <= 0.0) {
+            continue;
+        }
+
+        // Glow around the source itself
+        let d = length((in.uv - src.xy) * vec2<f32>(aspect, 1.0));
+        flare += vec3<f32>(1.0, 0.95, 0.85) * exp(-d * 18.0) * settings.intensity * vis;
+
+        // Ghosts mirrored through the screen center
+        let to_center = vec2<f32>(0.5) - src.xy;
+        for (var g = 0; g < 4; g++) {
+            let c = src.xy + to_center * 2.0 * ghost_pos[g];
+            flare += ghost_tint[g] * disc(in.uv, c, ghost_size[g], aspect) * settings.ghost_intensity * vis;
+        }
+
+        // Halo: a ring around the center, brightest on the side facing the source
+        let from_center = (in.uv - vec2<f32>(0.5)) * vec2<f32>(aspect, 1.0);
+        let r = length(from_center);
+        let src_dir = -to_center * vec2<f32>(aspect, 1.0);
+        let align = max(dot(from_center / max(r, 1e-4), src_dir / max(length(src_dir), 1e-4)), 0.0);
+        let ring = 1.0 - smoothstep(0.0, 0.03, abs(r - 0.35));
+        flare += vec3<f32>(0.8, 0.9, 1.0) * ring * pow(align, 4.0) * settings.halo_intensity * vis;
+    }
+
+    return vec4<f32>(base.rgb + flare, base.a);
+}
@@ -7,6 +7,10 @@ struct LutParams {
   enabled: u32,
   strength: f32,  // 0..1
   lut_size: f32,
+#ifdef SIXTEEN_BYTE_ALIGNMENT
+  // WebGL2 structs must be 16 byte aligned.
+  _webgl2_padding: f32,
+#endif
 }
 @group(0) @binding(2) var<uniform> params: LutParams;
 
@@ -0,0 +1,31 @@
+#import bevy_pbr::{
+    forward_io::VertexOutput,
+    mesh_view_bindings::view,
+}
+
+struct ReflectionParams {
+    strength: f32,       // 0..1 overall opacity of the reflection
+    fresnel_power: f32,  // higher = reflection only at grazing angles
+    _pad0: f32,
+    _pad1: f32,
+};
+@group(#{MATERIAL_BIND_GROUP}) @binding(0) var<uniform> params: ReflectionParams;
+@group(#{MATERIAL_BIND_GROUP}) @binding(1) var reflection_texture: texture_2d<f32>;
+@group(#{MATERIAL_BIND_GROUP}) @binding(2) var reflection_sampler: sampler;
+
+@fragment
+fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    // The mirror camera renders a horizontally flipped image (it is a proper rotation,
+    // not a true reflection), so sample it with x flipped.
+    let screen_uv = (in.position.xy - view.viewport.xy) / view.viewport.zw;
+    let uv = vec2<f32>(1.0 - screen_uv.x, screen_uv.y);
+    let reflected = textureSample(reflection_texture, reflection_sampler, uv).rgb;
+
+    // Schlick-ish fresnel: more reflection at grazing angles
+    let v = normalize(view.world_position - in.world_position.xyz);
+    let n = normalize(in.world_normal);
+    let fresnel = pow(1.0 - max(dot(v, n), 0.0), params.fresnel_power);
+    let alpha = clamp(params.strength * mix(0.35, 1.0, fresnel), 0.0, 1.0);
+
+    return vec4<f32>(reflected, alpha);
+}
@@ -0,0 +1,26 @@
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+
+struct ScopeSource {
+    // The view's area in the target, in UV: min x, min y, max x, max y
+    viewport: vec4<f32>,
+};
+@group(0) @binding(2) var<uniform> source: ScopeSource;
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let v = source.viewport;
+    let uv = mix(v.xy, v.zw, in.uv);
+
+    // Four taps a quarter of an output pixel apart, so small bright details still count
+    let step = (v.zw - v.xy) / vec2<f32>(256.0, 144.0) * 0.25;
+    var sum = vec3<f32>(0.0);
+    sum += textureSample(screen_texture, texture_sampler, uv + vec2<f32>(-step.x, -step.y)).rgb;
+    sum += textureSample(screen_texture, texture_sampler, uv + vec2<f32>(step.x, -step.y)).rgb;
+    sum += textureSample(screen_texture, texture_sampler, uv + vec2<f32>(-step.x, step.y)).rgb;
+    sum += textureSample(screen_texture, texture_sampler, uv + vec2<f32>(step.x, step.y)).rgb;
+
+    return vec4<f32>(clamp(sum * 0.25, vec3<f32>(0.0), vec3<f32>(1.0)), 1.0);
+}
@@ -0,0 +1,168 @@
+#import bevy_pbr::{
+    pbr_fragment::pbr_input_from_standard_material,
+    pbr_functions::alpha_discard,
+}
+
+#ifdef PREPASS_PIPELINE
+#import bevy_pbr::{
+    prepass_io::{VertexOutput, FragmentOutput},
+    pbr_deferred_functions::deferred_output,
+}
+#else
+#import bevy_pbr::{
+    forward_io::{VertexOutput, FragmentOutput},
+    mesh_view_bindings::view,
+    pbr_functions::{apply_pbr_lighting, main_pass_post_lighting_processing},
+    pbr_types::PbrInput,
+}
+#endif
+
+struct TriplanarParams {
+    scale: f32,      // texture repeats per world unit
+    sharpness: f32,  // blend exponent; higher = crisper edges between projections
+    enabled: f32,    // 1 = textured
+    _pad: f32,
+};
+struct ToonParams {
+    steps: f32,            // flat light bands; 0 = smooth shading
+    specular_cutoff: f32,  // specular brighter than this shows as a highlight; 1 = none
+    _pad0: f32,
+    _pad1: f32,
+};
+struct SplatParams {
+    layers: f32,   // paint layers; 0 = unpainted
+    repeats: f32,  // layer texture repeats per world unit
+    _pad0: f32,
+    _pad1: f32,
+};
+@group(#{MATERIAL_BIND_GROUP}) @binding(100) var<uniform> triplanar: TriplanarParams;
+@group(#{MATERIAL_BIND_GROUP}) @binding(101) var<uniform> toon: ToonParams;
+@group(#{MATERIAL_BIND_GROUP}) @binding(102) var top_texture: texture_2d<f32>;
+@group(#{MATERIAL_BIND_GROUP}) @binding(103) var top_sampler: sampler;
+@group(#{MATERIAL_BIND_GROUP}) @binding(104) var side_texture: texture_2d<f32>;
+@group(#{MATERIAL_BIND_GROUP}) @binding(105) var side_sampler: sampler;
+@group(#{MATERIAL_BIND_GROUP}) @binding(106) var<uniform> splat: SplatParams;
+@group(#{MATERIAL_BIND_GROUP}) @binding(107) var<uniform> splat_tints: array<vec4<f32>, 4>;
+@group(#{MATERIAL_BIND_GROUP}) @binding(108) var splat_map: texture_2d<f32>;
+@group(#{MATERIAL_BIND_GROUP}) @binding(109) var splat_sampler: sampler;
+@group(#{MATERIAL_BIND_GROUP}) @binding(110) var layer0_texture: texture_2d<f32>;
+@group(#{MATERIAL_BIND_GROUP}) @binding(111) var layer0_sampler: sampler;
+@group(#{MATERIAL_BIND_GROUP}) @binding(112) var layer1_texture: texture_2d<f32>;
+@group(#{MATERIAL_BIND_GROUP}) @binding(113) var layer1_sampler: sampler;
+@group(#{MATERIAL_BIND_GROUP}) @binding(114) var layer2_texture: texture_2d<f32>;
+@group(#{MATERIAL_BIND_GROUP}) @binding(115) var layer2_sampler: sampler;
+@group(#{MATERIAL_BIND_GROUP}) @binding(116) var layer3_texture: texture_2d<f32>;
+@group(#{MATERIAL_BIND_GROUP}) @binding(117) var layer3_sampler: sampler;
+
+fn triplanar_texel(world_position: vec3<f32>, world_normal: vec3<f32>) -> vec4<f32> {
+    // World-space projections, so blocks of any scale keep square texels and line up
+    let p = world_position * triplanar.scale;
+    let n = normalize(world_normal);
+    var w = pow(abs(n), vec3<f32>(triplanar.sharpness));
+    w = w / max(w.x + w.y + w.z, 1e-4);
+
+    // Flip v so the side textures stand upright
+    let x = textureSample(side_texture, side_sampler, vec2<f32>(p.z, -p.y));
+    let z = textureSample(side_texture, side_sampler, vec2<f32>(p.x, -p.y));
+    // The top texture only on upward faces; undersides use the side one
+    let y_top = textureSample(top_texture, top_sampler, p.xz);
+    let y_side = textureSample(side_texture, side_sampler, p.xz);
+    let y = select(y_side, y_top, n.y > 0.0);
+    return x * w.x + y * w.y + z * w.z;
+}
+
+// The paint layers mixed by their shares in the splat map (over the mesh UVs), each texture
+// tiling in world space.
+fn splat_color(uv: vec2<f32>, world_position: vec3<f32>) -> vec3<f32> {
+    // Channels of layers that were removed don't count
+    let present = vec4<f32>(1.0, step(1.5, splat.layers), step(2.5, splat.layers), step(3.5, splat.layers));
+    var w = textureSample(splat_map, splat_sampler, uv) * present;
+    let total = w.x + w.y + w.z + w.w;
+    w = select(vec4<f32>(1.0, 0.0, 0.0, 0.0), w / max(total, 1e-4), total > 1e-3);
+
+    let p = world_position.xz * splat.repeats;
+    let c0 = textureSample(layer0_texture, layer0_sampler, p).rgb * splat_tints[0].rgb;
+    let c1 = textureSample(layer1_texture, layer1_sampler, p).rgb * splat_tints[1].rgb;
+    let c2 = textureSample(layer2_texture, layer2_sampler, p).rgb * splat_tints[2].rgb;
+    let c3 = textureSample(layer3_texture, layer3_sampler, p).rgb * splat_tints[3].rgb;
+    return c0 * w.x + c1 * w.y + c2 * w.z + c3 * w.w;
+}
+
+fn luminance(c: vec3<f32>) -> f32 {
+    return dot(c, vec3<f32>(0.2126, 0.7152, 0.0722));
+}
+
+#ifndef PREPASS_PIPELINE
+// Diffuse light snapped to flat bands, plus a flat highlight where the specular is bright.
+fn toon_lighting(pbr_input: PbrInput) -> vec4<f32> {
+    let material = pbr_input.material;
+
+    // Diffuse only: no reflectance, nothing metallic, emissive added back unbanded
+    var diffuse_input = pbr_input;
+    diffuse_input.material.reflectance = vec3<f32>(0.0);
+    diffuse_input.material.metallic = 0.0;
+    diffuse_input.material.emissive = vec4<f32>(0.0);
+    let diffuse = apply_pbr_lighting(diffuse_input);
+
+    // How lit the surface is, relative to its own color, rounded up to the next band
+    let albedo = max(luminance(material.base_color.rgb), 1e-4);
+    let shade = luminance(diffuse.rgb) / albedo;
+    let banded = ceil(shade * toon.steps) / toon.steps;
+    var color = diffuse.rgb * (banded / max(shade, 1e-4));
+
+    if toon.specular_cutoff < 1.0 {
+        // Specular only: a black surface keeps just its reflections
+        var specular_input = pbr_input;
+        specular_input.material.base_color = vec4<f32>(0.0, 0.0, 0.0, material.base_color.a);
+        specular_input.material.emissive = vec4<f32>(0.0);
+        let specular = apply_pbr_lighting(specular_input).rgb;
+        let strength = luminance(specular);
+        if strength > toon.specular_cutoff {
+            color += specular / max(strength, 1e-4) * 0.6;
+        }
+    }
+
+    let emissive = material.emissive.rgb * material.base_color.a
+        * mix(1.0, view.exposure, material.emissive.a);
+    return vec4<f32>(color + emissive, diffuse.a);
+}
+#endif
+
+@fragment
+fn fragment(in: VertexOutput, @builtin(front_facing) is_front: bool) -> FragmentOutput {
+    var pbr_input = pbr_input_from_standard_material(in, is_front);
+
+    if triplanar.enabled > 0.5 {
+        let texel = triplanar_texel(in.world_position.xyz, in.world_normal);
+        pbr_input.material.base_color = vec4<f32>(
+            pbr_input.material.base_color.rgb * texel.rgb,
+            pbr_input.material.base_color.a,
+        );
+    }
+#ifdef VERTEX_UVS_A
+    // Ground paint replaces the color (each layer has its own tint)
+    if splat.layers > 0.5 {
+        var painted = splat_color(in.uv, in.world_position.xyz);
+#ifdef VERTEX_COLORS
+        // Vertex paint still shades it
+        painted = painted * in.color.rgb;
+#endif
+        pbr_input.material.base_color = vec4<f32>(painted, pbr_input.material.base_color.a);
+    }
+#endif
+    pbr_input.material.base_color = alpha_discard(pbr_input.material, pbr_input.material.base_color);
+
+#ifdef PREPASS_PIPELINE
+    // Deferred lighting is shared by every material, so it stays smooth
+    let out = deferred_output(in, pbr_input);
+#else
+    var out: FragmentOutput;
+    if toon.steps > 0.5 {
+        out.color = toon_lighting(pbr_input);
+    } else {
+        out.color = apply_pbr_lighting(pbr_input);
+    }
+    out.color = main_pass_post_lighting_processing(pbr_input, out.color);
+#endif
+    return out;
+}
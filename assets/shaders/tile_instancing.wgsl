@@ -0,0 +1,67 @@
+#import bevy_pbr::{
+    mesh_view_bindings::{view, lights, fog},
+    mesh_view_types::FOG_MODE_EXPONENTIAL,
+}
+#import bevy_render::maths::PI
+
+#ifdef TONEMAP_IN_SHADER
+#import bevy_core_pipeline::tonemapping::tone_mapping
+#endif
+
+struct TileInstance {
+    world_from_local: mat4x4<f32>,
+    color: vec4<f32>,  // linear RGBA
+};
+@group(1) @binding(0) var<storage, read> instances: array<TileInstance>;
+
+struct Vertex {
+    @builtin(instance_index) instance_index: u32,
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) world_position: vec3<f32>,
+    @location(1) world_normal: vec3<f32>,
+    @location(2) color: vec4<f32>,
+};
+
+@vertex
+fn vertex(vertex: Vertex) -> VertexOutput {
+    let instance = instances[vertex.instance_index];
+    let world_position = instance.world_from_local * vec4<f32>(vertex.position, 1.0);
+
+    var out: VertexOutput;
+    out.clip_position = view.clip_from_world * world_position;
+    out.world_position = world_position.xyz;
+    // Tiles are uniformly scaled, so the model matrix is fine for normals
+    out.world_normal = normalize((instance.world_from_local * vec4<f32>(vertex.normal, 0.0)).xyz);
+    out.color = instance.color;
+    return out;
+}
+
+@fragment
+fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    let n = normalize(in.world_normal);
+
+    // Lambert diffuse from the directional lights plus flat ambient (no shadows received)
+    var light = lights.ambient_color.rgb;
+    for (var i = 0u; i < lights.n_directional_lights; i = i + 1u) {
+        let sun = lights.directional_lights[i];
+        light += sun.color.rgb * max(dot(n, sun.direction_to_light), 0.0) / PI;
+    }
+    var color = vec4<f32>(in.color.rgb * light * view.exposure, in.color.a);
+
+    // Match the scene's exponential distance fog
+    if fog.mode == FOG_MODE_EXPONENTIAL {
+        let distance = length(in.world_position - view.world_position);
+        let visibility = exp(-distance * fog.be.x);
+        color = vec4<f32>(mix(fog.base_color.rgb, color.rgb, visibility), color.a);
+    }
+
+#ifdef TONEMAP_IN_SHADER
+    color = tone_mapping(color, view.color_grading);
+#endif
+    return color;
+}
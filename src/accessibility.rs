@@ -0,0 +1,347 @@
+//! Accessibility options, kept in the app settings: calm effects (no CRT, chromatic aberration,
+//! flickering lights or pulsing selection), high-contrast outlines, colorblind-safe gizmo
+//! colors, hold or toggle for held inputs, and the UI text size. The window also sets the
+//! post effects' photosensitive limiter (`post::governor`).
+//!
+//! Every panel can be driven from the keyboard: Tab and the arrow keys move between widgets,
+//! Space or Enter presses the focused one and Esc leaves the panels. While a widget has focus
+//! the editor's own hotkeys stay quiet, so typing a name or pressing a button with Space
+//! doesn't also toggle build mode or hide the editor.
+
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+use bevy_egui::input::EguiWantsInput;
+use bevy_egui::{EguiContext, EguiContexts, EguiPrimaryContextPass, PrimaryEguiContext, egui};
+use serde::{Deserialize, Serialize};
+
+use crate::SceneEditState;
+use crate::post::CalmEffects;
+use crate::post::governor::EffectGovernor;
+use crate::post::outlines::{OutlineParams, OutlineWidthMode};
+
+/// Colors for the selection box, axes and destructive previews.
+#[derive(Copy, Clone, Default, Eq, PartialEq, Debug, Serialize, Deserialize)]
+pub enum GizmoPalette {
+    #[default]
+    Standard,
+    /// Okabe–Ito colors, told apart with any common color vision deficiency
+    ColorblindSafe,
+    /// Pure, bright colors only
+    HighContrast,
+}
+
+impl GizmoPalette {
+    pub const ALL: [GizmoPalette; 3] = [
+        GizmoPalette::Standard,
+        GizmoPalette::ColorblindSafe,
+        GizmoPalette::HighContrast,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            GizmoPalette::Standard => "Standard",
+            GizmoPalette::ColorblindSafe => "Colorblind-safe",
+            GizmoPalette::HighContrast => "High contrast",
+        }
+    }
+
+    /// Selection box, at full intensity.
+    pub fn selection(self) -> Color {
+        match self {
+            GizmoPalette::Standard => Color::srgb(1.0, 0.85, 0.2),
+            GizmoPalette::ColorblindSafe => Color::srgb(0.94, 0.89, 0.26),
+            GizmoPalette::HighContrast => Color::WHITE,
+        }
+    }
+
+    /// X, Y and Z axes.
+    pub fn axes(self) -> [Color; 3] {
+        match self {
+            GizmoPalette::Standard => [
+                Color::srgb(1.0, 0.0, 0.0),
+                Color::srgb(0.0, 1.0, 0.0),
+                Color::srgb(0.0, 0.0, 1.0),
+            ],
+            GizmoPalette::ColorblindSafe => [
+                Color::srgb(0.84, 0.37, 0.0),
+                Color::srgb(0.0, 0.62, 0.45),
+                Color::srgb(0.34, 0.71, 0.91),
+            ],
+            GizmoPalette::HighContrast => [
+                Color::srgb(1.0, 0.2, 0.2),
+                Color::srgb(0.2, 1.0, 0.2),
+                Color::srgb(0.3, 0.6, 1.0),
+            ],
+        }
+    }
+
+    /// Previews of something about to be removed.
+    pub fn danger(self) -> Color {
+        match self {
+            GizmoPalette::Standard => Color::srgb(1.0, 0.25, 0.2),
+            GizmoPalette::ColorblindSafe => Color::srgb(0.8, 0.47, 0.65),
+            GizmoPalette::HighContrast => Color::srgb(1.0, 0.0, 1.0),
+        }
+    }
+}
+
+/// Whether an input acts while its key is held or flips on each press.
+#[derive(Copy, Clone, Default, Eq, PartialEq, Debug, Serialize, Deserialize)]
+pub enum InputStyle {
+    #[default]
+    Toggle,
+    Hold,
+}
+
+#[derive(Resource, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AccessibilityPrefs {
+    /// Switch off the CRT and chromatic aberration passes, hold animated lights steady and stop
+    /// the selection pulse
+    pub calm_effects: bool,
+    /// Thick black outlines on every object, whatever the post-effects panel says
+    pub high_contrast_outlines: bool,
+    pub palette: GizmoPalette,
+    /// The cutaway key flips cutaway (`Toggle`) or shows it while held (`Hold`)
+    pub cutaway_input: InputStyle,
+    /// Releasing the tilt keys springs the camera back (`Hold`) or leaves it tilted (`Toggle`)
+    pub tilt_input: InputStyle,
+    /// Size of body text in points; headings and the rest scale with it
+    pub font_size: f32,
+}
+
+/// egui's default body size.
+const DEFAULT_FONT_SIZE: f32 = 12.5;
+
+impl Default for AccessibilityPrefs {
+    fn default() -> Self {
+        Self {
+            calm_effects: false,
+            high_contrast_outlines: false,
+            palette: GizmoPalette::Standard,
+            cutaway_input: InputStyle::Toggle,
+            tilt_input: InputStyle::Hold,
+            font_size: DEFAULT_FONT_SIZE,
+        }
+    }
+}
+
+pub struct AccessibilityPlugin;
+impl Plugin for AccessibilityPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AccessibilityPrefs>()
+            .init_resource::<EffectGovernor>()
+            .add_systems(PreUpdate, keys_to_focused_widget.after(InputSystem))
+            .add_systems(
+                Update,
+                (calm_cameras, high_contrast_outlines, apply_font_size),
+            )
+            .add_systems(EguiPrimaryContextPass, accessibility_window);
+    }
+}
+
+/// Names for icon-only widgets.
+pub trait Described {
+    /// Show `text` as the tooltip and give it to screen readers as the widget's name, for
+    /// widgets whose own text is just an icon (✖, ◀, 🔗).
+    fn described(self, text: &str) -> Self;
+}
+
+impl Described for egui::Response {
+    fn described(self, text: &str) -> Self {
+        self.widget_info(|| {
+            egui::WidgetInfo::labeled(egui::WidgetType::Button, self.enabled(), text)
+        });
+        self.on_hover_text(text)
+    }
+}
+
+/// Held alongside other keys, so they aren't taken from shortcuts like Ctrl+click.
+const MODIFIERS: [KeyCode; 8] = [
+    KeyCode::ShiftLeft,
+    KeyCode::ShiftRight,
+    KeyCode::ControlLeft,
+    KeyCode::ControlRight,
+    KeyCode::AltLeft,
+    KeyCode::AltRight,
+    KeyCode::SuperLeft,
+    KeyCode::SuperRight,
+];
+
+/// While an egui widget has keyboard focus, hide its keys from the editor's hotkeys. egui reads
+/// its own keyboard events, so the widget still gets them.
+fn keys_to_focused_widget(
+    wants: Option<Res<EguiWantsInput>>,
+    mut keys: ResMut<ButtonInput<KeyCode>>,
+) {
+    if !wants.is_some_and(|w| w.wants_any_keyboard_input()) {
+        return;
+    }
+    let taken: Vec<KeyCode> = keys
+        .get_pressed()
+        .chain(keys.get_just_released())
+        .filter(|k| !MODIFIERS.contains(k))
+        .copied()
+        .collect();
+    for key in taken {
+        keys.reset(key);
+    }
+}
+
+/// Mark every camera for calm post effects while the option is on.
+fn calm_cameras(
+    mut commands: Commands,
+    prefs: Res<AccessibilityPrefs>,
+    q_cams: Query<(Entity, Has<CalmEffects>), With<Camera3d>>,
+) {
+    for (e, calm) in &q_cams {
+        if prefs.calm_effects && !calm {
+            commands.entity(e).insert(CalmEffects);
+        } else if !prefs.calm_effects && calm {
+            commands.entity(e).remove::<CalmEffects>();
+        }
+    }
+}
+
+/// Force thick black outlines while the option is on, and put the user's back after.
+fn high_contrast_outlines(
+    prefs: Res<AccessibilityPrefs>,
+    outline: Option<ResMut<OutlineParams>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    // Outline settings from before high contrast took over
+    mut saved: Local<Option<(bool, OutlineWidthMode, f32, Color)>>,
+) {
+    let Some(mut outline) = outline else {
+        return;
+    };
+    let (enabled, mode, pixels, color) = match (prefs.high_contrast_outlines, *saved) {
+        (true, None) => {
+            *saved = Some((outline.enabled, outline.mode, outline.pixels, outline.color));
+            (true, OutlineWidthMode::Screen, 3.0, Color::BLACK)
+        }
+        (false, Some(previous)) => {
+            *saved = None;
+            previous
+        }
+        _ => return,
+    };
+    outline.enabled = enabled;
+    outline.mode = mode;
+    outline.pixels = pixels;
+    outline.color = color;
+    if let Some(mat) = materials.get_mut(&outline.material) {
+        mat.base_color = color;
+    }
+}
+
+/// Scale egui's text styles from its defaults whenever the size changes (and once the context
+/// exists).
+fn apply_font_size(
+    prefs: Res<AccessibilityPrefs>,
+    mut q_ctx: Query<&mut EguiContext, With<PrimaryEguiContext>>,
+    mut applied: Local<Option<f32>>,
+) {
+    if *applied == Some(prefs.font_size) {
+        return;
+    }
+    let Ok(mut ctx) = q_ctx.single_mut() else {
+        return;
+    };
+    *applied = Some(prefs.font_size);
+
+    let factor = prefs.font_size.clamp(8.0, 32.0) / DEFAULT_FONT_SIZE;
+    let defaults = egui::Style::default().text_styles;
+    ctx.get_mut().all_styles_mut(|style| {
+        for (text_style, font) in &mut style.text_styles {
+            if let Some(default) = defaults.get(text_style) {
+                font.size = default.size * factor;
+            }
+        }
+    });
+}
+
+fn accessibility_window(
+    mut ctxs: EguiContexts,
+    edit_state: Res<SceneEditState>,
+    mut prefs: ResMut<AccessibilityPrefs>,
+    mut governor: ResMut<EffectGovernor>,
+    mut dragged_size: Local<Option<f32>>,
+) {
+    if !edit_state.open {
+        return;
+    }
+    let Ok(ctx) = ctxs.ctx_mut() else {
+        return;
+    };
+
+    let mut edited = prefs.clone();
+    let mut limits = governor.clone();
+    egui::Window::new("Accessibility")
+        .default_open(false)
+        .show(ctx, |ui| {
+            ui.checkbox(&mut edited.calm_effects, "Calm effects")
+                .on_hover_text(
+                    "No CRT or chromatic aberration, steady lights and no selection pulse",
+                );
+            ui.checkbox(&mut edited.high_contrast_outlines, "High-contrast outlines")
+                .on_hover_text("Thick black outlines on every object");
+
+            ui.separator();
+            ui.checkbox(&mut limits.enabled, "Limit effects")
+                .on_hover_text("Cap post effect strength and how fast it can change or flash");
+            ui.add_enabled_ui(limits.enabled, |ui| {
+                ui.add(
+                    egui::Slider::new(&mut limits.max_strength, 0.0..=1.0)
+                        .text("Max strength")
+                        .custom_formatter(|v, _| format!("{:.0}%", v * 100.0)),
+                );
+                ui.add(
+                    egui::Slider::new(&mut limits.max_flash_hz, 0.5..=3.0)
+                        .step_by(0.5)
+                        .text("Max flashes/s"),
+                );
+            });
+
+            ui.separator();
+            egui::ComboBox::from_label("Gizmo colors")
+                .selected_text(edited.palette.label())
+                .show_ui(ui, |ui| {
+                    for palette in GizmoPalette::ALL {
+                        ui.selectable_value(&mut edited.palette, palette, palette.label());
+                    }
+                });
+
+            ui.separator();
+            for (label, style) in [
+                ("Cutaway key", &mut edited.cutaway_input),
+                ("Camera tilt", &mut edited.tilt_input),
+            ] {
+                ui.horizontal(|ui| {
+                    ui.label(label);
+                    ui.selectable_value(style, InputStyle::Toggle, "Toggle");
+                    ui.selectable_value(style, InputStyle::Hold, "Hold");
+                });
+            }
+
+            ui.separator();
+            // Like the UI scale, the new size applies on release so the slider stays put
+            let mut size = dragged_size.unwrap_or(edited.font_size);
+            let response = ui.add(
+                egui::Slider::new(&mut size, 8.0..=32.0)
+                    .step_by(0.5)
+                    .text("Text size"),
+            );
+            if response.dragged() {
+                *dragged_size = Some(size);
+            } else {
+                *dragged_size = None;
+                edited.font_size = size;
+            }
+            if ui.button("Reset").clicked() {
+                edited = AccessibilityPrefs::default();
+                limits = EffectGovernor::default();
+            }
+        });
+    prefs.set_if_neq(edited);
+    governor.set_if_neq(limits);
+}
@@ -0,0 +1,369 @@
+//! Editor commands as events. Hotkeys, toolbar buttons, scene scripts and the remote API all
+//! send an [`EditorAction`], and a single system carries it out, so anything else
+//! that wants to drive the editor (a command palette, rebindable shortcuts, external tools) only
+//! has to send one too.
+
+use bevy::prelude::*;
+
+use crate::SceneEditState;
+use crate::accessibility::{AccessibilityPrefs, InputStyle};
+use crate::build_mode::BuildMode;
+use crate::camera::{ProjectionEvent, UiPointer};
+#[cfg(all(feature = "capture", not(target_arch = "wasm32")))]
+use crate::capture::ToggleCaptureEvent;
+use crate::clipboard::ClipboardEvent;
+use crate::diagnostics::DiagnosticsOverlay;
+use crate::editor_mode::EditorMode;
+use crate::help::HelpOverlay;
+use crate::inspector::{EditHistoryEvent, InspectorState, SaveSceneEvent};
+use crate::placement::Placement;
+use crate::post::compare::{CompareMode, PostCompare};
+use crate::selection_history::SelectionHistoryEvent;
+use crate::settings::{EditorPrefs, KeyBindings};
+
+/// Something the editor can be asked to do.
+#[derive(Event, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EditorAction {
+    /// Show or hide every editor panel
+    ToggleEditor,
+    ToggleBuildMode,
+    ToggleDiagnostics,
+    ToggleHelp,
+    SetMode(EditorMode),
+    /// Save the scene to the file named in the inspector
+    Save,
+    Undo,
+    Redo,
+    /// Select the previous object in the selection history
+    SelectionBack,
+    SelectionForward,
+    /// Copy the selected object, just beside it
+    Duplicate,
+    /// Copy the last copied object again, one step further on
+    RepeatCopy,
+    /// Put the selected objects on the system clipboard
+    CopyToClipboard,
+    /// Spawn the objects on the system clipboard
+    PasteFromClipboard,
+    /// See through what's in front of the selection
+    ToggleCutaway,
+    /// Start or stop recording the viewport
+    ToggleRecording,
+    /// Flip between the A and B post-effect snapshots, when comparing by toggling
+    FlipCompare,
+    /// Isometric orthographic camera
+    Orthographic,
+    Perspective,
+    /// Turn the object being placed a quarter around Y
+    TurnPlacement,
+    /// Quit the app
+    Quit,
+}
+
+impl EditorAction {
+    /// Every action, except [`EditorAction::SetMode`] which is one per mode.
+    pub const ALL: [EditorAction; 20] = [
+        EditorAction::ToggleEditor,
+        EditorAction::ToggleBuildMode,
+        EditorAction::ToggleDiagnostics,
+        EditorAction::ToggleHelp,
+        EditorAction::Save,
+        EditorAction::Undo,
+        EditorAction::Redo,
+        EditorAction::SelectionBack,
+        EditorAction::SelectionForward,
+        EditorAction::Duplicate,
+        EditorAction::RepeatCopy,
+        EditorAction::CopyToClipboard,
+        EditorAction::PasteFromClipboard,
+        EditorAction::ToggleCutaway,
+        EditorAction::ToggleRecording,
+        EditorAction::FlipCompare,
+        EditorAction::Orthographic,
+        EditorAction::Perspective,
+        EditorAction::TurnPlacement,
+        EditorAction::Quit,
+    ];
+
+    /// The snake_case name used by the remote API, e.g. `"undo"` or `"mode_move"`.
+    pub fn name(self) -> String {
+        let name = match self {
+            EditorAction::ToggleEditor => "toggle_editor",
+            EditorAction::ToggleBuildMode => "toggle_build_mode",
+            EditorAction::ToggleDiagnostics => "toggle_diagnostics",
+            EditorAction::ToggleHelp => "toggle_help",
+            EditorAction::SetMode(mode) => return format!("mode_{}", mode.label().to_lowercase()),
+            EditorAction::Save => "save",
+            EditorAction::Undo => "undo",
+            EditorAction::Redo => "redo",
+            EditorAction::SelectionBack => "selection_back",
+            EditorAction::SelectionForward => "selection_forward",
+            EditorAction::Duplicate => "duplicate",
+            EditorAction::RepeatCopy => "repeat_copy",
+            EditorAction::CopyToClipboard => "copy",
+            EditorAction::PasteFromClipboard => "paste",
+            EditorAction::ToggleCutaway => "toggle_cutaway",
+            EditorAction::ToggleRecording => "toggle_recording",
+            EditorAction::FlipCompare => "flip_compare",
+            EditorAction::Orthographic => "orthographic",
+            EditorAction::Perspective => "perspective",
+            EditorAction::TurnPlacement => "turn_placement",
+            EditorAction::Quit => "quit",
+        };
+        name.to_string()
+    }
+
+    /// The action called `name` (see [`EditorAction::name`]).
+    pub fn from_name(name: &str) -> Option<Self> {
+        let modes = EditorMode::ALL.map(EditorAction::SetMode);
+        Self::ALL
+            .into_iter()
+            .chain(modes)
+            .find(|action| action.name() == name)
+    }
+}
+
+pub struct ActionsPlugin;
+impl Plugin for ActionsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SceneEditState>()
+            .init_resource::<EditorMode>()
+            .init_resource::<KeyBindings>()
+            .add_event::<EditorAction>()
+            // Forwarded to whichever plugins handle them
+            .add_event::<SaveSceneEvent>()
+            .add_event::<EditHistoryEvent>()
+            .add_event::<SelectionHistoryEvent>()
+            .add_event::<ClipboardEvent>()
+            .add_event::<ProjectionEvent>()
+            .add_systems(Update, (action_hotkeys, dispatch_actions).chain());
+        #[cfg(all(feature = "capture", not(target_arch = "wasm32")))]
+        app.add_event::<ToggleCaptureEvent>();
+    }
+}
+
+/// The editor's fixed shortcuts and the rebindable ones from [`KeyBindings`].
+fn action_hotkeys(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    edit_state: Res<SceneEditState>,
+    mode: Res<EditorMode>,
+    mut ui_pointer: UiPointer,
+    mut actions: EventWriter<EditorAction>,
+    (access, prefs, placement, compare): (
+        Option<Res<AccessibilityPrefs>>,
+        Option<Res<EditorPrefs>>,
+        Option<Res<Placement>>,
+        Option<Res<PostCompare>>,
+    ),
+) {
+    let typing = ui_pointer.wants_keyboard();
+    if keys.just_pressed(bindings.toggle_editor) {
+        actions.write(EditorAction::ToggleEditor);
+    }
+    if keys.just_pressed(bindings.diagnostics) {
+        actions.write(EditorAction::ToggleDiagnostics);
+    }
+    if keys.just_pressed(bindings.help) {
+        actions.write(EditorAction::ToggleHelp);
+    }
+    if keys.just_pressed(bindings.quit) {
+        actions.write(EditorAction::Quit);
+    }
+    if keys.just_pressed(bindings.record) {
+        actions.write(EditorAction::ToggleRecording);
+    }
+    if keys.just_pressed(bindings.ab_compare)
+        && compare.is_some_and(|c| c.mode == CompareMode::Toggle)
+    {
+        actions.write(EditorAction::FlipCompare);
+    }
+    if !typing && keys.just_pressed(bindings.orthographic) {
+        actions.write(EditorAction::Orthographic);
+    } else if !typing && keys.just_pressed(bindings.perspective) {
+        actions.write(EditorAction::Perspective);
+    }
+    // While placing, Enter drops the object instead
+    #[cfg(feature = "player")]
+    if keys.just_pressed(bindings.drop_player)
+        && !placement.as_ref().is_some_and(|p| p.captures_pointer())
+    {
+        actions.write(EditorAction::SetMode(EditorMode::Play));
+    }
+    // In Play mode the editor is usually closed; held, the cutaway is on only while the key is
+    if (edit_state.open || *mode == EditorMode::Play) && !typing {
+        let on = prefs.is_some_and(|p| p.cutaway);
+        let flip = match access.map_or(InputStyle::Toggle, |a| a.cutaway_input) {
+            InputStyle::Toggle => keys.just_pressed(bindings.cutaway),
+            InputStyle::Hold => {
+                (keys.just_pressed(bindings.cutaway) && !on)
+                    || (keys.just_released(bindings.cutaway) && on)
+            }
+        };
+        if flip {
+            actions.write(EditorAction::ToggleCutaway);
+        }
+    }
+    if !edit_state.open {
+        return;
+    }
+    if keys.just_pressed(bindings.build_mode) {
+        actions.write(EditorAction::ToggleBuildMode);
+    }
+    if !typing
+        && keys.just_pressed(bindings.turn_placement)
+        && placement.is_some_and(|p| p.is_active())
+    {
+        actions.write(EditorAction::TurnPlacement);
+    }
+    // Arrow keys walk the player in Play mode
+    if *mode == EditorMode::Play {
+        return;
+    }
+
+    let ctrl = keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+    let shift = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    let alt = keys.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]);
+    // Ctrl shortcuts are the text field's while one is being typed in (its own undo, copy, ...)
+    if ctrl && !typing {
+        // Ctrl+Z undoes, Ctrl+Shift+Z (or Ctrl+Y) redoes
+        if keys.just_pressed(bindings.undo) {
+            actions.write(if shift {
+                EditorAction::Redo
+            } else {
+                EditorAction::Undo
+            });
+        } else if keys.just_pressed(bindings.redo) {
+            actions.write(EditorAction::Redo);
+        }
+        // Ctrl+D duplicates, Ctrl+Shift+D repeats the last copy
+        if shift && keys.just_pressed(bindings.repeat_copy) {
+            actions.write(EditorAction::RepeatCopy);
+        } else if !shift && keys.just_pressed(bindings.duplicate) {
+            actions.write(EditorAction::Duplicate);
+        }
+        // Ctrl+C/Ctrl+V copy and paste objects
+        if keys.just_pressed(bindings.copy) {
+            actions.write(EditorAction::CopyToClipboard);
+        }
+        if keys.just_pressed(bindings.paste) {
+            actions.write(EditorAction::PasteFromClipboard);
+        }
+    }
+    if alt {
+        if keys.just_pressed(bindings.selection_back) {
+            actions.write(EditorAction::SelectionBack);
+        }
+        if keys.just_pressed(bindings.selection_forward) {
+            actions.write(EditorAction::SelectionForward);
+        }
+    }
+}
+
+/// Carry out this frame's actions. Those of plugins that aren't added do nothing.
+fn dispatch_actions(
+    mut actions: EventReader<EditorAction>,
+    mut edit_state: ResMut<SceneEditState>,
+    mut mode: ResMut<EditorMode>,
+    mut build: Option<ResMut<BuildMode>>,
+    mut overlay: Option<ResMut<DiagnosticsOverlay>>,
+    mut help: Option<ResMut<HelpOverlay>>,
+    mut inspector: Option<ResMut<InspectorState>>,
+    mut ev_save: EventWriter<SaveSceneEvent>,
+    mut ev_edits: EventWriter<EditHistoryEvent>,
+    mut ev_selection: EventWriter<SelectionHistoryEvent>,
+    mut ev_clipboard: EventWriter<ClipboardEvent>,
+    (mut prefs, mut placement, mut compare): (
+        Option<ResMut<EditorPrefs>>,
+        Option<ResMut<Placement>>,
+        Option<ResMut<PostCompare>>,
+    ),
+    mut ev_projection: EventWriter<ProjectionEvent>,
+    mut ev_exit: EventWriter<AppExit>,
+    #[cfg(all(feature = "capture", not(target_arch = "wasm32")))] mut ev_capture: EventWriter<
+        ToggleCaptureEvent,
+    >,
+) {
+    for action in actions.read() {
+        match *action {
+            EditorAction::ToggleEditor => edit_state.open = !edit_state.open,
+            EditorAction::ToggleBuildMode => {
+                if let Some(build) = build.as_mut() {
+                    build.enabled = !build.enabled;
+                }
+            }
+            EditorAction::ToggleDiagnostics => {
+                if let Some(overlay) = overlay.as_mut() {
+                    overlay.enabled = !overlay.enabled;
+                }
+            }
+            EditorAction::ToggleHelp => {
+                if let Some(help) = help.as_mut() {
+                    help.open = !help.open;
+                }
+            }
+            EditorAction::SetMode(m) => {
+                mode.set_if_neq(m);
+            }
+            EditorAction::Save => {
+                ev_save.write(SaveSceneEvent::default());
+            }
+            EditorAction::Undo => {
+                ev_edits.write(EditHistoryEvent::Undo);
+            }
+            EditorAction::Redo => {
+                ev_edits.write(EditHistoryEvent::Redo);
+            }
+            EditorAction::SelectionBack => {
+                ev_selection.write(SelectionHistoryEvent::Back);
+            }
+            EditorAction::SelectionForward => {
+                ev_selection.write(SelectionHistoryEvent::Forward);
+            }
+            EditorAction::Duplicate => {
+                if let Some(inspector) = inspector.as_mut() {
+                    inspector.duplicate = true;
+                }
+            }
+            EditorAction::RepeatCopy => {
+                if let Some(inspector) = inspector.as_mut() {
+                    inspector.repeat_copy = true;
+                }
+            }
+            EditorAction::CopyToClipboard => {
+                ev_clipboard.write(ClipboardEvent::Copy);
+            }
+            EditorAction::PasteFromClipboard => {
+                ev_clipboard.write(ClipboardEvent::Paste);
+            }
+            EditorAction::ToggleCutaway => {
+                if let Some(prefs) = prefs.as_mut() {
+                    prefs.cutaway = !prefs.cutaway;
+                }
+            }
+            EditorAction::ToggleRecording => {
+                #[cfg(all(feature = "capture", not(target_arch = "wasm32")))]
+                ev_capture.write(ToggleCaptureEvent);
+            }
+            EditorAction::FlipCompare => {
+                if let Some(compare) = compare.as_mut() {
+                    compare.flip();
+                }
+            }
+            EditorAction::Orthographic => {
+                ev_projection.write(ProjectionEvent::Orthographic);
+            }
+            EditorAction::Perspective => {
+                ev_projection.write(ProjectionEvent::Perspective);
+            }
+            EditorAction::TurnPlacement => {
+                if let Some(placement) = placement.as_mut() {
+                    placement.turn();
+                }
+            }
+            EditorAction::Quit => {
+                ev_exit.write(AppExit::Success);
+            }
+        }
+    }
+}
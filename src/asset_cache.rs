@@ -0,0 +1,212 @@
+use std::collections::{HashMap, HashSet};
+
+use bevy::prelude::*;
+
+use crate::custom_kinds::{CustomKind, CustomKindId};
+use crate::inspector::SpawnKind;
+use crate::stairs::{StairsParams, stairs_mesh};
+
+pub struct AssetCachePlugin;
+impl Plugin for AssetCachePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AssetCache>();
+    }
+}
+
+/// Mesh and material handles shared by identical editable objects, so big scenes don't hold
+/// one copy per object and identical objects batch together.
+///
+/// Shared materials are copy-on-write: anything that edits the material of a single object
+/// go through `own_material`, which gives that object its own clone first.
+#[derive(Resource, Default)]
+pub struct AssetCache {
+    meshes: HashMap<MeshKey, Handle<Mesh>>,
+    materials: HashMap<MaterialKey, Handle<StandardMaterial>>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum MeshKey {
+    Primitive(SpawnKind),
+    Stairs([u32; 4]),
+    Custom(CustomKindId),
+}
+
+/// Bit patterns of the `PlainMaterial` fields (f32 isn't `Hash`).
+type MaterialKey = [u32; 10];
+
+/// The material properties editables round-trip through the inspector and scene files.
+#[derive(Clone, Copy)]
+pub struct PlainMaterial {
+    pub base_color: Color,
+    pub metallic: f32,
+    pub roughness: f32,
+    pub emissive: LinearRgba,
+}
+
+impl PlainMaterial {
+    fn key(&self) -> MaterialKey {
+        let c = self.base_color.to_srgba();
+        let e = self.emissive;
+        [
+            c.red,
+            c.green,
+            c.blue,
+            c.alpha,
+            self.metallic,
+            self.roughness,
+            e.red,
+            e.green,
+            e.blue,
+            e.alpha,
+        ]
+        .map(f32::to_bits)
+    }
+
+    fn to_material(self) -> StandardMaterial {
+        StandardMaterial {
+            base_color: self.base_color,
+            metallic: self.metallic.clamp(0.0, 1.0),
+            perceptual_roughness: self.roughness.clamp(0.0, 1.0),
+            emissive: self.emissive,
+            // Enable blending for see-through colors
+            alpha_mode: if self.base_color.alpha() < 0.999 {
+                AlphaMode::Blend
+            } else {
+                AlphaMode::Opaque
+            },
+            ..default()
+        }
+    }
+}
+
+/// The unit primitive for `kind` (author-only kinds get an empty placeholder, and so do custom
+/// kinds until `CustomKindsPlugin` swaps in their mesh).
+fn primitive_mesh(kind: SpawnKind) -> Mesh {
+    match kind {
+        SpawnKind::Cuboid => Mesh::from(Cuboid::new(1.0, 1.0, 1.0)),
+        SpawnKind::Sphere => Mesh::from(Sphere::new(0.5)),
+        SpawnKind::Plane => Mesh::from(Plane3d::default()),
+        SpawnKind::Prism => Extrusion::new(
+            Triangle2d::new(
+                Vec2::new(0.0, 1.0),
+                Vec2::new(0.0, 0.0),
+                Vec2::new(1.0, 0.0),
+            ),
+            1.0,
+        )
+        .into(),
+        SpawnKind::Stairs => stairs_mesh(&StairsParams::default()),
+        SpawnKind::ColliderBox
+        | SpawnKind::PointLight
+        | SpawnKind::SpotLight
+        | SpawnKind::FogVolume
+        | SpawnKind::Decal
+        | SpawnKind::Spline
+        | SpawnKind::Creatures
+        | SpawnKind::Custom(_) => Mesh::from(Sphere::new(0.0)),
+    }
+}
+
+impl AssetCache {
+    /// Shared mesh for a primitive kind.
+    pub fn mesh(&mut self, meshes: &mut Assets<Mesh>, kind: SpawnKind) -> Handle<Mesh> {
+        self.meshes
+            .entry(MeshKey::Primitive(kind))
+            .or_insert_with(|| meshes.add(primitive_mesh(kind)))
+            .clone()
+    }
+
+    /// Shared mesh for a staircase with these parameters.
+    pub fn stairs_mesh(
+        &mut self,
+        meshes: &mut Assets<Mesh>,
+        params: &StairsParams,
+    ) -> Handle<Mesh> {
+        let key = MeshKey::Stairs([
+            params.steps,
+            params.rise.to_bits(),
+            params.run.to_bits(),
+            params.width.to_bits(),
+        ]);
+        self.meshes
+            .entry(key)
+            .or_insert_with(|| meshes.add(stairs_mesh(params)))
+            .clone()
+    }
+
+    /// Shared mesh for a registered custom kind.
+    pub fn custom_mesh(&mut self, meshes: &mut Assets<Mesh>, kind: &CustomKind) -> Handle<Mesh> {
+        self.meshes
+            .entry(MeshKey::Custom(kind.id()))
+            .or_insert_with(|| meshes.add((kind.mesh)()))
+            .clone()
+    }
+
+    /// Shared material with these properties.
+    pub fn material(
+        &mut self,
+        materials: &mut Assets<StandardMaterial>,
+        plain: PlainMaterial,
+    ) -> Handle<StandardMaterial> {
+        self.materials
+            .entry(plain.key())
+            .or_insert_with(|| materials.add(plain.to_material()))
+            .clone()
+    }
+
+    /// Whether `handle` came from the cache (and so may be used by other objects).
+    pub fn is_shared_material(&self, handle: &Handle<StandardMaterial>) -> bool {
+        self.materials.values().any(|h| h.id() == handle.id())
+    }
+
+    /// The material to edit for one object showing `handle`: the handle itself, or a private
+    /// copy given to the object first when the handle is shared through the cache.
+    pub fn own_material(
+        &self,
+        commands: &mut Commands,
+        materials: &mut Assets<StandardMaterial>,
+        entity: Entity,
+        handle: &Handle<StandardMaterial>,
+    ) -> Option<Handle<StandardMaterial>> {
+        if !self.is_shared_material(handle) {
+            return Some(handle.clone());
+        }
+        let copy = materials.add(materials.get(handle)?.clone());
+        commands.entity(entity).insert(MeshMaterial3d(copy.clone()));
+        Some(copy)
+    }
+
+    /// How many meshes and materials are shared through the cache.
+    pub fn cached(&self) -> (usize, usize) {
+        (self.meshes.len(), self.materials.len())
+    }
+
+    /// How many cached handles no object uses: the cache keeps every mesh and material it ever
+    /// made alive otherwise, through scene loads and color edits.
+    pub fn unused(
+        &self,
+        meshes_in_use: &HashSet<AssetId<Mesh>>,
+        materials_in_use: &HashSet<AssetId<StandardMaterial>>,
+    ) -> usize {
+        let meshes = self.meshes.values().map(Handle::id);
+        let materials = self.materials.values().map(Handle::id);
+        meshes.filter(|id| !meshes_in_use.contains(id)).count()
+            + materials
+                .filter(|id| !materials_in_use.contains(id))
+                .count()
+    }
+
+    /// Drop the cached handles no object uses, so their assets are freed (they're made again
+    /// when needed). Returns how many were dropped.
+    pub fn purge_unused(
+        &mut self,
+        meshes_in_use: &HashSet<AssetId<Mesh>>,
+        materials_in_use: &HashSet<AssetId<StandardMaterial>>,
+    ) -> usize {
+        let before = self.meshes.len() + self.materials.len();
+        self.meshes.retain(|_, h| meshes_in_use.contains(&h.id()));
+        self.materials
+            .retain(|_, h| materials_in_use.contains(&h.id()));
+        before - self.meshes.len() - self.materials.len()
+    }
+}
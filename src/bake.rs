@@ -0,0 +1,252 @@
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+
+use crate::SceneEditState;
+use crate::environment::WindSway;
+use crate::inspector::{Editable, EditableMesh, Selected, SpawnKind};
+use crate::lights::LightAnimation;
+use crate::lod::Lod;
+use crate::reflection::PlanarReflection;
+
+/// Original object folded into a baked mesh. It stays in the world (hidden, colliders intact)
+/// and in the scene file, so the merged mesh can be rebuilt on load or undone.
+#[derive(Component, Copy, Clone)]
+pub struct BakedMember {
+    pub group: u32,
+}
+
+/// Single mesh entity drawing every member of a bake group.
+#[derive(Component)]
+struct BakedMesh {
+    group: u32,
+    members: Vec<Entity>,
+}
+
+/// Bake the selected static editables that share the material of `seed` into one mesh.
+#[derive(Event)]
+pub struct MergeBakeEvent {
+    pub seed: Entity,
+}
+
+pub struct BakePlugin;
+impl Plugin for BakePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<MergeBakeEvent>()
+            .add_systems(Update, (merge_on_request, sync_baked_meshes).chain())
+            .add_systems(EguiPrimaryContextPass, bake_panel);
+    }
+}
+
+type BakeCandidates<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static Mesh3d,
+        &'static MeshMaterial3d<StandardMaterial>,
+        &'static EditableMesh,
+    ),
+    (
+        With<Editable>,
+        Without<BakedMember>,
+        // Animated or specially rendered objects can't be folded into a static mesh
+        Without<WindSway>,
+        Without<LightAnimation>,
+        Without<PlanarReflection>,
+    ),
+>;
+
+fn bakeable(kind: SpawnKind) -> bool {
+    matches!(
+        kind,
+        SpawnKind::Cuboid
+            | SpawnKind::Sphere
+            | SpawnKind::Plane
+            | SpawnKind::Prism
+            | SpawnKind::Stairs
+    )
+}
+
+/// Materials are created per object, so compare what they look like rather than handles.
+fn same_look(a: &StandardMaterial, b: &StandardMaterial) -> bool {
+    a.base_color == b.base_color
+        && a.emissive == b.emissive
+        && a.metallic == b.metallic
+        && a.perceptual_roughness == b.perceptual_roughness
+        && a.base_color_texture == b.base_color_texture
+}
+
+fn merge_on_request(
+    mut commands: Commands,
+    mut ev: EventReader<MergeBakeEvent>,
+    materials: Res<Assets<StandardMaterial>>,
+    q_candidates: BakeCandidates,
+    q_selected: Query<(), With<Selected>>,
+    q_members: Query<&BakedMember>,
+) {
+    for MergeBakeEvent { seed } in ev.read() {
+        let Some(seed_mat) = q_candidates
+            .get(*seed)
+            .ok()
+            .filter(|(.., info)| bakeable(info.kind))
+            .and_then(|(_, _, mat_h, _)| materials.get(&mat_h.0))
+        else {
+            warn!("Merge: the selected object can't be baked");
+            continue;
+        };
+
+        let members: Vec<Entity> = q_candidates
+            .iter()
+            .filter(|(e, _, mat_h, info)| {
+                q_selected.contains(*e)
+                    && bakeable(info.kind)
+                    && materials
+                        .get(&mat_h.0)
+                        .is_some_and(|m| same_look(m, seed_mat))
+            })
+            .map(|(e, ..)| e)
+            .collect();
+        if members.len() < 2 {
+            warn!("Merge: select at least two static objects with the same material");
+            continue;
+        }
+        let skipped = q_selected.iter().count() - members.len();
+        if skipped > 0 {
+            warn!("Merge: left out {skipped} selected objects that can't join this bake");
+        }
+
+        let group = q_members.iter().map(|m| m.group + 1).max().unwrap_or(0);
+        for e in members {
+            commands.entity(e).insert(BakedMember { group });
+        }
+    }
+}
+
+/// Keep one merged mesh per bake group: build it for new groups (including ones loaded from a
+/// scene), rebuild it when the membership changes or a member moves, and drop it once the group
+/// is gone.
+fn sync_baked_meshes(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    q_members: Query<(
+        Entity,
+        &BakedMember,
+        &Mesh3d,
+        &MeshMaterial3d<StandardMaterial>,
+        &GlobalTransform,
+        Option<&Lod>,
+    )>,
+    q_new: Query<Entity, Added<BakedMember>>,
+    // Rather than `Changed<Transform>`: this also catches moved parents, and is already
+    // propagated, so the rebuild sees where the member is now
+    q_moved: Query<&BakedMember, Changed<GlobalTransform>>,
+    q_baked: Query<(Entity, &BakedMesh)>,
+) {
+    for e in &q_new {
+        commands.entity(e).insert(Visibility::Hidden);
+    }
+
+    let mut groups: BTreeMap<u32, Vec<Entity>> = BTreeMap::new();
+    for (e, member, ..) in &q_members {
+        groups.entry(member.group).or_default().push(e);
+    }
+    for list in groups.values_mut() {
+        list.sort();
+    }
+
+    let moved: Vec<u32> = q_moved.iter().map(|m| m.group).collect();
+    for (e, baked) in &q_baked {
+        if groups.get(&baked.group) == Some(&baked.members) && !moved.contains(&baked.group) {
+            groups.remove(&baked.group);
+        } else {
+            commands.entity(e).despawn();
+        }
+    }
+
+    // Whatever is left has no (up to date) merged mesh yet
+    for (group, members) in groups {
+        // Members loaded this frame don't have their world transforms yet
+        if members.iter().any(|&e| q_new.contains(e)) {
+            continue;
+        }
+        let mut merged: Option<Mesh> = None;
+        let mut material = None;
+        // The merged mesh sits where its first member does, the rest placed relative to it,
+        // wherever in the hierarchy they are
+        let mut root = GlobalTransform::IDENTITY;
+        for &e in &members {
+            let Ok((_, _, mesh_h, mat_h, gt, lod)) = q_members.get(e) else {
+                continue;
+            };
+            // Bake the full-detail mesh even if LOD had swapped in a simpler one
+            let mesh_h = lod.map_or(&mesh_h.0, |lod| &lod.full);
+            let Some(mesh) = meshes.get(mesh_h) else {
+                continue;
+            };
+            if merged.is_none() {
+                root = *gt;
+            }
+            let part = mesh.clone().transformed_by(gt.reparented_to(&root));
+            match merged.as_mut() {
+                Some(m) => {
+                    if let Err(err) = m.merge(&part) {
+                        warn!("Bake: skipping an object with mismatched mesh attributes: {err}");
+                    }
+                }
+                None => {
+                    merged = Some(part);
+                    material = Some(mat_h.0.clone());
+                }
+            }
+        }
+        let (Some(mesh), Some(material)) = (merged, material) else {
+            continue;
+        };
+        commands.spawn((
+            Mesh3d(meshes.add(mesh)),
+            MeshMaterial3d(material),
+            root.compute_transform(),
+            Name::new(format!("Baked mesh {group}")),
+            BakedMesh { group, members },
+        ));
+    }
+}
+
+fn bake_panel(
+    mut commands: Commands,
+    mut ctxs: EguiContexts,
+    edit_state: Res<SceneEditState>,
+    q_baked: Query<&BakedMesh>,
+) {
+    if !edit_state.open || q_baked.is_empty() {
+        return;
+    }
+    let Ok(ctx) = ctxs.ctx_mut() else {
+        return;
+    };
+    egui::Window::new("Baked meshes")
+        .default_open(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            for baked in &q_baked {
+                ui.horizontal(|ui| {
+                    ui.label(format!(
+                        "Group {}: {} objects",
+                        baked.group,
+                        baked.members.len()
+                    ));
+                    if ui.button("Unbake").clicked() {
+                        // Restore the originals; the merged mesh is dropped on the next sync
+                        for &e in &baked.members {
+                            commands
+                                .entity(e)
+                                .try_remove::<BakedMember>()
+                                .try_insert(Visibility::Inherited);
+                        }
+                    }
+                });
+            }
+        });
+}
@@ -0,0 +1,274 @@
+use std::f32::consts::TAU;
+use std::fs::write;
+
+use bevy::prelude::*;
+use bevy::render::renderer::RenderAdapterInfo;
+use serde::Serialize;
+
+use crate::SceneEditState;
+use crate::camera::OrbitCamera;
+#[cfg(feature = "inspector")]
+use crate::inspector::{LoadSceneEvent, SceneIoState};
+#[cfg(feature = "chroma")]
+use crate::post::chroma_aberration::ChromaAberrationSettings;
+#[cfg(feature = "crt")]
+use crate::post::crt::CRTSettings;
+#[cfg(feature = "gradient")]
+use crate::post::gradient_tint::GradientTintSettings;
+#[cfg(feature = "lut")]
+use crate::post::lut::LutSettings;
+use crate::post::{lens_flare::LensFlareSettings, outlines::OutlineParams};
+use crate::power::PowerPrefs;
+use crate::settings::{AppSettings, SettingsFile};
+
+/// Untimed frames first, so shader compilation and asset loading don't count.
+const WARMUP_SECS: f32 = 3.0;
+const SWEEP_SECS: f32 = 30.0;
+
+/// `--benchmark [scene.json] [--report out.json]`: load a scene, run a fixed camera sweep with
+/// every post effect on, write frame time statistics to a JSON report and exit.
+/// Does nothing without `--benchmark`.
+pub struct BenchmarkPlugin(Option<BenchmarkArgs>);
+
+struct BenchmarkArgs {
+    scene: Option<String>,
+    report: String,
+}
+
+impl BenchmarkPlugin {
+    pub fn from_args() -> Self {
+        Self(BenchmarkArgs::parse(std::env::args().skip(1).collect()))
+    }
+}
+
+impl BenchmarkArgs {
+    fn parse(args: Vec<String>) -> Option<Self> {
+        let at = args.iter().position(|a| a == "--benchmark")?;
+        let scene = args.get(at + 1).filter(|a| !a.starts_with("--")).cloned();
+        let report = args
+            .iter()
+            .position(|a| a == "--report")
+            .and_then(|i| args.get(i + 1))
+            .cloned()
+            .unwrap_or_else(|| "benchmark.json".into());
+        Some(Self { scene, report })
+    }
+}
+
+impl Plugin for BenchmarkPlugin {
+    fn build(&self, app: &mut App) {
+        let Some(args) = &self.0 else {
+            return;
+        };
+        // Run with default settings and leave the user's settings file alone; never throttled,
+        // so the frame times are the scene's
+        app.insert_resource(AppSettings::default())
+            .insert_resource(SettingsFile(None))
+            .insert_resource(PowerPrefs {
+                cap_fps: false,
+                idle_throttle: false,
+                ..default()
+            })
+            .insert_resource(BenchmarkRun {
+                scene: args.scene.clone(),
+                report: args.report.clone(),
+                elapsed: 0.0,
+                frame_ms: Vec::new(),
+                orbit: None,
+            })
+            .add_systems(PostStartup, start_benchmark)
+            .add_systems(Update, (enable_all_effects, run_benchmark).chain());
+        // Scenes are loaded through the inspector's scene I/O
+        #[cfg(feature = "inspector")]
+        app.add_systems(PostStartup, load_benchmark_scene);
+    }
+}
+
+#[derive(Resource)]
+struct BenchmarkRun {
+    scene: Option<String>,
+    report: String,
+    elapsed: f32,
+    frame_ms: Vec<f64>,
+    /// Orbit center and the camera's starting offset from it
+    orbit: Option<(Vec3, Vec3)>,
+}
+
+#[derive(Serialize)]
+struct BenchmarkReport {
+    scene: String,
+    adapter: String,
+    backend: String,
+    duration_secs: f32,
+    frames: usize,
+    min_ms: f64,
+    avg_ms: f64,
+    p99_ms: f64,
+    max_ms: f64,
+    avg_fps: f64,
+}
+
+fn start_benchmark(mut edit_state: ResMut<SceneEditState>) {
+    // Measure the scene, not the editor panels
+    edit_state.open = false;
+}
+
+#[cfg(feature = "inspector")]
+fn load_benchmark_scene(
+    run: Res<BenchmarkRun>,
+    mut io: ResMut<SceneIoState>,
+    mut ev_load: EventWriter<LoadSceneEvent>,
+) {
+    if let Some(scene) = &run.scene {
+        io.filename = scene.clone();
+        ev_load.write(LoadSceneEvent);
+    }
+}
+
+/// Keep every (compiled in) post effect on for the whole run, whatever the defaults are.
+fn enable_all_effects(
+    mut outlines: ResMut<OutlineParams>,
+    #[cfg(feature = "chroma")] mut q_chroma: Query<
+        &mut ChromaAberrationSettings,
+        With<OrbitCamera>,
+    >,
+    #[cfg(feature = "crt")] mut q_crt: Query<&mut CRTSettings, With<OrbitCamera>>,
+    #[cfg(feature = "gradient")] mut q_tint: Query<&mut GradientTintSettings, With<OrbitCamera>>,
+    #[cfg(feature = "lut")] mut q_lut: Query<&mut LutSettings, With<OrbitCamera>>,
+    mut q_flare: Query<&mut LensFlareSettings, With<OrbitCamera>>,
+) {
+    if !outlines.enabled {
+        outlines.enabled = true;
+    }
+    #[cfg(feature = "chroma")]
+    for mut s in &mut q_chroma {
+        s.enabled = 1;
+    }
+    #[cfg(feature = "crt")]
+    for mut s in &mut q_crt {
+        s.enabled = 1;
+    }
+    #[cfg(feature = "gradient")]
+    for mut s in &mut q_tint {
+        s.enabled = 1;
+    }
+    #[cfg(feature = "lut")]
+    for mut s in &mut q_lut {
+        s.enabled = 1;
+    }
+    for mut s in &mut q_flare {
+        s.enabled = 1;
+    }
+}
+
+fn run_benchmark(
+    time: Res<Time<Real>>,
+    mut run: ResMut<BenchmarkRun>,
+    adapter: Option<Res<RenderAdapterInfo>>,
+    mut q_cam: Query<&mut Transform, With<OrbitCamera>>,
+    mut exit: EventWriter<AppExit>,
+) {
+    let Ok(mut tf) = q_cam.single_mut() else {
+        return;
+    };
+    // Orbit around where the camera looks at the ground
+    let (center, offset) = *run.orbit.get_or_insert_with(|| {
+        let fwd = tf.forward();
+        let center = if fwd.y < -1e-3 {
+            tf.translation + fwd * (-tf.translation.y / fwd.y)
+        } else {
+            Vec3::ZERO
+        };
+        (center, tf.translation - center)
+    });
+
+    let dt = time.delta_secs();
+    run.elapsed += dt;
+    let t = run.elapsed - WARMUP_SECS;
+    if t > 0.0 {
+        run.frame_ms.push(time.delta_secs_f64() * 1000.0);
+    }
+
+    // One full turn with a slow bob in height, identical on every machine
+    let progress = (t / SWEEP_SECS).clamp(0.0, 1.0);
+    let swept = Quat::from_rotation_y(progress * TAU) * offset;
+    let bob = 1.0 + 0.25 * (progress * TAU * 2.0).sin();
+    let pos = center + Vec3::new(swept.x, swept.y * bob, swept.z);
+    *tf = Transform::from_translation(pos).looking_at(center, Vec3::Y);
+
+    if t < SWEEP_SECS {
+        return;
+    }
+
+    let mut sorted = run.frame_ms.clone();
+    sorted.sort_by(f64::total_cmp);
+    let frames = sorted.len();
+    let avg_ms = sorted.iter().sum::<f64>() / frames.max(1) as f64;
+    let p99 = ((frames as f64 * 0.99).ceil() as usize).saturating_sub(1);
+    let report = BenchmarkReport {
+        scene: run.scene.clone().unwrap_or_else(|| "built-in".into()),
+        adapter: adapter.as_ref().map_or(String::new(), |a| a.name.clone()),
+        backend: adapter
+            .as_ref()
+            .map_or(String::new(), |a| format!("{:?}", a.backend)),
+        duration_secs: SWEEP_SECS,
+        frames,
+        min_ms: sorted.first().copied().unwrap_or(0.0),
+        avg_ms,
+        p99_ms: sorted.get(p99).copied().unwrap_or(0.0),
+        max_ms: sorted.last().copied().unwrap_or(0.0),
+        avg_fps: if avg_ms > 0.0 { 1000.0 / avg_ms } else { 0.0 },
+    };
+
+    match serde_json::to_string_pretty(&report) {
+        Ok(json) => {
+            if let Err(e) = write(&run.report, json) {
+                eprintln!("Benchmark: cannot write {}: {e}", run.report);
+            } else {
+                eprintln!(
+                    "Benchmark: avg {:.2} ms, p99 {:.2} ms over {frames} frames, report in {}",
+                    report.avg_ms, report.p99_ms, run.report
+                );
+            }
+        }
+        Err(e) => eprintln!("Benchmark: serialize error: {e}"),
+    }
+    exit.write(AppExit::Success);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &str) -> Option<BenchmarkArgs> {
+        BenchmarkArgs::parse(args.split_whitespace().map(String::from).collect())
+    }
+
+    #[test]
+    fn off_without_the_flag() {
+        assert!(parse("").is_none());
+        assert!(parse("scene.json --report out.json").is_none());
+    }
+
+    #[test]
+    fn scene_and_report_are_optional() {
+        let args = parse("--benchmark").unwrap();
+        assert_eq!(args.scene, None);
+        assert_eq!(args.report, "benchmark.json");
+
+        let args = parse("--benchmark town.json").unwrap();
+        assert_eq!(args.scene.as_deref(), Some("town.json"));
+    }
+
+    #[test]
+    fn report_goes_anywhere_on_the_line() {
+        let args = parse("--report out.json --benchmark town.json").unwrap();
+        assert_eq!(args.scene.as_deref(), Some("town.json"));
+        assert_eq!(args.report, "out.json");
+
+        // A flag straight after `--benchmark` isn't the scene
+        let args = parse("--benchmark --report out.json").unwrap();
+        assert_eq!(args.scene, None);
+        assert_eq!(args.report, "out.json");
+    }
+}
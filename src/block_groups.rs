@@ -0,0 +1,243 @@
+//! Block groups for appearing-bridge puzzles: objects sharing a `block_group` custom property
+//! switch together between solid and ghost (walk-through, with only a few glowing specks left)
+//! in Play mode. Scripts switch them with `set_block_group` / `toggle_block_group`, and walking
+//! into an object with a `toggles_block_group` property flips the group it names. Blocks dissolve
+//! away and back in with a glowing edge (see `dissolve`); `block_state` = `ghost` on any member
+//! starts its group as a ghost. Leaving Play makes everything solid again.
+
+use std::collections::{HashMap, HashSet};
+
+use bevy::{prelude::*, render::primitives::Aabb};
+use bevy_rapier3d::prelude::{ColliderDisabled, KinematicCharacterController};
+
+use crate::custom_props::CustomProperties;
+use crate::dissolve::{DissolveAmount, DissolveSettings};
+use crate::editor_mode::EditorMode;
+use crate::inspector::{Editable, aabb_world, obb_to_world_aabb};
+
+/// Custom property naming the group an object belongs to
+const GROUP_KEY: &str = "block_group";
+/// Custom property (`ghost`) starting the object's group as a ghost
+const STATE_KEY: &str = "block_state";
+/// Custom property naming the group to flip when the player walks in
+const TRIGGER_KEY: &str = "toggles_block_group";
+
+/// How far a ghost block stays dissolved; the specks left show where it'll come back
+const GHOST_AMOUNT: f32 = 0.8;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BlockGroupAction {
+    Solid,
+    Ghost,
+    Toggle,
+}
+
+/// Switch a block group in Play mode (sent by scripts and triggers; game code can too).
+#[derive(Event, Clone, Debug)]
+pub struct BlockGroupEvent {
+    pub group: String,
+    pub action: BlockGroupAction,
+}
+
+/// Whether each group is solid, for the current play session.
+#[derive(Resource, Default)]
+pub struct BlockGroups {
+    solid: HashMap<String, bool>,
+}
+
+impl BlockGroups {
+    pub fn is_solid(&self, group: &str) -> bool {
+        self.solid.get(group).copied().unwrap_or(true)
+    }
+}
+
+/// A group member that isn't fully solid, dissolved by `amount`.
+#[derive(Component)]
+struct BlockFade {
+    /// 0 solid .. `GHOST_AMOUNT` ghost
+    amount: f32,
+    target: f32,
+}
+
+impl BlockFade {
+    fn at(amount: f32) -> Self {
+        Self {
+            amount,
+            target: amount,
+        }
+    }
+}
+
+pub struct BlockGroupsPlugin;
+impl Plugin for BlockGroupsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BlockGroups>()
+            .init_resource::<DissolveSettings>()
+            .init_resource::<EditorMode>()
+            .add_event::<BlockGroupEvent>()
+            .add_systems(
+                Update,
+                (block_triggers, switch_block_groups, fade_blocks).chain(),
+            );
+    }
+}
+
+type Members<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static CustomProperties,
+        Option<&'static mut BlockFade>,
+    ),
+    With<Editable>,
+>;
+
+/// Flip a trigger's group when a player walks into it.
+fn block_triggers(
+    mode: Res<EditorMode>,
+    q_triggers: Query<(Entity, &CustomProperties, &GlobalTransform, Option<&Aabb>), With<Editable>>,
+    q_players: Query<&GlobalTransform, With<KinematicCharacterController>>,
+    mut inside: Local<HashSet<Entity>>,
+    mut ev_blocks: EventWriter<BlockGroupEvent>,
+) {
+    if *mode != EditorMode::Play {
+        inside.clear();
+        return;
+    }
+    for (e, props, gt, aabb) in &q_triggers {
+        let Some(group) = props.get(TRIGGER_KEY) else {
+            continue;
+        };
+        let bounds = match aabb {
+            Some(aabb) => aabb_world(*aabb, gt),
+            // Collider boxes have no mesh: a unit cube scaled by the transform
+            None => obb_to_world_aabb(gt.compute_transform(), Vec3::ONE),
+        };
+        let (min, max) = (
+            Vec3::from(bounds.center - bounds.half_extents),
+            Vec3::from(bounds.center + bounds.half_extents),
+        );
+        let now = q_players.iter().any(|p| {
+            let p = p.translation();
+            p.cmpge(min).all() && p.cmple(max).all()
+        });
+        if now && inside.insert(e) {
+            ev_blocks.write(BlockGroupEvent {
+                group: group.to_string(),
+                action: BlockGroupAction::Toggle,
+            });
+        } else if !now {
+            inside.remove(&e);
+        }
+    }
+}
+
+/// Set groups up when Play starts, follow switch requests while it runs, and make everything
+/// solid again when it ends (the dissolve itself finishes with the mode switch).
+fn switch_block_groups(
+    mut commands: Commands,
+    mode: Res<EditorMode>,
+    mut groups: ResMut<BlockGroups>,
+    mut ev_blocks: EventReader<BlockGroupEvent>,
+    mut q_members: Members,
+) {
+    if mode.is_changed() {
+        groups.solid.clear();
+        for (e, props, fade) in &mut q_members {
+            if fade.is_some() {
+                commands.entity(e).remove::<(BlockFade, ColliderDisabled)>();
+            }
+            if *mode == EditorMode::Play
+                && let Some(group) = props.get(GROUP_KEY)
+            {
+                let ghost = props.get(STATE_KEY) == Some("ghost");
+                let solid = groups.solid.entry(group.to_string()).or_insert(true);
+                *solid &= !ghost;
+            }
+        }
+        if *mode != EditorMode::Play {
+            ev_blocks.clear();
+            return;
+        }
+        // Ghost groups start out as ghosts, without the fade
+        for (e, props, _) in &q_members {
+            if props.get(GROUP_KEY).is_some_and(|g| !groups.is_solid(g)) {
+                commands.entity(e).insert((
+                    BlockFade::at(GHOST_AMOUNT),
+                    DissolveAmount(GHOST_AMOUNT),
+                    ColliderDisabled,
+                ));
+            }
+        }
+        return;
+    }
+
+    if *mode != EditorMode::Play {
+        ev_blocks.clear();
+        return;
+    }
+    let mut switched = HashSet::new();
+    for ev in ev_blocks.read() {
+        let solid = match ev.action {
+            BlockGroupAction::Solid => true,
+            BlockGroupAction::Ghost => false,
+            BlockGroupAction::Toggle => !groups.is_solid(&ev.group),
+        };
+        groups.solid.insert(ev.group.clone(), solid);
+        switched.insert(ev.group.clone());
+    }
+    for (e, props, fade) in &mut q_members {
+        let Some(group) = props.get(GROUP_KEY).filter(|g| switched.contains(*g)) else {
+            continue;
+        };
+        let target = if groups.is_solid(group) {
+            0.0
+        } else {
+            GHOST_AMOUNT
+        };
+        match fade {
+            Some(mut fade) => fade.target = target,
+            None if target > 0.0 => {
+                commands.entity(e).insert(BlockFade {
+                    target,
+                    ..BlockFade::at(0.0)
+                });
+            }
+            None => {}
+        }
+    }
+}
+
+/// Move dissolving blocks towards their target, switching collision halfway.
+fn fade_blocks(
+    mut commands: Commands,
+    time: Res<Time>,
+    settings: Res<DissolveSettings>,
+    mut q_fading: Query<(Entity, &mut BlockFade)>,
+) {
+    let step = time.delta_secs() * GHOST_AMOUNT / settings.duration.max(0.01);
+    for (e, mut fade) in &mut q_fading {
+        if fade.amount == fade.target {
+            continue;
+        }
+        let was_solid = fade.amount < GHOST_AMOUNT * 0.5;
+        fade.amount = if fade.target > fade.amount {
+            (fade.amount + step).min(fade.target)
+        } else {
+            (fade.amount - step).max(fade.target)
+        };
+        let solid = fade.amount < GHOST_AMOUNT * 0.5;
+        if was_solid && !solid {
+            commands.entity(e).insert(ColliderDisabled);
+        } else if solid && !was_solid {
+            commands.entity(e).remove::<ColliderDisabled>();
+        }
+
+        // Whole again at 0, where the object itself takes over
+        commands.entity(e).insert(DissolveAmount(fade.amount));
+        if fade.amount <= 0.0 {
+            commands.entity(e).remove::<BlockFade>();
+        }
+    }
+}
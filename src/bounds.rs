@@ -0,0 +1,209 @@
+use std::collections::{HashMap, VecDeque};
+
+use bevy::prelude::*;
+use bevy::render::mesh::VertexAttributeValues;
+use bevy::render::primitives::Aabb;
+use bevy::tasks::{AsyncComputeTaskPool, Task, block_on, poll_once};
+use bevy_rapier3d::prelude::*;
+
+use crate::inspector::{Editable, EditableMesh, SpawnKind};
+use crate::stairs::{StairsParams, stairs_collider};
+
+/// Objects handled by one worker task.
+const BATCH_SIZE: usize = 256;
+/// Tasks in flight at once; a bigger edit waits for later frames instead of stalling this one.
+const MAX_TASKS: usize = 4;
+
+/// Recomputes bounds (picking AABBs) and physics colliders of edited objects on worker tasks.
+///
+/// Edits are picked up through change detection and coalesced per entity, so dragging a slider
+/// over thousands of objects queues each of them once and the results land over a few frames.
+pub struct BoundsPlugin;
+impl Plugin for BoundsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BoundsQueue>().add_systems(
+            Update,
+            (queue_rebuilds, dispatch_rebuilds, apply_rebuilds).chain(),
+        );
+    }
+}
+
+#[derive(Clone, Copy, Default)]
+struct Dirty {
+    aabb: bool,
+    collider: bool,
+}
+
+#[derive(Resource, Default)]
+struct BoundsQueue {
+    pending: HashMap<Entity, Dirty>,
+    // Applied strictly in order so an older result never overwrites a newer one
+    tasks: VecDeque<Task<Vec<Rebuilt>>>,
+}
+
+/// Everything a worker needs, copied out of the world.
+struct Job {
+    entity: Entity,
+    positions: Option<Vec<[f32; 3]>>,
+    collider: Option<(SpawnKind, Option<StairsParams>, Vec3)>,
+}
+
+struct Rebuilt {
+    entity: Entity,
+    aabb: Option<Aabb>,
+    collider: Option<(Option<Collider>, Vec3)>,
+}
+
+impl Job {
+    fn run(self) -> Rebuilt {
+        Rebuilt {
+            entity: self.entity,
+            aabb: self
+                .positions
+                .and_then(|p| Aabb::enclosing(p.into_iter().map(Vec3::from))),
+            collider: self
+                .collider
+                .map(|(kind, stairs, scale)| (editable_collider(kind, stairs.as_ref()), scale)),
+        }
+    }
+}
+
+/// Unit collider for an editable of `kind` (scaled through `ColliderScale`), if it's physical.
+pub fn editable_collider(kind: SpawnKind, stairs: Option<&StairsParams>) -> Option<Collider> {
+    Some(match kind {
+        SpawnKind::Cuboid => Collider::cuboid(0.5, 0.5, 0.5),
+        SpawnKind::Sphere => Collider::ball(0.5),
+        SpawnKind::Plane => Collider::cuboid(0.5, 0.01, 0.5),
+        SpawnKind::Prism => {
+            let tri = [
+                Vec2::new(0.0, 1.0),
+                Vec2::new(0.0, 0.0),
+                Vec2::new(1.0, 0.0),
+            ];
+            let depth = 1.0;
+            let z0 = -0.5 * depth; // assume Extrusion centers around Z=0
+            let z1 = 0.5 * depth;
+
+            // Build vertex cloud for the convex-hull (6 verts: two triangle caps)
+            let mut pts: Vec<Vec3> = Vec::with_capacity(6);
+            for &p in &tri {
+                pts.push(Vec3::new(p.x, p.y, z0));
+            }
+            for &p in &tri {
+                pts.push(Vec3::new(p.x, p.y, z1));
+            }
+
+            Collider::convex_hull(&pts).unwrap()
+        }
+        // Custom kinds are expected to fit the unit cube like the primitives
+        SpawnKind::ColliderBox | SpawnKind::Custom(_) => Collider::cuboid(0.5, 0.5, 0.5),
+        SpawnKind::Stairs => stairs_collider(&stairs.copied().unwrap_or_default()),
+        // Lights, fog volumes, decals and creature areas are not physical; spline walls carry
+        // their own
+        SpawnKind::PointLight
+        | SpawnKind::SpotLight
+        | SpawnKind::FogVolume
+        | SpawnKind::Decal
+        | SpawnKind::Spline
+        | SpawnKind::Creatures => return None,
+    })
+}
+
+fn queue_rebuilds(
+    mut queue: ResMut<BoundsQueue>,
+    q_mesh: Query<(Entity, Ref<Mesh3d>), (With<Editable>, Changed<Mesh3d>)>,
+    q_params: Query<Entity, (With<Editable>, With<Collider>, Changed<StairsParams>)>,
+    q_scaled: Query<(Entity, &Transform, &ColliderScale), (With<Editable>, Changed<Transform>)>,
+) {
+    // Fresh meshes get their bounds from Bevy; only swapped ones need a refresh
+    for (e, mesh) in &q_mesh {
+        if !mesh.is_added() {
+            queue.pending.entry(e).or_default().aabb = true;
+        }
+    }
+    for e in &q_params {
+        queue.pending.entry(e).or_default().collider = true;
+    }
+    for (e, tf, scale) in &q_scaled {
+        if !matches!(scale, ColliderScale::Absolute(s) if *s == tf.scale) {
+            queue.pending.entry(e).or_default().collider = true;
+        }
+    }
+}
+
+fn dispatch_rebuilds(
+    mut queue: ResMut<BoundsQueue>,
+    meshes: Res<Assets<Mesh>>,
+    q_objects: Query<(
+        Option<&Mesh3d>,
+        &EditableMesh,
+        &Transform,
+        Option<&StairsParams>,
+        Has<Collider>,
+    )>,
+) {
+    let pool = AsyncComputeTaskPool::get();
+    while queue.tasks.len() < MAX_TASKS && !queue.pending.is_empty() {
+        let batch: Vec<(Entity, Dirty)> = queue
+            .pending
+            .iter()
+            .take(BATCH_SIZE)
+            .map(|(e, d)| (*e, *d))
+            .collect();
+
+        let mut jobs = Vec::with_capacity(batch.len());
+        for (e, dirty) in batch {
+            queue.pending.remove(&e);
+            let Ok((mesh_h, info, tf, stairs, has_collider)) = q_objects.get(e) else {
+                continue;
+            };
+            let positions = dirty
+                .aabb
+                .then_some(mesh_h)
+                .flatten()
+                .and_then(|h| meshes.get(&h.0))
+                .and_then(|m| match m.attribute(Mesh::ATTRIBUTE_POSITION) {
+                    Some(VertexAttributeValues::Float32x3(p)) => Some(p.clone()),
+                    _ => None,
+                });
+            // Colliders only exist in play mode; don't create them for edit-only changes
+            let collider =
+                (dirty.collider && has_collider).then(|| (info.kind, stairs.copied(), tf.scale));
+            jobs.push(Job {
+                entity: e,
+                positions,
+                collider,
+            });
+        }
+
+        if !jobs.is_empty() {
+            let task = pool.spawn(async move { jobs.into_iter().map(Job::run).collect() });
+            queue.tasks.push_back(task);
+        }
+    }
+}
+
+fn apply_rebuilds(mut commands: Commands, mut queue: ResMut<BoundsQueue>) {
+    while let Some(task) = queue.tasks.front_mut() {
+        let Some(results) = block_on(poll_once(task)) else {
+            break;
+        };
+        queue.tasks.pop_front();
+
+        for rebuilt in results {
+            let mut ec = commands.entity(rebuilt.entity);
+            if let Some(aabb) = rebuilt.aabb {
+                ec.try_insert(aabb);
+            }
+            match rebuilt.collider {
+                Some((Some(collider), scale)) => {
+                    ec.try_insert((collider, ColliderScale::Absolute(scale)));
+                }
+                Some((None, _)) => {
+                    ec.try_remove::<Collider>();
+                }
+                None => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,320 @@
+use bevy::{prelude::*, render::primitives::Aabb, window::PrimaryWindow};
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+
+use crate::SceneEditState;
+use crate::accessibility::AccessibilityPrefs;
+use crate::asset_cache::{AssetCache, PlainMaterial};
+use crate::bake::BakedMember;
+use crate::camera::{OrbitCamera, UiPointer};
+use crate::dissolve::{DissolveSettings, dissolve_away};
+use crate::inspector::{EditHistory, Editable, EditableMesh, Selected, SpawnKind};
+use crate::instancing::InstancedTile;
+
+/// Edge length of a building tile (a unit cuboid).
+const TILE_SIZE: f32 = 1.0;
+
+/// Tile colors; each gets one shared material so every tile batches with its siblings.
+pub(crate) const TILE_PALETTE: [(&str, [f32; 3]); 6] = [
+    ("Stone", [0.62, 0.62, 0.66]),
+    ("Grass", [0.42, 0.66, 0.36]),
+    ("Dirt", [0.55, 0.40, 0.28]),
+    ("Sand", [0.86, 0.80, 0.58]),
+    ("Brick", [0.72, 0.34, 0.28]),
+    ("Wood", [0.64, 0.48, 0.30]),
+];
+
+/// Minecraft-style grid building: click a face to add a tile next to it, Shift+click to remove.
+#[derive(Resource, Default)]
+pub struct BuildMode {
+    pub enabled: bool,
+    palette_index: usize,
+}
+
+/// Mesh and per-color materials shared by all tiles (from the `AssetCache`, so scene
+/// objects with the same look share them too).
+#[derive(Resource)]
+struct TileAssets {
+    mesh: Handle<Mesh>,
+    materials: Vec<Handle<StandardMaterial>>,
+}
+
+pub struct BuildModePlugin;
+impl Plugin for BuildModePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BuildMode>()
+            .init_resource::<AccessibilityPrefs>()
+            .init_resource::<DissolveSettings>()
+            .add_systems(Startup, setup_tile_assets)
+            .add_systems(
+                Update,
+                (close_with_editor, build_on_click, draw_build_preview).chain(),
+            )
+            .add_systems(EguiPrimaryContextPass, build_mode_panel);
+    }
+}
+
+fn setup_tile_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut cache: ResMut<AssetCache>,
+) {
+    // Tiles are unit cuboids
+    let mesh = cache.mesh(&mut meshes, SpawnKind::Cuboid);
+    let materials = TILE_PALETTE
+        .iter()
+        .map(|(_, [r, g, b])| {
+            cache.material(
+                &mut materials,
+                PlainMaterial {
+                    base_color: Color::srgb(*r, *g, *b),
+                    metallic: 0.0,
+                    roughness: 0.8,
+                    emissive: LinearRgba::BLACK,
+                },
+            )
+        })
+        .collect();
+    commands.insert_resource(TileAssets { mesh, materials });
+}
+
+/// Build mode ends when the scene editor is closed ('B' toggles it while it's open).
+fn close_with_editor(edit_state: Res<SceneEditState>, mut build: ResMut<BuildMode>) {
+    if !edit_state.open && build.enabled {
+        build.enabled = false;
+    }
+}
+
+type EditableHits<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static GlobalTransform,
+        &'static Transform,
+        &'static Aabb,
+        Option<&'static EditableMesh>,
+    ),
+    (With<Editable>, Without<BakedMember>),
+>;
+
+/// What the cursor points at in build mode.
+struct BuildHit {
+    target: Entity,
+    /// Center of the tile that a click would place
+    place_at: Vec3,
+    /// The hit object is itself a tile-sized cuboid (removable with Shift+click)
+    removable: bool,
+    target_transform: Transform,
+}
+
+/// Ray vs. local AABB (slab method) that also reports the entry face normal.
+pub(crate) fn ray_aabb_face(origin: Vec3, dir: Vec3, min: Vec3, max: Vec3) -> Option<(f32, Vec3)> {
+    let mut t_near = f32::NEG_INFINITY;
+    let mut t_far = f32::INFINITY;
+    let mut normal = Vec3::ZERO;
+    for axis in 0..3 {
+        let (o, d) = (origin[axis], dir[axis]);
+        if d.abs() < 1e-8 {
+            if o < min[axis] || o > max[axis] {
+                return None;
+            }
+            continue;
+        }
+        let (mut t0, mut t1) = ((min[axis] - o) / d, (max[axis] - o) / d);
+        // Entering through the min face means the face points along -axis
+        let mut n = -Vec3::AXES[axis];
+        if t0 > t1 {
+            std::mem::swap(&mut t0, &mut t1);
+            n = Vec3::AXES[axis];
+        }
+        if t0 > t_near {
+            t_near = t0;
+            normal = n;
+        }
+        t_far = t_far.min(t1);
+    }
+    (t_near <= t_far && t_far >= 0.0 && t_near >= 0.0).then_some((t_near, normal))
+}
+
+/// Unit cuboids (tiles, stone blocks) snap to each other; everything else to the world grid.
+fn is_tile_sized(tf: &Transform, mesh: Option<&EditableMesh>) -> bool {
+    mesh.is_some_and(|m| m.kind == SpawnKind::Cuboid)
+        && (tf.scale - Vec3::splat(TILE_SIZE)).abs().max_element() < 1e-3
+}
+
+/// Snap a world normal to the closest world axis.
+fn snap_to_axis(n: Vec3) -> Vec3 {
+    let a = n.abs();
+    if a.x >= a.y && a.x >= a.z {
+        Vec3::X * n.x.signum()
+    } else if a.y >= a.z {
+        Vec3::Y * n.y.signum()
+    } else {
+        Vec3::Z * n.z.signum()
+    }
+}
+
+fn cursor_build_hit(
+    windows: &Query<&Window, With<PrimaryWindow>>,
+    q_cam: &Query<(&Camera, &GlobalTransform), With<OrbitCamera>>,
+    q_editables: &EditableHits,
+) -> Option<BuildHit> {
+    let cursor = windows.single().ok()?.cursor_position()?;
+    let (camera, cam_gt) = q_cam.single().ok()?;
+    let ray = camera.viewport_to_world(cam_gt, cursor).ok()?;
+
+    // Nearest face hit across all editable meshes, tested in each object's local space
+    let mut best: Option<(f32, Entity, Vec3, Vec3)> = None;
+    for (e, gt, _, aabb, _) in q_editables {
+        let inv = gt.affine().inverse();
+        let o = inv.transform_point3(ray.origin);
+        let d = inv.transform_vector3(*ray.direction);
+        let (min, max) = (
+            Vec3::from(aabb.center - aabb.half_extents),
+            Vec3::from(aabb.center + aabb.half_extents),
+        );
+        // Local-space `t` equals world-space `t` since `d` isn't renormalized
+        if let Some((t, n_local)) = ray_aabb_face(o, d, min, max)
+            && best.is_none_or(|(bt, ..)| t < bt)
+        {
+            let n_world = snap_to_axis(gt.rotation() * n_local);
+            best = Some((t, e, ray.origin + *ray.direction * t, n_world));
+        }
+    }
+    let (_, target, hit, n) = best?;
+    let (_, _, tf, _, mesh) = q_editables.get(target).ok()?;
+
+    let removable = is_tile_sized(tf, mesh);
+    let place_at = if removable {
+        tf.translation + n * TILE_SIZE
+    } else {
+        // Flush against the face along the normal, on the world grid across it
+        let snapped = ((hit / TILE_SIZE).floor() + 0.5) * TILE_SIZE;
+        let along = hit + n * (TILE_SIZE * 0.5);
+        snapped * (Vec3::ONE - n.abs()) + along * n.abs()
+    };
+
+    Some(BuildHit {
+        target,
+        place_at,
+        removable,
+        target_transform: *tf,
+    })
+}
+
+fn build_on_click(
+    mut commands: Commands,
+    mouse: Res<ButtonInput<MouseButton>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    build: Res<BuildMode>,
+    tiles: Res<TileAssets>,
+    mut ui_pointer: UiPointer,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    q_cam: Query<(&Camera, &GlobalTransform), With<OrbitCamera>>,
+    q_editables: EditableHits,
+    q_selected: Query<(), With<Selected>>,
+    mut history: Option<ResMut<EditHistory>>,
+    dissolve: Res<DissolveSettings>,
+) {
+    if !build.enabled || !mouse.just_pressed(MouseButton::Left) {
+        return;
+    }
+    if ui_pointer.wants_pointer_in_primary() {
+        return;
+    }
+    let Some(hit) = cursor_build_hit(&windows, &q_cam, &q_editables) else {
+        return;
+    };
+
+    let shift = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    if shift {
+        // Leave the inspected object alone; the inspector still points at it
+        if hit.removable && q_selected.get(hit.target).is_err() {
+            if let Some(history) = history.as_mut() {
+                history.record_delete(&mut commands, hit.target, None);
+            }
+            dissolve_away(&mut commands, hit.target, &dissolve);
+        }
+        return;
+    }
+
+    // Don't stack a tile inside another one
+    let occupied = q_editables.iter().any(|(_, _, tf, _, mesh)| {
+        is_tile_sized(tf, mesh) && tf.translation.distance(hit.place_at) < TILE_SIZE * 0.5
+    });
+    if occupied {
+        return;
+    }
+
+    commands.spawn((
+        Mesh3d(tiles.mesh.clone()),
+        MeshMaterial3d(tiles.materials[build.palette_index].clone()),
+        Transform::from_translation(hit.place_at),
+        Editable,
+        EditableMesh {
+            kind: SpawnKind::Cuboid,
+            collider: Some(true),
+        },
+        Name::new("Tile"),
+        InstancedTile,
+    ));
+}
+
+/// Ghost cube where the next tile goes (red, or the palette's warning color, outline on the tile
+/// Shift+click would remove).
+fn draw_build_preview(
+    mut gizmos: Gizmos,
+    keys: Res<ButtonInput<KeyCode>>,
+    build: Res<BuildMode>,
+    access: Res<AccessibilityPrefs>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    q_cam: Query<(&Camera, &GlobalTransform), With<OrbitCamera>>,
+    q_editables: EditableHits,
+) {
+    if !build.enabled {
+        return;
+    }
+    let Some(hit) = cursor_build_hit(&windows, &q_cam, &q_editables) else {
+        return;
+    };
+    if keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+        if hit.removable {
+            let tf = hit
+                .target_transform
+                .with_scale(Vec3::splat(TILE_SIZE * 1.02));
+            gizmos.cuboid(tf, access.palette.danger());
+        }
+        return;
+    }
+    let [r, g, b] = TILE_PALETTE[build.palette_index].1;
+    gizmos.cuboid(
+        Transform::from_translation(hit.place_at).with_scale(Vec3::splat(TILE_SIZE)),
+        Color::srgb(r, g, b),
+    );
+}
+
+fn build_mode_panel(
+    mut ctxs: EguiContexts,
+    edit_state: Res<SceneEditState>,
+    mut build: ResMut<BuildMode>,
+) {
+    if !edit_state.open {
+        return;
+    }
+    let Ok(ctx) = ctxs.ctx_mut() else {
+        return;
+    };
+    egui::Window::new("Build")
+        .default_open(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.checkbox(&mut build.enabled, "Build mode (B)");
+            ui.label("Click a face to add a tile, Shift+click a tile to remove it.");
+            ui.horizontal_wrapped(|ui| {
+                for (i, (name, _)) in TILE_PALETTE.iter().enumerate() {
+                    ui.selectable_value(&mut build.palette_index, i, *name);
+                }
+            });
+        });
+}
@@ -1,18 +1,28 @@
 use bevy::{
-    core_pipeline::{
-        bloom::Bloom,
-        dof::{DepthOfField, DepthOfFieldMode},
-        tonemapping::Tonemapping,
-    },
-    pbr::{DistanceFog, FogFalloff, ScreenSpaceAmbientOcclusion},
+    core_pipeline::{bloom::Bloom, tonemapping::Tonemapping},
+    ecs::system::SystemParam,
+    pbr::{DistanceFog, FogFalloff},
     prelude::*,
-    render::camera::ScalingMode,
-};
-
-use crate::post::{
-    chroma_aberration::ChromaAberrationSettings, crt::CRTSettings,
-    gradient_tint::GradientTintSettings, lut::LutSettings,
+    render::camera::{RenderTarget, ScalingMode},
+    window::{PrimaryWindow, WindowRef},
 };
+use bevy_egui::EguiContext;
+use serde::{Deserialize, Serialize};
+
+#[cfg(not(target_arch = "wasm32"))]
+use bevy::core_pipeline::dof::{DepthOfField, DepthOfFieldMode};
+
+use crate::accessibility::{AccessibilityPrefs, InputStyle};
+#[cfg(feature = "chroma")]
+use crate::post::chroma_aberration::ChromaAberrationSettings;
+#[cfg(feature = "crt")]
+use crate::post::crt::CRTSettings;
+#[cfg(feature = "gradient")]
+use crate::post::gradient_tint::GradientTintSettings;
+use crate::post::lens_flare::LensFlareSettings;
+#[cfg(feature = "lut")]
+use crate::post::lut::LutSettings;
+use crate::settings::KeyBindings;
 
 // Rotation speed (radians per second). ~0.8 rad/s ≈ 45.8°/s.
 const ANGULAR_SPEED: f32 = 0.8;
@@ -50,12 +60,80 @@ pub struct OrbitCamera {
     pitch: f32,
 }
 
+impl OrbitCamera {
+    pub fn target(&self) -> Vec3 {
+        self.target
+    }
+
+    /// Look at `target` from the same direction and distance.
+    pub fn set_target(&mut self, target: Vec3) {
+        self.target = target;
+    }
+
+    /// Orbit `target` from `yaw` (radians around +Y, measured from +X) and `pitch` (elevation
+    /// above the ground plane), keeping the current distance.
+    pub fn aim(&mut self, target: Vec3, yaw: f32, pitch: f32) {
+        self.target = target;
+        self.index_4 = 0;
+        self.yaw_extra_rad = yaw - self.yaw_offset_rad;
+        self.pitch = pitch;
+    }
+
+    pub fn yaw(&self) -> f32 {
+        self.yaw_offset_rad
+            + (self.index_4.rem_euclid(4) as f32) * std::f32::consts::FRAC_PI_2
+            + self.yaw_extra_rad
+    }
+
+    pub fn pitch(&self) -> f32 {
+        self.pitch
+    }
+
+    /// The preset view the camera last snapped to, 0..4 (keys 1–4)
+    pub fn view(&self) -> usize {
+        self.index_4.rem_euclid(4) as usize
+    }
+}
+
+/// How the view covers two players in Play mode.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum CoopCamera {
+    /// One view, aimed midway between them
+    #[default]
+    Shared,
+    /// Left and right halves of the viewport, one player each
+    Split,
+}
+
+impl CoopCamera {
+    pub const ALL: [Self; 2] = [Self::Shared, Self::Split];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Shared => "Shared view",
+            Self::Split => "Split screen",
+        }
+    }
+}
+
+/// Right-hand view of split-screen co-op, following the second player. Not an `OrbitCamera`,
+/// so picking and editing stay with the main view.
+#[derive(Component)]
+pub struct SplitCamera;
+
 #[derive(SystemSet, Debug, Hash, PartialEq, Eq, Clone)]
 pub enum OrbitSet {
     Input, // read keyboard; mutate state
     Pose,  // compute and write Transform once
 }
 
+/// Switch the orbit camera's projection.
+#[derive(Event, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ProjectionEvent {
+    Orthographic,
+    Perspective,
+}
+
 #[derive(Component)]
 pub struct PitchReset {
     timer: Timer,
@@ -65,23 +143,28 @@ pub struct PitchReset {
 pub struct CameraPlugin;
 impl Plugin for CameraPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, spawn_camera).add_systems(
-            Update,
-            (
-                orbit_camera_hotkeys.in_set(OrbitSet::Input),
-                camera_pitch_controls.in_set(OrbitSet::Pose),
-                orbit_snap_to_index.in_set(OrbitSet::Pose),
-                orbit_camera_rotate_continuous.in_set(OrbitSet::Pose),
-                camera_projection_toggle_system,
-            )
-                .chain(),
-        );
+        app.init_resource::<KeyBindings>()
+            .init_resource::<AccessibilityPrefs>()
+            .add_event::<ProjectionEvent>()
+            .configure_sets(Update, (OrbitSet::Input, OrbitSet::Pose).chain())
+            .add_systems(Startup, spawn_camera)
+            .add_systems(
+                Update,
+                (
+                    orbit_camera_hotkeys.in_set(OrbitSet::Input),
+                    camera_pitch_controls.in_set(OrbitSet::Pose),
+                    orbit_snap_to_index.in_set(OrbitSet::Pose),
+                    orbit_camera_rotate_continuous.in_set(OrbitSet::Pose),
+                    camera_projection_toggle_system,
+                )
+                    .chain(),
+            );
     }
 }
 
 /// Camera with bloom, filmic tonemapping, gentle DoF-like vibe.
 pub fn spawn_camera(mut commands: Commands) {
-    commands.spawn((
+    let mut cam = commands.spawn((
         Camera3d { ..default() },
         Transform::from_xyz(9.0, 9.0, 13.0).looking_at(Vec3::new(3.0, 1.0, 2.5), Vec3::Y),
         Projection::from(OrthographicProjection {
@@ -102,39 +185,17 @@ pub fn spawn_camera(mut commands: Commands) {
             // (distance is relative to camera; play with value range in your game space)
             ..default()
         },
-        DepthOfField {
-            mode: DepthOfFieldMode::Bokeh, // or Gaussian
-            focal_distance: 15.0,          // distance from camera to focus band
-            aperture_f_stops: 0.2,         // lower = blurrier
-            sensor_height: 0.01866,        // Super 35 default
-            ..default()
-        },
-        // Extremely light SSAO helps creases without mud (optional; safe default)
-        ScreenSpaceAmbientOcclusion::default(),
         Msaa::Off,
         // Add the setting to the camera.
         // This component is also used to determine on which camera to run the post processing effect.
-        ChromaAberrationSettings {
-            enabled: 1,
-            intensity: 0.002,
-        },
-        CRTSettings {
-            enabled: 1,
-            intensity: 0.025,
-            scanline_freq: 202.5,
-            line_intensity: 0.1,
-        },
-        GradientTintSettings {
-            enabled: 1,
-            additive: 0,
-            strength: 0.5,
-            color_top_right: Vec4::new(0.9, 0.2, 0.3, 1.0), // pink-tint
-            color_bottom_left: Vec4::new(0.2, 0.9, 0.8, 1.0), // cyan-tint
-        },
-        LutSettings {
+        LensFlareSettings {
             enabled: 1,
-            strength: 1.0,
-            lut_size: 16,
+            emissive_sources: 1,
+            intensity: 0.6,
+            ghost_intensity: 0.15,
+            halo_intensity: 0.1,
+            emissive_threshold: 1.5,
+            ..default()
         },
         OrbitCamera {
             target: Vec3::ZERO,
@@ -145,6 +206,156 @@ pub fn spawn_camera(mut commands: Commands) {
         },
         Name::new("MainCamera"),
     ));
+
+    // DoF and SSAO need compute shaders and depth sampling that WebGL2 doesn't have
+    #[cfg(not(target_arch = "wasm32"))]
+    cam.insert((
+        DepthOfField {
+            mode: DepthOfFieldMode::Bokeh, // or Gaussian
+            focal_distance: 15.0,          // distance from camera to focus band
+            aperture_f_stops: 0.2,         // lower = blurrier
+            sensor_height: 0.01866,        // Super 35 default
+            ..default()
+        },
+        // Extremely light SSAO helps creases without mud (optional; safe default)
+        bevy::pbr::ScreenSpaceAmbientOcclusion::default(),
+    ));
+
+    // Optional passes, depending on the enabled Cargo features
+    #[cfg(feature = "chroma")]
+    cam.insert(ChromaAberrationSettings {
+        enabled: 1,
+        intensity: 0.002,
+        #[cfg(target_arch = "wasm32")]
+        _webgl2_padding: Vec2::ZERO,
+    });
+    #[cfg(feature = "crt")]
+    cam.insert(CRTSettings {
+        enabled: 1,
+        intensity: 0.025,
+        scanline_freq: 202.5,
+        line_intensity: 0.1,
+    });
+    #[cfg(feature = "gradient")]
+    cam.insert(GradientTintSettings {
+        enabled: 1,
+        additive: 0,
+        strength: 0.5,
+        color_top_right: Vec4::new(0.9, 0.2, 0.3, 1.0), // pink-tint
+        color_bottom_left: Vec4::new(0.2, 0.9, 0.8, 1.0), // cyan-tint
+    });
+    #[cfg(feature = "lut")]
+    cam.insert(LutSettings::default());
+}
+
+/// A picking ray through a cursor position, plus how much world a screen pixel covers along it.
+///
+/// Cursor positions are in logical window pixels: `Camera::viewport_to_world` and
+/// `world_to_viewport` already account for the viewport the docked panels leave.
+#[derive(Clone, Copy, Debug)]
+pub struct PickRay {
+    pub ray: Ray3d,
+    /// World size of a pixel at the ray origin (everywhere, for orthographic)
+    pixel: f32,
+    /// How much that grows per unit of distance along the ray (perspective)
+    spread: f32,
+}
+
+impl PickRay {
+    /// Ray through `cursor`, built from the camera's actual projection.
+    ///
+    /// Orthographic rays come straight from the visible area rather than the inverted clip
+    /// matrix, so they stay on the pixel however far the view is zoomed out, and start on the
+    /// near plane instead of somewhere inside the scene.
+    pub fn new(
+        camera: &Camera,
+        cam_gt: &GlobalTransform,
+        projection: &Projection,
+        cursor: Vec2,
+    ) -> Option<Self> {
+        let rect = camera.logical_viewport_rect()?;
+        if rect.height() <= 0.0 {
+            return None;
+        }
+        match projection {
+            Projection::Orthographic(ortho) => {
+                let mut rel = (cursor - rect.min) / rect.size();
+                rel.y = 1.0 - rel.y;
+                let area = ortho.area;
+                let view = (area.min + rel * area.size()).extend(-ortho.near);
+                Some(Self {
+                    ray: Ray3d::new(cam_gt.transform_point(view), cam_gt.forward()),
+                    pixel: area.height() / rect.height(),
+                    spread: 0.0,
+                })
+            }
+            Projection::Perspective(persp) => Some(Self {
+                ray: camera.viewport_to_world(cam_gt, cursor).ok()?,
+                pixel: 0.0,
+                spread: 2.0 * (persp.fov * 0.5).tan() / rect.height(),
+            }),
+            Projection::Custom(_) => Some(Self {
+                ray: camera.viewport_to_world(cam_gt, cursor).ok()?,
+                pixel: 0.0,
+                spread: 0.0,
+            }),
+        }
+    }
+
+    /// World length of `px` screen pixels at `point`.
+    pub fn pixels_at(&self, px: f32, point: Vec3) -> f32 {
+        let depth = (point - self.ray.origin).dot(*self.ray.direction).max(0.0);
+        px * (self.pixel + self.spread * depth)
+    }
+}
+
+/// The window a camera draws to (`None` for offscreen cameras such as the water reflection).
+pub fn camera_window(camera: &Camera, primary: Option<Entity>) -> Option<Entity> {
+    match &camera.target {
+        RenderTarget::Window(WindowRef::Primary) => primary,
+        RenderTarget::Window(WindowRef::Entity(e)) => Some(*e),
+        _ => None,
+    }
+}
+
+/// Whether egui has the pointer, per window: a panel under the cursor (or being dragged) keeps
+/// clicks out of the scene in its own window only. Works with any number of windows and egui
+/// contexts, none included, unlike `EguiContexts::ctx_mut`.
+#[derive(SystemParam)]
+pub struct UiPointer<'w, 's> {
+    q_ctx: Query<'w, 's, (&'static mut EguiContext, &'static Camera)>,
+    q_primary: Query<'w, 's, Entity, With<PrimaryWindow>>,
+}
+
+impl UiPointer<'_, '_> {
+    /// The primary window, if there still is one.
+    pub fn primary_window(&self) -> Option<Entity> {
+        self.q_primary.single().ok()
+    }
+
+    /// Whether egui wants the pointer in `window`.
+    pub fn wants_pointer(&mut self, window: Entity) -> bool {
+        let primary = self.primary_window();
+        self.q_ctx.iter_mut().any(|(mut ctx, camera)| {
+            camera_window(camera, primary) == Some(window) && ctx.get_mut().wants_pointer_input()
+        })
+    }
+
+    /// Whether egui wants the pointer in the primary window, for the tools that only work in
+    /// the main viewport.
+    pub fn wants_pointer_in_primary(&mut self) -> bool {
+        self.q_primary
+            .single()
+            .is_ok_and(|window| self.wants_pointer(window))
+    }
+
+    /// Whether a text field has the keyboard, in any window; shortcuts that mean something to
+    /// text (copy, paste) are left to it then.
+    pub fn wants_keyboard(&mut self) -> bool {
+        self.q_ctx
+            .iter_mut()
+            .any(|(mut ctx, _)| ctx.get_mut().wants_keyboard_input())
+    }
 }
 
 /// Helper: compute the *local* transform that looks at `target` with `up = Vec3::Y`,
@@ -183,9 +394,10 @@ pub fn orbit_snap_to_index(mut q_cam: Query<(&mut Transform, &mut OrbitCamera),
 
 /// Hotkeys to snap the camera:
 /// 1 / 2 / 3 / 4  => 12 / 3 / 6 / 9 o'clock
-/// Q / E          => rotate left / right by 90 degrees
+/// Q / E          => rotate left / right by 90 degrees (rebindable)
 pub fn orbit_camera_hotkeys(
     keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
     mut q_cam: Query<(&mut Transform, &mut OrbitCamera), With<Camera3d>>,
 ) {
     // Early out if no relevant key pressed
@@ -193,8 +405,8 @@ pub fn orbit_camera_hotkeys(
         || keys.just_pressed(KeyCode::Digit2)
         || keys.just_pressed(KeyCode::Digit3)
         || keys.just_pressed(KeyCode::Digit4)
-        || keys.just_pressed(KeyCode::KeyQ)
-        || keys.just_pressed(KeyCode::KeyE);
+        || keys.just_pressed(bindings.rotate_left)
+        || keys.just_pressed(bindings.rotate_right);
     if !any {
         return;
     }
@@ -223,11 +435,11 @@ pub fn orbit_camera_hotkeys(
             ocam.index_4 = 3;
             ocam.yaw_extra_rad = 0.0;
         }
-        if keys.just_pressed(KeyCode::KeyQ) {
+        if keys.just_pressed(bindings.rotate_left) {
             ocam.index_4 -= 1;
             ocam.yaw_extra_rad = 0.0;
         }
-        if keys.just_pressed(KeyCode::KeyE) {
+        if keys.just_pressed(bindings.rotate_right) {
             ocam.index_4 += 1;
             ocam.yaw_extra_rad = 0.0;
         }
@@ -249,12 +461,14 @@ pub fn orbit_camera_hotkeys(
 
 pub fn orbit_camera_rotate_continuous(
     keys: Res<ButtonInput<KeyCode>>,
-    time: Res<Time>,
+    // Real time, so the camera still turns while the scene is paused
+    time: Res<Time<Real>>,
     mut q_cam: Query<(&mut Transform, &mut OrbitCamera), With<Camera3d>>,
 ) {
     let left = keys.pressed(KeyCode::KeyA);
     let right = keys.pressed(KeyCode::KeyD);
-    if !(left || right) {
+    // Ctrl+Shift+D repeats a copy
+    if !(left || right) || keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
         return;
     }
 
@@ -288,8 +502,9 @@ pub fn orbit_camera_rotate_continuous(
 
 pub fn camera_pitch_controls(
     mut commands: Commands,
-    time: Res<Time>,
+    time: Res<Time<Real>>,
     keys: Res<ButtonInput<KeyCode>>,
+    access: Res<AccessibilityPrefs>,
     mut q: Query<(Entity, &mut OrbitCamera), With<Camera3d>>,
     mut reset_q: Query<&mut PitchReset>,
 ) {
@@ -319,8 +534,10 @@ pub fn camera_pitch_controls(
     // Clamp pitch to sane range (side view parallel to the ground, straight down perpendicular to the ground)
     rig.pitch = rig.pitch.clamp(0.0, std::f32::consts::FRAC_PI_2);
 
-    // Snap back to default pitch when key released
-    if keys.just_released(KeyCode::KeyW) || keys.just_released(KeyCode::KeyS) {
+    // Snap back to default pitch when key released (a toggled tilt stays until tilted back)
+    if access.tilt_input == InputStyle::Hold
+        && (keys.just_released(KeyCode::KeyW) || keys.just_released(KeyCode::KeyS))
+    {
         commands.entity(cam_entity).insert(PitchReset {
             timer: Timer::from_seconds(CAMERA_PITCH_SNAPBACK_DUR, TimerMode::Once),
             start: rig.pitch,
@@ -342,18 +559,18 @@ pub fn camera_pitch_controls(
     }
 }
 
-/// Toggle camera projection with keyboard:
-/// 'O' => Orthographic (isometric-style)
-/// 'P' => Perspective
+/// Switch camera projection (O and P by default, see [`crate::actions`]):
+/// Orthographic => isometric-style
+/// Perspective => 30° pitch
 fn camera_projection_toggle_system(
-    keys: Res<ButtonInput<KeyCode>>,
+    mut ev_projection: EventReader<ProjectionEvent>,
     mut q_cam: Query<(&mut Projection, &mut Transform, &mut OrbitCamera), With<Camera3d>>,
 ) {
-    let to_ortho = keys.just_pressed(KeyCode::KeyO);
-    let to_persp = keys.just_pressed(KeyCode::KeyP);
-    if !to_ortho && !to_persp {
+    let Some(&to) = ev_projection.read().last() else {
         return;
-    }
+    };
+    let to_ortho = to == ProjectionEvent::Orthographic;
+    let to_persp = to == ProjectionEvent::Perspective;
 
     for (mut proj, mut tf, mut ocam) in &mut q_cam {
         let target = ocam.target;
@@ -0,0 +1,289 @@
+//! Camera rails: a spline that takes over the play camera while the player is inside its
+//! volume. The view aims at the point of the curve nearest the player and zooms to the rail's
+//! setting, easing on and off, so each area of a level gets its own authored framing. Leaving
+//! every rail's volume eases back to the free view.
+
+use bevy::prelude::*;
+use bevy_egui::egui;
+use bevy_rapier3d::prelude::KinematicCharacterController;
+use serde::{Deserialize, Serialize};
+
+use crate::SceneEditState;
+use crate::camera::{OrbitCamera, OrbitSet};
+use crate::editor_mode::EditorMode;
+use crate::inspector::{Editable, Selected};
+use crate::splines::{SplinePath, polyline};
+
+/// Persisted on a spline object.
+#[derive(Component, Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CameraRail {
+    /// Middle of the box the player has to be in (local to the spline, like its points)
+    pub volume_center: [f32; 3],
+    /// Size of that box
+    pub volume_size: [f32; 3],
+    /// View size relative to the free view (below 1 is closer)
+    pub zoom: f32,
+    /// Aim this far above the curve
+    pub lift: f32,
+    /// Seconds to ease onto the rail and back off
+    pub blend_secs: f32,
+}
+
+impl Default for CameraRail {
+    fn default() -> Self {
+        // Covers the default spline
+        Self {
+            volume_center: [2.25, 1.5, 0.0],
+            volume_size: [7.0, 4.0, 5.0],
+            zoom: 0.7,
+            lift: 0.5,
+            blend_secs: 0.8,
+        }
+    }
+}
+
+impl CameraRail {
+    /// Whether `at` (world) is inside the volume of the rail placed at `gt`.
+    fn contains(&self, gt: &GlobalTransform, at: Vec3) -> bool {
+        let local = gt.affine().inverse().transform_point3(at);
+        let offset = local - Vec3::from_array(self.volume_center);
+        offset
+            .abs()
+            .cmple(Vec3::from_array(self.volume_size) * 0.5)
+            .all()
+    }
+
+    /// Fit the volume around the control points, with room to walk beside and above them.
+    fn fit(&mut self, spline: &SplinePath) {
+        let mut min = Vec3::splat(f32::MAX);
+        let mut max = Vec3::splat(f32::MIN);
+        for p in &spline.points {
+            min = min.min(Vec3::from_array(*p));
+            max = max.max(Vec3::from_array(*p));
+        }
+        if min.x > max.x {
+            return;
+        }
+        let (min, max) = (
+            min - Vec3::new(1.5, 0.5, 1.5),
+            max + Vec3::new(1.5, 3.0, 1.5),
+        );
+        self.volume_center = ((min + max) * 0.5).to_array();
+        self.volume_size = (max - min).to_array();
+    }
+}
+
+/// The free view's framing, kept while a rail has the camera.
+#[derive(Resource, Default)]
+struct RailCamera {
+    /// Target and zoom to ease back to once off every rail
+    home: Option<(Vec3, f32)>,
+    /// Ease time of the rail last followed
+    blend_secs: f32,
+}
+
+pub struct CameraRailsPlugin;
+impl Plugin for CameraRailsPlugin {
+    fn build(&self, app: &mut App) {
+        let follow = follow_camera_rails.before(OrbitSet::Pose);
+        // Aim where the player ended up this frame
+        #[cfg(feature = "player")]
+        let follow = follow.after(crate::player::player_motion_with_gravity);
+        app.init_resource::<EditorMode>()
+            .init_resource::<RailCamera>()
+            .add_systems(Update, (follow, draw_rail_volumes));
+    }
+}
+
+/// Steer the play camera along the rail whose volume the players are in.
+fn follow_camera_rails(
+    time: Res<Time>,
+    mode: Res<EditorMode>,
+    mut state: ResMut<RailCamera>,
+    q_players: Query<&GlobalTransform, With<KinematicCharacterController>>,
+    q_rails: Query<(&GlobalTransform, &SplinePath, &CameraRail), With<Editable>>,
+    mut q_cam: Query<(&mut OrbitCamera, &mut Projection)>,
+) {
+    let Ok((mut cam, mut proj)) = q_cam.single_mut() else {
+        return;
+    };
+    if *mode != EditorMode::Play {
+        // Editing starts again from the view Play started with
+        if let Some((target, zoom)) = state.home.take() {
+            cam.set_target(target);
+            set_zoom(&mut proj, zoom);
+        }
+        return;
+    }
+
+    // Co-op players share the view: follow the point between them
+    let (sum, count) = q_players
+        .iter()
+        .fold((Vec3::ZERO, 0), |(s, n), p| (s + p.translation(), n + 1));
+    let at = (count > 0).then(|| sum / count as f32);
+    // Of the rails the players are in, the one whose curve is nearest
+    let rail = at.and_then(|at| {
+        q_rails
+            .iter()
+            .filter(|(gt, _, rail)| rail.contains(gt, at))
+            .filter_map(|(gt, spline, rail)| {
+                let line: Vec<Vec3> = polyline(&spline.points)
+                    .into_iter()
+                    .map(|p| gt.transform_point(p))
+                    .collect();
+                Some((nearest_on_line(&line, at)?, rail))
+            })
+            .min_by(|a, b| {
+                a.0.distance_squared(at)
+                    .total_cmp(&b.0.distance_squared(at))
+            })
+    });
+
+    let current = (cam.target(), zoom(&proj));
+    let (target, zoom_to) = match rail {
+        Some((point, rail)) => {
+            let home = *state.home.get_or_insert(current);
+            state.blend_secs = rail.blend_secs;
+            (point + Vec3::Y * rail.lift, home.1 * rail.zoom)
+        }
+        None => match state.home {
+            Some(home) => home,
+            None => return,
+        },
+    };
+    // Exponential ease, about 95% of the way there after the blend time
+    let k = if state.blend_secs > 0.0 {
+        1.0 - (-3.0 * time.delta_secs() / state.blend_secs).exp()
+    } else {
+        1.0
+    };
+    let mut eased = (
+        current.0.lerp(target, k),
+        current.1 + (zoom_to - current.1) * k,
+    );
+    if rail.is_none() && eased.0.distance(target) < 0.01 && (eased.1 - zoom_to).abs() < 0.001 {
+        // Back on the free view
+        eased = (target, zoom_to);
+        state.home = None;
+    }
+    if eased.0 != current.0 {
+        cam.set_target(eased.0);
+    }
+    set_zoom(&mut proj, eased.1);
+}
+
+/// Closest point to `p` on a polyline.
+fn nearest_on_line(line: &[Vec3], p: Vec3) -> Option<Vec3> {
+    if line.len() < 2 {
+        return line.first().copied();
+    }
+    line.windows(2)
+        .map(|w| {
+            let ab = w[1] - w[0];
+            let t = ((p - w[0]).dot(ab) / ab.length_squared().max(1e-6)).clamp(0.0, 1.0);
+            w[0] + ab * t
+        })
+        .min_by(|a, b| a.distance_squared(p).total_cmp(&b.distance_squared(p)))
+}
+
+/// View size relative to the default projection.
+fn zoom(proj: &Projection) -> f32 {
+    match proj {
+        Projection::Orthographic(o) => o.scale,
+        Projection::Perspective(p) => p.fov / std::f32::consts::FRAC_PI_4,
+        _ => 1.0,
+    }
+}
+
+fn set_zoom(proj: &mut Mut<Projection>, zoom_to: f32) {
+    if (zoom(proj) - zoom_to).abs() < 1e-5 {
+        return;
+    }
+    match &mut **proj {
+        Projection::Orthographic(o) => o.scale = zoom_to,
+        Projection::Perspective(p) => {
+            p.fov = (std::f32::consts::FRAC_PI_4 * zoom_to).clamp(0.1, 3.0);
+        }
+        _ => {}
+    }
+}
+
+/// Rail volumes while editing; the selected rail's is highlighted.
+fn draw_rail_volumes(
+    mut gizmos: Gizmos,
+    edit_state: Res<SceneEditState>,
+    mode: Res<EditorMode>,
+    q: Query<(&GlobalTransform, &CameraRail, Has<Selected>), With<Editable>>,
+) {
+    if !edit_state.open || *mode == EditorMode::Play {
+        return;
+    }
+    for (gt, rail, selected) in &q {
+        let color = if selected {
+            Color::srgb(0.4, 0.8, 1.0)
+        } else {
+            Color::srgba(0.4, 0.8, 1.0, 0.35)
+        };
+        let volume = Transform::from_translation(Vec3::from_array(rail.volume_center))
+            .with_scale(Vec3::from_array(rail.volume_size));
+        gizmos.cuboid(gt.mul_transform(volume), color);
+    }
+}
+
+/// Inspector section for a spline: whether it's a camera rail, and the rail's volume and view.
+pub fn camera_rail_inspector_ui(
+    ui: &mut egui::Ui,
+    commands: &mut Commands,
+    entity: Entity,
+    spline: &SplinePath,
+    rail: Option<Mut<CameraRail>>,
+) -> bool {
+    let mut on = rail.is_some();
+    if ui
+        .checkbox(&mut on, "Camera rail")
+        .on_hover_text("Steers the play camera while the player is inside the rail's volume")
+        .changed()
+    {
+        if on {
+            let mut rail = CameraRail::default();
+            rail.fit(spline);
+            commands.entity(entity).insert(rail);
+        } else {
+            commands.entity(entity).remove::<CameraRail>();
+        }
+        return true;
+    }
+    let Some(mut rail) = rail else {
+        return false;
+    };
+
+    let mut r = rail.clone();
+    ui.add(
+        egui::Slider::new(&mut r.zoom, 0.3..=3.0)
+            .logarithmic(true)
+            .text("Zoom (view size)"),
+    );
+    ui.add(egui::Slider::new(&mut r.lift, -2.0..=4.0).text("Aim above curve (m)"));
+    ui.add(egui::Slider::new(&mut r.blend_secs, 0.0..=3.0).text("Ease time (s)"));
+    ui.label("Volume");
+    for (label, values) in [
+        ("Center", &mut r.volume_center),
+        ("Size", &mut r.volume_size),
+    ] {
+        ui.horizontal(|ui| {
+            ui.label(label);
+            for v in values.iter_mut() {
+                ui.add(egui::DragValue::new(v).speed(0.05));
+            }
+        });
+    }
+    for s in &mut r.volume_size {
+        *s = s.max(0.1);
+    }
+    if ui.button("Fit volume to curve").clicked() {
+        r.fit(spline);
+    }
+    ui.weak("The Guide spline mode keeps the curve itself invisible.");
+    rail.set_if_neq(r)
+}
@@ -0,0 +1,359 @@
+//! Record the window (post effects included) to an animated GIF, or to an MP4 through `ffmpeg`.
+//!
+//! Frames are grabbed with Bevy's screenshot readback at the chosen framerate and streamed to an
+//! encoder thread, so a recording only holds the frames the encoder hasn't caught up with yet.
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+use std::sync::mpsc::{self, Receiver, Sender};
+
+use bevy::prelude::*;
+use bevy::render::view::screenshot::{Screenshot, ScreenshotCaptured};
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::{Delay, DynamicImage, Frame};
+
+use crate::SceneEditState;
+use crate::settings::KeyBindings;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CaptureFormat {
+    #[default]
+    Gif,
+    /// Needs `ffmpeg` on the PATH
+    Mp4,
+}
+
+impl CaptureFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            CaptureFormat::Gif => "gif",
+            CaptureFormat::Mp4 => "mp4",
+        }
+    }
+}
+
+/// Options from the Capture window.
+#[derive(Resource, Clone)]
+pub struct CaptureSettings {
+    pub format: CaptureFormat,
+    pub fps: u32,
+    /// Output size as a fraction of the window
+    pub scale: f32,
+    /// Stop after this many seconds; 0 records until stopped
+    pub max_secs: f32,
+    /// Close the editor panels while recording, so only the scene is captured
+    pub hide_editor: bool,
+}
+
+impl Default for CaptureSettings {
+    fn default() -> Self {
+        Self {
+            format: CaptureFormat::Gif,
+            fps: 15,
+            scale: 0.5,
+            max_secs: 10.0,
+            hide_editor: true,
+        }
+    }
+}
+
+enum EncoderMsg {
+    Frame(Box<Image>),
+    Finish,
+}
+
+struct Recording {
+    path: String,
+    started: f32,
+    next_frame: f32,
+    frames: u32,
+    tx: Sender<EncoderMsg>,
+    /// Editor visibility to restore afterwards
+    editor_was_open: bool,
+}
+
+/// The recording in progress, and what the last one ended with.
+#[derive(Resource, Default)]
+pub struct CaptureState {
+    recording: Option<Recording>,
+    /// Saved file or error, as reported by the encoder thread
+    pub status: Option<String>,
+    results: Option<Mutex<Receiver<String>>>,
+}
+
+impl CaptureState {
+    pub fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+}
+
+/// Start or stop a recording with the current `CaptureSettings`.
+#[derive(Event)]
+pub struct ToggleCaptureEvent;
+
+/// Capture window and the record key (F9 by default).
+pub struct CapturePlugin;
+impl Plugin for CapturePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SceneEditState>()
+            .init_resource::<KeyBindings>()
+            .init_resource::<CaptureSettings>()
+            .init_resource::<CaptureState>()
+            .add_event::<ToggleCaptureEvent>()
+            .add_systems(Update, (toggle_capture, grab_frames).chain())
+            .add_systems(EguiPrimaryContextPass, capture_window);
+    }
+}
+
+fn unix_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+fn toggle_capture(
+    mut ev_toggle: EventReader<ToggleCaptureEvent>,
+    time: Res<Time<Real>>,
+    settings: Res<CaptureSettings>,
+    mut state: ResMut<CaptureState>,
+    mut edit_state: ResMut<SceneEditState>,
+) {
+    if ev_toggle.read().count() == 0 {
+        return;
+    }
+    if state.is_recording() {
+        stop(&mut state, &mut edit_state);
+        return;
+    }
+
+    let path = format!("capture-{}.{}", unix_secs(), settings.format.extension());
+    let (tx, rx) = mpsc::channel();
+    let (result_tx, result_rx) = mpsc::channel();
+    let (thread_path, format, fps, scale) = (
+        path.clone(),
+        settings.format,
+        settings.fps.max(1),
+        settings.scale,
+    );
+    std::thread::spawn(move || {
+        let result = match encode(&thread_path, format, fps, scale, rx) {
+            Ok(0) => "Nothing recorded".to_string(),
+            Ok(frames) => format!("Saved {thread_path} ({frames} frames)"),
+            Err(e) => format!("Recording failed: {e}"),
+        };
+        eprintln!("Capture: {result}");
+        let _ = result_tx.send(result);
+    });
+
+    let now = time.elapsed_secs();
+    state.recording = Some(Recording {
+        path,
+        started: now,
+        next_frame: now,
+        frames: 0,
+        tx,
+        editor_was_open: edit_state.open,
+    });
+    state.status = None;
+    state.results = Some(Mutex::new(result_rx));
+    if settings.hide_editor {
+        edit_state.open = false;
+    }
+}
+
+fn stop(state: &mut CaptureState, edit_state: &mut SceneEditState) {
+    let Some(recording) = state.recording.take() else {
+        return;
+    };
+    // Screenshots still in flight arrive after this and are dropped
+    let _ = recording.tx.send(EncoderMsg::Finish);
+    edit_state.open = recording.editor_was_open;
+    state.status = Some(format!("Encoding {}...", recording.path));
+}
+
+/// Ask for a screenshot every `1 / fps` seconds while recording.
+fn grab_frames(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    settings: Res<CaptureSettings>,
+    mut state: ResMut<CaptureState>,
+    mut edit_state: ResMut<SceneEditState>,
+) {
+    // The encoder reports back once the file is complete
+    if let Some(result) = state
+        .results
+        .as_ref()
+        .and_then(|rx| rx.lock().ok()?.try_recv().ok())
+    {
+        state.status = Some(result);
+        state.results = None;
+    }
+    let now = time.elapsed_secs();
+    let Some(recording) = state.recording.as_mut() else {
+        return;
+    };
+    if settings.max_secs > 0.0 && now - recording.started >= settings.max_secs {
+        stop(&mut state, &mut edit_state);
+        return;
+    }
+    if now < recording.next_frame {
+        return;
+    }
+    let interval = 1.0 / settings.fps.max(1) as f32;
+    // Don't try to catch up after a stall, just keep the rate from here
+    recording.next_frame = (recording.next_frame + interval).max(now);
+    recording.frames += 1;
+    let tx = recording.tx.clone();
+    commands.spawn(Screenshot::primary_window()).observe(
+        move |trigger: Trigger<ScreenshotCaptured>| {
+            let _ = tx.send(EncoderMsg::Frame(Box::new(trigger.event().0.clone())));
+        },
+    );
+}
+
+/// Frame as RGBA at `size` (the first frame's size, so a resized window doesn't break the file).
+fn rgba_frame(shot: Image, scale: f32, size: &mut Option<(u32, u32)>) -> Option<image::RgbaImage> {
+    let dynamic: DynamicImage = shot.try_into_dynamic().ok()?;
+    let (w, h) = *size.get_or_insert_with(|| {
+        let w = ((dynamic.width() as f32 * scale).round() as u32).max(2);
+        let h = ((dynamic.height() as f32 * scale).round() as u32).max(2);
+        // Even sizes, for yuv420p video
+        (w & !1, h & !1)
+    });
+    let resized = if (dynamic.width(), dynamic.height()) == (w, h) {
+        dynamic
+    } else {
+        dynamic.thumbnail_exact(w, h)
+    };
+    let mut rgba = resized.into_rgba8();
+    // Alpha holds brightness with HDR on; GIF would take low values as transparent
+    for pixel in rgba.pixels_mut() {
+        pixel.0[3] = 255;
+    }
+    Some(rgba)
+}
+
+/// Runs on the encoder thread until `Finish`; returns the number of frames written.
+fn encode(
+    path: &str,
+    format: CaptureFormat,
+    fps: u32,
+    scale: f32,
+    rx: Receiver<EncoderMsg>,
+) -> Result<u32, String> {
+    let mut size = None;
+    let mut frames = 0;
+    match format {
+        CaptureFormat::Gif => {
+            let file = File::create(path).map_err(|e| e.to_string())?;
+            let mut encoder = GifEncoder::new_with_speed(BufWriter::new(file), 10);
+            encoder
+                .set_repeat(Repeat::Infinite)
+                .map_err(|e| e.to_string())?;
+            let delay = Delay::from_numer_denom_ms(1000, fps);
+            while let Ok(EncoderMsg::Frame(image)) = rx.recv() {
+                let Some(rgba) = rgba_frame(*image, scale, &mut size) else {
+                    continue;
+                };
+                encoder
+                    .encode_frame(Frame::from_parts(rgba, 0, 0, delay))
+                    .map_err(|e| e.to_string())?;
+                frames += 1;
+            }
+        }
+        CaptureFormat::Mp4 => {
+            // ffmpeg is started on the first frame, once the size is known
+            let mut ffmpeg = None;
+            while let Ok(EncoderMsg::Frame(image)) = rx.recv() {
+                let Some(rgba) = rgba_frame(*image, scale, &mut size) else {
+                    continue;
+                };
+                if ffmpeg.is_none() {
+                    let (w, h) = rgba.dimensions();
+                    let child = Command::new("ffmpeg")
+                        .args(["-y", "-loglevel", "error", "-f", "rawvideo"])
+                        .args(["-pix_fmt", "rgba", "-s", &format!("{w}x{h}")])
+                        .args(["-r", &fps.to_string(), "-i", "-"])
+                        .args(["-c:v", "libx264", "-pix_fmt", "yuv420p", path])
+                        .stdin(Stdio::piped())
+                        .spawn()
+                        .map_err(|e| format!("cannot run ffmpeg: {e}"))?;
+                    ffmpeg = Some(child);
+                }
+                let stdin = ffmpeg
+                    .as_mut()
+                    .and_then(|c| c.stdin.as_mut())
+                    .ok_or("ffmpeg has no input")?;
+                stdin.write_all(rgba.as_raw()).map_err(|e| e.to_string())?;
+                frames += 1;
+            }
+            if let Some(mut child) = ffmpeg {
+                // Closing its input lets ffmpeg finish the file
+                drop(child.stdin.take());
+                let status = child.wait().map_err(|e| e.to_string())?;
+                if !status.success() {
+                    return Err(format!("ffmpeg exited with {status}"));
+                }
+            }
+        }
+    }
+    Ok(frames)
+}
+
+fn capture_window(
+    mut ctxs: EguiContexts,
+    edit_state: Res<SceneEditState>,
+    bindings: Res<KeyBindings>,
+    mut settings: ResMut<CaptureSettings>,
+    state: Res<CaptureState>,
+    mut ev_toggle: EventWriter<ToggleCaptureEvent>,
+) {
+    if !edit_state.open {
+        return;
+    }
+    let Ok(ctx) = ctxs.ctx_mut() else {
+        return;
+    };
+    egui::Window::new("Capture")
+        .default_open(false)
+        .show(ctx, |ui| {
+            let recording = state.is_recording();
+            ui.add_enabled_ui(!recording, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Format");
+                    ui.selectable_value(&mut settings.format, CaptureFormat::Gif, "GIF");
+                    ui.selectable_value(&mut settings.format, CaptureFormat::Mp4, "MP4")
+                        .on_hover_text("Encoded with ffmpeg, which must be installed");
+                });
+                ui.add(egui::Slider::new(&mut settings.fps, 5..=60).text("Framerate"));
+                ui.horizontal(|ui| {
+                    ui.label("Size");
+                    for (scale, label) in [(1.0, "100%"), (0.5, "50%"), (0.25, "25%")] {
+                        ui.selectable_value(&mut settings.scale, scale, label);
+                    }
+                });
+                ui.add(
+                    egui::Slider::new(&mut settings.max_secs, 0.0..=120.0)
+                        .text("Seconds (0 = until stopped)"),
+                );
+                ui.checkbox(&mut settings.hide_editor, "Hide the editor while recording");
+            });
+
+            let label = if recording { "⏹ Stop" } else { "⏺ Record" };
+            if ui
+                .button(label)
+                .on_hover_text(format!("{:?} also starts and stops", bindings.record))
+                .clicked()
+            {
+                ev_toggle.write(ToggleCaptureEvent);
+            }
+            if let Some(recording) = &state.recording {
+                ui.weak(format!("Recording {} frames", recording.frames));
+            } else if let Some(status) = &state.status {
+                ui.weak(status);
+            }
+        });
+}
@@ -0,0 +1,690 @@
+//! Scene streaming: a streamed scene is saved as chunks, one per square grid cell on the ground
+//! plane, and only the cells around the camera target are spawned. Objects of cells that drop
+//! out of range are written to a file for their cell and despawned; the file is read back when
+//! the target returns. Worlds far larger than one diorama stay editable and playable that way,
+//! without holding the whole world in memory.
+//!
+//! Natively a streamed scene is saved the same way: the scene file lists its cells, each saved
+//! to its own file beside it (in `<scene>.cells/`), and loading it reads a cell's file only once
+//! the cell comes in range. The browser build keeps the cells inside the scene file.
+//!
+//! An object belongs to the cell its origin is in, wherever it was moved to; an object with a
+//! joint belongs to the cell of the object it's attached to, so both halves of a joint are
+//! always spawned together. Selected objects (and whatever they're jointed with) stay spawned
+//! until they're deselected. Play mode streams too; what it streamed out or in is put back with
+//! the rest of the scene when Play ends.
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use bevy::prelude::*;
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+use serde::{Deserialize, Serialize};
+
+use crate::SceneEditState;
+use crate::asset_cache::AssetCache;
+use crate::camera::OrbitCamera;
+use crate::editor_mode::EditorMode;
+use crate::inspector::{
+    Editable, SceneIoState, SceneObject, SceneObjects, Selected, sort_objects, spawn_scene_object,
+};
+use crate::joints::SceneJoint;
+use crate::scene_export::serialize_counted;
+use crate::scene_io;
+
+/// Seconds between checks for objects moved out of range, when the target stays in its cell.
+const RECHECK_SECS: f32 = 1.0;
+
+/// Where the cells out of range wait while the scene is open.
+const SCRATCH_DIR: &str = "streamed-cells";
+
+/// Whether saved cells go in files of their own (the browser's downloads are one file).
+const CELL_FILES: bool = cfg!(not(target_arch = "wasm32"));
+
+/// One cell of a streamed scene file.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct SceneChunk {
+    /// Cell index along X and Z
+    pub cell: [i32; 2],
+    /// The cell's own file, relative to the scene file, when its objects aren't inline
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    /// Objects in `file`
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub count: usize,
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        serialize_with = "serialize_counted"
+    )]
+    pub objects: Vec<SceneObject>,
+}
+
+fn is_zero(n: &usize) -> bool {
+    *n == 0
+}
+
+impl SceneChunk {
+    /// The cell's objects, read from its file if they aren't inline. `scene_path` is the scene
+    /// file it was loaded from.
+    pub(crate) fn into_objects(self, scene_path: &str) -> Result<Vec<SceneObject>, String> {
+        match self.file {
+            Some(file) => read_cell(&beside(scene_path, &file)),
+            None => Ok(self.objects),
+        }
+    }
+}
+
+/// The contents of a cell file.
+#[derive(Serialize)]
+#[serde(transparent)]
+struct CellFile(#[serde(serialize_with = "serialize_counted")] Vec<SceneObject>);
+
+/// `file` (relative to the scene file's folder) as a path from the working directory.
+fn beside(scene_path: &str, file: &str) -> String {
+    let dir = Path::new(scene_path).parent().unwrap_or(Path::new(""));
+    dir.join(file).to_string_lossy().into_owned()
+}
+
+fn read_cell(file: &str) -> Result<Vec<SceneObject>, String> {
+    let text = scene_io::read_text(file).ok_or_else(|| format!("can't read {file}"))?;
+    serde_json::from_str(&text).map_err(|e| format!("{file}: {e}"))
+}
+
+/// A cell out of range: the file holding its objects and how many there are.
+#[derive(Clone)]
+struct StoredCell {
+    file: String,
+    objects: usize,
+}
+
+/// What saving a cell takes.
+enum CellContents {
+    /// Serialize these objects
+    Objects(Vec<SceneObject>),
+    /// Copy this stored cell's file
+    CopyOf(String),
+}
+
+/// A streamed scene's cells as they're saved: what the scene file lists, and the cell files to
+/// write beside it with [`SavedCells::write`].
+#[derive(Default)]
+pub(crate) struct SavedCells {
+    pub chunks: Vec<SceneChunk>,
+    files: Vec<(String, CellContents)>,
+    /// Objects serialized on the way, inline or into files
+    pub counted: usize,
+}
+
+impl SavedCells {
+    /// Write the cell files. Run before the scene file that lists them.
+    pub(crate) fn write(self) -> Result<(), String> {
+        for (file, contents) in self.files {
+            match contents {
+                CellContents::Objects(objects) => {
+                    let json = serde_json::to_string_pretty(&CellFile(objects))
+                        .map_err(|e| e.to_string())?;
+                    scene_io::write_text(&file, &json)?;
+                }
+                // Saved over the file it was loaded from, untouched
+                CellContents::CopyOf(from) if from == file => {}
+                CellContents::CopyOf(from) => {
+                    let text =
+                        scene_io::read_text(&from).ok_or_else(|| format!("can't read {from}"))?;
+                    scene_io::write_text(&file, &text)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The streaming settings and where the cells that aren't spawned are kept.
+#[derive(Resource)]
+pub struct SceneChunks {
+    /// Stream the scene (and save it as chunks)
+    pub enabled: bool,
+    /// Cell width, in meters
+    pub size: f32,
+    /// Cells kept spawned in every direction from the target's cell
+    pub radius: i32,
+    /// Cells out of range
+    stored: HashMap<IVec2, StoredCell>,
+    /// Cells in range
+    loaded: HashSet<IVec2>,
+    /// Stored cells whose file couldn't be read, left out of range rather than retried
+    unreadable: HashSet<IVec2>,
+    /// Stored cells and cells in range as Play started, put back when it ends
+    before_play: Option<(HashMap<IVec2, StoredCell>, HashSet<IVec2>)>,
+    /// Files written to [`SCRATCH_DIR`]; each write gets a new one, so Play's rollback can go
+    /// back to the earlier ones
+    scratch: Vec<String>,
+}
+
+impl Default for SceneChunks {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            size: 32.0,
+            radius: 1,
+            stored: HashMap::new(),
+            loaded: HashSet::new(),
+            unreadable: HashSet::new(),
+            before_play: None,
+            scratch: Vec::new(),
+        }
+    }
+}
+
+impl SceneChunks {
+    fn cell_of(&self, at: Vec3) -> IVec2 {
+        (at.xz() / self.size.max(1.0)).floor().as_ivec2()
+    }
+
+    fn in_range(&self, center: IVec2, cell: IVec2) -> bool {
+        (cell - center).abs().max_element() <= self.radius.max(0)
+    }
+
+    /// The cell each of `objects` goes in: its own, or for a jointed object the cell of what
+    /// it's attached to (when that's among `objects`), followed to the end of the chain.
+    fn cells_of(&self, objects: &[SceneObject]) -> Vec<IVec2> {
+        let names: HashMap<&str, usize> = objects
+            .iter()
+            .enumerate()
+            .filter_map(|(i, obj)| Some((obj.name()?, i)))
+            .collect();
+        (0..objects.len())
+            .map(|mut i| {
+                for _ in 0..objects.len() {
+                    match objects[i].joint_target().and_then(|to| names.get(to)) {
+                        Some(&to) if to != i => i = to,
+                        _ => break,
+                    }
+                }
+                self.cell_of(objects[i].position())
+            })
+            .collect()
+    }
+
+    /// Write `objects` to `cell`'s file, along with any already waiting there.
+    fn store(&mut self, cell: IVec2, mut objects: Vec<SceneObject>) -> Result<(), String> {
+        if let Some(stored) = self.stored.get(&cell) {
+            let mut waiting = read_cell(&stored.file)?;
+            waiting.append(&mut objects);
+            objects = waiting;
+        }
+        let file = format!("{SCRATCH_DIR}/{}.json", self.scratch.len());
+        let json = serde_json::to_string(&objects).map_err(|e| e.to_string())?;
+        scene_io::write_text(&file, &json)?;
+        self.scratch.push(file.clone());
+        self.stored.insert(
+            cell,
+            StoredCell {
+                file,
+                objects: objects.len(),
+            },
+        );
+        Ok(())
+    }
+
+    /// Store `objects`, each in its cell.
+    fn store_all(&mut self, objects: Vec<SceneObject>) {
+        let mut cells: HashMap<IVec2, Vec<SceneObject>> = HashMap::new();
+        let at = self.cells_of(&objects);
+        for (obj, cell) in objects.into_iter().zip(at) {
+            cells.entry(cell).or_default().push(obj);
+        }
+        for (cell, objects) in cells {
+            if let Err(e) = self.store(cell, objects) {
+                eprintln!("Couldn't store cell {cell}: {e}");
+            }
+        }
+    }
+
+    /// Start streaming a scene loaded from `scene_path`: cells with a file of their own are read
+    /// when they come in range, and inline ones are stored until then.
+    pub(crate) fn stream(&mut self, size: f32, chunks: Vec<SceneChunk>, scene_path: &str) {
+        self.reset();
+        self.enabled = true;
+        self.size = size;
+        let mut inline = Vec::new();
+        for chunk in chunks {
+            match &chunk.file {
+                Some(file) => {
+                    let stored = StoredCell {
+                        file: beside(scene_path, file),
+                        objects: chunk.count,
+                    };
+                    self.stored.insert(IVec2::from_array(chunk.cell), stored);
+                }
+                None => inline.extend(chunk.objects),
+            }
+        }
+        self.store_all(inline);
+    }
+
+    /// Forget the streamed scene (another one was loaded).
+    pub(crate) fn reset(&mut self) {
+        self.enabled = false;
+        self.stored.clear();
+        self.loaded.clear();
+        self.unreadable.clear();
+        self.before_play = None;
+        for file in self.scratch.drain(..) {
+            scene_io::remove_text(&file);
+        }
+    }
+
+    /// Cells to save to `path`: the stored ones and the spawned objects, `spawned`, each in
+    /// their cell.
+    pub(crate) fn saved(
+        &self,
+        spawned: Vec<SceneObject>,
+        path: &str,
+    ) -> Result<SavedCells, String> {
+        let mut cells: HashMap<IVec2, CellContents> = HashMap::new();
+        let at = self.cells_of(&spawned);
+        for (obj, cell) in spawned.into_iter().zip(at) {
+            if let CellContents::Objects(objects) = cells
+                .entry(cell)
+                .or_insert(CellContents::Objects(Vec::new()))
+            {
+                objects.push(obj);
+            }
+        }
+        // Cells with spawned objects in them are written whole; the others are copied
+        for (cell, stored) in &self.stored {
+            match cells.get_mut(cell) {
+                Some(CellContents::Objects(objects)) => objects.extend(read_cell(&stored.file)?),
+                _ => {
+                    cells.insert(*cell, CellContents::CopyOf(stored.file.clone()));
+                }
+            }
+        }
+
+        let mut cells: Vec<(IVec2, CellContents)> = cells.into_iter().collect();
+        cells.sort_by_key(|(cell, _)| (cell.y, cell.x));
+        let name = Path::new(path)
+            .file_name()
+            .map_or("scene".into(), |n| n.to_string_lossy());
+        let mut saved = SavedCells::default();
+        for (cell, contents) in cells {
+            let contents = match contents {
+                CellContents::Objects(mut objects) => {
+                    sort_objects(&mut objects);
+                    saved.counted += objects.len();
+                    CellContents::Objects(objects)
+                }
+                CellContents::CopyOf(file) if !CELL_FILES => {
+                    let objects = read_cell(&file)?;
+                    saved.counted += objects.len();
+                    CellContents::Objects(objects)
+                }
+                copy => copy,
+            };
+            let mut chunk = SceneChunk {
+                cell: cell.to_array(),
+                file: None,
+                count: 0,
+                objects: Vec::new(),
+            };
+            match contents {
+                CellContents::Objects(objects) if !CELL_FILES => chunk.objects = objects,
+                contents => {
+                    let file = format!("{name}.cells/{}_{}.json", cell.x, cell.y);
+                    chunk.count = match &contents {
+                        CellContents::Objects(objects) => objects.len(),
+                        CellContents::CopyOf(_) => self.stored[&cell].objects,
+                    };
+                    saved.files.push((beside(path, &file), contents));
+                    chunk.file = Some(file);
+                }
+            }
+            saved.chunks.push(chunk);
+        }
+        Ok(saved)
+    }
+
+    /// Objects kept for cells out of range.
+    pub fn stored_objects(&self) -> usize {
+        self.stored.values().map(|c| c.objects).sum()
+    }
+
+    /// Regroup the stored objects after the cell size changed.
+    fn regroup(&mut self) {
+        let mut objects = Vec::new();
+        for (cell, stored) in std::mem::take(&mut self.stored) {
+            match read_cell(&stored.file) {
+                Ok(mut cell_objects) => objects.append(&mut cell_objects),
+                Err(e) => eprintln!("Couldn't regroup cell {cell}: {e}"),
+            }
+        }
+        self.unreadable.clear();
+        self.store_all(objects);
+    }
+}
+
+pub struct ChunksPlugin;
+impl Plugin for ChunksPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SceneChunks>()
+            .init_resource::<EditorMode>()
+            .init_resource::<SceneEditState>()
+            .add_systems(Update, (rollback_after_play, stream_out, stream_in).chain())
+            .add_systems(EguiPrimaryContextPass, streaming_window);
+    }
+}
+
+/// Keep the stored cells as Play starts, and put them back when it ends (the play rollback
+/// puts back the spawned objects).
+fn rollback_after_play(mode: Res<EditorMode>, mut chunks: ResMut<SceneChunks>) {
+    if !mode.is_changed() {
+        return;
+    }
+    if *mode == EditorMode::Play {
+        if chunks.before_play.is_none() {
+            chunks.before_play = Some((chunks.stored.clone(), chunks.loaded.clone()));
+        }
+    } else if let Some((stored, loaded)) = chunks.before_play.take() {
+        chunks.stored = stored;
+        chunks.loaded = loaded;
+    }
+}
+
+/// Store and despawn the objects out of range of the camera target. Checked when the target
+/// enters another cell or the settings change, and every [`RECHECK_SECS`].
+fn stream_out(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    mut chunks: ResMut<SceneChunks>,
+    mut io: Option<ResMut<SceneIoState>>,
+    scene: SceneObjects,
+    q_cam: Query<&OrbitCamera>,
+    q_editables: Query<
+        (
+            Entity,
+            &Transform,
+            Option<&Name>,
+            Option<&SceneJoint>,
+            Has<Selected>,
+        ),
+        With<Editable>,
+    >,
+    mut last: Local<Option<(IVec2, f32)>>,
+) {
+    if !chunks.enabled {
+        *last = None;
+        if !chunks.loaded.is_empty() {
+            chunks.loaded.clear();
+        }
+        return;
+    }
+    let Ok(cam) = q_cam.single() else {
+        return;
+    };
+    let center = chunks.cell_of(cam.target());
+    let now = time.elapsed_secs();
+    if !chunks.is_changed()
+        && last.is_some_and(|(cell, at)| cell == center && now - at < RECHECK_SECS)
+    {
+        return;
+    }
+    *last = Some((center, now));
+
+    // Jointed objects go with what they're attached to, and stay if any of them is selected
+    let names: HashMap<&str, Entity> = q_editables
+        .iter()
+        .filter_map(|(e, _, name, ..)| Some((name?.as_str(), e)))
+        .collect();
+    let root_of = |mut e: Entity| {
+        for _ in 0..names.len() {
+            let Ok((.., Some(joint), _)) = q_editables.get(e) else {
+                break;
+            };
+            match names.get(joint.to.as_str()) {
+                Some(&to) if to != e => e = to,
+                _ => break,
+            }
+        }
+        e
+    };
+    let roots: Vec<(Entity, Entity)> = q_editables.iter().map(|(e, ..)| (e, root_of(e))).collect();
+    let kept: HashSet<Entity> = roots
+        .iter()
+        .filter(|(e, _)| q_editables.get(*e).is_ok_and(|(.., selected)| selected))
+        .map(|&(_, root)| root)
+        .collect();
+    let out: HashMap<Entity, IVec2> = roots
+        .iter()
+        .filter(|(_, root)| !kept.contains(root))
+        .filter_map(|&(e, root)| {
+            let (_, tf, ..) = q_editables.get(root).ok()?;
+            let cell = chunks.cell_of(tf.translation);
+            (!chunks.in_range(center, cell)).then_some((e, cell))
+        })
+        .collect();
+
+    // Only what can be saved goes; anything else stays spawned rather than being lost
+    let mut cells: HashMap<IVec2, (Vec<Entity>, Vec<SceneObject>)> = HashMap::new();
+    for (e, obj) in scene.collect(|e| out.contains_key(&e)) {
+        let (entities, objects) = cells.entry(out[&e]).or_default();
+        entities.push(e);
+        objects.push(obj);
+    }
+    let moved = !cells.is_empty();
+    for (cell, (entities, objects)) in cells {
+        match chunks.store(cell, objects) {
+            Ok(()) => {
+                for e in entities {
+                    commands.entity(e).despawn();
+                }
+            }
+            Err(e) => eprintln!("Couldn't stream out cell {cell}: {e}"),
+        }
+    }
+
+    let loaded: HashSet<IVec2> = (-chunks.radius..=chunks.radius)
+        .flat_map(|x| (-chunks.radius..=chunks.radius).map(move |z| center + IVec2::new(x, z)))
+        .collect();
+    if loaded != chunks.loaded {
+        chunks.loaded = loaded;
+    }
+    // Streaming isn't an edit
+    if moved && let Some(io) = io.as_mut() {
+        io.skip_change_tracking();
+    }
+}
+
+/// Spawn the stored objects of the cells in range (all of them once streaming is off).
+fn stream_in(
+    mut commands: Commands,
+    mut chunks: ResMut<SceneChunks>,
+    mut io: Option<ResMut<SceneIoState>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut cache: ResMut<AssetCache>,
+) {
+    if chunks.stored.is_empty() {
+        return;
+    }
+    let cells: Vec<IVec2> = chunks
+        .stored
+        .keys()
+        .copied()
+        .filter(|cell| !chunks.enabled || chunks.loaded.contains(cell))
+        .filter(|cell| !chunks.unreadable.contains(cell))
+        .collect();
+    if cells.is_empty() {
+        return;
+    }
+    let mut spawned = false;
+    for cell in cells {
+        let Some(stored) = chunks.stored.get(&cell) else {
+            continue;
+        };
+        match read_cell(&stored.file) {
+            Ok(objects) => {
+                chunks.stored.remove(&cell);
+                for obj in objects {
+                    spawn_scene_object(&mut commands, &mut meshes, &mut materials, &mut cache, obj);
+                }
+                spawned = true;
+            }
+            // Kept as it is, so saving it still tries its file
+            Err(e) => {
+                eprintln!("Couldn't stream in cell {cell}: {e}");
+                chunks.unreadable.insert(cell);
+            }
+        }
+    }
+    if spawned && let Some(io) = io.as_mut() {
+        io.skip_change_tracking();
+    }
+}
+
+fn streaming_window(
+    mut ctxs: EguiContexts,
+    edit_state: Res<SceneEditState>,
+    mut chunks: ResMut<SceneChunks>,
+) {
+    if !edit_state.open {
+        return;
+    }
+    let Ok(ctx) = ctxs.ctx_mut() else {
+        return;
+    };
+
+    let (mut enabled, mut size, mut radius) = (chunks.enabled, chunks.size, chunks.radius);
+    egui::Window::new("Streaming")
+        .default_open(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.checkbox(&mut enabled, "Stream in chunks").on_hover_text(
+                "Only spawn the cells around the camera target, and save the scene as chunks",
+            );
+            ui.add_enabled_ui(enabled, |ui| {
+                ui.add(
+                    egui::Slider::new(&mut size, 8.0..=256.0)
+                        .logarithmic(true)
+                        .text("Cell size (m)"),
+                );
+                ui.add(egui::Slider::new(&mut radius, 0..=8).text("Cells around target"));
+                ui.weak(format!(
+                    "{} cells in range, {} objects stored out of range",
+                    chunks.loaded.len(),
+                    chunks.stored_objects()
+                ));
+            });
+        });
+
+    if (enabled, size, radius) != (chunks.enabled, chunks.size, chunks.radius) {
+        let resized = size != chunks.size;
+        chunks.enabled = enabled;
+        chunks.size = size;
+        chunks.radius = radius;
+        if resized {
+            chunks.regroup();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn object(name: &str, at: [f32; 3], joined_to: Option<&str>) -> SceneObject {
+        let mut obj = json!({
+            "name": name,
+            "kind": "Cuboid",
+            "position": at,
+            "rotation_euler_deg": [0.0, 0.0, 0.0],
+            "scale": [1.0, 1.0, 1.0],
+            "color_rgba": [1.0, 1.0, 1.0, 1.0],
+            "metallic": 0.0,
+            "roughness": 0.5,
+            "collider": null,
+        });
+        if let Some(to) = joined_to {
+            obj["joint"] = json!({
+                "kind": "Hinge",
+                "to": to,
+                "anchor": [0.0, 0.0, 0.0],
+                "axis": [0.0, 1.0, 0.0],
+            });
+        }
+        serde_json::from_value(obj).unwrap()
+    }
+
+    fn streaming(size: f32, radius: i32) -> SceneChunks {
+        SceneChunks {
+            size,
+            radius,
+            ..default()
+        }
+    }
+
+    #[test]
+    fn cells_are_floored_on_the_ground_plane() {
+        let chunks = streaming(10.0, 1);
+        assert_eq!(chunks.cell_of(Vec3::new(0.0, 50.0, 0.0)), IVec2::ZERO);
+        assert_eq!(chunks.cell_of(Vec3::new(9.99, 0.0, 10.0)), IVec2::new(0, 1));
+        assert_eq!(
+            chunks.cell_of(Vec3::new(-0.01, 0.0, -10.0)),
+            IVec2::new(-1, -1)
+        );
+        assert_eq!(
+            chunks.cell_of(Vec3::new(-10.01, 0.0, 25.0)),
+            IVec2::new(-2, 2)
+        );
+    }
+
+    #[test]
+    fn tiny_cells_are_a_meter_wide() {
+        let chunks = streaming(0.0, 1);
+        assert_eq!(chunks.cell_of(Vec3::new(2.5, 0.0, -0.5)), IVec2::new(2, -1));
+    }
+
+    #[test]
+    fn range_is_a_square_around_the_center() {
+        let chunks = streaming(10.0, 1);
+        let center = IVec2::new(3, -2);
+        assert!(chunks.in_range(center, center));
+        assert!(chunks.in_range(center, IVec2::new(4, -3)));
+        assert!(!chunks.in_range(center, IVec2::new(5, -2)));
+        // A negative radius keeps just the center cell
+        assert!(streaming(10.0, -1).in_range(center, center));
+        assert!(!streaming(10.0, -1).in_range(center, IVec2::new(3, -1)));
+    }
+
+    #[test]
+    fn jointed_objects_follow_what_they_hang_from() {
+        let chunks = streaming(10.0, 1);
+        let objects = [
+            object("door", [25.0, 0.0, 0.0], Some("frame")),
+            object("frame", [15.0, 0.0, 0.0], Some("wall")),
+            object("wall", [5.0, 0.0, 5.0], None),
+            object("sign", [-5.0, 0.0, 0.0], Some("elsewhere")),
+            object("loop_a", [35.0, 0.0, 0.0], Some("loop_b")),
+            object("loop_b", [45.0, 0.0, 0.0], Some("loop_a")),
+        ];
+        let cells = chunks.cells_of(&objects);
+        assert_eq!(cells[..3], [IVec2::new(0, 0); 3]);
+        // Attached to nothing that's being placed: its own cell
+        assert_eq!(cells[3], IVec2::new(-1, 0));
+        // A cycle still ends, in one of its cells
+        assert!(cells[4] == IVec2::new(3, 0) || cells[4] == IVec2::new(4, 0));
+    }
+
+    #[test]
+    fn cell_files_sit_beside_the_scene() {
+        assert_eq!(
+            beside("levels/town.json", "town.json.cells/0_1.json"),
+            Path::new("levels")
+                .join("town.json.cells/0_1.json")
+                .to_string_lossy()
+        );
+        assert_eq!(beside("town.json", "a.json"), "a.json");
+    }
+}
@@ -0,0 +1,155 @@
+//! Copy and paste objects through the system clipboard, as the JSON a scene file keeps them in,
+//! to move props from one scene to another without editing the files by hand. Ctrl+C copies the
+//! selection and Ctrl+V spawns what's on the clipboard, selected, where it stood; objects cut
+//! out of a scene file's `objects` list paste too.
+
+use std::collections::{HashMap, HashSet};
+
+use bevy::prelude::*;
+use bevy_egui::EguiClipboard;
+
+use crate::asset_cache::AssetCache;
+use crate::inspector::{
+    Editable, SceneObject, SceneObjects, SelectObjectEvent, Selected, ToggleSelectionEvent,
+    copy_name, spawn_scene_object,
+};
+use crate::toasts::Toasts;
+
+/// Put the selection on the clipboard, or spawn what it holds.
+#[derive(Event, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ClipboardEvent {
+    Copy,
+    Paste,
+}
+
+pub struct ClipboardPlugin;
+impl Plugin for ClipboardPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AssetCache>()
+            .add_event::<ClipboardEvent>()
+            .add_event::<SelectObjectEvent>()
+            .add_event::<ToggleSelectionEvent>()
+            .add_systems(Update, (copy_selection, paste_objects));
+    }
+}
+
+fn copy_selection(
+    mut ev: EventReader<ClipboardEvent>,
+    clipboard: Option<ResMut<EguiClipboard>>,
+    mut toasts: Option<ResMut<Toasts>>,
+    scene: SceneObjects,
+    q_selected: Query<Entity, (With<Selected>, With<Editable>)>,
+) {
+    if !ev.read().any(|ev| *ev == ClipboardEvent::Copy) {
+        return;
+    }
+    let selected: HashSet<Entity> = q_selected.iter().collect();
+    if selected.is_empty() {
+        return;
+    }
+    let objects: Vec<SceneObject> = scene
+        .collect(|e| selected.contains(&e))
+        .into_iter()
+        .map(|(_, obj)| obj)
+        .collect();
+    if objects.is_empty() {
+        if let Some(toasts) = toasts.as_mut() {
+            toasts.warn("The selection can't be copied");
+        }
+        return;
+    }
+    let Some(mut clipboard) = clipboard else {
+        return;
+    };
+    match serde_json::to_string_pretty(&objects) {
+        Ok(json) => {
+            clipboard.set_text(&json);
+            if let Some(toasts) = toasts.as_mut() {
+                match objects.as_slice() {
+                    [obj] => toasts.info(format!("Copied {}", obj.name().unwrap_or("1 object"))),
+                    _ => toasts.info(format!("Copied {} objects", objects.len())),
+                }
+            }
+        }
+        Err(e) => {
+            if let Some(toasts) = toasts.as_mut() {
+                toasts.warn(format!("Couldn't copy the selection: {e}"));
+            }
+        }
+    }
+}
+
+/// The objects in `text`: a list of them, or a single one.
+fn parse_objects(text: &str) -> Option<Vec<SceneObject>> {
+    serde_json::from_str::<Vec<SceneObject>>(text)
+        .or_else(|_| serde_json::from_str::<SceneObject>(text).map(|obj| vec![obj]))
+        .ok()
+}
+
+fn paste_objects(
+    mut ev: EventReader<ClipboardEvent>,
+    mut commands: Commands,
+    clipboard: Option<ResMut<EguiClipboard>>,
+    mut toasts: Option<ResMut<Toasts>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut cache: ResMut<AssetCache>,
+    q_names: Query<&Name, With<Editable>>,
+    mut ev_select: EventWriter<SelectObjectEvent>,
+    mut ev_toggle: EventWriter<ToggleSelectionEvent>,
+) {
+    if !ev.read().any(|ev| *ev == ClipboardEvent::Paste) {
+        return;
+    }
+    let Some(text) = clipboard.and_then(|mut c| c.get_text()) else {
+        return;
+    };
+    let Some(mut objects) = parse_objects(&text).filter(|o| !o.is_empty()) else {
+        if let Some(toasts) = toasts.as_mut() {
+            toasts.warn("The clipboard doesn't hold scene objects");
+        }
+        return;
+    };
+
+    // Pasted next to the originals, they're numbered on like copies
+    let mut taken: HashSet<String> = q_names.iter().map(|n| n.as_str().to_string()).collect();
+    let count = objects.len();
+    let mut pasted = String::from("1 object");
+    let mut renamed: HashMap<String, String> = HashMap::new();
+    for obj in &mut objects {
+        if let Some(name) = obj
+            .name()
+            .filter(|n| taken.contains(*n))
+            .map(str::to_string)
+        {
+            let names: HashSet<&str> = taken.iter().map(String::as_str).collect();
+            let new_name = copy_name(&name, &names);
+            obj.rename(new_name.clone());
+            renamed.insert(name, new_name);
+        }
+        if let Some(name) = obj.name() {
+            taken.insert(name.to_string());
+            pasted = name.to_string();
+        }
+    }
+    for (i, mut obj) in objects.into_iter().enumerate() {
+        // Joints between pasted objects join the copies, not the originals
+        if let Some(to) = obj.joint_target().and_then(|to| renamed.get(to)) {
+            obj.retarget_joint(to.clone());
+        }
+        let e = spawn_scene_object(&mut commands, &mut meshes, &mut materials, &mut cache, obj);
+        // The pasted objects become the whole selection
+        if i == 0 {
+            ev_select.write(SelectObjectEvent(e));
+        } else {
+            ev_toggle.write(ToggleSelectionEvent(e));
+        }
+    }
+    if let Some(toasts) = toasts.as_mut() {
+        if count == 1 {
+            toasts.info(format!("Pasted {pasted}"));
+        } else {
+            toasts.info(format!("Pasted {count} objects"));
+        }
+    }
+}
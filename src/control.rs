@@ -0,0 +1,557 @@
+//! MIDI CC and OSC control of the post-processing parameters, for playing the look live.
+//!
+//! Every MIDI input port is opened at startup and OSC is read from UDP. In the **Controls**
+//! window, press **Learn** next to a parameter and move a knob (or send an OSC message) to bind
+//! it. Bindings are kept in `controls.toml`.
+
+use std::net::UdpSocket;
+use std::sync::Mutex;
+use std::sync::mpsc::{self, Receiver, Sender};
+
+use bevy::pbr::FogFalloff;
+use bevy::prelude::*;
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+use midir::MidiInput;
+use rosc::{OscPacket, OscType};
+use serde::{Deserialize, Serialize};
+
+use crate::SceneEditState;
+use crate::post::PostEffects;
+use crate::scene_io;
+
+pub const CONTROLS_FILE: &str = "controls.toml";
+
+/// A physical control: a MIDI controller number on a channel, or an OSC address.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ControlSource {
+    /// Channel 1-16, as printed on controllers
+    Midi {
+        channel: u8,
+        cc: u8,
+    },
+    Osc {
+        address: String,
+    },
+}
+
+impl std::fmt::Display for ControlSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ControlSource::Midi { channel, cc } => write!(f, "MIDI ch {channel} CC {cc}"),
+            ControlSource::Osc { address } => write!(f, "OSC {address}"),
+        }
+    }
+}
+
+/// A parameter a control can drive.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ControlTarget {
+    ChromaIntensity,
+    CrtIntensity,
+    CrtScanlineFreq,
+    CrtLineIntensity,
+    GradientStrength,
+    /// Hue of the top-right gradient color
+    GradientTopRightHue,
+    /// Hue of the bottom-left gradient color
+    GradientBottomLeftHue,
+    LutStrength,
+    FlareIntensity,
+    FogDensity,
+}
+
+impl ControlTarget {
+    pub const ALL: [ControlTarget; 10] = [
+        ControlTarget::ChromaIntensity,
+        ControlTarget::CrtIntensity,
+        ControlTarget::CrtScanlineFreq,
+        ControlTarget::CrtLineIntensity,
+        ControlTarget::GradientStrength,
+        ControlTarget::GradientTopRightHue,
+        ControlTarget::GradientBottomLeftHue,
+        ControlTarget::LutStrength,
+        ControlTarget::FlareIntensity,
+        ControlTarget::FogDensity,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ControlTarget::ChromaIntensity => "Chroma intensity",
+            ControlTarget::CrtIntensity => "CRT intensity",
+            ControlTarget::CrtScanlineFreq => "CRT scanline frequency",
+            ControlTarget::CrtLineIntensity => "CRT line intensity",
+            ControlTarget::GradientStrength => "Gradient strength",
+            ControlTarget::GradientTopRightHue => "Gradient top-right hue",
+            ControlTarget::GradientBottomLeftHue => "Gradient bottom-left hue",
+            ControlTarget::LutStrength => "LUT strength",
+            ControlTarget::FlareIntensity => "Lens flare glow",
+            ControlTarget::FogDensity => "Fog density",
+        }
+    }
+
+    /// Range a fresh binding sweeps, matching the Effect settings sliders
+    pub fn default_range(self) -> (f32, f32) {
+        match self {
+            ControlTarget::ChromaIntensity => (0.0, 0.05),
+            ControlTarget::CrtIntensity => (0.0, 0.5),
+            ControlTarget::CrtScanlineFreq => (50.0, 500.0),
+            ControlTarget::FlareIntensity => (0.0, 2.0),
+            ControlTarget::GradientTopRightHue | ControlTarget::GradientBottomLeftHue => {
+                (0.0, 360.0)
+            }
+            ControlTarget::FogDensity => (0.0, 0.2),
+            _ => (0.0, 1.0),
+        }
+    }
+}
+
+/// One control bound to one parameter; the control's 0..1 value maps onto `min..max`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ControlBinding {
+    pub source: ControlSource,
+    pub target: ControlTarget,
+    pub min: f32,
+    pub max: f32,
+}
+
+/// Bindings as written to `controls.toml`.
+#[derive(Resource, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ControlMappings {
+    pub bindings: Vec<ControlBinding>,
+}
+
+/// Learn mode and the latest control seen, for the Controls window.
+#[derive(Resource, Default)]
+pub struct ControlState {
+    /// Parameter waiting for the next control to move
+    pub learning: Option<ControlTarget>,
+    pub last: Option<(ControlSource, f32)>,
+}
+
+/// A control moved, with its value scaled to 0..1.
+struct ControlMessage {
+    source: ControlSource,
+    value: f32,
+}
+
+#[derive(Resource)]
+struct ControlInbox(Mutex<Receiver<ControlMessage>>);
+
+/// Opens every MIDI input and listens for OSC on `osc_addr` (all interfaces by default, so a
+/// tablet on the same network can send).
+pub struct ControlPlugin {
+    pub osc_addr: String,
+}
+
+impl Default for ControlPlugin {
+    fn default() -> Self {
+        Self {
+            osc_addr: "0.0.0.0:9000".into(),
+        }
+    }
+}
+
+impl Plugin for ControlPlugin {
+    fn build(&self, app: &mut App) {
+        let (tx, rx) = mpsc::channel();
+        let midi_tx = tx.clone();
+        std::thread::spawn(move || listen_midi(midi_tx));
+        let addr = self.osc_addr.clone();
+        std::thread::spawn(move || listen_osc(&addr, tx));
+
+        app.init_resource::<SceneEditState>()
+            .insert_resource(load_mappings(CONTROLS_FILE))
+            .init_resource::<ControlState>()
+            .insert_resource(ControlInbox(Mutex::new(rx)))
+            .add_systems(Update, (apply_controls, save_mappings).chain())
+            .add_systems(EguiPrimaryContextPass, controls_window);
+    }
+}
+
+fn load_mappings(path: &str) -> ControlMappings {
+    let Some(text) = scene_io::read_text(path) else {
+        return ControlMappings::default();
+    };
+    toml::from_str(&text).unwrap_or_else(|e| {
+        eprintln!("Controls: ignoring invalid {path}: {e}");
+        ControlMappings::default()
+    })
+}
+
+fn save_mappings(mappings: Res<ControlMappings>) {
+    // Skip the insert at startup, there's nothing new to write
+    if !mappings.is_changed() || mappings.is_added() {
+        return;
+    }
+    match toml::to_string_pretty(&*mappings) {
+        Ok(text) => {
+            if let Err(e) = scene_io::write_text(CONTROLS_FILE, &text) {
+                eprintln!("Controls: cannot write {CONTROLS_FILE}: {e}");
+            }
+        }
+        Err(e) => eprintln!("Controls: serialize error: {e}"),
+    }
+}
+
+/// Connect to every MIDI input port and forward control changes. Ports plugged in later need a
+/// restart.
+fn listen_midi(tx: Sender<ControlMessage>) {
+    let port_count = match MidiInput::new("tunable_world") {
+        Ok(midi) => midi.port_count(),
+        Err(e) => {
+            eprintln!("Controls: no MIDI: {e}");
+            return;
+        }
+    };
+    // `connect` consumes the `MidiInput`, so each port gets its own
+    let mut connections = Vec::new();
+    for i in 0..port_count {
+        let Ok(midi) = MidiInput::new("tunable_world") else {
+            continue;
+        };
+        let Some(port) = midi.ports().into_iter().nth(i) else {
+            continue;
+        };
+        let name = midi.port_name(&port).unwrap_or_default();
+        let tx = tx.clone();
+        let connection = midi.connect(
+            &port,
+            "tunable_world-in",
+            move |_, bytes, _| {
+                // Control change: 0xBn controller value
+                if let &[status, cc, value] = bytes
+                    && status & 0xF0 == 0xB0
+                {
+                    let _ = tx.send(ControlMessage {
+                        source: ControlSource::Midi {
+                            channel: (status & 0x0F) + 1,
+                            cc,
+                        },
+                        value: f32::from(value) / 127.0,
+                    });
+                }
+            },
+            (),
+        );
+        match connection {
+            Ok(c) => {
+                eprintln!("Controls: listening to MIDI '{name}'");
+                connections.push(c);
+            }
+            Err(e) => eprintln!("Controls: cannot open MIDI '{name}': {e}"),
+        }
+    }
+    if connections.is_empty() {
+        return;
+    }
+    // The connections close when dropped
+    loop {
+        std::thread::park();
+    }
+}
+
+fn listen_osc(addr: &str, tx: Sender<ControlMessage>) {
+    let socket = match UdpSocket::bind(addr) {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("Controls: cannot listen for OSC on {addr}: {e}");
+            return;
+        }
+    };
+    eprintln!("Controls: OSC on udp://{addr}");
+    let mut buf = [0u8; rosc::decoder::MTU];
+    while let Ok(len) = socket.recv(&mut buf) {
+        if let Ok((_, packet)) = rosc::decoder::decode_udp(&buf[..len]) {
+            forward_osc(packet, &tx);
+        }
+    }
+}
+
+/// Floats are taken as 0..1 (the TouchOSC convention); integers as MIDI-style 0..127.
+fn forward_osc(packet: OscPacket, tx: &Sender<ControlMessage>) {
+    match packet {
+        OscPacket::Message(msg) => {
+            let value = match msg.args.first() {
+                Some(OscType::Float(v)) => *v,
+                Some(OscType::Double(v)) => *v as f32,
+                Some(OscType::Int(v)) => *v as f32 / 127.0,
+                Some(OscType::Bool(v)) => f32::from(u8::from(*v)),
+                _ => return,
+            };
+            let _ = tx.send(ControlMessage {
+                source: ControlSource::Osc { address: msg.addr },
+                value: value.clamp(0.0, 1.0),
+            });
+        }
+        OscPacket::Bundle(bundle) => {
+            for packet in bundle.content {
+                forward_osc(packet, tx);
+            }
+        }
+    }
+}
+
+#[cfg(feature = "gradient")]
+fn with_hue(color: Vec4, hue: f32) -> Vec4 {
+    let hsva = Hsva::from(Srgba::rgb(color.x, color.y, color.z));
+    // Grey has no hue to turn, so give it some color first
+    let hsva = Hsva {
+        saturation: hsva.saturation.max(0.5),
+        value: hsva.value.max(0.5),
+        ..hsva
+    };
+    let rgb = Srgba::from(hsva.with_hue(hue.rem_euclid(360.0)));
+    Vec4::new(rgb.red, rgb.green, rgb.blue, color.w)
+}
+
+/// Parameters whose pass is compiled out (or fog while it's off) are left alone.
+#[allow(unreachable_patterns)]
+fn set_target(effects: &mut PostEffects, target: ControlTarget, v: f32) {
+    match target {
+        #[cfg(feature = "chroma")]
+        ControlTarget::ChromaIntensity => {
+            for mut s in &mut effects.chroma {
+                s.intensity = v;
+            }
+        }
+        #[cfg(feature = "crt")]
+        ControlTarget::CrtIntensity => {
+            for mut s in &mut effects.crt {
+                s.intensity = v;
+            }
+        }
+        #[cfg(feature = "crt")]
+        ControlTarget::CrtScanlineFreq => {
+            for mut s in &mut effects.crt {
+                s.scanline_freq = v;
+            }
+        }
+        #[cfg(feature = "crt")]
+        ControlTarget::CrtLineIntensity => {
+            for mut s in &mut effects.crt {
+                s.line_intensity = v;
+            }
+        }
+        #[cfg(feature = "gradient")]
+        ControlTarget::GradientStrength => {
+            for mut s in &mut effects.gradient {
+                s.strength = v;
+            }
+        }
+        #[cfg(feature = "gradient")]
+        ControlTarget::GradientTopRightHue => {
+            for mut s in &mut effects.gradient {
+                s.color_top_right = with_hue(s.color_top_right, v);
+            }
+        }
+        #[cfg(feature = "gradient")]
+        ControlTarget::GradientBottomLeftHue => {
+            for mut s in &mut effects.gradient {
+                s.color_bottom_left = with_hue(s.color_bottom_left, v);
+            }
+        }
+        #[cfg(feature = "lut")]
+        ControlTarget::LutStrength => {
+            for mut s in &mut effects.lut {
+                s.strength = v;
+            }
+        }
+        ControlTarget::FlareIntensity => {
+            for mut s in &mut effects.flare {
+                s.intensity = v;
+            }
+        }
+        ControlTarget::FogDensity => {
+            for mut fog in &mut effects.fog {
+                fog.falloff = FogFalloff::Exponential { density: v };
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Drain the control messages: bind the first one while learning, apply the rest.
+fn apply_controls(
+    inbox: Res<ControlInbox>,
+    mut mappings: ResMut<ControlMappings>,
+    mut state: ResMut<ControlState>,
+    mut effects: PostEffects,
+) {
+    let inbox = inbox.0.lock().expect("control inbox");
+    for msg in inbox.try_iter() {
+        if let Some(target) = state.learning.take() {
+            // One control per parameter, and a control drives a single parameter
+            mappings
+                .bindings
+                .retain(|b| b.target != target && b.source != msg.source);
+            let (min, max) = target.default_range();
+            mappings.bindings.push(ControlBinding {
+                source: msg.source.clone(),
+                target,
+                min,
+                max,
+            });
+        }
+        for binding in mappings.bindings.iter().filter(|b| b.source == msg.source) {
+            set_target(
+                &mut effects,
+                binding.target,
+                binding.min + (binding.max - binding.min) * msg.value,
+            );
+        }
+        state.last = Some((msg.source, msg.value));
+    }
+}
+
+fn controls_window(
+    mut ctxs: EguiContexts,
+    edit_state: Res<SceneEditState>,
+    mut mappings: ResMut<ControlMappings>,
+    mut state: ResMut<ControlState>,
+) {
+    if !edit_state.open {
+        return;
+    }
+    let Ok(ctx) = ctxs.ctx_mut() else {
+        return;
+    };
+    egui::Window::new("Controls")
+        .default_open(false)
+        .show(ctx, |ui| {
+            match &state.last {
+                Some((source, value)) => ui.weak(format!("Last: {source} = {value:.2}")),
+                None => ui.weak("No MIDI or OSC input yet"),
+            };
+            ui.separator();
+
+            egui::Grid::new("control_bindings")
+                .num_columns(4)
+                .striped(true)
+                .show(ui, |ui| {
+                    for target in ControlTarget::ALL {
+                        ui.label(target.label());
+                        let learning = state.learning == Some(target);
+                        let index = mappings.bindings.iter().position(|b| b.target == target);
+                        match (learning, index) {
+                            (true, _) => ui.strong("Move a control..."),
+                            (false, Some(i)) => ui.label(mappings.bindings[i].source.to_string()),
+                            (false, None) => ui.weak("Unbound"),
+                        };
+
+                        ui.horizontal(|ui| {
+                            if ui.selectable_label(learning, "Learn").clicked() {
+                                state.learning = (!learning).then_some(target);
+                            }
+                            if ui
+                                .add_enabled(index.is_some(), egui::Button::new("Clear"))
+                                .clicked()
+                                && let Some(i) = index
+                            {
+                                mappings.bindings.remove(i);
+                            }
+                        });
+
+                        // Only touch the resource on edits, a change writes the file
+                        if let Some(i) = index {
+                            let (mut min, mut max) =
+                                (mappings.bindings[i].min, mappings.bindings[i].max);
+                            let speed = (target.default_range().1 / 200.0).max(0.001);
+                            let changed = ui
+                                .horizontal(|ui| {
+                                    let a = ui.add(egui::DragValue::new(&mut min).speed(speed));
+                                    ui.label("to");
+                                    let b = ui.add(egui::DragValue::new(&mut max).speed(speed));
+                                    a.changed() || b.changed()
+                                })
+                                .inner;
+                            if changed {
+                                mappings.bindings[i].min = min;
+                                mappings.bindings[i].max = max;
+                            }
+                        } else {
+                            ui.label("");
+                        }
+                        ui.end_row();
+                    }
+                });
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use rosc::{OscBundle, OscMessage, OscTime};
+
+    use super::*;
+
+    fn message(addr: &str, args: Vec<OscType>) -> OscPacket {
+        OscPacket::Message(OscMessage {
+            addr: addr.into(),
+            args,
+        })
+    }
+
+    fn forwarded(packet: OscPacket) -> Vec<(String, f32)> {
+        let (tx, rx) = mpsc::channel();
+        forward_osc(packet, &tx);
+        drop(tx);
+        rx.iter()
+            .map(|msg| match msg.source {
+                ControlSource::Osc { address } => (address, msg.value),
+                ControlSource::Midi { .. } => unreachable!(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn values_are_scaled_and_clamped() {
+        let cases = [
+            (OscType::Float(0.25), 0.25),
+            (OscType::Double(0.5), 0.5),
+            (OscType::Int(127), 1.0),
+            (OscType::Bool(true), 1.0),
+            (OscType::Bool(false), 0.0),
+            (OscType::Float(3.0), 1.0),
+            (OscType::Int(-5), 0.0),
+        ];
+        for (arg, value) in cases {
+            let sent = forwarded(message("/fader1", vec![arg.clone()]));
+            assert_eq!(sent, [("/fader1".to_string(), value)], "{arg:?}");
+        }
+    }
+
+    #[test]
+    fn only_the_first_argument_counts() {
+        let sent = forwarded(message(
+            "/xy",
+            vec![OscType::Float(0.1), OscType::Float(0.9)],
+        ));
+        assert_eq!(sent, [("/xy".to_string(), 0.1)]);
+    }
+
+    #[test]
+    fn messages_without_a_number_are_ignored() {
+        assert!(forwarded(message("/ping", Vec::new())).is_empty());
+        assert!(forwarded(message("/name", vec![OscType::String("a".into())])).is_empty());
+    }
+
+    #[test]
+    fn bundles_are_unpacked() {
+        let inner = OscPacket::Bundle(OscBundle {
+            timetag: OscTime {
+                seconds: 0,
+                fractional: 1,
+            },
+            content: vec![message("/b", vec![OscType::Float(0.5)])],
+        });
+        let outer = OscPacket::Bundle(OscBundle {
+            timetag: OscTime {
+                seconds: 0,
+                fractional: 1,
+            },
+            content: vec![message("/a", vec![OscType::Int(0)]), inner],
+        });
+        let sent = forwarded(outer);
+        assert_eq!(sent, [("/a".to_string(), 0.0), ("/b".to_string(), 0.5)]);
+    }
+}
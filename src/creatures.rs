@@ -0,0 +1,416 @@
+//! Ambient creatures: birds that perch on the highest objects in their area and flutter between
+//! them, and fish that school around inside it (put the box over a water slab). Each flock comes
+//! from a spawner object whose box (a unit cube, scaled and turned) is the area it keeps to; the
+//! creatures themselves aren't scene objects and are rebuilt whenever the spawner changes.
+
+use std::collections::HashMap;
+
+use bevy::math::Affine3A;
+use bevy::prelude::*;
+use bevy::render::primitives::Aabb;
+use bevy_egui::egui;
+use serde::{Deserialize, Serialize};
+
+use crate::SceneEditState;
+use crate::asset_cache::{AssetCache, PlainMaterial};
+use crate::inspector::{Editable, EditableMesh, Selected, SpawnKind, aabb_world};
+use crate::randomize::Rng;
+
+/// How quickly creatures turn towards where they want to go (per second)
+const STEER_RATE: f32 = 3.0;
+/// Fish keep this far apart
+const FISH_SPACING: f32 = 0.3;
+/// Seconds a bird sits before flying on
+const PERCH_SECS: (f32, f32) = (2.0, 7.0);
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum CreatureKind {
+    /// Perch on the highest objects in the box and flutter between them
+    #[default]
+    Birds,
+    /// School around inside the box
+    Fish,
+}
+
+impl CreatureKind {
+    pub const ALL: [Self; 2] = [Self::Birds, Self::Fish];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Birds => "Birds",
+            Self::Fish => "Fish",
+        }
+    }
+}
+
+/// Persisted parameters of a creature spawner.
+/// The area the flock keeps to is the entity's Transform (a unit cube, scaled/rotated).
+#[derive(Component, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CreatureSpawner {
+    pub kind: CreatureKind,
+    pub count: u32,
+    /// Cruising speed, in units per second
+    pub speed: f32,
+    pub color: [f32; 3], // sRGB
+}
+
+impl Default for CreatureSpawner {
+    fn default() -> Self {
+        Self {
+            kind: CreatureKind::Birds,
+            count: 6,
+            speed: 1.5,
+            color: [0.3, 0.27, 0.25],
+        }
+    }
+}
+
+/// One member of a spawner's flock.
+#[derive(Component)]
+struct Creature {
+    spawner: Entity,
+    kind: CreatureKind,
+    velocity: Vec3,
+    state: CreatureState,
+    rng: Rng,
+    /// Wing beat / tail wiggle clock
+    phase: f32,
+}
+
+enum CreatureState {
+    /// Sitting still until the time runs out
+    Perched {
+        left: f32,
+    },
+    /// On the way to a perch
+    Flying {
+        to: Vec3,
+    },
+    Swimming,
+}
+
+pub struct CreaturesPlugin;
+impl Plugin for CreaturesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AssetCache>().add_systems(
+            Update,
+            (spawn_flocks, steer_creatures, draw_spawner_gizmos).chain(),
+        );
+    }
+}
+
+/// Spawn an editable creature spawner (no mesh; drawn as a gizmo box in the editor).
+pub fn spawn_creature_spawner(
+    commands: &mut Commands,
+    tf: Transform,
+    spawner: CreatureSpawner,
+) -> Entity {
+    commands
+        .spawn((
+            Editable,
+            Name::new(spawner.kind.label()),
+            tf,
+            EditableMesh {
+                kind: SpawnKind::Creatures,
+                collider: None,
+            },
+            spawner,
+        ))
+        .id()
+}
+
+/// Scale of a creature's mesh (a unit cube for birds, a unit sphere for fish).
+fn body_scale(kind: CreatureKind) -> Vec3 {
+    match kind {
+        // Wings out, seen mostly from above
+        CreatureKind::Birds => Vec3::new(0.26, 0.03, 0.1),
+        CreatureKind::Fish => Vec3::new(0.05, 0.07, 0.16),
+    }
+}
+
+/// Rebuild the flock of every new or edited spawner, and clear away flocks whose spawner is gone.
+fn spawn_flocks(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut cache: ResMut<AssetCache>,
+    // The Transform, since a spawner placed this frame has no GlobalTransform yet
+    q_changed: Query<(Entity, &Transform, &CreatureSpawner), Changed<CreatureSpawner>>,
+    q_spawners: Query<(), With<CreatureSpawner>>,
+    q_creatures: Query<(Entity, &Creature)>,
+) {
+    for (e, creature) in &q_creatures {
+        if !q_spawners.contains(creature.spawner) || q_changed.contains(creature.spawner) {
+            commands.entity(e).despawn();
+        }
+    }
+
+    for (spawner_e, spawner_tf, spawner) in &q_changed {
+        let [r, g, b] = spawner.color;
+        let material = cache.material(
+            &mut materials,
+            PlainMaterial {
+                base_color: Color::srgb(r, g, b),
+                metallic: 0.0,
+                roughness: 0.8,
+                emissive: LinearRgba::BLACK,
+            },
+        );
+        let mesh = cache.mesh(
+            &mut meshes,
+            match spawner.kind {
+                CreatureKind::Birds => SpawnKind::Cuboid,
+                CreatureKind::Fish => SpawnKind::Sphere,
+            },
+        );
+        let mut rng = Rng(spawner_e.to_bits());
+        for _ in 0..spawner.count {
+            // Anywhere inside the box, clear of its walls
+            let local = Vec3::new(rng.signed(), rng.signed(), rng.signed()) * 0.4;
+            let at = spawner_tf.transform_point(local);
+            let heading = Vec3::new(rng.signed(), 0.0, rng.signed()).normalize_or(Vec3::X);
+            let state = match spawner.kind {
+                // Birds start in the air and settle on the first perch they find
+                CreatureKind::Birds => CreatureState::Perched { left: 0.0 },
+                CreatureKind::Fish => CreatureState::Swimming,
+            };
+            commands.spawn((
+                Mesh3d(mesh.clone()),
+                MeshMaterial3d(material.clone()),
+                Transform::from_translation(at)
+                    .looking_to(heading, Vec3::Y)
+                    .with_scale(body_scale(spawner.kind)),
+                Creature {
+                    spawner: spawner_e,
+                    kind: spawner.kind,
+                    velocity: heading * spawner.speed,
+                    state,
+                    rng: Rng(rng.next_u64()),
+                    phase: rng.unit() * std::f32::consts::TAU,
+                },
+                Name::new(spawner.kind.label()),
+            ));
+        }
+    }
+}
+
+/// A spawner's area, for one frame of steering.
+struct Flock {
+    to_world: Affine3A,
+    to_local: Affine3A,
+    speed: f32,
+    /// Tops of the highest objects in the area, for birds to land on
+    perches: Vec<Vec3>,
+}
+
+impl Flock {
+    /// A random point in the upper half of the area, clear of its walls.
+    fn random_point_above(&self, rng: &mut Rng) -> Vec3 {
+        let local = Vec3::new(rng.signed() * 0.4, rng.unit() * 0.4, rng.signed() * 0.4);
+        self.to_world.transform_point3(local)
+    }
+
+    /// Somewhere for a bird to land, other than `from`.
+    fn next_perch(&self, rng: &mut Rng, from: Vec3) -> Vec3 {
+        let others: Vec<Vec3> = self
+            .perches
+            .iter()
+            .copied()
+            .filter(|p| p.distance_squared(from) > 0.25)
+            .collect();
+        if others.is_empty() {
+            // Nothing to land on: hover somewhere in the air for a while instead
+            return self.random_point_above(rng);
+        }
+        others[(rng.next_u64() % others.len() as u64) as usize]
+    }
+
+    /// Steering back inside when near a wall.
+    fn containment(&self, at: Vec3) -> Vec3 {
+        let local = self.to_local.transform_point3(at);
+        let outside = (local.abs() - Vec3::splat(0.4)).max(Vec3::ZERO) * local.signum();
+        if outside == Vec3::ZERO {
+            return Vec3::ZERO;
+        }
+        -self.to_world.transform_vector3(outside).normalize_or_zero() * 2.0
+    }
+}
+
+fn steer_creatures(
+    time: Res<Time>,
+    q_spawners: Query<(Entity, &GlobalTransform, &CreatureSpawner)>,
+    q_perches: Query<(&GlobalTransform, &Aabb), (With<Editable>, Without<CreatureSpawner>)>,
+    mut q_creatures: Query<(&mut Creature, &mut Transform)>,
+) {
+    let dt = time.delta_secs();
+    if dt <= 0.0 {
+        return;
+    }
+
+    let mut flocks = HashMap::new();
+    for (e, gt, spawner) in &q_spawners {
+        let to_world = gt.affine();
+        let to_local = to_world.inverse();
+        let mut perches = Vec::new();
+        if spawner.kind == CreatureKind::Birds {
+            let mut tops: Vec<Vec3> = q_perches
+                .iter()
+                .map(|(gt, aabb)| {
+                    let world = aabb_world(*aabb, gt);
+                    Vec3::from(world.center) + Vec3::Y * world.half_extents.y
+                })
+                .filter(|top| {
+                    let local = to_local.transform_point3(*top);
+                    local.abs().max_element() <= 0.5
+                })
+                .collect();
+            // The highest half, so they keep to terraces and roofs rather than the floor
+            tops.sort_by(|a, b| b.y.total_cmp(&a.y));
+            tops.truncate(tops.len().div_ceil(2));
+            perches = tops;
+        }
+        flocks.insert(
+            e,
+            Flock {
+                to_world,
+                to_local,
+                speed: spawner.speed,
+                perches,
+            },
+        );
+    }
+
+    // Where everyone is, for fish to keep their distance
+    let positions: Vec<(Entity, Vec3)> = q_creatures
+        .iter()
+        .map(|(c, tf)| (c.spawner, tf.translation))
+        .collect();
+
+    for (mut creature, mut tf) in &mut q_creatures {
+        let Some(flock) = flocks.get(&creature.spawner) else {
+            continue;
+        };
+        let creature = &mut *creature;
+        let at = tf.translation;
+        creature.phase += dt;
+
+        let desired = match creature.state {
+            CreatureState::Perched { ref mut left } => {
+                *left -= dt;
+                if *left <= 0.0 {
+                    let to = flock.next_perch(&mut creature.rng, at);
+                    creature.state = CreatureState::Flying { to };
+                }
+                Vec3::ZERO
+            }
+            CreatureState::Flying { to } => {
+                let offset = to - at;
+                let dist = offset.length();
+                if dist < 0.05 {
+                    let (min, max) = PERCH_SECS;
+                    creature.state = CreatureState::Perched {
+                        left: min + creature.rng.unit() * (max - min),
+                    };
+                    creature.velocity = Vec3::ZERO;
+                    tf.translation = to;
+                    tf.scale = body_scale(creature.kind);
+                    continue;
+                }
+                // Climb over what's in the way on longer hops, slow down to land
+                let lift = Vec3::Y * (dist - 1.0).clamp(0.0, 1.0);
+                (offset / dist + lift).normalize_or_zero() * flock.speed * (dist / 0.5).min(1.0)
+            }
+            CreatureState::Swimming => {
+                let heading = creature.velocity.normalize_or(Vec3::X);
+                let wander = Vec3::new(creature.rng.signed(), 0.0, creature.rng.signed()) * 0.6;
+                let apart: Vec3 = positions
+                    .iter()
+                    .filter(|(spawner, _)| *spawner == creature.spawner)
+                    .map(|(_, p)| at - *p)
+                    .filter(|d| *d != Vec3::ZERO && d.length_squared() < FISH_SPACING.powi(2))
+                    .map(|d| d.normalize() * (FISH_SPACING - d.length()) / FISH_SPACING)
+                    .sum();
+                let mut dir = heading + wander + apart * 2.0 + flock.containment(at);
+                // Mostly level
+                dir.y *= 0.3;
+                dir.normalize_or_zero() * flock.speed
+            }
+        };
+
+        let blend = (STEER_RATE * dt).min(1.0);
+        creature.velocity = creature.velocity.lerp(desired, blend);
+        tf.translation += creature.velocity * dt;
+
+        let horizontal = Vec3::new(creature.velocity.x, 0.0, creature.velocity.z);
+        if horizontal.length_squared() > 1e-6 {
+            let facing = match creature.kind {
+                CreatureKind::Birds => horizontal,
+                CreatureKind::Fish => creature.velocity,
+            };
+            tf.look_to(facing, Vec3::Y);
+        }
+
+        // Wing beats while flying, a tail wiggle while swimming
+        let base = body_scale(creature.kind);
+        tf.scale = match (creature.kind, &creature.state) {
+            (CreatureKind::Birds, CreatureState::Flying { .. }) => {
+                let beat = (creature.phase * 18.0).sin().abs();
+                base * Vec3::new(0.5 + 0.5 * beat, 1.0, 1.0)
+            }
+            (CreatureKind::Fish, _) => {
+                let wiggle = (creature.phase * 8.0).sin() * 0.15;
+                base * Vec3::new(1.0 + wiggle, 1.0, 1.0)
+            }
+            _ => base,
+        };
+    }
+}
+
+/// Spawners have no mesh; show their box in the editor.
+fn draw_spawner_gizmos(
+    mut gizmos: Gizmos,
+    edit_state: Res<SceneEditState>,
+    q: Query<(&GlobalTransform, &CreatureSpawner, Has<Selected>), With<Editable>>,
+) {
+    if !edit_state.open {
+        return;
+    }
+    for (gt, spawner, selected) in &q {
+        let color = if selected {
+            Color::srgb(1.0, 0.85, 0.2)
+        } else {
+            match spawner.kind {
+                CreatureKind::Birds => Color::srgba(0.9, 0.75, 0.5, 0.6),
+                CreatureKind::Fish => Color::srgba(0.4, 0.7, 0.95, 0.6),
+            }
+        };
+        gizmos.cuboid(gt.compute_transform(), color);
+        // Click target: spawners are picked by their center so objects inside stay clickable
+        gizmos.sphere(Isometry3d::from_translation(gt.translation()), 0.15, color);
+    }
+}
+
+/// Inspector section for creature spawner parameters.
+pub fn creature_spawner_inspector_ui(ui: &mut egui::Ui, mut spawner: Mut<CreatureSpawner>) -> bool {
+    ui.heading("Creatures");
+
+    let mut s = *spawner;
+    ui.horizontal(|ui| {
+        for kind in CreatureKind::ALL {
+            ui.selectable_value(&mut s.kind, kind, kind.label());
+        }
+    });
+    ui.add(egui::Slider::new(&mut s.count, 1..=64).text("Count"));
+    ui.add(egui::Slider::new(&mut s.speed, 0.2..=6.0).text("Speed"));
+    ui.horizontal(|ui| {
+        ui.label("Color");
+        ui.color_edit_button_rgb(&mut s.color);
+    });
+    ui.label(match s.kind {
+        CreatureKind::Birds => "Birds land on the highest objects inside the box.",
+        CreatureKind::Fish => "Fish swim inside the box; fit it to the water.",
+    });
+
+    // Only touch the component on edits, since any change rebuilds the flock
+    spawner.set_if_neq(s)
+}
@@ -0,0 +1,202 @@
+//! Object kinds registered by downstream code next to the built-in ones (a pickup, a spawn
+//! point, an emitter), so new gameplay objects don't each need a `SpawnKind` variant and
+//! special cases all over `inspector.rs`.
+//!
+//! A kind brings its unit mesh, a block of JSON parameters saved with each object, an optional
+//! inspector section for them, and a hook that turns them into components:
+//!
+//! ```ignore
+//! app.register_spawn_kind(CustomKind {
+//!     name: "pickup",
+//!     label: "Pickup",
+//!     mesh: || Mesh::from(Torus::new(0.25, 0.5)),
+//!     default_data: || serde_json::json!({ "value": 10 }),
+//!     inspector_ui: Some(|ui, data| {
+//!         let mut value = data["value"].as_u64().unwrap_or(0);
+//!         let changed = ui.add(egui::Slider::new(&mut value, 0..=100).text("Value")).changed();
+//!         data["value"] = value.into();
+//!         changed
+//!     }),
+//!     apply: Some(|entity, data| {
+//!         entity.insert(Pickup(data["value"].as_u64().unwrap_or(0) as u32));
+//!     }),
+//! });
+//! ```
+//!
+//! New objects of the kind show up under *Create New*; saved scenes refer to it by `name`.
+
+use std::sync::Mutex;
+
+use bevy::prelude::*;
+use bevy_egui::egui;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::asset_cache::AssetCache;
+use crate::inspector::{EditableMesh, SpawnKind};
+
+/// A registered kind, by name. Cheap to copy, so `SpawnKind` stays `Copy`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct CustomKindId(&'static str);
+
+impl CustomKindId {
+    pub fn new(name: &str) -> Self {
+        Self(intern(name))
+    }
+
+    pub fn name(self) -> &'static str {
+        self.0
+    }
+}
+
+/// Names read from scene files are leaked once each, not once per object.
+fn intern(name: &str) -> &'static str {
+    static NAMES: Mutex<Vec<&'static str>> = Mutex::new(Vec::new());
+    let mut names = NAMES.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(&known) = names.iter().find(|n| **n == name) {
+        return known;
+    }
+    let leaked: &'static str = Box::leak(name.to_owned().into_boxed_str());
+    names.push(leaked);
+    leaked
+}
+
+impl Serialize for CustomKindId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for CustomKindId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Ok(Self::new(&name))
+    }
+}
+
+/// Callbacks describing a custom kind.
+#[derive(Clone, Copy)]
+pub struct CustomKind {
+    /// Written to scene files; keep it stable
+    pub name: &'static str,
+    /// Shown under *Create New*
+    pub label: &'static str,
+    /// Unit-sized mesh, shared by every object of the kind (the collider is a unit box)
+    pub mesh: fn() -> Mesh,
+    /// Parameters of a new object
+    pub default_data: fn() -> serde_json::Value,
+    /// Inspector section editing the parameters; returns whether they changed
+    pub inspector_ui: Option<fn(&mut egui::Ui, &mut serde_json::Value) -> bool>,
+    /// Turn the parameters into components; run on spawn, on load and after every edit
+    pub apply: Option<fn(&mut EntityCommands, &serde_json::Value)>,
+}
+
+impl CustomKind {
+    pub fn id(&self) -> CustomKindId {
+        CustomKindId::new(self.name)
+    }
+}
+
+/// Every registered custom kind, in registration order.
+#[derive(Resource, Default)]
+pub struct SpawnKinds {
+    kinds: Vec<CustomKind>,
+}
+
+impl SpawnKinds {
+    /// Add a kind, replacing one registered under the same name.
+    pub fn register(&mut self, kind: CustomKind) {
+        match self.kinds.iter_mut().find(|k| k.name == kind.name) {
+            Some(existing) => *existing = kind,
+            None => self.kinds.push(kind),
+        }
+    }
+
+    pub fn get(&self, id: CustomKindId) -> Option<&CustomKind> {
+        self.kinds.iter().find(|k| k.name == id.name())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &CustomKind> {
+        self.kinds.iter()
+    }
+}
+
+/// `app.register_spawn_kind(..)`, usable before or after the plugins are added.
+pub trait RegisterSpawnKind {
+    fn register_spawn_kind(&mut self, kind: CustomKind) -> &mut Self;
+}
+
+impl RegisterSpawnKind for App {
+    fn register_spawn_kind(&mut self, kind: CustomKind) -> &mut Self {
+        self.init_resource::<SpawnKinds>();
+        self.world_mut().resource_mut::<SpawnKinds>().register(kind);
+        self
+    }
+}
+
+/// Parameters of a custom-kind object, saved with the scene.
+#[derive(Component, Clone, Default, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct CustomKindData(pub serde_json::Value);
+
+pub struct CustomKindsPlugin;
+impl Plugin for CustomKindsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SpawnKinds>()
+            .init_resource::<AssetCache>()
+            .add_systems(Update, (init_custom_objects, apply_custom_data).chain());
+    }
+}
+
+/// Give new custom-kind objects their mesh, and default parameters if they came without any.
+fn init_custom_objects(
+    mut commands: Commands,
+    kinds: Res<SpawnKinds>,
+    mut cache: ResMut<AssetCache>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    q_new: Query<(Entity, &EditableMesh, Has<CustomKindData>), Added<EditableMesh>>,
+) {
+    for (e, info, has_data) in &q_new {
+        let SpawnKind::Custom(id) = info.kind else {
+            continue;
+        };
+        let Some(kind) = kinds.get(id) else {
+            // Kept (and saved back) as is, in case the kind is registered again later
+            warn!("Unknown object kind '{}'", id.name());
+            continue;
+        };
+        let mut ecmd = commands.entity(e);
+        ecmd.insert(Mesh3d(cache.custom_mesh(&mut meshes, kind)));
+        if !has_data {
+            ecmd.insert(CustomKindData((kind.default_data)()));
+        }
+    }
+}
+
+fn apply_custom_data(
+    mut commands: Commands,
+    kinds: Res<SpawnKinds>,
+    q_changed: Query<(Entity, &EditableMesh, &CustomKindData), Changed<CustomKindData>>,
+) {
+    for (e, info, data) in &q_changed {
+        if let SpawnKind::Custom(id) = info.kind
+            && let Some(apply) = kinds.get(id).and_then(|k| k.apply)
+        {
+            apply(&mut commands.entity(e), &data.0);
+        }
+    }
+}
+
+/// Inspector section for a custom-kind object. Returns whether its parameters changed.
+pub fn custom_kind_inspector_ui(
+    ui: &mut egui::Ui,
+    kind: &CustomKind,
+    mut data: Mut<CustomKindData>,
+) -> bool {
+    let Some(edit) = kind.inspector_ui else {
+        return false;
+    };
+    ui.heading(kind.label);
+    let mut value = data.0.clone();
+    // Only touch the component on edits so `apply` doesn't run every frame
+    edit(ui, &mut value) && data.set_if_neq(CustomKindData(value))
+}
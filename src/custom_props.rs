@@ -0,0 +1,86 @@
+//! Free-form key/value metadata on scene objects (`"door_group": "A"`, `"speed": "2.5"`), saved
+//! with the scene for scripts and exporters to read. Values are strings; readers parse them.
+
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+use bevy_egui::egui;
+use serde::{Deserialize, Serialize};
+
+use crate::accessibility::Described;
+
+/// Sorted by key, so saved scenes list them in a stable order.
+#[derive(Component, Clone, Default, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct CustomProperties(pub BTreeMap<String, String>);
+
+impl CustomProperties {
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).map(String::as_str)
+    }
+}
+
+/// Inspector section: a table of the object's properties plus a row to add one. Returns
+/// whether anything changed.
+pub fn custom_properties_inspector_ui(
+    ui: &mut egui::Ui,
+    commands: &mut Commands,
+    entity: Entity,
+    props: Option<&CustomProperties>,
+) -> bool {
+    let mut edited = props.cloned().unwrap_or_default();
+    ui.heading("Custom properties");
+
+    let mut remove = None;
+    egui::Grid::new(("custom_props", entity))
+        .num_columns(3)
+        .striped(true)
+        .show(ui, |ui| {
+            for (key, value) in edited.0.iter_mut() {
+                ui.label(key.as_str());
+                ui.text_edit_singleline(value);
+                if ui.small_button("✖").described("Remove").clicked() {
+                    remove = Some(key.clone());
+                }
+                ui.end_row();
+            }
+        });
+    if let Some(key) = remove {
+        edited.0.remove(&key);
+    }
+
+    // The new row's text lives in egui's memory until it's added
+    let id = ui.make_persistent_id(("new_custom_prop", entity));
+    let (mut key, mut value) =
+        ui.data_mut(|d| d.get_temp::<(String, String)>(id).unwrap_or_default());
+    ui.horizontal(|ui| {
+        ui.add(
+            egui::TextEdit::singleline(&mut key)
+                .hint_text("key")
+                .desired_width(80.0),
+        );
+        ui.add(
+            egui::TextEdit::singleline(&mut value)
+                .hint_text("value")
+                .desired_width(80.0),
+        );
+        let key_ok = !key.trim().is_empty();
+        if ui.add_enabled(key_ok, egui::Button::new("Add")).clicked() {
+            edited
+                .0
+                .insert(key.trim().to_string(), std::mem::take(&mut value));
+            key.clear();
+        }
+    });
+    ui.data_mut(|d| d.insert_temp(id, (key, value)));
+
+    if props.map_or(edited.0.is_empty(), |p| *p == edited) {
+        return false;
+    }
+    if edited.0.is_empty() {
+        commands.entity(entity).remove::<CustomProperties>();
+    } else {
+        commands.entity(entity).insert(edited);
+    }
+    true
+}
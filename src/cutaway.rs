@@ -0,0 +1,323 @@
+//! Cutaway (X-ray) view: objects standing between the camera and what is being worked on (the
+//! selection, or the player in Play mode) turn see-through or outline-only, so things inside
+//! closed rooms can be edited from the fixed corner views.
+//!
+//! The occlusion mask is worked out on the CPU: rays from points inside each target towards the
+//! camera are cast against the other objects' meshes, so it works with every material and
+//! projection, and turned or hollow objects (an L-shaped wall, a room's shell) only count where
+//! their triangles actually are. Cutting away is decided per object (it swaps the material), so
+//! a GPU depth mask would still have to be read back; the rays are only cast again when the
+//! camera, a target or an object moved. Objects with per-view visibility (see `view_layers`)
+//! are left to that, baked members to their merged mesh, and instanced tiles (whose shader has
+//! no alpha) are hidden rather than faded.
+
+use std::collections::{HashMap, HashSet};
+
+use bevy::picking::mesh_picking::ray_cast::{MeshRayCast, MeshRayCastSettings, RayCastVisibility};
+use bevy::prelude::*;
+use bevy::render::primitives::Aabb;
+use serde::{Deserialize, Serialize};
+
+use crate::accessibility::AccessibilityPrefs;
+use crate::bake::BakedMember;
+use crate::camera::{OrbitCamera, OrbitSet};
+use crate::editor_mode::EditorMode;
+use crate::inspector::{Editable, Selected, aabb_world};
+use crate::instancing::InstancedTile;
+use crate::lod::Lod;
+#[cfg(feature = "player")]
+use crate::player::Player;
+use crate::settings::{EditorPrefs, KeyBindings};
+use crate::view_layers::{ViewHidden, ViewLayers};
+
+/// How cut-away objects are drawn.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum CutawayStyle {
+    /// Faded to `cutaway_opacity`
+    #[default]
+    Transparent,
+    /// Only their bounds, as a wireframe box
+    Outline,
+}
+
+impl CutawayStyle {
+    pub const ALL: [Self; 2] = [Self::Transparent, Self::Outline];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Transparent => "Transparent",
+            Self::Outline => "Outline only",
+        }
+    }
+}
+
+/// On an object currently cut away. Keeps the material it had, which is what gets saved.
+#[derive(Component, Clone)]
+pub struct Cutaway {
+    /// `None` when the object is hidden instead of drawn with a see-through copy
+    pub original: Option<Handle<StandardMaterial>>,
+}
+
+/// See-through copies of the materials of cut-away objects, by original material.
+#[derive(Resource, Default)]
+struct CutawayMaterials {
+    copies: HashMap<AssetId<StandardMaterial>, Handle<StandardMaterial>>,
+    /// Style and opacity the copies were made for
+    made_for: Option<(CutawayStyle, f32)>,
+}
+
+pub struct CutawayPlugin;
+impl Plugin for CutawayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EditorPrefs>()
+            .init_resource::<KeyBindings>()
+            .init_resource::<EditorMode>()
+            .init_resource::<AccessibilityPrefs>()
+            .init_resource::<CutawayMaterials>()
+            .add_systems(
+                Update,
+                (update_cutaway, draw_cutaway_outlines)
+                    .chain()
+                    .after(OrbitSet::Pose),
+            );
+    }
+}
+
+/// See-through copy of `original` at `opacity`.
+fn see_through(original: &StandardMaterial, opacity: f32) -> StandardMaterial {
+    let mut copy = original.clone();
+    copy.base_color
+        .set_alpha(original.base_color.alpha() * opacity);
+    copy.alpha_mode = AlphaMode::Blend;
+    copy
+}
+
+/// Give an object back its own look.
+fn release(commands: &mut Commands, e: Entity, cut: &Cutaway, lod: Option<&Lod>) {
+    match &cut.original {
+        Some(original) => {
+            commands.entity(e).insert(MeshMaterial3d(original.clone()));
+        }
+        None if !lod.is_some_and(|l| l.is_culled()) => {
+            commands.entity(e).insert(Visibility::Inherited);
+        }
+        None => {}
+    }
+    commands.entity(e).remove::<Cutaway>();
+}
+
+fn update_cutaway(
+    mut commands: Commands,
+    prefs: Res<EditorPrefs>,
+    mode: Res<EditorMode>,
+    mut cut_materials: ResMut<CutawayMaterials>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut material_events: EventReader<AssetEvent<StandardMaterial>>,
+    mut ray_cast: MeshRayCast,
+    q_cam: Query<(&GlobalTransform, &Projection), With<OrbitCamera>>,
+    q_selected: Query<(&GlobalTransform, &Aabb), With<Selected>>,
+    #[cfg(feature = "player")] q_player: Query<(&GlobalTransform, &Aabb), With<Player>>,
+    q_objects: Query<
+        (
+            Entity,
+            &MeshMaterial3d<StandardMaterial>,
+            Option<&Cutaway>,
+            Option<&Lod>,
+            Has<InstancedTile>,
+        ),
+        (
+            With<Editable>,
+            Without<Selected>,
+            Without<ViewLayers>,
+            Without<ViewHidden>,
+            Without<BakedMember>,
+        ),
+    >,
+    q_stale: Query<
+        (Entity, &Cutaway, Option<&Lod>),
+        Or<(With<Selected>, With<ViewLayers>, With<BakedMember>)>,
+    >,
+    q_moved: Query<
+        (),
+        (
+            Or<(With<Editable>, With<OrbitCamera>)>,
+            Or<(
+                Changed<GlobalTransform>,
+                Changed<Projection>,
+                Changed<Mesh3d>,
+                Added<Selected>,
+            )>,
+        ),
+    >,
+    #[cfg(feature = "player")] q_player_moved: Query<(), (With<Player>, Changed<GlobalTransform>)>,
+    mut deselected: RemovedComponents<Selected>,
+) {
+    // Objects that became selected (or otherwise exempt) while cut away
+    for (e, cut, lod) in &q_stale {
+        release(&mut commands, e, cut, lod);
+    }
+
+    // Switching style: drop every cut and the old copies, the next frame cuts again
+    let restyled = cut_materials
+        .made_for
+        .is_some_and(|(style, _)| style != prefs.cutaway_style);
+    if !prefs.cutaway || restyled {
+        for (e, _, cut, lod, _) in &q_objects {
+            if let Some(cut) = cut {
+                release(&mut commands, e, cut, lod);
+            }
+        }
+        for (_, copy) in cut_materials.copies.drain() {
+            materials.remove(&copy);
+        }
+        cut_materials.made_for = None;
+        return;
+    }
+    // A new opacity redoes every copy, an edited original just its own
+    let opacity = prefs.cutaway_opacity;
+    let reopaque = cut_materials
+        .made_for
+        .is_some_and(|(_, made)| made != opacity);
+    let edited: HashSet<AssetId<StandardMaterial>> = material_events
+        .read()
+        .filter_map(|ev| match ev {
+            AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+    for (original, copy) in &cut_materials.copies {
+        if !reopaque && !edited.contains(original) {
+            continue;
+        }
+        let Some(redone) = materials.get(*original).map(|m| see_through(m, opacity)) else {
+            continue;
+        };
+        if let Some(copy) = materials.get_mut(copy) {
+            *copy = redone;
+        }
+    }
+    let recut = cut_materials.made_for.is_none();
+    cut_materials.made_for = Some((prefs.cutaway_style, opacity));
+
+    // Nothing moved: the cuts made last time still hold
+    let moved = !q_moved.is_empty() || deselected.read().count() > 0;
+    #[cfg(feature = "player")]
+    let moved = moved || !q_player_moved.is_empty();
+    if !(moved || recut || mode.is_changed()) {
+        return;
+    }
+
+    let Ok((cam_tf, proj)) = q_cam.single() else {
+        return;
+    };
+    let mut targets: Vec<Aabb> = Vec::new();
+    #[cfg(feature = "player")]
+    if *mode == EditorMode::Play {
+        targets.extend(q_player.iter().map(|(gt, aabb)| aabb_world(*aabb, gt)));
+    }
+    if *mode != EditorMode::Play {
+        targets.extend(q_selected.iter().map(|(gt, aabb)| aabb_world(*aabb, gt)));
+    }
+
+    // Rays towards the camera from the center and from points halfway to the corners; the
+    // corners themselves would catch the floor the target stands on
+    let ortho = matches!(proj, Projection::Orthographic(_));
+    let cam_pos = cam_tf.translation();
+    let rays: Vec<(Vec3, Vec3, f32)> = targets
+        .iter()
+        .flat_map(|world| {
+            let center = Vec3::from(world.center);
+            let half = Vec3::from(world.half_extents) * 0.5;
+            let corners = (0..8).map(move |i| {
+                let sign = Vec3::new(
+                    if i & 1 == 0 { -1.0 } else { 1.0 },
+                    if i & 2 == 0 { -1.0 } else { 1.0 },
+                    if i & 4 == 0 { -1.0 } else { 1.0 },
+                );
+                center + half * sign
+            });
+            std::iter::once(center).chain(corners)
+        })
+        .map(|p| {
+            if ortho {
+                (p, *cam_tf.back(), f32::INFINITY)
+            } else {
+                let to_cam = cam_pos - p;
+                (p, to_cam.normalize_or_zero(), to_cam.length())
+            }
+        })
+        .collect();
+
+    // Every candidate a ray passes through before reaching the camera; cut-away objects are
+    // hidden or faded, so visibility can't be asked for
+    let candidate = |e| q_objects.contains(e);
+    let settings = MeshRayCastSettings::default()
+        .with_visibility(RayCastVisibility::Any)
+        .with_filter(&candidate)
+        .with_early_exit_test(&|_| false);
+    let mut occluders: HashSet<Entity> = HashSet::new();
+    for &(origin, dir, reach) in &rays {
+        let Ok(dir) = Dir3::new(dir) else {
+            continue;
+        };
+        occluders.extend(
+            ray_cast
+                .cast_ray(Ray3d::new(origin, dir), &settings)
+                .iter()
+                .filter(|(_, hit)| hit.distance < reach)
+                .map(|(e, _)| *e),
+        );
+    }
+
+    for (e, mat, cut, lod, tile) in &q_objects {
+        let occludes = occluders.contains(&e);
+
+        match (occludes, cut) {
+            (false, Some(cut)) => release(&mut commands, e, cut, lod),
+            (true, None) => {
+                // Tiles ignore material alpha, and outlines need nothing drawn
+                if tile || prefs.cutaway_style == CutawayStyle::Outline {
+                    commands
+                        .entity(e)
+                        .insert((Visibility::Hidden, Cutaway { original: None }));
+                    continue;
+                }
+                let copy = match cut_materials.copies.get(&mat.0.id()) {
+                    Some(copy) => copy.clone(),
+                    None => {
+                        let Some(copy) = materials.get(&mat.0).map(|m| see_through(m, opacity))
+                        else {
+                            continue;
+                        };
+                        let copy = materials.add(copy);
+                        cut_materials.copies.insert(mat.0.id(), copy.clone());
+                        copy
+                    }
+                };
+                commands.entity(e).insert((
+                    MeshMaterial3d(copy),
+                    Cutaway {
+                        original: Some(mat.0.clone()),
+                    },
+                ));
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Bounds of the hidden cut-away objects, so it's still clear where the walls are.
+fn draw_cutaway_outlines(mut gizmos: Gizmos, q_cut: Query<(&Cutaway, &GlobalTransform, &Aabb)>) {
+    let color = Color::srgba(0.6, 0.8, 1.0, 0.5);
+    for (cut, gt, aabb) in &q_cut {
+        if cut.original.is_some() {
+            continue;
+        }
+        let world = aabb_world(*aabb, gt);
+        gizmos.cuboid(
+            Transform::from_translation(world.center.into())
+                .with_scale(Vec3::from(world.half_extents * 2.0).max(Vec3::splat(0.0001))),
+            color,
+        );
+    }
+}
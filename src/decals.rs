@@ -0,0 +1,166 @@
+//! Decals: textured quads projected onto whatever surface they sit on, for cracks, moss or
+//! painted symbols without touching the objects underneath. They're scene objects like any
+//! other, saved with their texture and opacity; the footprint is the object's scale. Bevy's
+//! forward decals do the blending, reading the depth prepass this module switches on.
+
+use bevy::{
+    core_pipeline::prepass::DepthPrepass,
+    pbr::{
+        ExtendedMaterial,
+        decal::{ForwardDecal, ForwardDecalMaterial, ForwardDecalMaterialExt},
+    },
+    prelude::*,
+};
+use bevy_egui::egui;
+use serde::{Deserialize, Serialize};
+
+use crate::camera::OrbitCamera;
+use crate::inspector::{Editable, EditableMesh, SpawnKind};
+
+/// Built-in decal textures (under `assets/`), offered as quick picks in the inspector.
+pub const DECAL_PRESETS: [(&str, &str); 3] = [
+    ("Cracks", "decals/cracks.png"),
+    ("Moss", "decals/moss.png"),
+    ("Symbol", "decals/symbol.png"),
+];
+
+/// Persisted parameters for a decal: a textured quad that blends onto the surface below it.
+/// The footprint is the entity's Transform scale (x/z); place it flush with the surface.
+#[derive(Component, Clone, Serialize, Deserialize)]
+pub struct SceneDecal {
+    pub texture: String, // path relative to `assets/`
+    pub opacity: f32,
+    pub depth_fade: f32, // how far (m) the decal blends into uneven surfaces
+}
+
+impl Default for SceneDecal {
+    fn default() -> Self {
+        Self {
+            texture: DECAL_PRESETS[0].1.to_string(),
+            opacity: 1.0,
+            depth_fade: 0.5,
+        }
+    }
+}
+
+pub struct DecalsPlugin;
+impl Plugin for DecalsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (enable_decal_prepass, sync_decal_materials));
+    }
+}
+
+/// Spawn an editable decal at `tf`.
+pub fn spawn_decal_object(commands: &mut Commands, tf: Transform, decal: SceneDecal) -> Entity {
+    commands
+        .spawn((
+            Editable,
+            Name::new("Decal"),
+            tf,
+            EditableMesh {
+                kind: SpawnKind::Decal,
+                collider: None,
+            },
+            ForwardDecal,
+            decal,
+        ))
+        .id()
+}
+
+/// Forward decals read the depth prepass; SSAO already enables it, but don't rely on that.
+fn enable_decal_prepass(
+    mut commands: Commands,
+    q_new: Query<(), Added<SceneDecal>>,
+    q_cam: Query<Entity, (With<OrbitCamera>, Without<DepthPrepass>)>,
+) {
+    if q_new.is_empty() {
+        return;
+    }
+    for cam in &q_cam {
+        commands.entity(cam).insert(DepthPrepass);
+    }
+}
+
+/// (Re)build the decal material when its texture or opacity changes.
+fn sync_decal_materials(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<ForwardDecalMaterial<StandardMaterial>>>,
+    q: Query<
+        (
+            Entity,
+            &SceneDecal,
+            Option<&MeshMaterial3d<ForwardDecalMaterial<StandardMaterial>>>,
+        ),
+        Changed<SceneDecal>,
+    >,
+) {
+    for (e, decal, mat_h) in &q {
+        let base = StandardMaterial {
+            base_color: Color::srgba(1.0, 1.0, 1.0, decal.opacity.clamp(0.0, 1.0)),
+            base_color_texture: Some(asset_server.load(decal.texture.clone())),
+            alpha_mode: AlphaMode::Blend,
+            ..default()
+        };
+        let extension = ForwardDecalMaterialExt {
+            depth_fade_factor: decal.depth_fade.max(0.01),
+        };
+
+        if let Some(mat) = mat_h.and_then(|h| materials.get_mut(&h.0)) {
+            mat.base = base;
+            mat.extension = extension;
+        } else {
+            let handle = materials.add(ExtendedMaterial { base, extension });
+            commands.entity(e).insert(MeshMaterial3d(handle));
+        }
+    }
+}
+
+/// Inspector section for decal parameters.
+pub fn decal_inspector_ui(ui: &mut egui::Ui, entity: Entity, mut decal: Mut<SceneDecal>) {
+    ui.heading("Decal");
+
+    // Edit the path in a scratch buffer so the texture only reloads once editing is done
+    let id = ui.make_persistent_id(("decal_texture", entity));
+    let mut path = ui
+        .data_mut(|d| d.get_temp::<String>(id))
+        .unwrap_or_else(|| decal.texture.clone());
+    ui.horizontal(|ui| {
+        ui.label("Texture");
+        let resp = ui.text_edit_singleline(&mut path);
+        if resp.lost_focus() {
+            ui.data_mut(|d| d.remove::<String>(id));
+            if path != decal.texture {
+                decal.texture = path;
+            }
+        } else if resp.has_focus() {
+            ui.data_mut(|d| d.insert_temp(id, path));
+        }
+    });
+    ui.horizontal(|ui| {
+        for (label, preset) in DECAL_PRESETS {
+            if ui
+                .selectable_label(decal.texture == preset, label)
+                .clicked()
+            {
+                decal.texture = preset.to_string();
+            }
+        }
+    });
+
+    let mut opacity = decal.opacity;
+    if ui
+        .add(egui::Slider::new(&mut opacity, 0.0..=1.0).text("Opacity"))
+        .changed()
+    {
+        decal.opacity = opacity;
+    }
+    let mut depth_fade = decal.depth_fade;
+    if ui
+        .add(egui::Slider::new(&mut depth_fade, 0.01..=2.0).text("Depth fade (m)"))
+        .changed()
+    {
+        decal.depth_fade = depth_fade;
+    }
+    ui.label("Size: Scale X/Z.");
+}
@@ -0,0 +1,285 @@
+//! Performance and memory readouts. The overlay splits each frame between the main world, the
+//! render world and the costliest render passes. The Memory panel totals what meshes, textures
+//! and materials take up, and can free cached ones nothing uses any more.
+
+use std::collections::HashSet;
+use std::fmt::Write as _;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use bevy::diagnostic::{
+    Diagnostic, DiagnosticPath, Diagnostics, DiagnosticsStore, EntityCountDiagnosticsPlugin,
+    FrameTimeDiagnosticsPlugin, RegisterDiagnostic,
+};
+use bevy::ecs::entity_disabling::Disabled;
+use bevy::platform::time::Instant;
+use bevy::prelude::*;
+use bevy::render::diagnostic::RenderDiagnosticsPlugin;
+use bevy::render::mesh::Indices;
+use bevy::render::{Render, RenderApp, RenderSet};
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+
+use crate::SceneEditState;
+use crate::asset_cache::AssetCache;
+use crate::cutaway::Cutaway;
+use crate::editor_mode::EditorMode;
+use crate::toasts::Toasts;
+
+pub const MAIN_WORLD_TIME: DiagnosticPath = DiagnosticPath::const_new("tunable/main_world_time");
+pub const RENDER_WORLD_TIME: DiagnosticPath =
+    DiagnosticPath::const_new("tunable/render_world_time");
+
+/// How many of the most expensive render passes the overlay lists.
+const SLOWEST_SHOWN: usize = 5;
+
+/// Frame-time breakdown in place of the plain FPS counter, toggled with F3, and the Memory
+/// panel.
+///
+/// The breakdown goes down to render passes, which Bevy measures itself. It has no
+/// per-system timings: Bevy only records those as tracing spans, with its `trace` feature on.
+pub struct DiagnosticsOverlayPlugin;
+impl Plugin for DiagnosticsOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        let render_time = RenderWorldTime::default();
+
+        app.add_plugins((EntityCountDiagnosticsPlugin, RenderDiagnosticsPlugin))
+            .register_diagnostic(Diagnostic::new(MAIN_WORLD_TIME).with_suffix("ms"))
+            .register_diagnostic(Diagnostic::new(RENDER_WORLD_TIME).with_suffix("ms"))
+            .init_resource::<DiagnosticsOverlay>()
+            .init_resource::<MainWorldTimer>()
+            .init_resource::<SceneEditState>()
+            .init_resource::<EditorMode>()
+            .insert_resource(render_time.clone())
+            .add_systems(First, start_main_world_timer)
+            .add_systems(Last, record_world_times)
+            .add_systems(EguiPrimaryContextPass, memory_panel);
+
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app
+            .insert_resource(render_time)
+            .init_resource::<RenderWorldTimer>()
+            .add_systems(
+                Render,
+                (
+                    start_render_world_timer.in_set(RenderSet::ExtractCommands),
+                    stop_render_world_timer.in_set(RenderSet::PostCleanup),
+                ),
+            );
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct DiagnosticsOverlay {
+    pub enabled: bool,
+}
+
+#[derive(Resource, Default)]
+struct MainWorldTimer(Option<Instant>);
+
+#[derive(Resource, Default)]
+struct RenderWorldTimer(Option<Instant>);
+
+/// Last render world duration in microseconds, written by the render world (which may run on
+/// another thread) and read back into the main world's diagnostics.
+#[derive(Resource, Clone, Default)]
+struct RenderWorldTime(Arc<AtomicU64>);
+
+fn start_main_world_timer(mut timer: ResMut<MainWorldTimer>) {
+    timer.0 = Some(Instant::now());
+}
+
+fn record_world_times(
+    mut diagnostics: Diagnostics,
+    timer: Res<MainWorldTimer>,
+    render_time: Res<RenderWorldTime>,
+) {
+    if let Some(start) = timer.0 {
+        diagnostics.add_measurement(&MAIN_WORLD_TIME, || start.elapsed().as_secs_f64() * 1000.0);
+    }
+    let micros = render_time.0.load(Ordering::Relaxed);
+    if micros > 0 {
+        diagnostics.add_measurement(&RENDER_WORLD_TIME, || micros as f64 / 1000.0);
+    }
+}
+
+fn start_render_world_timer(mut timer: ResMut<RenderWorldTimer>) {
+    timer.0 = Some(Instant::now());
+}
+
+fn stop_render_world_timer(timer: Res<RenderWorldTimer>, render_time: Res<RenderWorldTime>) {
+    if let Some(start) = timer.0 {
+        render_time
+            .0
+            .store(start.elapsed().as_micros() as u64, Ordering::Relaxed);
+    }
+}
+
+/// Multi-line overlay text: frame time, world times, entity count and the slowest render passes.
+pub fn overlay_text(diagnostics: &DiagnosticsStore) -> String {
+    let smoothed = |path: &DiagnosticPath| {
+        diagnostics
+            .get(path)
+            .and_then(|d| d.smoothed())
+            .unwrap_or(0.0)
+    };
+
+    let mut text = format!(
+        "{:.0} fps  {:.2} ms\nmain world {:.2} ms\nrender world {:.2} ms\nentities {:.0}",
+        smoothed(&FrameTimeDiagnosticsPlugin::FPS),
+        smoothed(&FrameTimeDiagnosticsPlugin::FRAME_TIME),
+        smoothed(&MAIN_WORLD_TIME),
+        smoothed(&RENDER_WORLD_TIME),
+        smoothed(&EntityCountDiagnosticsPlugin::ENTITY_COUNT),
+    );
+
+    // Render passes report as "render/<pass>/elapsed_cpu" (and "elapsed_gpu" where supported)
+    let mut passes: Vec<(String, f64)> = diagnostics
+        .iter()
+        .filter_map(|d| {
+            let pass = d.path().as_str().strip_prefix("render/")?;
+            let (pass, unit) = pass
+                .strip_suffix("/elapsed_cpu")
+                .map(|p| (p, "cpu"))
+                .or_else(|| pass.strip_suffix("/elapsed_gpu").map(|p| (p, "gpu")))?;
+            Some((format!("{pass} ({unit})"), d.smoothed()?))
+        })
+        .collect();
+    passes.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    if !passes.is_empty() {
+        text.push_str("\nslowest passes:");
+        for (name, ms) in passes.iter().take(SLOWEST_SHOWN) {
+            let _ = write!(text, "\n  {name} {ms:.2} ms");
+        }
+    }
+    text
+}
+
+/// Size of a mesh's vertex attributes and indices.
+fn mesh_bytes(mesh: &Mesh) -> usize {
+    let vertices: usize = mesh
+        .attributes()
+        .map(|(_, values)| values.get_bytes().len())
+        .sum();
+    let indices = match mesh.indices() {
+        Some(Indices::U16(i)) => i.len() * 2,
+        Some(Indices::U32(i)) => i.len() * 4,
+        None => 0,
+    };
+    vertices + indices
+}
+
+/// Size of an image's pixels; render targets have none on the CPU, so theirs is worked out
+/// from the size and format.
+fn image_bytes(image: &Image) -> usize {
+    image.data.as_ref().map_or_else(
+        || {
+            let desc = &image.texture_descriptor;
+            let texel = desc.format.block_copy_size(None).unwrap_or(4);
+            (desc.size.width * desc.size.height * desc.size.depth_or_array_layers * texel) as usize
+        },
+        Vec::len,
+    )
+}
+
+fn megabytes(bytes: usize) -> String {
+    format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+}
+
+/// egui panel: what the mesh, texture and material assets take up, and a purge of the shared
+/// ones no object uses any more.
+fn memory_panel(
+    mut ctxs: EguiContexts,
+    edit_state: Res<SceneEditState>,
+    mode: Res<EditorMode>,
+    meshes: Res<Assets<Mesh>>,
+    images: Res<Assets<Image>>,
+    materials: Res<Assets<StandardMaterial>>,
+    mut cache: Option<ResMut<AssetCache>>,
+    mut toasts: Option<ResMut<Toasts>>,
+    // Play mode's disabled backups use assets too
+    q_meshes: Query<(&Mesh3d, Has<Disabled>)>,
+    q_materials: Query<(&MeshMaterial3d<StandardMaterial>, Has<Disabled>)>,
+    q_cutaway: Query<(&Cutaway, Has<Disabled>)>,
+) {
+    if !edit_state.open {
+        return;
+    }
+    let Ok(ctx) = ctxs.ctx_mut() else {
+        return;
+    };
+
+    egui::Window::new("Memory")
+        .default_open(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            let mesh_total: usize = meshes.iter().map(|(_, m)| mesh_bytes(m)).sum();
+            let image_total: usize = images.iter().map(|(_, i)| image_bytes(i)).sum();
+            egui::Grid::new("memory_grid")
+                .num_columns(3)
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.label("Meshes");
+                    ui.label(meshes.len().to_string());
+                    ui.label(megabytes(mesh_total));
+                    ui.end_row();
+                    ui.label("Textures");
+                    ui.label(images.len().to_string());
+                    ui.label(megabytes(image_total));
+                    ui.end_row();
+                    ui.label("Materials");
+                    ui.label(materials.len().to_string());
+                    ui.label(megabytes(
+                        materials.len() * std::mem::size_of::<StandardMaterial>(),
+                    ));
+                    ui.end_row();
+                });
+            ui.weak("Sizes of the CPU-side copies; render targets count by their dimensions");
+
+            let Some(cache) = cache.as_mut() else {
+                return;
+            };
+            ui.separator();
+            let meshes_in_use: HashSet<AssetId<Mesh>> =
+                q_meshes.iter().map(|(m, _)| m.id()).collect();
+            // A cut-away object shows a copy of its material; the one it keeps counts as used
+            let materials_in_use: HashSet<AssetId<StandardMaterial>> = q_materials
+                .iter()
+                .map(|(m, _)| m.id())
+                .chain(
+                    q_cutaway
+                        .iter()
+                        .filter_map(|(c, _)| c.original.as_ref().map(Handle::id)),
+                )
+                .collect();
+            let (shared_meshes, shared_materials) = cache.cached();
+            let unused = cache.unused(&meshes_in_use, &materials_in_use);
+            ui.label(format!(
+                "Shared: {shared_meshes} meshes, {shared_materials} materials ({unused} unused)"
+            ));
+            let playing = *mode == EditorMode::Play;
+            if ui
+                .add_enabled(
+                    unused > 0 && !playing,
+                    egui::Button::new("Purge unused assets"),
+                )
+                .on_hover_text(
+                    "Free the shared meshes and materials no object uses any more, \
+                     left behind by earlier scenes and edits",
+                )
+                .on_disabled_hover_text(if playing {
+                    "Leave Play mode first"
+                } else {
+                    "Nothing to purge"
+                })
+                .clicked()
+            {
+                let purged = cache.purge_unused(&meshes_in_use, &materials_in_use);
+                if let Some(toasts) = toasts.as_mut() {
+                    toasts.info(format!("Purged {purged} unused assets"));
+                }
+            }
+        });
+}
@@ -0,0 +1,324 @@
+//! Dissolve: an object burns away through 3D noise with a glowing edge, or builds back up the
+//! same way. While it's partly gone the object is hidden and a copy with the dissolve material
+//! stands in for it.
+//!
+//! New and duplicated objects dissolve in, deleted ones dissolve away before they're despawned,
+//! and block groups (`block_groups`) fade between solid and ghost with it. Anything else can be
+//! dissolved with a tween:
+//!
+//! ```ignore
+//! commands.entity(door).insert(AnimateTo {
+//!     target: TweenTarget::Dissolve(1.0),
+//!     duration: 0.8,
+//!     easing: Easing::EaseIn,
+//! });
+//! ```
+//!
+//! The duration and edge color are set in Effect settings → Dissolve ([`DissolveSettings`]).
+
+use bevy::{
+    pbr::{ExtendedMaterial, MaterialExtension, NotShadowCaster},
+    prelude::*,
+    render::render_resource::{AsBindGroup, ShaderRef},
+    transform::TransformSystem,
+};
+use bevy_egui::egui;
+use bevy_rapier3d::prelude::ColliderDisabled;
+
+use crate::editor_mode::EditorMode;
+use crate::inspector::{Editable, Selected};
+use crate::instancing::InstancedTile;
+use crate::tween::{AnimateTo, Easing, TweenTarget};
+
+const SHADER_ASSET_PATH: &str = "shaders/dissolve.wgsl";
+
+/// Noise cells per meter
+const NOISE_SCALE: f32 = 4.0;
+
+pub type DissolveMaterial = ExtendedMaterial<StandardMaterial, DissolveExtension>;
+
+/// Cuts a standard material away through 3D noise, glowing along the cut.
+#[derive(Asset, AsBindGroup, Reflect, Debug, Clone)]
+pub struct DissolveExtension {
+    /// x = amount (0 whole .. 1 gone), y = edge width, z = noise scale
+    #[uniform(100)]
+    pub params: Vec4,
+    /// Linear RGB of the glowing edge
+    #[uniform(101)]
+    pub edge_color: Vec4,
+}
+
+impl MaterialExtension for DissolveExtension {
+    fn fragment_shader() -> ShaderRef {
+        SHADER_ASSET_PATH.into()
+    }
+
+    fn deferred_fragment_shader() -> ShaderRef {
+        SHADER_ASSET_PATH.into()
+    }
+}
+
+/// Set from the Dissolve section of the Effect settings.
+#[derive(Resource, Clone, Copy, PartialEq)]
+pub struct DissolveSettings {
+    /// Dissolve objects in when they're created and away when they're deleted
+    pub on_edit: bool,
+    /// Seconds from whole to gone (or back)
+    pub duration: f32,
+    pub edge_color: Color,
+    /// Multiplies the edge color; above 1 for bloom to catch
+    pub edge_glow: f32,
+    /// Noise range that glows just before it's cut away
+    pub edge_width: f32,
+}
+
+impl Default for DissolveSettings {
+    fn default() -> Self {
+        Self {
+            on_edit: true,
+            duration: 0.6,
+            edge_color: Color::linear_rgb(0.23, 0.69, 1.0),
+            edge_glow: 2.6,
+            edge_width: 0.08,
+        }
+    }
+}
+
+impl DissolveSettings {
+    fn edge(&self) -> Vec4 {
+        (self.edge_color.to_linear() * self.edge_glow)
+            .with_alpha(1.0)
+            .to_vec4()
+    }
+}
+
+/// How far an object is dissolved: 0 whole .. 1 gone. Above 0 the dissolving copy is drawn in its
+/// place; back at 0 the component goes away. `TweenTarget::Dissolve` animates it.
+#[derive(Component, PartialEq, Debug, Default)]
+pub struct DissolveAmount(pub f32);
+
+/// On an object deleted with a dissolve: despawned once it's gone.
+#[derive(Component)]
+pub struct DespawnDissolved;
+
+/// An edit dissolve under way (see [`dissolve_in`] and [`dissolve_away`]). It runs on real time
+/// and apart from `AnimateTo`, so paused game time or a tween started on the object meanwhile
+/// can't leave it half-built or undeleted; a dissolve tween of its own takes over from it.
+#[derive(Component)]
+struct DissolveFade {
+    /// Taken from the object's amount on the first frame
+    from: Option<f32>,
+    to: f32,
+    easing: Easing,
+    elapsed: f32,
+}
+
+/// On a dissolving object: the copy drawn in its place and the visibility given back after.
+#[derive(Component)]
+struct DissolveShell {
+    shell: Entity,
+    material: Handle<DissolveMaterial>,
+    visibility: Visibility,
+}
+
+/// On the dissolving copy: the object it stands in for.
+#[derive(Component)]
+struct DissolveOf(Entity);
+
+pub struct DissolvePlugin;
+impl Plugin for DissolvePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(MaterialPlugin::<DissolveMaterial>::default())
+            .init_resource::<DissolveSettings>()
+            .init_resource::<EditorMode>()
+            .add_systems(
+                PostUpdate,
+                update_dissolves.before(TransformSystem::TransformPropagate),
+            );
+    }
+}
+
+/// Make a new object build up from nothing.
+pub fn dissolve_in(commands: &mut Commands, e: Entity, settings: &DissolveSettings) {
+    if !settings.on_edit {
+        return;
+    }
+    commands.entity(e).insert((
+        DissolveAmount(1.0),
+        DissolveFade {
+            from: None,
+            to: 0.0,
+            easing: Easing::EaseOut,
+            elapsed: 0.0,
+        },
+    ));
+}
+
+/// Delete an object: it stops being part of the scene (saved, selectable, solid) straight away,
+/// and despawns once it has dissolved.
+pub fn dissolve_away(commands: &mut Commands, e: Entity, settings: &DissolveSettings) {
+    let Ok(mut ecmd) = commands.get_entity(e) else {
+        return;
+    };
+    if !settings.on_edit {
+        ecmd.despawn();
+        return;
+    }
+    ecmd.remove::<(Editable, Selected)>()
+        .insert_if_new(DissolveAmount(0.0))
+        .insert((
+            ColliderDisabled,
+            DespawnDissolved,
+            DissolveFade {
+                from: None,
+                to: 1.0,
+                easing: Easing::EaseIn,
+                elapsed: 0.0,
+            },
+        ));
+}
+
+/// Advance edit dissolves, put a dissolving copy in place of objects that start dissolving, keep
+/// it following the object and the settings, and take it away again (or the object with it) when
+/// it's done. Instanced tiles and objects without a mesh have nothing to dissolve: they skip
+/// straight to the end.
+fn update_dissolves(
+    mut commands: Commands,
+    mode: Res<EditorMode>,
+    settings: Res<DissolveSettings>,
+    time: Res<Time<Real>>,
+    std_materials: Res<Assets<StandardMaterial>>,
+    mut dissolve_materials: ResMut<Assets<DissolveMaterial>>,
+    mut q_objects: Query<(
+        Entity,
+        Mut<DissolveAmount>,
+        Option<Mut<DissolveFade>>,
+        Option<&AnimateTo>,
+        Option<(&Mesh3d, &MeshMaterial3d<StandardMaterial>)>,
+        &GlobalTransform,
+        &Visibility,
+        Option<&DissolveShell>,
+        Has<DespawnDissolved>,
+        Has<InstancedTile>,
+    )>,
+    mut q_shells: Query<(Entity, &DissolveOf, &mut Transform), Without<DissolveAmount>>,
+) {
+    // Objects despawned (or made whole) some other way, like loading a scene, leave their copy
+    for (shell, of, _) in &q_shells {
+        if !q_objects.contains(of.0) {
+            commands.entity(shell).despawn();
+        }
+    }
+
+    let dt = time.delta_secs();
+    for (e, mut amount, fade, tween, mesh, gt, visibility, shell, despawn, tile) in &mut q_objects {
+        if let Some(mut fade) = fade {
+            if tween.is_some_and(|t| matches!(t.target, TweenTarget::Dissolve(_))) {
+                commands.entity(e).remove::<DissolveFade>();
+            } else {
+                let from = *fade.from.get_or_insert(amount.0);
+                fade.elapsed += dt;
+                let t = if settings.duration > 0.0 {
+                    (fade.elapsed / settings.duration).min(1.0)
+                } else {
+                    1.0
+                };
+                amount.set_if_neq(DissolveAmount(
+                    from + (fade.to - from) * fade.easing.ease(t),
+                ));
+                if t >= 1.0 {
+                    commands.entity(e).remove::<DissolveFade>();
+                }
+            }
+        }
+        // Switching between Edit and Play finishes what's under way (but not what the switch
+        // itself started, like ghost block groups)
+        let finish = mode.is_changed() && !amount.is_added();
+        let drawable = mesh.filter(|_| !tile);
+        let gone = amount.0 >= 1.0 || (despawn && (finish || drawable.is_none()));
+        let whole = amount.0 <= 0.0 || (!despawn && finish) || drawable.is_none();
+
+        if despawn && gone {
+            if let Some(shell) = shell {
+                commands.entity(shell.shell).despawn();
+            }
+            commands.entity(e).despawn();
+            continue;
+        }
+        if whole && !despawn {
+            if let Some(shell) = shell {
+                commands.entity(shell.shell).despawn();
+                commands.entity(e).insert(shell.visibility);
+            }
+            commands
+                .entity(e)
+                .remove::<(DissolveAmount, DissolveShell, DissolveFade)>();
+            continue;
+        }
+        let Some((mesh, mat)) = drawable else {
+            continue;
+        };
+
+        let Some(shell) = shell else {
+            let material = dissolve_materials.add(DissolveMaterial {
+                base: std_materials.get(&mat.0).cloned().unwrap_or_default(),
+                extension: DissolveExtension {
+                    params: Vec4::new(amount.0, settings.edge_width, NOISE_SCALE, 0.0),
+                    edge_color: settings.edge(),
+                },
+            });
+            let shell = commands
+                .spawn((
+                    Mesh3d(mesh.0.clone()),
+                    MeshMaterial3d(material.clone()),
+                    gt.compute_transform(),
+                    // A half-gone object shouldn't cast a whole shadow
+                    NotShadowCaster,
+                    Name::new("Dissolve"),
+                    DissolveOf(e),
+                ))
+                .id();
+            commands.entity(e).insert((
+                DissolveShell {
+                    shell,
+                    material,
+                    visibility: *visibility,
+                },
+                Visibility::Hidden,
+            ));
+            continue;
+        };
+
+        // Objects can be moved by scripts and tweens while they dissolve
+        if let Ok((_, _, mut tf)) = q_shells.get_mut(shell.shell) {
+            tf.set_if_neq(gt.compute_transform());
+        }
+        if (amount.is_changed() || settings.is_changed())
+            && let Some(m) = dissolve_materials.get_mut(&shell.material)
+        {
+            m.extension.params = Vec4::new(amount.0, settings.edge_width, NOISE_SCALE, 0.0);
+            m.extension.edge_color = settings.edge();
+        }
+    }
+}
+
+/// Effect settings section.
+pub fn dissolve_settings_ui(ui: &mut egui::Ui, settings: &mut DissolveSettings) {
+    ui.checkbox(&mut settings.on_edit, "On create and delete")
+        .on_hover_text("Block groups and dissolve tweens use it either way");
+    ui.add(
+        egui::Slider::new(&mut settings.duration, 0.05..=3.0)
+            .logarithmic(true)
+            .suffix(" s")
+            .text("Duration"),
+    );
+    ui.horizontal(|ui| {
+        let mut rgb = settings.edge_color.to_srgba().to_f32_array_no_alpha();
+        if ui.color_edit_button_rgb(&mut rgb).changed() {
+            settings.edge_color = Color::srgb(rgb[0], rgb[1], rgb[2]);
+        }
+        ui.label("Edge color");
+    });
+    ui.add(egui::Slider::new(&mut settings.edge_glow, 0.0..=8.0).text("Edge glow"));
+    ui.add(egui::Slider::new(&mut settings.edge_width, 0.0..=0.3).text("Edge width"));
+}
@@ -0,0 +1,276 @@
+//! Dockable editor panels. The hierarchy, inspector and effect settings each go in a slot: a
+//! window edge or floating over the view. The slots are saved with the app settings, and the
+//! 3D view is shrunk to the space the docked panels leave free, so nothing draws under them.
+
+use bevy::prelude::*;
+use bevy::render::camera::Viewport;
+use bevy::window::PrimaryWindow;
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+use serde::{Deserialize, Serialize};
+
+use crate::accessibility::Described;
+use crate::camera::{OrbitCamera, SplitCamera};
+
+/// Where a panel lives: docked to a window edge or floating on top of the viewport.
+#[derive(Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub enum DockSlot {
+    Left,
+    Right,
+    Bottom,
+    Floating,
+}
+
+impl DockSlot {
+    pub const ALL: [DockSlot; 4] = [
+        DockSlot::Left,
+        DockSlot::Right,
+        DockSlot::Bottom,
+        DockSlot::Floating,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            DockSlot::Left => "Left",
+            DockSlot::Right => "Right",
+            DockSlot::Bottom => "Bottom",
+            DockSlot::Floating => "Floating",
+        }
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub enum DockPanel {
+    Hierarchy,
+    Inspector,
+    Effects,
+}
+
+/// Which slot each editor panel is docked to (kept in the app settings; panel sizes are kept
+/// with the rest of the egui layout).
+#[derive(Resource, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DockLayout {
+    pub hierarchy: DockSlot,
+    pub inspector: DockSlot,
+    pub effects: DockSlot,
+}
+
+impl Default for DockLayout {
+    fn default() -> Self {
+        Self {
+            hierarchy: DockSlot::Left,
+            inspector: DockSlot::Right,
+            effects: DockSlot::Bottom,
+        }
+    }
+}
+
+impl DockLayout {
+    pub fn slot(&self, panel: DockPanel) -> DockSlot {
+        match panel {
+            DockPanel::Hierarchy => self.hierarchy,
+            DockPanel::Inspector => self.inspector,
+            DockPanel::Effects => self.effects,
+        }
+    }
+
+    pub fn slot_mut(&mut self, panel: DockPanel) -> &mut DockSlot {
+        match panel {
+            DockPanel::Hierarchy => &mut self.hierarchy,
+            DockPanel::Inspector => &mut self.inspector,
+            DockPanel::Effects => &mut self.effects,
+        }
+    }
+
+    /// Back to the default slots, forgetting dragged panel sizes and window positions.
+    pub fn reset(&mut self, ctx: &egui::Context) {
+        *self = Self::default();
+        ctx.memory_mut(|m| {
+            for title in PANEL_TITLES {
+                m.data
+                    .remove::<egui::containers::panel::PanelState>(panel_id(title));
+            }
+            m.reset_areas();
+        });
+    }
+}
+
+const PANEL_TITLES: [&str; 3] = ["Hierarchy", "Object Inspector", "Effect settings"];
+
+fn panel_id(title: &str) -> egui::Id {
+    egui::Id::new(("dock", title))
+}
+
+/// Systems drawing the dockable panels, in a fixed order so panels sharing an edge don't swap
+/// places between frames. The viewport is fitted to what they leave over.
+#[derive(SystemSet, Debug, Hash, PartialEq, Eq, Clone)]
+pub enum DockSet {
+    Hierarchy,
+    Inspector,
+    Effects,
+}
+
+/// Hierarchy left, inspector right, effects bottom and the 3D viewport in the space between.
+pub struct DockPlugin;
+impl Plugin for DockPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DockLayout>()
+            .configure_sets(
+                EguiPrimaryContextPass,
+                (DockSet::Hierarchy, DockSet::Inspector, DockSet::Effects).chain(),
+            )
+            .add_systems(EguiPrimaryContextPass, fit_viewport.after(DockSet::Effects));
+    }
+}
+
+/// An editor panel, drawn as a side/bottom panel or as a floating window depending on the
+/// `DockLayout`. Mirrors `egui::Window`'s builder.
+pub struct DockedWindow<'a> {
+    panel: DockPanel,
+    title: &'a str,
+    open: Option<&'a mut bool>,
+    scroll: bool,
+}
+
+impl<'a> DockedWindow<'a> {
+    pub fn new(panel: DockPanel, title: &'a str) -> Self {
+        Self {
+            panel,
+            title,
+            open: None,
+            scroll: true,
+        }
+    }
+
+    /// Show a close button that clears `open`.
+    pub fn open(mut self, open: &'a mut bool) -> Self {
+        self.open = Some(open);
+        self
+    }
+
+    /// Wrap the contents in a vertical scroll area (on by default); turn off for contents that
+    /// scroll themselves.
+    pub fn scroll(mut self, scroll: bool) -> Self {
+        self.scroll = scroll;
+        self
+    }
+
+    pub fn show(self, ctx: &egui::Context, layout: &DockLayout, body: impl FnOnce(&mut egui::Ui)) {
+        let Self {
+            panel,
+            title,
+            open,
+            scroll,
+        } = self;
+        if open.as_deref() == Some(&false) {
+            return;
+        }
+        let contents = |ui: &mut egui::Ui| {
+            if scroll {
+                egui::ScrollArea::vertical().show(ui, body);
+            } else {
+                body(ui);
+            }
+        };
+
+        let slot = layout.slot(panel);
+        if slot == DockSlot::Floating {
+            let mut window = egui::Window::new(title)
+                .resizable(true)
+                .collapsible(true)
+                .default_width(300.0);
+            if let Some(open) = open {
+                window = window.open(open);
+            }
+            window.show(ctx, contents);
+            return;
+        }
+
+        let docked = |ui: &mut egui::Ui| {
+            ui.horizontal(|ui| {
+                ui.strong(title);
+                if let Some(open) = open {
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.small_button("✖").described("Close").clicked() {
+                            *open = false;
+                        }
+                    });
+                }
+            });
+            ui.separator();
+            contents(ui);
+        };
+        let id = panel_id(title);
+        match slot {
+            DockSlot::Left => {
+                egui::SidePanel::left(id)
+                    .resizable(true)
+                    .default_width(240.0)
+                    .show(ctx, docked);
+            }
+            DockSlot::Right => {
+                egui::SidePanel::right(id)
+                    .resizable(true)
+                    .default_width(320.0)
+                    .show(ctx, docked);
+            }
+            DockSlot::Bottom => {
+                egui::TopBottomPanel::bottom(id)
+                    .resizable(true)
+                    .default_height(240.0)
+                    .show(ctx, docked);
+            }
+            DockSlot::Floating => unreachable!(),
+        }
+    }
+}
+
+/// Render the 3D view only into the area the docked panels leave free (split between the two
+/// views in split-screen co-op).
+fn fit_viewport(
+    mut ctxs: EguiContexts,
+    q_window: Query<&Window, With<PrimaryWindow>>,
+    mut q_cam: Query<&mut Camera, With<OrbitCamera>>,
+    mut q_split: Query<&mut Camera, (With<SplitCamera>, Without<OrbitCamera>)>,
+) {
+    let (Ok(ctx), Ok(window), Ok(mut camera)) =
+        (ctxs.ctx_mut(), q_window.single(), q_cam.single_mut())
+    else {
+        return;
+    };
+    let rect = ctx.available_rect();
+    let ppp = ctx.pixels_per_point();
+    let size = window.physical_size();
+    let min = UVec2::new((rect.min.x * ppp) as u32, (rect.min.y * ppp) as u32).min(size);
+    let mut max = UVec2::new((rect.max.x * ppp) as u32, (rect.max.y * ppp) as u32).min(size);
+    if max.x <= min.x || max.y <= min.y {
+        // Panels cover everything; keep the last viewport rather than a zero-sized one
+        return;
+    }
+
+    if let Ok(mut split) = q_split.single_mut()
+        && max.x - min.x >= 2
+    {
+        // The main view keeps the left half
+        let mid = UVec2::new((min.x + max.x) / 2, min.y);
+        set_viewport(&mut split, Some((mid, max - mid)));
+        max.x = mid.x;
+    }
+    let viewport = (min != UVec2::ZERO || max != size).then_some((min, max - min));
+    set_viewport(&mut camera, viewport);
+}
+
+/// Change `camera`'s viewport to `viewport` (position and size), if it isn't that already.
+fn set_viewport(camera: &mut Mut<Camera>, viewport: Option<(UVec2, UVec2)>) {
+    let current = camera
+        .viewport
+        .as_ref()
+        .map(|v| (v.physical_position, v.physical_size));
+    if current != viewport {
+        camera.viewport = viewport.map(|(physical_position, physical_size)| Viewport {
+            physical_position,
+            physical_size,
+            ..default()
+        });
+    }
+}
@@ -0,0 +1,75 @@
+//! Line style of the editor's own gizmos (the selection box and axes, the proportional-editing
+//! radius, and any transform handles), in a config group of their own so they can draw over the
+//! scene and thicker than debug gizmos.
+
+use bevy::core_pipeline::fxaa::Fxaa;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::camera::OrbitCamera;
+use crate::settings::EditorPrefs;
+
+/// Draw through `Gizmos<EditorGizmos>` for anything that marks the selection or edits it.
+#[derive(Default, Reflect, GizmoConfigGroup)]
+pub struct EditorGizmos;
+
+/// Whether editor gizmos are hidden by the scene in front of them.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum GizmoDepth {
+    /// Always visible, even behind other objects
+    #[default]
+    OnTop,
+    /// Hidden by whatever is in front, like scene geometry
+    DepthTested,
+}
+
+impl GizmoDepth {
+    pub const ALL: [Self; 2] = [Self::OnTop, Self::DepthTested];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::OnTop => "Always on top",
+            Self::DepthTested => "Hidden behind objects",
+        }
+    }
+}
+
+pub struct EditorGizmosPlugin;
+impl Plugin for EditorGizmosPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EditorPrefs>()
+            .init_gizmo_group::<EditorGizmos>()
+            .add_systems(
+                Update,
+                apply_gizmo_prefs.run_if(
+                    resource_changed::<EditorPrefs>.or(any_match_filter::<Added<OrbitCamera>>),
+                ),
+            );
+    }
+}
+
+fn apply_gizmo_prefs(
+    mut commands: Commands,
+    prefs: Res<EditorPrefs>,
+    mut store: ResMut<GizmoConfigStore>,
+    q_cam: Query<(Entity, Has<Fxaa>), With<OrbitCamera>>,
+) {
+    let (config, _) = store.config_mut::<EditorGizmos>();
+    config.depth_bias = match prefs.gizmo_depth {
+        GizmoDepth::OnTop => -1.0,
+        // Pulled forward a little so the box doesn't z-fight with the faces it wraps
+        GizmoDepth::DepthTested => -0.002,
+    };
+    config.line.width = prefs.gizmo_line_width;
+    // Round corners, so thick boxes don't show notches where edges meet
+    config.line.joints = GizmoLineJoint::Round(4);
+
+    // The camera runs without MSAA, so smooth lines need FXAA over the whole frame
+    for (cam, has_fxaa) in &q_cam {
+        if prefs.gizmo_smoothing && !has_fxaa {
+            commands.entity(cam).insert(Fxaa::default());
+        } else if !prefs.gizmo_smoothing && has_fxaa {
+            commands.entity(cam).remove::<Fxaa>();
+        }
+    }
+}
@@ -0,0 +1,346 @@
+use std::f32::consts::FRAC_PI_2;
+
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use serde::{Deserialize, Serialize};
+
+use crate::build_mode::BuildMode;
+use crate::camera::{OrbitCamera, UiPointer};
+use crate::editor_gizmos::EditorGizmos;
+use crate::inspector::{Editable, GroupPivot, InspectorState, Selected, move_selection_with};
+use crate::settings::EditorPrefs;
+
+/// What a left drag in the viewport does, picked from the toolbar.
+#[derive(Resource, Copy, Clone, Default, Debug, Eq, PartialEq)]
+pub enum EditorMode {
+    /// Click to select, nothing else
+    #[default]
+    Select,
+    /// Drag the selection along the ground plane
+    Move,
+    /// Drag left/right to turn the selection around Y
+    Rotate,
+    /// Drag up/down to grow or shrink the selection
+    Scale,
+    /// Build mode: click to place tiles
+    Place,
+    /// Click two points to measure between them
+    Measure,
+    /// Walk the scene with the player capsule
+    Play,
+}
+
+impl EditorMode {
+    pub const ALL: [EditorMode; 7] = [
+        EditorMode::Select,
+        EditorMode::Move,
+        EditorMode::Rotate,
+        EditorMode::Scale,
+        EditorMode::Place,
+        EditorMode::Measure,
+        EditorMode::Play,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            EditorMode::Select => "Select",
+            EditorMode::Move => "Move",
+            EditorMode::Rotate => "Rotate",
+            EditorMode::Scale => "Scale",
+            EditorMode::Place => "Place",
+            EditorMode::Measure => "Measure",
+            EditorMode::Play => "Play",
+        }
+    }
+}
+
+/// Which axes the inspector's transform fields (and the selection's axes gizmo) use.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum TransformSpace {
+    /// Relative to the parent, as stored (the same as World for top-level objects)
+    #[default]
+    Local,
+    World,
+}
+
+impl TransformSpace {
+    pub const ALL: [Self; 2] = [Self::Local, Self::World];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Local => "Local",
+            Self::World => "World",
+        }
+    }
+
+    /// `local` (relative to `parent`) as shown in this space.
+    pub fn shown(self, local: Transform, parent: Option<&GlobalTransform>) -> Transform {
+        match (self, parent) {
+            (Self::World, Some(parent)) => parent.mul_transform(local).compute_transform(),
+            _ => local,
+        }
+    }
+
+    /// Back from this space to the transform relative to `parent`.
+    pub fn to_local(self, shown: Transform, parent: Option<&GlobalTransform>) -> Transform {
+        match (self, parent) {
+            (Self::World, Some(parent)) => GlobalTransform::from(shown).reparented_to(parent),
+            _ => shown,
+        }
+    }
+}
+
+/// Pixels the cursor has to travel before a press turns into a drag (so clicks still just select)
+const DRAG_THRESHOLD_PX: f32 = 4.0;
+const ROTATE_DEG_PER_PX: f32 = 0.5;
+const SCALE_PER_PX: f32 = 0.01;
+
+/// `EditorMode` and the viewport drags for Move / Rotate / Scale.
+pub struct EditorModePlugin;
+impl Plugin for EditorModePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EditorMode>()
+            .init_resource::<EditorPrefs>()
+            .init_gizmo_group::<EditorGizmos>()
+            .add_systems(
+                Update,
+                (sync_build_mode, drag_transform, draw_proportional_radius),
+            );
+    }
+}
+
+/// Keep Place and `BuildMode` in step, whichever of them was switched (toolbar or the B key).
+fn sync_build_mode(mut mode: ResMut<EditorMode>, build: Option<ResMut<BuildMode>>) {
+    let Some(mut build) = build else {
+        return;
+    };
+    if build.is_changed() {
+        if build.enabled {
+            mode.set_if_neq(EditorMode::Place);
+        } else if *mode == EditorMode::Place {
+            *mode = EditorMode::Select;
+        }
+    } else if mode.is_changed() {
+        let enabled = *mode == EditorMode::Place;
+        if build.enabled != enabled {
+            build.enabled = enabled;
+        }
+    }
+}
+
+struct Drag {
+    press: Vec2,
+    /// Selection transform and ground-plane grab point, once the drag has started
+    start: Option<(Transform, Option<Vec3>)>,
+    /// Objects a proportional move pulls along: where they started and how much they follow
+    pulled: Vec<(Entity, Vec3, f32)>,
+}
+
+/// How much of a proportional move reaches an object `distance` away: all of it at the
+/// center, easing out to nothing at `radius`.
+fn proportional_falloff(distance: f32, radius: f32) -> f32 {
+    let t = (distance / radius.max(0.001)).clamp(0.0, 1.0);
+    1.0 - t * t * (3.0 - 2.0 * t)
+}
+
+fn drag_transform(
+    mode: Res<EditorMode>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    prefs: Res<EditorPrefs>,
+    mut ui_pointer: UiPointer,
+    q_window: Query<&Window, With<PrimaryWindow>>,
+    q_cam: Query<(&Camera, &GlobalTransform), With<OrbitCamera>>,
+    inspector: Option<Res<InspectorState>>,
+    q_selected: Query<Entity, (With<Selected>, With<Editable>)>,
+    q_others: Query<Entity, (With<Editable>, Without<Selected>)>,
+    mut q_tf: Query<&mut Transform>,
+    q_child_of: Query<&ChildOf>,
+    q_gt: Query<&GlobalTransform>,
+    mut drag: Local<Option<Drag>>,
+) {
+    if !matches!(
+        *mode,
+        EditorMode::Move | EditorMode::Rotate | EditorMode::Scale
+    ) || !mouse.pressed(MouseButton::Left)
+    {
+        *drag = None;
+        return;
+    }
+    let Some(cursor) = q_window.single().ok().and_then(|w| w.cursor_position()) else {
+        return;
+    };
+    if mouse.just_pressed(MouseButton::Left) {
+        let over_ui = ui_pointer.wants_pointer_in_primary();
+        *drag = (!over_ui).then_some(Drag {
+            press: cursor,
+            start: None,
+            pulled: Vec::new(),
+        });
+    }
+    let Some(drag) = drag.as_mut() else {
+        return;
+    };
+    // The press may have just changed the selection, so it's read once the drag starts. The
+    // inspector's object leads and the rest of the selection follows it, as with its fields
+    let active = inspector
+        .as_ref()
+        .and_then(|i| i.active())
+        .filter(|&e| q_selected.contains(e))
+        .or_else(|| q_selected.iter().next());
+    let Some((active, tf)) = active.and_then(|e| Some((e, *q_tf.get(e).ok()?))) else {
+        return;
+    };
+    let cam = q_cam.single().ok();
+    let ground_hit = |at: Vec2, height: f32| -> Option<Vec3> {
+        let (camera, cam_gt) = cam?;
+        let ray = camera.viewport_to_world(cam_gt, at).ok()?;
+        let t = ray.intersect_plane(Vec3::Y * height, InfinitePlane3d::new(Vec3::Y))?;
+        Some(ray.get_point(t))
+    };
+
+    let (start, grab) = match drag.start {
+        Some(start) => start,
+        None if cursor.distance(drag.press) >= DRAG_THRESHOLD_PX => {
+            let start = (tf, ground_hit(drag.press, tf.translation.y));
+            drag.start = Some(start);
+            if *mode == EditorMode::Move && prefs.proportional {
+                let radius = prefs.proportional_radius;
+                drag.pulled = q_others
+                    .iter()
+                    .filter_map(|e| {
+                        let other = q_tf.get(e).ok()?;
+                        let d = other.translation.distance(tf.translation);
+                        (d < radius)
+                            .then(|| (e, other.translation, proportional_falloff(d, radius)))
+                    })
+                    .collect();
+            }
+            start
+        }
+        None => return,
+    };
+    let snap = |v: f32, step: f32| {
+        if prefs.snap && step > 0.0 {
+            (v / step).round() * step
+        } else {
+            v
+        }
+    };
+
+    let mut new_tf = start;
+    match *mode {
+        EditorMode::Move => {
+            let (Some(grab), Some(hit)) = (grab, ground_hit(cursor, start.translation.y)) else {
+                return;
+            };
+            let delta = hit - grab;
+            new_tf.translation.x = snap(start.translation.x + delta.x, prefs.move_snap);
+            new_tf.translation.z = snap(start.translation.z + delta.z, prefs.move_snap);
+            let moved = new_tf.translation - start.translation;
+            for &(e, from, weight) in &drag.pulled {
+                if let Ok(mut other) = q_tf.get_mut(e) {
+                    other.translation = from + moved * weight;
+                }
+            }
+        }
+        EditorMode::Rotate => {
+            let deg = snap(
+                (cursor.x - drag.press.x) * ROTATE_DEG_PER_PX,
+                prefs.rotate_snap_deg,
+            );
+            new_tf.rotation = Quat::from_rotation_y(deg.to_radians()) * start.rotation;
+        }
+        EditorMode::Scale => {
+            // Dragging up grows, down shrinks; exponential so it feels the same at any size
+            let factor = (-(cursor.y - drag.press.y) * SCALE_PER_PX).exp();
+            let scale = start.scale * factor;
+            new_tf.scale = Vec3::new(
+                snap(scale.x, prefs.scale_snap),
+                snap(scale.y, prefs.scale_snap),
+                snap(scale.z, prefs.scale_snap),
+            )
+            .max(Vec3::splat(0.01));
+        }
+        _ => return,
+    }
+    if new_tf == tf {
+        return;
+    }
+    if let Ok(mut active_tf) = q_tf.get_mut(active) {
+        *active_tf = new_tf;
+    }
+    let others: Vec<Entity> = q_selected.iter().filter(|&e| e != active).collect();
+    let pivot = inspector.map_or_else(GroupPivot::default, |i| i.pivot());
+    move_selection_with(
+        active,
+        (tf, new_tf),
+        pivot,
+        &others,
+        &mut q_tf,
+        &q_child_of,
+        &q_gt,
+    );
+}
+
+/// Reach of proportional moves, around the selection, while in Move mode.
+fn draw_proportional_radius(
+    mut gizmos: Gizmos<EditorGizmos>,
+    mode: Res<EditorMode>,
+    prefs: Res<EditorPrefs>,
+    q_selected: Query<&GlobalTransform, (With<Selected>, With<Editable>)>,
+) {
+    if *mode != EditorMode::Move || !prefs.proportional {
+        return;
+    }
+    for gt in &q_selected {
+        gizmos.circle(
+            Isometry3d::new(gt.translation(), Quat::from_rotation_x(FRAC_PI_2)),
+            prefs.proportional_radius,
+            Color::srgba(1.0, 0.8, 0.3, 0.6),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPS: f32 = 1e-4;
+
+    #[test]
+    fn dragging_turns_the_whole_selection() {
+        let mut world = World::new();
+        world.insert_resource(EditorMode::Rotate);
+        world.init_resource::<EditorPrefs>();
+        let mut mouse = ButtonInput::<MouseButton>::default();
+        mouse.press(MouseButton::Left);
+        world.insert_resource(mouse);
+        let mut window = Window::default();
+        window.set_cursor_position(Some(Vec2::new(100.0, 100.0)));
+        let window = world.spawn((window, PrimaryWindow)).id();
+        let a = world.spawn((Transform::IDENTITY, Editable, Selected)).id();
+        let b = world
+            .spawn((Transform::from_xyz(2.0, 0.0, 0.0), Editable, Selected))
+            .id();
+
+        let drag = world.register_system(drag_transform);
+        world.run_system(drag).unwrap();
+        // Still held, a quarter turn's worth to the right
+        world.resource_mut::<ButtonInput<MouseButton>>().clear();
+        let quarter = Vec2::new(100.0 + 90.0 / ROTATE_DEG_PER_PX, 100.0);
+        world
+            .get_mut::<Window>(window)
+            .unwrap()
+            .set_cursor_position(Some(quarter));
+        world.run_system(drag).unwrap();
+
+        // Both turn, and the other one swings around the first
+        let turn = Quat::from_rotation_y(FRAC_PI_2);
+        let tf_a = world.get::<Transform>(a).unwrap();
+        assert!(tf_a.rotation.abs_diff_eq(turn, EPS));
+        assert!(tf_a.translation.abs_diff_eq(Vec3::ZERO, EPS));
+        let tf_b = world.get::<Transform>(b).unwrap();
+        assert!(tf_b.rotation.abs_diff_eq(turn, EPS));
+        assert!(tf_b.translation.abs_diff_eq(Vec3::new(0.0, 0.0, -2.0), EPS));
+    }
+}
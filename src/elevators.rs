@@ -0,0 +1,399 @@
+//! Elevators: any object can be made into a platform that travels between floor heights in Play
+//! mode, called with a button on each floor. The buttons are generated from the elevator's
+//! settings (they aren't scene objects); a player standing next to one presses the interact key
+//! (or a gamepad's south button) to call it, and pressing it while riding sends it on to the next
+//! floor. At each stop it waits a while before answering the next call. There are no door
+//! objects: a door that opens with the stop is up to the scene (a slider joint, or a script).
+
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use bevy::render::primitives::Aabb;
+use bevy_egui::egui;
+use bevy_rapier3d::prelude::{KinematicCharacterController, RigidBody};
+use serde::{Deserialize, Serialize};
+
+use crate::SceneEditState;
+use crate::accessibility::Described;
+use crate::asset_cache::{AssetCache, PlainMaterial};
+use crate::editor_mode::EditorMode;
+use crate::inspector::{Editable, Selected, SpawnKind, aabb_world};
+use crate::settings::KeyBindings;
+
+/// How close a player has to stand to press a call button
+const BUTTON_REACH: f32 = 1.2;
+/// How far above the platform's top a player still counts as riding it
+const RIDE_HEIGHT: f32 = 1.2;
+const BUTTON_SIZE: Vec3 = Vec3::new(0.2, 0.3, 0.1);
+
+/// Persisted elevator settings, on the platform object.
+#[derive(Component, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Elevator {
+    /// Heights of the stops above where the platform is placed (the first is usually 0)
+    pub floors: Vec<f32>,
+    /// Meters per second
+    pub speed: f32,
+    /// Seconds it waits at a floor before leaving again (named for the doors it has no objects
+    /// for, and kept so for saved scenes)
+    pub door_secs: f32,
+    /// Where each floor's call button stands, from the platform's center in its own
+    /// (unscaled) axes
+    pub button_offset: [f32; 3],
+}
+
+impl Default for Elevator {
+    fn default() -> Self {
+        Self {
+            floors: vec![0.0, 3.0],
+            speed: 1.5,
+            door_secs: 2.0,
+            button_offset: [1.5, 1.0, 0.0],
+        }
+    }
+}
+
+/// An elevator's state while playing.
+#[derive(Component)]
+struct ElevatorRun {
+    /// Where the platform stood when Play started (its floor heights count from here)
+    base: Vec3,
+    /// Floors asked for, in the order they'll be served
+    calls: VecDeque<usize>,
+    /// Seconds left waiting at the floor
+    open: f32,
+    /// How far the platform went this frame, to carry riders along
+    moved: Vec3,
+}
+
+/// A floor's call button, generated from its elevator.
+#[derive(Component)]
+struct CallButton {
+    elevator: Entity,
+    floor: usize,
+}
+
+pub struct ElevatorsPlugin;
+impl Plugin for ElevatorsPlugin {
+    fn build(&self, app: &mut App) {
+        let systems = (
+            sync_elevators_with_mode,
+            spawn_call_buttons,
+            press_call_buttons,
+            move_elevators,
+            carry_riders,
+            place_call_buttons,
+        )
+            .chain();
+        // Riders are carried on top of the walking and falling they did this frame
+        #[cfg(feature = "player")]
+        let systems = systems.after(crate::player::player_motion_with_gravity);
+        app.init_resource::<EditorMode>()
+            .init_resource::<KeyBindings>()
+            .init_resource::<AssetCache>()
+            .add_systems(Update, (systems, draw_elevator_gizmos));
+    }
+}
+
+impl Elevator {
+    /// Where the platform stands at `floor`, from where it's placed.
+    fn stop(&self, base: Vec3, floor: usize) -> Vec3 {
+        base + Vec3::Y * self.floors.get(floor).copied().unwrap_or(0.0)
+    }
+
+    fn button_at(&self, base: &Transform, floor: usize) -> Vec3 {
+        self.stop(base.translation, floor) + base.rotation * Vec3::from_array(self.button_offset)
+    }
+}
+
+/// Give elevators their run state (and a kinematic body) when Play starts; put them back at
+/// their place when it ends.
+fn sync_elevators_with_mode(
+    mut commands: Commands,
+    mode: Res<EditorMode>,
+    mut q: Query<(Entity, &mut Transform, Option<&ElevatorRun>), With<Elevator>>,
+) {
+    if !mode.is_changed() {
+        return;
+    }
+    for (e, mut tf, run) in &mut q {
+        if let Some(run) = run {
+            tf.translation = run.base;
+            commands.entity(e).remove::<(ElevatorRun, RigidBody)>();
+        }
+        if *mode == EditorMode::Play {
+            commands.entity(e).insert((
+                ElevatorRun {
+                    base: tf.translation,
+                    calls: VecDeque::new(),
+                    open: 0.0,
+                    moved: Vec3::ZERO,
+                },
+                RigidBody::KinematicPositionBased,
+            ));
+        }
+    }
+}
+
+/// Rebuild the buttons of new or edited elevators, and drop those of removed ones.
+fn spawn_call_buttons(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut cache: ResMut<AssetCache>,
+    q_changed: Query<(Entity, &Transform, &Elevator), Changed<Elevator>>,
+    q_elevators: Query<(), With<Elevator>>,
+    q_buttons: Query<(Entity, &CallButton)>,
+) {
+    for (e, button) in &q_buttons {
+        if !q_elevators.contains(button.elevator) || q_changed.contains(button.elevator) {
+            commands.entity(e).despawn();
+        }
+    }
+    for (e, tf, elevator) in &q_changed {
+        let mesh = cache.mesh(&mut meshes, SpawnKind::Cuboid);
+        let material = cache.material(&mut materials, button_material(false));
+        for floor in 0..elevator.floors.len() {
+            commands.spawn((
+                Mesh3d(mesh.clone()),
+                MeshMaterial3d(material.clone()),
+                Transform::from_translation(elevator.button_at(tf, floor))
+                    .with_rotation(tf.rotation)
+                    .with_scale(BUTTON_SIZE),
+                CallButton { elevator: e, floor },
+                Name::new(format!("Call button {}", floor + 1)),
+            ));
+        }
+    }
+}
+
+fn button_material(lit: bool) -> PlainMaterial {
+    PlainMaterial {
+        base_color: Color::srgb(0.35, 0.35, 0.38),
+        metallic: 0.6,
+        roughness: 0.4,
+        emissive: if lit {
+            LinearRgba::rgb(2.5, 1.4, 0.3)
+        } else {
+            LinearRgba::BLACK
+        },
+    }
+}
+
+/// Call an elevator to the floor of the button a player presses, or send it on from inside.
+fn press_call_buttons(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    q_pads: Query<&Gamepad>,
+    q_players: Query<&GlobalTransform, With<KinematicCharacterController>>,
+    q_buttons: Query<(&GlobalTransform, &CallButton)>,
+    mut q_elevators: Query<(&GlobalTransform, &Aabb, &Elevator, &mut ElevatorRun)>,
+) {
+    let pressed = keys.just_pressed(bindings.interact)
+        || q_pads
+            .iter()
+            .any(|pad| pad.just_pressed(GamepadButton::South));
+    if !pressed {
+        return;
+    }
+    for player in &q_players {
+        let at = player.translation();
+        let nearest = q_buttons
+            .iter()
+            .map(|(gt, b)| (gt.translation().distance(at), b))
+            .filter(|(d, _)| *d <= BUTTON_REACH)
+            .min_by(|a, b| a.0.total_cmp(&b.0));
+        if let Some((_, button)) = nearest {
+            if let Ok((.., elevator, mut run)) = q_elevators.get_mut(button.elevator) {
+                call(&mut run, elevator, button.floor);
+            }
+            continue;
+        }
+        // Riding: on to the next floor (back to the first from the top)
+        for (gt, aabb, elevator, mut run) in &mut q_elevators {
+            if rides(at, gt, aabb) && run.calls.is_empty() && !elevator.floors.is_empty() {
+                let next = (nearest_floor(elevator, &run, gt) + 1) % elevator.floors.len();
+                call(&mut run, elevator, next);
+            }
+        }
+    }
+}
+
+fn call(run: &mut ElevatorRun, elevator: &Elevator, floor: usize) {
+    if floor >= elevator.floors.len() || run.calls.contains(&floor) {
+        return;
+    }
+    run.calls.push_back(floor);
+}
+
+/// The floor the platform is at (or passing).
+fn nearest_floor(elevator: &Elevator, run: &ElevatorRun, gt: &GlobalTransform) -> usize {
+    let height = gt.translation().y - run.base.y;
+    (0..elevator.floors.len())
+        .min_by(|&a, &b| {
+            (elevator.floors[a] - height)
+                .abs()
+                .total_cmp(&(elevator.floors[b] - height).abs())
+        })
+        .unwrap_or(0)
+}
+
+/// Whether a player at `at` stands on the platform.
+fn rides(at: Vec3, gt: &GlobalTransform, aabb: &Aabb) -> bool {
+    let bounds = aabb_world(*aabb, gt);
+    let (center, half) = (Vec3::from(bounds.center), Vec3::from(bounds.half_extents));
+    let above = at.y - (center.y + half.y);
+    (at.x - center.x).abs() <= half.x
+        && (at.z - center.z).abs() <= half.z
+        && (0.0..=RIDE_HEIGHT).contains(&above)
+}
+
+/// Travel to the next floor asked for, then wait there.
+fn move_elevators(time: Res<Time>, mut q: Query<(&mut Transform, &Elevator, &mut ElevatorRun)>) {
+    let dt = time.delta_secs();
+    for (mut tf, elevator, mut run) in &mut q {
+        let before = tf.translation;
+        if run.open > 0.0 {
+            run.open -= dt;
+        } else if let Some(&floor) = run.calls.front() {
+            let to = elevator.stop(run.base, floor) - tf.translation;
+            let step = elevator.speed.max(0.01) * dt;
+            if to.length() <= step {
+                tf.translation += to;
+                run.calls.pop_front();
+                run.open = elevator.door_secs;
+            } else {
+                tf.translation += to.normalize() * step;
+            }
+        }
+        run.moved = tf.translation - before;
+    }
+}
+
+/// Move players standing on a moving platform along with it.
+fn carry_riders(
+    q_elevators: Query<(&GlobalTransform, &Aabb, &ElevatorRun)>,
+    mut q_players: Query<(&GlobalTransform, &mut KinematicCharacterController)>,
+) {
+    for (gt, aabb, run) in &q_elevators {
+        if run.moved == Vec3::ZERO {
+            continue;
+        }
+        for (player, mut kcc) in &mut q_players {
+            if rides(player.translation(), gt, aabb) {
+                let walked = kcc.translation.unwrap_or_default();
+                kcc.translation = Some(walked + run.moved);
+            }
+        }
+    }
+}
+
+/// Keep buttons at their floors (following the platform while it's edited) and light up the
+/// floors an elevator has been called to.
+fn place_call_buttons(
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut cache: ResMut<AssetCache>,
+    q_elevators: Query<(&Transform, &Elevator, Option<&ElevatorRun>), Without<CallButton>>,
+    mut q_buttons: Query<(
+        &CallButton,
+        &mut Transform,
+        &mut MeshMaterial3d<StandardMaterial>,
+    )>,
+) {
+    let unlit = cache.material(&mut materials, button_material(false));
+    let lit = cache.material(&mut materials, button_material(true));
+    for (button, mut tf, mut material) in &mut q_buttons {
+        let Ok((elevator_tf, elevator, run)) = q_elevators.get(button.elevator) else {
+            continue;
+        };
+        let base = Transform {
+            translation: run.map_or(elevator_tf.translation, |r| r.base),
+            ..*elevator_tf
+        };
+        let at = elevator.button_at(&base, button.floor);
+        if tf.translation != at || tf.rotation != base.rotation {
+            tf.translation = at;
+            tf.rotation = base.rotation;
+        }
+        let called = run.is_some_and(|r| r.calls.contains(&button.floor));
+        let want = if called { &lit } else { &unlit };
+        if material.0 != *want {
+            material.0 = want.clone();
+        }
+    }
+}
+
+/// Outline the selected elevator's stops.
+fn draw_elevator_gizmos(
+    mut gizmos: Gizmos,
+    edit_state: Res<SceneEditState>,
+    mode: Res<EditorMode>,
+    q: Query<(&Transform, &Elevator), (With<Selected>, With<Editable>)>,
+) {
+    if !edit_state.open || *mode == EditorMode::Play {
+        return;
+    }
+    let color = Color::srgba(0.95, 0.7, 0.3, 0.8);
+    for (tf, elevator) in &q {
+        for floor in 0..elevator.floors.len() {
+            let stop = Transform {
+                translation: elevator.stop(tf.translation, floor),
+                ..*tf
+            };
+            gizmos.cuboid(stop, color);
+        }
+    }
+}
+
+/// Inspector section: make the object an elevator and set its floors.
+pub fn elevator_inspector_ui(
+    ui: &mut egui::Ui,
+    commands: &mut Commands,
+    entity: Entity,
+    elevator: Option<Mut<Elevator>>,
+) -> bool {
+    let mut on = elevator.is_some();
+    if ui.checkbox(&mut on, "Elevator").changed() {
+        if on {
+            commands.entity(entity).insert(Elevator::default());
+        } else {
+            commands.entity(entity).remove::<Elevator>();
+        }
+        return true;
+    }
+    let Some(mut current) = elevator else {
+        return false;
+    };
+    let mut e = current.clone();
+
+    ui.label("Floor heights (from where it's placed):");
+    let mut remove = None;
+    for (i, height) in e.floors.iter_mut().enumerate() {
+        ui.horizontal(|ui| {
+            ui.label(format!("{}", i + 1));
+            ui.add(egui::DragValue::new(height).speed(0.05).suffix(" m"));
+            if ui.small_button("✖").described("Remove floor").clicked() {
+                remove = Some(i);
+            }
+        });
+    }
+    if let Some(i) = remove
+        && e.floors.len() > 1
+    {
+        e.floors.remove(i);
+    }
+    if ui.button("Add floor").clicked() {
+        let top = e.floors.iter().copied().fold(0.0, f32::max);
+        e.floors.push(top + 3.0);
+    }
+    ui.add(egui::Slider::new(&mut e.speed, 0.2..=8.0).text("Speed (m/s)"));
+    ui.add(egui::Slider::new(&mut e.door_secs, 0.0..=10.0).text("Wait at floors (s)"));
+    ui.horizontal(|ui| {
+        ui.label("Buttons at");
+        for v in &mut e.button_offset {
+            ui.add(egui::DragValue::new(v).speed(0.05));
+        }
+    });
+
+    // Only touch the component on edits, since any change rebuilds the buttons
+    current.set_if_neq(e)
+}
@@ -0,0 +1,151 @@
+use bevy::prelude::*;
+use bevy::transform::TransformSystem;
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+use serde::{Deserialize, Serialize};
+
+use crate::SceneEditState;
+use crate::inspector::Selected;
+
+/// Gently rocks a decor object (bushes, flags, reeds) around its authored pose.
+/// Amplitude is scaled by the global [`Wind`] strength.
+#[derive(Component, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WindSway {
+    pub amplitude: f32, // peak tilt in degrees at full wind strength
+    pub frequency: f32, // oscillations per second
+    pub phase: f32,     // radians; offsets neighbouring props so they don't move in lockstep
+}
+
+impl Default for WindSway {
+    fn default() -> Self {
+        Self {
+            amplitude: 6.0,
+            frequency: 0.6,
+            phase: 0.0,
+        }
+    }
+}
+
+/// Authored rotation of a swaying object and the rotation we wrote on top of it last frame.
+/// The sway is only present between PostUpdate and the next PreUpdate, so editing
+/// and scene saving always see the authored pose.
+#[derive(Component, Clone)]
+pub struct SwayRest {
+    rest: Quat,
+    swayed: Quat,
+}
+
+/// Global wind shared by every `WindSway` object (edited in the Environment panel).
+#[derive(Resource)]
+pub struct Wind {
+    pub direction_deg: f32, // heading on the XZ plane, 0° = +X
+    pub strength: f32,      // 0..1
+}
+
+impl Default for Wind {
+    fn default() -> Self {
+        Self {
+            direction_deg: 30.0,
+            strength: 0.5,
+        }
+    }
+}
+
+impl Wind {
+    /// Unit wind direction on the ground plane.
+    pub fn direction(&self) -> Vec3 {
+        let a = self.direction_deg.to_radians();
+        Vec3::new(a.cos(), 0.0, a.sin())
+    }
+}
+
+pub struct EnvironmentPlugin;
+impl Plugin for EnvironmentPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Wind>()
+            .add_systems(PreUpdate, restore_sway_rest)
+            .add_systems(
+                PostUpdate,
+                (init_sway_rest, apply_wind_sway)
+                    .chain()
+                    .before(TransformSystem::TransformPropagate),
+            )
+            .add_systems(EguiPrimaryContextPass, environment_panel);
+    }
+}
+
+fn init_sway_rest(
+    mut commands: Commands,
+    q: Query<(Entity, &Transform), (With<WindSway>, Without<SwayRest>)>,
+) {
+    for (e, tf) in &q {
+        commands.entity(e).insert(SwayRest {
+            rest: tf.rotation,
+            swayed: tf.rotation,
+        });
+    }
+}
+
+/// Undo last frame's sway so gameplay/editor systems work on the authored rotation.
+/// If something else wrote the rotation since (inspector, loader), keep theirs.
+fn restore_sway_rest(mut q: Query<(&SwayRest, &mut Transform)>) {
+    for (sway, mut tf) in &mut q {
+        if tf.rotation == sway.swayed {
+            tf.rotation = sway.rest;
+        }
+    }
+}
+
+fn apply_wind_sway(
+    time: Res<Time>,
+    wind: Res<Wind>,
+    mut q: Query<(&WindSway, &mut SwayRest, &mut Transform, Has<Selected>)>,
+) {
+    let t = time.elapsed_secs();
+    let dir = wind.direction();
+    // Rotating around (Y x dir) tips the object's up axis towards the wind direction
+    let axis = Vec3::Y.cross(dir).normalize_or_zero();
+
+    for (sway, mut rest, mut tf, selected) in &mut q {
+        rest.rest = tf.rotation;
+        // Selected objects hold still so the inspector edits the authored pose
+        if selected || wind.strength <= 0.0 || axis == Vec3::ZERO {
+            rest.swayed = tf.rotation;
+            continue;
+        }
+
+        // Spatial offset along the wind so a row of props ripples instead of moving in unison
+        let wave = tf.translation.dot(dir) * 0.5;
+        let osc = (t * sway.frequency * std::f32::consts::TAU + sway.phase - wave).sin();
+        // Lean with the wind and oscillate around that lean (never against it)
+        let angle = (sway.amplitude * wind.strength * (0.5 + 0.5 * osc)).to_radians();
+
+        tf.rotation = Quat::from_axis_angle(axis, angle) * rest.rest;
+        rest.swayed = tf.rotation;
+    }
+}
+
+/// egui panel: global environment settings (wind for now).
+fn environment_panel(
+    mut ctxs: EguiContexts,
+    mut wind: ResMut<Wind>,
+    edit_state: Res<SceneEditState>,
+) {
+    if !edit_state.open {
+        return;
+    }
+
+    let Ok(ctx) = ctxs.ctx_mut() else {
+        return;
+    };
+    egui::Window::new("Environment")
+        .default_open(false)
+        .default_width(260.0)
+        .show(ctx, |ui| {
+            ui.heading("Wind");
+            ui.add(egui::Slider::new(&mut wind.direction_deg, 0.0..=360.0).text("Direction (deg)"));
+            ui.add(egui::Slider::new(&mut wind.strength, 0.0..=1.0).text("Strength"));
+            if ui.button("Reset Wind").clicked() {
+                *wind = Wind::default();
+            }
+        });
+}
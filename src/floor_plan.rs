@@ -0,0 +1,624 @@
+//! Top-down blueprint of the scene, exported as SVG or PNG for reviewing layouts outside the app.
+//!
+//! Every solid editable (spline walls too) is drawn as the outline of its footprint, shaded
+//! lighter the higher its top is. Objects named `Spawn...` or `Goal...` (and the player's drop
+//! point) get markers.
+//! World +X points right and +Z down, the same way round as an imported Tiled map.
+
+use std::fmt::Write;
+
+use bevy::prelude::*;
+use bevy::render::primitives::Aabb;
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+
+use crate::SceneEditState;
+use crate::inspector::{Editable, EditableMesh, SpawnKind};
+use crate::scene_io;
+use crate::splines::{SplineMode, SplinePath, polyline};
+
+/// Empty space kept around the scene, in meters.
+const MARGIN: f32 = 1.0;
+/// PNG exports are capped to this many pixels per side.
+#[cfg(not(target_arch = "wasm32"))]
+const MAX_PNG_SIZE: f32 = 4096.0;
+
+const BACKGROUND: [f32; 3] = [0.09, 0.17, 0.32];
+const GRID: [f32; 3] = [0.14, 0.24, 0.42];
+/// Fill of the lowest and the highest tops
+const LOW: [f32; 3] = [0.16, 0.30, 0.52];
+const HIGH: [f32; 3] = [0.66, 0.80, 0.96];
+const OUTLINE: [f32; 3] = [0.95, 0.97, 1.0];
+const SPAWN: [f32; 3] = [0.30, 0.85, 0.40];
+const GOAL: [f32; 3] = [1.0, 0.78, 0.20];
+
+/// Write a floor plan of the scene to `path` (`.svg`, or `.png` natively).
+#[derive(Event)]
+pub struct ExportFloorPlanEvent {
+    pub path: String,
+    /// Pixels per meter (PNG) or user units per meter (SVG)
+    pub scale: f32,
+}
+
+#[derive(Resource)]
+struct FloorPlanState {
+    path: String,
+    scale: f32,
+    status: Option<String>,
+}
+
+impl Default for FloorPlanState {
+    fn default() -> Self {
+        Self {
+            path: "floor_plan.svg".into(),
+            scale: 32.0,
+            status: None,
+        }
+    }
+}
+
+pub struct FloorPlanPlugin;
+impl Plugin for FloorPlanPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FloorPlanState>()
+            .add_event::<ExportFloorPlanEvent>()
+            .add_systems(Update, export_floor_plan)
+            .add_systems(EguiPrimaryContextPass, floor_plan_window);
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum MarkerKind {
+    Spawn,
+    Goal,
+}
+
+/// One object seen from above.
+struct Footprint {
+    /// Convex outline on the XZ plane, counter-clockwise
+    outline: Vec<Vec2>,
+    top: f32,
+}
+
+struct FloorPlan {
+    footprints: Vec<Footprint>,
+    /// Objects drawn (a wall is several footprints)
+    objects: usize,
+    markers: Vec<(Vec2, MarkerKind)>,
+    min: Vec2,
+    max: Vec2,
+    top_range: (f32, f32),
+}
+
+impl FloorPlan {
+    fn size(&self) -> Vec2 {
+        self.max - self.min
+    }
+
+    /// Fill color of a footprint with this top height.
+    fn shade(&self, top: f32) -> [f32; 3] {
+        let (low, high) = self.top_range;
+        let t = if high > low {
+            (top - low) / (high - low)
+        } else {
+            1.0
+        };
+        mix(LOW, HIGH, t)
+    }
+}
+
+fn mix(a: [f32; 3], b: [f32; 3], t: f32) -> [f32; 3] {
+    [0, 1, 2].map(|i| a[i] + (b[i] - a[i]) * t)
+}
+
+/// Andrew's monotone chain; the result is counter-clockwise.
+fn convex_hull(mut points: Vec<Vec2>) -> Vec<Vec2> {
+    points.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    points.dedup_by(|a, b| a.distance_squared(*b) < 1e-10);
+    if points.len() < 3 {
+        return points;
+    }
+    let mut hull: Vec<Vec2> = Vec::with_capacity(points.len() * 2);
+    for pass in 0..2 {
+        let start = hull.len();
+        for &p in &points {
+            while hull.len() >= start + 2 {
+                let (a, b) = (hull[hull.len() - 2], hull[hull.len() - 1]);
+                if (b - a).perp_dot(p - a) > 0.0 {
+                    break;
+                }
+                hull.pop();
+            }
+            hull.push(p);
+        }
+        // The last point is the first of the other half
+        hull.pop();
+        if pass == 0 {
+            points.reverse();
+        }
+    }
+    hull
+}
+
+/// Objects that stand in the world (lights, volumes, decals and paths don't). Splines only do
+/// as walls, which [`wall_footprints`] draws.
+fn is_solid(kind: SpawnKind) -> bool {
+    matches!(
+        kind,
+        SpawnKind::Cuboid
+            | SpawnKind::Sphere
+            | SpawnKind::Plane
+            | SpawnKind::Prism
+            | SpawnKind::Stairs
+            | SpawnKind::Custom(_)
+    )
+}
+
+/// A spline wall as one footprint per polyline segment, since a curved wall isn't convex.
+fn wall_footprints(gt: &GlobalTransform, spline: &SplinePath) -> Vec<Footprint> {
+    let line = polyline(&spline.points);
+    let up = Vec3::Y * spline.height;
+    line.windows(2)
+        .filter_map(|seg| {
+            let (a, b) = (seg[0], seg[1]);
+            let dir = Vec3::new(b.x - a.x, 0.0, b.z - a.z).try_normalize()?;
+            let side = Vec3::Y.cross(dir) * (0.5 * spline.width);
+            let corners = [a + side, a - side, b + side, b - side];
+            let top = corners
+                .iter()
+                .map(|&p| gt.transform_point(p + up).y)
+                .fold(f32::MIN, f32::max);
+            Some(Footprint {
+                outline: convex_hull(corners.map(|p| gt.transform_point(p).xz()).to_vec()),
+                top,
+            })
+        })
+        .collect()
+}
+
+fn marker_kind(name: &str) -> Option<MarkerKind> {
+    let name = name.to_ascii_lowercase();
+    if name.starts_with("spawn") {
+        Some(MarkerKind::Spawn)
+    } else if name.starts_with("goal") {
+        Some(MarkerKind::Goal)
+    } else {
+        None
+    }
+}
+
+fn collect_plan(
+    q_objects: &Query<(&GlobalTransform, &Aabb, &EditableMesh, Option<&Name>), With<Editable>>,
+    q_splines: &Query<(&GlobalTransform, &SplinePath), With<Editable>>,
+) -> Option<FloorPlan> {
+    let mut footprints = Vec::new();
+    let mut markers = Vec::new();
+    let mut objects = 0;
+    for (gt, spline) in q_splines {
+        if spline.mode == SplineMode::Wall {
+            footprints.extend(wall_footprints(gt, spline));
+            objects += 1;
+        }
+    }
+    for (gt, aabb, mesh, name) in q_objects {
+        let center = gt.translation();
+        if let Some(kind) = name.and_then(|n| marker_kind(n.as_str())) {
+            markers.push((center.xz(), kind));
+        }
+        if !is_solid(mesh.kind) {
+            continue;
+        }
+        let (c, h) = (Vec3::from(aabb.center), Vec3::from(aabb.half_extents));
+        let corners: Vec<Vec3> = (0..8)
+            .map(|i| {
+                let sign = Vec3::new(
+                    if i & 1 == 0 { -1.0 } else { 1.0 },
+                    if i & 2 == 0 { -1.0 } else { 1.0 },
+                    if i & 4 == 0 { -1.0 } else { 1.0 },
+                );
+                gt.transform_point(c + h * sign)
+            })
+            .collect();
+        let top = corners.iter().map(|p| p.y).fold(f32::MIN, f32::max);
+        footprints.push(Footprint {
+            outline: convex_hull(corners.iter().map(|p| p.xz()).collect()),
+            top,
+        });
+        objects += 1;
+    }
+    #[cfg(feature = "player")]
+    if !markers.iter().any(|(_, k)| *k == MarkerKind::Spawn) {
+        markers.push((crate::player::PLAYER_START.xz(), MarkerKind::Spawn));
+    }
+
+    let points = footprints
+        .iter()
+        .flat_map(|f| f.outline.iter().copied())
+        .chain(markers.iter().map(|(p, _)| *p));
+    let (min, max) = points.fold(None, |acc: Option<(Vec2, Vec2)>, p| {
+        Some(acc.map_or((p, p), |(lo, hi)| (lo.min(p), hi.max(p))))
+    })?;
+    let top_range = footprints.iter().fold((f32::MAX, f32::MIN), |(lo, hi), f| {
+        (lo.min(f.top), hi.max(f.top))
+    });
+    // Draw low objects first so higher ones cover them
+    footprints.sort_by(|a, b| a.top.total_cmp(&b.top));
+    Some(FloorPlan {
+        footprints,
+        objects,
+        markers,
+        min: (min - MARGIN).floor(),
+        max: (max + MARGIN).ceil(),
+        top_range,
+    })
+}
+
+fn svg_color(c: [f32; 3]) -> String {
+    let [r, g, b] = c.map(|v| (v.clamp(0.0, 1.0) * 255.0).round() as u8);
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+fn to_svg(plan: &FloorPlan, scale: f32) -> String {
+    let size = plan.size() * scale;
+    let at = |p: Vec2| (p - plan.min) * scale;
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="0 0 {} {}">"#,
+        size.x, size.y, size.x, size.y
+    );
+    let _ = writeln!(
+        svg,
+        r#"<rect width="100%" height="100%" fill="{}"/>"#,
+        svg_color(BACKGROUND)
+    );
+
+    // One-meter grid
+    let _ = writeln!(svg, r#"<g stroke="{}" stroke-width="1">"#, svg_color(GRID));
+    for x in plan.min.x as i32..=plan.max.x as i32 {
+        let sx = (x as f32 - plan.min.x) * scale;
+        let _ = writeln!(svg, r#"<line x1="{sx}" y1="0" x2="{sx}" y2="{}"/>"#, size.y);
+    }
+    for z in plan.min.y as i32..=plan.max.y as i32 {
+        let sz = (z as f32 - plan.min.y) * scale;
+        let _ = writeln!(svg, r#"<line x1="0" y1="{sz}" x2="{}" y2="{sz}"/>"#, size.x);
+    }
+    let _ = writeln!(svg, "</g>");
+
+    let _ = writeln!(
+        svg,
+        r#"<g stroke="{}" stroke-width="1.5" stroke-linejoin="round">"#,
+        svg_color(OUTLINE)
+    );
+    for f in &plan.footprints {
+        let points: Vec<String> = f
+            .outline
+            .iter()
+            .map(|&p| {
+                let s = at(p);
+                format!("{:.2},{:.2}", s.x, s.y)
+            })
+            .collect();
+        let _ = writeln!(
+            svg,
+            r#"<polygon points="{}" fill="{}"><title>top {:.2} m</title></polygon>"#,
+            points.join(" "),
+            svg_color(plan.shade(f.top)),
+            f.top
+        );
+    }
+    let _ = writeln!(svg, "</g>");
+
+    let radius = 0.3 * scale;
+    for &(p, kind) in &plan.markers {
+        let (color, letter) = match kind {
+            MarkerKind::Spawn => (SPAWN, "S"),
+            MarkerKind::Goal => (GOAL, "G"),
+        };
+        let s = at(p);
+        let _ = writeln!(
+            svg,
+            r#"<circle cx="{:.2}" cy="{:.2}" r="{radius:.2}" fill="{}" stroke="{}" stroke-width="1.5"/>"#,
+            s.x,
+            s.y,
+            svg_color(color),
+            svg_color(BACKGROUND)
+        );
+        let _ = writeln!(
+            svg,
+            r#"<text x="{:.2}" y="{:.2}" font-family="sans-serif" font-size="{radius:.2}" font-weight="bold" text-anchor="middle" dominant-baseline="central" fill="{}">{letter}</text>"#,
+            s.x,
+            s.y,
+            svg_color(BACKGROUND)
+        );
+    }
+
+    let (low, high) = plan.top_range;
+    if low <= high {
+        let _ = writeln!(
+            svg,
+            r#"<text x="6" y="{:.2}" font-family="sans-serif" font-size="12" fill="{}">tops {low:.1} m (dark) to {high:.1} m (light), grid 1 m</text>"#,
+            size.y - 6.0,
+            svg_color(OUTLINE)
+        );
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Software raster of the plan, RGBA8 rows top to bottom.
+#[cfg(not(target_arch = "wasm32"))]
+struct Raster {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Raster {
+    fn new(width: u32, height: u32, color: [f32; 3]) -> Self {
+        let mut raster = Self {
+            width,
+            height,
+            pixels: vec![255; (width * height * 4) as usize],
+        };
+        for y in 0..height {
+            for x in 0..width {
+                raster.put(x as i32, y as i32, color);
+            }
+        }
+        raster
+    }
+
+    fn put(&mut self, x: i32, y: i32, color: [f32; 3]) {
+        if x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32 {
+            return;
+        }
+        let i = ((y as u32 * self.width + x as u32) * 4) as usize;
+        let [r, g, b] = color.map(|v| (v.clamp(0.0, 1.0) * 255.0).round() as u8);
+        self.pixels[i..i + 3].copy_from_slice(&[r, g, b]);
+    }
+
+    fn line(&mut self, a: Vec2, b: Vec2, color: [f32; 3]) {
+        let steps = (b - a).abs().max_element().ceil().max(1.0) as i32;
+        for i in 0..=steps {
+            let p = a.lerp(b, i as f32 / steps as f32);
+            self.put(p.x.floor() as i32, p.y.floor() as i32, color);
+        }
+    }
+
+    /// Fill a convex, counter-clockwise polygon (clockwise on screen, where y points down).
+    fn fill_convex(&mut self, poly: &[Vec2], color: [f32; 3]) {
+        if poly.len() < 3 {
+            return;
+        }
+        let (lo, hi) = poly.iter().fold((Vec2::MAX, Vec2::MIN), |(lo, hi), &p| {
+            (lo.min(p), hi.max(p))
+        });
+        for y in lo.y.floor() as i32..=hi.y.ceil() as i32 {
+            for x in lo.x.floor() as i32..=hi.x.ceil() as i32 {
+                let p = Vec2::new(x as f32 + 0.5, y as f32 + 0.5);
+                let inside = poly
+                    .iter()
+                    .zip(poly.iter().cycle().skip(1))
+                    .all(|(&a, &b)| (b - a).perp_dot(p - a) >= 0.0);
+                if inside {
+                    self.put(x, y, color);
+                }
+            }
+        }
+    }
+
+    fn disc(&mut self, center: Vec2, radius: f32, color: [f32; 3]) {
+        let r = radius.ceil() as i32;
+        for dy in -r..=r {
+            for dx in -r..=r {
+                let p = Vec2::new(dx as f32, dy as f32);
+                if p.length() <= radius {
+                    self.put(center.x as i32 + dx, center.y as i32 + dy, color);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn to_png(plan: &FloorPlan, scale: f32, path: &str) -> Result<(), String> {
+    use bevy::render::render_asset::RenderAssetUsages;
+    use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+
+    let scale = scale.min(MAX_PNG_SIZE / plan.size().max_element());
+    let size = (plan.size() * scale).ceil().as_uvec2().max(UVec2::ONE);
+    let at = |p: Vec2| (p - plan.min) * scale;
+    let mut raster = Raster::new(size.x, size.y, BACKGROUND);
+
+    for x in plan.min.x as i32..=plan.max.x as i32 {
+        let sx = (x as f32 - plan.min.x) * scale;
+        raster.line(Vec2::new(sx, 0.0), Vec2::new(sx, size.y as f32), GRID);
+    }
+    for z in plan.min.y as i32..=plan.max.y as i32 {
+        let sz = (z as f32 - plan.min.y) * scale;
+        raster.line(Vec2::new(0.0, sz), Vec2::new(size.x as f32, sz), GRID);
+    }
+    for f in &plan.footprints {
+        let outline: Vec<Vec2> = f.outline.iter().map(|&p| at(p)).collect();
+        raster.fill_convex(&outline, plan.shade(f.top));
+        for (&a, &b) in outline.iter().zip(outline.iter().cycle().skip(1)) {
+            raster.line(a, b, OUTLINE);
+        }
+    }
+    for &(p, kind) in &plan.markers {
+        let color = match kind {
+            MarkerKind::Spawn => SPAWN,
+            MarkerKind::Goal => GOAL,
+        };
+        raster.disc(at(p), 0.3 * scale + 1.5, BACKGROUND);
+        raster.disc(at(p), 0.3 * scale, color);
+    }
+
+    let image = Image::new(
+        Extent3d {
+            width: raster.width,
+            height: raster.height,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        raster.pixels,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    image
+        .try_into_dynamic()
+        .map_err(|e| e.to_string())?
+        .save(path)
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(target_arch = "wasm32")]
+fn to_png(_: &FloorPlan, _: f32, _: &str) -> Result<(), String> {
+    Err("PNG export is only available natively; use .svg".into())
+}
+
+fn export_floor_plan(
+    mut events: EventReader<ExportFloorPlanEvent>,
+    mut state: ResMut<FloorPlanState>,
+    q_objects: Query<(&GlobalTransform, &Aabb, &EditableMesh, Option<&Name>), With<Editable>>,
+    q_splines: Query<(&GlobalTransform, &SplinePath), With<Editable>>,
+) {
+    for ev in events.read() {
+        let Some(plan) = collect_plan(&q_objects, &q_splines) else {
+            state.status = Some("Nothing to draw".into());
+            continue;
+        };
+        let scale = ev.scale.max(1.0);
+        let result = if ev.path.to_ascii_lowercase().ends_with(".png") {
+            to_png(&plan, scale, &ev.path)
+        } else {
+            // In the browser this downloads the file
+            scene_io::write_scene(&ev.path, &to_svg(&plan, scale))
+        };
+        state.status = Some(match result {
+            Ok(()) => format!(
+                "Wrote {} ({} objects, {:.0} x {:.0} m)",
+                ev.path,
+                plan.objects,
+                plan.size().x,
+                plan.size().y
+            ),
+            Err(e) => {
+                eprintln!("Floor plan: {e}");
+                format!("Export failed: {e}")
+            }
+        });
+    }
+}
+
+fn floor_plan_window(
+    mut ctxs: EguiContexts,
+    edit_state: Res<SceneEditState>,
+    mut state: ResMut<FloorPlanState>,
+    mut ev_export: EventWriter<ExportFloorPlanEvent>,
+) {
+    if !edit_state.open {
+        return;
+    }
+    let Ok(ctx) = ctxs.ctx_mut() else {
+        return;
+    };
+    egui::Window::new("Floor Plan")
+        .default_open(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("File:");
+                ui.add(egui::TextEdit::singleline(&mut state.path).desired_width(200.0));
+            });
+            ui.add(egui::Slider::new(&mut state.scale, 8.0..=128.0).text("px per meter"));
+            ui.small("Top-down schematic; .svg or .png by extension.");
+            let path = state.path.trim().to_string();
+            if ui
+                .add_enabled(!path.is_empty(), egui::Button::new("Export"))
+                .clicked()
+            {
+                ev_export.write(ExportFloorPlanEvent {
+                    path,
+                    scale: state.scale,
+                });
+            }
+            if let Some(status) = &state.status {
+                ui.label(status);
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hull_drops_inner_and_collinear_points() {
+        let points = vec![
+            Vec2::new(0.0, 0.0),
+            Vec2::new(1.0, 0.0),
+            Vec2::new(2.0, 0.0),
+            Vec2::new(2.0, 2.0),
+            Vec2::new(0.0, 2.0),
+            Vec2::new(1.0, 1.0),
+            Vec2::new(2.0, 2.0),
+        ];
+        let hull = convex_hull(points);
+        assert_eq!(
+            hull,
+            [
+                Vec2::new(0.0, 0.0),
+                Vec2::new(2.0, 0.0),
+                Vec2::new(2.0, 2.0),
+                Vec2::new(0.0, 2.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn hull_is_counter_clockwise() {
+        let points = (0..12)
+            .map(|i| Vec2::from_angle(i as f32 * 0.5) * (1.0 + (i % 3) as f32))
+            .collect();
+        let hull = convex_hull(points);
+        assert!(hull.len() >= 3);
+        for i in 0..hull.len() {
+            let (a, b, c) = (
+                hull[i],
+                hull[(i + 1) % hull.len()],
+                hull[(i + 2) % hull.len()],
+            );
+            assert!((b - a).perp_dot(c - b) > 0.0, "turns clockwise at {b}");
+        }
+    }
+
+    #[test]
+    fn hull_of_too_few_points_is_the_points() {
+        assert!(convex_hull(Vec::new()).is_empty());
+        let two = vec![Vec2::ONE, Vec2::ZERO];
+        assert_eq!(convex_hull(two), [Vec2::ZERO, Vec2::ONE]);
+    }
+
+    #[test]
+    fn straight_walls_cover_their_thickness_and_height() {
+        let spline = SplinePath {
+            points: vec![[0.0, 0.0, 0.0], [4.0, 0.0, 0.0]],
+            mode: SplineMode::Wall,
+            width: 0.5,
+            height: 2.0,
+            ..default()
+        };
+        let gt = GlobalTransform::from_translation(Vec3::new(0.0, 1.0, 10.0));
+        let footprints = wall_footprints(&gt, &spline);
+        assert!(!footprints.is_empty());
+        for f in &footprints {
+            assert_eq!(f.top, 3.0);
+            for p in &f.outline {
+                assert!((0.0..=4.0).contains(&p.x), "{p}");
+                assert!((p.y - 10.0).abs() <= 0.25 + 1e-5, "{p}");
+            }
+        }
+    }
+}
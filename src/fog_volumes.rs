@@ -0,0 +1,214 @@
+//! Fog volumes: boxes of volumetric fog placed in the scene, each with its own color, density
+//! and a falloff that thins it out towards the top of the box. They have no mesh, so the editor
+//! draws their outline. Volumetric fog costs a raymarch per frame, so cameras only get it while
+//! a volume exists, and only in perspective.
+
+use bevy::{
+    image::{ImageAddressMode, ImageSampler, ImageSamplerDescriptor},
+    pbr::{FogVolume, VolumetricFog, VolumetricLight},
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+};
+use bevy_egui::egui;
+use serde::{Deserialize, Serialize};
+
+use crate::SceneEditState;
+use crate::camera::OrbitCamera;
+use crate::inspector::{Editable, EditableMesh, Selected, SpawnKind};
+
+/// Vertical resolution of the generated height-falloff density texture.
+const FALLOFF_TEXELS: u32 = 32;
+
+/// Persisted parameters for an author-placed fog volume.
+/// The box itself is the entity's Transform (a unit cube, scaled/rotated).
+#[derive(Component, Clone, Copy, Serialize, Deserialize)]
+pub struct SceneFogVolume {
+    pub color: [f32; 3], // sRGB
+    pub density: f32,
+    pub height_falloff: f32, // 0 = uniform; higher = mist settles at the bottom of the box
+}
+
+impl Default for SceneFogVolume {
+    fn default() -> Self {
+        Self {
+            color: [0.9, 0.93, 0.97],
+            density: 0.4,
+            height_falloff: 3.0,
+        }
+    }
+}
+
+pub struct FogVolumesPlugin;
+impl Plugin for FogVolumesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                sync_fog_volumes,
+                toggle_volumetric_rendering,
+                draw_fog_volume_gizmos,
+            ),
+        );
+    }
+}
+
+/// Spawn an editable fog volume (no mesh; drawn as a gizmo box in the editor).
+pub fn spawn_fog_volume_object(
+    commands: &mut Commands,
+    tf: Transform,
+    settings: SceneFogVolume,
+) -> Entity {
+    commands
+        .spawn((
+            Editable,
+            Name::new("Fog Volume"),
+            tf,
+            EditableMesh {
+                kind: SpawnKind::FogVolume,
+                collider: None,
+            },
+            settings,
+            FogVolume::default(),
+        ))
+        .id()
+}
+
+/// 1 x N x 1 density ramp: dense at the bottom of the box, thinning out with height.
+fn falloff_data(height_falloff: f32) -> Vec<u8> {
+    (0..FALLOFF_TEXELS)
+        .map(|i| {
+            let y = (i as f32 + 0.5) / FALLOFF_TEXELS as f32;
+            ((-height_falloff * y).exp() * 255.0).round() as u8
+        })
+        .collect()
+}
+
+fn falloff_image(height_falloff: f32) -> Image {
+    let mut image = Image::new(
+        Extent3d {
+            width: 1,
+            height: FALLOFF_TEXELS,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D3,
+        falloff_data(height_falloff),
+        TextureFormat::R8Unorm,
+        RenderAssetUsages::default(),
+    );
+    // Clamp so the top of the box doesn't wrap around to the dense bottom row
+    image.sampler = ImageSampler::Descriptor(ImageSamplerDescriptor {
+        address_mode_u: ImageAddressMode::ClampToEdge,
+        address_mode_v: ImageAddressMode::ClampToEdge,
+        address_mode_w: ImageAddressMode::ClampToEdge,
+        ..ImageSamplerDescriptor::linear()
+    });
+    image
+}
+
+/// Push authored settings into Bevy's `FogVolume` (color, density, height falloff texture).
+fn sync_fog_volumes(
+    mut images: ResMut<Assets<Image>>,
+    mut q: Query<(&SceneFogVolume, &mut FogVolume), Changed<SceneFogVolume>>,
+) {
+    for (settings, mut volume) in &mut q {
+        let [r, g, b] = settings.color;
+        volume.fog_color = Color::srgb(r, g, b);
+        volume.density_factor = settings.density.max(0.0);
+
+        if settings.height_falloff <= 0.0 {
+            volume.density_texture = None;
+            continue;
+        }
+        // Rewrite the existing ramp in place when possible
+        if let Some(image) = volume
+            .density_texture
+            .as_ref()
+            .and_then(|h| images.get_mut(h))
+        {
+            image.data = Some(falloff_data(settings.height_falloff));
+        } else {
+            volume.density_texture = Some(images.add(falloff_image(settings.height_falloff)));
+        }
+    }
+}
+
+/// Only pay for volumetric fog while fog volumes exist. Bevy's raymarch assumes a
+/// perspective view, so the main camera only gets it in perspective mode ('P').
+fn toggle_volumetric_rendering(
+    mut commands: Commands,
+    q_volumes: Query<(), With<SceneFogVolume>>,
+    q_cam: Query<(Entity, &Projection, Has<VolumetricFog>), With<OrbitCamera>>,
+    q_sun: Query<(Entity, Has<VolumetricLight>), With<DirectionalLight>>,
+) {
+    let any = !q_volumes.is_empty();
+
+    for (e, proj, has_fog) in &q_cam {
+        let want = any && matches!(proj, Projection::Perspective(_));
+        if want && !has_fog {
+            commands.entity(e).insert(VolumetricFog {
+                // Ambient only; the sun adds light shafts through `VolumetricLight`
+                ambient_intensity: 0.2,
+                ..default()
+            });
+        } else if !want && has_fog {
+            commands.entity(e).remove::<VolumetricFog>();
+        }
+    }
+
+    for (e, has_light) in &q_sun {
+        if any && !has_light {
+            commands.entity(e).insert(VolumetricLight);
+        } else if !any && has_light {
+            commands.entity(e).remove::<VolumetricLight>();
+        }
+    }
+}
+
+/// Fog volumes have no mesh; show their box in the editor.
+fn draw_fog_volume_gizmos(
+    mut gizmos: Gizmos,
+    edit_state: Res<SceneEditState>,
+    q: Query<(&GlobalTransform, &SceneFogVolume, Has<Selected>), With<Editable>>,
+) {
+    if !edit_state.open {
+        return;
+    }
+    for (gt, settings, selected) in &q {
+        let color = if selected {
+            Color::srgb(1.0, 0.85, 0.2)
+        } else {
+            let [r, g, b] = settings.color;
+            Color::srgba(r, g, b, 0.6)
+        };
+        gizmos.cuboid(gt.compute_transform(), color);
+        // Click target: volumes are picked by their center so objects inside stay clickable
+        gizmos.sphere(Isometry3d::from_translation(gt.translation()), 0.15, color);
+    }
+}
+
+/// Inspector section for fog volume parameters.
+pub fn fog_volume_inspector_ui(ui: &mut egui::Ui, mut settings: Mut<SceneFogVolume>) {
+    ui.heading("Fog volume");
+
+    let mut s = *settings;
+    let mut changed = false;
+    ui.horizontal(|ui| {
+        ui.label("Color");
+        changed |= ui.color_edit_button_rgb(&mut s.color).changed();
+    });
+    changed |= ui
+        .add(egui::Slider::new(&mut s.density, 0.0..=2.0).text("Density"))
+        .changed();
+    changed |= ui
+        .add(egui::Slider::new(&mut s.height_falloff, 0.0..=10.0).text("Height falloff"))
+        .changed();
+    ui.label("Visible in perspective view ('P').");
+
+    // Only touch the component on edits so the density texture isn't rebuilt every frame
+    if changed {
+        *settings = s;
+    }
+}
@@ -0,0 +1,182 @@
+//! Editor reference grid and axes widget. The grid is one large plane at Y=0 that follows the
+//! camera, with its lines drawn by a shader from world coordinates: cells as wide as the move
+//! snap, a heavier line every few cells and a fade with distance. The widget shows which way
+//! the world axes point from the current view.
+
+use bevy::{
+    pbr::{Material, MaterialPlugin, NotShadowCaster, NotShadowReceiver},
+    prelude::*,
+    render::render_resource::{AsBindGroup, ShaderRef},
+};
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+
+use crate::SceneEditState;
+use crate::camera::OrbitCamera;
+use crate::dock::DockSet;
+use crate::settings::EditorPrefs;
+
+const SHADER_ASSET_PATH: &str = "shaders/editor_grid.wgsl";
+
+/// Side of the grid plane; it follows the camera, and the shader fades out well before the edge
+const GRID_EXTENT: f32 = 2000.0;
+/// Grid lines fade out at this many camera heights from the camera
+const GRID_FADE_FACTOR: f32 = 12.0;
+const AXES_WIDGET_RADIUS: f32 = 32.0;
+
+/// Reference grid on the ground plane, drawn by the fragment shader from world coordinates.
+#[derive(Asset, TypePath, AsBindGroup, Clone)]
+pub struct GridMaterial {
+    /// x = cell size, y = cells per major line, z = fade factor, w = opacity
+    #[uniform(0)]
+    pub params: Vec4,
+    #[uniform(1)]
+    pub color: Vec4,
+}
+
+impl Material for GridMaterial {
+    fn fragment_shader() -> ShaderRef {
+        SHADER_ASSET_PATH.into()
+    }
+
+    fn alpha_mode(&self) -> AlphaMode {
+        AlphaMode::Blend
+    }
+
+    // Ground tiles sitting on Y=0 would z-fight with the grid
+    fn depth_bias(&self) -> f32 {
+        1.0
+    }
+}
+
+/// Tag for the grid plane.
+#[derive(Component)]
+pub struct EditorGrid;
+
+/// Infinite-looking grid at Y=0 (cell size follows the move snap) and an axes widget in the
+/// viewport corner, both toggled in Preferences.
+pub struct GridPlugin;
+impl Plugin for GridPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(MaterialPlugin::<GridMaterial>::default())
+            .init_resource::<EditorPrefs>()
+            .add_systems(Startup, spawn_grid)
+            .add_systems(Update, update_grid)
+            .add_systems(EguiPrimaryContextPass, axes_widget.after(DockSet::Effects));
+    }
+}
+
+fn grid_params(prefs: &EditorPrefs) -> Vec4 {
+    Vec4::new(
+        prefs.move_snap.max(0.01),
+        prefs.grid_major_every.max(1) as f32,
+        GRID_FADE_FACTOR,
+        1.0,
+    )
+}
+
+fn spawn_grid(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<GridMaterial>>,
+    prefs: Res<EditorPrefs>,
+) {
+    commands.spawn((
+        Mesh3d(meshes.add(Plane3d::default().mesh().size(GRID_EXTENT, GRID_EXTENT))),
+        MeshMaterial3d(materials.add(GridMaterial {
+            params: grid_params(&prefs),
+            color: Vec4::new(0.55, 0.55, 0.6, 1.0),
+        })),
+        Transform::default(),
+        NotShadowCaster,
+        NotShadowReceiver,
+        EditorGrid,
+        Name::new("EditorGrid"),
+    ));
+}
+
+/// Keep the grid under the camera, in step with the snap size, and hidden with the editor.
+fn update_grid(
+    prefs: Res<EditorPrefs>,
+    edit_state: Res<SceneEditState>,
+    mut materials: ResMut<Assets<GridMaterial>>,
+    q_cam: Query<&GlobalTransform, With<OrbitCamera>>,
+    mut q_grid: Query<
+        (
+            &mut Transform,
+            &mut Visibility,
+            &MeshMaterial3d<GridMaterial>,
+        ),
+        With<EditorGrid>,
+    >,
+) {
+    let Ok((mut tf, mut visibility, material)) = q_grid.single_mut() else {
+        return;
+    };
+    visibility.set_if_neq(if edit_state.open && prefs.show_grid {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    });
+    if let Ok(cam) = q_cam.single() {
+        // Lines come from world coordinates, so moving the plane doesn't move them
+        let at = cam.translation();
+        tf.set_if_neq(Transform::from_xyz(at.x, 0.0, at.z));
+    }
+    // Compare first: `get_mut` alone would re-upload the material
+    let params = grid_params(&prefs);
+    if materials
+        .get(&material.0)
+        .is_some_and(|m| m.params != params)
+        && let Some(mat) = materials.get_mut(&material.0)
+    {
+        mat.params = params;
+    }
+}
+
+/// World X/Y/Z as seen from the camera, in the viewport's bottom-left corner.
+fn axes_widget(
+    mut ctxs: EguiContexts,
+    edit_state: Res<SceneEditState>,
+    prefs: Res<EditorPrefs>,
+    q_cam: Query<&GlobalTransform, With<OrbitCamera>>,
+) {
+    if !edit_state.open || !prefs.show_axes {
+        return;
+    }
+    let (Ok(ctx), Ok(cam)) = (ctxs.ctx_mut(), q_cam.single()) else {
+        return;
+    };
+    // What the docked panels leave over is the 3D viewport
+    let viewport = ctx.available_rect();
+    let center = egui::pos2(
+        viewport.min.x + AXES_WIDGET_RADIUS + 12.0,
+        viewport.max.y - AXES_WIDGET_RADIUS - 12.0,
+    );
+    let to_view = cam.compute_transform().rotation.inverse();
+
+    let mut axes = [
+        (Vec3::X, "X", egui::Color32::from_rgb(230, 60, 60)),
+        (Vec3::Y, "Y", egui::Color32::from_rgb(90, 210, 90)),
+        (Vec3::Z, "Z", egui::Color32::from_rgb(70, 110, 240)),
+    ]
+    .map(|(axis, label, color)| (to_view * axis, label, color));
+    // Back to front, so the axes pointing at the viewer are drawn on top
+    axes.sort_by(|a, b| a.0.z.total_cmp(&b.0.z));
+
+    let painter = ctx.layer_painter(egui::LayerId::new(
+        egui::Order::Background,
+        egui::Id::new("axes_widget"),
+    ));
+    for (dir, label, color) in axes {
+        let end = center + egui::vec2(dir.x, -dir.y) * AXES_WIDGET_RADIUS;
+        painter.line_segment([center, end], egui::Stroke::new(2.0, color));
+        painter.circle_filled(end, 7.0, color);
+        painter.text(
+            end,
+            egui::Align2::CENTER_CENTER,
+            label,
+            egui::FontId::proportional(10.0),
+            egui::Color32::WHITE,
+        );
+    }
+}
@@ -0,0 +1,551 @@
+//! Ground painting: paths, worn patches and flower beds painted straight onto the base ground
+//! instead of being built from extra geometry. The ground mixes two to four tiling layers, each
+//! a texture (or a flat color when it has none) with a tint, by a splat map: a square image over
+//! the ground's plane whose channels are the layers' shares at each spot. The brush in the
+//! **Ground Paint** window drags the selected layer in.
+//!
+//! The splat map is saved with the scene, once something has been painted. The painted object is
+//! found by name (`BaseGround` to start with) and drawn through the stylized skin (see
+//! [`crate::stylized`]), which samples the map with the plane's own UVs.
+
+use bevy::asset::RenderAssetUsages;
+use bevy::image::ImageSampler;
+use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use bevy::window::PrimaryWindow;
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::SceneEditState;
+use crate::camera::{OrbitCamera, UiPointer};
+use crate::editor_gizmos::EditorGizmos;
+use crate::editor_mode::EditorMode;
+use crate::inspector::Editable;
+use crate::vertex_paint::VertexBrush;
+
+/// Texels along each side of a new splat map.
+const SPLAT_SIZE: u32 = 256;
+/// Most RGBA values (splat map texels, vertex colors) a scene file may hold in one list.
+const MAX_RGBA_VALUES: usize = 4096 * 4096;
+pub const MAX_LAYERS: usize = 4;
+const MIN_LAYERS: usize = 2;
+/// Brush dabs per second at full strength it takes to paint a spot over completely.
+const PAINT_RATE: f32 = 4.0;
+
+/// One texture the ground mixes in.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct PaintLayer {
+    /// Relative to `assets/`; empty paints the flat tint
+    pub texture: String,
+    /// Linear RGB the texture is multiplied by
+    pub tint: [f32; 3],
+}
+
+/// The scene's ground paint: its layers and the splat map mixing them, saved with the scene.
+#[derive(Resource, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GroundPaint {
+    /// Name of the (plane) object painted
+    pub ground: String,
+    pub layers: Vec<PaintLayer>,
+    /// Texture repeats per meter
+    pub repeats: f32,
+    /// Splat map texels along each side
+    pub size: u32,
+    /// Each texel's layer shares, one channel per layer (empty until something is painted)
+    #[serde(
+        serialize_with = "serialize_rgba_runs",
+        deserialize_with = "deserialize_rgba_runs"
+    )]
+    weights: Vec<[u8; 4]>,
+    /// GPU copy of `weights`
+    #[serde(skip)]
+    map: Handle<Image>,
+}
+
+impl Default for GroundPaint {
+    fn default() -> Self {
+        Self {
+            ground: "BaseGround".into(),
+            layers: vec![
+                PaintLayer {
+                    texture: "textures/grass_top.png".into(),
+                    tint: [1.0, 1.0, 1.0],
+                },
+                PaintLayer {
+                    texture: "textures/dirt_side.png".into(),
+                    tint: [1.0, 1.0, 1.0],
+                },
+                PaintLayer {
+                    texture: String::new(),
+                    tint: [0.72, 0.62, 0.45],
+                },
+            ],
+            repeats: 0.5,
+            size: SPLAT_SIZE,
+            weights: Vec::new(),
+            map: Handle::default(),
+        }
+    }
+}
+
+/// What the stylized skin of the painted ground draws.
+#[derive(Clone, PartialEq, Debug)]
+pub(crate) struct Splat {
+    pub layers: Vec<PaintLayer>,
+    pub repeats: f32,
+    pub map: Handle<Image>,
+}
+
+impl GroundPaint {
+    /// Nothing has been painted (or the loaded map doesn't fit its size).
+    pub fn is_empty(&self) -> bool {
+        self.weights.is_empty() || self.weights.len() != self.texels()
+    }
+
+    /// Wipe the paint off, back to the first layer everywhere.
+    pub fn clear(&mut self) {
+        self.weights.clear();
+    }
+
+    fn texels(&self) -> usize {
+        (self.size as usize).pow(2)
+    }
+
+    /// The look of the painted ground, once there's paint and its map is on the GPU.
+    pub(crate) fn splat(&self) -> Option<Splat> {
+        (!self.is_empty() && self.map != Handle::default()).then(|| Splat {
+            layers: self.layers.clone(),
+            repeats: self.repeats,
+            map: self.map.clone(),
+        })
+    }
+
+    /// Take on a loaded scene's paint, keeping the splat map image to draw it with.
+    pub(crate) fn load(&mut self, saved: GroundPaint) {
+        let map = std::mem::take(&mut self.map);
+        *self = GroundPaint { map, ..saved };
+    }
+
+    /// Drop the last layer, and its paint with it.
+    fn remove_layer(&mut self) {
+        if self.layers.len() <= MIN_LAYERS {
+            return;
+        }
+        self.layers.pop();
+        let channel = self.layers.len();
+        for w in &mut self.weights {
+            w[channel] = 0;
+        }
+    }
+
+    /// Paint `layer` in around `uv` (in the plane's UVs) over an ellipse of `radius`, easing
+    /// out to its edge. `amount` is the share of the way to full coverage gone at the center.
+    fn dab(&mut self, uv: Vec2, radius: Vec2, layer: usize, amount: f32) {
+        if self.is_empty() {
+            self.size = self.size.clamp(16, 4096);
+            self.weights = vec![[255, 0, 0, 0]; self.texels()];
+        }
+        let size = self.size as f32;
+        let radius = radius.max(Vec2::splat(0.5 / size));
+        let (lo, hi) = ((uv - radius) * size, (uv + radius) * size);
+        let (x0, y0) = (
+            lo.x.floor().max(0.0) as usize,
+            lo.y.floor().max(0.0) as usize,
+        );
+        let (x1, y1) = (
+            (hi.x.ceil().min(size)) as usize,
+            (hi.y.ceil().min(size)) as usize,
+        );
+        for y in y0..y1 {
+            for x in x0..x1 {
+                let texel = (Vec2::new(x as f32, y as f32) + 0.5) / size;
+                let d = ((texel - uv) / radius).length();
+                if d >= 1.0 {
+                    continue;
+                }
+                let falloff = 1.0 - d * d * (3.0 - 2.0 * d);
+                let share = (amount * falloff).clamp(0.0, 1.0);
+                if share < 0.002 {
+                    continue;
+                }
+                let w = &mut self.weights[y * self.size as usize + x];
+                for (channel, value) in w.iter_mut().enumerate() {
+                    let target = if channel == layer { 255.0 } else { 0.0 };
+                    let step = (target - *value as f32) * share;
+                    // At least a step each dab, so shares reach all or nothing
+                    let step = if step > 0.0 {
+                        step.ceil()
+                    } else {
+                        step.floor()
+                    };
+                    *value = (*value as f32 + step).clamp(0.0, 255.0) as u8;
+                }
+            }
+        }
+    }
+}
+
+/// RGBA values saved as runs of equal ones, `rrggbbaa*count` (or just `rrggbbaa`),
+/// space-separated: unpainted ground (or mesh) takes next to nothing.
+pub(crate) fn serialize_rgba_runs<S: Serializer>(
+    values: &[[u8; 4]],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let runs: Vec<String> = values
+        .chunk_by(|a, b| a == b)
+        .map(|run| {
+            let hex: String = run[0].iter().map(|c| format!("{c:02x}")).collect();
+            if run.len() > 1 {
+                format!("{hex}*{}", run.len())
+            } else {
+                hex
+            }
+        })
+        .collect();
+    serializer.serialize_str(&runs.join(" "))
+}
+
+pub(crate) fn deserialize_rgba_runs<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<[u8; 4]>, D::Error> {
+    let text = String::deserialize(deserializer)?;
+    let mut values = Vec::new();
+    for run in text.split_whitespace() {
+        let (hex, count) = match run.split_once('*') {
+            Some((hex, count)) => (hex, count.parse::<usize>().map_err(D::Error::custom)?),
+            None => (run, 1),
+        };
+        let value = u32::from_str_radix(hex, 16)
+            .ok()
+            .filter(|_| hex.len() == 8)
+            .ok_or_else(|| D::Error::custom(format!("bad RGBA value {hex:?}")))?;
+        if values.len() + count > MAX_RGBA_VALUES {
+            return Err(D::Error::custom("too many RGBA values"));
+        }
+        values.extend(std::iter::repeat_n(value.to_be_bytes(), count));
+    }
+    Ok(values)
+}
+
+/// The ground brush, set in the **Ground Paint** window.
+#[derive(Resource)]
+pub struct GroundBrush {
+    /// Left drags on the ground paint
+    pub active: bool,
+    /// Index of the layer painted
+    pub layer: usize,
+    /// Meters
+    pub radius: f32,
+    /// How quickly a dab covers what's under it, 0..=1
+    pub strength: f32,
+}
+
+impl Default for GroundBrush {
+    fn default() -> Self {
+        Self {
+            active: false,
+            layer: 1,
+            radius: 1.0,
+            strength: 0.5,
+        }
+    }
+}
+
+impl GroundBrush {
+    /// Viewport clicks paint rather than pick objects.
+    pub fn captures_pointer(&self) -> bool {
+        self.active
+    }
+}
+
+pub struct GroundPaintPlugin;
+impl Plugin for GroundPaintPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GroundPaint>()
+            .init_resource::<GroundBrush>()
+            .init_resource::<SceneEditState>()
+            .init_resource::<EditorMode>()
+            .init_gizmo_group::<EditorGizmos>()
+            .add_systems(Update, (paint_ground, sync_splat_map).chain())
+            .add_systems(EguiPrimaryContextPass, ground_paint_window);
+    }
+}
+
+/// Paint under the cursor while the left button is held (the press has to start in the
+/// viewport), and outline the brush on the ground.
+fn paint_ground(
+    mut brush: ResMut<GroundBrush>,
+    mut paint: ResMut<GroundPaint>,
+    edit_state: Res<SceneEditState>,
+    mode: Res<EditorMode>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    time: Res<Time<Real>>,
+    mut ui_pointer: UiPointer,
+    q_window: Query<&Window, With<PrimaryWindow>>,
+    q_cam: Query<(&Camera, &GlobalTransform), With<OrbitCamera>>,
+    q_ground: Query<(&Name, &GlobalTransform), With<Editable>>,
+    mut gizmos: Gizmos<EditorGizmos>,
+    mut stroke: Local<bool>,
+) {
+    if !brush.active {
+        *stroke = false;
+        return;
+    }
+    // Other modes own left drags (and Play the whole scene)
+    if !edit_state.open || *mode != EditorMode::Select {
+        brush.active = false;
+        return;
+    }
+    if mouse.just_pressed(MouseButton::Left) {
+        *stroke = !ui_pointer.wants_pointer_in_primary();
+    } else if !mouse.pressed(MouseButton::Left) {
+        *stroke = false;
+    }
+
+    let Some((_, ground)) = q_ground.iter().find(|(n, _)| n.as_str() == paint.ground) else {
+        return;
+    };
+    let Some(cursor) = q_window.single().ok().and_then(|w| w.cursor_position()) else {
+        return;
+    };
+    let Ok((camera, cam_gt)) = q_cam.single() else {
+        return;
+    };
+    let Ok(ray) = camera.viewport_to_world(cam_gt, cursor) else {
+        return;
+    };
+    let up = ground.up();
+    let Some(t) = ray.intersect_plane(ground.translation(), InfinitePlane3d::new(up)) else {
+        return;
+    };
+    let hit = ray.get_point(t);
+    gizmos.circle(
+        Isometry3d::new(hit + *up * 0.02, Quat::from_rotation_arc(Vec3::Z, *up)),
+        brush.radius,
+        Color::srgba(1.0, 0.8, 0.3, 0.8),
+    );
+    if !*stroke || paint.layers.is_empty() {
+        return;
+    }
+
+    // The plane's UVs run from its -X/-Z corner (0, 0) to its +X/+Z one (1, 1)
+    let local = ground.affine().inverse().transform_point3(hit);
+    let uv = local.xz() + 0.5;
+    let scale = ground.scale().xz().abs().max(Vec2::splat(0.01));
+    let layer = brush.layer.min(paint.layers.len() - 1);
+    let amount = brush.strength * time.delta_secs() * PAINT_RATE;
+    paint.dab(uv, Vec2::splat(brush.radius) / scale, layer, amount);
+}
+
+/// Copy the painted weights to the splat map image, making it the first time.
+fn sync_splat_map(mut paint: ResMut<GroundPaint>, mut images: ResMut<Assets<Image>>) {
+    if !paint.is_changed() || paint.is_empty() {
+        return;
+    }
+    let data: Vec<u8> = paint.weights.iter().flatten().copied().collect();
+    let size = paint.size;
+    if let Some(image) = images.get_mut(&paint.map)
+        && image.size() == UVec2::splat(size)
+    {
+        image.data = Some(data);
+        return;
+    }
+    let mut image = Image::new(
+        Extent3d {
+            width: size,
+            height: size,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        // Shares, not colors: no sRGB curve
+        TextureFormat::Rgba8Unorm,
+        RenderAssetUsages::default(),
+    );
+    image.sampler = ImageSampler::linear();
+    // Not a change to the paint itself
+    paint.bypass_change_detection().map = images.add(image);
+}
+
+fn ground_paint_window(
+    mut ctxs: EguiContexts,
+    edit_state: Res<SceneEditState>,
+    mut mode: ResMut<EditorMode>,
+    mut brush: ResMut<GroundBrush>,
+    mut paint: ResMut<GroundPaint>,
+    q_names: Query<&Name, With<Editable>>,
+    vertex_brush: Option<ResMut<VertexBrush>>,
+) {
+    if !edit_state.open {
+        return;
+    }
+    let Ok(ctx) = ctxs.ctx_mut() else {
+        return;
+    };
+
+    let mut active = brush.active;
+    let (mut layer, mut radius, mut strength) = (brush.layer, brush.radius, brush.strength);
+    let (mut ground, mut layers, mut repeats) =
+        (paint.ground.clone(), paint.layers.clone(), paint.repeats);
+    let (mut remove_layer, mut clear) = (false, false);
+    let playing = *mode == EditorMode::Play;
+    egui::Window::new("Ground Paint")
+        .default_open(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.add_enabled(!playing, egui::Checkbox::new(&mut active, "Paint"))
+                .on_hover_text("Drag on the ground to paint the picked layer in");
+            ui.horizontal(|ui| {
+                ui.label("Ground");
+                ui.text_edit_singleline(&mut ground)
+                    .on_hover_text("Name of the plane object painted");
+            });
+            if !q_names.iter().any(|n| n.as_str() == ground) {
+                ui.weak(format!("No object is called {ground:?}."));
+            }
+
+            ui.separator();
+            for (i, paint_layer) in layers.iter_mut().enumerate() {
+                ui.push_id(i, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.radio_value(&mut layer, i, format!("{}", i + 1))
+                            .on_hover_text("Paint this layer");
+                        ui.color_edit_button_rgb(&mut paint_layer.tint);
+                        // Edit the path in a scratch buffer so the texture only reloads once
+                        // editing is done
+                        let id = ui.make_persistent_id("ground_layer_texture");
+                        let mut text = ui
+                            .data_mut(|d| d.get_temp::<String>(id))
+                            .unwrap_or_else(|| paint_layer.texture.clone());
+                        let resp = ui.text_edit_singleline(&mut text);
+                        if resp.lost_focus() {
+                            ui.data_mut(|d| d.remove::<String>(id));
+                            paint_layer.texture = text;
+                        } else if resp.has_focus() {
+                            ui.data_mut(|d| d.insert_temp(id, text));
+                        }
+                    });
+                });
+            }
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(layers.len() < MAX_LAYERS, egui::Button::new("Add layer"))
+                    .clicked()
+                {
+                    layers.push(PaintLayer {
+                        texture: String::new(),
+                        tint: [0.5, 0.5, 0.5],
+                    });
+                }
+                remove_layer = ui
+                    .add_enabled(layers.len() > MIN_LAYERS, egui::Button::new("Remove last"))
+                    .on_hover_text("Its paint goes too")
+                    .clicked();
+            });
+            ui.weak("Layer 1 covers the ground to start with; a layer without a texture paints its tint.");
+            ui.add(
+                egui::Slider::new(&mut repeats, 0.05..=4.0)
+                    .logarithmic(true)
+                    .text("Repeats per m"),
+            );
+
+            ui.separator();
+            ui.add(
+                egui::Slider::new(&mut radius, 0.1..=10.0)
+                    .logarithmic(true)
+                    .text("Brush radius (m)"),
+            );
+            ui.add(egui::Slider::new(&mut strength, 0.05..=1.0).text("Strength"));
+            clear = ui
+                .add_enabled(!paint.is_empty(), egui::Button::new("Clear paint"))
+                .clicked();
+        });
+
+    if active != brush.active {
+        brush.active = active;
+        // The brush owns left drags, which Move, Rotate and Scale (and the vertex brush) would
+        // take too
+        if active {
+            mode.set_if_neq(EditorMode::Select);
+            if let Some(mut vertex_brush) = vertex_brush {
+                vertex_brush.active = false;
+            }
+        }
+    }
+    if (layer, radius, strength) != (brush.layer, brush.radius, brush.strength) {
+        brush.layer = layer;
+        brush.radius = radius;
+        brush.strength = strength;
+    }
+    if ground != paint.ground {
+        paint.ground = ground;
+    }
+    if layers != paint.layers {
+        paint.layers = layers;
+    }
+    if repeats != paint.repeats {
+        paint.repeats = repeats;
+    }
+    if remove_layer {
+        paint.remove_layer();
+        brush.layer = brush.layer.min(paint.layers.len() - 1);
+    }
+    if clear {
+        paint.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct Runs(
+        #[serde(
+            serialize_with = "serialize_rgba_runs",
+            deserialize_with = "deserialize_rgba_runs"
+        )]
+        Vec<[u8; 4]>,
+    );
+
+    fn read(text: &str) -> Result<Runs, serde_json::Error> {
+        serde_json::from_value(serde_json::Value::String(text.into()))
+    }
+
+    #[test]
+    fn equal_values_collapse_into_runs() {
+        let runs = Runs(vec![
+            [255, 0, 0, 0],
+            [255, 0, 0, 0],
+            [255, 0, 0, 0],
+            [0, 128, 0, 1],
+            [255, 0, 0, 0],
+        ]);
+        let text = serde_json::to_value(&runs).unwrap();
+        assert_eq!(text, "ff000000*3 00800001 ff000000");
+        assert_eq!(read(text.as_str().unwrap()).unwrap(), runs);
+    }
+
+    #[test]
+    fn nothing_saves_as_nothing() {
+        assert_eq!(serde_json::to_value(Runs(Vec::new())).unwrap(), "");
+        assert_eq!(read("").unwrap(), Runs(Vec::new()));
+    }
+
+    #[test]
+    fn bad_runs_are_errors() {
+        for text in [
+            "ff0000",
+            "ff00000000",
+            "gg000000",
+            "ff000000*",
+            "ff000000*x",
+        ] {
+            assert!(read(text).is_err(), "{text:?}");
+        }
+        let too_many = format!("00000000*{}", MAX_RGBA_VALUES + 1);
+        assert!(read(&too_many).is_err());
+    }
+}
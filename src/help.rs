@@ -0,0 +1,114 @@
+//! F1 cheat sheet: the current key bindings, the editor mode and how to drive the camera and
+//! the viewport tools.
+
+use bevy::prelude::*;
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+
+use crate::build_mode::BuildMode;
+use crate::editor_mode::EditorMode;
+use crate::settings::{KeyBindings, key_label};
+
+#[derive(Resource, Default)]
+pub struct HelpOverlay {
+    pub open: bool,
+}
+
+pub struct HelpPlugin;
+impl Plugin for HelpPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HelpOverlay>()
+            .init_resource::<KeyBindings>()
+            .init_resource::<EditorMode>()
+            .add_systems(EguiPrimaryContextPass, help_window);
+    }
+}
+
+/// What the viewport does in each mode.
+fn mode_hint(mode: EditorMode) -> &'static str {
+    match mode {
+        EditorMode::Select => "Click an object to select it.",
+        EditorMode::Move => "Drag the selection over the ground.",
+        EditorMode::Rotate => "Drag left/right to turn the selection around Y.",
+        EditorMode::Scale => "Drag up/down to grow or shrink the selection.",
+        EditorMode::Place => "Click a face to add a tile, Shift+click a tile to remove it.",
+        EditorMode::Measure => "Click two points to measure; right click drops the last one.",
+        EditorMode::Play => "Arrow keys walk the player; pick another mode to stop.",
+    }
+}
+
+fn help_window(
+    mut ctxs: EguiContexts,
+    mut help: ResMut<HelpOverlay>,
+    bindings: Res<KeyBindings>,
+    mode: Res<EditorMode>,
+    build: Option<Res<BuildMode>>,
+) {
+    if !help.open {
+        return;
+    }
+    let mut open = true;
+    let Ok(ctx) = ctxs.ctx_mut() else {
+        return;
+    };
+    egui::Window::new("Help")
+        .open(&mut open)
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.strong("Mode:");
+                ui.label(mode.label());
+                if build.is_some_and(|b| b.enabled) && *mode != EditorMode::Place {
+                    ui.label("(build mode on)");
+                }
+            });
+            ui.label(mode_hint(*mode));
+
+            ui.separator();
+            ui.strong("Keys");
+            egui::Grid::new("help_keys").striped(true).show(ui, |ui| {
+                for (action, key) in bindings.actions() {
+                    ui.monospace(key_label(key));
+                    ui.label(action);
+                    ui.end_row();
+                }
+            });
+            ui.small("Rebind these in the [keys] table of app_settings.toml.");
+
+            ui.separator();
+            ui.strong("Camera");
+            egui::Grid::new("help_camera").striped(true).show(ui, |ui| {
+                for (keys, action) in [
+                    ("1 / 2 / 3 / 4", "Jump to a corner view"),
+                    ("A / D", "Orbit around the scene"),
+                    ("W / S", "Tilt up or down"),
+                ] {
+                    ui.monospace(keys);
+                    ui.label(action);
+                    ui.end_row();
+                }
+            });
+
+            ui.separator();
+            ui.strong("Editing");
+            ui.label("Click an object (or its Hierarchy entry) to inspect it.");
+            ui.label("Pick Move, Rotate or Scale in the toolbar, then drag in the viewport.");
+            ui.label("Hold Shift while dragging a value for finer steps.");
+            ui.label(format!(
+                "While placing a new object, {} turns it a quarter and right click cancels.",
+                key_label(bindings.turn_placement)
+            ));
+            ui.label(
+                "Arrow keys and Page Up/Down nudge the selection; Tab moves through the panels \
+                 and Esc leaves them.",
+            );
+            ui.label(format!(
+                "Press {} to walk the scene; Play in the toolbar does the same.",
+                key_label(bindings.drop_player)
+            ));
+        });
+    if !open {
+        help.open = false;
+    }
+}
@@ -0,0 +1,169 @@
+//! The hierarchy panel: every scene object in one list, to select, rename and reorder by hand.
+//! The order the rows are dragged into is saved with the scene, so a tidied list stays tidy.
+
+use bevy::prelude::*;
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+
+use crate::SceneEditState;
+use crate::bake::BakedMember;
+use crate::dock::{DockLayout, DockPanel, DockSet, DockedWindow};
+use crate::inspector::{Editable, SceneIoState, SelectObjectEvent, Selected, ToggleSelectionEvent};
+
+/// Scene object list; click a row to select the object, Ctrl/Shift+click to add it to the
+/// selection, double-click to rename it and drag it up or down to reorder the list. From the
+/// keyboard, Tab to a row, Space selects it and F2 renames it.
+pub struct HierarchyPlugin;
+impl Plugin for HierarchyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            EguiPrimaryContextPass,
+            hierarchy_panel.in_set(DockSet::Hierarchy),
+        );
+    }
+}
+
+/// Position in the hierarchy list, set by dragging rows and saved with the scene (which also
+/// writes objects in this order). Objects without one follow, by name.
+#[derive(Component, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub struct HierarchyOrder(pub u32);
+
+/// How the list is sorted: by `HierarchyOrder`, then name, then entity.
+pub(crate) fn list_order(
+    a: (Option<HierarchyOrder>, &str, Entity),
+    b: (Option<HierarchyOrder>, &str, Entity),
+) -> std::cmp::Ordering {
+    let order = |o: Option<HierarchyOrder>| o.map_or(u32::MAX, |o| o.0);
+    order(a.0)
+        .cmp(&order(b.0))
+        .then_with(|| a.1.cmp(b.1))
+        .then(a.2.cmp(&b.2))
+}
+
+/// The row being renamed and its text so far.
+#[derive(Default)]
+struct Renaming {
+    entity: Option<Entity>,
+    text: String,
+    /// Focus the text field on its first frame only
+    focus: bool,
+}
+
+fn hierarchy_panel(
+    mut ctxs: EguiContexts,
+    mut commands: Commands,
+    edit_state: Res<SceneEditState>,
+    dock: Res<DockLayout>,
+    mut io: Option<ResMut<SceneIoState>>,
+    mut renaming: Local<Renaming>,
+    q_objects: Query<
+        (
+            Entity,
+            Option<&Name>,
+            Option<&HierarchyOrder>,
+            Has<Selected>,
+        ),
+        (With<Editable>, Without<BakedMember>),
+    >,
+    mut ev_select: EventWriter<SelectObjectEvent>,
+    mut ev_toggle: EventWriter<ToggleSelectionEvent>,
+) {
+    if !edit_state.open {
+        return;
+    }
+
+    let mut rows: Vec<(Entity, String, Option<HierarchyOrder>, bool)> = q_objects
+        .iter()
+        .map(|(e, name, order, selected)| {
+            let label = name.map_or_else(|| format!("Entity {e}"), |n| n.to_string());
+            (e, label, order.copied(), selected)
+        })
+        .collect();
+    rows.sort_by(|a, b| list_order((a.2, a.1.as_str(), a.0), (b.2, b.1.as_str(), b.0)));
+
+    let mut renamed = None;
+    let mut moved = None;
+    let Ok(ctx) = ctxs.ctx_mut() else {
+        return;
+    };
+    DockedWindow::new(DockPanel::Hierarchy, "Hierarchy").show(ctx, &dock, |ui| {
+        if rows.is_empty() {
+            ui.weak("No objects");
+        }
+        for (index, (entity, label, _, selected)) in rows.iter().enumerate() {
+            let entity = *entity;
+            if renaming.entity == Some(entity) {
+                let response = ui.text_edit_singleline(&mut renaming.text);
+                if std::mem::take(&mut renaming.focus) {
+                    response.request_focus();
+                }
+                if ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+                    renaming.entity = None;
+                } else if response.lost_focus() {
+                    renamed = Some((entity, renaming.text.trim().to_string()));
+                    renaming.entity = None;
+                }
+                continue;
+            }
+
+            let response = ui
+                .selectable_label(*selected, label)
+                .interact(egui::Sense::drag());
+            response.dnd_set_drag_payload(entity);
+            if let Some(dragged) = response.dnd_release_payload::<Entity>() {
+                moved = Some((*dragged, index));
+            }
+            // Line where the dragged row would land
+            if response.dnd_hover_payload::<Entity>().is_some() {
+                let rect = response.rect;
+                ui.painter()
+                    .hline(rect.x_range(), rect.top(), ui.visuals().selection.stroke);
+            }
+
+            if response.double_clicked()
+                || (response.has_focus() && ui.input(|i| i.key_pressed(egui::Key::F2)))
+            {
+                *renaming = Renaming {
+                    entity: Some(entity),
+                    text: label.clone(),
+                    focus: true,
+                };
+                continue;
+            }
+            if !response.clicked() {
+                continue;
+            }
+            if ui.input(|i| i.modifiers.command || i.modifiers.shift) {
+                ev_toggle.write(ToggleSelectionEvent(entity));
+            } else {
+                // Also narrows a multi-selection down to this row
+                ev_select.write(SelectObjectEvent(entity));
+            }
+        }
+    });
+
+    let mut edited = false;
+    if let Some((entity, name)) = renamed {
+        if name.is_empty() {
+            commands.entity(entity).remove::<Name>();
+        } else {
+            commands.entity(entity).insert(Name::new(name));
+        }
+        edited = true;
+    }
+    // Dropped onto a row: take the dragged one out and put it in that row's place, then number
+    // every row so the order sticks
+    if let Some((dragged, target)) = moved
+        && let Some(from) = rows.iter().position(|r| r.0 == dragged)
+        && from != target
+    {
+        let row = rows.remove(from);
+        rows.insert(target, row);
+        for (i, (e, ..)) in rows.iter().enumerate() {
+            commands.entity(*e).insert(HierarchyOrder(i as u32));
+        }
+        edited = true;
+    }
+    if edited && let Some(io) = io.as_mut() {
+        io.mark_edited();
+    }
+}
@@ -1,14 +1,72 @@
+use std::collections::{HashMap, HashSet};
+
+use bevy::asset::AssetEvents;
+use bevy::diagnostic::FrameCount;
+use bevy::ecs::system::SystemParam;
 use bevy::input::mouse::MouseButtonInput;
+use bevy::math::Affine3A;
 use bevy::prelude::*;
 use bevy::render::primitives::Aabb;
 use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
 use serde::{Deserialize, Serialize};
-use std::fs::{read_to_string, write};
 
 use crate::SceneEditState;
+use crate::accessibility::{AccessibilityPrefs, Described};
+use crate::actions::EditorAction;
+use crate::asset_cache::{AssetCache, PlainMaterial};
+use crate::bake::{BakedMember, MergeBakeEvent};
+use crate::build_mode::BuildMode;
+use crate::camera::{PickRay, UiPointer, camera_window};
+use crate::camera_rails::{CameraRail, camera_rail_inspector_ui};
+use crate::chunks::{SavedCells, SceneChunk, SceneChunks};
+use crate::creatures::{CreatureSpawner, creature_spawner_inspector_ui, spawn_creature_spawner};
+use crate::custom_kinds::{CustomKindData, CustomKindId, SpawnKinds, custom_kind_inspector_ui};
+use crate::custom_props::{CustomProperties, custom_properties_inspector_ui};
+use crate::cutaway::Cutaway;
+use crate::decals::{SceneDecal, decal_inspector_ui, spawn_decal_object};
+use crate::dissolve::{DissolveSettings, dissolve_away, dissolve_in};
+use crate::dock::{DockLayout, DockPanel, DockSet, DockedWindow};
+use crate::editor_gizmos::EditorGizmos;
+use crate::editor_mode::{EditorMode, TransformSpace};
+use crate::elevators::{Elevator, elevator_inspector_ui};
+use crate::environment::{SwayRest, WindSway};
+use crate::fog_volumes::{SceneFogVolume, fog_volume_inspector_ui, spawn_fog_volume_object};
+use crate::ground_paint::{GroundBrush, GroundPaint};
+use crate::hierarchy::HierarchyOrder;
+use crate::instancing::InstancedTile;
+use crate::joints::SceneJoint;
+use crate::lights::{
+    LightAnimBase, LightAnimation, SceneLight, insert_light, light_animation_inspector_ui,
+    light_inspector_ui, scene_light_of, spawn_light_object,
+};
+use crate::material_presets::{MaterialLibrary, material_presets_ui};
+use crate::material_slots::{SlotFilter, material_slots, material_slots_inspector_ui};
+use crate::num_expr;
+use crate::objectives::Objectives;
+use crate::placement::Placement;
+use crate::play_rollback::{disabled_copy, live_copy};
+use crate::post::glow::{BloomIsolation, Glow, glow_inspector_ui};
+use crate::post::outlines::{OutlineShell, outline_shell};
+use crate::power::every_frame;
+use crate::reflection::{PlanarReflection, reflection_inspector_ui};
+use crate::rotation_edit::{
+    RotationEdit, RotationMode, displayed_rotation, loaded_rotation, saved_rotation,
+};
+use crate::scene_export::{ExportOutcome, SceneExport, SceneExported, serialize_counted};
+use crate::scene_io;
+use crate::settings::EditorPrefs;
+use crate::signs::{Sign, sign_inspector_ui};
+use crate::splines::{SplineEditState, SplinePath, spawn_spline_object, spline_inspector_ui};
+use crate::stairs::{StairsParams, stairs_inspector_ui};
+use crate::stylized::{Toon, ToonShading, Triplanar, toon_inspector_ui, triplanar_inspector_ui};
+use crate::surfaces::{Surface, surface_inspector_ui};
+use crate::timeline::{Timeline, TimelinePlayback};
+use crate::toasts::Toasts;
+use crate::vertex_paint::{VertexBrush, VertexPaint};
+use crate::view_layers::{ViewLayers, view_layers_inspector_ui};
 
 /// Tag any entity you want to be clickable/editable.
-#[derive(Component)]
+#[derive(Component, Clone)]
 pub struct Editable;
 
 /// Tag the currently selected entity (helps for highlighting, if you want).
@@ -24,13 +82,21 @@ pub struct EditableMesh {
 
 /// Keeps UI state and the currently selected entity.
 #[derive(Resource, Default)]
-struct InspectorState {
+pub(crate) struct InspectorState {
     last_selected: Option<Entity>,
     selected: Option<Entity>,
     // Cached UI fields (what the user is editing)
     pos: Vec3,
     scale: Vec3,
-    rot_deg: Vec3,
+    // Rotation fields in the selected object's editing mode
+    rot: [f32; 4],
+    rot_mode: RotationMode,
+    // Space the cached transform fields are in
+    space: TransformSpace,
+    // What the rest of a multi-selection turns and scales around
+    pivot: GroupPivot,
+    // Scale axes that change together, keeping their proportions
+    scale_link: [bool; 3],
     color_srgba: egui::Color32,
     metallic: f32,
     roughness: f32,
@@ -40,9 +106,79 @@ struct InspectorState {
     // Whether the pos/scale cache reflects the currently selected entity.
     // When selection changes, we set this to false so the inspector reloads values.
     cache_initialized: bool,
+    // Transform last written to the selection; anything else means it was moved elsewhere
+    written: Option<Transform>,
     // Choice for object creation
     spawn_kind: SpawnKind,
     cb_display: ColliderDisplay,
+    // Where the last copied object stood, and its copy; repeating steps by the difference
+    last_copy: Option<(Transform, Entity)>,
+    pub(crate) duplicate: bool,
+    pub(crate) repeat_copy: bool,
+    // Material fields that differ across the multi-selection, and the active object they
+    // were compared with
+    mixed: MaterialFields,
+    mixed_for: Option<Entity>,
+}
+
+impl InspectorState {
+    /// The object the inspector shows; the rest of a multi-selection follows its edits.
+    pub(crate) fn active(&self) -> Option<Entity> {
+        self.selected
+    }
+
+    pub(crate) fn pivot(&self) -> GroupPivot {
+        self.pivot
+    }
+}
+
+/// What the rest of a multi-selection turns and scales around when the inspector edits it.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub(crate) enum GroupPivot {
+    /// The object the inspector shows
+    #[default]
+    Active,
+    /// The middle of the selected objects' origins
+    Center,
+}
+
+impl GroupPivot {
+    const ALL: [Self; 2] = [Self::Active, Self::Center];
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Active => "Active object",
+            Self::Center => "Selection center",
+        }
+    }
+}
+
+/// Material fields, for what differs across a multi-selection and what was just edited.
+#[derive(Clone, Copy, Default)]
+struct MaterialFields {
+    color: bool,
+    metallic: bool,
+    roughness: bool,
+}
+
+impl MaterialFields {
+    fn any(&self) -> bool {
+        self.color || self.metallic || self.roughness
+    }
+}
+
+/// Transform fields the inspector widgets changed this frame.
+#[derive(Default)]
+struct TransformFields {
+    translation: bool,
+    rotation: bool,
+    scale: bool,
+}
+
+impl TransformFields {
+    fn any(&self) -> bool {
+        self.translation || self.rotation || self.scale
+    }
 }
 
 /// Author-only collider box (no mesh). Drawn as gizmo in editor; exported to physics for games.
@@ -57,7 +193,7 @@ pub enum ColliderDisplay {
     Hidden,
 }
 
-#[derive(Copy, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[derive(Copy, Clone, Default, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub enum SpawnKind {
     #[default]
     Cuboid,
@@ -65,64 +201,365 @@ pub enum SpawnKind {
     Plane,
     Prism,
     ColliderBox,
+    PointLight,
+    SpotLight,
+    FogVolume,
+    Decal,
+    Stairs,
+    Spline,
+    Creatures,
+    /// Registered by downstream code, see [`crate::custom_kinds`]
+    Custom(CustomKindId),
 }
 
 // ========== Scene JSON format ==========
 #[derive(Serialize, Deserialize)]
 struct SceneDoc {
     version: u32,
+    #[serde(serialize_with = "serialize_counted")]
     objects: Vec<SceneObject>,
+    /// Rhai script run in Play mode
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    script: Option<String>,
+    /// Keyframe animation (omitted when there are no keys)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    timeline: Option<Timeline>,
+    /// Ordered goals for Play mode (omitted when there are none)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    objectives: Option<Objectives>,
+    /// Layers and splat map painted on the ground (omitted until something is painted)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    ground_paint: Option<GroundPaint>,
+    /// Cell width of a streamed scene, whose objects are in `chunks` (or the cell files they
+    /// list) instead of `objects`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    chunk_size: Option<f32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    chunks: Vec<SceneChunk>,
 }
 
-#[derive(Serialize, Deserialize)]
-struct SceneObject {
+/// One saved object; also what streamed-out chunks keep (see [`crate::chunks`]).
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct SceneObject {
     name: Option<String>,
     kind: SpawnKind,
     position: [f32; 3],
     rotation_euler_deg: [f32; 3],
+    /// Rotation editing mode and values, when not plain Euler XYZ (see `rotation_edit`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    rotation_edit: Option<RotationEdit>,
     scale: [f32; 3],
     color_rgba: [f32; 4],
     metallic: f32,
     roughness: f32,
     collider: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    wind_sway: Option<WindSway>,
+    /// Linear RGB emissive; omitted for non-glowing materials
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    emissive: Option<[f32; 3]>,
+    /// Point/spot light parameters (color is stored in `color_rgba`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    light: Option<SceneLight>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    light_animation: Option<LightAnimation>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    planar_reflection: Option<PlanarReflection>,
+    /// Fog volume parameters (the box is position/rotation/scale)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    fog_volume: Option<SceneFogVolume>,
+    /// Decal texture/opacity (the footprint is the scale)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    decal: Option<SceneDecal>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    stairs: Option<StairsParams>,
+    /// Control points and generation mode (points are relative to the position)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    spline: Option<SplinePath>,
+    /// Creature kind and flock size (the area is position/rotation/scale)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    creatures: Option<CreatureSpawner>,
+    /// Volume and view of a spline used as a camera rail
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    camera_rail: Option<CameraRail>,
+    /// Merged into the baked mesh of this group (rebuilt on load; the object stays hidden)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    bake_group: Option<u32>,
+    /// Drawn through the GPU instancing path
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    instanced_tile: bool,
+    /// Orbit views the object shows from (omitted when it shows from all of them)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    views: Option<ViewLayers>,
+    /// Position in the hierarchy list, once it has been arranged by hand
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    order: Option<u32>,
+    /// Key/value metadata for scripts and exporters
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    properties: Option<CustomProperties>,
+    /// Physics joint to another object, by name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    joint: Option<SceneJoint>,
+    /// Floors, speed and call buttons of an elevator platform
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    elevator: Option<Elevator>,
+    /// Conveyor, ice or bounce pad behavior for whoever walks on it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    surface: Option<Surface>,
+    /// Pages of text players read in Play mode
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sign: Option<Sign>,
+    /// World-projected top and side textures
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    triplanar: Option<Triplanar>,
+    /// Banded light and highlight
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    toon: Option<Toon>,
+    /// Blooms while the bloom is glow-only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    glow: Option<Glow>,
+    /// Painted vertex colors
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    vertex_paint: Option<VertexPaint>,
+    /// Parameters of a registered custom kind
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    custom: Option<CustomKindData>,
 }
 
-#[derive(Resource, Default)]
-struct SceneIoState {
-    filename: String,
+impl SceneObject {
+    pub(crate) fn position(&self) -> Vec3 {
+        Vec3::from_array(self.position)
+    }
+
+    pub(crate) fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Name of the object its joint attaches it to
+    pub(crate) fn joint_target(&self) -> Option<&str> {
+        self.joint.as_ref().map(|j| j.to.as_str())
+    }
+
+    pub(crate) fn rename(&mut self, name: String) {
+        self.name = Some(name);
+    }
+
+    /// Attach its joint (if it has one) to the object named `to` instead
+    pub(crate) fn retarget_joint(&mut self, to: String) {
+        if let Some(joint) = self.joint.as_mut() {
+            joint.to = to;
+        }
+    }
+}
+
+#[derive(Resource)]
+pub struct SceneIoState {
+    pub filename: String,
     _status: Option<String>,
+    /// Script file saved with the scene (empty for none)
+    pub script: String,
+    /// Modified since the last save or load
+    pub dirty: bool,
+    /// `Time<Real>` seconds of the last successful save
+    pub saved_at: Option<f32>,
+    /// Skip the next round of change tracking (objects just spawned by a load or at startup)
+    settling: bool,
+    /// Edits counted by [`SceneIoState::mark_edited`]
+    edits: u64,
+}
+
+impl SceneIoState {
+    /// Count an edit to the scene, which now has unsaved changes.
+    pub fn mark_edited(&mut self) {
+        self.dirty = true;
+        self.edits = self.edits.wrapping_add(1);
+    }
+
+    /// Edits counted so far; compare with an earlier count to see if anything was edited since.
+    pub fn edits(&self) -> u64 {
+        self.edits
+    }
+
+    /// Don't count the next round of changes as edits (objects put back by the editor itself).
+    pub fn skip_change_tracking(&mut self) {
+        self.settling = true;
+    }
+}
+
+impl Default for SceneIoState {
+    fn default() -> Self {
+        Self {
+            filename: String::new(),
+            _status: None,
+            script: String::new(),
+            dirty: false,
+            saved_at: None,
+            settling: true,
+            edits: 0,
+        }
+    }
+}
+
+/// Save the scene to `SceneIoState::filename`.
+#[derive(Event, Default, Clone, Copy)]
+pub struct SaveSceneEvent {
+    /// Write the localStorage autosave instead of downloading a file
+    #[cfg(target_arch = "wasm32")]
+    autosave: bool,
+}
+
+impl SaveSceneEvent {
+    fn write(&self, path: &str, json: &str) -> Result<(), String> {
+        #[cfg(target_arch = "wasm32")]
+        if self.autosave {
+            return scene_io::autosave(json);
+        }
+        scene_io::write_scene(path, json)
+    }
+
+    /// Whether this is a save the user asked for (browser autosaves don't count).
+    fn is_user_save(&self) -> bool {
+        #[cfg(target_arch = "wasm32")]
+        return !self.autosave;
+        #[cfg(not(target_arch = "wasm32"))]
+        true
+    }
+}
+
+/// Seconds between browser autosaves.
+#[cfg(target_arch = "wasm32")]
+const AUTOSAVE_SECS: f32 = 30.0;
+
+/// Screen pixels of leeway around every object when clicking on it.
+const PICK_SLOP_PX: f32 = 3.0;
+/// How far (in pixels) the cursor may move between clicks that cycle through overlapping objects.
+const PICK_CYCLE_PX: f32 = 4.0;
+/// How far (in pixels) the cursor may move while the button is down for it to still be a click
+/// rather than a drag.
+const CLICK_DRAG_PX: f32 = 5.0;
+
+/// Click tracking for picking, across frames.
+#[derive(Default)]
+struct PickClicks {
+    /// Window and cursor position of a left press that can still turn into a click
+    pressed: Option<(Entity, Vec2)>,
+    /// Where the last plain click picked, so another click there reaches the next object behind
+    last_pick: Option<(Entity, Vec2)>,
 }
 
 #[derive(Event)]
-struct SaveSceneEvent;
+pub struct LoadSceneEvent;
 
+/// Make this the selected object.
 #[derive(Event)]
-struct LoadSceneEvent;
+pub struct SelectObjectEvent(pub Entity);
+
+/// Add an object to the selection, or take it out if it's already in (Ctrl/Shift+click).
+#[derive(Event)]
+pub struct ToggleSelectionEvent(pub Entity);
 
 /// Plugin to wire everything up.
 pub struct InspectorPlugin;
 impl Plugin for InspectorPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<InspectorState>()
+            .init_resource::<EditorPrefs>()
+            .init_resource::<AccessibilityPrefs>()
+            .init_resource::<EditorMode>()
             .init_resource::<SceneIoState>()
+            .init_resource::<Placement>()
+            .init_resource::<SpawnKinds>()
+            .init_resource::<DissolveSettings>()
+            .init_resource::<SceneExport>()
+            .init_resource::<EditHistory>()
+            .init_gizmo_group::<EditorGizmos>()
+            .add_event::<EditorAction>()
             .add_event::<SaveSceneEvent>()
+            .add_event::<SceneExported>()
+            .add_event::<EditHistoryEvent>()
             .add_event::<LoadSceneEvent>()
+            .add_event::<SelectObjectEvent>()
+            .add_event::<ToggleSelectionEvent>()
             .add_systems(
                 Update,
                 (
-                    pick_on_click,
-                    save_scene_system,
+                    (
+                        pick_on_click,
+                        apply_selection,
+                        compare_selected_materials.run_if(
+                            every_frame
+                                .or(selected_materials_stale)
+                                .or(any_component_removed::<Selected>)
+                                .or(on_event::<AssetEvent<StandardMaterial>>),
+                        ),
+                    )
+                        .chain(),
+                    apply_edit_history,
+                    duplicate_selected,
+                    (save_scene_system, finish_save).chain(),
                     load_scene_system,
-                    highlight_selected_gizmos,
-                    draw_collider_gizmos,
+                    highlight_selected_gizmos
+                        .run_if(every_frame.or(any_with_component::<Selected>)),
+                    draw_collider_gizmos.run_if(every_frame.or(any_with_component::<ColliderBox>)),
                 ),
             )
-            .add_systems(EguiPrimaryContextPass, inspector_window);
+            .add_systems(
+                EguiPrimaryContextPass,
+                inspector_window.in_set(DockSet::Inspector),
+            )
+            // After Update's commands are applied, so a load's spawns are seen while settling
+            .add_systems(
+                PostUpdate,
+                record_edits.after(AssetEvents).before(track_scene_changes),
+            )
+            .add_systems(
+                PostUpdate,
+                track_scene_changes.run_if(
+                    every_frame
+                        .or(any_match_filter::<(With<Editable>, Changed<Transform>)>)
+                        .or(any_component_removed::<Editable>)
+                        .or(resource_exists_and_changed::<Timeline>)
+                        .or(resource_exists_and_changed::<Objectives>)
+                        .or(resource_exists_and_changed::<GroundPaint>)
+                        .or(any_match_filter::<Changed<VertexPaint>>)
+                        .or(any_component_removed::<VertexPaint>),
+                ),
+            );
+
+        #[cfg(target_arch = "wasm32")]
+        app.add_systems(Update, (autosave_timer, receive_uploads));
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn autosave_timer(
+    time: Res<Time<Real>>,
+    mut timer: Local<Option<Timer>>,
+    mut ev_save: EventWriter<SaveSceneEvent>,
+) {
+    let timer =
+        timer.get_or_insert_with(|| Timer::from_seconds(AUTOSAVE_SECS, TimerMode::Repeating));
+    if timer.tick(time.delta()).just_finished() {
+        ev_save.write(SaveSceneEvent { autosave: true });
+    }
+}
+
+/// Load a scene file once the browser has finished reading an upload.
+#[cfg(target_arch = "wasm32")]
+fn receive_uploads(mut io: ResMut<SceneIoState>, mut ev_load: EventWriter<LoadSceneEvent>) {
+    if let Some(name) = scene_io::take_upload() {
+        io.filename = name;
+        ev_load.write(LoadSceneEvent);
     }
 }
 
 /// Ray-AABB intersection helper (slab method). Returns Some(t) if hit; t is entry distance.
-fn ray_aabb_intersection(origin: Vec3, dir: Vec3, aabb_min: Vec3, aabb_max: Vec3) -> Option<f32> {
+pub(crate) fn ray_aabb_intersection(
+    origin: Vec3,
+    dir: Vec3,
+    aabb_min: Vec3,
+    aabb_max: Vec3,
+) -> Option<f32> {
     // Avoid div by zero; replace zero components with a small epsilon.
     let eps = 1e-8;
     let inv_dir = Vec3::new(
@@ -167,7 +604,7 @@ fn ray_aabb_intersection(origin: Vec3, dir: Vec3, aabb_min: Vec3, aabb_max: Vec3
 
 /// Transform a local-space AABB to world space using the entity's GlobalTransform.
 /// Works for any combination of rotation + non-uniform scale + translation.
-fn aabb_world(local: Aabb, global: &GlobalTransform) -> Aabb {
+pub(crate) fn aabb_world(local: Aabb, global: &GlobalTransform) -> Aabb {
     // Affine3A = [ R*S | t ]
     let aff = global.affine();
     let m = aff.matrix3; // Mat3A (rotation * scale)
@@ -198,7 +635,7 @@ fn aabb_world(local: Aabb, global: &GlobalTransform) -> Aabb {
 
 /// Convert an OBB (Transform + local half-extents) to a world-space AABB.
 /// Accounts for rotation and non-uniform scale.
-fn obb_to_world_aabb(tf: Transform, half_extents_local: Vec3) -> Aabb {
+pub(crate) fn obb_to_world_aabb(tf: Transform, half_extents_local: Vec3) -> Aabb {
     // Apply (absolute) scaling to the local half-extents first
     let he = half_extents_local * tf.scale.abs();
 
@@ -221,349 +658,1254 @@ fn obb_to_world_aabb(tf: Transform, half_extents_local: Vec3) -> Aabb {
     }
 }
 
-/// On left-click in the 3D viewport, cast a ray and select the closest hit Editable entity.
+/// On left-click in the 3D viewport, cast a ray and select the closest hit Editable entity, or
+/// the one behind the current selection when clicking the same spot again.
 fn pick_on_click(
     mut ev_mousebtn: EventReader<MouseButtonInput>,
     windows: Query<&Window>,
-    cameras: Query<(&Camera, &GlobalTransform)>,
-    mut state: ResMut<InspectorState>,
-    mut commands: Commands,
+    cameras: Query<(&Camera, &GlobalTransform, &Projection)>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut ev_select: EventWriter<SelectObjectEvent>,
+    mut ev_toggle: EventWriter<ToggleSelectionEvent>,
+    q_editables: Query<
+        (Entity, &GlobalTransform, &Aabb),
+        (With<Editable>, Without<BakedMember>, Without<OutlineShell>),
+    >,
+    mut ui_pointer: UiPointer,
+    q_cb: Query<(Entity, &Transform, &ColliderBox), (With<Editable>, Without<OutlineShell>)>,
+    q_gizmos: Query<
+        (Entity, &Transform),
+        (
+            With<Editable>,
+            Or<(
+                With<PointLight>,
+                With<SpotLight>,
+                With<SceneFogVolume>,
+                With<SplinePath>,
+                With<CreatureSpawner>,
+            )>,
+        ),
+    >,
     q_selected: Query<Entity, With<Selected>>,
-    q_editables: Query<(Entity, &GlobalTransform, &Aabb), With<Editable>>,
-    mut egui_ctxs: EguiContexts,
-    mut edit_state: ResMut<SceneEditState>,
-    q_cb: Query<(Entity, &Transform, &ColliderBox), With<Editable>>,
+    mut clicks: Local<PickClicks>,
+    build: Res<BuildMode>,
+    (spline_edit, brush, vertex_brush): (
+        Res<SplineEditState>,
+        Option<Res<GroundBrush>>,
+        Option<Res<VertexBrush>>,
+    ),
+    mode: Res<EditorMode>,
+    placement: Res<Placement>,
 ) {
-    // Only act on left button press events
-    let clicked = ev_mousebtn
-        .read()
-        .any(|e| e.button == MouseButton::Left && e.state.is_pressed());
-    if !clicked {
-        return;
-    }
-
-    // If egui wants the pointer, don't pick (prevents UI clicks selecting scene).
-    if egui_ctxs
-        .ctx_mut()
-        .expect("single egui context")
-        .wants_pointer_input()
+    // Clicks place/remove tiles in build mode, drop new objects, grab spline control points,
+    // take measurements or paint the ground or vertices
+    let captured = build.enabled
+        || placement.captures_pointer()
+        || spline_edit.captures_pointer()
+        || brush.is_some_and(|b| b.captures_pointer())
+        || vertex_brush.is_some_and(|b| b.captures_pointer())
+        || *mode == EditorMode::Measure;
+    let cursor_in = |window: Entity| windows.get(window).ok().and_then(|w| w.cursor_position());
+
+    // A press that travels too far is a drag (moving an object, a spline point), not a click
+    if let Some((window, at)) = clicks.pressed
+        && cursor_in(window).is_none_or(|now| now.distance(at) > CLICK_DRAG_PX)
     {
-        return;
+        clicks.pressed = None;
     }
 
-    // Get primary window & any active 3D camera with a cursor position available.
-    let window = match windows.single() {
-        Ok(w) => w,
-        Err(_) => return,
-    };
-    let Some(cursor_pos) = window.cursor_position() else {
-        return;
-    };
-
-    let mut best_hit: Option<(Entity, f32)> = None;
-
-    // Try each camera until one gives us a world ray for this cursor pos.
-    for (camera, cam_xform) in cameras.iter() {
-        if !camera.is_active {
+    // Pick on release, at the press position, so it's known the press never became a drag.
+    // Events are read even when ignored, so they don't linger.
+    let mut clicked = None;
+    for ev in ev_mousebtn.read() {
+        if ev.button != MouseButton::Left {
             continue;
         }
-        // Convert screen cursor to a world ray (origin + direction)
-        let Ok(ray) = camera.viewport_to_world(cam_xform, cursor_pos) else {
+        if ev.state.is_pressed() {
+            // Presses handled by another tool or by egui (in that window) never count
+            clicks.pressed = if captured || ui_pointer.wants_pointer(ev.window) {
+                None
+            } else {
+                cursor_in(ev.window).map(|at| (ev.window, at))
+            };
+        } else if let Some((window, at)) = clicks.pressed.take()
+            && window == ev.window
+        {
+            clicked = Some((window, at));
+        }
+    }
+    let Some((window_e, cursor_pos)) = clicked else {
+        return;
+    };
+    let primary = ui_pointer.primary_window();
+
+    // With several viewports in the window, the topmost one under the cursor gets the click
+    let mut viewports: Vec<_> = cameras
+        .iter()
+        .filter(|(camera, ..)| {
+            // Offscreen cameras (e.g. the water reflection) draw to no window
+            camera.is_active
+                && camera_window(camera, primary) == Some(window_e)
+                && camera
+                    .logical_viewport_rect()
+                    .is_some_and(|r| r.contains(cursor_pos))
+        })
+        .collect();
+    viewports.sort_by_key(|(camera, ..)| std::cmp::Reverse(camera.order));
+
+    // Try each of them until one gives us a world ray for this cursor pos.
+    for (camera, cam_xform, projection) in viewports {
+        // Convert screen cursor to a world ray (origin + direction) from the camera's projection
+        let Some(pick) = PickRay::new(camera, cam_xform, projection, cursor_pos) else {
             continue;
         };
-        let origin = ray.origin;
-        let dir = ray.direction;
+        let origin = pick.ray.origin;
+        let dir = *pick.ray.direction;
+        // Boxes grow by a few pixels, so objects shrunk to a speck by zooming out stay clickable
+        let hit = |center: Vec3, half_extents: Vec3| {
+            let half_extents = half_extents + Vec3::splat(pick.pixels_at(PICK_SLOP_PX, center));
+            ray_aabb_intersection(origin, dir, center - half_extents, center + half_extents)
+        };
+        let mut hits: Vec<(Entity, f32)> = Vec::new();
 
         // Test against all editables using their world AABB
         for (e, global, aabb) in q_editables.iter() {
             let world_aabb = aabb_world(*aabb, global);
-            let min = world_aabb.center - world_aabb.half_extents;
-            let max = world_aabb.center + world_aabb.half_extents;
-
-            if let Some(t) = ray_aabb_intersection(origin, *dir, min.into(), max.into()) {
-                // Keep the nearest hit
-                if best_hit.is_none_or(|(_, best_t)| t < best_t) {
-                    best_hit = Some((e, t));
-                }
-            }
+            hits.extend(
+                hit(world_aabb.center.into(), world_aabb.half_extents.into()).map(|t| (e, t)),
+            );
         }
 
         // Also test against author-only ColliderBox entities (no mesh)
         for (e, tf, _cb) in &q_cb {
-            let he = Vec3::splat(1.0);
-            let aabb = obb_to_world_aabb(*tf, he);
-            if let Some(t) = ray_aabb_intersection(
-                origin,
-                *dir,
-                (aabb.center - aabb.half_extents).into(),
-                (aabb.center + aabb.half_extents).into(),
-            ) {
-                // Keep the nearest hit
-                if best_hit.is_none_or(|(_, best_t)| t < best_t) {
-                    best_hit = Some((e, t));
-                }
-            }
+            let aabb = obb_to_world_aabb(*tf, Vec3::splat(1.0));
+            hits.extend(hit(aabb.center.into(), aabb.half_extents.into()).map(|t| (e, t)));
         }
 
-        // If this camera produced any hit, commit selection and stop checking other cameras.
-        if let Some((hit_e, _t)) = best_hit {
-            // Clear previous selection tag, if any
-            if let Ok(prev) = q_selected.single() {
-                commands.entity(prev).remove::<Selected>();
-            }
-
-            // Tag new selection
-            commands.entity(hit_e).insert(Selected);
-
-            // Initialize inspector state for UI
-            let newly_selected = Some(hit_e);
-            let selection_changed = state.selected != newly_selected;
-            state.selected = newly_selected;
-            state.window_open = true;
-            if selection_changed {
-                state.cache_initialized = false;
-                state.last_selected = newly_selected;
-            }
+        // Author-placed lights, fog volumes and splines (no mesh): pick a small box around
+        // the gizmo's center, so clicks inside a fog volume still reach the objects in it
+        for (e, tf) in &q_gizmos {
+            hits.extend(hit(tf.translation, Vec3::splat(0.2)).map(|t| (e, t)));
+        }
 
-            if !edit_state.open {
-                edit_state.open = true;
-            }
+        // If this camera produced any hit, commit selection and stop checking other cameras.
+        if hits.is_empty() {
+            continue;
+        }
+        // Nearest first; an entity hit twice (a mesh with a collider box) keeps its nearest
+        hits.sort_by(|a, b| a.1.total_cmp(&b.1));
+        let mut seen = Vec::with_capacity(hits.len());
+        hits.retain(|(e, _)| {
+            let first = !seen.contains(e);
+            seen.push(*e);
+            first
+        });
 
+        let additive = keys.any_pressed([
+            KeyCode::ControlLeft,
+            KeyCode::ControlRight,
+            KeyCode::ShiftLeft,
+            KeyCode::ShiftRight,
+        ]);
+        if additive {
+            clicks.last_pick = None;
+            ev_toggle.write(ToggleSelectionEvent(hits[0].0));
             return;
         }
+
+        // Clicking the same spot again selects the next object behind the current one
+        let same_spot = clicks
+            .last_pick
+            .is_some_and(|(w, at)| w == window_e && at.distance(cursor_pos) <= PICK_CYCLE_PX);
+        let next = match q_selected.single() {
+            Ok(current) if same_spot => hits
+                .iter()
+                .position(|(e, _)| *e == current)
+                .map_or(0, |i| (i + 1) % hits.len()),
+            _ => 0,
+        };
+        clicks.last_pick = Some((window_e, cursor_pos));
+        ev_select.write(SelectObjectEvent(hits[next].0));
+        return;
     }
 }
 
-/// egui window that shows when an entity is selected. Edits translation & scale live.
-fn inspector_window(
-    mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
-    q_mat: Query<&MeshMaterial3d<StandardMaterial>>,
-    mut state: ResMut<InspectorState>,
-    mut egui_ctxs: EguiContexts,
-    q_selected: Query<Entity, With<Selected>>,
-
-    // Scene I/O resources and events
+/// Flag the scene as modified when editables are added, removed or moved (wind sway doesn't
+/// count; inspector edits to materials and colliders flag it directly).
+fn track_scene_changes(
     mut io: ResMut<SceneIoState>,
-    mut ev_save: EventWriter<SaveSceneEvent>,
-    mut ev_load: EventWriter<LoadSceneEvent>,
+    q_changed: Query<(), (With<Editable>, Changed<Transform>, Without<WindSway>)>,
+    mut removed: RemovedComponents<Editable>,
+    timeline: Option<Res<Timeline>>,
+    playback: Option<Res<TimelinePlayback>>,
+    frame: Res<FrameCount>,
+    objectives: Option<Res<Objectives>>,
+    ground_paint: Option<Res<GroundPaint>>,
+    q_vertex_paint: Query<(), (With<Editable>, Changed<VertexPaint>)>,
+    mut unpainted: RemovedComponents<VertexPaint>,
+) {
+    let removed_any = removed.read().count() > 0;
+    // Only written when set, so reactive updates don't see the state change every frame
+    if io.settling {
+        io.settling = false;
+        return;
+    }
+    // A playing or scrubbed timeline moves objects, that's not an edit
+    let moved = !q_changed.is_empty() && !playback.is_some_and(|p| p.posing(&frame));
+    let keyed = timeline.is_some_and(|t| t.is_changed());
+    let goals = objectives.is_some_and(|o| o.is_changed());
+    let painted = ground_paint.is_some_and(|p| p.is_changed())
+        || !q_vertex_paint.is_empty()
+        || unpainted.read().count() > 0;
+    if removed_any || moved || keyed || goals || painted {
+        io.mark_edited();
+    }
+}
 
-    // Group Transform (&mut) and the duplication read query into a ParamSet to avoid conflicts.
-    // For duplication: read Name/Transform/Mesh/Material/EditableMesh off the selected entity
-    mut ps_tf_dup: ParamSet<(
-        Query<&mut Transform>,
-        Query<(
-            Option<&Name>,
-            &Transform,
-            &Mesh3d,
-            &MeshMaterial3d<StandardMaterial>,
-            Option<&EditableMesh>,
-        )>,
-        Query<&EditableMesh>,
-        Query<&mut EditableMesh>,
-    )>,
+/// How far along X a copy lands from its original when snapping is off.
+const DUPLICATE_OFFSET: f32 = 0.5;
 
-    mut edit_state: ResMut<SceneEditState>,
-    q_cb_ro: Query<&ColliderBox>,
-) {
-    // We now allow the inspector to be open even when nothing is selected.
-    let selected_entity = state.selected;
-    let mut delete_requested = false;
-    let mut deselect_requested = false;
-    let mut copy_requested = false;
+/// Undo steps kept; the oldest go first.
+const MAX_UNDO: usize = 100;
 
-    // Load current values from Transform when opening, then keep editing the cached fields.
-    // Also refresh when selection changes, so new objects don't inherit stale UI values.
-    if let Some(entity) = selected_entity
-        && let Ok(tf) = ps_tf_dup.p0().get_mut(entity)
-    {
-        if !state.cache_initialized || state.last_selected != Some(entity) {
-            state.pos = tf.translation;
-            state.scale = tf.scale;
-            let (rx, ry, rz) = tf.rotation.to_euler(EulerRot::XYZ);
-            state.rot_deg = Vec3::new(rx.to_degrees(), ry.to_degrees(), rz.to_degrees());
-            // Sync color from material
-            if let Ok(h) = q_mat.get(entity) {
-                if let Some(mat) = materials.get(&h.0) {
-                    let s = mat.base_color.to_srgba();
-                    state.color_srgba = egui::Color32::from_rgba_premultiplied(
-                        (s.red * 255.0).clamp(0.0, 255.0) as u8,
-                        (s.green * 255.0).clamp(0.0, 255.0) as u8,
-                        (s.blue * 255.0).clamp(0.0, 255.0) as u8,
-                        (s.alpha * 255.0).clamp(0.0, 255.0) as u8,
-                    );
-                    // Also sync metallic / roughness
-                    state.metallic = mat.metallic;
-                    state.roughness = mat.perceptual_roughness;
-                }
-            }
-            // Sync collider flag from component (if present)
-            if let Ok(em) = ps_tf_dup.p2().get(entity) {
-                state.collider = em.collider;
-            }
-            state.cache_initialized = true;
-            state.window_open = true;
-            state.last_selected = Some(entity);
-        }
+/// The part of an object's material the inspector edits.
+#[derive(Clone, Copy, PartialEq)]
+struct MaterialLook {
+    color: Color,
+    metallic: f32,
+    roughness: f32,
+}
 
-        if let Ok(tf) = ps_tf_dup.p0().get_mut(entity) {
-            // If the window was just opened this frame (or we don't have cache yet), sync cache
-            // This keeps the cache in sync if the entity was transformed by other systems.
-            if !state.window_open {
-                state.pos = tf.translation;
-                state.scale = tf.scale;
-                // Convert current rotation to Euler XYZ (degrees) for UI
-                let (rx, ry, rz) = tf.rotation.to_euler(EulerRot::XYZ);
-                state.rot_deg = Vec3::new(rx.to_degrees(), ry.to_degrees(), rz.to_degrees());
-                state.window_open = true;
-            } else if state.pos == Vec3::ZERO && state.scale == Vec3::ZERO {
-                state.pos = tf.translation;
-                state.scale = tf.scale;
-            }
+impl MaterialLook {
+    fn of(mat: &StandardMaterial) -> Self {
+        Self {
+            color: mat.base_color,
+            metallic: mat.metallic,
+            roughness: mat.perceptual_roughness,
         }
     }
+}
 
-    let ctx = egui_ctxs.ctx_mut().expect("single egui context");
-    let mut open = edit_state.open && state.window_open;
-    let was_open = open;
-    egui::Window::new("Object Inspector")
-        .open(&mut open)
-        .resizable(true)
-        .collapsible(true)
-        .show(ctx, |ui| {
-            ui.label("Edit the selected object’s transform");
+/// On the disabled copy of an object taken out of the scene by undo/redo (or deleted), kept to
+/// bring it back the way Play mode's backups are.
+#[derive(Component)]
+struct UndoBackup;
+
+/// An object undo/redo takes out of the scene and brings back.
+struct Stash {
+    /// The object while it's in the scene (its last entity while it's not)
+    live: Entity,
+    /// Disabled copy while it's out of the scene
+    backup: Option<Entity>,
+    parent: Option<Entity>,
+}
 
-            // Header
-            if let Some(entity) = selected_entity {
-                ui.label(format!("Selected: {entity:?}"));
-            } else {
-                ui.weak("No object selected");
-            }
+enum Edit {
+    Transform {
+        entity: Entity,
+        before: Transform,
+        after: Transform,
+    },
+    Material {
+        entity: Entity,
+        before: MaterialLook,
+        after: MaterialLook,
+    },
+    /// Undoing takes the object out of the scene again
+    Spawn(Stash),
+    /// Undoing brings the object back
+    Delete(Stash),
+}
 
-            ui.separator();
+impl Edit {
+    fn entities_mut(&mut self) -> impl Iterator<Item = &mut Entity> {
+        let (entity, parent) = match self {
+            Edit::Transform { entity, .. } | Edit::Material { entity, .. } => (entity, None),
+            Edit::Spawn(stash) | Edit::Delete(stash) => (&mut stash.live, stash.parent.as_mut()),
+        };
+        std::iter::once(entity).chain(parent)
+    }
 
-            let controls_enabled = selected_entity.is_some();
-            // Disable transform controls when nothing is selected
-            ui.add_enabled_ui(controls_enabled, |ui| {
-                ui.heading("Position");
-                ui.horizontal(|ui| {
-                    ui.label("x");
-                    ui.add(egui::DragValue::new(&mut state.pos.x).speed(0.05));
-                    ui.label("y");
-                    ui.add(egui::DragValue::new(&mut state.pos.y).speed(0.05));
-                    ui.label("z");
-                    ui.add(egui::DragValue::new(&mut state.pos.z).speed(0.05));
-                });
+    fn backup(&self) -> Option<Entity> {
+        match self {
+            Edit::Spawn(stash) | Edit::Delete(stash) => stash.backup,
+            _ => None,
+        }
+    }
+}
 
-                ui.heading("Rotation (deg)");
-                ui.horizontal(|ui| {
-                    ui.label("x");
-                    ui.add(egui::DragValue::new(&mut state.rot_deg.x).speed(0.5));
-                    ui.label("y");
-                    ui.add(egui::DragValue::new(&mut state.rot_deg.y).speed(0.5));
-                    ui.label("z");
-                    ui.add(egui::DragValue::new(&mut state.rot_deg.z).speed(0.5));
-                });
+/// Undo/redo of scene edits: moves, material changes, new objects and deletions, whatever made
+/// them (the inspector, dragging in the viewport, Randomize, build mode, ...). Edits are picked
+/// up through change detection; everything changed while a mouse button is held down (one
+/// drag) becomes one step. Deletions are recorded by whoever deletes, with
+/// [`EditHistory::record_delete`], while the object is still whole.
+#[derive(Resource, Default)]
+pub struct EditHistory {
+    undo: Vec<Vec<Edit>>,
+    redo: Vec<Vec<Edit>>,
+    /// New objects and deletions of the step in progress
+    pending: Vec<Edit>,
+    /// Transforms and materials before the step in progress
+    moved_from: HashMap<Entity, Transform>,
+    restyled_from: HashMap<Entity, MaterialLook>,
+    /// Last seen transform and material of every editable
+    transforms: HashMap<Entity, Transform>,
+    looks: HashMap<Entity, MaterialLook>,
+    /// The changes just made by undo/redo aren't new edits
+    applying: bool,
+}
 
-                ui.heading("Scale");
-                ui.horizontal(|ui| {
-                    ui.label("x");
-                    ui.add(
-                        egui::DragValue::new(&mut state.scale.x)
-                            .speed(0.02)
-                            .range(0.001..=1000.0),
-                    );
-                    ui.label("y");
-                    ui.add(
-                        egui::DragValue::new(&mut state.scale.y)
-                            .speed(0.02)
-                            .range(0.001..=1000.0),
-                    );
-                    ui.label("z");
-                    ui.add(
-                        egui::DragValue::new(&mut state.scale.z)
-                            .speed(0.02)
-                            .range(0.001..=1000.0),
-                    );
-                });
-            });
+/// Step back or forward through [`EditHistory`].
+#[derive(Event, Clone, Copy, PartialEq, Eq)]
+pub enum EditHistoryEvent {
+    Undo,
+    Redo,
+}
 
-            ui.separator();
+impl EditHistory {
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty() || self.has_pending()
+    }
 
-            ui.add_enabled_ui(controls_enabled, |ui| {
-                ui.horizontal(|ui| {
-                    ui.vertical(|ui| {
-                        ui.heading("Color");
-                        {
-                            use egui::color_picker::Alpha;
-                            let mut c = state.color_srgba;
-                            egui::color_picker::color_edit_button_srgba(ui, &mut c, Alpha::Opaque);
-                            if c != state.color_srgba {
-                                state.color_srgba = c;
-                                // Apply immediately to material (if available)
-                                if let Some(e) = selected_entity {
-                                    if let Ok(h) = q_mat.get(e) {
-                                        if let Some(mat) = materials.get_mut(h) {
-                                            let (r, g, b, a) = (
-                                                c.r() as f32 / 255.0,
-                                                c.g() as f32 / 255.0,
-                                                c.b() as f32 / 255.0,
-                                                c.a() as f32 / 255.0,
-                                            );
-                                            mat.base_color = Color::srgba(r, g, b, a);
-                                        }
-                                    }
-                                }
-                            }
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
 
-                            if ui.button("Reset Color").clicked() {
-                                state.color_srgba =
-                                    egui::Color32::from_rgba_premultiplied(209, 209, 219, 255);
-                                if let Some(e) = selected_entity {
-                                    if let Ok(h) = q_mat.get(e) {
-                                        if let Some(mat) = materials.get_mut(h) {
-                                            let (r, g, b, a) = (
-                                                state.color_srgba.r() as f32 / 255.0,
-                                                state.color_srgba.g() as f32 / 255.0,
-                                                state.color_srgba.b() as f32 / 255.0,
-                                                state.color_srgba.a() as f32 / 255.0,
-                                            );
-                                            mat.base_color = Color::srgba(r, g, b, a);
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    });
+    fn has_pending(&self) -> bool {
+        !self.pending.is_empty() || !self.moved_from.is_empty() || !self.restyled_from.is_empty()
+    }
 
-                    ui.separator();
-                    ui.vertical(|ui| {
-                        ui.heading("Material");
-                        ui.label("Metallic");
-                        let _ = ui.add(
-                            egui::Slider::new(&mut state.metallic, 0.0..=1.0).fixed_decimals(3),
-                        );
-                        ui.label("Roughness");
-                        let _ = ui.add(
-                            egui::Slider::new(&mut state.roughness, 0.0..=1.0).fixed_decimals(3),
-                        );
-                    });
+    /// Record that `e` (a child of `parent`) is about to be deleted, keeping a disabled copy of
+    /// it to bring back. Call before deleting it, in the same command queue.
+    pub fn record_delete(&mut self, commands: &mut Commands, e: Entity, parent: Option<Entity>) {
+        let backup = disabled_copy(commands, e, UndoBackup, true);
+        self.pending.push(Edit::Delete(Stash {
+            live: e,
+            backup: Some(backup),
+            parent,
+        }));
+    }
 
-                    ui.vertical(|ui| {
-                        ui.heading("Physics");
-                        ui.label("Collider");
-                        egui::ComboBox::from_label("")
-                            .selected_text(match state.collider {
-                                Some(true) => "On",
-                                Some(false) => "Off",
-                                None => "Unset",
-                            })
-                            .show_ui(ui, |ui| {
-                                ui.selectable_value(
-                                    &mut state.collider,
-                                    Some(true),
-                                    "On (collider)",
+    /// Make the edits in progress one undo step.
+    fn commit(&mut self, commands: &mut Commands) {
+        if !self.has_pending() {
+            return;
+        }
+        let mut step = Vec::new();
+        // Moves and material changes first, so undoing them comes after bringing a deleted
+        // object back
+        for (entity, before) in std::mem::take(&mut self.moved_from) {
+            if let Some(&after) = self.transforms.get(&entity)
+                && after != before
+            {
+                step.push(Edit::Transform {
+                    entity,
+                    before,
+                    after,
+                });
+            }
+        }
+        for (entity, before) in std::mem::take(&mut self.restyled_from) {
+            if let Some(&after) = self.looks.get(&entity)
+                && after != before
+            {
+                step.push(Edit::Material {
+                    entity,
+                    before,
+                    after,
+                });
+            }
+        }
+        step.append(&mut self.pending);
+        if step.is_empty() {
+            return;
+        }
+        self.undo.push(step);
+        // A new edit forgets what was undone
+        for step in std::mem::take(&mut self.redo) {
+            drop_backups(commands, &step);
+        }
+        if self.undo.len() > MAX_UNDO {
+            let oldest = self.undo.remove(0);
+            drop_backups(commands, &oldest);
+        }
+    }
+
+    /// Forget everything (a scene was loaded or replayed).
+    pub(crate) fn clear(&mut self, commands: &mut Commands) {
+        self.pending.retain(|edit| edit.backup().is_some());
+        let pending = std::mem::take(&mut self.pending);
+        for step in self
+            .undo
+            .drain(..)
+            .chain(self.redo.drain(..))
+            .chain(std::iter::once(pending))
+        {
+            drop_backups(commands, &step);
+        }
+        self.moved_from.clear();
+        self.restyled_from.clear();
+        self.transforms.clear();
+        self.looks.clear();
+    }
+
+    /// Point every recorded edit of `from` at `to` (an object brought back as a new entity).
+    fn rename(&mut self, from: Entity, to: Entity) {
+        for edit in self.undo.iter_mut().chain(self.redo.iter_mut()).flatten() {
+            for e in edit.entities_mut() {
+                if *e == from {
+                    *e = to;
+                }
+            }
+        }
+    }
+}
+
+fn drop_backups(commands: &mut Commands, step: &[Edit]) {
+    for backup in step.iter().filter_map(Edit::backup) {
+        commands.entity(backup).despawn();
+    }
+}
+
+/// Pick up this frame's edits, and close the step once nothing changed and no mouse button
+/// is held. After Update's commands and asset events, before change tracking clears
+/// `settling`.
+fn record_edits(
+    mut commands: Commands,
+    mut history: ResMut<EditHistory>,
+    io: Res<SceneIoState>,
+    mode: Res<EditorMode>,
+    playback: Option<Res<TimelinePlayback>>,
+    frame: Res<FrameCount>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    materials: Res<Assets<StandardMaterial>>,
+    mut ev_load: EventReader<LoadSceneEvent>,
+    mut ev_assets: EventReader<AssetEvent<StandardMaterial>>,
+    mut removed: RemovedComponents<Editable>,
+    q_added: Query<(Entity, Option<&ChildOf>), Added<Editable>>,
+    // Wind sway moves objects every frame, like in change tracking
+    q_moved: Query<(Entity, &Transform), (With<Editable>, Changed<Transform>, Without<WindSway>)>,
+    q_looks: Query<(Entity, Ref<MeshMaterial3d<StandardMaterial>>), With<Editable>>,
+) {
+    let history = &mut *history;
+    if ev_load.read().count() > 0 {
+        history.clear(&mut commands);
+    }
+    // Loads, streaming, Play and its rollback, timeline playback and scrubbing, and undo/redo
+    // itself change objects without it being an edit: only the last seen values follow
+    let quiet = io.settling
+        || *mode == EditorMode::Play
+        || playback.is_some_and(|p| p.posing(&frame))
+        || std::mem::take(&mut history.applying);
+
+    for e in removed.read() {
+        history.transforms.remove(&e);
+        history.looks.remove(&e);
+    }
+    let mut changed = false;
+    for (e, child_of) in &q_added {
+        if !quiet {
+            history.pending.push(Edit::Spawn(Stash {
+                live: e,
+                backup: None,
+                parent: child_of.map(ChildOf::parent),
+            }));
+            changed = true;
+        }
+    }
+    for (e, tf) in &q_moved {
+        if let Some(before) = history.transforms.insert(e, *tf)
+            && before != *tf
+            && !quiet
+        {
+            history.moved_from.entry(e).or_insert(before);
+            changed = true;
+        }
+    }
+
+    let restyled: HashSet<AssetId<StandardMaterial>> = ev_assets
+        .read()
+        .filter_map(|ev| match ev {
+            AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+    for (e, mat) in &q_looks {
+        if !mat.is_changed() && !restyled.contains(&mat.0.id()) {
+            continue;
+        }
+        let Some(look) = materials.get(&mat.0).map(MaterialLook::of) else {
+            continue;
+        };
+        if let Some(before) = history.looks.insert(e, look)
+            && before != look
+            && !quiet
+        {
+            history.restyled_from.entry(e).or_insert(before);
+            changed = true;
+        }
+    }
+
+    if !changed && !mouse.any_pressed([MouseButton::Left, MouseButton::Right]) {
+        history.commit(&mut commands);
+    }
+}
+
+/// Undo or redo a step.
+fn apply_edit_history(
+    mut commands: Commands,
+    mut events: EventReader<EditHistoryEvent>,
+    mut history: ResMut<EditHistory>,
+    mut state: ResMut<InspectorState>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    cache: Res<AssetCache>,
+    mut io: ResMut<SceneIoState>,
+    q_mat: Query<&MeshMaterial3d<StandardMaterial>>,
+    q_child_of: Query<&ChildOf>,
+    q_alive: Query<()>,
+) {
+    for ev in events.read() {
+        let history = &mut *history;
+        history.commit(&mut commands);
+        let (from, to) = match ev {
+            EditHistoryEvent::Undo => (&mut history.undo, &mut history.redo),
+            EditHistoryEvent::Redo => (&mut history.redo, &mut history.undo),
+        };
+        let Some(mut step) = from.pop() else {
+            continue;
+        };
+        let undo = *ev == EditHistoryEvent::Undo;
+
+        // Undone back to front, redone front to back
+        let mut renamed: Vec<(Entity, Entity)> = Vec::new();
+        let order: Vec<usize> = if undo {
+            (0..step.len()).rev().collect()
+        } else {
+            (0..step.len()).collect()
+        };
+        for i in order {
+            for e in step[i].entities_mut() {
+                if let Some(&(_, to)) = renamed.iter().find(|(from, _)| from == e) {
+                    *e = to;
+                }
+            }
+            let is_delete = matches!(step[i], Edit::Delete(_));
+            match &mut step[i] {
+                Edit::Transform {
+                    entity,
+                    before,
+                    after,
+                } => {
+                    let tf = if undo { *before } else { *after };
+                    if let Ok(mut ec) = commands.get_entity(*entity) {
+                        ec.try_insert(tf);
+                    }
+                }
+                Edit::Material {
+                    entity,
+                    before,
+                    after,
+                } => {
+                    let look = if undo { *before } else { *after };
+                    let Ok(handle) = q_mat.get(*entity) else {
+                        continue;
+                    };
+                    // Cached materials are shared with other objects: change a private copy
+                    let handle = if cache.is_shared_material(&handle.0) {
+                        let Some(copy) = materials.get(&handle.0).cloned() else {
+                            continue;
+                        };
+                        let copy = materials.add(copy);
+                        commands
+                            .entity(*entity)
+                            .insert(MeshMaterial3d(copy.clone()));
+                        copy
+                    } else {
+                        handle.0.clone()
+                    };
+                    if let Some(mat) = materials.get_mut(&handle) {
+                        mat.base_color = look.color;
+                        mat.metallic = look.metallic;
+                        mat.perceptual_roughness = look.roughness;
+                    }
+                }
+                Edit::Spawn(stash) | Edit::Delete(stash) => {
+                    // Undoing a deletion or redoing a new object brings it back
+                    if is_delete == undo {
+                        let Some(backup) = stash.backup.take() else {
+                            continue;
+                        };
+                        let e = live_copy::<UndoBackup>(&mut commands, backup);
+                        if let Some(parent) = stash.parent.filter(|&p| q_alive.contains(p)) {
+                            commands.entity(e).insert(ChildOf(parent));
+                        }
+                        commands.entity(backup).despawn();
+                        renamed.push((std::mem::replace(&mut stash.live, e), e));
+                    } else if q_alive.contains(stash.live) {
+                        stash.parent = q_child_of.get(stash.live).ok().map(ChildOf::parent);
+                        let backup = disabled_copy(&mut commands, stash.live, UndoBackup, true);
+                        commands.entity(stash.live).despawn();
+                        stash.backup = Some(backup);
+                        if state.selected == Some(stash.live) {
+                            state.selected = None;
+                            state.last_selected = None;
+                            state.cache_initialized = false;
+                        }
+                    }
+                }
+            }
+        }
+        to.push(step);
+        for (from, to) in renamed {
+            history.rename(from, to);
+        }
+        history.applying = true;
+        io.mark_edited();
+    }
+}
+
+/// Carry the rest of the selection, `others`, along with an inspector edit that took `active`
+/// from `before` to `after` (local transforms), so the group keeps its shape. Objects that are
+/// already carried by a selected ancestor are left to it.
+pub(crate) fn move_selection_with(
+    active: Entity,
+    (before, after): (Transform, Transform),
+    pivot: GroupPivot,
+    others: &[Entity],
+    q_tf: &mut Query<&mut Transform>,
+    q_child_of: &Query<&ChildOf>,
+    q_gt: &Query<&GlobalTransform>,
+) {
+    let parent_of = |e: Entity| {
+        q_child_of
+            .get(e)
+            .ok()
+            .and_then(|c| q_gt.get(c.parent()).ok())
+            .copied()
+    };
+    let to_world = |local: Transform, parent: Option<GlobalTransform>| match parent {
+        Some(parent) => parent.mul_transform(local).compute_transform(),
+        None => local,
+    };
+    let to_local = |world: Transform, parent: Option<GlobalTransform>| match parent {
+        Some(parent) => GlobalTransform::from(world).reparented_to(&parent),
+        None => world,
+    };
+
+    let selected = |e: Entity| e == active || others.contains(&e);
+    let followers: Vec<(Entity, Option<GlobalTransform>, Transform)> = others
+        .iter()
+        .copied()
+        .filter(|&e| {
+            !q_child_of.iter_ancestors(e).any(selected)
+                && !q_child_of.iter_ancestors(active).any(|a| a == e)
+        })
+        .filter_map(|e| {
+            let parent = parent_of(e);
+            Some((e, parent, to_world(*q_tf.get(e).ok()?, parent)))
+        })
+        .collect();
+
+    let active_parent = parent_of(active);
+    let (before, after) = (
+        to_world(before, active_parent),
+        to_world(after, active_parent),
+    );
+    let around = match pivot {
+        GroupPivot::Active => before.translation,
+        GroupPivot::Center => {
+            let sum: Vec3 = followers.iter().map(|(.., w)| w.translation).sum();
+            (before.translation + sum) / (followers.len() + 1) as f32
+        }
+    };
+
+    for (e, parent, world) in followers {
+        let next = to_local(follow_group_edit(&world, &before, &after, around), parent);
+        if let Ok(mut tf) = q_tf.get_mut(e) {
+            tf.set_if_neq(next);
+        }
+    }
+    // Around the selection's center the active object swings around too
+    if pivot == GroupPivot::Center
+        && let Ok(mut tf) = q_tf.get_mut(active)
+    {
+        let moved = Transform {
+            translation: follow_group_edit(&before, &before, &after, around).translation,
+            ..after
+        };
+        tf.set_if_neq(to_local(moved, active_parent));
+    }
+}
+
+/// Where `tf` goes when its group follows an object going from `before` to `after` (all in world
+/// space): moved by as much, and turned and scaled around `pivot` along that object's axes.
+/// `tf`'s own axes are lengthened as much as that stretch lengthens them, which is exact when
+/// they line up with the object's and keeps `tf` unsheared when they don't.
+fn follow_group_edit(
+    tf: &Transform,
+    before: &Transform,
+    after: &Transform,
+    pivot: Vec3,
+) -> Transform {
+    let ratio = Vec3::select(
+        before.scale.cmpeq(Vec3::ZERO),
+        Vec3::ONE,
+        after.scale / before.scale,
+    );
+    let turn = after.rotation * before.rotation.inverse();
+    // The offset from the pivot along the object's axes, scaled, then turned with it
+    let offset = before.rotation.inverse() * (tf.translation - pivot);
+    let stretch =
+        |axis: Vec3| (ratio * (before.rotation.inverse() * (tf.rotation * axis))).length();
+    Transform {
+        translation: pivot
+            + (after.translation - before.translation)
+            + after.rotation * (ratio * offset),
+        rotation: (turn * tf.rotation).normalize(),
+        scale: tf.scale * Vec3::new(stretch(Vec3::X), stretch(Vec3::Y), stretch(Vec3::Z)),
+    }
+}
+
+/// A name for a copy of `name` that no object has yet: numbered on from it ("Block" → "Block 2",
+/// "Block 2" → "Block 3").
+pub(crate) fn copy_name(name: &str, taken: &HashSet<&str>) -> String {
+    let (base, after) = match name.rsplit_once(' ') {
+        Some((base, n)) if !base.is_empty() => match n.parse::<u32>() {
+            Ok(n) => (base, n + 1),
+            Err(_) => (name, 2),
+        },
+        _ => (name, 2),
+    };
+    (after..)
+        .map(|n| format!("{base} {n}"))
+        .find(|candidate| !taken.contains(candidate.as_str()))
+        .unwrap_or_else(|| format!("{name} copy"))
+}
+
+/// Where to put the next copy in a run: as far on from `to` as `to` is from `from`, turning by
+/// the same amount too, so runs of copies can follow a curve (a spiral staircase).
+fn repeat_step(from: &Transform, to: &Transform) -> Transform {
+    let turn = to.rotation * from.rotation.inverse();
+    Transform {
+        translation: to.translation + turn * (to.translation - from.translation),
+        rotation: (turn * to.rotation).normalize(),
+        scale: to.scale,
+    }
+}
+
+/// Select an object (from a viewport click or the hierarchy) and show it in the inspector.
+/// The last object added to the selection is the one the inspector shows.
+fn apply_selection(
+    mut ev_select: EventReader<SelectObjectEvent>,
+    mut ev_toggle: EventReader<ToggleSelectionEvent>,
+    mut state: ResMut<InspectorState>,
+    mut commands: Commands,
+    q_selected: Query<Entity, With<Selected>>,
+    mut edit_state: ResMut<SceneEditState>,
+) {
+    let select = ev_select.read().last().map(|ev| ev.0);
+    let toggles: Vec<Entity> = ev_toggle.read().map(|ev| ev.0).collect();
+    if select.is_none() && toggles.is_empty() {
+        return;
+    }
+
+    let mut selection: Vec<Entity> = q_selected.iter().collect();
+    let mut primary = state.selected;
+    if let Some(entity) = select {
+        selection = vec![entity];
+        primary = Some(entity);
+    }
+    for entity in toggles {
+        if let Some(i) = selection.iter().position(|&e| e == entity) {
+            selection.remove(i);
+            if primary == Some(entity) {
+                primary = selection.last().copied();
+            }
+        } else {
+            selection.push(entity);
+            primary = Some(entity);
+        }
+    }
+
+    // Sync the selection tags
+    for e in &q_selected {
+        if !selection.contains(&e) {
+            commands.entity(e).remove::<Selected>();
+        }
+    }
+    for &e in &selection {
+        commands.entity(e).insert(Selected);
+    }
+
+    // Initialize inspector state for UI
+    let selection_changed = state.selected != primary;
+    state.selected = primary;
+    state.window_open = true;
+    if selection_changed {
+        state.cache_initialized = false;
+        state.last_selected = primary;
+    }
+
+    if !edit_state.open {
+        edit_state.open = true;
+    }
+}
+
+/// Whether the selection's materials may differ from what [`compare_selected_materials`] last
+/// saw: another active object, a newly selected one or a swapped material.
+fn selected_materials_stale(
+    state: Res<InspectorState>,
+    q_changed: Query<
+        (),
+        (
+            With<Selected>,
+            Or<(Added<Selected>, Changed<MeshMaterial3d<StandardMaterial>>)>,
+        ),
+    >,
+) -> bool {
+    state.mixed_for != state.selected || !q_changed.is_empty()
+}
+
+/// Note which material fields of the rest of a multi-selection differ from the active
+/// object's, so the inspector can show them as mixed.
+fn compare_selected_materials(
+    mut state: ResMut<InspectorState>,
+    materials: Res<Assets<StandardMaterial>>,
+    q_mat: Query<&MeshMaterial3d<StandardMaterial>>,
+    q_selected: Query<(Entity, &MeshMaterial3d<StandardMaterial>), With<Selected>>,
+) {
+    let mut mixed = MaterialFields::default();
+    if let Some(primary) = state
+        .selected
+        .and_then(|e| q_mat.get(e).ok())
+        .and_then(|h| materials.get(&h.0))
+    {
+        for mat in q_selected
+            .iter()
+            .filter(|&(e, _)| Some(e) != state.selected)
+            .filter_map(|(_, h)| materials.get(&h.0))
+        {
+            mixed.color |= mat.base_color != primary.base_color;
+            mixed.metallic |= mat.metallic != primary.metallic;
+            mixed.roughness |= mat.perceptual_roughness != primary.perceptual_roughness;
+        }
+    }
+    state.mixed = mixed;
+    state.mixed_for = state.selected;
+}
+
+/// egui window that shows when an entity is selected. Edits translation & scale live.
+fn inspector_window(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    q_mat: Query<&MeshMaterial3d<StandardMaterial>>,
+    mut state: ResMut<InspectorState>,
+    mut egui_ctxs: EguiContexts,
+    q_selected: Query<Entity, With<Selected>>,
+
+    // Scene I/O resources and events
+    mut io: ResMut<SceneIoState>,
+    mut actions: EventWriter<EditorAction>,
+    mut ev_load: EventWriter<LoadSceneEvent>,
+    mut ev_merge: EventWriter<MergeBakeEvent>,
+    mut cache: ResMut<AssetCache>,
+
+    // Transforms and mesh info, read and written in turn
+    mut ps_tf: ParamSet<(
+        Query<&mut Transform>,
+        Query<&EditableMesh>,
+        Query<&mut EditableMesh>,
+    )>,
+
+    mut edit_state: ResMut<SceneEditState>,
+    q_cb_ro: Query<&ColliderBox>,
+    dissolve: Res<DissolveSettings>,
+    (
+        mut q_wind,
+        mut q_light,
+        mut q_light_anim,
+        mut q_reflection,
+        mut q_fog,
+        mut q_decal,
+        mut q_stairs,
+        mut q_spline,
+        q_tile,
+        mut prefs,
+        dock,
+        q_views,
+        q_children,
+        q_slots,
+        q_props,
+        (
+            mut placement,
+            spawn_kinds,
+            mut q_custom,
+            q_rot_edit,
+            q_child_of,
+            q_gt,
+            mut q_creature,
+            mut q_elevator,
+            mut q_surface,
+            mut q_rail,
+            mut q_sign,
+            mut library,
+            mut q_triplanar,
+            mut q_toon,
+            toon_shading,
+            (mut q_glow, bloom_isolation, mut history),
+        ),
+    ): (
+        Query<&mut WindSway>,
+        Query<
+            (
+                Option<&mut PointLight>,
+                Option<&mut SpotLight>,
+                Option<&mut LightAnimBase>,
+            ),
+            Or<(With<PointLight>, With<SpotLight>)>,
+        >,
+        Query<&mut LightAnimation>,
+        Query<&mut PlanarReflection>,
+        Query<&mut SceneFogVolume>,
+        Query<&mut SceneDecal>,
+        Query<&mut StairsParams>,
+        Query<&mut SplinePath>,
+        Query<(), With<InstancedTile>>,
+        ResMut<EditorPrefs>,
+        Res<DockLayout>,
+        Query<&ViewLayers>,
+        Query<&Children>,
+        Query<(Option<&Name>, &MeshMaterial3d<StandardMaterial>), SlotFilter>,
+        Query<&CustomProperties>,
+        (
+            ResMut<Placement>,
+            Res<SpawnKinds>,
+            Query<&mut CustomKindData>,
+            Query<&RotationEdit>,
+            Query<&ChildOf>,
+            Query<&GlobalTransform>,
+            Query<&mut CreatureSpawner>,
+            Query<&mut Elevator>,
+            Query<&mut Surface>,
+            Query<&mut CameraRail>,
+            Query<&mut Sign>,
+            ResMut<MaterialLibrary>,
+            Query<&mut Triplanar>,
+            Query<&mut Toon>,
+            Res<ToonShading>,
+            (Query<&mut Glow>, Res<BloomIsolation>, ResMut<EditHistory>),
+        ),
+    ),
+) {
+    // We now allow the inspector to be open even when nothing is selected.
+    let selected_entity = state.selected;
+    let mut delete_requested = false;
+    let mut deselect_requested = false;
+
+    // Load current values from Transform when opening, then keep editing the cached fields.
+    // Also refresh when selection changes, so new objects don't inherit stale UI values.
+    // Fields show the transform relative to the parent (as stored) or in world space
+    let space = prefs.transform_space;
+    let parent_gt = selected_entity
+        .and_then(|e| q_child_of.get(e).ok())
+        .and_then(|c| q_gt.get(c.parent()).ok())
+        .copied();
+    // Remembered rotation values are relative to the parent too
+    let edits_local = space == TransformSpace::Local || parent_gt.is_none();
+    let rot_edit_of = |e: Entity| q_rot_edit.get(e).ok().filter(|_| edits_local);
+
+    if let Some(entity) = selected_entity
+        && let Ok(tf) = ps_tf.p0().get_mut(entity)
+    {
+        let shown = space.shown(*tf, parent_gt.as_ref());
+        if !state.cache_initialized || state.last_selected != Some(entity) || state.space != space {
+            state.pos = shown.translation;
+            state.scale = shown.scale;
+            state.space = space;
+            let rot_edit = q_rot_edit.get(entity).ok();
+            state.rot_mode = rot_edit.map_or_else(RotationMode::default, |r| r.mode);
+            state.rot = displayed_rotation(rot_edit_of(entity), state.rot_mode, shown.rotation);
+            // Sync color from material
+            if let Ok(h) = q_mat.get(entity) {
+                if let Some(mat) = materials.get(&h.0) {
+                    state.color_srgba = color32_of(mat.base_color);
+                    // Also sync metallic / roughness
+                    state.metallic = mat.metallic;
+                    state.roughness = mat.perceptual_roughness;
+                }
+            }
+            // Sync collider flag from component (if present)
+            if let Ok(em) = ps_tf.p1().get(entity) {
+                state.collider = em.collider;
+            }
+            state.cache_initialized = true;
+            state.window_open = true;
+            state.last_selected = Some(entity);
+            state.written = None;
+        } else if state.written.is_some_and(|w| w != *tf) {
+            // Moved outside the inspector (viewport tools): show the new values
+            state.pos = shown.translation;
+            state.scale = shown.scale;
+            state.rot = displayed_rotation(rot_edit_of(entity), state.rot_mode, shown.rotation);
+        }
+
+        // If the window was just opened this frame (or we don't have cache yet), sync cache
+        // This keeps the cache in sync if the entity was transformed by other systems.
+        if !state.window_open {
+            state.pos = shown.translation;
+            state.scale = shown.scale;
+            // Convert current rotation to the object's rotation mode for UI
+            state.rot = displayed_rotation(rot_edit_of(entity), state.rot_mode, shown.rotation);
+            state.window_open = true;
+        } else if state.pos == Vec3::ZERO && state.scale == Vec3::ZERO {
+            state.pos = shown.translation;
+            state.scale = shown.scale;
+        }
+    }
+
+    // The rest of a multi-selection follows material edits; flag fields where it differs
+    let others: Vec<Entity> = q_selected
+        .iter()
+        .filter(|&e| Some(e) != selected_entity)
+        .collect();
+    let mixed = state.mixed;
+    let mut edited = MaterialFields::default();
+    let mut preset = None;
+    let mut moved = TransformFields::default();
+    let mut rot_mode_changed = false;
+
+    let Ok(ctx) = egui_ctxs.ctx_mut() else {
+        return;
+    };
+    let mut open = edit_state.open && state.window_open;
+    let was_open = open;
+    DockedWindow::new(DockPanel::Inspector, "Object Inspector")
+        .open(&mut open)
+        .show(ctx, &dock, |ui| {
+            ui.label("Edit the selected object’s transform");
+
+            // Header
+            if let Some(entity) = selected_entity {
+                if others.is_empty() {
+                    ui.label(format!("Selected: {entity:?}"));
+                } else {
+                    ui.label(format!("Selected: {entity:?} (+{} more)", others.len()));
+                }
+            } else {
+                ui.weak("No object selected");
+            }
+
+            ui.separator();
+
+            let controls_enabled = selected_entity.is_some();
+            // Disable transform controls when nothing is selected
+            ui.add_enabled_ui(controls_enabled, |ui| {
+                let snap = prefs.snap;
+                let (move_step, rotate_step, scale_step) =
+                    (prefs.move_snap, prefs.rotate_snap_deg, prefs.scale_snap);
+
+                ui.horizontal(|ui| {
+                    ui.label("Axes");
+                    for s in TransformSpace::ALL {
+                        ui.selectable_value(&mut prefs.transform_space, s, s.label());
+                    }
+                })
+                .response
+                .on_hover_text("Edit relative to the parent object, or in world space");
+                if !others.is_empty() {
+                    ui.horizontal(|ui| {
+                        ui.label("Pivot");
+                        for p in GroupPivot::ALL {
+                            ui.selectable_value(&mut state.pivot, p, p.label());
+                        }
+                    })
+                    .response
+                    .on_hover_text("What the whole selection turns and scales around");
+                }
+
+                ui.heading("Position");
+                ui.horizontal(|ui| {
+                    let v3 = &mut state.pos;
+                    for (label, v) in [("x", &mut v3.x), ("y", &mut v3.y), ("z", &mut v3.z)] {
+                        ui.label(label);
+                        moved.translation |=
+                            snapped_drag(ui, v, 0.05, f32::MIN..=f32::MAX, move_step, snap);
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    ui.heading("Rotation");
+                    let mut mode = state.rot_mode;
+                    egui::ComboBox::from_id_salt("rotation_mode")
+                        .selected_text(mode.label())
+                        .show_ui(ui, |ui| {
+                            for m in RotationMode::ALL {
+                                ui.selectable_value(&mut mode, m, m.label());
+                            }
+                        })
+                        .response
+                        .on_hover_text("How the rotation is entered; kept with the object");
+                    // Same rotation, shown the new way
+                    if mode != state.rot_mode {
+                        let rotation = state.rot_mode.rotation_of(state.rot);
+                        state.rot = mode.values_of(rotation);
+                        state.rot_mode = mode;
+                        rot_mode_changed = true;
+                    }
+                });
+                ui.horizontal(|ui| {
+                    let mode = state.rot_mode;
+                    for (i, label) in mode.fields().iter().enumerate() {
+                        ui.label(*label);
+                        let v = &mut state.rot[i];
+                        moved.rotation |= if mode.is_angle(i) {
+                            snapped_drag(ui, v, 0.5, f32::MIN..=f32::MAX, rotate_step, snap)
+                        } else {
+                            // Axis and quaternion components, which don't snap
+                            snapped_drag(ui, v, 0.01, -1.0..=1.0, 0.1, false)
+                        };
+                    }
+                    if mode != RotationMode::Quaternion {
+                        ui.weak("deg");
+                    }
+                });
+
+                ui.heading("Scale");
+                ui.horizontal(|ui| {
+                    let before = state.scale;
+                    let mut edited_axis = None;
+                    for (i, label) in ["x", "y", "z"].into_iter().enumerate() {
+                        ui.label(label);
+                        if snapped_drag(
+                            ui,
+                            &mut state.scale[i],
+                            0.02,
+                            0.001..=1000.0,
+                            scale_step,
+                            snap,
+                        ) {
+                            edited_axis = Some(i);
+                        }
+                        ui.toggle_value(&mut state.scale_link[i], "🔗")
+                            .described("Linked axes scale together, keeping their proportions");
+                    }
+                    if ui
+                        .small_button("All")
+                        .on_hover_text("Link every axis (uniform scale)")
+                        .clicked()
+                    {
+                        let all = !state.scale_link.iter().all(|l| *l);
+                        state.scale_link = [all; 3];
+                    }
+
+                    // Linked axes follow the edited one by the same factor
+                    if let Some(i) = edited_axis {
+                        moved.scale = true;
+                        if state.scale_link[i] && before[i] != 0.0 {
+                            let factor = state.scale[i] / before[i];
+                            let link = state.scale_link;
+                            for j in (0..3).filter(|&j| j != i && link[j]) {
+                                state.scale[j] = (before[j] * factor).clamp(0.001, 1000.0);
+                            }
+                        }
+                    }
+                });
+            });
+
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut prefs.snap, "Snap");
+                ui.add_enabled_ui(prefs.snap, |ui| {
+                    ui.add(
+                        egui::DragValue::new(&mut prefs.move_snap)
+                            .speed(0.01)
+                            .range(0.01..=10.0)
+                            .prefix("move "),
+                    );
+                    ui.add(
+                        egui::DragValue::new(&mut prefs.rotate_snap_deg)
+                            .speed(0.5)
+                            .range(1.0..=90.0)
+                            .prefix("rot ")
+                            .suffix("°"),
+                    );
+                    ui.add(
+                        egui::DragValue::new(&mut prefs.scale_snap)
+                            .speed(0.01)
+                            .range(0.01..=10.0)
+                            .prefix("scale "),
+                    );
+                });
+            });
+
+            ui.separator();
+
+            ui.add_enabled_ui(controls_enabled, |ui| {
+                ui.horizontal(|ui| {
+                    ui.vertical(|ui| {
+                        ui.horizontal(|ui| {
+                            ui.heading("Color");
+                            if mixed.color {
+                                ui.weak("(mixed)");
+                            }
+                        });
+                        {
+                            use egui::color_picker::Alpha;
+                            // Applied to the material below, together with metallic/roughness
+                            edited.color |= egui::color_picker::color_edit_button_srgba(
+                                ui,
+                                &mut state.color_srgba,
+                                Alpha::Opaque,
+                            )
+                            .changed();
+
+                            if ui.button("Reset Color").clicked() {
+                                state.color_srgba =
+                                    egui::Color32::from_rgba_premultiplied(209, 209, 219, 255);
+                                edited.color = true;
+                            }
+                        }
+                    });
+
+                    ui.separator();
+                    ui.vertical(|ui| {
+                        ui.heading("Material");
+                        ui.label(if mixed.metallic {
+                            "Metallic (mixed)"
+                        } else {
+                            "Metallic"
+                        });
+                        edited.metallic |= ui
+                            .add(
+                                egui::Slider::new(&mut state.metallic, 0.0..=1.0).fixed_decimals(3),
+                            )
+                            .changed();
+                        ui.label(if mixed.roughness {
+                            "Roughness (mixed)"
+                        } else {
+                            "Roughness"
+                        });
+                        edited.roughness |= ui
+                            .add(
+                                egui::Slider::new(&mut state.roughness, 0.0..=1.0)
+                                    .fixed_decimals(3),
+                            )
+                            .changed();
+                    });
+
+                    ui.vertical(|ui| {
+                        ui.heading("Physics");
+                        ui.label("Collider");
+                        egui::ComboBox::from_label("")
+                            .selected_text(match state.collider {
+                                Some(true) => "On",
+                                Some(false) => "Off",
+                                None => "Unset",
+                            })
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(
+                                    &mut state.collider,
+                                    Some(true),
+                                    "On (collider)",
                                 );
                                 ui.selectable_value(
                                     &mut state.collider,
@@ -573,19 +1915,19 @@ fn inspector_window(
                                 ui.selectable_value(&mut state.collider, None, "Unset / default");
                             });
                     });
-
-                    // Apply material changes immediately
-                    if let Some(e) = state.selected {
-                        if let Ok(h) = q_mat.get(e) {
-                            if let Some(mat) = materials.get_mut(&h.0) {
-                                mat.metallic = state.metallic.clamp(0.0, 1.0);
-                                mat.perceptual_roughness = state.roughness.clamp(0.0, 1.0);
-                            }
-                        }
-                    }
                 });
+                preset = material_presets_ui(ui, &mut library);
             });
 
+            // Meshes with a material per primitive
+            if let Some(entity) = selected_entity {
+                let slots = material_slots(entity, &q_children, &q_slots);
+                if slots.len() > 1 {
+                    ui.separator();
+                    material_slots_inspector_ui(ui, &mut commands, &cache, &mut materials, &slots);
+                }
+            }
+
             // ColliderBox inspector
             if let Some(entity) = selected_entity {
                 if let Ok(_cb_ro) = q_cb_ro.get(entity) {
@@ -617,19 +1959,246 @@ fn inspector_window(
                 }
             }
 
+            // Point / spot light parameters
+            if let Some(entity) = selected_entity
+                && let Ok((point, spot, base)) = q_light.get_mut(entity)
+            {
+                ui.separator();
+                light_inspector_ui(ui, point, spot, base);
+            }
+
+            // Fog volume parameters
+            if let Some(entity) = selected_entity
+                && let Ok(fog) = q_fog.get_mut(entity)
+            {
+                ui.separator();
+                fog_volume_inspector_ui(ui, fog);
+            }
+
+            // Creature spawner parameters
+            if let Some(entity) = selected_entity
+                && let Ok(spawner) = q_creature.get_mut(entity)
+            {
+                ui.separator();
+                if creature_spawner_inspector_ui(ui, spawner) {
+                    io.mark_edited();
+                }
+            }
+
+            // Parametric stairs
+            if let Some(entity) = selected_entity
+                && let Ok(params) = q_stairs.get_mut(entity)
+            {
+                ui.separator();
+                stairs_inspector_ui(ui, params);
+            }
+
+            // Spline generation mode and control points
+            if let Some(entity) = selected_entity
+                && let Ok(spline) = q_spline.get_mut(entity)
+            {
+                ui.separator();
+                spline_inspector_ui(ui, spline);
+            }
+            if let Some(entity) = selected_entity
+                && let Ok(spline) = q_spline.get(entity)
+            {
+                ui.separator();
+                if camera_rail_inspector_ui(
+                    ui,
+                    &mut commands,
+                    entity,
+                    spline,
+                    q_rail.get_mut(entity).ok(),
+                ) {
+                    io.mark_edited();
+                }
+            }
+
+            // Decal texture / opacity
+            if let Some(entity) = selected_entity
+                && let Ok(decal) = q_decal.get_mut(entity)
+            {
+                ui.separator();
+                decal_inspector_ui(ui, entity, decal);
+            }
+
+            // Parameters of a registered custom kind
+            if let Some(entity) = selected_entity
+                && let Ok(SpawnKind::Custom(id)) = ps_tf.p1().get(entity).map(|info| info.kind)
+                && let Some(kind) = spawn_kinds.get(id)
+                && let Ok(data) = q_custom.get_mut(entity)
+            {
+                ui.separator();
+                if custom_kind_inspector_ui(ui, kind, data) {
+                    io.mark_edited();
+                }
+            }
+
+            // Flicker / pulse / color cycle for lights and emissive materials
+            if let Some(entity) = selected_entity {
+                ui.separator();
+                light_animation_inspector_ui(
+                    ui,
+                    &mut commands,
+                    entity,
+                    q_light_anim.get_mut(entity).ok(),
+                );
+            }
+
+            // Wind sway (decor objects)
+            if let Some(entity) = selected_entity {
+                ui.separator();
+                let mut sway_on = q_wind.get(entity).is_ok();
+                if ui.checkbox(&mut sway_on, "Wind sway").changed() {
+                    if sway_on {
+                        commands.entity(entity).insert(WindSway::default());
+                    } else {
+                        commands.entity(entity).remove::<(WindSway, SwayRest)>();
+                    }
+                }
+                if let Ok(mut current) = q_wind.get_mut(entity) {
+                    let mut sway = *current;
+                    ui.add(
+                        egui::Slider::new(&mut sway.amplitude, 0.0..=30.0).text("Amplitude (deg)"),
+                    );
+                    ui.add(
+                        egui::Slider::new(&mut sway.frequency, 0.05..=3.0).text("Frequency (Hz)"),
+                    );
+                    ui.add(
+                        egui::Slider::new(&mut sway.phase, 0.0..=std::f32::consts::TAU)
+                            .text("Phase"),
+                    );
+                    if current.set_if_neq(sway) {
+                        io.mark_edited();
+                    }
+                }
+            }
+
+            // Elevator platform, surface behavior and sign text (mesh objects)
+            if let Some(entity) = selected_entity
+                && q_light.get(entity).is_err()
+                && q_cb_ro.get(entity).is_err()
+                && q_fog.get(entity).is_err()
+                && q_decal.get(entity).is_err()
+                && q_spline.get(entity).is_err()
+                && q_creature.get(entity).is_err()
+            {
+                let mut changed = false;
+                ui.separator();
+                changed |= elevator_inspector_ui(
+                    ui,
+                    &mut commands,
+                    entity,
+                    q_elevator.get_mut(entity).ok(),
+                );
+                ui.separator();
+                changed |=
+                    surface_inspector_ui(ui, &mut commands, entity, q_surface.get_mut(entity).ok());
+                ui.separator();
+                changed |=
+                    sign_inspector_ui(ui, &mut commands, entity, q_sign.get_mut(entity).ok());
+                ui.separator();
+                changed |= triplanar_inspector_ui(
+                    ui,
+                    &mut commands,
+                    entity,
+                    q_triplanar.get_mut(entity).ok(),
+                );
+                changed |= toon_inspector_ui(
+                    ui,
+                    &mut commands,
+                    entity,
+                    q_toon.get_mut(entity).ok(),
+                    toon_shading.all,
+                );
+                changed |= glow_inspector_ui(
+                    ui,
+                    &mut commands,
+                    entity,
+                    q_glow.get_mut(entity).ok(),
+                    bloom_isolation.glow_only,
+                );
+                if changed {
+                    io.mark_edited();
+                }
+            }
+
+            // Key/value metadata for scripts and exporters
+            if let Some(entity) = selected_entity {
+                ui.separator();
+                if custom_properties_inspector_ui(
+                    ui,
+                    &mut commands,
+                    entity,
+                    q_props.get(entity).ok(),
+                ) {
+                    io.mark_edited();
+                }
+            }
+
+            // Reveals and cutaways: only show from some orbit views
+            if let Some(entity) = selected_entity {
+                ui.separator();
+                if view_layers_inspector_ui(ui, &mut commands, entity, q_views.get(entity).ok()) {
+                    io.mark_edited();
+                }
+            }
+
+            // Planar reflection (flat mesh surfaces such as water)
+            if let Some(entity) = selected_entity
+                && q_light.get(entity).is_err()
+                && q_cb_ro.get(entity).is_err()
+                && q_fog.get(entity).is_err()
+                && q_decal.get(entity).is_err()
+                && q_spline.get(entity).is_err()
+                && q_creature.get(entity).is_err()
+            {
+                ui.separator();
+                reflection_inspector_ui(
+                    ui,
+                    &mut commands,
+                    entity,
+                    q_reflection.get_mut(entity).ok(),
+                );
+            }
+
+            // GPU instancing for repeated blocks (mesh objects only)
+            if let Some(entity) = selected_entity
+                && q_mat.get(entity).is_ok()
+            {
+                let mut tile = q_tile.get(entity).is_ok();
+                if ui
+                    .checkbox(&mut tile, "Instanced tile")
+                    .on_hover_text(
+                        "Draw with all tiles of the same mesh in one call (base color only)",
+                    )
+                    .changed()
+                {
+                    if tile {
+                        commands.entity(entity).insert(InstancedTile);
+                    } else {
+                        commands.entity(entity).remove::<InstancedTile>();
+                    }
+                }
+            }
+
             ui.add_enabled_ui(controls_enabled, |ui| {
                 ui.separator();
 
                 ui.horizontal(|ui| {
                     if ui.button("Reset Pos").clicked() {
                         state.pos = Vec3::ZERO;
+                        moved.translation = true;
                     }
                     if ui.button("Reset Scale (1,1,1)").clicked() {
                         state.scale = Vec3::ONE;
+                        moved.scale = true;
                     }
                     ui.horizontal(|ui| {
                         if ui.button("Reset Rotation (0,0,0)").clicked() {
-                            state.rot_deg = Vec3::ZERO;
+                            state.rot = state.rot_mode.values_of(Quat::IDENTITY);
+                            moved.rotation = true;
                         }
                     });
                 });
@@ -648,12 +2217,24 @@ fn inspector_window(
                         .desired_width(200.0);
                     ui.add(te);
                     if ui.button("Save").clicked() {
-                        ev_save.write(SaveSceneEvent);
+                        actions.write(EditorAction::Save);
                     }
                     if ui.button("Load").clicked() {
                         ev_load.write(LoadSceneEvent);
                     }
                 });
+                // In the browser, Save downloads the file and Load reads the copy kept in
+                // localStorage; other files come in through Upload
+                #[cfg(target_arch = "wasm32")]
+                ui.horizontal(|ui| {
+                    if ui.button("Upload...").clicked() {
+                        scene_io::pick_upload();
+                    }
+                    if ui.button("Restore autosave").clicked() {
+                        io.filename = scene_io::AUTOSAVE.into();
+                        ev_load.write(LoadSceneEvent);
+                    }
+                });
             });
 
             ui.small("Tip: hold Shift for finer DragValue steps");
@@ -663,9 +2244,33 @@ fn inspector_window(
                 ui.horizontal(|ui| {
                     if ui
                         .add_enabled(selected_entity.is_some(), egui::Button::new("Copy"))
+                        .on_hover_text("Duplicate the selected object, just beside it (Ctrl+D)")
+                        .clicked()
+                    {
+                        state.duplicate = true;
+                    }
+                    if ui
+                        .add_enabled(state.last_copy.is_some(), egui::Button::new("Repeat"))
+                        .on_hover_text(
+                            "Copy the last copy again, moved and turned as far from it as it is \
+                             from its original (Ctrl+Shift+D)",
+                        )
                         .clicked()
                     {
-                        copy_requested = true;
+                        state.repeat_copy = true;
+                    }
+
+                    // Fold the selected static objects with this material into one mesh
+                    if ui
+                        .add_enabled(selected_entity.is_some(), egui::Button::new("Merge"))
+                        .on_hover_text(
+                            "Bake the selected objects sharing this material into a single mesh",
+                        )
+                        .clicked()
+                        && let Some(seed) = selected_entity
+                    {
+                        ev_merge.write(MergeBakeEvent { seed });
+                        deselect_requested = true;
                     }
 
                     if ui.button("Deselect").clicked() {
@@ -691,84 +2296,55 @@ fn inspector_window(
                 ui.selectable_value(&mut state.spawn_kind, SpawnKind::Sphere, "Sphere");
                 ui.selectable_value(&mut state.spawn_kind, SpawnKind::Plane, "Plane");
                 ui.selectable_value(&mut state.spawn_kind, SpawnKind::Prism, "Prism");
+                ui.selectable_value(&mut state.spawn_kind, SpawnKind::Stairs, "Stairs");
                 ui.selectable_value(&mut state.spawn_kind, SpawnKind::ColliderBox, "ColliderBox");
             });
-            if ui.button("Add object at (0,0,0)").clicked() {
-                if matches!(state.spawn_kind, SpawnKind::ColliderBox) {
-                    let e = commands
-                        .spawn((
-                            Editable,
-                            Name::new("Bounds Collider"),
-                            Transform::from_translation(Vec3::ZERO),
-                            EditableMesh {
-                                kind: SpawnKind::ColliderBox,
-                                collider: Some(true),
-                            },
-                            ColliderBox,
-                        ))
-                        .id();
-                    let newly_selected = Some(e);
-                    state.selected = newly_selected;
-                    state.window_open = true;
-                    state.cache_initialized = false;
-                    state.last_selected = newly_selected;
-                    return; // skip mesh path
-                }
-
-                // Remove previous Selected tag (single-select)
-                if let Ok(prev) = q_selected.single() {
-                    commands.entity(prev).remove::<Selected>();
-                }
-                // Build mesh
-                let mesh_handle = match state.spawn_kind {
-                    SpawnKind::Cuboid => meshes.add(Mesh::from(Cuboid::new(1.0, 1.0, 1.0))),
-                    SpawnKind::Sphere => meshes.add(Mesh::from(Sphere::new(0.5))),
-                    SpawnKind::Plane => meshes.add(Mesh::from(Plane3d::default())),
-                    SpawnKind::Prism => meshes.add(Extrusion::new(
-                        Triangle2d::new(
-                            Vec2::new(0.0, 1.0),
-                            Vec2::new(0.0, 0.0),
-                            Vec2::new(1.0, 0.0),
-                        ),
-                        1.0,
-                    )),
-                    // This branch should never happen
-                    SpawnKind::ColliderBox => meshes.add(Mesh::from(Sphere::new(0.0))),
-                };
-                // Simple default material
-                let mat = materials.add(StandardMaterial {
-                    base_color: Color::srgb(0.82, 0.82, 0.86),
-                    perceptual_roughness: 0.6,
-                    metallic: 0.0,
-                    ..Default::default()
+            ui.horizontal(|ui| {
+                ui.label("Light:");
+                ui.selectable_value(&mut state.spawn_kind, SpawnKind::PointLight, "Point");
+                ui.selectable_value(&mut state.spawn_kind, SpawnKind::SpotLight, "Spot");
+            });
+            ui.horizontal(|ui| {
+                ui.label("Effect:");
+                ui.selectable_value(&mut state.spawn_kind, SpawnKind::FogVolume, "Fog");
+                ui.selectable_value(&mut state.spawn_kind, SpawnKind::Decal, "Decal");
+                ui.selectable_value(&mut state.spawn_kind, SpawnKind::Spline, "Spline");
+                ui.selectable_value(&mut state.spawn_kind, SpawnKind::Creatures, "Creatures");
+            });
+            if spawn_kinds.iter().next().is_some() {
+                ui.horizontal_wrapped(|ui| {
+                    ui.label("Custom:");
+                    for kind in spawn_kinds.iter() {
+                        ui.selectable_value(
+                            &mut state.spawn_kind,
+                            SpawnKind::Custom(kind.id()),
+                            kind.label,
+                        );
+                    }
                 });
-                // Spawn at origin with unit scale; tag as Editable and Selected
-                let e = commands
-                    .spawn((
-                        Mesh3d(mesh_handle),
-                        MeshMaterial3d(mat),
-                        Transform::from_translation(Vec3::ZERO).with_scale(Vec3::ONE),
-                        Editable,
-                        EditableMesh {
-                            kind: state.spawn_kind,
-                            collider: None,
-                        },
-                        Selected,
-                        Name::new(match state.spawn_kind {
-                            SpawnKind::Cuboid => "Cuboid",
-                            SpawnKind::Sphere => "Sphere",
-                            SpawnKind::Plane => "Plane",
-                            SpawnKind::Prism => "Prism",
-                            SpawnKind::ColliderBox => "ColliderBox",
-                        }),
-                    ))
-                    .id();
-                // Focus the new entity in the inspector
-                let newly_selected = Some(e);
-                state.selected = newly_selected;
-                state.window_open = true;
-                state.cache_initialized = false; // force reload pos/scale from Transform on next frame
-                state.last_selected = newly_selected;
+            }
+            let placing = placement.is_active();
+            let label = if placing {
+                "Cancel placing"
+            } else {
+                "Place in scene"
+            };
+            if ui
+                .button(label)
+                .on_hover_text(
+                    "Click in the viewport to drop it; R turns it 90°, right click cancels",
+                )
+                .clicked()
+            {
+                if placing {
+                    placement.cancel();
+                } else {
+                    placement.start(state.spawn_kind);
+                }
+            }
+            // Switching shapes while placing swaps the one following the cursor
+            if placing {
+                placement.set_kind(state.spawn_kind);
             }
         });
 
@@ -780,35 +2356,136 @@ fn inspector_window(
     // Apply changes live while open
     if open {
         if let Some(entity) = selected_entity {
-            if let Ok(mut tf) = ps_tf_dup.p0().get_mut(entity) {
-                tf.translation = state.pos;
-                tf.scale = state.scale;
-                let (rx, ry, rz) = (
-                    state.rot_deg.x.to_radians(),
-                    state.rot_deg.y.to_radians(),
-                    state.rot_deg.z.to_radians(),
+            let mut group_edit = None;
+            if let Ok(mut tf) = ps_tf.p0().get_mut(entity) {
+                // Only the fields the widgets changed, so an Euler round trip doesn't nudge the
+                // rotation (and flag the object as moved) every frame
+                if moved.any() {
+                    let mut next = space.shown(*tf, parent_gt.as_ref());
+                    if moved.translation {
+                        next.translation = state.pos;
+                    }
+                    if moved.rotation {
+                        next.rotation = state.rot_mode.rotation_of(state.rot);
+                    }
+                    if moved.scale {
+                        next.scale = state.scale;
+                    }
+                    let before = *tf;
+                    tf.set_if_neq(space.to_local(next, parent_gt.as_ref()));
+                    group_edit = Some((before, *tf));
+                }
+                state.written = Some(*tf);
+                // Remember the values as entered, so they show (and save) as typed
+                if moved.rotation || rot_mode_changed {
+                    let edit = if edits_local {
+                        RotationEdit {
+                            mode: state.rot_mode,
+                            values: state.rot,
+                        }
+                    } else {
+                        RotationEdit::from_rotation(state.rot_mode, tf.rotation)
+                    };
+                    commands.entity(entity).insert(edit);
+                }
+                if rot_mode_changed {
+                    io.mark_edited();
+                }
+            }
+            // The rest of the selection moves, turns and scales along
+            if let Some((before, after)) = group_edit
+                && !others.is_empty()
+            {
+                move_selection_with(
+                    entity,
+                    (before, after),
+                    state.pivot,
+                    &others,
+                    &mut ps_tf.p0(),
+                    &q_child_of,
+                    &q_gt,
                 );
-                tf.rotation = Quat::from_euler(EulerRot::XYZ, rx, ry, rz);
             }
 
             // Keep material in sync with UI (color + metal/rough)
-            if let Ok(h) = q_mat.get(entity) {
-                if let Some(mat) = materials.get_mut(h) {
-                    let c = state.color_srgba;
-                    let (r, g, b, a) = (
-                        c.r() as f32 / 255.0,
-                        c.g() as f32 / 255.0,
-                        c.b() as f32 / 255.0,
-                        c.a() as f32 / 255.0,
-                    );
-                    mat.base_color = Color::srgba(r, g, b, a);
-                    mat.metallic = state.metallic.clamp(0.0, 1.0);
-                    mat.perceptual_roughness = state.roughness.clamp(0.0, 1.0);
+            let (metallic, roughness) = (
+                state.metallic.clamp(0.0, 1.0),
+                state.roughness.clamp(0.0, 1.0),
+            );
+            if !edited.any()
+                && let Some(mat) = q_mat.get(entity).ok().and_then(|h| materials.get(&h.0))
+                && (color32_of(mat.base_color) != state.color_srgba
+                    || mat.metallic != metallic
+                    || mat.perceptual_roughness != roughness)
+            {
+                // Changed elsewhere (Randomize, timeline, scripts): show the new values
+                state.color_srgba = color32_of(mat.base_color);
+                state.metallic = mat.metallic;
+                state.roughness = mat.perceptual_roughness;
+            }
+            // A preset replaces the whole material, shared with every object using it
+            if let Some(preset) = &preset {
+                let handle = cache.material(&mut materials, preset.plain());
+                for e in std::iter::once(entity).chain(others.iter().copied()) {
+                    if q_mat.contains(e) {
+                        commands.entity(e).insert(MeshMaterial3d(handle.clone()));
+                    }
+                }
+                state.color_srgba = color32_of(preset.plain().base_color);
+                state.metallic = preset.metallic;
+                state.roughness = preset.roughness;
+                io.mark_edited();
+            }
+            // Only the fields touched this frame, so the rest of a multi-selection keeps its
+            // own values. Each material is edited once however many selected objects use it.
+            if edited.any() {
+                let mut users: HashMap<
+                    AssetId<StandardMaterial>,
+                    (Handle<StandardMaterial>, Vec<Entity>),
+                > = HashMap::new();
+                for e in std::iter::once(entity).chain(others.iter().copied()) {
+                    if let Ok(h) = q_mat.get(e) {
+                        users
+                            .entry(h.0.id())
+                            .or_insert_with(|| (h.0.clone(), Vec::new()))
+                            .1
+                            .push(e);
+                    }
+                }
+                for (handle, entities) in users.into_values() {
+                    // Cached materials are shared with other objects: edit a private copy
+                    let handle = if cache.is_shared_material(&handle) {
+                        let Some(copy) = materials.get(&handle).cloned() else {
+                            continue;
+                        };
+                        let copy = materials.add(copy);
+                        for e in entities {
+                            commands.entity(e).insert(MeshMaterial3d(copy.clone()));
+                        }
+                        copy
+                    } else {
+                        handle
+                    };
+                    if let Some(mat) = materials.get_mut(&handle) {
+                        if edited.color {
+                            mat.base_color = color_of(state.color_srgba);
+                        }
+                        if edited.metallic {
+                            mat.metallic = metallic;
+                        }
+                        if edited.roughness {
+                            mat.perceptual_roughness = roughness;
+                        }
+                    }
                 }
+                io.mark_edited();
             }
             // Keep collider flag in sync with UI
-            if let Ok(mut em) = ps_tf_dup.p3().get_mut(entity) {
+            if let Ok(mut em) = ps_tf.p2().get_mut(entity)
+                && em.collider != state.collider
+            {
                 em.collider = state.collider;
+                io.mark_edited();
             }
         }
     } else {
@@ -820,186 +2497,766 @@ fn inspector_window(
         state.scale = Vec3::ZERO;
     }
 
-    // Perform deferred duplication if requested
-    if copy_requested {
-        if let Some(src) = selected_entity {
-            if let Ok((name_opt, tf, mesh3d, mat3d, mesh_info_opt)) = ps_tf_dup.p1().get(src) {
-                // Clone (duplicate) the material asset so edits to the new copy won't affect the original
-                let new_mat_handle = if let Some(src_mat) = materials.get(&mat3d.0) {
-                    let cloned = src_mat.clone();
-                    materials.add(cloned)
-                } else {
-                    // Fallback: if the material is missing from Assets, reuse the handle
-                    mat3d.0.clone()
-                };
+    // Perform deferred deselect or deletion if requested
+    if deselect_requested || delete_requested {
+        if deselect_requested {
+            state.selected = None;
+            // Remove visual/logic selection tags
+            for e in &q_selected {
+                commands.entity(e).remove::<Selected>();
+            }
+        } else if delete_requested {
+            if let Some(e) = state.selected.take() {
+                let parent = q_child_of.get(e).ok().map(ChildOf::parent);
+                history.record_delete(&mut commands, e, parent);
+                dissolve_away(&mut commands, e, &dissolve);
+            }
+        }
+
+        // Keep the inspector open in "no selection" mode
+        state.window_open = true;
+        state.cache_initialized = false;
+        state.last_selected = None;
+        // zero out cached values to visually indicate "inactive"
+        state.pos = Vec3::ZERO;
+        state.scale = Vec3::ZERO;
+        state.rot = [0.0; 4];
+    }
+}
 
-                // TODO: tweak name
-                let new_name = name_opt
-                    .map(|n| format!("{} (copy)", n.as_str()))
-                    .unwrap_or_else(|| "Copy".to_string());
+/// Copy the selected object, or the last copy again, when the inspector or its shortcut asks.
+/// The copy is the object as it would be saved and loaded back, under the same parent.
+fn duplicate_selected(
+    mut commands: Commands,
+    mut state: ResMut<InspectorState>,
+    mut scene: ParamSet<(SceneObjects, ResMut<Assets<StandardMaterial>>)>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut cache: ResMut<AssetCache>,
+    prefs: Res<EditorPrefs>,
+    dissolve: Res<DissolveSettings>,
+    q_tf: Query<&Transform>,
+    q_child_of: Query<&ChildOf>,
+    q_children: Query<&Children>,
+    q_shells: Query<(&Mesh3d, &MeshMaterial3d<StandardMaterial>), With<OutlineShell>>,
+    q_names: Query<&Name, With<Editable>>,
+    q_selected: Query<Entity, With<Selected>>,
+) {
+    // Repeating copies the last copy again, moved on by the same step
+    let repeat = std::mem::take(&mut state.repeat_copy);
+    let copy = std::mem::take(&mut state.duplicate);
+    let repeat_from = state
+        .last_copy
+        .filter(|(_, copy)| q_tf.contains(*copy))
+        .filter(|_| repeat);
+    let src = match repeat_from {
+        Some((_, copy)) => copy,
+        None if copy => {
+            let Some(selected) = state.selected else {
+                return;
+            };
+            selected
+        }
+        None => return,
+    };
+    let (Ok(src_tf), Some((_, mut obj))) = (
+        q_tf.get(src).copied(),
+        scene.p0().collect(|e| e == src).pop(),
+    ) else {
+        return;
+    };
 
-                // Keep the same transform (position/rotation/scale) and mesh
-                let mut ecmd = commands.spawn((
-                    mesh3d.clone(),
-                    MeshMaterial3d(new_mat_handle),
-                    *tf,
-                    Editable,
-                    Selected,
-                    Name::new(new_name),
-                ));
-                // Preserve mesh metadata if present (so save/load & inspector know the kind)
-                if let Some(mi) = mesh_info_opt {
-                    ecmd.insert(*mi);
-                }
+    let taken: HashSet<&str> = q_names.iter().map(Name::as_str).collect();
+    let name = obj
+        .name()
+        .map_or_else(|| "Copy".to_string(), |n| copy_name(n, &taken));
+    obj.rename(name.clone());
+    let copy = spawn_scene_object(&mut commands, &mut meshes, &mut scene.p1(), &mut cache, obj);
+    let placed = match repeat_from {
+        Some((from, _)) => repeat_step(&from, &src_tf),
+        // Beside the original rather than inside it, so it's plain there are two
+        None => {
+            let step = if prefs.snap {
+                prefs.move_snap
+            } else {
+                DUPLICATE_OFFSET
+            };
+            let mut beside = src_tf;
+            beside.translation.x += step;
+            beside
+        }
+    };
+    commands.entity(copy).insert((placed, Selected));
+    if let Ok(child_of) = q_child_of.get(src) {
+        commands.entity(copy).insert(ChildOf(child_of.parent()));
+    }
+    // Outline shells come along, named after the copy
+    let shells = q_children.get(src).into_iter().flatten();
+    for (shell_mesh, shell_mat) in shells.filter_map(|&c| q_shells.get(c).ok()) {
+        commands.entity(copy).with_child(outline_shell(
+            shell_mesh.0.clone(),
+            shell_mat.0.clone(),
+            0.0,
+            &name,
+        ));
+    }
+    dissolve_in(&mut commands, copy, &dissolve);
+
+    // The copy becomes the whole selection
+    for prev in &q_selected {
+        commands.entity(prev).remove::<Selected>();
+    }
+    state.selected = Some(copy);
+    state.window_open = true;
+    state.cache_initialized = false;
+    state.last_selected = Some(copy);
+    state.last_copy = Some((src_tf, copy));
+}
+
+/// Every editable object as it would be saved, as one system parameter.
+#[derive(SystemParam)]
+pub(crate) struct SceneObjects<'w, 's> {
+    edit: Query<
+        'w,
+        's,
+        (
+            Entity,
+            Option<&'static Name>,
+            &'static Transform,
+            &'static MeshMaterial3d<StandardMaterial>,
+            Option<&'static EditableMesh>,
+            Option<&'static WindSway>,
+            Option<&'static LightAnimation>,
+            Option<&'static LightAnimBase>,
+            Option<&'static PlanarReflection>,
+            Option<&'static StairsParams>,
+            Option<&'static BakedMember>,
+            Has<InstancedTile>,
+            Option<&'static ViewLayers>,
+            Option<&'static Cutaway>,
+            (
+                Option<&'static HierarchyOrder>,
+                Option<&'static CustomProperties>,
+                Option<&'static SceneJoint>,
+                Option<&'static CustomKindData>,
+                Option<&'static RotationEdit>,
+                Option<&'static Elevator>,
+                Option<&'static Surface>,
+                Option<&'static Sign>,
+                Option<&'static Triplanar>,
+                Option<&'static Toon>,
+                Option<&'static Glow>,
+                Option<&'static VertexPaint>,
+            ),
+        ),
+        (With<Editable>, With<Mesh3d>),
+    >,
+    cb: Query<
+        'w,
+        's,
+        (
+            Entity,
+            &'static Name,
+            &'static Transform,
+            Option<&'static RotationEdit>,
+            &'static ColliderBox,
+            Option<&'static HierarchyOrder>,
+            Option<&'static CustomProperties>,
+        ),
+        With<Editable>,
+    >,
+    lights: Query<
+        'w,
+        's,
+        (
+            Entity,
+            Option<&'static Name>,
+            &'static Transform,
+            Option<&'static RotationEdit>,
+            &'static EditableMesh,
+            Option<&'static PointLight>,
+            Option<&'static SpotLight>,
+            Option<&'static LightAnimation>,
+            Option<&'static LightAnimBase>,
+            Option<&'static HierarchyOrder>,
+            Option<&'static CustomProperties>,
+        ),
+        (With<Editable>, Or<(With<PointLight>, With<SpotLight>)>),
+    >,
+    fog: Query<
+        'w,
+        's,
+        (
+            Entity,
+            Option<&'static Name>,
+            &'static Transform,
+            Option<&'static RotationEdit>,
+            &'static SceneFogVolume,
+            Option<&'static HierarchyOrder>,
+            Option<&'static CustomProperties>,
+        ),
+        With<Editable>,
+    >,
+    decals: Query<
+        'w,
+        's,
+        (
+            Entity,
+            Option<&'static Name>,
+            &'static Transform,
+            Option<&'static RotationEdit>,
+            &'static SceneDecal,
+            Option<&'static HierarchyOrder>,
+            Option<&'static CustomProperties>,
+        ),
+        With<Editable>,
+    >,
+    splines: Query<
+        'w,
+        's,
+        (
+            Entity,
+            Option<&'static Name>,
+            &'static Transform,
+            Option<&'static RotationEdit>,
+            &'static SplinePath,
+            Option<&'static HierarchyOrder>,
+            Option<&'static CustomProperties>,
+            Option<&'static CameraRail>,
+        ),
+        With<Editable>,
+    >,
+    creatures: Query<
+        'w,
+        's,
+        (
+            Entity,
+            Option<&'static Name>,
+            &'static Transform,
+            Option<&'static RotationEdit>,
+            &'static CreatureSpawner,
+            Option<&'static HierarchyOrder>,
+            Option<&'static CustomProperties>,
+        ),
+        With<Editable>,
+    >,
+    materials: Res<'w, Assets<StandardMaterial>>,
+}
+
+impl SceneObjects<'_, '_> {
+    /// The objects `keep` accepts, as they'd be saved, with their entities.
+    pub(crate) fn collect(&self, keep: impl Fn(Entity) -> bool) -> Vec<(Entity, SceneObject)> {
+        let mut objects = Vec::new();
+        // Save standard mesh objects
+        for (
+            e,
+            name,
+            tf,
+            mat_h,
+            mesh_info,
+            sway,
+            anim,
+            anim_base,
+            refl,
+            stairs,
+            baked,
+            tile,
+            views,
+            cut,
+            (
+                order,
+                props,
+                joint,
+                custom,
+                rot_edit,
+                elevator,
+                surface,
+                sign,
+                triplanar,
+                toon,
+                glow,
+                vertex_paint,
+            ),
+        ) in self.edit.iter()
+        {
+            if !keep(e) {
+                continue;
+            }
+            let (rotation_euler_deg, rotation_edit) = saved_rotation(tf.rotation, rot_edit);
 
-                // Update inspector selection to the new entity
-                let new_e = ecmd.id();
-                // Remove Selected from the previous one to keep single-select
-                commands.entity(src).remove::<Selected>();
-                state.selected = Some(new_e);
-                state.window_open = true;
-                state.cache_initialized = false;
-                state.last_selected = Some(new_e);
-            } else if q_cb_ro.get(src).is_ok() {
-                // Duplicate ColliderBox: reuse the same transform; no mesh/material.
-                let tf_copy = if let Ok(tfm) = ps_tf_dup.p0().get(src) {
-                    *tfm
+            // A cut-away object is showing a see-through copy; save its own material
+            let mat_h = cut.and_then(|c| c.original.as_ref()).unwrap_or(&mat_h.0);
+            let (color_rgba, metallic, roughness, emissive) =
+                if let Some(mat) = self.materials.get(mat_h) {
+                    let s = mat.base_color.to_srgba();
+                    // Save the authored emissive, not the current animation frame
+                    let e = anim_base.map(|b| b.emissive).unwrap_or(mat.emissive);
+                    (
+                        [s.red, s.green, s.blue, s.alpha],
+                        mat.metallic,
+                        mat.perceptual_roughness,
+                        (e != LinearRgba::BLACK).then_some([e.red, e.green, e.blue]),
+                    )
                 } else {
-                    Transform::IDENTITY
+                    ([0.82, 0.82, 0.86, 1.0], 0.0, 0.6, None)
                 };
 
-                let new_name = "ColliderBox (copy)".to_string();
-
-                let ecmd = commands.spawn((
-                    tf_copy,
-                    Editable,
-                    Selected,
-                    ColliderBox,
-                    EditableMesh {
-                        kind: SpawnKind::ColliderBox,
-                        collider: Some(true),
-                    },
-                    Name::new(new_name),
-                ));
-
-                // Switch selection to the new copy
-                let new_e = ecmd.id();
-                commands.entity(src).remove::<Selected>();
-                state.selected = Some(new_e);
-                state.window_open = true;
-                state.cache_initialized = false;
-                state.last_selected = Some(new_e);
+            objects.push((
+                e,
+                SceneObject {
+                    name: name.map(|n| n.as_str().to_string()),
+                    kind: mesh_info.unwrap().kind,
+                    position: [tf.translation.x, tf.translation.y, tf.translation.z],
+                    rotation_euler_deg,
+                    rotation_edit,
+                    scale: [tf.scale.x, tf.scale.y, tf.scale.z],
+                    color_rgba,
+                    metallic,
+                    roughness,
+                    collider: mesh_info.unwrap().collider,
+                    wind_sway: sway.copied(),
+                    emissive,
+                    light: None,
+                    light_animation: anim.copied(),
+                    planar_reflection: refl.copied(),
+                    fog_volume: None,
+                    decal: None,
+                    stairs: stairs.copied(),
+                    spline: None,
+                    creatures: None,
+                    camera_rail: None,
+                    bake_group: baked.map(|b| b.group),
+                    instanced_tile: tile,
+                    views: views.copied(),
+                    order: order.map(|o| o.0),
+                    properties: props.cloned(),
+                    joint: joint.cloned(),
+                    elevator: elevator.cloned(),
+                    surface: surface.copied(),
+                    sign: sign.cloned(),
+                    triplanar: triplanar.cloned(),
+                    toon: toon.copied(),
+                    glow: glow.copied(),
+                    vertex_paint: vertex_paint.cloned(),
+                    custom: custom.cloned(),
+                },
+            ));
+        }
+        // Save colliders
+        for (e, name, tf, rot_edit, _cb, order, props) in self.cb.iter() {
+            if !keep(e) {
+                continue;
+            }
+            let (rotation_euler_deg, rotation_edit) = saved_rotation(tf.rotation, rot_edit);
+            objects.push((
+                e,
+                SceneObject {
+                    name: Some(name.to_string()),
+                    kind: SpawnKind::ColliderBox,
+                    position: [tf.translation.x, tf.translation.y, tf.translation.z],
+                    rotation_euler_deg,
+                    rotation_edit,
+                    scale: [tf.scale.x, tf.scale.y, tf.scale.z],
+                    color_rgba: [0.0, 0.0, 0.0, 0.0],
+                    metallic: 0.0,
+                    roughness: 0.0,
+                    collider: Some(true),
+                    wind_sway: None,
+                    emissive: None,
+                    light: None,
+                    light_animation: None,
+                    planar_reflection: None,
+                    fog_volume: None,
+                    decal: None,
+                    stairs: None,
+                    spline: None,
+                    creatures: None,
+                    camera_rail: None,
+                    bake_group: None,
+                    instanced_tile: false,
+                    views: None,
+                    order: order.map(|o| o.0),
+                    properties: props.cloned(),
+                    joint: None,
+                    elevator: None,
+                    surface: None,
+                    sign: None,
+                    triplanar: None,
+                    toon: None,
+                    glow: None,
+                    vertex_paint: None,
+                    custom: None,
+                },
+            ));
+        }
+        // Save author-placed lights
+        for (e, name, tf, rot_edit, mesh_info, point, spot, anim, anim_base, order, props) in
+            self.lights.iter()
+        {
+            if !keep(e) {
+                continue;
+            }
+            let Some((color, light)) = scene_light_of(point, spot, anim_base) else {
+                continue;
+            };
+            let (rotation_euler_deg, rotation_edit) = saved_rotation(tf.rotation, rot_edit);
+            let s = color.to_srgba();
+            objects.push((
+                e,
+                SceneObject {
+                    name: name.map(|n| n.as_str().to_string()),
+                    kind: mesh_info.kind,
+                    position: [tf.translation.x, tf.translation.y, tf.translation.z],
+                    rotation_euler_deg,
+                    rotation_edit,
+                    scale: [tf.scale.x, tf.scale.y, tf.scale.z],
+                    color_rgba: [s.red, s.green, s.blue, s.alpha],
+                    metallic: 0.0,
+                    roughness: 0.0,
+                    collider: None,
+                    wind_sway: None,
+                    emissive: None,
+                    light: Some(light),
+                    light_animation: anim.copied(),
+                    planar_reflection: None,
+                    fog_volume: None,
+                    decal: None,
+                    stairs: None,
+                    spline: None,
+                    creatures: None,
+                    camera_rail: None,
+                    bake_group: None,
+                    instanced_tile: false,
+                    views: None,
+                    order: order.map(|o| o.0),
+                    properties: props.cloned(),
+                    joint: None,
+                    elevator: None,
+                    surface: None,
+                    sign: None,
+                    triplanar: None,
+                    toon: None,
+                    glow: None,
+                    vertex_paint: None,
+                    custom: None,
+                },
+            ));
+        }
+        // Save fog volumes
+        for (e, name, tf, rot_edit, fog, order, props) in self.fog.iter() {
+            if !keep(e) {
+                continue;
+            }
+            let (rotation_euler_deg, rotation_edit) = saved_rotation(tf.rotation, rot_edit);
+            objects.push((
+                e,
+                SceneObject {
+                    name: name.map(|n| n.as_str().to_string()),
+                    kind: SpawnKind::FogVolume,
+                    position: [tf.translation.x, tf.translation.y, tf.translation.z],
+                    rotation_euler_deg,
+                    rotation_edit,
+                    scale: [tf.scale.x, tf.scale.y, tf.scale.z],
+                    color_rgba: [0.0, 0.0, 0.0, 0.0],
+                    metallic: 0.0,
+                    roughness: 0.0,
+                    collider: None,
+                    wind_sway: None,
+                    emissive: None,
+                    light: None,
+                    light_animation: None,
+                    planar_reflection: None,
+                    fog_volume: Some(*fog),
+                    decal: None,
+                    stairs: None,
+                    spline: None,
+                    creatures: None,
+                    camera_rail: None,
+                    bake_group: None,
+                    instanced_tile: false,
+                    views: None,
+                    order: order.map(|o| o.0),
+                    properties: props.cloned(),
+                    joint: None,
+                    elevator: None,
+                    surface: None,
+                    sign: None,
+                    triplanar: None,
+                    toon: None,
+                    glow: None,
+                    vertex_paint: None,
+                    custom: None,
+                },
+            ));
+        }
+        // Save creature spawners
+        for (e, name, tf, rot_edit, spawner, order, props) in self.creatures.iter() {
+            if !keep(e) {
+                continue;
             }
+            let (rotation_euler_deg, rotation_edit) = saved_rotation(tf.rotation, rot_edit);
+            let [r, g, b] = spawner.color;
+            objects.push((
+                e,
+                SceneObject {
+                    name: name.map(|n| n.as_str().to_string()),
+                    kind: SpawnKind::Creatures,
+                    position: [tf.translation.x, tf.translation.y, tf.translation.z],
+                    rotation_euler_deg,
+                    rotation_edit,
+                    scale: [tf.scale.x, tf.scale.y, tf.scale.z],
+                    color_rgba: [r, g, b, 1.0],
+                    metallic: 0.0,
+                    roughness: 0.0,
+                    collider: None,
+                    wind_sway: None,
+                    emissive: None,
+                    light: None,
+                    light_animation: None,
+                    planar_reflection: None,
+                    fog_volume: None,
+                    decal: None,
+                    stairs: None,
+                    spline: None,
+                    creatures: Some(*spawner),
+                    camera_rail: None,
+                    bake_group: None,
+                    instanced_tile: false,
+                    views: None,
+                    order: order.map(|o| o.0),
+                    properties: props.cloned(),
+                    joint: None,
+                    elevator: None,
+                    surface: None,
+                    sign: None,
+                    triplanar: None,
+                    toon: None,
+                    glow: None,
+                    vertex_paint: None,
+                    custom: None,
+                },
+            ));
         }
-    }
-
-    // Perform deferred deselect or deletion if requested
-    if deselect_requested || delete_requested {
-        if deselect_requested {
-            if let Some(e) = state.selected.take() {
-                // Remove visual/logic selection tag
-                commands.entity(e).remove::<Selected>();
+        // Save decals
+        for (e, name, tf, rot_edit, decal, order, props) in self.decals.iter() {
+            if !keep(e) {
+                continue;
             }
-        } else if delete_requested {
-            if let Some(e) = state.selected.take() {
-                commands.entity(e).despawn();
+            let (rotation_euler_deg, rotation_edit) = saved_rotation(tf.rotation, rot_edit);
+            objects.push((
+                e,
+                SceneObject {
+                    name: name.map(|n| n.as_str().to_string()),
+                    kind: SpawnKind::Decal,
+                    position: [tf.translation.x, tf.translation.y, tf.translation.z],
+                    rotation_euler_deg,
+                    rotation_edit,
+                    scale: [tf.scale.x, tf.scale.y, tf.scale.z],
+                    color_rgba: [1.0, 1.0, 1.0, decal.opacity],
+                    metallic: 0.0,
+                    roughness: 0.0,
+                    collider: None,
+                    wind_sway: None,
+                    emissive: None,
+                    light: None,
+                    light_animation: None,
+                    planar_reflection: None,
+                    fog_volume: None,
+                    decal: Some(decal.clone()),
+                    stairs: None,
+                    spline: None,
+                    creatures: None,
+                    camera_rail: None,
+                    bake_group: None,
+                    instanced_tile: false,
+                    views: None,
+                    order: order.map(|o| o.0),
+                    properties: props.cloned(),
+                    joint: None,
+                    elevator: None,
+                    surface: None,
+                    sign: None,
+                    triplanar: None,
+                    toon: None,
+                    glow: None,
+                    vertex_paint: None,
+                    custom: None,
+                },
+            ));
+        }
+        // Save splines (generated content is rebuilt on load)
+        for (e, name, tf, rot_edit, spline, order, props, rail) in self.splines.iter() {
+            if !keep(e) {
+                continue;
             }
+            let (rotation_euler_deg, rotation_edit) = saved_rotation(tf.rotation, rot_edit);
+            let [r, g, b] = spline.color;
+            objects.push((
+                e,
+                SceneObject {
+                    name: name.map(|n| n.as_str().to_string()),
+                    kind: SpawnKind::Spline,
+                    position: [tf.translation.x, tf.translation.y, tf.translation.z],
+                    rotation_euler_deg,
+                    rotation_edit,
+                    scale: [tf.scale.x, tf.scale.y, tf.scale.z],
+                    color_rgba: [r, g, b, 1.0],
+                    metallic: 0.0,
+                    roughness: 0.8,
+                    collider: None,
+                    wind_sway: None,
+                    emissive: None,
+                    light: None,
+                    light_animation: None,
+                    planar_reflection: None,
+                    fog_volume: None,
+                    decal: None,
+                    stairs: None,
+                    spline: Some(spline.clone()),
+                    creatures: None,
+                    camera_rail: rail.cloned(),
+                    bake_group: None,
+                    instanced_tile: false,
+                    views: None,
+                    order: order.map(|o| o.0),
+                    properties: props.cloned(),
+                    joint: None,
+                    elevator: None,
+                    surface: None,
+                    sign: None,
+                    triplanar: None,
+                    toon: None,
+                    glow: None,
+                    vertex_paint: None,
+                    custom: None,
+                },
+            ));
         }
 
-        // Keep the inspector open in "no selection" mode
-        state.window_open = true;
-        state.cache_initialized = false;
-        state.last_selected = None;
-        // zero out cached values to visually indicate "inactive"
-        state.pos = Vec3::ZERO;
-        state.scale = Vec3::ZERO;
-        state.rot_deg = Vec3::ZERO;
+        objects
     }
 }
 
+/// Hierarchy order first, then by name, so saves are stable and diff well.
+pub(crate) fn sort_objects(objects: &mut [SceneObject]) {
+    objects.sort_by(|a, b| {
+        let order = |o: &SceneObject| o.order.unwrap_or(u32::MAX);
+        order(a)
+            .cmp(&order(b))
+            .then_with(|| a.name.cmp(&b.name))
+            .then_with(|| {
+                a.position
+                    .partial_cmp(&b.position)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
+    });
+}
+
+/// Gather the objects and start writing them out in the background (see
+/// [`crate::scene_export`]).
 fn save_scene_system(
     mut ev: EventReader<SaveSceneEvent>,
-    io: Res<SceneIoState>,
-    q_edit: Query<
-        (
-            Option<&Name>,
-            &Transform,
-            &Mesh3d,
-            &MeshMaterial3d<StandardMaterial>,
-            Option<&EditableMesh>,
-        ),
-        With<Editable>,
-    >,
-    q_cb: Query<(&Name, &Transform, &ColliderBox), With<Editable>>,
-    materials: Res<Assets<StandardMaterial>>,
+    mut io: ResMut<SceneIoState>,
+    mut export: ResMut<SceneExport>,
+    mut toasts: Option<ResMut<Toasts>>,
+    time: Res<Time<Real>>,
+    scene: SceneObjects,
+    chunks: Option<Res<SceneChunks>>,
+    timeline: Option<Res<Timeline>>,
+    objectives: Option<Res<Objectives>>,
+    ground_paint: Option<Res<GroundPaint>>,
 ) {
-    if ev.is_empty() {
+    // One save covers every request this frame; the user's own wins over an autosave
+    let Some(save) = ev.read().copied().max_by_key(SaveSceneEvent::is_user_save) else {
+        return;
+    };
+    if export.is_running() {
+        if save.is_user_save()
+            && let Some(toasts) = toasts.as_mut()
+        {
+            toasts.warn("Still saving; try again once it's done");
+        }
         return;
     }
-    for _ in ev.read() {
-        let mut objects = Vec::new();
-        // Save standard mesh objects
-        for (name, tf, _mesh, mat_h, mesh_info) in q_edit.iter() {
-            let (rx, ry, rz) = tf.rotation.to_euler(EulerRot::XYZ);
-
-            // TODO: store the emmisive (used in crystal material in main.rs)
-            let (color_rgba, metallic, roughness) = if let Some(mat) = materials.get(&mat_h.0) {
-                let s = mat.base_color.to_srgba();
-                (
-                    [s.red, s.green, s.blue, s.alpha],
-                    mat.metallic,
-                    mat.perceptual_roughness,
-                )
-            } else {
-                ([0.82, 0.82, 0.86, 1.0], 0.0, 0.6)
-            };
 
-            objects.push(SceneObject {
-                name: name.map(|n| n.as_str().to_string()),
-                kind: mesh_info.unwrap().kind,
-                position: [tf.translation.x, tf.translation.y, tf.translation.z],
-                rotation_euler_deg: [rx.to_degrees(), ry.to_degrees(), rz.to_degrees()],
-                scale: [tf.scale.x, tf.scale.y, tf.scale.z],
-                color_rgba,
-                metallic,
-                roughness,
-                collider: mesh_info.unwrap().collider,
-            });
-        }
-        // Save colliders
-        for (name, tf, _cb) in q_cb.iter() {
-            let (rx, ry, rz) = tf.rotation.to_euler(EulerRot::XYZ);
-            objects.push(SceneObject {
-                name: Some(name.to_string()),
-                kind: SpawnKind::ColliderBox,
-                position: [tf.translation.x, tf.translation.y, tf.translation.z],
-                rotation_euler_deg: [rx.to_degrees(), ry.to_degrees(), rz.to_degrees()],
-                scale: [tf.scale.x, tf.scale.y, tf.scale.z],
-                color_rgba: [0.0, 0.0, 0.0, 0.0],
-                metallic: 0.0,
-                roughness: 0.0,
-                collider: Some(true),
-            });
-        }
+    let mut objects: Vec<SceneObject> = scene
+        .collect(|_| true)
+        .into_iter()
+        .map(|(_, obj)| obj)
+        .collect();
+    let path = if io.filename.trim().is_empty() {
+        "scene.json".into()
+    } else {
+        io.filename.clone()
+    };
+    // A streamed scene keeps every object, spawned or not, in its cell's chunk
+    let (chunk_size, mut cells) = match chunks.as_deref().filter(|c| c.enabled) {
+        Some(chunks) => match chunks.saved(std::mem::take(&mut objects), &path) {
+            Ok(cells) => (Some(chunks.size), cells),
+            Err(e) => {
+                if let Some(toasts) = toasts.as_mut() {
+                    toasts.warn(format!("Couldn't save {path}: {e}"));
+                }
+                return;
+            }
+        },
+        None => (None, SavedCells::default()),
+    };
+    sort_objects(&mut objects);
+    let total = objects.len() + cells.counted;
+
+    let script = io.script.trim();
+    let doc = SceneDoc {
+        version: 1,
+        objects,
+        script: (!script.is_empty()).then(|| script.to_string()),
+        timeline: timeline.as_deref().filter(|t| !t.is_empty()).cloned(),
+        objectives: objectives.as_deref().filter(|o| !o.is_empty()).cloned(),
+        ground_paint: ground_paint.as_deref().filter(|p| !p.is_empty()).cloned(),
+        chunk_size,
+        chunks: std::mem::take(&mut cells.chunks),
+    };
+    // Edits made while it's written mark the scene dirty again; a failed save puts it back
+    if save.is_user_save() {
+        io.dirty = false;
+    }
+    export.start(
+        path,
+        doc,
+        total,
+        save.is_user_save(),
+        time.elapsed_secs(),
+        // The cells' files first, so the scene file never lists one that isn't there
+        move |path, json| {
+            cells.write()?;
+            save.write(path, json)
+        },
+    );
+}
 
-        let doc = SceneDoc {
-            version: 1,
-            objects,
-        };
-        let path = if io.filename.trim().is_empty() {
-            "scene.json".into()
-        } else {
-            io.filename.clone()
-        };
-        match serde_json::to_string_pretty(&doc) {
-            Ok(json) => {
-                if let Err(e) = write(&path, json) {
-                    eprintln!("Save error: {e}");
-                } else {
-                    eprintln!("Scene saved to {path}");
+fn finish_save(
+    mut ev: EventReader<SceneExported>,
+    mut io: ResMut<SceneIoState>,
+    time: Res<Time<Real>>,
+) {
+    for done in ev.read() {
+        match &done.outcome {
+            ExportOutcome::Written => {
+                eprintln!("Scene saved to {}", done.path);
+                if done.user_save {
+                    io.saved_at = Some(time.elapsed_secs());
+                }
+            }
+            ExportOutcome::Cancelled => {
+                if done.user_save {
+                    io.dirty = true;
+                }
+            }
+            ExportOutcome::Failed(e) => {
+                eprintln!("Save error: {e}");
+                if done.user_save {
+                    io.dirty = true;
                 }
             }
-            Err(e) => eprintln!("Serialize error: {e}"),
         }
     }
 }
@@ -1009,8 +3266,14 @@ fn load_scene_system(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
-    io: Res<SceneIoState>,
+    mut io: ResMut<SceneIoState>,
+    mut cache: ResMut<AssetCache>,
     q_existing: Query<Entity, With<Editable>>,
+    mut timeline: Option<ResMut<Timeline>>,
+    mut playback: Option<ResMut<TimelinePlayback>>,
+    mut objectives: Option<ResMut<Objectives>>,
+    mut chunks: Option<ResMut<SceneChunks>>,
+    mut ground_paint: Option<ResMut<GroundPaint>>,
 ) {
     if ev.is_empty() {
         return;
@@ -1021,7 +3284,7 @@ fn load_scene_system(
         } else {
             io.filename.clone()
         };
-        let Ok(text) = read_to_string(&path) else {
+        let Some(text) = scene_io::read_scene(&path) else {
             eprintln!("Load error: cannot read {path}");
             continue;
         };
@@ -1033,146 +3296,499 @@ fn load_scene_system(
         for e in q_existing.iter() {
             commands.entity(e).despawn();
         }
+        io.dirty = false;
+        io.settling = true;
+        io.script = doc.script.unwrap_or_default();
+        if let Some(timeline) = timeline.as_mut() {
+            **timeline = doc.timeline.unwrap_or_default();
+        }
+        if let Some(playback) = playback.as_mut() {
+            **playback = TimelinePlayback::default();
+        }
+        if let Some(objectives) = objectives.as_mut() {
+            **objectives = doc.objectives.unwrap_or_default();
+        }
+        if let Some(ground_paint) = ground_paint.as_mut() {
+            ground_paint.load(doc.ground_paint.unwrap_or_default());
+        }
 
-        for obj in doc.objects {
-            // Handle ColliderBox (author-only, no render mesh)
-            if matches!(obj.kind, SpawnKind::ColliderBox) {
-                let tf = Transform {
-                    translation: Vec3::new(obj.position[0], obj.position[1], obj.position[2]),
-                    rotation: Quat::from_euler(
-                        EulerRot::XYZ,
-                        obj.rotation_euler_deg[0].to_radians(),
-                        obj.rotation_euler_deg[1].to_radians(),
-                        obj.rotation_euler_deg[2].to_radians(),
-                    ),
-                    scale: Vec3::new(obj.scale[0], obj.scale[1], obj.scale[2]),
-                };
-                let mut ecmd = commands.spawn((
-                    tf,
-                    Editable,
-                    ColliderBox,
-                    EditableMesh {
-                        kind: SpawnKind::ColliderBox,
-                        collider: Some(true),
-                    },
-                ));
-                if let Some(name) = obj.name.clone() {
-                    ecmd.insert(Name::new(name));
+        // A streamed scene's cells are spawned around the camera target from here on (all of
+        // them at once without the streaming plugin)
+        let mut objects = doc.objects;
+        match (chunks.as_deref_mut(), doc.chunk_size) {
+            (Some(chunks), Some(size)) => chunks.stream(size, doc.chunks, &path),
+            (chunks, _) => {
+                if let Some(chunks) = chunks {
+                    chunks.reset();
+                }
+                for chunk in doc.chunks {
+                    match chunk.into_objects(&path) {
+                        Ok(chunk_objects) => objects.extend(chunk_objects),
+                        Err(e) => eprintln!("Load error: {e}"),
+                    }
                 }
-                continue;
             }
-            // Mesh: support Cube, Cuboid, Plane, Sphere
-            let (mesh_h, mesh_info) = match obj.kind {
-                SpawnKind::Cuboid => (
-                    meshes.add(Mesh::from(Cuboid::new(1.0, 1.0, 1.0))),
-                    EditableMesh {
-                        kind: SpawnKind::Cuboid,
-                        collider: obj.collider,
-                    },
-                ),
-                SpawnKind::Plane => (
-                    meshes.add(Mesh::from(Plane3d::default())),
-                    EditableMesh {
-                        kind: SpawnKind::Plane,
-                        collider: obj.collider,
-                    },
-                ),
-                SpawnKind::Sphere => (
-                    meshes.add(Mesh::from(Sphere::new(0.5))),
-                    EditableMesh {
-                        kind: SpawnKind::Sphere,
-                        collider: obj.collider,
-                    },
-                ),
-                SpawnKind::Prism => (
-                    meshes.add(Extrusion::new(
-                        Triangle2d::new(
-                            Vec2::new(0.0, 1.0),
-                            Vec2::new(0.0, 0.0),
-                            Vec2::new(1.0, 0.0),
-                        ),
-                        1.0,
-                    )),
-                    EditableMesh {
-                        kind: SpawnKind::Prism,
-                        collider: obj.collider,
-                    },
-                ),
-                // Making the compiler happy
-                SpawnKind::ColliderBox => todo!(),
-            };
+        }
+        for obj in objects {
+            spawn_scene_object(&mut commands, &mut meshes, &mut materials, &mut cache, obj);
+        }
+    }
+}
 
-            // Material: color + PBR params; enable blending if alpha < 1
-            let c = obj.color_rgba;
-            let mut mat = StandardMaterial {
-                base_color: Color::srgba(c[0], c[1], c[2], c[3]),
-                perceptual_roughness: obj.roughness.clamp(0.0, 1.0),
-                metallic: obj.metallic.clamp(0.0, 1.0),
-                ..Default::default()
-            };
-            if c[3] < 0.999 {
-                mat.alpha_mode = AlphaMode::Blend;
-                // Optional: tweak depth bias/ordering for semi-transparent if needed
-            }
-            let mat_h = materials.add(mat);
+/// Spawn a saved object as it was saved, returning its entity.
+pub(crate) fn spawn_scene_object(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    cache: &mut AssetCache,
+    obj: SceneObject,
+) -> Entity {
+    let (rotation, rot_edit) = loaded_rotation(obj.rotation_euler_deg, obj.rotation_edit);
+    // Handle ColliderBox (author-only, no render mesh)
+    if matches!(obj.kind, SpawnKind::ColliderBox) {
+        let tf = Transform {
+            translation: Vec3::new(obj.position[0], obj.position[1], obj.position[2]),
+            rotation,
+            scale: Vec3::new(obj.scale[0], obj.scale[1], obj.scale[2]),
+        };
+        let mut ecmd = commands.spawn((
+            tf,
+            Editable,
+            ColliderBox,
+            EditableMesh {
+                kind: SpawnKind::ColliderBox,
+                collider: Some(true),
+            },
+        ));
+        if let Some(name) = obj.name.clone() {
+            ecmd.insert(Name::new(name));
+        }
+        if let Some(order) = obj.order {
+            ecmd.insert(HierarchyOrder(order));
+        }
+        if let Some(props) = obj.properties {
+            ecmd.insert(props);
+        }
+        ecmd.insert(rot_edit);
+        return ecmd.id();
+    }
+    // Author-placed lights (no render mesh)
+    if matches!(obj.kind, SpawnKind::PointLight | SpawnKind::SpotLight) {
+        let tf = Transform {
+            translation: Vec3::from_array(obj.position),
+            rotation,
+            scale: Vec3::from_array(obj.scale),
+        };
+        let c = obj.color_rgba;
+        let mut ecmd = commands.spawn((
+            tf,
+            Editable,
+            EditableMesh {
+                kind: obj.kind,
+                collider: None,
+            },
+        ));
+        insert_light(
+            &mut ecmd,
+            obj.kind,
+            Color::srgb(c[0], c[1], c[2]),
+            obj.light.unwrap_or_default(),
+        );
+        if let Some(anim) = obj.light_animation {
+            ecmd.insert(anim);
+        }
+        if let Some(name) = obj.name.clone() {
+            ecmd.insert(Name::new(name));
+        }
+        if let Some(order) = obj.order {
+            ecmd.insert(HierarchyOrder(order));
+        }
+        if let Some(props) = obj.properties {
+            ecmd.insert(props);
+        }
+        ecmd.insert(rot_edit);
+        return ecmd.id();
+    }
+    // Fog volumes (no render mesh)
+    if matches!(obj.kind, SpawnKind::FogVolume) {
+        let tf = Transform {
+            translation: Vec3::from_array(obj.position),
+            rotation,
+            scale: Vec3::from_array(obj.scale),
+        };
+        let e = spawn_fog_volume_object(commands, tf, obj.fog_volume.unwrap_or_default());
+        if let Some(name) = obj.name.clone() {
+            commands.entity(e).insert(Name::new(name));
+        }
+        if let Some(order) = obj.order {
+            commands.entity(e).insert(HierarchyOrder(order));
+        }
+        if let Some(props) = obj.properties {
+            commands.entity(e).insert(props);
+        }
+        commands.entity(e).insert(rot_edit);
+        return e;
+    }
+    // Creature spawners (the flock is rebuilt from the settings)
+    if matches!(obj.kind, SpawnKind::Creatures) {
+        let tf = Transform {
+            translation: Vec3::from_array(obj.position),
+            rotation,
+            scale: Vec3::from_array(obj.scale),
+        };
+        let e = spawn_creature_spawner(commands, tf, obj.creatures.unwrap_or_default());
+        if let Some(name) = obj.name.clone() {
+            commands.entity(e).insert(Name::new(name));
+        }
+        if let Some(order) = obj.order {
+            commands.entity(e).insert(HierarchyOrder(order));
+        }
+        if let Some(props) = obj.properties {
+            commands.entity(e).insert(props);
+        }
+        commands.entity(e).insert(rot_edit);
+        return e;
+    }
+    // Decals (forward-decal quad, own material)
+    if matches!(obj.kind, SpawnKind::Decal) {
+        let tf = Transform {
+            translation: Vec3::from_array(obj.position),
+            rotation,
+            scale: Vec3::from_array(obj.scale),
+        };
+        let e = spawn_decal_object(commands, tf, obj.decal.unwrap_or_default());
+        if let Some(name) = obj.name.clone() {
+            commands.entity(e).insert(Name::new(name));
+        }
+        if let Some(order) = obj.order {
+            commands.entity(e).insert(HierarchyOrder(order));
+        }
+        if let Some(props) = obj.properties {
+            commands.entity(e).insert(props);
+        }
+        commands.entity(e).insert(rot_edit);
+        return e;
+    }
+    // Splines (content generated from the control points)
+    if matches!(obj.kind, SpawnKind::Spline) {
+        let tf = Transform {
+            translation: Vec3::from_array(obj.position),
+            rotation,
+            scale: Vec3::from_array(obj.scale),
+        };
+        let e = spawn_spline_object(commands, tf, obj.spline.unwrap_or_default());
+        if let Some(rail) = obj.camera_rail {
+            commands.entity(e).insert(rail);
+        }
+        if let Some(name) = obj.name.clone() {
+            commands.entity(e).insert(Name::new(name));
+        }
+        if let Some(order) = obj.order {
+            commands.entity(e).insert(HierarchyOrder(order));
+        }
+        if let Some(props) = obj.properties {
+            commands.entity(e).insert(props);
+        }
+        commands.entity(e).insert(rot_edit);
+        return e;
+    }
+    // Mesh and material are shared between identical objects
+    let mesh_h = match (obj.kind, obj.stairs.as_ref()) {
+        (SpawnKind::Stairs, Some(params)) => cache.stairs_mesh(meshes, params),
+        (kind, _) => cache.mesh(meshes, kind),
+    };
+    let mesh_info = EditableMesh {
+        kind: obj.kind,
+        collider: obj.collider,
+    };
+    let c = obj.color_rgba;
+    let mat_h = cache.material(
+        materials,
+        PlainMaterial {
+            base_color: Color::srgba(c[0], c[1], c[2], c[3]),
+            metallic: obj.metallic,
+            roughness: obj.roughness,
+            emissive: obj
+                .emissive
+                .map(|[r, g, b]| LinearRgba::rgb(r, g, b))
+                .unwrap_or(LinearRgba::BLACK),
+        },
+    );
 
-            // Transform: translation, rotation (deg->rad), **scale** (restores X/Y/Z sizes)
-            let (rx, ry, rz) = (
-                obj.rotation_euler_deg[0].to_radians(),
-                obj.rotation_euler_deg[1].to_radians(),
-                obj.rotation_euler_deg[2].to_radians(),
-            );
-            let tf = Transform {
-                translation: Vec3::from_array(obj.position),
-                rotation: Quat::from_euler(EulerRot::XYZ, rx, ry, rz),
-                scale: Vec3::from_array(obj.scale),
-            };
+    // Transform: translation, rotation (in its editing mode), **scale** (restores X/Y/Z sizes)
+    let tf = Transform {
+        translation: Vec3::from_array(obj.position),
+        rotation,
+        scale: Vec3::from_array(obj.scale),
+    };
 
-            let mut ecmd = commands.spawn((
-                Mesh3d(mesh_h),
-                MeshMaterial3d(mat_h),
-                tf,
+    let mut ecmd = commands.spawn((
+        Mesh3d(mesh_h),
+        MeshMaterial3d(mat_h),
+        tf,
+        Editable,
+        mesh_info,
+    ));
+    if let Some(name) = obj.name {
+        ecmd.insert(Name::new(name));
+    }
+    if let Some(order) = obj.order {
+        ecmd.insert(HierarchyOrder(order));
+    }
+    if let Some(props) = obj.properties {
+        ecmd.insert(props);
+    }
+    ecmd.insert(rot_edit);
+    if let Some(sway) = obj.wind_sway {
+        ecmd.insert(sway);
+    }
+    if let Some(anim) = obj.light_animation {
+        ecmd.insert(anim);
+    }
+    if let Some(refl) = obj.planar_reflection {
+        ecmd.insert(refl);
+    }
+    if obj.kind == SpawnKind::Stairs {
+        ecmd.insert(obj.stairs.unwrap_or_default());
+    }
+    if let Some(group) = obj.bake_group {
+        ecmd.insert(BakedMember { group });
+    }
+    if obj.instanced_tile {
+        ecmd.insert(InstancedTile);
+    }
+    if let Some(views) = obj.views {
+        ecmd.insert(views);
+    }
+    if let Some(joint) = obj.joint {
+        ecmd.insert(joint);
+    }
+    if let Some(elevator) = obj.elevator {
+        ecmd.insert(elevator);
+    }
+    if let Some(surface) = obj.surface {
+        ecmd.insert(surface);
+    }
+    if let Some(sign) = obj.sign {
+        ecmd.insert(sign);
+    }
+    if let Some(triplanar) = obj.triplanar {
+        ecmd.insert(triplanar);
+    }
+    if let Some(toon) = obj.toon {
+        ecmd.insert(toon);
+    }
+    if let Some(glow) = obj.glow {
+        ecmd.insert(glow);
+    }
+    if let Some(paint) = obj.vertex_paint {
+        ecmd.insert(paint);
+    }
+    if let Some(custom) = obj.custom {
+        ecmd.insert(custom);
+    }
+    ecmd.id()
+}
+
+/// Spawn a new object of `kind` (as picked under *Create New*) with its origin at `at`, turned
+/// by `yaw`. Mesh objects share the default mesh and material until their first edit.
+pub(crate) fn spawn_new_object(
+    commands: &mut Commands,
+    cache: &mut AssetCache,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    kind: SpawnKind,
+    at: Vec3,
+    yaw: Quat,
+) -> Entity {
+    let tf = Transform::from_translation(at).with_rotation(yaw);
+    match kind {
+        SpawnKind::ColliderBox => commands
+            .spawn((
                 Editable,
-                mesh_info,
-            ));
-            if let Some(name) = obj.name {
-                ecmd.insert(Name::new(name));
+                Name::new("Bounds Collider"),
+                tf,
+                EditableMesh {
+                    kind: SpawnKind::ColliderBox,
+                    collider: Some(true),
+                },
+                ColliderBox,
+            ))
+            .id(),
+        SpawnKind::PointLight | SpawnKind::SpotLight => {
+            let e = spawn_light_object(commands, kind);
+            // Spot lights keep pointing down
+            let down = match kind {
+                SpawnKind::SpotLight => Quat::from_rotation_x(-std::f32::consts::FRAC_PI_2),
+                _ => Quat::IDENTITY,
+            };
+            commands.entity(e).insert(tf.with_rotation(yaw * down));
+            e
+        }
+        SpawnKind::FogVolume => spawn_fog_volume_object(
+            commands,
+            tf.with_scale(Vec3::new(4.0, 1.0, 4.0)),
+            SceneFogVolume::default(),
+        ),
+        SpawnKind::Decal => spawn_decal_object(commands, tf, SceneDecal::default()),
+        SpawnKind::Spline => spawn_spline_object(commands, tf, SplinePath::default()),
+        SpawnKind::Creatures => spawn_creature_spawner(
+            commands,
+            tf.with_scale(Vec3::new(6.0, 3.0, 6.0)),
+            CreatureSpawner::default(),
+        ),
+        _ => {
+            let mesh_handle = cache.mesh(meshes, kind);
+            let mat = cache.material(
+                materials,
+                PlainMaterial {
+                    base_color: Color::srgb(0.82, 0.82, 0.86),
+                    metallic: 0.0,
+                    roughness: 0.6,
+                    emissive: LinearRgba::BLACK,
+                },
+            );
+            let e = commands
+                .spawn((
+                    Mesh3d(mesh_handle),
+                    MeshMaterial3d(mat),
+                    tf,
+                    Editable,
+                    EditableMesh {
+                        kind,
+                        collider: None,
+                    },
+                    Name::new(match kind {
+                        SpawnKind::Sphere => "Sphere",
+                        SpawnKind::Plane => "Plane",
+                        SpawnKind::Prism => "Prism",
+                        SpawnKind::Stairs => "Stairs",
+                        SpawnKind::Custom(id) => id.name(),
+                        _ => "Cuboid",
+                    }),
+                ))
+                .id();
+            if kind == SpawnKind::Stairs {
+                commands.entity(e).insert(StairsParams::default());
             }
+            e
         }
     }
 }
 
-/// Draw a pulsing wireframe AABB + tiny axes for the currently selected object.
+/// A DragValue whose edits land on multiples of `step` when snapping is on, and whose arrow keys
+/// then move by one step. Typed text can be arithmetic, with `u` for one step (see `num_expr`).
+/// True if edited.
+fn snapped_drag(
+    ui: &mut egui::Ui,
+    value: &mut f32,
+    speed: f32,
+    range: std::ops::RangeInclusive<f32>,
+    step: f32,
+    snap: bool,
+) -> bool {
+    let step = (step > 0.0).then_some(step);
+    let snap = step.filter(|_| snap);
+    // The field's arrow keys change it by its speed while it's being typed in; anything smaller
+    // than a step would just snap back
+    let typing = ui.memory(|m| m.has_focus(ui.next_auto_id()));
+    let speed = match snap {
+        Some(step) if typing => step,
+        _ => speed,
+    };
+    let unit = f64::from(step.unwrap_or(1.0));
+    let response = ui.add(
+        egui::DragValue::new(value)
+            .speed(speed)
+            .range(range.clone())
+            .custom_parser(move |text| num_expr::eval(text, unit)),
+    );
+    if response.changed()
+        && let Some(step) = snap
+    {
+        *value = ((*value / step).round() * step).clamp(*range.start(), *range.end());
+    }
+    response.changed()
+}
+
+/// Color picked in the inspector, as a material color.
+fn color_of(c: egui::Color32) -> Color {
+    Color::srgba_u8(c.r(), c.g(), c.b(), c.a())
+}
+
+/// Material color as shown in the inspector's color picker.
+fn color32_of(color: Color) -> egui::Color32 {
+    let s = color.to_srgba();
+    egui::Color32::from_rgba_premultiplied(
+        (s.red * 255.0).clamp(0.0, 255.0) as u8,
+        (s.green * 255.0).clamp(0.0, 255.0) as u8,
+        (s.blue * 255.0).clamp(0.0, 255.0) as u8,
+        (s.alpha * 255.0).clamp(0.0, 255.0) as u8,
+    )
+}
+
+/// Draw a pulsing wireframe box + tiny axes for the currently selected object: its world AABB,
+/// or its own bounds turned with it when the oriented box is on in the preferences.
 fn highlight_selected_gizmos(
-    mut gizmos: Gizmos,
-    time: Res<Time>,
-    q_sel: Query<(&GlobalTransform, &Aabb), With<Selected>>,
+    mut gizmos: Gizmos<EditorGizmos>,
+    time: Res<Time<Real>>,
+    prefs: Res<EditorPrefs>,
+    access: Res<AccessibilityPrefs>,
+    q_sel: Query<(&GlobalTransform, &Aabb, Option<&ChildOf>), With<Selected>>,
+    q_parents: Query<&GlobalTransform>,
 ) {
-    // Pulse between 70% and 100% intensity (~0.5Hz)
+    // Pulse between 70% and 100% intensity (~0.5Hz), unless effects are calmed
     let t = time.elapsed_secs_wrapped();
-    let pulse = 0.7 + 0.3 * (t * std::f32::consts::TAU * 0.5).sin().abs();
-    let box_color = Color::srgb(1.0 * pulse, 0.85 * pulse, 0.2 * pulse);
+    let pulse = if access.calm_effects {
+        1.0
+    } else {
+        0.7 + 0.3 * (t * std::f32::consts::TAU * 0.5).sin().abs()
+    };
+    let base = access.palette.selection().to_srgba();
+    let box_color = Color::srgb(base.red * pulse, base.green * pulse, base.blue * pulse);
+    let axis_colors = access.palette.axes();
 
-    for (global, aabb) in &q_sel {
+    for (global, aabb, child_of) in &q_sel {
         // World-space AABB using your helper
         let world = aabb_world(*aabb, global);
         let center: Vec3 = world.center.into();
         let extents: Vec3 = (world.half_extents * 2.0).into();
+        let axis_len = extents.length().max(0.0001) * 0.1; // 10% of overall size
 
-        // Wireframe cuboid gizmo around the object
-        let tf = Transform {
-            translation: center,
-            rotation: Quat::IDENTITY,
-            scale: extents.max(Vec3::splat(0.0001)), // guard against zero
+        let p = if prefs.oriented_selection_box {
+            // The local box carried through the whole transform, so it turns (and skews) with
+            // the object and hugs rotated slabs
+            let local_extents = Vec3::from(aabb.half_extents * 2.0).max(Vec3::splat(0.0001));
+            let local = Affine3A::from_scale_rotation_translation(
+                local_extents,
+                Quat::IDENTITY,
+                aabb.center.into(),
+            );
+            gizmos.cuboid(global.affine() * local, box_color);
+            global.transform_point(aabb.center.into())
+        } else {
+            // Wireframe cuboid gizmo around the object
+            let tf = Transform {
+                translation: center,
+                rotation: Quat::IDENTITY,
+                scale: extents.max(Vec3::splat(0.0001)), // guard against zero
+            };
+            gizmos.cuboid(tf, box_color);
+            center
         };
-        gizmos.cuboid(tf, box_color);
 
-        // Tiny XYZ axes at the center for orientation
-        let axis_len = extents.length().max(0.0001) * 0.1; // 10% of overall size
-        let p = center;
-        gizmos.ray(p, Vec3::X * axis_len, Color::srgb(1.0, 0.0, 0.0));
-        gizmos.ray(p, Vec3::Y * axis_len, Color::srgb(0.0, 1.0, 0.0));
-        gizmos.ray(p, Vec3::Z * axis_len, Color::srgb(0.0, 0.0, 1.0));
+        // Tiny axes at the center, along the parent's or the world's (whichever space the
+        // transform fields are in; a root object's local space is the world's)
+        let parent = child_of
+            .filter(|_| prefs.transform_space == TransformSpace::Local)
+            .and_then(|c| q_parents.get(c.parent()).ok());
+        let axes = match parent {
+            Some(parent) => [*parent.right(), *parent.up(), *parent.back()],
+            None => [Vec3::X, Vec3::Y, Vec3::Z],
+        };
+        for (axis, color) in axes.into_iter().zip(axis_colors) {
+            gizmos.ray(p, axis * axis_len, color);
+        }
     }
 }
 
@@ -1193,3 +3809,225 @@ fn draw_collider_gizmos(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_4;
+
+    use super::*;
+
+    const EPS: f32 = 1e-4;
+
+    fn unit_box() -> Aabb {
+        Aabb::from_min_max(Vec3::splat(-0.5), Vec3::splat(0.5))
+    }
+
+    #[test]
+    fn ray_hits_box_in_front() {
+        let t = ray_aabb_intersection(
+            Vec3::new(0.0, 0.0, 10.0),
+            -Vec3::Z,
+            Vec3::splat(-1.0),
+            Vec3::ONE,
+        );
+        assert!((t.unwrap() - 9.0).abs() < EPS);
+    }
+
+    #[test]
+    fn ray_misses_box_beside_or_behind() {
+        let (min, max) = (Vec3::splat(-1.0), Vec3::ONE);
+        assert!(ray_aabb_intersection(Vec3::new(2.0, 0.0, 10.0), -Vec3::Z, min, max).is_none());
+        assert!(ray_aabb_intersection(Vec3::new(0.0, 0.0, 10.0), Vec3::Z, min, max).is_none());
+    }
+
+    #[test]
+    fn ray_from_inside_box_hits_at_zero() {
+        let t = ray_aabb_intersection(Vec3::ZERO, Vec3::X, Vec3::splat(-1.0), Vec3::ONE);
+        assert_eq!(t, Some(0.0));
+    }
+
+    #[test]
+    fn axis_parallel_ray_respects_other_slabs() {
+        // Direction has exact zeros on two axes: only the origin's y/z decide
+        let (min, max) = (Vec3::splat(-1.0), Vec3::ONE);
+        assert!(ray_aabb_intersection(Vec3::new(-5.0, 0.99, 0.0), Vec3::X, min, max).is_some());
+        assert!(ray_aabb_intersection(Vec3::new(-5.0, 1.01, 0.0), Vec3::X, min, max).is_none());
+    }
+
+    #[test]
+    fn oblique_ray_grazing_a_corner() {
+        let dir = Vec3::new(1.0, 1.0, 0.0).normalize();
+        let (min, max) = (Vec3::splat(-1.0), Vec3::ONE);
+        assert!(ray_aabb_intersection(Vec3::new(-2.9, -1.0, 0.0), dir, min, max).is_some());
+        assert!(ray_aabb_intersection(Vec3::new(-3.1, -1.0, 0.0), dir, min, max).is_none());
+    }
+
+    #[test]
+    fn tiny_box_far_away_is_still_hit() {
+        // An orthographic ray zoomed far out: a long way to travel for a small target
+        let origin = Vec3::new(0.01, 0.0, 5000.0);
+        let t = ray_aabb_intersection(origin, -Vec3::Z, Vec3::splat(-0.05), Vec3::splat(0.05));
+        assert!((t.unwrap() - 4999.95).abs() < 1e-2);
+    }
+
+    #[test]
+    fn aabb_world_translation_and_uniform_scale() {
+        let gt =
+            GlobalTransform::from(Transform::from_xyz(1.0, 2.0, 3.0).with_scale(Vec3::splat(2.0)));
+        let world = aabb_world(unit_box(), &gt);
+        assert!(Vec3::from(world.center).abs_diff_eq(Vec3::new(1.0, 2.0, 3.0), EPS));
+        assert!(Vec3::from(world.half_extents).abs_diff_eq(Vec3::ONE, EPS));
+    }
+
+    #[test]
+    fn aabb_world_non_uniform_scale() {
+        let gt = GlobalTransform::from(Transform::from_scale(Vec3::new(4.0, 1.0, 0.5)));
+        let world = aabb_world(unit_box(), &gt);
+        assert!(Vec3::from(world.half_extents).abs_diff_eq(Vec3::new(2.0, 0.5, 0.25), EPS));
+    }
+
+    #[test]
+    fn aabb_world_rotated_encloses_corners() {
+        let gt = GlobalTransform::from(Transform::from_rotation(Quat::from_rotation_y(FRAC_PI_4)));
+        let world = aabb_world(unit_box(), &gt);
+        let r = 0.5 * std::f32::consts::SQRT_2;
+        assert!(Vec3::from(world.half_extents).abs_diff_eq(Vec3::new(r, 0.5, r), EPS));
+    }
+
+    #[test]
+    fn aabb_world_rotated_non_uniform_off_center() {
+        // Scale is applied before rotation: the long x side ends up along z
+        let local = Aabb::from_min_max(Vec3::new(0.0, 0.0, 0.0), Vec3::new(2.0, 1.0, 1.0));
+        let gt = GlobalTransform::from(
+            Transform::from_xyz(0.0, 5.0, 0.0)
+                .with_rotation(Quat::from_rotation_y(-std::f32::consts::FRAC_PI_2))
+                .with_scale(Vec3::new(3.0, 1.0, 1.0)),
+        );
+        let world = aabb_world(local, &gt);
+        assert!(Vec3::from(world.center).abs_diff_eq(Vec3::new(-0.5, 5.5, 3.0), EPS));
+        assert!(Vec3::from(world.half_extents).abs_diff_eq(Vec3::new(0.5, 0.5, 3.0), EPS));
+
+        // Same box as obb_to_world_aabb computes for a centred local box
+        let centred = aabb_world(unit_box(), &gt);
+        let obb = obb_to_world_aabb(gt.compute_transform(), Vec3::splat(0.5));
+        assert!(Vec3::from(centred.half_extents).abs_diff_eq(obb.half_extents.into(), EPS));
+    }
+
+    #[test]
+    fn copies_are_numbered_on_from_the_original() {
+        let taken = HashSet::from(["Block", "Block 2", "Terrace 3"]);
+        assert_eq!(copy_name("Block", &taken), "Block 3");
+        assert_eq!(copy_name("Terrace 3", &taken), "Terrace 4");
+        assert_eq!(copy_name("Big Tree", &taken), "Big Tree 2");
+    }
+
+    #[test]
+    fn copy_keeps_properties_and_parent() {
+        use bevy::ecs::system::RunSystemOnce;
+
+        let mut world = World::new();
+        world.init_resource::<Assets<Mesh>>();
+        world.init_resource::<Assets<StandardMaterial>>();
+        world.init_resource::<AssetCache>();
+        world.init_resource::<EditorPrefs>();
+        world.insert_resource(DissolveSettings {
+            on_edit: false,
+            ..default()
+        });
+        let parent = world.spawn(Transform::from_xyz(10.0, 0.0, 0.0)).id();
+        let props = CustomProperties([("door".to_string(), "east".to_string())].into());
+        let src = world
+            .spawn((
+                Mesh3d::default(),
+                MeshMaterial3d::<StandardMaterial>::default(),
+                Transform::from_xyz(1.0, 2.0, 0.0),
+                Editable,
+                EditableMesh {
+                    kind: SpawnKind::Cuboid,
+                    collider: None,
+                },
+                Name::new("Crate"),
+                props.clone(),
+                ChildOf(parent),
+                Selected,
+            ))
+            .id();
+        world.insert_resource(InspectorState {
+            selected: Some(src),
+            duplicate: true,
+            ..default()
+        });
+        world.run_system_once(duplicate_selected).unwrap();
+
+        let copy = world.resource::<InspectorState>().selected.unwrap();
+        assert_ne!(copy, src);
+        let copy = world.entity(copy);
+        assert_eq!(copy.get::<Name>().unwrap().as_str(), "Crate 2");
+        assert_eq!(copy.get::<CustomProperties>(), Some(&props));
+        assert_eq!(copy.get::<ChildOf>().map(ChildOf::parent), Some(parent));
+        // Beside the original, in the parent's space
+        let tf = copy.get::<Transform>().unwrap();
+        assert!(
+            tf.translation
+                .abs_diff_eq(Vec3::new(1.0 + DUPLICATE_OFFSET, 2.0, 0.0), EPS)
+        );
+        assert!(copy.contains::<Selected>());
+        assert!(!world.entity(src).contains::<Selected>());
+    }
+
+    #[test]
+    fn group_follows_a_move_rigidly() {
+        let before = Transform::from_xyz(1.0, 0.0, 0.0);
+        let after = Transform::from_xyz(3.0, 1.0, 0.0);
+        let other = Transform::from_xyz(1.0, 0.0, 5.0);
+        let next = follow_group_edit(&other, &before, &after, before.translation);
+        assert!(next.translation.abs_diff_eq(Vec3::new(3.0, 1.0, 5.0), EPS));
+        assert_eq!(next.rotation, Quat::IDENTITY);
+    }
+
+    #[test]
+    fn group_turns_around_the_pivot() {
+        let before = Transform::IDENTITY;
+        let after = Transform::from_rotation(Quat::from_rotation_y(std::f32::consts::FRAC_PI_2));
+        let other = Transform::from_xyz(2.0, 0.0, 0.0);
+        let next = follow_group_edit(&other, &before, &after, Vec3::ZERO);
+        assert!(next.translation.abs_diff_eq(Vec3::new(0.0, 0.0, -2.0), EPS));
+        assert!(next.rotation.abs_diff_eq(after.rotation, EPS));
+
+        // Around a center between them, the active object swings too
+        let active = follow_group_edit(&before, &before, &after, Vec3::X);
+        assert!(
+            active
+                .translation
+                .abs_diff_eq(Vec3::new(1.0, 0.0, 1.0), EPS)
+        );
+    }
+
+    #[test]
+    fn group_scales_along_the_active_objects_axes() {
+        // Turned a quarter around Y, the object's own x axis is the world's -z
+        let turned = Quat::from_rotation_y(std::f32::consts::FRAC_PI_2);
+        let before = Transform::from_rotation(turned);
+        let after = before.with_scale(Vec3::new(2.0, 1.0, 1.0));
+        let other = Transform::from_xyz(0.0, 1.0, -3.0);
+        let next = follow_group_edit(&other, &before, &after, Vec3::ZERO);
+        assert!(next.translation.abs_diff_eq(Vec3::new(0.0, 1.0, -6.0), EPS));
+        // Unturned, the stretch is along its own z
+        assert!(next.scale.abs_diff_eq(Vec3::new(1.0, 1.0, 2.0), EPS));
+        // Turned like the active object, along its own x
+        let next = follow_group_edit(&other.with_rotation(turned), &before, &after, Vec3::ZERO);
+        assert!(next.scale.abs_diff_eq(Vec3::new(2.0, 1.0, 1.0), EPS));
+
+        // A flattened axis can't be scaled back up by a ratio; it's left as is
+        let flat = Transform::from_scale(Vec3::new(0.0, 1.0, 1.0));
+        let next = follow_group_edit(&other, &flat, &Transform::IDENTITY, Vec3::ZERO);
+        assert!(next.translation.is_finite());
+    }
+
+    #[test]
+    fn aabb_world_mirrored_scale_stays_positive() {
+        let gt = GlobalTransform::from(Transform::from_scale(Vec3::new(-2.0, 1.0, 1.0)));
+        let world = aabb_world(unit_box(), &gt);
+        assert!(Vec3::from(world.half_extents).abs_diff_eq(Vec3::new(1.0, 0.5, 0.5), EPS));
+    }
+}
@@ -0,0 +1,519 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    sync::Arc,
+};
+
+use bevy::{
+    core_pipeline::{
+        core_3d::Transparent3d,
+        prepass::{DeferredPrepass, DepthPrepass, MotionVectorPrepass, NormalPrepass},
+        tonemapping::Tonemapping,
+    },
+    ecs::{
+        query::QueryItem,
+        system::{SystemParamItem, lifetimeless::*},
+    },
+    pbr::{
+        MeshPipeline, MeshPipelineKey, RenderMeshInstances, SetMeshViewBindGroup,
+        tonemapping_pipeline_key,
+    },
+    prelude::*,
+    render::{
+        Render, RenderApp, RenderSet,
+        extract_component::{ExtractComponent, ExtractComponentPlugin},
+        mesh::{
+            MeshVertexBufferLayoutRef, RenderMesh, RenderMeshBufferInfo, allocator::MeshAllocator,
+        },
+        render_asset::RenderAssets,
+        render_phase::{
+            AddRenderCommand, DrawFunctions, PhaseItem, PhaseItemExtraIndex, RenderCommand,
+            RenderCommandResult, SetItemPipeline, TrackedRenderPass, ViewSortedRenderPhases,
+        },
+        render_resource::{binding_types::storage_buffer_read_only, *},
+        renderer::{RenderDevice, RenderQueue},
+        sync_world::MainEntity,
+        view::{ExtractedView, NoFrustumCulling, RenderLayers},
+    },
+};
+
+const SHADER_ASSET_PATH: &str = "shaders/tile_instancing.wgsl";
+
+/// Render layer that tiles live on: no camera renders it (the instanced pass draws them on the
+/// tile's own layers instead), but the sun does, so tiles still cast shadows.
+const TILE_SOURCE_LAYER: usize = 31;
+
+/// Flags an editable as a "tile": drawn through the instanced path in one draw call per mesh,
+/// with its transform and base color in a storage buffer. Only the material's base color is
+/// used, and tiles don't receive shadows or SSAO.
+#[derive(Component, Copy, Clone, Default)]
+pub struct InstancedTile;
+
+/// The tile's own render layers (a reflective surface's, say), kept while it sits on
+/// `TILE_SOURCE_LAYER` and given back when it stops being a tile.
+#[derive(Component)]
+struct TileLayers(Option<RenderLayers>);
+
+/// Per-instance data, mirrored by `TileInstance` in the shader.
+#[derive(Clone, Copy, PartialEq, ShaderType)]
+struct TileInstance {
+    world_from_local: Mat4,
+    color: Vec4, // linear RGBA
+}
+
+/// One draw call's worth of tiles sharing a mesh and render layers. The instance list is behind
+/// an `Arc` so extracting it every frame is cheap and the render side can tell when it was
+/// rebuilt.
+#[derive(Component, Clone)]
+struct TileBatch {
+    mesh: AssetId<Mesh>,
+    layers: RenderLayers,
+    instances: Arc<Vec<TileInstance>>,
+}
+
+impl ExtractComponent for TileBatch {
+    type QueryData = &'static TileBatch;
+    type QueryFilter = ();
+    type Out = Self;
+
+    fn extract_component(item: QueryItem<'_, Self::QueryData>) -> Option<Self> {
+        Some(item.clone())
+    }
+}
+
+pub struct InstancingPlugin;
+impl Plugin for InstancingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(ExtractComponentPlugin::<TileBatch>::default())
+            .add_systems(Update, (route_tiles_to_instancing, gather_tile_batches));
+
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app
+            .add_render_command::<Transparent3d, DrawTiles>()
+            .init_resource::<SpecializedMeshPipelines<TilePipeline>>()
+            .add_systems(
+                Render,
+                (
+                    queue_tile_batches.in_set(RenderSet::QueueMeshes),
+                    prepare_tile_buffers.in_set(RenderSet::PrepareBindGroups),
+                ),
+            );
+    }
+
+    fn finish(&self, app: &mut App) {
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app.init_resource::<TilePipeline>();
+    }
+}
+
+/// Move new tiles off the camera layers, keeping their own for the batch (and give them back
+/// when the flag is removed); make sure the sun still sees them for shadows.
+fn route_tiles_to_instancing(
+    mut commands: Commands,
+    q_tiles: Query<
+        (Entity, Option<&RenderLayers>),
+        (
+            With<InstancedTile>,
+            Or<(Added<InstancedTile>, Changed<RenderLayers>)>,
+        ),
+    >,
+    q_kept: Query<&TileLayers, Without<InstancedTile>>,
+    mut removed: RemovedComponents<InstancedTile>,
+    q_sun: Query<(Entity, Option<&RenderLayers>), With<DirectionalLight>>,
+) {
+    let source = RenderLayers::layer(TILE_SOURCE_LAYER);
+    for (e, layers) in &q_tiles {
+        // Layers set on the tile since (it became a reflective surface, say) are the batch's
+        if layers == Some(&source) {
+            continue;
+        }
+        commands
+            .entity(e)
+            .insert((TileLayers(layers.cloned()), source.clone()));
+    }
+    for e in removed.read() {
+        let Ok(TileLayers(layers)) = q_kept.get(e) else {
+            continue;
+        };
+        let mut entity = commands.entity(e);
+        entity.remove::<TileLayers>();
+        match layers {
+            Some(layers) => entity.insert(layers.clone()),
+            None => entity.remove::<RenderLayers>(),
+        };
+    }
+    for (e, layers) in &q_sun {
+        let layers = layers.cloned().unwrap_or_default();
+        if !layers.intersects(&source) {
+            commands.entity(e).insert(layers.with(TILE_SOURCE_LAYER));
+        }
+    }
+}
+
+/// Rebuild the per-mesh instance lists whenever a tile moves, appears or goes, or the color of
+/// a tile's material is edited. Lists that come out the same are left alone, so a material that
+/// is animated every frame doesn't re-upload the tiles sharing it.
+fn gather_tile_batches(
+    mut commands: Commands,
+    materials: Res<Assets<StandardMaterial>>,
+    mut material_events: EventReader<AssetEvent<StandardMaterial>>,
+    mut removed: RemovedComponents<InstancedTile>,
+    q_tiles: Query<
+        (
+            &GlobalTransform,
+            &Mesh3d,
+            &MeshMaterial3d<StandardMaterial>,
+            &InheritedVisibility,
+            Option<&TileLayers>,
+        ),
+        With<InstancedTile>,
+    >,
+    q_changed: Query<
+        (),
+        (
+            With<InstancedTile>,
+            Or<(
+                Added<InstancedTile>,
+                Changed<GlobalTransform>,
+                Changed<Mesh3d>,
+                Changed<MeshMaterial3d<StandardMaterial>>,
+                Changed<InheritedVisibility>,
+                Changed<TileLayers>,
+            )>,
+        ),
+    >,
+    q_batches: Query<(Entity, &TileBatch)>,
+) {
+    let modified: HashSet<AssetId<StandardMaterial>> = material_events
+        .read()
+        .filter_map(|ev| match ev {
+            AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+    let material_edited = !modified.is_empty()
+        && q_tiles
+            .iter()
+            .any(|(_, _, mat_h, ..)| modified.contains(&mat_h.0.id()));
+    let tiles_removed = removed.read().count() > 0;
+    if q_changed.is_empty() && !material_edited && !tiles_removed {
+        return;
+    }
+
+    type Batches = BTreeMap<(AssetId<Mesh>, RenderLayers), (Handle<Mesh>, Vec<TileInstance>)>;
+    let mut batches = Batches::new();
+    for (gt, mesh, mat_h, visibility, layers) in &q_tiles {
+        if !visibility.get() {
+            continue;
+        }
+        let color = materials
+            .get(&mat_h.0)
+            .map(|m| m.base_color.to_linear().to_vec4())
+            .unwrap_or(Vec4::ONE);
+        let layers = layers
+            .and_then(|TileLayers(l)| l.clone())
+            .unwrap_or_default();
+        batches
+            .entry((mesh.0.id(), layers))
+            .or_insert_with(|| (mesh.0.clone(), Vec::new()))
+            .1
+            .push(TileInstance {
+                world_from_local: gt.compute_matrix(),
+                color,
+            });
+    }
+
+    // Update batches in place, drop empty ones, spawn new ones
+    for (e, batch) in &q_batches {
+        match batches.remove(&(batch.mesh, batch.layers.clone())) {
+            Some((_, instances)) if *batch.instances == instances => {}
+            Some((_, instances)) => {
+                commands.entity(e).insert(TileBatch {
+                    instances: Arc::new(instances),
+                    ..batch.clone()
+                });
+            }
+            None => commands.entity(e).despawn(),
+        }
+    }
+    for ((id, layers), (mesh, instances)) in batches {
+        commands.spawn((
+            Mesh3d(mesh),
+            Transform::IDENTITY,
+            Visibility::default(),
+            // Instances carry their own transforms; the batch's bounds mean nothing
+            NoFrustumCulling,
+            layers.clone(),
+            Name::new("Tile batch"),
+            TileBatch {
+                mesh: id,
+                layers,
+                instances: Arc::new(instances),
+            },
+        ));
+    }
+}
+
+// ========== Render world ==========
+
+#[derive(Resource)]
+struct TilePipeline {
+    shader: Handle<Shader>,
+    mesh_pipeline: MeshPipeline,
+    instances_layout: BindGroupLayout,
+}
+
+impl FromWorld for TilePipeline {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+        let instances_layout = render_device.create_bind_group_layout(
+            "tile_instances_layout",
+            &BindGroupLayoutEntries::single(
+                ShaderStages::VERTEX,
+                storage_buffer_read_only::<TileInstance>(false),
+            ),
+        );
+        TilePipeline {
+            shader: world.load_asset(SHADER_ASSET_PATH),
+            mesh_pipeline: world.resource::<MeshPipeline>().clone(),
+            instances_layout,
+        }
+    }
+}
+
+impl SpecializedMeshPipeline for TilePipeline {
+    type Key = MeshPipelineKey;
+
+    fn specialize(
+        &self,
+        key: Self::Key,
+        layout: &MeshVertexBufferLayoutRef,
+    ) -> Result<RenderPipelineDescriptor, SpecializedMeshPipelineError> {
+        let mut descriptor = self.mesh_pipeline.specialize(key, layout)?;
+        descriptor.vertex.shader = self.shader.clone();
+        // Instances take the mesh bind group's place: the shader reads no per-mesh data, so the
+        // draw doesn't depend on the mesh uniforms (or on indirect drawing being off)
+        descriptor.layout.truncate(1);
+        descriptor.layout.push(self.instances_layout.clone());
+        if let Some(fragment) = descriptor.fragment.as_mut() {
+            fragment.shader = self.shader.clone();
+        }
+        Ok(descriptor)
+    }
+}
+
+/// Uploaded instance buffer of a batch; re-uploaded only when the batch's list was rebuilt.
+#[derive(Component)]
+struct TileInstanceBuffer {
+    source: Arc<Vec<TileInstance>>,
+    // Owns the GPU buffer the bind group points at
+    _buffer: StorageBuffer<Vec<TileInstance>>,
+    bind_group: BindGroup,
+}
+
+fn prepare_tile_buffers(
+    mut commands: Commands,
+    pipeline: Res<TilePipeline>,
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+    q: Query<(Entity, &TileBatch, Option<&TileInstanceBuffer>)>,
+) {
+    for (e, batch, uploaded) in &q {
+        if uploaded.is_some_and(|u| Arc::ptr_eq(&u.source, &batch.instances)) {
+            continue;
+        }
+        let mut buffer = StorageBuffer::from(batch.instances.as_ref().clone());
+        buffer.set_label(Some("tile_instances"));
+        buffer.write_buffer(&render_device, &render_queue);
+        let Some(binding) = buffer.binding() else {
+            continue;
+        };
+        let bind_group = render_device.create_bind_group(
+            "tile_instances_bind_group",
+            &pipeline.instances_layout,
+            &BindGroupEntries::single(binding),
+        );
+        commands.entity(e).insert(TileInstanceBuffer {
+            source: batch.instances.clone(),
+            _buffer: buffer,
+            bind_group,
+        });
+    }
+}
+
+fn queue_tile_batches(
+    draw_functions: Res<DrawFunctions<Transparent3d>>,
+    pipeline: Res<TilePipeline>,
+    mut pipelines: ResMut<SpecializedMeshPipelines<TilePipeline>>,
+    pipeline_cache: Res<PipelineCache>,
+    meshes: Res<RenderAssets<RenderMesh>>,
+    render_mesh_instances: Res<RenderMeshInstances>,
+    q_batches: Query<(Entity, &MainEntity, &TileBatch)>,
+    mut phases: ResMut<ViewSortedRenderPhases<Transparent3d>>,
+    views: Query<(
+        &ExtractedView,
+        &Msaa,
+        Option<&Tonemapping>,
+        (
+            Has<DepthPrepass>,
+            Has<NormalPrepass>,
+            Has<MotionVectorPrepass>,
+            Has<DeferredPrepass>,
+        ),
+        Option<&RenderLayers>,
+    )>,
+) {
+    let draw_tiles = draw_functions.read().id::<DrawTiles>();
+
+    for (view, msaa, tonemapping, (depth, normal, motion, deferred), layers) in &views {
+        let layers = layers.cloned().unwrap_or_default();
+        let Some(phase) = phases.get_mut(&view.retained_view_entity) else {
+            continue;
+        };
+
+        // The pipeline layout must match the view's bind group, which depends on its prepasses
+        let mut view_key = MeshPipelineKey::from_msaa_samples(msaa.samples())
+            | MeshPipelineKey::from_hdr(view.hdr);
+        if depth {
+            view_key |= MeshPipelineKey::DEPTH_PREPASS;
+        }
+        if normal {
+            view_key |= MeshPipelineKey::NORMAL_PREPASS;
+        }
+        if motion {
+            view_key |= MeshPipelineKey::MOTION_VECTOR_PREPASS;
+        }
+        if deferred {
+            view_key |= MeshPipelineKey::DEFERRED_PREPASS;
+        }
+        if !view.hdr
+            && let Some(tonemapping) = tonemapping
+        {
+            view_key |= MeshPipelineKey::TONEMAP_IN_SHADER | tonemapping_pipeline_key(*tonemapping);
+        }
+
+        let rangefinder = view.rangefinder3d();
+        for (entity, main_entity, batch) in &q_batches {
+            // Batches draw on their tiles' layers; cameras that see none of them (overdraw) skip
+            if !layers.intersects(&batch.layers) {
+                continue;
+            }
+            let Some(mesh_instance) = render_mesh_instances.render_mesh_queue_data(*main_entity)
+            else {
+                continue;
+            };
+            let Some(mesh) = meshes.get(mesh_instance.mesh_asset_id) else {
+                continue;
+            };
+            let key =
+                view_key | MeshPipelineKey::from_primitive_topology(mesh.primitive_topology());
+            let Ok(pipeline_id) =
+                pipelines.specialize(&pipeline_cache, &pipeline, key, &mesh.layout)
+            else {
+                continue;
+            };
+            phase.add(Transparent3d {
+                entity: (entity, *main_entity),
+                pipeline: pipeline_id,
+                draw_function: draw_tiles,
+                distance: rangefinder.distance_translation(&mesh_instance.translation),
+                batch_range: 0..1,
+                extra_index: PhaseItemExtraIndex::None,
+                indexed: true,
+            });
+        }
+    }
+}
+
+type DrawTiles = (
+    SetItemPipeline,
+    SetMeshViewBindGroup<0>,
+    SetTileInstancesBindGroup<1>,
+    DrawTileInstances,
+);
+
+struct SetTileInstancesBindGroup<const I: usize>;
+impl<P: PhaseItem, const I: usize> RenderCommand<P> for SetTileInstancesBindGroup<I> {
+    type Param = ();
+    type ViewQuery = ();
+    type ItemQuery = Read<TileInstanceBuffer>;
+
+    fn render<'w>(
+        _item: &P,
+        _view: (),
+        buffer: Option<&'w TileInstanceBuffer>,
+        _param: SystemParamItem<'w, '_, Self::Param>,
+        pass: &mut TrackedRenderPass<'w>,
+    ) -> RenderCommandResult {
+        let Some(buffer) = buffer else {
+            return RenderCommandResult::Skip;
+        };
+        pass.set_bind_group(I, &buffer.bind_group, &[]);
+        RenderCommandResult::Success
+    }
+}
+
+struct DrawTileInstances;
+impl<P: PhaseItem> RenderCommand<P> for DrawTileInstances {
+    type Param = (
+        SRes<RenderAssets<RenderMesh>>,
+        SRes<RenderMeshInstances>,
+        SRes<MeshAllocator>,
+    );
+    type ViewQuery = ();
+    type ItemQuery = Read<TileInstanceBuffer>;
+
+    fn render<'w>(
+        item: &P,
+        _view: (),
+        buffer: Option<&'w TileInstanceBuffer>,
+        (meshes, render_mesh_instances, mesh_allocator): SystemParamItem<'w, '_, Self::Param>,
+        pass: &mut TrackedRenderPass<'w>,
+    ) -> RenderCommandResult {
+        // A borrow check workaround
+        let mesh_allocator = mesh_allocator.into_inner();
+
+        let Some(mesh_instance) = render_mesh_instances.render_mesh_queue_data(item.main_entity())
+        else {
+            return RenderCommandResult::Skip;
+        };
+        let Some(gpu_mesh) = meshes.into_inner().get(mesh_instance.mesh_asset_id) else {
+            return RenderCommandResult::Skip;
+        };
+        let Some(buffer) = buffer else {
+            return RenderCommandResult::Skip;
+        };
+        let Some(vertex_slice) = mesh_allocator.mesh_vertex_slice(&mesh_instance.mesh_asset_id)
+        else {
+            return RenderCommandResult::Skip;
+        };
+        let count = buffer.source.len() as u32;
+
+        pass.set_vertex_buffer(0, vertex_slice.buffer.slice(..));
+        match &gpu_mesh.buffer_info {
+            RenderMeshBufferInfo::Indexed {
+                index_format,
+                count: index_count,
+            } => {
+                let Some(index_slice) =
+                    mesh_allocator.mesh_index_slice(&mesh_instance.mesh_asset_id)
+                else {
+                    return RenderCommandResult::Skip;
+                };
+                pass.set_index_buffer(index_slice.buffer.slice(..), 0, *index_format);
+                pass.draw_indexed(
+                    index_slice.range.start..(index_slice.range.start + index_count),
+                    vertex_slice.range.start as i32,
+                    0..count,
+                );
+            }
+            RenderMeshBufferInfo::NonIndexed => {
+                pass.draw(vertex_slice.range, 0..count);
+            }
+        }
+        RenderCommandResult::Success
+    }
+}
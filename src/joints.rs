@@ -0,0 +1,388 @@
+//! Physics joints between objects: hinges (doors, draw-bridges, see-saws), sliders (lifts,
+//! sliding walls) and fixed joints (gluing parts together). A joint lives on the object that
+//! moves and names the object it hangs from, so it survives a save and load. Joints come alive
+//! in Play mode, where jointed objects become dynamic bodies; leaving Play puts them back.
+
+use bevy::prelude::*;
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::SceneEditState;
+use crate::accessibility::Described;
+use crate::editor_mode::EditorMode;
+use crate::inspector::{Editable, SceneIoState, Selected};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum JointKind {
+    /// Turns around the axis
+    #[default]
+    Hinge,
+    /// Moves along the axis
+    Slider,
+    /// Doesn't move relative to the other object
+    Fixed,
+}
+
+impl JointKind {
+    pub const ALL: [Self; 3] = [Self::Hinge, Self::Slider, Self::Fixed];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Hinge => "Hinge",
+            Self::Slider => "Slider",
+            Self::Fixed => "Fixed",
+        }
+    }
+}
+
+/// On the moving object: joins it to the object named `to`.
+#[derive(Component, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct SceneJoint {
+    pub kind: JointKind,
+    /// Name of the object it's attached to
+    pub to: String,
+    /// Pivot, from the object's origin along its own (unscaled) axes
+    pub anchor: [f32; 3],
+    /// Hinge or slide direction in the object's own axes
+    pub axis: [f32; 3],
+    /// Degrees for a hinge, meters for a slider; free when `None`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limits: Option<[f32; 2]>,
+}
+
+impl SceneJoint {
+    fn new(to: String) -> Self {
+        Self {
+            kind: JointKind::Hinge,
+            to,
+            anchor: [0.0; 3],
+            axis: [0.0, 1.0, 0.0],
+            limits: None,
+        }
+    }
+
+    fn axis(&self) -> Vec3 {
+        Vec3::from_array(self.axis)
+            .try_normalize()
+            .unwrap_or(Vec3::Y)
+    }
+
+    /// Rapier joint from `b` (this object) to `a` (the one it's attached to), as they stand.
+    fn build(&self, a: &Transform, b: &Transform) -> TypedJoint {
+        let anchor2 = Vec3::from_array(self.anchor);
+        let pivot = b.translation + b.rotation * anchor2;
+        let anchor1 = a.rotation.inverse() * (pivot - a.translation);
+        let axis2 = self.axis();
+        let axis1 = a.rotation.inverse() * (b.rotation * axis2);
+        match self.kind {
+            JointKind::Hinge => {
+                let mut joint = RevoluteJointBuilder::new(axis1)
+                    .local_axis2(axis2)
+                    .local_anchor1(anchor1)
+                    .local_anchor2(anchor2);
+                if let Some([min, max]) = self.limits {
+                    joint = joint.limits([min.to_radians(), max.to_radians()]);
+                }
+                joint.into()
+            }
+            JointKind::Slider => {
+                let mut joint = PrismaticJointBuilder::new(axis1)
+                    .local_axis2(axis2)
+                    .local_anchor1(anchor1)
+                    .local_anchor2(anchor2);
+                if let Some(limits) = self.limits {
+                    joint = joint.limits(limits);
+                }
+                joint.into()
+            }
+            JointKind::Fixed => FixedJointBuilder::new()
+                .local_basis1(a.rotation.inverse() * b.rotation)
+                .local_anchor1(anchor1)
+                .local_anchor2(anchor2)
+                .into(),
+        }
+    }
+}
+
+/// Where a body stood before Play mode set it moving.
+#[derive(Component)]
+struct JointRest(Transform);
+
+pub struct JointsPlugin;
+impl Plugin for JointsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EditorMode>()
+            .add_systems(Update, (play_joints, draw_joint_gizmos))
+            .add_systems(EguiPrimaryContextPass, joints_window);
+    }
+}
+
+/// Make jointed objects (and what they hang from) bodies in Play mode, and undo it after.
+fn play_joints(
+    mut commands: Commands,
+    mode: Res<EditorMode>,
+    q_joints: Query<(Entity, &SceneJoint), With<Editable>>,
+    q_named: Query<(Entity, &Name, Has<SceneJoint>), With<Editable>>,
+    q_rest: Query<(Entity, &JointRest)>,
+    mut q_tf: Query<&mut Transform>,
+) {
+    if !mode.is_changed() {
+        return;
+    }
+    if *mode != EditorMode::Play {
+        for (e, rest) in &q_rest {
+            if let Ok(mut tf) = q_tf.get_mut(e) {
+                *tf = rest.0;
+            }
+            commands
+                .entity(e)
+                .remove::<(RigidBody, ImpulseJoint, JointRest)>();
+        }
+        return;
+    }
+    if !q_rest.is_empty() {
+        return;
+    }
+
+    for (e, joint) in &q_joints {
+        let Some((a, _, a_jointed)) = q_named
+            .iter()
+            .find(|(a, name, _)| *a != e && name.as_str() == joint.to)
+        else {
+            eprintln!("Joint: no object named {:?} to attach to", joint.to);
+            continue;
+        };
+        let (Ok(tf), Ok(a_tf)) = (q_tf.get(e).copied(), q_tf.get(a).copied()) else {
+            continue;
+        };
+        commands.entity(e).insert((
+            RigidBody::Dynamic,
+            ImpulseJoint::new(a, joint.build(&a_tf, &tf)),
+            JointRest(tf),
+        ));
+        // Chains: an object that is itself jointed stays dynamic
+        if !a_jointed {
+            commands
+                .entity(a)
+                .insert((RigidBody::Fixed, JointRest(a_tf)));
+        }
+    }
+}
+
+/// Pivot, axis and limits of every joint, and a line to what it's attached to.
+fn draw_joint_gizmos(
+    mut gizmos: Gizmos,
+    edit_state: Res<SceneEditState>,
+    q_joints: Query<(&SceneJoint, &GlobalTransform, Has<Selected>)>,
+    q_named: Query<(&Name, &GlobalTransform), With<Editable>>,
+) {
+    if !edit_state.open {
+        return;
+    }
+    for (joint, gt, selected) in &q_joints {
+        let (_, rotation, translation) = gt.to_scale_rotation_translation();
+        let pivot = translation + rotation * Vec3::from_array(joint.anchor);
+        let axis = rotation * joint.axis();
+        let color = if selected {
+            Color::srgb(1.0, 0.6, 0.1)
+        } else {
+            Color::srgba(1.0, 0.6, 0.1, 0.5)
+        };
+
+        if let Some((_, a_gt)) = q_named.iter().find(|(n, _)| n.as_str() == joint.to) {
+            gizmos.line(pivot, a_gt.translation(), color.with_alpha(0.3));
+        }
+        gizmos.sphere(Isometry3d::from_translation(pivot), 0.06, color);
+        match joint.kind {
+            JointKind::Hinge => {
+                gizmos.line(pivot - axis * 0.5, pivot + axis * 0.5, color);
+                let turn = Quat::from_rotation_arc(Vec3::Y, axis);
+                match joint.limits {
+                    // Swept range, starting from the lower limit
+                    Some([min, max]) => {
+                        let start = turn * Quat::from_rotation_y(min.to_radians());
+                        gizmos.arc_3d(
+                            (max - min).to_radians(),
+                            0.4,
+                            Isometry3d::new(pivot, start),
+                            color,
+                        );
+                    }
+                    None => {
+                        gizmos.circle(
+                            Isometry3d::new(pivot, Quat::from_rotation_arc(Vec3::Z, axis)),
+                            0.4,
+                            color,
+                        );
+                    }
+                }
+            }
+            JointKind::Slider => {
+                let [min, max] = joint.limits.unwrap_or([-1.0, 1.0]);
+                gizmos.arrow(pivot + axis * min, pivot + axis * max, color);
+            }
+            JointKind::Fixed => {
+                gizmos.cuboid(
+                    Transform::from_translation(pivot)
+                        .with_rotation(rotation)
+                        .with_scale(Vec3::splat(0.15)),
+                    color,
+                );
+            }
+        }
+    }
+}
+
+/// Joints of the selected objects, and a way to join two selected objects.
+fn joints_window(
+    mut ctxs: EguiContexts,
+    edit_state: Res<SceneEditState>,
+    mut commands: Commands,
+    mut io: Option<ResMut<SceneIoState>>,
+    mut swap: Local<bool>,
+    q_selected: Query<
+        (Entity, Option<&Name>, Option<&SceneJoint>),
+        (With<Selected>, With<Editable>),
+    >,
+) {
+    if !edit_state.open {
+        return;
+    }
+    let mut changed = false;
+    let Ok(ctx) = ctxs.ctx_mut() else {
+        return;
+    };
+    egui::Window::new("Joints")
+        .default_open(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            let selected: Vec<_> = q_selected.iter().collect();
+
+            // Joining two objects
+            if let [first, second] = selected[..] {
+                let (moving, fixed) = if *swap {
+                    (second, first)
+                } else {
+                    (first, second)
+                };
+                let name_of =
+                    |n: Option<&Name>| n.map_or("(unnamed)".to_string(), |n| n.to_string());
+                ui.horizontal(|ui| {
+                    ui.label(format!(
+                        "{} hangs from {}",
+                        name_of(moving.1),
+                        name_of(fixed.1)
+                    ));
+                    if ui.small_button("⇄").described("Swap").clicked() {
+                        *swap = !*swap;
+                    }
+                });
+                let can_join = fixed.1.is_some() && moving.2.is_none();
+                ui.horizontal(|ui| {
+                    for kind in JointKind::ALL {
+                        if ui
+                            .add_enabled(can_join, egui::Button::new(kind.label()))
+                            .on_hover_text(format!("Add a {} joint", kind.label().to_lowercase()))
+                            .clicked()
+                            && let Some(name) = fixed.1
+                        {
+                            let mut joint = SceneJoint::new(name.to_string());
+                            joint.kind = kind;
+                            commands.entity(moving.0).insert(joint);
+                            changed = true;
+                        }
+                    }
+                });
+                if fixed.1.is_none() {
+                    ui.small("The object to attach to needs a name.");
+                } else if moving.2.is_some() {
+                    ui.small("Already jointed; remove its joint first.");
+                }
+                ui.separator();
+            } else if selected.iter().all(|(.., joint)| joint.is_none()) {
+                ui.label("Select two objects to join them.");
+            }
+
+            for (e, name, joint) in selected {
+                let Some(joint) = joint else {
+                    continue;
+                };
+                ui.push_id(e, |ui| {
+                    ui.strong(name.map_or("(unnamed)".to_string(), |n| n.to_string()));
+                    changed |= joint_ui(ui, &mut commands, e, joint);
+                });
+                ui.separator();
+            }
+        });
+    if changed && let Some(io) = io.as_mut() {
+        io.mark_edited();
+    }
+}
+
+/// Edit one joint. Returns whether anything changed.
+fn joint_ui(ui: &mut egui::Ui, commands: &mut Commands, e: Entity, joint: &SceneJoint) -> bool {
+    let mut edited = joint.clone();
+    ui.horizontal(|ui| {
+        ui.label("Attached to");
+        ui.text_edit_singleline(&mut edited.to);
+    });
+    ui.horizontal(|ui| {
+        for kind in JointKind::ALL {
+            ui.selectable_value(&mut edited.kind, kind, kind.label());
+        }
+    });
+    ui.horizontal(|ui| {
+        ui.label("Pivot");
+        for v in &mut edited.anchor {
+            ui.add(egui::DragValue::new(v).speed(0.05));
+        }
+    });
+    if edited.kind != JointKind::Fixed {
+        ui.horizontal(|ui| {
+            ui.label("Axis");
+            for (label, axis) in [
+                ("X", [1.0, 0.0, 0.0]),
+                ("Y", [0.0, 1.0, 0.0]),
+                ("Z", [0.0, 0.0, 1.0]),
+            ] {
+                ui.selectable_value(&mut edited.axis, axis, label);
+            }
+            for v in &mut edited.axis {
+                ui.add(egui::DragValue::new(v).speed(0.05).range(-1.0..=1.0));
+            }
+        });
+        let mut limited = edited.limits.is_some();
+        let unit = if edited.kind == JointKind::Hinge {
+            "°"
+        } else {
+            " m"
+        };
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut limited, "Limits");
+            if limited {
+                let [min, max] = edited
+                    .limits
+                    .get_or_insert(if edited.kind == JointKind::Hinge {
+                        [-45.0, 45.0]
+                    } else {
+                        [-1.0, 1.0]
+                    });
+                ui.add(egui::DragValue::new(min).suffix(unit));
+                ui.add(egui::DragValue::new(max).suffix(unit));
+                *max = max.max(*min);
+            } else {
+                edited.limits = None;
+            }
+        });
+    }
+    if ui.button("Remove joint").clicked() {
+        commands.entity(e).remove::<SceneJoint>();
+        return true;
+    }
+    if edited == *joint {
+        return false;
+    }
+    commands.entity(e).insert(edited);
+    true
+}
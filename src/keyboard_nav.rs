@@ -0,0 +1,141 @@
+//! Editing without a mouse: step the selection through the scene objects in Hierarchy order
+//! (`]` and `[`), and nudge it one move snap step with the arrow keys (along the grid axis
+//! nearest to where the camera looks) or up and down with Page Up / Page Down. The panels
+//! themselves are reached with Tab; see `accessibility` for how their keys are kept apart.
+
+use bevy::prelude::*;
+
+use crate::SceneEditState;
+use crate::bake::BakedMember;
+use crate::camera::OrbitCamera;
+use crate::editor_mode::EditorMode;
+use crate::hierarchy::{HierarchyOrder, list_order};
+use crate::inspector::{Editable, SelectObjectEvent, Selected};
+use crate::settings::{EditorPrefs, KeyBindings};
+
+pub struct KeyboardNavPlugin;
+impl Plugin for KeyboardNavPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<KeyBindings>()
+            .init_resource::<EditorPrefs>()
+            .init_resource::<EditorMode>()
+            .add_systems(Update, (cycle_selection, nudge_selection));
+    }
+}
+
+/// Whether the editor's keys apply: its panels are up and the arrow keys don't walk a player.
+fn editing(edit_state: &SceneEditState, mode: &EditorMode) -> bool {
+    edit_state.open && *mode != EditorMode::Play
+}
+
+/// Select the object after (or before) the selected one, wrapping around the list.
+fn cycle_selection(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    edit_state: Res<SceneEditState>,
+    mode: Res<EditorMode>,
+    q_objects: Query<
+        (
+            Entity,
+            Option<&Name>,
+            Option<&HierarchyOrder>,
+            Has<Selected>,
+        ),
+        (With<Editable>, Without<BakedMember>),
+    >,
+    mut ev_select: EventWriter<SelectObjectEvent>,
+) {
+    if !editing(&edit_state, &mode) {
+        return;
+    }
+    let step: isize = if keys.just_pressed(bindings.select_next) {
+        1
+    } else if keys.just_pressed(bindings.select_previous) {
+        -1
+    } else {
+        return;
+    };
+
+    let mut rows: Vec<(Entity, String, Option<HierarchyOrder>, bool)> = q_objects
+        .iter()
+        .map(|(e, name, order, selected)| {
+            let label = name.map_or_else(|| format!("Entity {e}"), |n| n.to_string());
+            (e, label, order.copied(), selected)
+        })
+        .collect();
+    if rows.is_empty() {
+        return;
+    }
+    rows.sort_by(|a, b| list_order((a.2, a.1.as_str(), a.0), (b.2, b.1.as_str(), b.0)));
+
+    let count = rows.len() as isize;
+    let next = match rows.iter().position(|r| r.3) {
+        Some(i) => (i as isize + step).rem_euclid(count),
+        // Nothing selected yet: start from whichever end is first in that direction
+        None if step > 0 => 0,
+        None => count - 1,
+    };
+    ev_select.write(SelectObjectEvent(rows[next as usize].0));
+}
+
+/// Move the selection one snap step on an arrow key (Page Up / Page Down for height).
+fn nudge_selection(
+    keys: Res<ButtonInput<KeyCode>>,
+    edit_state: Res<SceneEditState>,
+    mode: Res<EditorMode>,
+    prefs: Res<EditorPrefs>,
+    q_cam: Query<&Transform, (With<OrbitCamera>, Without<Selected>)>,
+    mut q_sel: Query<(&mut Transform, Option<&ChildOf>), (With<Selected>, With<Editable>)>,
+    q_selected: Query<(), With<Selected>>,
+    q_parents: Query<&GlobalTransform>,
+) {
+    if !editing(&edit_state, &mode) {
+        return;
+    }
+    // Alt+arrows walk the selection history
+    if keys.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]) {
+        return;
+    }
+    let Ok(cam) = q_cam.single() else {
+        return;
+    };
+
+    // Screen up and right on the ground, snapped to the nearest grid axis
+    let look = cam.forward().with_y(0.0);
+    let forward = if look.x.abs() > look.z.abs() {
+        Vec3::X * look.x.signum()
+    } else {
+        Vec3::Z * look.z.signum()
+    };
+    let right = forward.cross(Vec3::Y);
+    let mut dir = Vec3::ZERO;
+    for (key, d) in [
+        (KeyCode::ArrowUp, forward),
+        (KeyCode::ArrowDown, -forward),
+        (KeyCode::ArrowRight, right),
+        (KeyCode::ArrowLeft, -right),
+        (KeyCode::PageUp, Vec3::Y),
+        (KeyCode::PageDown, -Vec3::Y),
+    ] {
+        if keys.just_pressed(key) {
+            dir += d;
+        }
+    }
+    if dir == Vec3::ZERO {
+        return;
+    }
+    let delta = dir * prefs.move_snap.max(0.01);
+
+    for (mut tf, child_of) in &mut q_sel {
+        let local = match child_of {
+            // Moved along with its selected parent already
+            Some(c) if q_selected.contains(c.parent()) => continue,
+            // Children's positions are in their parent's space
+            Some(c) => q_parents
+                .get(c.parent())
+                .map_or(delta, |gt| gt.affine().inverse().transform_vector3(delta)),
+            None => delta,
+        };
+        tf.translation += local;
+    }
+}
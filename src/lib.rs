@@ -0,0 +1,220 @@
+//! Tunable World: a diorama editor and post-processing stack for Bevy.
+//!
+//! Add [`TunableWorldPlugins`] next to `DefaultPlugins` for the whole tool, or pick individual
+//! plugins (`camera::CameraPlugin`, `inspector::InspectorPlugin`, `post::PostEffectsPlugins`, ...)
+//! to embed just the parts you need.
+#![allow(clippy::too_many_arguments)]
+#![allow(clippy::type_complexity)]
+
+use bevy::app::PluginGroupBuilder;
+use bevy::diagnostic::FrameTimeDiagnosticsPlugin;
+use bevy::prelude::*;
+use bevy_egui::EguiPlugin;
+use bevy_rapier3d::prelude::*;
+
+pub mod accessibility;
+pub mod actions;
+pub mod asset_cache;
+pub mod bake;
+pub mod benchmark;
+pub mod block_groups;
+pub mod bounds;
+pub mod build_mode;
+pub mod camera;
+pub mod camera_rails;
+#[cfg(all(feature = "capture", not(target_arch = "wasm32")))]
+pub mod capture;
+pub mod chunks;
+pub mod clipboard;
+#[cfg(all(feature = "control", not(target_arch = "wasm32")))]
+pub mod control;
+pub mod creatures;
+pub mod custom_kinds;
+pub mod custom_props;
+pub mod cutaway;
+pub mod decals;
+pub mod diagnostics;
+pub mod dissolve;
+pub mod dock;
+pub mod editor_gizmos;
+pub mod editor_mode;
+pub mod elevators;
+pub mod environment;
+pub mod floor_plan;
+pub mod fog_volumes;
+pub mod grid;
+pub mod ground_paint;
+pub mod help;
+pub mod hierarchy;
+pub mod inspector;
+pub mod instancing;
+pub mod joints;
+pub mod keyboard_nav;
+pub mod lights;
+pub mod lod;
+pub mod material_presets;
+pub mod material_replace;
+pub mod material_slots;
+pub mod measure;
+pub mod num_expr;
+pub mod objectives;
+pub mod physics_preview;
+pub mod placement;
+pub mod play_rollback;
+#[cfg(feature = "player")]
+pub mod player;
+pub mod post;
+pub mod power;
+pub mod project;
+pub mod randomize;
+pub mod reflection;
+#[cfg(all(feature = "remote", not(target_arch = "wasm32")))]
+pub mod remote;
+pub mod rotation_edit;
+pub mod scene_export;
+pub mod scene_io;
+#[cfg(feature = "scripting")]
+pub mod scripting;
+pub mod selection_history;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod session_log;
+pub mod settings;
+pub mod signs;
+pub mod splines;
+pub mod stairs;
+pub mod stylized;
+pub mod surfaces;
+pub mod tiled;
+pub mod time_controls;
+pub mod timeline;
+pub mod toasts;
+pub mod toolbar;
+pub mod tween;
+pub mod ui_theme;
+pub mod validation;
+pub mod vertex_paint;
+pub mod view_layers;
+
+/// Global UI state for toggling panels like the Inspector.
+#[derive(Resource)]
+pub struct SceneEditState {
+    pub open: bool,
+}
+
+impl Default for SceneEditState {
+    fn default() -> Self {
+        Self { open: true }
+    }
+}
+
+/// `SceneEditState`, which every editor panel listens to, and the editor's actions (Spacebar, or
+/// its rebinding, toggles the panels).
+pub struct SceneEditPlugin;
+impl Plugin for SceneEditPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SceneEditState>()
+            .init_resource::<settings::KeyBindings>()
+            .add_plugins(actions::ActionsPlugin);
+    }
+}
+
+/// Everything the standalone app runs (as far as the enabled Cargo features allow), minus its
+/// demo scene.
+///
+/// Includes `EguiPlugin`, `RapierPhysicsPlugin` and `FrameTimeDiagnosticsPlugin`; disable them
+/// with `.disable::<T>()` if the host app already adds its own.
+pub struct TunableWorldPlugins;
+impl PluginGroup for TunableWorldPlugins {
+    fn build(self) -> PluginGroupBuilder {
+        let group = PluginGroupBuilder::start::<Self>()
+            .add(FrameTimeDiagnosticsPlugin::default()) // collects fps and frame time
+            .add(RapierPhysicsPlugin::<NoUserData>::default())
+            .add(SceneEditPlugin)
+            .add(settings::SettingsPlugin)
+            .add(asset_cache::AssetCachePlugin)
+            .add(camera::CameraPlugin)
+            .add_group(post::PostEffectsPlugins)
+            // UI plugin (egui)
+            .add(EguiPlugin::default())
+            .add(ui_theme::UiThemePlugin)
+            .add(accessibility::AccessibilityPlugin)
+            .add(toasts::ToastsPlugin)
+            .add(dock::DockPlugin)
+            .add(editor_mode::EditorModePlugin)
+            .add(editor_gizmos::EditorGizmosPlugin)
+            .add(time_controls::TimeControlsPlugin)
+            .add(power::PowerPlugin)
+            .add(toolbar::ToolbarPlugin)
+            .add(measure::MeasurePlugin)
+            .add(grid::GridPlugin)
+            .add(post::ui::PostUiPlugin);
+        #[cfg(feature = "inspector")]
+        let group = group
+            .add(inspector::InspectorPlugin)
+            .add(scene_export::SceneExportPlugin)
+            .add(hierarchy::HierarchyPlugin)
+            .add(selection_history::SelectionHistoryPlugin)
+            .add(keyboard_nav::KeyboardNavPlugin)
+            .add(placement::PlacementPlugin)
+            .add(chunks::ChunksPlugin)
+            .add(clipboard::ClipboardPlugin)
+            .add(project::ProjectPlugin::default());
+        // The log is a file; the browser build has none to append to
+        #[cfg(all(feature = "inspector", not(target_arch = "wasm32")))]
+        let group = group.add(session_log::SessionLogPlugin);
+        let group = group
+            .add(environment::EnvironmentPlugin)
+            .add(lights::LightsPlugin)
+            .add(reflection::ReflectionPlugin)
+            .add(fog_volumes::FogVolumesPlugin)
+            .add(creatures::CreaturesPlugin)
+            .add(decals::DecalsPlugin)
+            .add(build_mode::BuildModePlugin)
+            .add(tiled::TiledPlugin)
+            .add(floor_plan::FloorPlanPlugin)
+            .add(stairs::StairsPlugin)
+            .add(splines::SplinesPlugin)
+            .add(camera_rails::CameraRailsPlugin)
+            .add(custom_kinds::CustomKindsPlugin)
+            .add(bake::BakePlugin)
+            .add(randomize::RandomizePlugin)
+            .add(material_presets::MaterialPresetsPlugin)
+            .add(material_replace::MaterialReplacePlugin)
+            .add(physics_preview::PhysicsPreviewPlugin)
+            .add(joints::JointsPlugin)
+            .add(dissolve::DissolvePlugin)
+            .add(block_groups::BlockGroupsPlugin)
+            .add(elevators::ElevatorsPlugin)
+            .add(surfaces::SurfacesPlugin)
+            .add(stylized::StylizedPlugin)
+            .add(ground_paint::GroundPaintPlugin)
+            .add(vertex_paint::VertexPaintPlugin)
+            .add(signs::SignsPlugin)
+            .add(objectives::ObjectivesPlugin)
+            .add(play_rollback::PlayRollbackPlugin)
+            .add(timeline::TimelinePlugin)
+            .add(tween::TweenPlugin);
+        // Instanced tiles read a storage buffer, which WebGL2 lacks; tiles draw as plain meshes
+        #[cfg(not(target_arch = "wasm32"))]
+        let group = group.add(instancing::InstancingPlugin);
+        let group = group
+            .add(lod::LodPlugin)
+            .add(view_layers::ViewLayersPlugin)
+            .add(cutaway::CutawayPlugin)
+            .add(bounds::BoundsPlugin)
+            .add(validation::ValidationPlugin)
+            .add(diagnostics::DiagnosticsOverlayPlugin)
+            .add(help::HelpPlugin);
+        #[cfg(feature = "player")]
+        let group = group.add(player::PlayerPlugin);
+        #[cfg(feature = "scripting")]
+        let group = group.add(scripting::ScriptingPlugin);
+        #[cfg(all(feature = "capture", not(target_arch = "wasm32")))]
+        let group = group.add(capture::CapturePlugin);
+        #[cfg(all(feature = "remote", not(target_arch = "wasm32")))]
+        let group = group.add(remote::RemotePlugin::default());
+        #[cfg(all(feature = "control", not(target_arch = "wasm32")))]
+        let group = group.add(control::ControlPlugin::default());
+        group
+    }
+}
@@ -0,0 +1,527 @@
+use bevy::prelude::*;
+use bevy_egui::egui;
+use serde::{Deserialize, Serialize};
+
+use crate::SceneEditState;
+use crate::accessibility::AccessibilityPrefs;
+use crate::asset_cache::AssetCache;
+use crate::inspector::{Editable, EditableMesh, Selected, SpawnKind};
+
+const LIGHT_GIZMO_RADIUS: f32 = 0.15;
+
+/// Persisted light parameters for author-placed point/spot lights.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct SceneLight {
+    pub intensity: f32, // lumens
+    pub range: f32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spot_angle_deg: Option<f32>, // outer cone angle, spot lights only
+}
+
+impl Default for SceneLight {
+    fn default() -> Self {
+        Self {
+            intensity: 20_000.0,
+            range: 8.0,
+            spot_angle_deg: None,
+        }
+    }
+}
+
+/// What a `LightAnimation` does over time.
+#[derive(Copy, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub enum LightAnimMode {
+    #[default]
+    Flicker, // irregular intensity jitter (torches, candles)
+    Pulse,      // periodic intensity swell (crystals, beacons)
+    ColorCycle, // blend between the base color and `color_b`
+}
+
+/// Shape of one Pulse / ColorCycle period.
+#[derive(Copy, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub enum AnimCurve {
+    #[default]
+    Sine,
+    Triangle,
+    Square,
+    Smoothstep,
+}
+
+impl AnimCurve {
+    /// Map a 0..1 phase to a 0..1 weight.
+    fn sample(self, p: f32) -> f32 {
+        let p = p.rem_euclid(1.0);
+        match self {
+            AnimCurve::Sine => 0.5 - 0.5 * (p * std::f32::consts::TAU).cos(),
+            AnimCurve::Triangle => 1.0 - (2.0 * p - 1.0).abs(),
+            AnimCurve::Square => {
+                if p < 0.5 {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            AnimCurve::Smoothstep => {
+                let x = 1.0 - (2.0 * p - 1.0).abs();
+                x * x * (3.0 - 2.0 * x)
+            }
+        }
+    }
+}
+
+/// Animates a point/spot light's intensity/color, or the emissive of the entity's material.
+/// Note: emissive animation edits the material asset, so objects sharing it animate together.
+#[derive(Component, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LightAnimation {
+    pub mode: LightAnimMode,
+    pub curve: AnimCurve,
+    pub speed: f32,        // cycles per second
+    pub amount: f32,       // 0..1 depth of the modulation
+    pub color_b: [f32; 3], // ColorCycle target (sRGB)
+    pub phase: f32,        // 0..1, to desync neighbouring lights
+}
+
+impl Default for LightAnimation {
+    fn default() -> Self {
+        Self {
+            mode: LightAnimMode::Flicker,
+            curve: AnimCurve::Sine,
+            speed: 1.0,
+            amount: 0.35,
+            color_b: [1.0, 0.55, 0.2],
+            phase: 0.0,
+        }
+    }
+}
+
+/// Authored (un-animated) values; the animation is applied on top of these.
+/// The inspector edits these while an animation is running, and the scene saves them.
+#[derive(Component, Clone)]
+pub struct LightAnimBase {
+    pub intensity: f32,
+    pub color: Color,
+    pub emissive: LinearRgba,
+}
+
+pub struct LightsPlugin;
+impl Plugin for LightsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AccessibilityPrefs>().add_systems(
+            Update,
+            (
+                init_light_anim_base,
+                restore_removed_light_anims,
+                animate_lights,
+                draw_light_gizmos,
+            )
+                .chain(),
+        );
+    }
+}
+
+/// Insert the light component matching `kind` (PointLight or SpotLight) on an entity.
+pub fn insert_light(ecmd: &mut EntityCommands, kind: SpawnKind, color: Color, light: SceneLight) {
+    match kind {
+        SpawnKind::SpotLight => {
+            let outer = light.spot_angle_deg.unwrap_or(35.0).to_radians();
+            ecmd.insert(SpotLight {
+                color,
+                intensity: light.intensity,
+                range: light.range,
+                outer_angle: outer,
+                inner_angle: outer * 0.8,
+                shadows_enabled: true,
+                ..default()
+            });
+        }
+        _ => {
+            ecmd.insert(PointLight {
+                color,
+                intensity: light.intensity,
+                range: light.range,
+                shadows_enabled: true,
+                ..default()
+            });
+        }
+    }
+}
+
+/// Spawn an editable, author-placed light (no mesh; drawn as a gizmo in the editor).
+pub fn spawn_light_object(commands: &mut Commands, kind: SpawnKind) -> Entity {
+    let (name, tf, light) = match kind {
+        SpawnKind::SpotLight => (
+            "Spot Light",
+            // Point straight down by default
+            Transform::from_xyz(0.0, 3.0, 0.0)
+                .with_rotation(Quat::from_rotation_x(-std::f32::consts::FRAC_PI_2)),
+            SceneLight {
+                spot_angle_deg: Some(35.0),
+                ..default()
+            },
+        ),
+        _ => (
+            "Point Light",
+            Transform::from_xyz(0.0, 1.5, 0.0),
+            SceneLight::default(),
+        ),
+    };
+
+    let mut ecmd = commands.spawn((
+        Editable,
+        Name::new(name),
+        tf,
+        EditableMesh {
+            kind,
+            collider: None,
+        },
+    ));
+    insert_light(&mut ecmd, kind, Color::srgb(1.0, 0.78, 0.5), light);
+    ecmd.id()
+}
+
+/// Read back the persisted light parameters (authored values if animated).
+pub fn scene_light_of(
+    point: Option<&PointLight>,
+    spot: Option<&SpotLight>,
+    base: Option<&LightAnimBase>,
+) -> Option<(Color, SceneLight)> {
+    let (color, mut light) = if let Some(p) = point {
+        (
+            p.color,
+            SceneLight {
+                intensity: p.intensity,
+                range: p.range,
+                spot_angle_deg: None,
+            },
+        )
+    } else if let Some(s) = spot {
+        (
+            s.color,
+            SceneLight {
+                intensity: s.intensity,
+                range: s.range,
+                spot_angle_deg: Some(s.outer_angle.to_degrees()),
+            },
+        )
+    } else {
+        return None;
+    };
+    if let Some(b) = base {
+        light.intensity = b.intensity;
+        return Some((b.color, light));
+    }
+    Some((color, light))
+}
+
+fn init_light_anim_base(
+    mut commands: Commands,
+    q: Query<
+        (
+            Entity,
+            Option<&PointLight>,
+            Option<&SpotLight>,
+            Option<&MeshMaterial3d<StandardMaterial>>,
+        ),
+        (With<LightAnimation>, Without<LightAnimBase>),
+    >,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    cache: Res<AssetCache>,
+) {
+    for (e, point, spot, mat) in &q {
+        let (intensity, color) = if let Some(p) = point {
+            (p.intensity, p.color)
+        } else if let Some(s) = spot {
+            (s.intensity, s.color)
+        } else {
+            (0.0, Color::WHITE)
+        };
+        let emissive = mat
+            .and_then(|h| materials.get(&h.0))
+            .map(|m| m.emissive)
+            .unwrap_or(LinearRgba::BLACK);
+        // The animation writes the material every frame; don't drag shared copies along
+        if let Some(h) = mat
+            && cache.is_shared_material(&h.0)
+            && let Some(own) = materials.get(&h.0).cloned()
+        {
+            commands
+                .entity(e)
+                .insert(MeshMaterial3d(materials.add(own)));
+        }
+        commands.entity(e).insert(LightAnimBase {
+            intensity,
+            color,
+            emissive,
+        });
+    }
+}
+
+/// Put the authored values back when an animation is removed from an entity.
+fn restore_removed_light_anims(
+    mut commands: Commands,
+    mut removed: RemovedComponents<LightAnimation>,
+    mut q: Query<(
+        &LightAnimBase,
+        Option<&mut PointLight>,
+        Option<&mut SpotLight>,
+        Option<&MeshMaterial3d<StandardMaterial>>,
+    )>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    for e in removed.read() {
+        let Ok((base, point, spot, mat)) = q.get_mut(e) else {
+            continue;
+        };
+        if let Some(mut p) = point {
+            p.intensity = base.intensity;
+            p.color = base.color;
+        }
+        if let Some(mut s) = spot {
+            s.intensity = base.intensity;
+            s.color = base.color;
+        }
+        if let Some(m) = mat.and_then(|h| materials.get_mut(&h.0)) {
+            m.emissive = base.emissive;
+        }
+        commands.entity(e).remove::<LightAnimBase>();
+    }
+}
+
+/// Returns (intensity factor, color blend weight) for this frame.
+fn evaluate(anim: &LightAnimation, t: f32) -> (f32, f32) {
+    let amount = anim.amount.clamp(0.0, 1.0);
+    let p = t * anim.speed + anim.phase;
+    match anim.mode {
+        LightAnimMode::Flicker => {
+            // Sum of incommensurate sines: cheap, deterministic and irregular enough for fire
+            let x = p * std::f32::consts::TAU;
+            let n =
+                0.5 * (x * 1.7).sin() + 0.3 * (x * 3.1 + 1.3).sin() + 0.2 * (x * 7.9 + 4.1).sin();
+            (1.0 - amount * (0.5 + 0.5 * n), 0.0)
+        }
+        LightAnimMode::Pulse => (1.0 - amount * (1.0 - anim.curve.sample(p)), 0.0),
+        LightAnimMode::ColorCycle => (1.0, amount * anim.curve.sample(p)),
+    }
+}
+
+fn animate_lights(
+    time: Res<Time>,
+    access: Res<AccessibilityPrefs>,
+    mut q: Query<(
+        &LightAnimation,
+        &LightAnimBase,
+        Option<&mut PointLight>,
+        Option<&mut SpotLight>,
+        Option<&MeshMaterial3d<StandardMaterial>>,
+    )>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let t = time.elapsed_secs();
+    for (anim, base, point, spot, mat) in &mut q {
+        // Calm effects hold every light at its base look
+        let (factor, blend) = if access.calm_effects {
+            (1.0, 0.0)
+        } else {
+            evaluate(anim, t)
+        };
+        let [r, g, b] = anim.color_b;
+        let color = Color::from(
+            LinearRgba::from(base.color).mix(&LinearRgba::from(Color::srgb(r, g, b)), blend),
+        );
+
+        if let Some(mut p) = point {
+            p.intensity = base.intensity * factor;
+            p.color = color;
+        }
+        if let Some(mut s) = spot {
+            s.intensity = base.intensity * factor;
+            s.color = color;
+        }
+        // Emissive: only touch materials that actually glow
+        if base.emissive != LinearRgba::BLACK
+            && let Some(m) = mat.and_then(|h| materials.get_mut(&h.0))
+        {
+            let tinted = if blend > 0.0 {
+                let c = LinearRgba::from(Color::srgb(r, g, b));
+                let lum = base
+                    .emissive
+                    .red
+                    .max(base.emissive.green)
+                    .max(base.emissive.blue);
+                base.emissive.mix(&(c * lum), blend)
+            } else {
+                base.emissive
+            };
+            m.emissive = tinted * factor;
+        }
+    }
+}
+
+/// Author-placed lights have no mesh; show them as small spheres (spot lights get a direction
+/// arrow).
+fn draw_light_gizmos(
+    mut gizmos: Gizmos,
+    edit_state: Res<SceneEditState>,
+    q: Query<
+        (
+            &GlobalTransform,
+            Option<&PointLight>,
+            Option<&SpotLight>,
+            Has<Selected>,
+        ),
+        (With<Editable>, Or<(With<PointLight>, With<SpotLight>)>),
+    >,
+) {
+    if !edit_state.open {
+        return;
+    }
+    for (gt, point, spot, selected) in &q {
+        let color = point
+            .map(|p| p.color)
+            .or(spot.map(|s| s.color))
+            .unwrap_or(Color::WHITE);
+        let color = if selected {
+            Color::srgb(1.0, 0.85, 0.2)
+        } else {
+            color
+        };
+        let pos = gt.translation();
+        gizmos.sphere(Isometry3d::from_translation(pos), LIGHT_GIZMO_RADIUS, color);
+        if spot.is_some() {
+            gizmos.arrow(pos, pos + gt.forward() * 0.6, color);
+        }
+    }
+}
+
+/// Inspector section for point/spot light parameters. Edits the authored values when animated.
+pub fn light_inspector_ui(
+    ui: &mut egui::Ui,
+    point: Option<Mut<PointLight>>,
+    spot: Option<Mut<SpotLight>>,
+    base: Option<Mut<LightAnimBase>>,
+) {
+    ui.heading("Light");
+
+    let (mut color, mut intensity, mut range) = if let Some(p) = point.as_deref() {
+        (p.color, p.intensity, p.range)
+    } else if let Some(s) = spot.as_deref() {
+        (s.color, s.intensity, s.range)
+    } else {
+        return;
+    };
+    if let Some(b) = base.as_deref() {
+        color = b.color;
+        intensity = b.intensity;
+    }
+    let mut outer_deg = spot.as_deref().map(|s| s.outer_angle.to_degrees());
+
+    let s = color.to_srgba();
+    let mut rgb = [s.red, s.green, s.blue];
+    let mut changed = false;
+    ui.horizontal(|ui| {
+        ui.label("Color");
+        changed |= ui.color_edit_button_rgb(&mut rgb).changed();
+    });
+    changed |= ui
+        .add(
+            egui::Slider::new(&mut intensity, 0.0..=500_000.0)
+                .logarithmic(true)
+                .text("Intensity (lm)"),
+        )
+        .changed();
+    changed |= ui
+        .add(egui::Slider::new(&mut range, 0.5..=50.0).text("Range"))
+        .changed();
+    if let Some(deg) = outer_deg.as_mut() {
+        changed |= ui
+            .add(egui::Slider::new(deg, 1.0..=89.0).text("Cone angle (deg)"))
+            .changed();
+    }
+    if !changed {
+        return;
+    }
+
+    let color = Color::srgb(rgb[0], rgb[1], rgb[2]);
+    if let Some(mut b) = base {
+        b.color = color;
+        b.intensity = intensity;
+    }
+    if let Some(mut p) = point {
+        p.color = color;
+        p.intensity = intensity;
+        p.range = range;
+    }
+    if let Some(mut s) = spot {
+        s.color = color;
+        s.intensity = intensity;
+        s.range = range;
+        if let Some(deg) = outer_deg {
+            s.outer_angle = deg.to_radians();
+            s.inner_angle = s.outer_angle * 0.8;
+        }
+    }
+}
+
+/// Inspector section to add/remove/tune a `LightAnimation`.
+pub fn light_animation_inspector_ui(
+    ui: &mut egui::Ui,
+    commands: &mut Commands,
+    entity: Entity,
+    anim: Option<Mut<LightAnimation>>,
+) {
+    let mut on = anim.is_some();
+    if ui.checkbox(&mut on, "Light animation").changed() {
+        if on {
+            commands.entity(entity).insert(LightAnimation::default());
+        } else {
+            commands.entity(entity).remove::<LightAnimation>();
+        }
+    }
+    let Some(mut current) = anim else {
+        return;
+    };
+    let mut anim = *current;
+
+    egui::ComboBox::from_label("Mode")
+        .selected_text(match anim.mode {
+            LightAnimMode::Flicker => "Flicker",
+            LightAnimMode::Pulse => "Pulse",
+            LightAnimMode::ColorCycle => "Color cycle",
+        })
+        .show_ui(ui, |ui| {
+            ui.selectable_value(&mut anim.mode, LightAnimMode::Flicker, "Flicker");
+            ui.selectable_value(&mut anim.mode, LightAnimMode::Pulse, "Pulse");
+            ui.selectable_value(&mut anim.mode, LightAnimMode::ColorCycle, "Color cycle");
+        });
+    if anim.mode != LightAnimMode::Flicker {
+        egui::ComboBox::from_label("Curve")
+            .selected_text(match anim.curve {
+                AnimCurve::Sine => "Sine",
+                AnimCurve::Triangle => "Triangle",
+                AnimCurve::Square => "Square",
+                AnimCurve::Smoothstep => "Smoothstep",
+            })
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut anim.curve, AnimCurve::Sine, "Sine");
+                ui.selectable_value(&mut anim.curve, AnimCurve::Triangle, "Triangle");
+                ui.selectable_value(&mut anim.curve, AnimCurve::Square, "Square");
+                ui.selectable_value(&mut anim.curve, AnimCurve::Smoothstep, "Smoothstep");
+            });
+    }
+    ui.add(
+        egui::Slider::new(&mut anim.speed, 0.05..=10.0)
+            .logarithmic(true)
+            .text("Speed (Hz)"),
+    );
+    ui.add(egui::Slider::new(&mut anim.amount, 0.0..=1.0).text("Amount"));
+    ui.add(egui::Slider::new(&mut anim.phase, 0.0..=1.0).text("Phase"));
+    if anim.mode == LightAnimMode::ColorCycle {
+        ui.horizontal(|ui| {
+            ui.label("Second color");
+            ui.color_edit_button_rgb(&mut anim.color_b);
+        });
+    }
+
+    // Only touch the component on edits
+    current.set_if_neq(anim);
+}
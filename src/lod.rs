@@ -0,0 +1,218 @@
+use bevy::prelude::*;
+use bevy::render::primitives::Aabb;
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+
+use crate::SceneEditState;
+use crate::bake::BakedMember;
+use crate::camera::OrbitCamera;
+use crate::cutaway::Cutaway;
+use crate::inspector::{Editable, EditableMesh, Selected, SpawnKind};
+use crate::view_layers::ViewHidden;
+
+pub struct LodPlugin;
+impl Plugin for LodPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LodSettings>()
+            .add_systems(Update, (assign_lods, update_lods).chain())
+            .add_systems(EguiPrimaryContextPass, lod_panel);
+    }
+}
+
+/// Global level-of-detail settings for small decor.
+///
+/// Thresholds are in screen coverage (object size / visible height at the object), so the
+/// same values work for the perspective camera's distance and the orthographic zoom.
+#[derive(Resource, Clone, PartialEq)]
+pub struct LodSettings {
+    pub enabled: bool,
+    /// Objects whose largest world extent is above this are never reduced
+    pub max_decor_size: f32,
+    /// Below this coverage, swap to the low-detail mesh (where the kind has one)
+    pub low_detail_below: f32,
+    /// Below this coverage, hide the object
+    pub cull_below: f32,
+}
+
+impl Default for LodSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_decor_size: 1.2,
+            low_detail_below: 0.03,
+            cull_below: 0.004,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+enum LodLevel {
+    #[default]
+    Full,
+    Low,
+    Culled,
+}
+
+/// Added automatically to editable meshes; remembers the full mesh while a simpler one is shown.
+#[derive(Component, Clone)]
+pub struct Lod {
+    pub full: Handle<Mesh>,
+    low: Option<Handle<Mesh>>,
+    level: LodLevel,
+}
+
+impl Lod {
+    /// Hidden for being too small on screen
+    pub fn is_culled(&self) -> bool {
+        self.level == LodLevel::Culled
+    }
+}
+
+fn assign_lods(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut low_sphere: Local<Option<Handle<Mesh>>>,
+    q_new: Query<(Entity, &Mesh3d, &EditableMesh), (With<Editable>, Without<Lod>)>,
+) {
+    for (e, mesh, info) in &q_new {
+        // Only round shapes have a meaningfully cheaper stand-in; boxy ones just get culled
+        let low = match info.kind {
+            SpawnKind::Sphere => Some(
+                low_sphere
+                    .get_or_insert_with(|| meshes.add(Sphere::new(0.5).mesh().uv(10, 6)))
+                    .clone(),
+            ),
+            _ => None,
+        };
+        commands.entity(e).insert(Lod {
+            full: mesh.0.clone(),
+            low,
+            level: LodLevel::Full,
+        });
+    }
+}
+
+/// Height of the world visible on screen at `point`.
+fn visible_height(cam_tf: &GlobalTransform, proj: &Projection, point: Vec3) -> f32 {
+    match proj {
+        Projection::Perspective(p) => {
+            let depth = (point - cam_tf.translation())
+                .dot(*cam_tf.forward())
+                .max(p.near);
+            2.0 * depth * (p.fov * 0.5).tan()
+        }
+        Projection::Orthographic(o) => o.area.height(),
+        _ => f32::INFINITY,
+    }
+}
+
+fn update_lods(
+    settings: Res<LodSettings>,
+    q_cam: Query<(&GlobalTransform, &Projection), With<OrbitCamera>>,
+    mut q_lod: Query<
+        (
+            &mut Lod,
+            &mut Mesh3d,
+            &mut Visibility,
+            &Aabb,
+            &GlobalTransform,
+            Has<Selected>,
+            Has<ViewHidden>,
+            Option<&Cutaway>,
+        ),
+        Without<BakedMember>,
+    >,
+) {
+    let Ok((cam_tf, proj)) = q_cam.single() else {
+        return;
+    };
+
+    for (mut lod, mut mesh, mut vis, aabb, tf, selected, view_hidden, cut) in &mut q_lod {
+        // A mesh swapped in by an edit (e.g. a staircase rebuild) is the new full-detail one
+        if mesh.is_changed() && lod.low.as_ref() != Some(&mesh.0) && lod.full != mesh.0 {
+            lod.full = mesh.0.clone();
+            lod.level = LodLevel::Full;
+        }
+        let (scale, _, _) = tf.to_scale_rotation_translation();
+        let size = (Vec3::from(aabb.half_extents) * scale.abs()).max_element() * 2.0;
+
+        // Selected objects always show at full detail so they can be edited
+        let wanted = if !settings.enabled || selected || size > settings.max_decor_size {
+            LodLevel::Full
+        } else {
+            let center = tf.transform_point(aabb.center.into());
+            let coverage = size / visible_height(cam_tf, proj, center);
+            if coverage < settings.cull_below {
+                LodLevel::Culled
+            } else if coverage < settings.low_detail_below && lod.low.is_some() {
+                LodLevel::Low
+            } else {
+                LodLevel::Full
+            }
+        };
+        // Something else (e.g. unbaking) may have shown a culled object again
+        let applied = wanted != LodLevel::Culled || *vis == Visibility::Hidden;
+        if wanted == lod.level && applied {
+            continue;
+        }
+
+        if wanted == LodLevel::Low {
+            if let Some(low) = &lod.low {
+                mesh.0 = low.clone();
+            }
+        } else if lod.level == LodLevel::Low {
+            // Only touch the mesh when leaving the low level; edits that swap it were picked up
+            // as the full mesh above
+            mesh.0 = lod.full.clone();
+        }
+        // Objects hidden from the current view (or cut away) stay hidden whatever their level
+        let hidden = view_hidden || cut.is_some_and(|c| c.original.is_none());
+        *vis = if wanted == LodLevel::Culled || hidden {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        };
+        lod.level = wanted;
+    }
+}
+
+/// egui panel: global LOD thresholds and how many objects they currently affect.
+fn lod_panel(
+    mut ctxs: EguiContexts,
+    mut settings: ResMut<LodSettings>,
+    edit_state: Res<SceneEditState>,
+    q_lod: Query<&Lod>,
+) {
+    if !edit_state.open {
+        return;
+    }
+
+    let Ok(ctx) = ctxs.ctx_mut() else {
+        return;
+    };
+    egui::Window::new("Level of detail")
+        .default_open(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.checkbox(&mut settings.enabled, "Reduce small decor");
+            ui.add(
+                egui::Slider::new(&mut settings.max_decor_size, 0.1..=5.0).text("Max decor size"),
+            );
+            ui.add(
+                egui::Slider::new(&mut settings.low_detail_below, 0.0..=0.2)
+                    .text("Low detail below (screen)"),
+            );
+            ui.add(
+                egui::Slider::new(&mut settings.cull_below, 0.0..=0.05).text("Hide below (screen)"),
+            );
+            if ui.button("Reset").clicked() {
+                *settings = LodSettings::default();
+            }
+
+            let (low, culled) = q_lod.iter().fold((0, 0), |(l, c), lod| match lod.level {
+                LodLevel::Full => (l, c),
+                LodLevel::Low => (l + 1, c),
+                LodLevel::Culled => (l, c + 1),
+            });
+            ui.label(format!("{low} low detail, {culled} hidden"));
+        });
+}
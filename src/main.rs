@@ -1,42 +1,14 @@
-#![allow(clippy::too_many_arguments)]
-#![allow(clippy::type_complexity)]
+use bevy::{math::primitives::Cuboid, pbr::NotShadowCaster, prelude::*};
 
-use bevy::{
-    diagnostic::FrameTimeDiagnosticsPlugin,
-    math::primitives::{Cuboid, Plane3d, Sphere},
-    pbr::NotShadowCaster,
-    prelude::*,
-    render::render_resource::Face,
-};
-use bevy_egui::{EguiPlugin, EguiPrimaryContextPass};
-use bevy_rapier3d::prelude::*;
-
-mod camera;
-mod inspector;
-mod player;
-mod post;
-
-use crate::camera::{CameraPlugin, OrbitSet};
-use crate::inspector::{Editable, EditableMesh, InspectorPlugin, SpawnKind};
-use crate::player::{Player, player_horizontal_velocity, player_motion_with_gravity, spawn_player};
-use crate::post::chroma_aberration::ChromaAberrationPlugin;
-use crate::post::crt::CRTPlugin;
-use crate::post::gradient_tint::GradientTintPlugin;
-use crate::post::lut::{LutPlugin, lut_apply_pending};
-use crate::post::outlines::{OutlineParams, OutlineShell, spawn_outlined, update_outlines};
-use crate::post::ui::{post_process_edit_panel, setup_fps_text, update_fps_text};
-
-/// Global UI state for toggling panels like the Inspector.
-#[derive(Resource)]
-pub struct SceneEditState {
-    pub open: bool,
-}
-
-impl Default for SceneEditState {
-    fn default() -> Self {
-        Self { open: true }
-    }
-}
+use tunable_world::TunableWorldPlugins;
+use tunable_world::asset_cache::{AssetCache, PlainMaterial};
+use tunable_world::benchmark::BenchmarkPlugin;
+use tunable_world::decals::{SceneDecal, spawn_decal_object};
+use tunable_world::fog_volumes::{SceneFogVolume, spawn_fog_volume_object};
+use tunable_world::inspector::{Editable, EditableMesh, SpawnKind};
+use tunable_world::lights::{AnimCurve, LightAnimMode, LightAnimation};
+use tunable_world::post::outlines::{OutlineParams, OutlineShell, spawn_outlined};
+use tunable_world::reflection::PlanarReflection;
 
 fn main() {
     App::new()
@@ -47,36 +19,9 @@ fn main() {
             }),
             ..default()
         }))
-        .add_plugins((
-            FrameTimeDiagnosticsPlugin::default(), // collects fps and frame time
-        ))
-        .add_plugins(RapierPhysicsPlugin::<NoUserData>::default())
-        .add_plugins(CameraPlugin)
-        .add_plugins(ChromaAberrationPlugin)
-        .add_plugins(CRTPlugin)
-        .add_plugins(GradientTintPlugin)
-        .add_plugins(LutPlugin)
-        // UI plugin (egui)
-        .add_plugins(EguiPlugin::default())
-        .add_plugins(InspectorPlugin)
-        .init_resource::<SceneEditState>()
+        .add_plugins(TunableWorldPlugins)
+        .add_plugins(BenchmarkPlugin::from_args())
         .add_systems(Startup, (spawn_light, spawn_scene))
-        .add_systems(PostStartup, setup_fps_text)
-        .add_systems(EguiPrimaryContextPass, post_process_edit_panel)
-        .configure_sets(Update, (OrbitSet::Input, OrbitSet::Pose).chain())
-        .add_systems(
-            Update,
-            (
-                update_outlines,
-                update_fps_text,
-                lut_apply_pending,
-                space_closes_scene_inspector,
-                enter_drops_player,
-                player_horizontal_velocity,
-                player_motion_with_gravity,
-                esc_quits_app,
-            ),
-        )
         .run();
 }
 
@@ -112,62 +57,45 @@ fn spawn_scene(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
+    mut cache: ResMut<AssetCache>,
+    outline: Res<OutlineParams>,
 ) {
-    // --- Palette (gentle pastels, mostly rough)
-    let grass_a = materials.add(StandardMaterial {
-        base_color: Color::srgb(126.0 / 255.0, 171.0 / 255.0, 139.0 / 255.0),
-        perceptual_roughness: 0.85,
-        metallic: 0.0,
-        ..default()
-    });
-    let grass_b = materials.add(StandardMaterial {
-        base_color: Color::srgb(0.58, 0.79, 0.64),
-        perceptual_roughness: 0.9,
-        ..default()
-    });
-    let dirt = materials.add(StandardMaterial {
-        base_color: Color::srgb(0.72, 0.64, 0.54),
-        perceptual_roughness: 0.95,
-        ..default()
-    });
-    let stone = materials.add(StandardMaterial {
-        base_color: Color::srgb(0.76, 0.78, 0.82),
-        perceptual_roughness: 0.8,
-        ..default()
-    });
+    // --- Palette (gentle pastels, mostly rough); shared with loaded/added objects of the same look
+    let mut plain = |base_color: Color, roughness: f32, emissive: LinearRgba| {
+        cache.material(
+            &mut materials,
+            PlainMaterial {
+                base_color,
+                metallic: 0.0,
+                roughness,
+                emissive,
+            },
+        )
+    };
+    let grass_a = plain(
+        Color::srgb(126.0 / 255.0, 171.0 / 255.0, 139.0 / 255.0),
+        0.85,
+        LinearRgba::BLACK,
+    );
+    let grass_b = plain(Color::srgb(0.58, 0.79, 0.64), 0.9, LinearRgba::BLACK);
+    let dirt = plain(Color::srgb(0.72, 0.64, 0.54), 0.95, LinearRgba::BLACK);
+    let stone = plain(Color::srgb(0.76, 0.78, 0.82), 0.8, LinearRgba::BLACK);
     // Emissive “glow” accent for the bloom to catch (keep base dark so bloom pops)
-    let crystal = materials.add(StandardMaterial {
-        base_color: Color::BLACK,
-        emissive: LinearRgba::from(Color::srgb(0.75, 0.95, 1.0)) * 2.5, // try 1.5–3.0
-        perceptual_roughness: 0.1,
-        metallic: 0.0,
-        ..default()
-    });
-
-    // Shared outline material (front-face culled so backfaces show; unlit for flat color)
-    let outline_color = Color::srgb(0.08, 0.10, 0.12);
-    let outline_material = materials.add(StandardMaterial {
-        base_color: outline_color,
-        unlit: true,
-        cull_mode: Some(Face::Front),
-        // keep depth test/write default so it hugs the mesh properly
-        ..default()
-    });
+    let crystal = plain(
+        Color::BLACK,
+        0.1,
+        LinearRgba::from(Color::srgb(0.75, 0.95, 1.0)) * 2.5, // try 1.5–3.0
+    );
 
-    // Make outline settings globally available (egui will edit these)
-    commands.insert_resource(OutlineParams {
-        enabled: true,
-        width: 0.02,
-        color: outline_color,
-        material: outline_material.clone(),
-    });
+    // Shared outline material, set up by the outlines plugin
+    let outline_material = outline.material.clone();
 
     // --- Mesh prims
-    let plane = meshes.add(Mesh::from(Plane3d::default()));
-    let step = meshes.add(Mesh::from(Cuboid::new(1.0, 1.0, 1.0)));
-    let slab = meshes.add(Mesh::from(Cuboid::new(1.0, 1.0, 1.0)));
-    let block = meshes.add(Mesh::from(Cuboid::new(1.0, 1.0, 1.0)));
-    let sphere = meshes.add(Mesh::from(Sphere::new(0.5)));
+    let plane = cache.mesh(&mut meshes, SpawnKind::Plane);
+    let step = cache.mesh(&mut meshes, SpawnKind::Cuboid);
+    let slab = step.clone();
+    let block = step.clone();
+    let sphere = cache.mesh(&mut meshes, SpawnKind::Sphere);
 
     // --- Base ground (big plane) – slightly tilted camera gives the “tabletop” feel
     commands.spawn((
@@ -254,7 +182,7 @@ fn spawn_scene(
     }
 
     // --- Emissive “crystal” on the mid terrace so bloom has a target
-    spawn_outlined(
+    let crystal = spawn_outlined(
         &mut commands,
         sphere.clone(),
         crystal,
@@ -264,6 +192,14 @@ fn spawn_scene(
         "Crystal",
         SpawnKind::Sphere,
     );
+    // Slow breathing glow
+    commands.entity(crystal).insert(LightAnimation {
+        mode: LightAnimMode::Pulse,
+        curve: AnimCurve::Smoothstep,
+        speed: 0.4,
+        amount: 0.5,
+        ..default()
+    });
 
     // --- A thin “water” slab (very light roughness so the sun sparkles a bit)
     let water = materials.add(StandardMaterial {
@@ -286,89 +222,40 @@ fn spawn_scene(
             kind: SpawnKind::Cuboid,
             collider: Some(true),
         },
+        // mirror the terraces in the water surface
+        PlanarReflection::default(),
         Name::new("Water"),
     ));
-}
-
-/// Hide the Scene Editor UI when Spacebar is pressed.
-fn space_closes_scene_inspector(kb: Res<ButtonInput<KeyCode>>, mut state: ResMut<SceneEditState>) {
-    if kb.just_pressed(KeyCode::Space) {
-        state.open = !state.open;
-    }
-}
-
-/// Quit the whole app on Escape.
-fn esc_quits_app(kb: Res<ButtonInput<KeyCode>>, mut exit: EventWriter<bevy::app::AppExit>) {
-    if kb.just_pressed(KeyCode::Escape) {
-        exit.write(bevy::app::AppExit::Success);
-    }
-}
-
-fn enter_drops_player(
-    mut commands: Commands,
-    kb: Res<ButtonInput<KeyCode>>,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
-    player_q: Query<Entity, With<Player>>,
-    object_q: Query<(Entity, &Transform, &EditableMesh), (With<Editable>, Without<Collider>)>,
-) {
-    if kb.just_pressed(KeyCode::Enter) {
-        if let Some(_p) = (&player_q).into_iter().next() {
-            // already have a player
-            return;
-        }
-
-        spawn_player(&mut commands, &mut meshes, &mut materials);
-        make_colliders(&mut commands, object_q);
-    }
-}
 
-fn make_colliders(
-    commands: &mut Commands,
-    object_q: Query<(Entity, &Transform, &EditableMesh), (With<Editable>, Without<Collider>)>,
-) {
-    for (e, tf, mesh_info) in object_q {
-        commands.entity(e).insert(match mesh_info.kind {
-            SpawnKind::Cuboid => (
-                Collider::cuboid(0.5, 0.5, 0.5),
-                ColliderScale::Absolute(Vec3::new(tf.scale.x, tf.scale.y, tf.scale.z)),
-            ),
-            SpawnKind::Sphere => (
-                Collider::ball(0.5),
-                ColliderScale::Absolute(Vec3::new(tf.scale.x, tf.scale.y, tf.scale.z)),
-            ),
-            SpawnKind::Plane => (
-                Collider::cuboid(0.5, 0.01, 0.5),
-                ColliderScale::Absolute(Vec3::new(tf.scale.x, tf.scale.y, tf.scale.z)),
-            ),
-            SpawnKind::Prism => {
-                let tri = [
-                    Vec2::new(0.0, 1.0),
-                    Vec2::new(0.0, 0.0),
-                    Vec2::new(1.0, 0.0),
-                ];
-                let depth = 1.0;
-                let z0 = -0.5 * depth; // assume Extrusion centers around Z=0
-                let z1 = 0.5 * depth;
-
-                // Build vertex cloud for the convex-hull (6 verts: two triangle caps)
-                let mut pts: Vec<Vec3> = Vec::with_capacity(6);
-                for &p in &tri {
-                    pts.push(Vec3::new(p.x, p.y, z0));
-                }
-                for &p in &tri {
-                    pts.push(Vec3::new(p.x, p.y, z1));
-                }
+    // --- Weathering decals on the terrace tops
+    let moss = spawn_decal_object(
+        &mut commands,
+        Transform::from_xyz(-3.2, 0.61, 2.0).with_scale(Vec3::new(1.8, 1.0, 1.8)),
+        SceneDecal {
+            texture: "decals/moss.png".into(),
+            ..default()
+        },
+    );
+    commands.entity(moss).insert(Name::new("MossDecal"));
+    let cracks = spawn_decal_object(
+        &mut commands,
+        Transform::from_xyz(0.4, 0.61, -1.6).with_scale(Vec3::new(1.4, 1.0, 1.4)),
+        SceneDecal {
+            texture: "decals/cracks.png".into(),
+            opacity: 0.8,
+            ..default()
+        },
+    );
+    commands.entity(cracks).insert(Name::new("CracksDecal"));
 
-                (
-                    Collider::convex_hull(&pts).unwrap(),
-                    ColliderScale::Absolute(Vec3::new(tf.scale.x, tf.scale.y, tf.scale.z)),
-                )
-            }
-            SpawnKind::ColliderBox => (
-                Collider::cuboid(0.5, 0.5, 0.5),
-                ColliderScale::Absolute(Vec3::new(tf.scale.x, tf.scale.y, tf.scale.z)),
-            ),
-        });
-    }
+    // --- Low mist pooling over the water (rendered in perspective view)
+    let mist = spawn_fog_volume_object(
+        &mut commands,
+        Transform::from_xyz(-2.7, 0.4, -2.3).with_scale(Vec3::new(5.0, 0.8, 3.5)),
+        SceneFogVolume {
+            density: 0.3,
+            ..default()
+        },
+    );
+    commands.entity(mist).insert(Name::new("Mist"));
 }
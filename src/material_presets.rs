@@ -0,0 +1,194 @@
+//! Quick material presets: a row of chips in the inspector that put a ready-made material on
+//! every selected object in one click. The built-in ones match the demo scene's palette; more
+//! are read from `materials.toml`, where a preset with a built-in's name replaces it:
+//!
+//! ```toml
+//! [[preset]]
+//! name = "Lava"
+//! color = [0.2, 0.05, 0.0]
+//! roughness = 0.7
+//! emissive = [3.0, 0.8, 0.1]
+//! ```
+
+use bevy::prelude::*;
+use bevy_egui::egui;
+use serde::{Deserialize, Serialize};
+
+use crate::accessibility::Described;
+use crate::asset_cache::PlainMaterial;
+use crate::scene_io;
+
+pub const MATERIALS_FILE: &str = "materials.toml";
+
+/// A named material, as written in `materials.toml`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MaterialPreset {
+    pub name: String,
+    /// sRGB
+    pub color: [f32; 3],
+    /// Below 1 the material blends with what's behind it
+    pub alpha: f32,
+    pub metallic: f32,
+    pub roughness: f32,
+    /// Linear RGB, above 1 for bloom to catch
+    pub emissive: [f32; 3],
+}
+
+impl Default for MaterialPreset {
+    fn default() -> Self {
+        Self {
+            name: "Preset".into(),
+            color: [0.82, 0.82, 0.86],
+            alpha: 1.0,
+            metallic: 0.0,
+            roughness: 0.6,
+            emissive: [0.0; 3],
+        }
+    }
+}
+
+impl MaterialPreset {
+    fn new(name: &str, color: [f32; 3], roughness: f32) -> Self {
+        Self {
+            name: name.into(),
+            color,
+            roughness,
+            ..default()
+        }
+    }
+
+    pub fn plain(&self) -> PlainMaterial {
+        let [r, g, b] = self.color;
+        let [er, eg, eb] = self.emissive;
+        PlainMaterial {
+            base_color: Color::srgba(r, g, b, self.alpha.clamp(0.0, 1.0)),
+            metallic: self.metallic,
+            roughness: self.roughness,
+            emissive: LinearRgba::rgb(er, eg, eb),
+        }
+    }
+
+    /// Chip color: the base color, lit up by any glow.
+    fn swatch(&self) -> egui::Color32 {
+        let glow = Color::from(LinearRgba::from_f32_array_no_alpha(self.emissive)).to_srgba();
+        let [r, g, b] = self.color;
+        let [r, g, b] = [r + glow.red, g + glow.green, b + glow.blue]
+            .map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8);
+        egui::Color32::from_rgb(r, g, b)
+    }
+}
+
+/// The demo scene's materials.
+pub fn builtin_presets() -> Vec<MaterialPreset> {
+    let crystal_glow = LinearRgba::from(Color::srgb(0.75, 0.95, 1.0)) * 2.5;
+    vec![
+        MaterialPreset::new(
+            "Grass A",
+            [126.0 / 255.0, 171.0 / 255.0, 139.0 / 255.0],
+            0.85,
+        ),
+        MaterialPreset::new("Grass B", [0.58, 0.79, 0.64], 0.9),
+        MaterialPreset::new("Dirt", [0.72, 0.64, 0.54], 0.95),
+        MaterialPreset::new("Stone", [0.76, 0.78, 0.82], 0.8),
+        MaterialPreset {
+            emissive: crystal_glow.to_f32_array_no_alpha(),
+            ..MaterialPreset::new("Crystal", [0.0; 3], 0.1)
+        },
+        MaterialPreset {
+            alpha: 0.8,
+            ..MaterialPreset::new("Water", [0.55, 0.85, 0.95], 0.05)
+        },
+    ]
+}
+
+/// The built-in presets followed by those from `materials.toml`.
+#[derive(Resource, Clone)]
+pub struct MaterialLibrary {
+    pub presets: Vec<MaterialPreset>,
+}
+
+impl Default for MaterialLibrary {
+    fn default() -> Self {
+        Self {
+            presets: builtin_presets(),
+        }
+    }
+}
+
+/// `materials.toml` as written.
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct LibraryFile {
+    preset: Vec<MaterialPreset>,
+}
+
+impl MaterialLibrary {
+    pub fn load(path: &str) -> Self {
+        let mut library = Self::default();
+        let Some(text) = scene_io::read_text(path) else {
+            return library;
+        };
+        match toml::from_str::<LibraryFile>(&text) {
+            Ok(file) => {
+                for preset in file.preset {
+                    match library.presets.iter_mut().find(|p| p.name == preset.name) {
+                        Some(builtin) => *builtin = preset,
+                        None => library.presets.push(preset),
+                    }
+                }
+            }
+            Err(e) => eprintln!("Materials: ignoring invalid {path}: {e}"),
+        }
+        library
+    }
+}
+
+pub struct MaterialPresetsPlugin;
+impl Plugin for MaterialPresetsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(MaterialLibrary::load(MATERIALS_FILE));
+    }
+}
+
+/// Inspector row with a chip per preset (and one to re-read the file). Returns the clicked
+/// preset.
+pub fn material_presets_ui(
+    ui: &mut egui::Ui,
+    library: &mut MaterialLibrary,
+) -> Option<MaterialPreset> {
+    let mut clicked = None;
+    ui.horizontal_wrapped(|ui| {
+        ui.label("Presets");
+        for (i, preset) in library.presets.iter().enumerate() {
+            let fill = preset.swatch();
+            let text = if fill.intensity() > 0.5 {
+                egui::Color32::BLACK
+            } else {
+                egui::Color32::WHITE
+            };
+            let chip = egui::Button::new(egui::RichText::new(&preset.name).color(text).small())
+                .fill(fill)
+                .corner_radius(8.0);
+            if ui
+                .add(chip)
+                .on_hover_text(format!(
+                    "Metallic {:.2}, roughness {:.2}",
+                    preset.metallic, preset.roughness
+                ))
+                .clicked()
+            {
+                clicked = Some(i);
+            }
+        }
+        if ui
+            .small_button("⟳")
+            .described(&format!("Reload {MATERIALS_FILE}"))
+            .clicked()
+        {
+            *library = MaterialLibrary::load(MATERIALS_FILE);
+            clicked = None;
+        }
+    });
+    clicked.and_then(|i| library.presets.get(i).cloned())
+}
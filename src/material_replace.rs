@@ -0,0 +1,227 @@
+//! Find and replace for materials: every object whose color is close to a given one gets a
+//! new material, or has its hue shifted, in one go, so palette changes don't mean editing
+//! objects one by one.
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+
+use crate::SceneEditState;
+use crate::asset_cache::{AssetCache, PlainMaterial};
+use crate::inspector::{Editable, SceneIoState, Selected};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ReplaceAction {
+    /// Color, metallic and roughness all replaced
+    Material,
+    /// Same material, hue turned
+    HueShift,
+}
+
+#[derive(Resource)]
+struct ReplaceSettings {
+    /// sRGB color to look for
+    find: [u8; 3],
+    /// Largest difference on any channel that still matches, 0..1
+    tolerance: f32,
+    action: ReplaceAction,
+    color: [u8; 3],
+    metallic: f32,
+    roughness: f32,
+    hue_deg: f32,
+}
+
+impl Default for ReplaceSettings {
+    fn default() -> Self {
+        Self {
+            find: [209, 209, 219],
+            tolerance: 0.05,
+            action: ReplaceAction::Material,
+            color: [209, 209, 219],
+            metallic: 0.0,
+            roughness: 0.6,
+            hue_deg: 30.0,
+        }
+    }
+}
+
+impl ReplaceSettings {
+    fn matches(&self, color: Color) -> bool {
+        let c = color.to_srgba();
+        let [r, g, b] = self.find.map(|v| v as f32 / 255.0);
+        (c.red - r)
+            .abs()
+            .max((c.green - g).abs())
+            .max((c.blue - b).abs())
+            <= self.tolerance
+    }
+
+    /// The replacement for a matching material.
+    fn replace(&self, mat: &StandardMaterial) -> PlainMaterial {
+        let mut plain = PlainMaterial {
+            base_color: mat.base_color,
+            metallic: mat.metallic,
+            roughness: mat.perceptual_roughness,
+            emissive: mat.emissive,
+        };
+        match self.action {
+            ReplaceAction::Material => {
+                let [r, g, b] = self.color;
+                let alpha = mat.base_color.alpha();
+                plain.base_color = Color::srgb_u8(r, g, b).with_alpha(alpha);
+                plain.metallic = self.metallic;
+                plain.roughness = self.roughness;
+            }
+            ReplaceAction::HueShift => {
+                let mut hsva = Hsva::from(mat.base_color);
+                hsva.hue = (hsva.hue + self.hue_deg).rem_euclid(360.0);
+                plain.base_color = hsva.into();
+            }
+        }
+        plain
+    }
+}
+
+fn srgb_u8(color: Color) -> [u8; 3] {
+    let [r, g, b, _] = color.to_srgba().to_u8_array();
+    [r, g, b]
+}
+
+pub struct MaterialReplacePlugin;
+impl Plugin for MaterialReplacePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ReplaceSettings>()
+            .init_resource::<AssetCache>()
+            .add_systems(EguiPrimaryContextPass, material_replace_window);
+    }
+}
+
+fn material_replace_window(
+    mut ctxs: EguiContexts,
+    edit_state: Res<SceneEditState>,
+    mut settings: ResMut<ReplaceSettings>,
+    mut commands: Commands,
+    mut cache: ResMut<AssetCache>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut io: Option<ResMut<SceneIoState>>,
+    q_objects: Query<(Entity, &MeshMaterial3d<StandardMaterial>, Has<Selected>), With<Editable>>,
+) {
+    if !edit_state.open {
+        return;
+    }
+    let matching: Vec<(Entity, &Handle<StandardMaterial>)> = q_objects
+        .iter()
+        .filter(|(_, mat, _)| {
+            materials
+                .get(&mat.0)
+                .is_some_and(|m| settings.matches(m.base_color))
+        })
+        .map(|(e, mat, _)| (e, &mat.0))
+        .collect();
+    let selected_color = q_objects
+        .iter()
+        .find(|(.., selected)| *selected)
+        .and_then(|(_, mat, _)| materials.get(&mat.0))
+        .map(|m| (m.base_color, m.metallic, m.perceptual_roughness));
+
+    let mut apply = false;
+    let Ok(ctx) = ctxs.ctx_mut() else {
+        return;
+    };
+    egui::Window::new("Find & replace material")
+        .default_open(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            let settings = &mut *settings;
+            ui.horizontal(|ui| {
+                ui.label("Find");
+                ui.color_edit_button_srgb(&mut settings.find);
+                if ui
+                    .add_enabled(
+                        selected_color.is_some(),
+                        egui::Button::new("From selection"),
+                    )
+                    .clicked()
+                    && let Some((color, ..)) = selected_color
+                {
+                    settings.find = srgb_u8(color);
+                }
+            });
+            ui.add(egui::Slider::new(&mut settings.tolerance, 0.0..=0.5).text("Tolerance"));
+            ui.weak(match matching.len() {
+                0 => "No objects match".to_string(),
+                1 => "1 object matches".to_string(),
+                n => format!("{n} objects match"),
+            });
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.selectable_value(&mut settings.action, ReplaceAction::Material, "Replace");
+                ui.selectable_value(&mut settings.action, ReplaceAction::HueShift, "Shift hue");
+            });
+            match settings.action {
+                ReplaceAction::Material => {
+                    ui.horizontal(|ui| {
+                        ui.label("With");
+                        ui.color_edit_button_srgb(&mut settings.color);
+                        if ui
+                            .add_enabled(
+                                selected_color.is_some(),
+                                egui::Button::new("From selection"),
+                            )
+                            .clicked()
+                            && let Some((color, metallic, roughness)) = selected_color
+                        {
+                            settings.color = srgb_u8(color);
+                            settings.metallic = metallic;
+                            settings.roughness = roughness;
+                        }
+                    });
+                    ui.add(egui::Slider::new(&mut settings.metallic, 0.0..=1.0).text("Metallic"));
+                    ui.add(egui::Slider::new(&mut settings.roughness, 0.0..=1.0).text("Roughness"));
+                }
+                ReplaceAction::HueShift => {
+                    ui.add(egui::Slider::new(&mut settings.hue_deg, -180.0..=180.0).text("Hue °"));
+                }
+            }
+            apply = ui
+                .add_enabled(!matching.is_empty(), egui::Button::new("Apply"))
+                .clicked();
+        });
+    if !apply {
+        return;
+    }
+
+    // Each distinct material is worked out once, then handed to everything using it
+    let mut users: HashMap<AssetId<StandardMaterial>, (Handle<StandardMaterial>, Vec<Entity>)> =
+        HashMap::new();
+    for (e, handle) in matching {
+        users
+            .entry(handle.id())
+            .or_insert_with(|| (handle.clone(), Vec::new()))
+            .1
+            .push(e);
+    }
+    for (handle, entities) in users.into_values() {
+        let Some(plain) = materials.get(&handle).map(|m| settings.replace(m)) else {
+            continue;
+        };
+        if cache.is_shared_material(&handle) {
+            // Copy-on-write: move the objects over to the (shared) replacement
+            let replacement = cache.material(&mut materials, plain);
+            for e in entities {
+                commands
+                    .entity(e)
+                    .insert(MeshMaterial3d(replacement.clone()));
+            }
+        } else if let Some(mat) = materials.get_mut(&handle) {
+            mat.base_color = plain.base_color;
+            mat.metallic = plain.metallic;
+            mat.perceptual_roughness = plain.roughness;
+        }
+    }
+    if let Some(io) = io.as_mut() {
+        io.mark_edited();
+    }
+}
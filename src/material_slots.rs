@@ -0,0 +1,158 @@
+//! Objects made of several mesh primitives, each with its own material (one child entity per
+//! primitive). The inspector lists every slot so each can be edited or swapped on its own;
+//! objects with a single material keep using the main color and material controls. Slot
+//! edits only last for the session: scenes save one material per object.
+
+use bevy::prelude::*;
+use bevy_egui::egui;
+
+use crate::asset_cache::AssetCache;
+use crate::post::debug_view::OverdrawProxy;
+use crate::post::outlines::OutlineShell;
+use crate::splines::SplineGenerated;
+
+/// Children the editor adds to objects (outline shells, spline segments, debug view copies)
+/// rather than parts of the object.
+pub type SlotFilter = (
+    Without<OutlineShell>,
+    Without<SplineGenerated>,
+    Without<OverdrawProxy>,
+);
+
+/// One primitive's material under the selected object.
+pub struct MaterialSlot {
+    pub entity: Entity,
+    pub label: String,
+    pub material: Handle<StandardMaterial>,
+}
+
+/// The object's own material (if it has one) followed by those of its descendants.
+pub fn material_slots(
+    entity: Entity,
+    q_children: &Query<&Children>,
+    q_slots: &Query<(Option<&Name>, &MeshMaterial3d<StandardMaterial>), SlotFilter>,
+) -> Vec<MaterialSlot> {
+    std::iter::once(entity)
+        .chain(q_children.iter_descendants(entity))
+        .filter_map(|e| {
+            let (name, mat) = q_slots.get(e).ok()?;
+            Some((e, name, mat.0.clone()))
+        })
+        .enumerate()
+        .map(|(i, (e, name, material))| MaterialSlot {
+            entity: e,
+            label: name.map_or_else(|| format!("Slot {i}"), |n| n.to_string()),
+            material,
+        })
+        .collect()
+}
+
+/// Give the slot a material of its own if it shares a cached one. Returns the handle to edit.
+fn own_material(
+    commands: &mut Commands,
+    cache: &AssetCache,
+    materials: &mut Assets<StandardMaterial>,
+    slot: &MaterialSlot,
+) -> Option<Handle<StandardMaterial>> {
+    if !cache.is_shared_material(&slot.material) {
+        return Some(slot.material.clone());
+    }
+    let copy = materials.get(&slot.material)?.clone();
+    let copy = materials.add(copy);
+    commands
+        .entity(slot.entity)
+        .insert(MeshMaterial3d(copy.clone()));
+    Some(copy)
+}
+
+/// Inspector section with color, metallic and roughness per slot, and a way to replace a slot's
+/// material with a fresh one or a copy of another slot's.
+pub fn material_slots_inspector_ui(
+    ui: &mut egui::Ui,
+    commands: &mut Commands,
+    cache: &AssetCache,
+    materials: &mut Assets<StandardMaterial>,
+    slots: &[MaterialSlot],
+) {
+    egui::CollapsingHeader::new(format!("Material slots ({})", slots.len()))
+        .default_open(true)
+        .show(ui, |ui| {
+            for (i, slot) in slots.iter().enumerate() {
+                let Some(mat) = materials.get(&slot.material) else {
+                    continue;
+                };
+                let srgba = mat.base_color.to_srgba().to_u8_array();
+                let mut color =
+                    egui::Color32::from_rgba_unmultiplied(srgba[0], srgba[1], srgba[2], srgba[3]);
+                let (mut metallic, mut roughness) = (mat.metallic, mat.perceptual_roughness);
+
+                let mut edited = false;
+                let mut replace = None;
+                ui.push_id(i, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.strong(&slot.label);
+                        edited |= egui::color_picker::color_edit_button_srgba(
+                            ui,
+                            &mut color,
+                            egui::color_picker::Alpha::OnlyBlend,
+                        )
+                        .changed();
+                        egui::ComboBox::from_id_salt("replace")
+                            .selected_text("Replace…")
+                            .show_ui(ui, |ui| {
+                                if ui.selectable_label(false, "New material").clicked() {
+                                    replace = Some(None);
+                                }
+                                for (j, other) in slots.iter().enumerate() {
+                                    if j != i
+                                        && other.material != slot.material
+                                        && ui
+                                            .selectable_label(
+                                                false,
+                                                format!("Copy of {}", other.label),
+                                            )
+                                            .clicked()
+                                    {
+                                        replace = Some(materials.get(&other.material).cloned());
+                                    }
+                                }
+                            });
+                    });
+                    ui.horizontal(|ui| {
+                        edited |= ui
+                            .add(egui::Slider::new(&mut metallic, 0.0..=1.0).text("Metallic"))
+                            .changed();
+                        edited |= ui
+                            .add(egui::Slider::new(&mut roughness, 0.0..=1.0).text("Roughness"))
+                            .changed();
+                    });
+                });
+
+                if let Some(replacement) = replace {
+                    let handle = materials.add(replacement.unwrap_or_else(|| StandardMaterial {
+                        base_color: Color::srgb(0.82, 0.82, 0.86),
+                        perceptual_roughness: 0.6,
+                        ..default()
+                    }));
+                    commands.entity(slot.entity).insert(MeshMaterial3d(handle));
+                    continue;
+                }
+                if !edited {
+                    continue;
+                }
+                let Some(handle) = own_material(commands, cache, materials, slot) else {
+                    continue;
+                };
+                if let Some(mat) = materials.get_mut(&handle) {
+                    let [r, g, b, a] = color.to_srgba_unmultiplied();
+                    mat.base_color = Color::srgba_u8(r, g, b, a);
+                    mat.metallic = metallic;
+                    mat.perceptual_roughness = roughness;
+                    // Leave masked and other imported alpha modes alone
+                    if a < 255 && mat.alpha_mode == AlphaMode::Opaque {
+                        mat.alpha_mode = AlphaMode::Blend;
+                    }
+                }
+            }
+        });
+}
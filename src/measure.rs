@@ -0,0 +1,169 @@
+use bevy::picking::mesh_picking::ray_cast::{MeshRayCast, MeshRayCastSettings};
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+
+use crate::SceneEditState;
+use crate::camera::{OrbitCamera, UiPointer};
+use crate::editor_mode::EditorMode;
+use crate::grid::EditorGrid;
+
+const MEASURE_COLOR: Color = Color::srgb(1.0, 0.85, 0.2);
+const PROBE_SIZE: f32 = 0.15;
+
+/// One finished measurement, kept on screen until removed.
+#[derive(Clone, Copy)]
+pub struct Measurement {
+    pub from: Vec3,
+    pub to: Vec3,
+}
+
+impl Measurement {
+    pub fn delta(&self) -> Vec3 {
+        self.to - self.from
+    }
+
+    /// Slope above (or below) the horizontal, in degrees
+    pub fn angle_deg(&self) -> f32 {
+        let d = self.delta();
+        d.y.atan2(d.xz().length()).to_degrees()
+    }
+}
+
+/// Measurements taken in Measure mode, and the first point of the one in progress.
+#[derive(Resource, Default)]
+pub struct Measurements {
+    pub pending: Option<Vec3>,
+    pub done: Vec<Measurement>,
+    /// Scene point under the cursor (the crosshair)
+    probe: Option<Vec3>,
+}
+
+/// Measure mode: click two points on the scene to get their distance, per-axis delta and slope.
+/// Right click drops the point in progress, or else the latest measurement.
+pub struct MeasurePlugin;
+impl Plugin for MeasurePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EditorMode>()
+            .init_resource::<Measurements>()
+            .add_systems(Update, (measure_on_click, draw_measurements).chain())
+            .add_systems(EguiPrimaryContextPass, measurement_labels);
+    }
+}
+
+fn measure_on_click(
+    mode: Res<EditorMode>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    mut ui_pointer: UiPointer,
+    q_window: Query<&Window, With<PrimaryWindow>>,
+    q_cam: Query<(&Camera, &GlobalTransform), With<OrbitCamera>>,
+    mut ray_cast: MeshRayCast,
+    q_grid: Query<(), With<EditorGrid>>,
+    mut measurements: ResMut<Measurements>,
+) {
+    if *mode != EditorMode::Measure {
+        measurements.probe = None;
+        return;
+    }
+    let over_ui = ui_pointer.wants_pointer_in_primary();
+    let cursor = q_window.single().ok().and_then(|w| w.cursor_position());
+    let (Some(cursor), Ok((camera, cam_gt)), false) = (cursor, q_cam.single(), over_ui) else {
+        measurements.probe = None;
+        return;
+    };
+    let Ok(ray) = camera.viewport_to_world(cam_gt, cursor) else {
+        return;
+    };
+
+    let probe = ray_cast
+        .cast_ray(
+            ray,
+            &MeshRayCastSettings::default().with_filter(&|e| !q_grid.contains(e)),
+        )
+        .first()
+        .map(|(_, hit)| hit.point);
+    measurements.probe = probe;
+
+    if mouse.just_pressed(MouseButton::Right) && measurements.pending.take().is_none() {
+        measurements.done.pop();
+    }
+    if !mouse.just_pressed(MouseButton::Left) {
+        return;
+    }
+    let Some(point) = probe else {
+        return;
+    };
+    match measurements.pending.take() {
+        None => measurements.pending = Some(point),
+        Some(from) => measurements.done.push(Measurement { from, to: point }),
+    }
+}
+
+fn cross(gizmos: &mut Gizmos, p: Vec3, color: Color) {
+    for axis in [Vec3::X, Vec3::Y, Vec3::Z] {
+        gizmos.line(p - axis * PROBE_SIZE, p + axis * PROBE_SIZE, color);
+    }
+}
+
+fn draw_measurements(measurements: Res<Measurements>, mut gizmos: Gizmos) {
+    for m in &measurements.done {
+        gizmos.line(m.from, m.to, MEASURE_COLOR);
+        // Per-axis legs: X, then Z, then Y
+        let d = m.delta();
+        let a = m.from + Vec3::X * d.x;
+        let b = a + Vec3::Z * d.z;
+        gizmos.line(m.from, a, Color::srgba(1.0, 0.2, 0.2, 0.6));
+        gizmos.line(a, b, Color::srgba(0.2, 0.4, 1.0, 0.6));
+        gizmos.line(b, m.to, Color::srgba(0.2, 1.0, 0.2, 0.6));
+        cross(&mut gizmos, m.from, MEASURE_COLOR);
+        cross(&mut gizmos, m.to, MEASURE_COLOR);
+    }
+    if let Some(from) = measurements.pending {
+        cross(&mut gizmos, from, MEASURE_COLOR);
+        if let Some(probe) = measurements.probe {
+            gizmos.line(from, probe, MEASURE_COLOR.with_alpha(0.5));
+        }
+    }
+    if let Some(probe) = measurements.probe {
+        cross(&mut gizmos, probe, Color::WHITE);
+    }
+}
+
+/// Distance, delta and slope next to each measurement's midpoint.
+fn measurement_labels(
+    mut ctxs: EguiContexts,
+    edit_state: Res<SceneEditState>,
+    measurements: Res<Measurements>,
+    q_window: Query<&Window, With<PrimaryWindow>>,
+    q_cam: Query<(&Camera, &GlobalTransform), With<OrbitCamera>>,
+) {
+    if !edit_state.open || measurements.done.is_empty() {
+        return;
+    }
+    let (Ok(ctx), Ok(window), Ok((camera, cam_gt))) =
+        (ctxs.ctx_mut(), q_window.single(), q_cam.single())
+    else {
+        return;
+    };
+    // Viewport positions are in logical window pixels (docked panels included); egui points
+    // also include the UI scale
+    let to_points = window.scale_factor() / ctx.pixels_per_point();
+
+    for (i, m) in measurements.done.iter().enumerate() {
+        let Ok(pos) = camera.world_to_viewport(cam_gt, m.from.lerp(m.to, 0.5)) else {
+            continue;
+        };
+        let pos = pos * to_points;
+        let d = m.delta();
+        egui::Area::new(egui::Id::new(("measurement", i)))
+            .fixed_pos(egui::pos2(pos.x, pos.y))
+            .interactable(false)
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.monospace(format!("{:.2} m", d.length()));
+                    ui.monospace(format!("Δ {:.2} {:.2} {:.2}", d.x, d.y, d.z));
+                    ui.monospace(format!("∠ {:.1}°", m.angle_deg()));
+                });
+            });
+    }
+}
@@ -0,0 +1,188 @@
+//! Arithmetic typed into the inspector's number fields: `1.5+0.6`, `90/4`, `-(2 - 0.25) * 3`.
+//!
+//! `u` stands for the field's snap unit (the move, rotate or scale step), alone or after a
+//! number, so `3u + 0.1` is three grid steps and a bit whatever the grid size is.
+
+/// Deepest nesting of signs and parentheses taken; anything deeper (pasted junk) is rejected
+/// instead of overflowing the stack.
+const MAX_DEPTH: usize = 64;
+
+/// Value of `text`, or `None` if it isn't a well-formed expression.
+pub fn eval(text: &str, unit: f64) -> Option<f64> {
+    let chars: Vec<char> = text
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        // egui's own parser accepts the typographic minus, so keep doing that
+        .map(|c| if c == '−' { '-' } else { c })
+        .collect();
+    let mut parser = Parser {
+        chars: &chars,
+        pos: 0,
+        unit,
+        depth: 0,
+    };
+    let value = parser.expr()?;
+    (parser.pos == chars.len() && value.is_finite()).then_some(value)
+}
+
+struct Parser<'a> {
+    chars: &'a [char],
+    pos: usize,
+    unit: f64,
+    /// Factors being parsed, one inside the other
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        let found = self.peek() == Some(c);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    /// term (('+' | '-') term)*
+    fn expr(&mut self) -> Option<f64> {
+        let mut value = self.term()?;
+        loop {
+            if self.eat('+') {
+                value += self.term()?;
+            } else if self.eat('-') {
+                value -= self.term()?;
+            } else {
+                return Some(value);
+            }
+        }
+    }
+
+    /// factor (('*' | '/') factor)*
+    fn term(&mut self) -> Option<f64> {
+        let mut value = self.factor()?;
+        loop {
+            if self.eat('*') {
+                value *= self.factor()?;
+            } else if self.eat('/') {
+                value /= self.factor()?;
+            } else {
+                return Some(value);
+            }
+        }
+    }
+
+    /// ('-' | '+') factor | '(' expr ')' | number ['u'] | 'u'
+    fn factor(&mut self) -> Option<f64> {
+        if self.depth == MAX_DEPTH {
+            return None;
+        }
+        self.depth += 1;
+        let value = self.signed();
+        self.depth -= 1;
+        value
+    }
+
+    fn signed(&mut self) -> Option<f64> {
+        if self.eat('-') {
+            return Some(-self.factor()?);
+        }
+        if self.eat('+') {
+            return self.factor();
+        }
+        if self.eat('(') {
+            let value = self.expr()?;
+            return self.eat(')').then_some(value);
+        }
+        if self.eat('u') {
+            return Some(self.unit);
+        }
+
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_ascii_digit() || c == '.') {
+            self.pos += 1;
+        }
+        // Exponents, as egui's parser takes them: 1e-3
+        if self.pos > start && matches!(self.peek(), Some('e' | 'E')) {
+            let mark = self.pos;
+            self.pos += 1;
+            if matches!(self.peek(), Some('+' | '-')) {
+                self.pos += 1;
+            }
+            let digits = self.pos;
+            while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+                self.pos += 1;
+            }
+            if self.pos == digits {
+                self.pos = mark;
+            }
+        }
+        let number: String = self.chars[start..self.pos].iter().collect();
+        let value: f64 = number.parse().ok()?;
+        Some(if self.eat('u') {
+            value * self.unit
+        } else {
+            value
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn products_before_sums() {
+        assert_eq!(eval("1+2*3", 1.0), Some(7.0));
+        assert_eq!(eval("8-4/2", 1.0), Some(6.0));
+        assert_eq!(eval("2 * 3 - 4 * 0.5", 1.0), Some(4.0));
+    }
+
+    #[test]
+    fn unary_signs() {
+        assert_eq!(eval("-3", 1.0), Some(-3.0));
+        assert_eq!(eval("--3", 1.0), Some(3.0));
+        assert_eq!(eval("2*-3", 1.0), Some(-6.0));
+        assert_eq!(eval("+1.5", 1.0), Some(1.5));
+        assert_eq!(eval("−2", 1.0), Some(-2.0));
+    }
+
+    #[test]
+    fn parentheses_group() {
+        assert_eq!(eval("(1+2)*3", 1.0), Some(9.0));
+        assert_eq!(eval("-(2 - 0.25) * 3", 1.0), Some(-5.25));
+        assert_eq!(eval("((4))", 1.0), Some(4.0));
+    }
+
+    #[test]
+    fn units_and_exponents() {
+        assert_eq!(eval("3u + 1", 0.5), Some(2.5));
+        assert_eq!(eval("u/2", 15.0), Some(7.5));
+        assert_eq!(eval("1e-3", 1.0), Some(0.001));
+    }
+
+    #[test]
+    fn division_by_zero_is_rejected() {
+        assert_eq!(eval("1/0", 1.0), None);
+        assert_eq!(eval("0/0", 1.0), None);
+    }
+
+    #[test]
+    fn garbage_is_rejected() {
+        for text in ["", "abc", "1+", "(1", "1)", "2**3", "1..2", "e5", "1u2"] {
+            assert_eq!(eval(text, 1.0), None, "{text:?}");
+        }
+    }
+
+    #[test]
+    fn deep_nesting_is_rejected_not_overflowed() {
+        let minuses = format!("{}1", "-".repeat(100_000));
+        assert_eq!(eval(&minuses, 1.0), None);
+        let parens = format!("{}1{}", "(".repeat(100_000), ")".repeat(100_000));
+        assert_eq!(eval(&parens, 1.0), None);
+        // Well within the limit is fine
+        assert_eq!(eval(&format!("{}1", "-".repeat(10)), 1.0), Some(1.0));
+    }
+}
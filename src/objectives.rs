@@ -0,0 +1,435 @@
+//! Objectives: an ordered list of things to do in a scene, authored in the **Objectives** window
+//! and saved with the scene. In Play mode a tracker lists them and only the current one can be
+//! completed:
+//!
+//! - *Reach* an object, by name (the player touches it)
+//! - *Collect* a number of items: objects with a `collectible` custom property (`gem`, say)
+//!   vanish when a player touches them and count towards every objective for that tag
+//! - *Activate* an object, by name, with the interact key while standing next to it
+//!
+//! Each completion sends an [`ObjectiveCompleted`]. While a scene has objectives its `Goal`
+//! objects stay shut until they're all done, and "Completing them finishes the level" makes the
+//! last one count as reaching the goal.
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use bevy::render::primitives::Aabb;
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+use bevy_rapier3d::prelude::{ColliderDisabled, KinematicCharacterController};
+use serde::{Deserialize, Serialize};
+
+use crate::SceneEditState;
+use crate::accessibility::Described;
+use crate::custom_props::CustomProperties;
+use crate::editor_mode::EditorMode;
+use crate::inspector::{Editable, aabb_world, obb_to_world_aabb};
+use crate::settings::{KeyBindings, key_label};
+use crate::toasts::Toasts;
+
+/// Custom property tagging an object as an item to collect
+const COLLECTIBLE_KEY: &str = "collectible";
+/// How close the player's center has to come to an object's bounds to touch it
+const TOUCH_REACH: f32 = 0.3;
+/// How far from an object's bounds a player can activate it
+const ACTIVATE_REACH: f32 = 1.2;
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub enum Condition {
+    /// Touch the object with this name
+    Reach { object: String },
+    /// Pick up `count` objects whose `collectible` property is `tag`
+    Collect { tag: String, count: u32 },
+    /// Press interact next to the object with this name
+    Activate { object: String },
+}
+
+impl Condition {
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Reach { .. } => "Reach",
+            Self::Collect { .. } => "Collect",
+            Self::Activate { .. } => "Activate",
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Objective {
+    /// Shown in the tracker; described from the condition when empty
+    #[serde(default)]
+    pub text: String,
+    pub condition: Condition,
+}
+
+impl Objective {
+    fn describe(&self) -> String {
+        if !self.text.trim().is_empty() {
+            return self.text.trim().to_string();
+        }
+        match &self.condition {
+            Condition::Reach { object } => format!("Reach {object}"),
+            Condition::Collect { tag, count } => format!("Collect {count} × {tag}"),
+            Condition::Activate { object } => format!("Activate {object}"),
+        }
+    }
+}
+
+/// The scene's objectives, saved with the scene.
+#[derive(Resource, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Objectives {
+    pub list: Vec<Objective>,
+    /// Completing the last one counts as reaching the goal
+    pub finish_level: bool,
+}
+
+impl Objectives {
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+}
+
+/// How far the current play session has got.
+#[derive(Resource, Default)]
+pub struct ObjectiveProgress {
+    /// Index of the objective being worked on
+    current: usize,
+    /// Items picked up so far, by tag
+    collected: HashMap<String, u32>,
+    complete: bool,
+}
+
+impl ObjectiveProgress {
+    /// Every objective is done (or there are none).
+    pub fn is_complete(&self, objectives: &Objectives) -> bool {
+        self.complete || objectives.is_empty()
+    }
+}
+
+/// An objective was completed in Play mode.
+#[derive(Event, Clone, Debug)]
+pub struct ObjectiveCompleted {
+    pub index: usize,
+    /// It was the last one
+    pub last: bool,
+}
+
+/// A picked-up item, hidden until Play ends.
+#[derive(Component)]
+struct Collected {
+    visibility: Visibility,
+}
+
+pub struct ObjectivesPlugin;
+impl Plugin for ObjectivesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Objectives>()
+            .init_resource::<ObjectiveProgress>()
+            .init_resource::<EditorMode>()
+            .init_resource::<KeyBindings>()
+            .init_resource::<Toasts>()
+            .add_event::<ObjectiveCompleted>()
+            .add_systems(
+                Update,
+                (reset_objectives, collect_items, advance_objectives).chain(),
+            )
+            .add_systems(
+                EguiPrimaryContextPass,
+                (objectives_window, objective_tracker),
+            );
+    }
+}
+
+/// World bounds, grown by `reach`; collider boxes have no mesh, so a unit cube scaled by the
+/// transform.
+fn touches(gt: &GlobalTransform, aabb: Option<&Aabb>, reach: f32, at: Vec3) -> bool {
+    let bounds = match aabb {
+        Some(aabb) => aabb_world(*aabb, gt),
+        None => obb_to_world_aabb(gt.compute_transform(), Vec3::ONE),
+    };
+    let half = bounds.half_extents + Vec3A::splat(reach);
+    ((Vec3A::from(at) - bounds.center).abs() - half).max_element() <= 0.0
+}
+
+/// Start over each time Play starts or ends, putting picked-up items back.
+fn reset_objectives(
+    mut commands: Commands,
+    mode: Res<EditorMode>,
+    mut progress: ResMut<ObjectiveProgress>,
+    q_collected: Query<(Entity, &Collected)>,
+) {
+    if !mode.is_changed() {
+        return;
+    }
+    *progress = ObjectiveProgress::default();
+    for (e, collected) in &q_collected {
+        commands
+            .entity(e)
+            .insert(collected.visibility)
+            .remove::<(Collected, ColliderDisabled)>();
+    }
+}
+
+/// Pick up collectibles the players touch.
+fn collect_items(
+    mut commands: Commands,
+    mode: Res<EditorMode>,
+    mut progress: ResMut<ObjectiveProgress>,
+    q_players: Query<&GlobalTransform, With<KinematicCharacterController>>,
+    q_items: Query<
+        (
+            Entity,
+            &CustomProperties,
+            &GlobalTransform,
+            Option<&Aabb>,
+            &Visibility,
+        ),
+        (With<Editable>, Without<Collected>),
+    >,
+) {
+    if *mode != EditorMode::Play {
+        return;
+    }
+    for (e, props, gt, aabb, vis) in &q_items {
+        let Some(tag) = props.get(COLLECTIBLE_KEY) else {
+            continue;
+        };
+        if q_players
+            .iter()
+            .any(|p| touches(gt, aabb, TOUCH_REACH, p.translation()))
+        {
+            *progress.collected.entry(tag.to_string()).or_default() += 1;
+            commands.entity(e).insert((
+                Collected { visibility: *vis },
+                Visibility::Hidden,
+                ColliderDisabled,
+            ));
+        }
+    }
+}
+
+/// Complete the current objective once its condition holds, and any after it that already do.
+fn advance_objectives(
+    mode: Res<EditorMode>,
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    q_pads: Query<&Gamepad>,
+    objectives: Res<Objectives>,
+    mut progress: ResMut<ObjectiveProgress>,
+    mut toasts: ResMut<Toasts>,
+    mut ev_done: EventWriter<ObjectiveCompleted>,
+    q_players: Query<&GlobalTransform, With<KinematicCharacterController>>,
+    q_objects: Query<(&Name, &GlobalTransform, Option<&Aabb>), With<Editable>>,
+) {
+    if *mode != EditorMode::Play || progress.complete || objectives.is_empty() {
+        return;
+    }
+    let interact = keys.just_pressed(bindings.interact)
+        || q_pads
+            .iter()
+            .any(|pad| pad.just_pressed(GamepadButton::South));
+    let near = |object: &str, reach: f32| {
+        q_objects
+            .iter()
+            .filter(|(name, ..)| name.as_str() == object)
+            .any(|(_, gt, aabb)| {
+                q_players
+                    .iter()
+                    .any(|p| touches(gt, aabb, reach, p.translation()))
+            })
+    };
+
+    while let Some(objective) = objectives.list.get(progress.current) {
+        let met = match &objective.condition {
+            Condition::Reach { object } => near(object, TOUCH_REACH),
+            Condition::Collect { tag, count } => {
+                progress.collected.get(tag).copied().unwrap_or(0) >= *count
+            }
+            Condition::Activate { object } => interact && near(object, ACTIVATE_REACH),
+        };
+        if !met {
+            return;
+        }
+        let index = progress.current;
+        progress.current += 1;
+        let last = progress.current == objectives.list.len();
+        progress.complete = last;
+        ev_done.write(ObjectiveCompleted { index, last });
+        toasts.info(if last {
+            "All objectives complete!".to_string()
+        } else {
+            format!("✔ {}", objective.describe())
+        });
+    }
+}
+
+/// In Play mode: what's done, what's next and how far along it is.
+fn objective_tracker(
+    mut ctxs: EguiContexts,
+    mode: Res<EditorMode>,
+    objectives: Res<Objectives>,
+    progress: Res<ObjectiveProgress>,
+) {
+    if *mode != EditorMode::Play || objectives.is_empty() {
+        return;
+    }
+    let Ok(ctx) = ctxs.ctx_mut() else {
+        return;
+    };
+    egui::Area::new(egui::Id::new("objective_tracker"))
+        .anchor(egui::Align2::RIGHT_TOP, egui::vec2(-12.0, 12.0))
+        .order(egui::Order::Foreground)
+        .interactable(false)
+        .show(ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.strong("Objectives");
+                for (i, objective) in objectives.list.iter().enumerate() {
+                    let mut text = objective.describe();
+                    if let Condition::Collect { tag, count } = &objective.condition {
+                        let got = progress.collected.get(tag).copied().unwrap_or(0);
+                        text = format!("{text} ({}/{count})", got.min(*count));
+                    }
+                    if i < progress.current {
+                        ui.weak(format!("✔ {text}"));
+                    } else if i == progress.current {
+                        ui.label(egui::RichText::new(format!("▸ {text}")).strong());
+                    } else {
+                        ui.weak(format!("   {text}"));
+                    }
+                }
+            });
+        });
+}
+
+/// Author the scene's objectives.
+fn objectives_window(
+    mut ctxs: EguiContexts,
+    edit_state: Res<SceneEditState>,
+    bindings: Res<KeyBindings>,
+    mut objectives: ResMut<Objectives>,
+) {
+    if !edit_state.open {
+        return;
+    }
+    let Ok(ctx) = ctxs.ctx_mut() else {
+        return;
+    };
+    let mut edited = objectives.clone();
+    egui::Window::new("Objectives")
+        .default_open(false)
+        .show(ctx, |ui| {
+            if edited.list.is_empty() {
+                ui.weak("None: the scene is played freely.");
+            }
+            let mut remove = None;
+            let mut swap = None;
+            let count = edited.list.len();
+            for (i, objective) in edited.list.iter_mut().enumerate() {
+                ui.push_id(i, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label(format!("{}.", i + 1));
+                        egui::ComboBox::from_id_salt("condition")
+                            .selected_text(objective.condition.label())
+                            .show_ui(ui, |ui| {
+                                let object = match &objective.condition {
+                                    Condition::Reach { object }
+                                    | Condition::Activate { object } => object.clone(),
+                                    Condition::Collect { .. } => String::new(),
+                                };
+                                for kind in [
+                                    Condition::Reach {
+                                        object: object.clone(),
+                                    },
+                                    Condition::Collect {
+                                        tag: "gem".into(),
+                                        count: 3,
+                                    },
+                                    Condition::Activate { object },
+                                ] {
+                                    let same = kind.label() == objective.condition.label();
+                                    if ui.selectable_label(same, kind.label()).clicked() && !same {
+                                        objective.condition = kind;
+                                    }
+                                }
+                            });
+                        if ui
+                            .add_enabled(i > 0, egui::Button::new("⏶").small())
+                            .described("Move up")
+                            .clicked()
+                        {
+                            swap = Some((i - 1, i));
+                        }
+                        if ui
+                            .add_enabled(i + 1 < count, egui::Button::new("⏷").small())
+                            .described("Move down")
+                            .clicked()
+                        {
+                            swap = Some((i, i + 1));
+                        }
+                        if ui.small_button("✖").described("Remove").clicked() {
+                            remove = Some(i);
+                        }
+                    });
+                    match &mut objective.condition {
+                        Condition::Reach { object } | Condition::Activate { object } => {
+                            ui.horizontal(|ui| {
+                                ui.label("Object name");
+                                ui.text_edit_singleline(object);
+                            });
+                        }
+                        Condition::Collect { tag, count } => {
+                            ui.horizontal(|ui| {
+                                ui.label("Collectible");
+                                ui.add(egui::TextEdit::singleline(tag).desired_width(80.0));
+                                ui.add(egui::DragValue::new(count).range(1..=999));
+                            });
+                        }
+                    }
+                    ui.horizontal(|ui| {
+                        ui.label("Text");
+                        ui.add(
+                            egui::TextEdit::singleline(&mut objective.text)
+                                .hint_text(objective_hint(&objective.condition)),
+                        );
+                    });
+                });
+                ui.separator();
+            }
+            if let Some(i) = remove {
+                edited.list.remove(i);
+            }
+            if let Some((a, b)) = swap {
+                edited.list.swap(a, b);
+            }
+            if ui.button("Add objective").clicked() {
+                edited.list.push(Objective {
+                    text: String::new(),
+                    condition: Condition::Reach {
+                        object: "Goal".into(),
+                    },
+                });
+            }
+            ui.checkbox(
+                &mut edited.finish_level,
+                "Completing them finishes the level",
+            );
+            ui.weak(format!(
+                "Collectibles have a `{COLLECTIBLE_KEY}` property naming their kind; objects are \
+                 activated with {}.",
+                key_label(bindings.interact)
+            ));
+        });
+    // Only touch the resource on edits, which mark the scene modified
+    if edited != *objectives {
+        *objectives = edited;
+    }
+}
+
+fn objective_hint(condition: &Condition) -> String {
+    Objective {
+        text: String::new(),
+        condition: condition.clone(),
+    }
+    .describe()
+}
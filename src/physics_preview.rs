@@ -0,0 +1,252 @@
+//! Physics preview in Edit mode: the selected objects become dynamic bodies for a couple of
+//! seconds and drop onto the rest of the scene (and the ground), then their resting places can
+//! be kept or thrown away. Handy for scattering rocks and debris naturally.
+
+use bevy::prelude::*;
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+use bevy_rapier3d::prelude::*;
+
+use crate::SceneEditState;
+use crate::bounds::editable_collider;
+use crate::editor_mode::EditorMode;
+use crate::inspector::{Editable, EditableMesh, Selected};
+use crate::stairs::StairsParams;
+
+#[derive(Default, Clone, Copy, PartialEq, Eq)]
+enum Phase {
+    #[default]
+    Idle,
+    Running,
+    /// Stopped; waiting to be accepted or reverted
+    Settled,
+}
+
+#[derive(Resource)]
+struct PhysicsPreview {
+    /// How long the simulation runs
+    seconds: f32,
+    phase: Phase,
+    elapsed: f32,
+    /// Where the simulated objects started, to revert to
+    originals: Vec<(Entity, Transform)>,
+}
+
+impl Default for PhysicsPreview {
+    fn default() -> Self {
+        Self {
+            seconds: 2.0,
+            phase: Phase::Idle,
+            elapsed: 0.0,
+            originals: Vec::new(),
+        }
+    }
+}
+
+#[derive(Event, Clone, Copy, PartialEq, Eq)]
+enum PhysicsPreviewEvent {
+    Start,
+    Stop,
+    Accept,
+    Revert,
+}
+
+/// A selected object simulated as a dynamic body.
+#[derive(Component)]
+struct PreviewBody;
+
+/// An object given a static collider for the preview to land on.
+#[derive(Component)]
+struct PreviewCollider;
+
+/// The ground plane (Y=0) for the preview.
+#[derive(Component)]
+struct PreviewGround;
+
+pub struct PhysicsPreviewPlugin;
+impl Plugin for PhysicsPreviewPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PhysicsPreview>()
+            .init_resource::<EditorMode>()
+            .add_event::<PhysicsPreviewEvent>()
+            .add_systems(Update, run_physics_preview)
+            .add_systems(EguiPrimaryContextPass, physics_preview_window);
+    }
+}
+
+fn physics_preview_window(
+    mut ctxs: EguiContexts,
+    edit_state: Res<SceneEditState>,
+    mode: Res<EditorMode>,
+    mut preview: ResMut<PhysicsPreview>,
+    q_selected: Query<(), (With<Selected>, With<Editable>)>,
+    mut ev_preview: EventWriter<PhysicsPreviewEvent>,
+) {
+    if !edit_state.open {
+        return;
+    }
+    let count = q_selected.iter().count();
+    let Ok(ctx) = ctxs.ctx_mut() else {
+        return;
+    };
+    egui::Window::new("Physics preview")
+        .default_open(false)
+        .resizable(false)
+        .show(ctx, |ui| match preview.phase {
+            Phase::Idle => {
+                ui.add(egui::Slider::new(&mut preview.seconds, 0.5..=10.0).text("Seconds"));
+                let can_run = count > 0 && *mode != EditorMode::Play;
+                if ui
+                    .add_enabled(can_run, egui::Button::new("Simulate selected"))
+                    .on_hover_text("Let the selected objects fall and settle")
+                    .clicked()
+                {
+                    ev_preview.write(PhysicsPreviewEvent::Start);
+                }
+                ui.small("The rest of the scene and the ground stay put.");
+            }
+            Phase::Running => {
+                ui.add(
+                    egui::ProgressBar::new(preview.elapsed / preview.seconds.max(0.01))
+                        .text("Simulating…"),
+                );
+                if ui.button("Stop").clicked() {
+                    ev_preview.write(PhysicsPreviewEvent::Stop);
+                }
+            }
+            Phase::Settled => {
+                ui.label(format!(
+                    "Keep where {} came to rest?",
+                    match preview.originals.len() {
+                        1 => "the object".to_string(),
+                        n => format!("the {n} objects"),
+                    }
+                ));
+                ui.horizontal(|ui| {
+                    if ui.button("Accept").clicked() {
+                        ev_preview.write(PhysicsPreviewEvent::Accept);
+                    }
+                    if ui.button("Revert").clicked() {
+                        ev_preview.write(PhysicsPreviewEvent::Revert);
+                    }
+                });
+            }
+        });
+}
+
+type PhysicalObjects<'w, 's, F> = Query<
+    'w,
+    's,
+    (Entity, &'static EditableMesh, Option<&'static StairsParams>),
+    (With<Editable>, Without<Collider>, F),
+>;
+
+/// Take away everything the simulation added.
+fn end_simulation(
+    commands: &mut Commands,
+    q_bodies: &Query<Entity, With<PreviewBody>>,
+    q_colliders: &Query<Entity, With<PreviewCollider>>,
+    q_ground: &Query<Entity, With<PreviewGround>>,
+) {
+    for e in q_bodies {
+        commands
+            .entity(e)
+            .remove::<(RigidBody, Collider, ColliderScale, PreviewBody)>();
+    }
+    for e in q_colliders {
+        commands
+            .entity(e)
+            .remove::<(Collider, ColliderScale, PreviewCollider)>();
+    }
+    for e in q_ground {
+        commands.entity(e).despawn();
+    }
+}
+
+fn run_physics_preview(
+    mut commands: Commands,
+    mut events: EventReader<PhysicsPreviewEvent>,
+    mut preview: ResMut<PhysicsPreview>,
+    mode: Res<EditorMode>,
+    time: Res<Time>,
+    q_selected: PhysicalObjects<With<Selected>>,
+    q_static: PhysicalObjects<Without<Selected>>,
+    q_bodies: Query<Entity, With<PreviewBody>>,
+    q_colliders: Query<Entity, With<PreviewCollider>>,
+    q_ground: Query<Entity, With<PreviewGround>>,
+    mut q_tf: Query<&mut Transform>,
+) {
+    let mut events: Vec<PhysicsPreviewEvent> = events.read().copied().collect();
+    // Play mode builds its own colliders from scratch
+    if *mode == EditorMode::Play && preview.phase != Phase::Idle {
+        events.push(PhysicsPreviewEvent::Revert);
+    }
+    if preview.phase == Phase::Running {
+        preview.elapsed += time.delta_secs();
+        if preview.elapsed >= preview.seconds {
+            events.push(PhysicsPreviewEvent::Stop);
+        }
+    }
+
+    for ev in events {
+        match (ev, preview.phase) {
+            (PhysicsPreviewEvent::Start, Phase::Idle) if *mode != EditorMode::Play => {
+                preview.originals.clear();
+                for (e, info, stairs) in &q_selected {
+                    let (Some(collider), Ok(tf)) =
+                        (editable_collider(info.kind, stairs), q_tf.get(e))
+                    else {
+                        continue;
+                    };
+                    preview.originals.push((e, *tf));
+                    commands.entity(e).insert((
+                        RigidBody::Dynamic,
+                        collider,
+                        ColliderScale::Absolute(tf.scale),
+                        PreviewBody,
+                    ));
+                }
+                if preview.originals.is_empty() {
+                    continue;
+                }
+                for (e, info, stairs) in &q_static {
+                    let (Some(collider), Ok(tf)) =
+                        (editable_collider(info.kind, stairs), q_tf.get(e))
+                    else {
+                        continue;
+                    };
+                    commands.entity(e).insert((
+                        collider,
+                        ColliderScale::Absolute(tf.scale),
+                        PreviewCollider,
+                    ));
+                }
+                if let Some(ground) = Collider::halfspace(Vec3::Y) {
+                    commands.spawn((ground, Transform::IDENTITY, PreviewGround));
+                }
+                preview.elapsed = 0.0;
+                preview.phase = Phase::Running;
+            }
+            (PhysicsPreviewEvent::Stop, Phase::Running) => {
+                // Freeze everything where it is now
+                end_simulation(&mut commands, &q_bodies, &q_colliders, &q_ground);
+                preview.phase = Phase::Settled;
+            }
+            (PhysicsPreviewEvent::Accept, Phase::Settled) => {
+                preview.originals.clear();
+                preview.phase = Phase::Idle;
+            }
+            (PhysicsPreviewEvent::Revert, Phase::Running | Phase::Settled) => {
+                if preview.phase == Phase::Running {
+                    end_simulation(&mut commands, &q_bodies, &q_colliders, &q_ground);
+                }
+                for (e, original) in preview.originals.drain(..) {
+                    if let Ok(mut tf) = q_tf.get_mut(e) {
+                        *tf = original;
+                    }
+                }
+                preview.phase = Phase::Idle;
+            }
+            _ => {}
+        }
+    }
+}
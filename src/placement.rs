@@ -0,0 +1,243 @@
+//! Placing new objects: after picking a shape under *Create New*, a ghost of it follows the
+//! cursor over the scene (on top of whatever is under it, or the ground), snapped to the grid.
+//! A click drops it there, R turns it 90° first and a right click cancels. Without a mouse the
+//! ghost sits in the middle of the view and Enter drops it.
+
+use bevy::prelude::*;
+use bevy::render::primitives::Aabb;
+use bevy::window::PrimaryWindow;
+
+use crate::asset_cache::AssetCache;
+use crate::bake::BakedMember;
+use crate::build_mode::{BuildMode, ray_aabb_face};
+use crate::camera::{OrbitCamera, UiPointer};
+use crate::dissolve::{DissolveSettings, dissolve_in};
+use crate::inspector::{Editable, SelectObjectEvent, SpawnKind, spawn_new_object};
+use crate::settings::EditorPrefs;
+use crate::stairs::StairsParams;
+
+/// The object being placed, if any.
+#[derive(Resource, Default)]
+pub struct Placement {
+    kind: Option<SpawnKind>,
+    /// Turns of 90° around Y
+    quarter_turns: u8,
+    /// A click just dropped an object; it shouldn't also select what's under the cursor (nor
+    /// Enter start Play mode)
+    just_placed: bool,
+}
+
+impl Placement {
+    pub fn start(&mut self, kind: SpawnKind) {
+        self.kind = Some(kind);
+    }
+
+    pub fn set_kind(&mut self, kind: SpawnKind) {
+        if self.kind.is_some_and(|k| k != kind) {
+            self.kind = Some(kind);
+        }
+    }
+
+    pub fn cancel(&mut self) {
+        self.kind = None;
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.kind.is_some()
+    }
+
+    /// Turn the object being placed a quarter around Y.
+    pub fn turn(&mut self) {
+        self.quarter_turns = (self.quarter_turns + 1) % 4;
+    }
+
+    /// Viewport clicks belong to placement this frame.
+    pub fn captures_pointer(&self) -> bool {
+        self.kind.is_some() || self.just_placed
+    }
+
+    fn yaw(&self) -> Quat {
+        Quat::from_rotation_y(self.quarter_turns as f32 * std::f32::consts::FRAC_PI_2)
+    }
+}
+
+pub struct PlacementPlugin;
+impl Plugin for PlacementPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Placement>()
+            .init_resource::<EditorPrefs>()
+            .init_resource::<DissolveSettings>()
+            .add_systems(Update, (place_on_click, draw_placement_preview).chain());
+    }
+}
+
+/// Half size of a new object of `kind` (unturned), and how high its origin sits above the
+/// surface it's placed on.
+fn footprint(kind: SpawnKind) -> (Vec3, f32) {
+    match kind {
+        SpawnKind::Plane => (Vec3::new(0.5, 0.0, 0.5), 0.0),
+        SpawnKind::Decal => (Vec3::new(0.5, 0.0, 0.5), 0.01),
+        SpawnKind::FogVolume => (Vec3::new(2.0, 0.5, 2.0), 0.5),
+        SpawnKind::Stairs => {
+            let p = StairsParams::default();
+            let height = p.steps as f32 * p.rise;
+            (
+                Vec3::new(0.5 * p.width, 0.5 * height, 0.5 * p.steps as f32 * p.run),
+                0.0,
+            )
+        }
+        // Lights hang above the spot they're dropped on
+        SpawnKind::PointLight => (Vec3::splat(0.1), 1.5),
+        SpawnKind::SpotLight => (Vec3::splat(0.1), 3.0),
+        SpawnKind::Spline => (Vec3::new(0.5, 0.0, 0.5), 0.0),
+        SpawnKind::Creatures => (Vec3::new(3.0, 1.5, 3.0), 1.5),
+        _ => (Vec3::splat(0.5), 0.5),
+    }
+}
+
+type PlacementTargets<'w, 's> = Query<
+    'w,
+    's,
+    (&'static GlobalTransform, &'static Aabb),
+    (With<Editable>, Without<BakedMember>),
+>;
+
+/// Where the object's origin would go for the current cursor position.
+fn placement_point(
+    placement: &Placement,
+    prefs: &EditorPrefs,
+    windows: &Query<&Window, With<PrimaryWindow>>,
+    q_cam: &Query<(&Camera, &GlobalTransform), With<OrbitCamera>>,
+    q_targets: &PlacementTargets,
+) -> Option<Vec3> {
+    let kind = placement.kind?;
+    let (camera, cam_gt) = q_cam.single().ok()?;
+    // With the cursor outside the window (keyboard-only use), the middle of the view
+    let cursor = windows
+        .single()
+        .ok()?
+        .cursor_position()
+        .or_else(|| camera.logical_viewport_rect().map(|r| r.center()))?;
+    let ray = camera.viewport_to_world(cam_gt, cursor).ok()?;
+
+    // Nearest face of an object, tested in its local space, else the ground plane
+    let mut best: Option<(f32, Vec3)> = ray
+        .intersect_plane(Vec3::ZERO, InfinitePlane3d::new(Vec3::Y))
+        .map(|t| (t, Vec3::Y));
+    for (gt, aabb) in q_targets {
+        let inv = gt.affine().inverse();
+        let (min, max) = (
+            Vec3::from(aabb.center - aabb.half_extents),
+            Vec3::from(aabb.center + aabb.half_extents),
+        );
+        if let Some((t, n_local)) = ray_aabb_face(
+            inv.transform_point3(ray.origin),
+            inv.transform_vector3(*ray.direction),
+            min,
+            max,
+        ) && best.is_none_or(|(bt, _)| t < bt)
+        {
+            best = Some((t, (gt.rotation() * n_local).normalize_or_zero()));
+        }
+    }
+    let (t, normal) = best?;
+    let hit = ray.get_point(t);
+
+    let (mut half, lift) = footprint(kind);
+    if placement.quarter_turns % 2 == 1 {
+        half = Vec3::new(half.z, half.y, half.x);
+    }
+    let step = prefs.move_snap.max(0.01);
+    let snap = |v: f32| (v / step).round() * step;
+    let at = if normal.y > 0.5 {
+        // On top of a surface
+        Vec3::new(snap(hit.x), hit.y + lift, snap(hit.z))
+    } else {
+        // Against a wall (or under a ceiling): pushed out by half its size
+        let center = hit + normal * (half * normal.abs()).length();
+        let snapped = Vec3::new(snap(center.x), snap(center.y), snap(center.z));
+        let n = normal.abs();
+        let center = snapped * (Vec3::ONE - n) + center * n;
+        center - Vec3::Y * (half.y - lift)
+    };
+    Some(at)
+}
+
+fn place_on_click(
+    mut commands: Commands,
+    mut placement: ResMut<Placement>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    prefs: Res<EditorPrefs>,
+    build: Option<Res<BuildMode>>,
+    mut cache: ResMut<AssetCache>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut ui_pointer: UiPointer,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    q_cam: Query<(&Camera, &GlobalTransform), With<OrbitCamera>>,
+    q_targets: PlacementTargets,
+    mut ev_select: EventWriter<SelectObjectEvent>,
+    dissolve: Res<DissolveSettings>,
+) {
+    if placement.just_placed {
+        placement.just_placed = false;
+    }
+    let Some(kind) = placement.kind else {
+        return;
+    };
+    // Build mode owns viewport clicks
+    if build.is_some_and(|b| b.enabled) || mouse.just_pressed(MouseButton::Right) {
+        placement.cancel();
+        return;
+    }
+    let clicked = mouse.just_pressed(MouseButton::Left) && !ui_pointer.wants_pointer_in_primary();
+    if !clicked && !keys.just_pressed(KeyCode::Enter) {
+        return;
+    }
+    let Some(at) = placement_point(&placement, &prefs, &windows, &q_cam, &q_targets) else {
+        return;
+    };
+    let e = spawn_new_object(
+        &mut commands,
+        &mut cache,
+        &mut meshes,
+        &mut materials,
+        kind,
+        at,
+        placement.yaw(),
+    );
+    dissolve_in(&mut commands, e, &dissolve);
+    ev_select.write(SelectObjectEvent(e));
+    placement.kind = None;
+    placement.just_placed = true;
+}
+
+/// Outline of the object about to be placed.
+fn draw_placement_preview(
+    mut gizmos: Gizmos,
+    placement: Res<Placement>,
+    prefs: Res<EditorPrefs>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    q_cam: Query<(&Camera, &GlobalTransform), With<OrbitCamera>>,
+    q_targets: PlacementTargets,
+) {
+    let Some(kind) = placement.kind else {
+        return;
+    };
+    let Some(at) = placement_point(&placement, &prefs, &windows, &q_cam, &q_targets) else {
+        return;
+    };
+    let (half, lift) = footprint(kind);
+    let yaw = placement.yaw();
+    let center = at + Vec3::Y * (half.y - lift);
+    let color = Color::srgb(0.3, 0.9, 1.0);
+    gizmos.cuboid(
+        Transform::from_translation(center)
+            .with_rotation(yaw)
+            .with_scale((half * 2.0).max(Vec3::splat(0.02))),
+        color,
+    );
+    // Which way is "forward", so R turns are visible on symmetric shapes
+    gizmos.arrow(center, center + yaw * Vec3::NEG_Z * (half.z + 0.3), color);
+}
@@ -0,0 +1,204 @@
+//! Play mode tries the scene out without changing it: every editable object is copied aside
+//! (disabled) when Play starts, and the copies are put back when it ends. Whatever the player,
+//! physics and scripts did in between (crates pushed, doors opened, items collected or spawned)
+//! never reaches the authored scene.
+
+use std::collections::{HashMap, HashSet};
+
+use bevy::ecs::entity::EntityCloner;
+use bevy::ecs::entity_disabling::Disabled;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::editor_mode::EditorMode;
+use crate::inspector::{Editable, LoadSceneEvent, SceneIoState, Selected};
+
+/// Disabled copy of an editable object, kept while Play runs.
+#[derive(Component)]
+struct PlayBackup;
+
+struct Saved {
+    original: Entity,
+    copy: Entity,
+    parent: Option<Entity>,
+}
+
+#[derive(Resource, Default)]
+struct PlaySnapshot {
+    saved: Vec<Saved>,
+    /// Whether a snapshot is being held (Play started since the last restore)
+    taken: bool,
+    /// Whether the scene had unsaved changes when Play started
+    dirty: bool,
+}
+
+pub struct PlayRollbackPlugin;
+impl Plugin for PlayRollbackPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PlaySnapshot>()
+            .init_resource::<EditorMode>()
+            .add_event::<LoadSceneEvent>()
+            // Before Update, where the player, joints and scripts start acting on the new mode
+            .add_systems(PreUpdate, snapshot_play);
+    }
+}
+
+/// Clone `e` into a disabled copy, tagged with `marker`, that keeps its state aside (and out of
+/// every query). Its children (outline shells and the like) are copied along with it, disabled
+/// too; editable children only with `editable_children`, as Play copies those by themselves.
+/// The selection stays with the live object, and so do its parent link and the physics world's
+/// handles; bodies and joints are Play's own.
+pub(crate) fn disabled_copy(
+    commands: &mut Commands,
+    e: Entity,
+    marker: impl Bundle,
+    editable_children: bool,
+) -> Entity {
+    let copy = commands.spawn((Disabled, marker)).id();
+    commands.queue(move |world: &mut World| {
+        let mut cloner = EntityCloner::build(world);
+        cloner.deny::<(
+            Selected,
+            ChildOf,
+            Children,
+            RigidBody,
+            ImpulseJoint,
+            RapierRigidBodyHandle,
+            RapierColliderHandle,
+            RapierImpulseJointHandle,
+            RapierContextEntityLink,
+        )>();
+        let mut cloner = cloner.finish();
+        clone_tree(world, &mut cloner, e, copy, true, editable_children);
+    });
+    copy
+}
+
+/// Bring a [`disabled_copy`] (tagged with `M`) back as a new live object, children and all.
+/// The copy itself is left for the caller to despawn, and the parent link to put back.
+pub(crate) fn live_copy<M: Component>(commands: &mut Commands, copy: Entity) -> Entity {
+    let e = commands.spawn_empty().id();
+    commands.queue(move |world: &mut World| {
+        let mut cloner = EntityCloner::build(world);
+        cloner.deny::<(Disabled, M, ChildOf, Children)>();
+        let mut cloner = cloner.finish();
+        clone_tree(world, &mut cloner, copy, e, false, true);
+    });
+    e
+}
+
+/// Clone `from` onto `to`, and each of its children onto a new child of `to`, all the way down
+/// (editable children only with `editables`).
+fn clone_tree(
+    world: &mut World,
+    cloner: &mut EntityCloner,
+    from: Entity,
+    to: Entity,
+    disabled: bool,
+    editables: bool,
+) {
+    cloner.clone_entity(world, from, to);
+    let children: Vec<Entity> = world
+        .get::<Children>(from)
+        .map(|c| c.to_vec())
+        .unwrap_or_default();
+    for child in children {
+        if !editables && world.get::<Editable>(child).is_some() {
+            continue;
+        }
+        let copy = world.spawn(ChildOf(to)).id();
+        if disabled {
+            world.entity_mut(copy).insert(Disabled);
+        }
+        clone_tree(world, cloner, child, copy, disabled, editables);
+    }
+}
+
+/// Copy every editable aside when Play starts, and put the copies back when it stops.
+fn snapshot_play(
+    mut commands: Commands,
+    mode: Res<EditorMode>,
+    mut snapshot: ResMut<PlaySnapshot>,
+    mut io: Option<ResMut<SceneIoState>>,
+    mut ev_load: EventReader<LoadSceneEvent>,
+    q_editables: Query<(Entity, Option<&ChildOf>), With<Editable>>,
+    q_alive: Query<()>,
+) {
+    // A loaded scene replaces the one being played, copies and all
+    if ev_load.read().count() > 0 && snapshot.taken {
+        for saved in snapshot.saved.drain(..) {
+            commands.entity(saved.copy).despawn();
+        }
+        snapshot.taken = false;
+    }
+    if !mode.is_changed() {
+        return;
+    }
+
+    if *mode == EditorMode::Play {
+        if snapshot.taken {
+            return;
+        }
+        for (e, child_of) in &q_editables {
+            let copy = disabled_copy(&mut commands, e, PlayBackup, false);
+            snapshot.saved.push(Saved {
+                original: e,
+                copy,
+                parent: child_of.map(ChildOf::parent),
+            });
+        }
+        snapshot.taken = true;
+        snapshot.dirty = io.as_ref().is_some_and(|io| io.dirty);
+        return;
+    }
+
+    if !snapshot.taken {
+        return;
+    }
+    snapshot.taken = false;
+    let saved = std::mem::take(&mut snapshot.saved);
+
+    // Objects added while playing go away
+    let originals: HashSet<Entity> = saved.iter().map(|s| s.original).collect();
+    for (e, _) in &q_editables {
+        if !originals.contains(&e) {
+            commands.entity(e).despawn();
+        }
+    }
+
+    // Objects still around take their old state back in place (keeping their entity, so the
+    // selection and anything else pointing at them still does); removed ones come back as new
+    // entities, and their children follow them
+    let mut respawned = HashMap::new();
+    for s in &saved {
+        if q_editables.contains(s.original) {
+            // Its children are still there
+            commands.entity(s.copy).clone_with(s.original, |b| {
+                b.deny::<(Disabled, PlayBackup, Children)>();
+            });
+        } else {
+            let e = live_copy::<PlayBackup>(&mut commands, s.copy);
+            respawned.insert(s.original, e);
+        }
+    }
+    for s in &saved {
+        if let Some(&e) = respawned.get(&s.original) {
+            let parent = s.parent.and_then(|p| {
+                respawned
+                    .get(&p)
+                    .copied()
+                    .or(q_alive.contains(p).then_some(p))
+            });
+            if let Some(parent) = parent {
+                commands.entity(e).insert(ChildOf(parent));
+            }
+        }
+        commands.entity(s.copy).despawn();
+    }
+
+    // Putting things back isn't an edit
+    if let Some(io) = io.as_mut() {
+        io.dirty = snapshot.dirty;
+        io.skip_change_tracking();
+    }
+}
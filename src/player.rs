@@ -1,26 +1,107 @@
+use bevy::core_pipeline::{bloom::Bloom, tonemapping::Tonemapping};
+use bevy::pbr::DistanceFog;
 use bevy::prelude::*;
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
 use bevy_rapier3d::prelude::*;
 
-const PLAYER_START: Vec3 = Vec3::new(0.0, 4.0, 0.0);
+use crate::SceneEditState;
+use crate::bounds::editable_collider;
+use crate::camera::{CoopCamera, OrbitCamera, OrbitSet, SplitCamera};
+use crate::editor_mode::EditorMode;
+use crate::inspector::{Editable, EditableMesh};
+use crate::settings::{EditorPrefs, KeyBindings, PlayerTuning};
+use crate::stairs::StairsParams;
+use crate::surfaces::Surface;
+
+pub const PLAYER_START: Vec3 = Vec3::new(0.0, 4.0, 0.0);
+/// Where the second player drops, next to the first
+const SECOND_PLAYER_OFFSET: Vec3 = Vec3::new(1.0, 0.0, 0.0);
 const PLAYER_SIZE: Vec2 = Vec2::new(0.25, 0.5);
 const PLAYER_SPEED: f32 = 2.0; // speed units per second
 
 const GRAVITY_Y: f32 = -24.0; // tune to taste
 const TERMINAL_SPEED_Y: f32 = -50.0;
 
+/// Play mode (Enter or the toolbar) drops a capsule player that walks the scene with the arrow
+/// keys; leaving it removes the player again. With the second player preference on, another
+/// one walks with the first gamepad, sharing the view or on its own half of the screen. Step
+/// height and ledge grabbing are set in the **Player tuning** window.
+pub struct PlayerPlugin;
+impl Plugin for PlayerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<KeyBindings>()
+            .init_resource::<EditorMode>()
+            .init_resource::<EditorPrefs>()
+            .init_resource::<PlayerTuning>()
+            .add_systems(
+                Update,
+                (
+                    (sync_player_with_mode, sync_second_player).chain(),
+                    apply_player_tuning,
+                    (
+                        (player_horizontal_velocity, second_player_velocity),
+                        grab_ledges,
+                        pull_up,
+                        player_motion_with_gravity,
+                    )
+                        .chain(),
+                    frame_players.before(OrbitSet::Pose),
+                    follow_second_player.after(OrbitSet::Pose),
+                ),
+            )
+            .add_systems(EguiPrimaryContextPass, player_tuning_window);
+    }
+}
+
+/// Every player capsule, the first and the second.
 #[derive(Component)]
 pub struct Player;
 
+/// The gamepad-driven player of local co-op.
+#[derive(Component)]
+pub struct SecondPlayer;
+
 #[derive(Component, Deref, DerefMut, Default)]
 pub struct Velocity(pub Vec3);
 
+/// Climbing onto a ledge: the player is moved along by hand until it's on top.
+#[derive(Component)]
+pub struct PullUp {
+    from: Vec3,
+    to: Vec3,
+    t: f32,
+}
+
+/// What a player's feet are doing, carried between frames for surface behaviors.
+#[derive(Component, Default)]
+pub struct Footing {
+    /// Horizontal speed actually moved at (lags behind walking on ice)
+    slide: Vec3,
+}
+
 pub fn spawn_player(
     commands: &mut Commands,
     meshes: &mut ResMut<Assets<Mesh>>,
     materials: &mut ResMut<Assets<StandardMaterial>>,
 ) {
+    spawn_capsule(
+        commands,
+        meshes,
+        materials,
+        PLAYER_START,
+        Color::srgb(0.82, 0.82, 0.86),
+    );
+}
+
+fn spawn_capsule<'a>(
+    commands: &'a mut Commands,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<StandardMaterial>>,
+    at: Vec3,
+    color: Color,
+) -> EntityCommands<'a> {
     let mat = materials.add(StandardMaterial {
-        base_color: Color::srgb(0.82, 0.82, 0.86),
+        base_color: color,
         perceptual_roughness: 0.6,
         metallic: 0.0,
         ..Default::default()
@@ -29,9 +110,10 @@ pub fn spawn_player(
     commands.spawn((
         Mesh3d(meshes.add(Capsule3d::new(PLAYER_SIZE.x, PLAYER_SIZE.y / 2.0))),
         MeshMaterial3d(mat),
-        Transform::from_xyz(PLAYER_START.x, PLAYER_START.y, PLAYER_START.z),
+        Transform::from_translation(at),
         Player,
         Velocity(Vec3::ZERO),
+        Footing::default(),
         RigidBody::KinematicPositionBased,
         Collider::capsule_y(PLAYER_SIZE.x, PLAYER_SIZE.y / 2.0),
         KinematicCharacterController {
@@ -51,14 +133,14 @@ pub fn spawn_player(
             min_slope_slide_angle: 60.0_f32.to_radians(),
             ..default()
         },
-    ));
+    ))
 }
 
 // Sets X/Z from input
 pub fn player_horizontal_velocity(
-    mut q_player_vel: Query<&mut Velocity, With<Player>>,
+    mut q_player_vel: Query<&mut Velocity, (With<Player>, Without<SecondPlayer>)>,
     keys: Res<ButtonInput<KeyCode>>,
-    cam_q: Query<&Transform, With<Camera3d>>,
+    cam_q: Query<&Transform, With<OrbitCamera>>,
 ) {
     let Ok(cam_tf) = cam_q.single() else {
         return;
@@ -80,9 +162,34 @@ pub fn player_horizontal_velocity(
     if input.length_squared() > 1.0 {
         input = input.normalize();
     }
+    for mut vel in &mut q_player_vel {
+        walk(&mut vel, input, cam_tf.rotation);
+    }
+}
+
+/// The second player's X/Z from the first gamepad's left stick or D-pad.
+fn second_player_velocity(
+    mut q_player_vel: Query<&mut Velocity, With<SecondPlayer>>,
+    q_pads: Query<&Gamepad>,
+    cam_q: Query<&Transform, With<OrbitCamera>>,
+) {
+    let Ok(cam_tf) = cam_q.single() else {
+        return;
+    };
+    // No gamepad: it stands still
+    let input = q_pads
+        .iter()
+        .next()
+        .map(|pad| (pad.left_stick() + pad.dpad()).clamp_length_max(1.0))
+        .unwrap_or(Vec2::ZERO);
+    for mut vel in &mut q_player_vel {
+        walk(&mut vel, input, cam_tf.rotation);
+    }
+}
 
+/// Set a player's horizontal velocity from `input` (x right, y forward) as seen by the camera.
+fn walk(vel: &mut Velocity, input: Vec2, cam_rot: Quat) {
     // Camera-relative basis on ground plane
-    let cam_rot = cam_tf.rotation;
     let cam_right = cam_rot * Vec3::X;
     let cam_forward = cam_rot * -Vec3::Z;
 
@@ -92,43 +199,365 @@ pub fn player_horizontal_velocity(
     // World-space movement direction on XZ
     let dir_world_xz = (right_xz * input.x) + (forward_xz * input.y) * PLAYER_SPEED;
 
-    for mut vel in &mut q_player_vel {
-        let old_y = vel.y;
-        vel.0 = Vec3::new(
-            dir_world_xz.x * PLAYER_SPEED,
-            old_y,
-            dir_world_xz.y * PLAYER_SPEED,
-        );
-    }
+    let old_y = vel.y;
+    vel.0 = Vec3::new(
+        dir_world_xz.x * PLAYER_SPEED,
+        old_y,
+        dir_world_xz.y * PLAYER_SPEED,
+    );
 }
 
-// Integrates Y and pushes KCC
+// Integrates Y, applies what the player stands on and pushes KCC
 pub fn player_motion_with_gravity(
     time: Res<Time>,
+    rapier: ReadRapierContext,
+    q_surfaces: Query<(&Surface, &GlobalTransform)>,
     mut q: Query<
         (
+            Entity,
+            &GlobalTransform,
             &mut Velocity,
+            &mut Footing,
             &mut KinematicCharacterController,
             Option<&KinematicCharacterControllerOutput>,
         ),
-        With<Player>,
+        (With<Player>, Without<PullUp>),
     >,
 ) {
     let dt = time.delta_secs();
+    let context = rapier.single().ok();
 
-    for (mut vel, mut kcc, output) in &mut q {
+    for (e, gt, mut vel, mut footing, mut kcc, output) in &mut q {
         // grounded info from previous KCC step (present after the first physics tick)
         let grounded = output.map(|o| o.grounded).unwrap_or(false);
 
+        // The object right under the capsule, if it has a surface behavior
+        let surface = context
+            .as_ref()
+            .filter(|_| grounded)
+            .and_then(|ctx| {
+                let reach = PLAYER_SIZE.y / 2.0 + PLAYER_SIZE.x + 0.2;
+                let filter = QueryFilter::new().exclude_collider(e);
+                ctx.cast_ray(gt.translation(), Vec3::NEG_Y, reach, true, filter)
+            })
+            .and_then(|(ground, _)| q_surfaces.get(ground).ok());
+
         // gravity integration
-        if grounded && vel.y < 0.0 {
+        if let Some((Surface::Bounce { height }, _)) = surface
+            && vel.y <= 0.0
+        {
+            vel.y = (2.0 * -GRAVITY_Y * height).sqrt();
+        } else if grounded && vel.y < 0.0 {
             vel.y = 0.0; // clear accumulated downward speed
         } else {
             vel.y = (vel.y + GRAVITY_Y * dt).max(TERMINAL_SPEED_Y);
         }
 
+        // Ice eases towards the walking speed instead of taking it at once
+        let walk = Vec3::new(vel.x, 0.0, vel.z);
+        footing.slide = match surface {
+            Some((Surface::Ice { grip }, _)) => footing.slide.lerp(walk, (grip * dt).min(1.0)),
+            _ => walk,
+        };
+        let belt = match surface {
+            Some((Surface::Conveyor { speed }, belt_gt)) => {
+                let forward = belt_gt.forward().as_vec3() * Vec3::new(1.0, 0.0, 1.0);
+                forward.normalize_or_zero() * *speed
+            }
+            _ => Vec3::ZERO,
+        };
+
         // The controller expects a displacement this frame.
-        let frame_delta = **vel * dt;
+        let frame_delta = (footing.slide + belt + Vec3::Y * vel.y) * dt;
         kcc.translation = Some(frame_delta);
     }
 }
+
+/// Step height follows the tuning.
+fn apply_player_tuning(
+    tuning: Res<PlayerTuning>,
+    mut q: Query<&mut KinematicCharacterController, With<Player>>,
+) {
+    for mut kcc in &mut q {
+        kcc.autostep = Some(CharacterAutostep {
+            max_height: CharacterLength::Absolute(tuning.step_height),
+            min_width: CharacterLength::Absolute(tuning.step_min_width),
+            include_dynamic_bodies: false,
+        });
+    }
+}
+
+/// Walking into a wall whose top is within reach (but too high to step onto) starts a pull-up.
+fn grab_ledges(
+    mut commands: Commands,
+    tuning: Res<PlayerTuning>,
+    rapier: ReadRapierContext,
+    q: Query<(Entity, &GlobalTransform, &Velocity), (With<Player>, Without<PullUp>)>,
+) {
+    if !tuning.ledge_grab {
+        return;
+    }
+    let Ok(ctx) = rapier.single() else {
+        return;
+    };
+    let foot_offset = PLAYER_SIZE.y / 2.0 + PLAYER_SIZE.x;
+    for (e, gt, vel) in &q {
+        let Some(dir) = Vec3::new(vel.0.x, 0.0, vel.0.z).try_normalize() else {
+            continue;
+        };
+        let at = gt.translation();
+        let filter = QueryFilter::new().exclude_collider(e);
+        // A wall right in front, at chest height
+        let Some((_, wall)) = ctx.cast_ray(at, dir, PLAYER_SIZE.x + 0.15, true, filter) else {
+            continue;
+        };
+        // Its top, looking down from the highest ledge that can be caught, far enough in
+        // to stand on
+        let feet = at.y - foot_offset;
+        let over = at + dir * (wall + PLAYER_SIZE.x + 0.1);
+        let from_y = feet + tuning.ledge_reach;
+        let origin = Vec3::new(over.x, from_y, over.z);
+        let Some((_, drop)) = ctx.cast_ray(origin, Vec3::NEG_Y, tuning.ledge_reach, true, filter)
+        else {
+            continue;
+        };
+        // Nothing to grab if the ray starts inside something; low ones are stepped onto
+        let ledge = from_y - drop;
+        if drop <= 0.0 || ledge - feet <= tuning.step_height {
+            continue;
+        }
+        commands.entity(e).insert(PullUp {
+            from: at,
+            to: Vec3::new(over.x, ledge + foot_offset + 0.05, over.z),
+            t: 0.0,
+        });
+    }
+}
+
+/// Lift a grabbing player up to the ledge, then over it.
+fn pull_up(
+    mut commands: Commands,
+    time: Res<Time>,
+    tuning: Res<PlayerTuning>,
+    mut q: Query<(
+        Entity,
+        &mut Transform,
+        &mut Velocity,
+        &mut KinematicCharacterController,
+        &mut PullUp,
+    )>,
+) {
+    for (e, mut tf, mut vel, mut kcc, mut pull) in &mut q {
+        pull.t = (pull.t + time.delta_secs() / tuning.pull_up_secs.max(0.05)).min(1.0);
+        // Straight up for most of it, then forward onto the top
+        let rise = (pull.t / 0.7).min(1.0);
+        let over = ((pull.t - 0.7) / 0.3).clamp(0.0, 1.0);
+        let mut at = pull.from.lerp(pull.to, over);
+        at.y = pull.from.y + (pull.to.y - pull.from.y) * rise;
+        tf.translation = at;
+        kcc.translation = None;
+        vel.0 = Vec3::ZERO;
+        if pull.t >= 1.0 {
+            commands.entity(e).remove::<PullUp>();
+        }
+    }
+}
+
+/// Step height and ledge grab settings, kept in the app settings.
+fn player_tuning_window(
+    mut ctxs: EguiContexts,
+    edit_state: Res<SceneEditState>,
+    mut tuning: ResMut<PlayerTuning>,
+) {
+    if !edit_state.open {
+        return;
+    }
+    let Ok(ctx) = ctxs.ctx_mut() else {
+        return;
+    };
+    let mut t = tuning.clone();
+    egui::Window::new("Player tuning")
+        .default_open(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.add(egui::Slider::new(&mut t.step_height, 0.0..=1.5).text("Step height (m)"));
+            ui.add(egui::Slider::new(&mut t.step_min_width, 0.05..=1.0).text("Min step width (m)"));
+            ui.separator();
+            ui.checkbox(&mut t.ledge_grab, "Grab ledges and pull up");
+            ui.add_enabled_ui(t.ledge_grab, |ui| {
+                ui.add(egui::Slider::new(&mut t.ledge_reach, 0.5..=3.0).text("Reach (m)"));
+                ui.add(egui::Slider::new(&mut t.pull_up_secs, 0.1..=2.0).text("Pull-up time (s)"));
+            });
+            if t.ledge_grab && t.ledge_reach <= t.step_height {
+                ui.weak("Reach is below the step height: every ledge in reach is stepped onto.");
+            }
+        });
+    // Only touch the resource on edits
+    if t != *tuning {
+        *tuning = t;
+    }
+}
+
+/// Spawn the player when Play mode starts and despawn it when it ends.
+fn sync_player_with_mode(
+    mut commands: Commands,
+    mode: Res<EditorMode>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    player_q: Query<Entity, (With<Player>, Without<SecondPlayer>)>,
+    object_q: Query<
+        (Entity, &Transform, &EditableMesh, Option<&StairsParams>),
+        (With<Editable>, Without<Collider>),
+    >,
+) {
+    if !mode.is_changed() {
+        return;
+    }
+    if *mode != EditorMode::Play {
+        for e in &player_q {
+            commands.entity(e).despawn();
+        }
+        return;
+    }
+    if !player_q.is_empty() {
+        // already have a player
+        return;
+    }
+
+    spawn_player(&mut commands, &mut meshes, &mut materials);
+    make_colliders(&mut commands, object_q);
+}
+
+/// Keep a second player (and the split-screen view) around while playing with it turned on.
+fn sync_second_player(
+    mut commands: Commands,
+    mode: Res<EditorMode>,
+    prefs: Res<EditorPrefs>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    q_second: Query<Entity, With<SecondPlayer>>,
+    q_split: Query<Entity, With<SplitCamera>>,
+    q_main: Query<
+        (
+            &Transform,
+            &Projection,
+            &Tonemapping,
+            Option<&Bloom>,
+            Option<&DistanceFog>,
+        ),
+        With<OrbitCamera>,
+    >,
+) {
+    let second = *mode == EditorMode::Play && prefs.second_player;
+    match (second, q_second.iter().next()) {
+        (true, None) => {
+            spawn_capsule(
+                &mut commands,
+                &mut meshes,
+                &mut materials,
+                PLAYER_START + SECOND_PLAYER_OFFSET,
+                Color::srgb(0.55, 0.72, 0.95),
+            )
+            .insert(SecondPlayer);
+        }
+        (false, Some(e)) => commands.entity(e).despawn(),
+        _ => {}
+    }
+
+    let split = second && prefs.coop_camera == CoopCamera::Split;
+    match (split, q_split.iter().next()) {
+        (true, None) => {
+            let Ok((tf, projection, tonemapping, bloom, fog)) = q_main.single() else {
+                return;
+            };
+            // Looks like the main view; `follow_second_player` moves it. Drawn before the main
+            // view, so the editor UI (drawn with that one) stays on top
+            let mut cam = commands.spawn((
+                Camera3d::default(),
+                Camera {
+                    order: -1,
+                    ..default()
+                },
+                *tf,
+                projection.clone(),
+                *tonemapping,
+                Msaa::Off,
+                SplitCamera,
+                Name::new("SplitCamera"),
+            ));
+            if let Some(bloom) = bloom {
+                cam.insert(bloom.clone());
+            }
+            if let Some(fog) = fog {
+                cam.insert(fog.clone());
+            }
+        }
+        (false, Some(e)) => commands.entity(e).despawn(),
+        _ => {}
+    }
+}
+
+/// With a second player, aim the main view between the two (shared) or at the first (split).
+fn frame_players(
+    mode: Res<EditorMode>,
+    prefs: Res<EditorPrefs>,
+    q_first: Query<&GlobalTransform, (With<Player>, Without<SecondPlayer>)>,
+    q_second: Query<&GlobalTransform, With<SecondPlayer>>,
+    mut q_cam: Query<&mut OrbitCamera>,
+) {
+    if *mode != EditorMode::Play {
+        return;
+    }
+    let (Ok(first), Ok(second), Ok(mut cam)) =
+        (q_first.single(), q_second.single(), q_cam.single_mut())
+    else {
+        return;
+    };
+    let target = match prefs.coop_camera {
+        CoopCamera::Shared => first.translation().lerp(second.translation(), 0.5),
+        CoopCamera::Split => first.translation(),
+    };
+    if cam.target() != target {
+        cam.set_target(target);
+    }
+}
+
+/// Keep the split-screen view on the second player, from the main view's angle and distance.
+fn follow_second_player(
+    q_main: Query<(&Transform, &OrbitCamera, Ref<Projection>), Without<SplitCamera>>,
+    q_second: Query<&GlobalTransform, With<SecondPlayer>>,
+    mut q_split: Query<(&mut Transform, &mut Projection), With<SplitCamera>>,
+) {
+    let (Ok((main_tf, orbit, main_projection)), Ok(second), Ok((mut tf, mut projection))) =
+        (q_main.single(), q_second.single(), q_split.single_mut())
+    else {
+        return;
+    };
+    let offset = second.translation() - orbit.target();
+    tf.set_if_neq(Transform {
+        translation: main_tf.translation + offset,
+        ..*main_tf
+    });
+    // Zoom and projection changes follow too
+    if main_projection.is_changed() {
+        *projection = main_projection.clone();
+    }
+}
+
+fn make_colliders(
+    commands: &mut Commands,
+    object_q: Query<
+        (Entity, &Transform, &EditableMesh, Option<&StairsParams>),
+        (With<Editable>, Without<Collider>),
+    >,
+) {
+    for (e, tf, mesh_info, stairs) in object_q {
+        let Some(collider) = editable_collider(mesh_info.kind, stairs) else {
+            continue;
+        };
+        commands.entity(e).insert((
+            collider,
+            ColliderScale::Absolute(Vec3::new(tf.scale.x, tf.scale.y, tf.scale.z)),
+        ));
+    }
+}
@@ -23,10 +23,57 @@ use bevy::{
     },
 };
 
-#[derive(Component, Default, Clone, Copy, ExtractComponent, ShaderType)]
+use super::CalmEffects;
+use super::governor::{Governable, Governed};
+
+#[derive(Component, Default, Clone, Copy, ShaderType)]
 pub struct ChromaAberrationSettings {
     pub enabled: u32, // ShaderType doesn't support bool
     pub intensity: f32,
+    // WebGL2 wants uniform structs padded to 16 bytes
+    #[cfg(target_arch = "wasm32")]
+    pub _webgl2_padding: Vec2,
+}
+
+impl ExtractComponent for ChromaAberrationSettings {
+    type QueryData = (
+        &'static Self,
+        Has<CalmEffects>,
+        Option<&'static Governed<Self>>,
+    );
+    type QueryFilter = ();
+    type Out = Self;
+
+    fn extract_component(
+        (settings, calm, governed): QueryItem<'_, Self::QueryData>,
+    ) -> Option<Self> {
+        let mut out = governed.map_or(*settings, |g| g.apply(settings));
+        if calm {
+            out.enabled = 0;
+        }
+        Some(out)
+    }
+}
+
+impl Governable for ChromaAberrationSettings {
+    const FULL: [f32; 4] = [0.05, 0.0, 0.0, 0.0];
+    const CAPPED: [bool; 4] = [true, false, false, false];
+
+    fn is_enabled(&self) -> bool {
+        self.enabled != 0
+    }
+
+    fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled as u32;
+    }
+
+    fn strengths(&self) -> [f32; 4] {
+        [self.intensity, 0.0, 0.0, 0.0]
+    }
+
+    fn set_strengths(&mut self, strengths: [f32; 4]) {
+        self.intensity = strengths[0];
+    }
 }
 
 #[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
@@ -0,0 +1,737 @@
+//! A/B compare for grading choices: store the camera's post settings as snapshot A and B, then
+//! either split the view (A left, B right, with a divider to drag) or flip between the two
+//! with a key.
+//!
+//! The split draws the scene a second time, through a camera carrying snapshot B, into an image
+//! that the last pass of the main camera's post chain shows right of the divider. Snapshots hold
+//! what's set per camera (tonemapping, bloom, fog, depth of field, SSAO and the post passes);
+//! scene-wide settings like outlines, toon shading and the LUT image are the same on both sides.
+
+use bevy::{
+    core_pipeline::{
+        bloom::Bloom,
+        core_3d::graph::{Core3d, Node3d},
+        dof::DepthOfField,
+        fullscreen_vertex_shader::fullscreen_shader_vertex_state,
+        tonemapping::Tonemapping,
+    },
+    ecs::{query::QueryItem, system::SystemParam},
+    pbr::{DistanceFog, ScreenSpaceAmbientOcclusion},
+    prelude::*,
+    render::{
+        RenderApp,
+        camera::RenderTarget,
+        extract_component::{
+            ComponentUniforms, DynamicUniformIndex, ExtractComponent, ExtractComponentPlugin,
+            UniformComponentPlugin,
+        },
+        extract_resource::{ExtractResource, ExtractResourcePlugin},
+        render_asset::{RenderAssetUsages, RenderAssets},
+        render_graph::{
+            NodeRunError, RenderGraphApp, RenderGraphContext, RenderLabel, ViewNode, ViewNodeRunner,
+        },
+        render_resource::{
+            binding_types::{sampler, texture_2d, uniform_buffer},
+            *,
+        },
+        renderer::{RenderContext, RenderDevice},
+        texture::GpuImage,
+        view::{RenderLayers, ViewTarget},
+    },
+    window::PrimaryWindow,
+};
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+
+#[cfg(feature = "chroma")]
+use super::chroma_aberration::ChromaAberrationSettings;
+#[cfg(feature = "crt")]
+use super::crt::CRTSettings;
+#[cfg(feature = "gradient")]
+use super::gradient_tint::GradientTintSettings;
+use super::lens_flare::LensFlareSettings;
+#[cfg(feature = "lut")]
+use super::lut::LutSettings;
+use super::{CalmEffects, PostEffects};
+use crate::camera::OrbitCamera;
+use crate::settings::KeyBindings;
+
+const SHADER_ASSET_PATH: &str = "shaders/ab_split.wgsl";
+
+/// Divider width, in physical pixels
+const DIVIDER_PX: f32 = 2.0;
+
+/// Snapshot A or B: everything `apply` puts back on a camera.
+#[derive(Clone)]
+pub struct PostSnapshot {
+    tonemapping: Tonemapping,
+    bloom: Option<Bloom>,
+    fog: Option<DistanceFog>,
+    dof: Option<DepthOfField>,
+    ssao: Option<ScreenSpaceAmbientOcclusion>,
+    flare: Option<LensFlareSettings>,
+    #[cfg(feature = "chroma")]
+    chroma: Option<ChromaAberrationSettings>,
+    #[cfg(feature = "crt")]
+    crt: Option<CRTSettings>,
+    #[cfg(feature = "gradient")]
+    tint: Option<GradientTintSettings>,
+    #[cfg(feature = "lut")]
+    lut: Option<LutSettings>,
+}
+
+/// Insert `value` on the entity, or take the component off if there's none.
+fn put<T: Component + Clone>(ecmd: &mut EntityCommands, value: &Option<T>) {
+    match value {
+        Some(v) => ecmd.insert(v.clone()),
+        None => ecmd.remove::<T>(),
+    };
+}
+
+impl PostSnapshot {
+    /// Give a camera these settings.
+    fn apply(&self, ecmd: &mut EntityCommands) {
+        ecmd.insert(self.tonemapping);
+        put(ecmd, &self.bloom);
+        put(ecmd, &self.fog);
+        put(ecmd, &self.dof);
+        put(ecmd, &self.ssao);
+        put(ecmd, &self.flare);
+        #[cfg(feature = "chroma")]
+        put(ecmd, &self.chroma);
+        #[cfg(feature = "crt")]
+        put(ecmd, &self.crt);
+        #[cfg(feature = "gradient")]
+        put(ecmd, &self.tint);
+        #[cfg(feature = "lut")]
+        put(ecmd, &self.lut);
+    }
+}
+
+/// The main camera's post settings, to take snapshots of.
+#[derive(SystemParam)]
+struct MainPost<'w, 's> {
+    common: Query<
+        'w,
+        's,
+        (
+            Entity,
+            &'static Tonemapping,
+            Option<&'static Bloom>,
+            Option<&'static DepthOfField>,
+            Option<&'static ScreenSpaceAmbientOcclusion>,
+        ),
+        With<OrbitCamera>,
+    >,
+    effects: PostEffects<'w, 's>,
+}
+
+impl MainPost<'_, '_> {
+    fn camera(&self) -> Option<Entity> {
+        self.common.single().ok().map(|(e, ..)| e)
+    }
+
+    fn snapshot(&self) -> Option<PostSnapshot> {
+        let (e, tonemapping, bloom, dof, ssao) = self.common.single().ok()?;
+        let effects = &self.effects;
+        Some(PostSnapshot {
+            tonemapping: *tonemapping,
+            bloom: bloom.cloned(),
+            fog: effects.fog.get(e).ok().cloned(),
+            dof: dof.cloned(),
+            ssao: ssao.cloned(),
+            flare: effects.flare.get(e).ok().copied(),
+            #[cfg(feature = "chroma")]
+            chroma: effects.chroma.get(e).ok().copied(),
+            #[cfg(feature = "crt")]
+            crt: effects.crt.get(e).ok().copied(),
+            #[cfg(feature = "gradient")]
+            tint: effects.gradient.get(e).ok().copied(),
+            #[cfg(feature = "lut")]
+            lut: effects.lut.get(e).ok().cloned(),
+        })
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum CompareMode {
+    #[default]
+    Off,
+    /// A left of the divider, B right of it
+    Split,
+    /// One at a time, flipped with the A/B key
+    Toggle,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum Slot {
+    #[default]
+    A,
+    B,
+}
+
+/// The two snapshots and how they're compared; set from the Effect settings.
+#[derive(Resource)]
+pub struct PostCompare {
+    pub a: Option<PostSnapshot>,
+    pub b: Option<PostSnapshot>,
+    pub mode: CompareMode,
+    /// Divider position across the view, 0 left .. 1 right
+    pub divider: f32,
+    /// The snapshot on screen while toggling
+    pub showing: Slot,
+    /// Store the camera's current settings into this slot (next frame)
+    pub store: Option<Slot>,
+    /// The camera's settings from just before the compare put a snapshot on it
+    pub live: Option<PostSnapshot>,
+    /// Put `live` back on the camera (next frame)
+    pub restore_live: bool,
+}
+
+impl Default for PostCompare {
+    fn default() -> Self {
+        Self {
+            a: None,
+            b: None,
+            mode: CompareMode::Off,
+            divider: 0.5,
+            showing: Slot::A,
+            store: None,
+            live: None,
+            restore_live: false,
+        }
+    }
+}
+
+impl PostCompare {
+    fn get(&self, slot: Slot) -> Option<&PostSnapshot> {
+        match slot {
+            Slot::A => self.a.as_ref(),
+            Slot::B => self.b.as_ref(),
+        }
+    }
+
+    /// Everything the section's widgets set.
+    fn controls(&self) -> (CompareMode, f32, Slot, Option<Slot>, bool) {
+        (
+            self.mode,
+            self.divider,
+            self.showing,
+            self.store,
+            self.restore_live,
+        )
+    }
+
+    /// Show the other snapshot, when comparing by toggling.
+    pub fn flip(&mut self) {
+        if self.mode == CompareMode::Toggle {
+            self.showing = match self.showing {
+                Slot::A => Slot::B,
+                Slot::B => Slot::A,
+            };
+        }
+    }
+}
+
+/// The camera drawing snapshot B for the split view. Leave it out of queries for "the" camera's
+/// post settings.
+#[derive(Component)]
+pub struct CompareCamera;
+
+/// Where snapshot B is drawn.
+#[derive(Resource, Clone, ExtractResource)]
+struct CompareImage {
+    image: Handle<Image>,
+}
+
+/// On the main camera while split: where the divider goes.
+#[derive(Component, Clone, Copy, ExtractComponent, ShaderType)]
+struct CompareSplit {
+    /// The view's area in the target, in UV: min x, min y, max x, max y
+    viewport: Vec4,
+    /// 0 left .. 1 right of the view
+    divider: f32,
+    /// Divider width in UV
+    line_width: f32,
+    _padding: Vec2,
+}
+
+pub struct PostComparePlugin;
+impl Plugin for PostComparePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            ExtractComponentPlugin::<CompareSplit>::default(),
+            UniformComponentPlugin::<CompareSplit>::default(),
+            ExtractResourcePlugin::<CompareImage>::default(),
+        ))
+        .init_resource::<PostCompare>()
+        .init_resource::<KeyBindings>()
+        .add_systems(Startup, setup_compare_image)
+        .add_systems(
+            Update,
+            (store_snapshots, show_snapshots, sync_compare_camera).chain(),
+        )
+        .add_systems(EguiPrimaryContextPass, divider_handle);
+
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app
+            .add_render_graph_node::<ViewNodeRunner<CompareNode>>(Core3d, CompareLabel)
+            .add_render_graph_edges(
+                Core3d,
+                (
+                    Node3d::EndMainPassPostProcessing,
+                    CompareLabel,
+                    Node3d::Upscaling,
+                ),
+            );
+    }
+
+    fn finish(&self, app: &mut App) {
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app.init_resource::<ComparePipeline>();
+    }
+}
+
+fn compare_image(size: UVec2) -> Image {
+    let size = Extent3d {
+        width: size.x.max(1),
+        height: size.y.max(1),
+        ..default()
+    };
+    let mut image = Image::new_fill(
+        size,
+        TextureDimension::D2,
+        &[0, 0, 0, 255],
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    image.texture_descriptor.usage =
+        TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST | TextureUsages::RENDER_ATTACHMENT;
+    image
+}
+
+fn setup_compare_image(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    let image = images.add(compare_image(UVec2::ONE));
+    commands.insert_resource(CompareImage { image });
+}
+
+fn store_snapshots(mut compare: ResMut<PostCompare>, main: MainPost) {
+    let Some(slot) = compare.store else {
+        return;
+    };
+    let snapshot = main.snapshot();
+    match slot {
+        Slot::A => compare.a = snapshot,
+        Slot::B => compare.b = snapshot,
+    }
+    compare.store = None;
+}
+
+/// Put the snapshot being looked at on the main camera: A while split, either while toggling
+/// (the key flips it, see [`PostCompare::flip`]). The settings it replaces are kept first, so
+/// unstored edits can be brought back.
+fn show_snapshots(
+    mut commands: Commands,
+    mut compare: ResMut<PostCompare>,
+    main: MainPost,
+    mut shown: Local<Option<(CompareMode, Slot)>>,
+) {
+    if compare.restore_live {
+        compare.restore_live = false;
+        if let (Some(live), Some(camera)) = (compare.live.take(), main.camera()) {
+            live.apply(&mut commands.entity(camera));
+        }
+    }
+    let want = match compare.mode {
+        CompareMode::Off => None,
+        CompareMode::Split => Some((CompareMode::Split, Slot::A)),
+        CompareMode::Toggle => Some((CompareMode::Toggle, compare.showing)),
+    };
+    if want == *shown {
+        return;
+    }
+    if shown.is_none() {
+        compare.live = main.snapshot();
+    }
+    *shown = want;
+    // Turning the compare off leaves whichever was showing, to keep editing
+    if let (Some((_, slot)), Some(camera)) = (want, main.camera())
+        && let Some(snapshot) = compare.get(slot)
+    {
+        snapshot.apply(&mut commands.entity(camera));
+    }
+}
+
+/// While split, keep a camera drawing snapshot B from the main camera's point of view into the
+/// compare image, and tell the main camera's last pass where the divider is.
+fn sync_compare_camera(
+    mut commands: Commands,
+    compare: Res<PostCompare>,
+    target: Res<CompareImage>,
+    mut images: ResMut<Assets<Image>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    q_main: Query<
+        (
+            Entity,
+            &Camera,
+            &Transform,
+            &Projection,
+            Option<&RenderLayers>,
+            Has<CalmEffects>,
+        ),
+        (With<OrbitCamera>, Without<CompareCamera>),
+    >,
+    mut q_compare: Query<
+        (Entity, &mut Camera, &mut Transform, &mut Projection),
+        With<CompareCamera>,
+    >,
+    mut applied_b: Local<Option<Handle<Image>>>,
+) {
+    let split = compare.mode == CompareMode::Split && compare.b.is_some();
+    let (Ok(window), Ok((main_e, main_cam, main_tf, main_proj, layers, calm)), true) =
+        (windows.single(), q_main.single(), split)
+    else {
+        for (e, ..) in &q_compare {
+            commands.entity(e).despawn();
+        }
+        if let Ok((main_e, ..)) = q_main.single() {
+            commands.entity(main_e).remove::<CompareSplit>();
+        }
+        *applied_b = None;
+        return;
+    };
+
+    // Same size as the window, so both sides line up pixel for pixel
+    let size = window.physical_size();
+    let want = Extent3d {
+        width: size.x.max(1),
+        height: size.y.max(1),
+        ..default()
+    };
+    let current = images.get(&target.image).map(|i| i.texture_descriptor.size);
+    if current.is_some_and(|s| s != want)
+        && let Some(image) = images.get_mut(&target.image)
+    {
+        image.resize(want);
+    }
+
+    let Some(b) = compare.b.as_ref() else {
+        return;
+    };
+    match q_compare.single_mut() {
+        Ok((e, mut camera, mut tf, mut proj)) => {
+            camera.viewport = main_cam.viewport.clone();
+            tf.set_if_neq(*main_tf);
+            *proj = main_proj.clone();
+            // A snapshot stored again shows straight away
+            if compare.is_changed() || applied_b.is_none() {
+                b.apply(&mut commands.entity(e));
+                *applied_b = Some(target.image.clone());
+            }
+        }
+        Err(_) => {
+            // Drawn before the main view, so the image is ready for its last pass
+            let mut ecmd = commands.spawn((
+                Camera3d::default(),
+                Camera {
+                    target: RenderTarget::Image(target.image.clone().into()),
+                    order: -1,
+                    viewport: main_cam.viewport.clone(),
+                    hdr: main_cam.hdr,
+                    ..default()
+                },
+                *main_tf,
+                main_proj.clone(),
+                Msaa::Off,
+                layers.cloned().unwrap_or_default(),
+                CompareCamera,
+                Name::new("CompareCamera"),
+            ));
+            if calm {
+                ecmd.insert(CalmEffects);
+            }
+            b.apply(&mut ecmd);
+            *applied_b = Some(target.image.clone());
+        }
+    }
+
+    let size = size.as_vec2().max(Vec2::ONE);
+    let viewport = match &main_cam.viewport {
+        Some(v) => {
+            let min = v.physical_position.as_vec2() / size;
+            let max = (v.physical_position + v.physical_size).as_vec2() / size;
+            Vec4::new(min.x, min.y, max.x, max.y)
+        }
+        None => Vec4::new(0.0, 0.0, 1.0, 1.0),
+    };
+    commands.entity(main_e).insert(CompareSplit {
+        viewport,
+        divider: compare.divider.clamp(0.0, 1.0),
+        line_width: DIVIDER_PX / size.x,
+        _padding: Vec2::ZERO,
+    });
+}
+
+/// A grip on the divider to drag it sideways (an egui area, so the drag doesn't also pick
+/// whatever is behind it).
+fn divider_handle(
+    mut ctxs: EguiContexts,
+    mut compare: ResMut<PostCompare>,
+    q_main: Query<&Camera, (With<OrbitCamera>, With<CompareSplit>)>,
+) {
+    let Ok(camera) = q_main.single() else {
+        return;
+    };
+    let Some(rect) = camera.physical_viewport_rect() else {
+        return;
+    };
+    let Ok(ctx) = ctxs.ctx_mut() else {
+        return;
+    };
+    let ppp = ctx.pixels_per_point();
+    let (min, max) = (rect.min.as_vec2() / ppp, rect.max.as_vec2() / ppp);
+    let width = (max.x - min.x).max(1.0);
+    let x = min.x + compare.divider.clamp(0.0, 1.0) * width;
+    let grip = egui::vec2(18.0, 44.0);
+    let at = egui::pos2(x - grip.x * 0.5, (min.y + max.y) * 0.5 - grip.y * 0.5);
+
+    let mut divider = compare.divider;
+    egui::Area::new(egui::Id::new("ab_compare_divider"))
+        .fixed_pos(at)
+        .order(egui::Order::Foreground)
+        .show(ctx, |ui| {
+            let (rect, response) = ui.allocate_exact_size(grip, egui::Sense::drag());
+            let fill = if response.hovered() || response.dragged() {
+                egui::Color32::WHITE
+            } else {
+                egui::Color32::from_gray(210)
+            };
+            ui.painter().rect_filled(rect, 6.0, fill);
+            ui.painter().text(
+                rect.center(),
+                egui::Align2::CENTER_CENTER,
+                "⇔",
+                egui::FontId::proportional(14.0),
+                egui::Color32::BLACK,
+            );
+            let response = response.on_hover_text("A | B: drag to move the divider");
+            if response.dragged() {
+                divider = (divider + response.drag_delta().x / width).clamp(0.0, 1.0);
+            }
+        });
+    if divider != compare.divider {
+        compare.divider = divider;
+    }
+}
+
+/// What the Effect settings section needs, as one parameter of the (crowded) panel system.
+#[derive(SystemParam)]
+pub struct CompareUi<'w> {
+    compare: ResMut<'w, PostCompare>,
+    bindings: Res<'w, KeyBindings>,
+}
+
+impl CompareUi<'_> {
+    /// Effect settings section.
+    pub fn show(&mut self, ui: &mut egui::Ui) {
+        // Only an actual edit marks the resource changed, or the split camera would be
+        // reconfigured every frame
+        let compare = self.compare.bypass_change_detection();
+        let before = compare.controls();
+        compare_ui(ui, compare, self.bindings.ab_compare);
+        if compare.controls() != before {
+            self.compare.set_changed();
+        }
+    }
+}
+
+fn compare_ui(ui: &mut egui::Ui, compare: &mut PostCompare, key: KeyCode) {
+    ui.horizontal(|ui| {
+        for (slot, label) in [(Slot::A, "Store A"), (Slot::B, "Store B")] {
+            let stored = compare.get(slot).is_some();
+            let text = if stored {
+                format!("{label} ✔")
+            } else {
+                label.to_string()
+            };
+            if ui
+                .button(text)
+                .on_hover_text("Keep the camera's current post settings")
+                .clicked()
+            {
+                compare.store = Some(slot);
+            }
+        }
+    });
+    let ready = compare.a.is_some() && compare.b.is_some();
+    ui.add_enabled_ui(ready, |ui| {
+        ui.horizontal(|ui| {
+            ui.selectable_value(&mut compare.mode, CompareMode::Off, "Off");
+            ui.selectable_value(&mut compare.mode, CompareMode::Split, "Split")
+                .on_hover_text("A left of the divider, B right of it");
+            ui.selectable_value(&mut compare.mode, CompareMode::Toggle, "Toggle")
+                .on_hover_text(format!("{key:?} flips between A and B"));
+        });
+        match compare.mode {
+            CompareMode::Split => {
+                ui.add(egui::Slider::new(&mut compare.divider, 0.0..=1.0).text("Divider"));
+            }
+            CompareMode::Toggle => {
+                ui.horizontal(|ui| {
+                    ui.selectable_value(&mut compare.showing, Slot::A, "Showing A");
+                    ui.selectable_value(&mut compare.showing, Slot::B, "Showing B");
+                });
+            }
+            CompareMode::Off => {}
+        }
+    });
+    if compare.mode == CompareMode::Off
+        && compare.live.is_some()
+        && ui
+            .button("Back to live settings")
+            .on_hover_text("The settings from before comparing, with any edits not stored")
+            .clicked()
+    {
+        compare.restore_live = true;
+    }
+    if !ready {
+        compare.mode = CompareMode::Off;
+        ui.weak("Store both snapshots to compare them.");
+    } else if compare.mode == CompareMode::Split {
+        ui.weak("Edits change A; store B again to change the right side.");
+    }
+}
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub(super) struct CompareLabel;
+
+#[derive(Default)]
+struct CompareNode;
+
+impl ViewNode for CompareNode {
+    type ViewQuery = (
+        &'static ViewTarget,
+        &'static CompareSplit,
+        &'static DynamicUniformIndex<CompareSplit>,
+    );
+
+    fn run(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        (view_target, _split, split_index): QueryItem<Self::ViewQuery>,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let compare_pipeline = world.resource::<ComparePipeline>();
+        let pipeline_cache = world.resource::<PipelineCache>();
+        let Some(pipeline) = pipeline_cache.get_render_pipeline(compare_pipeline.pipeline_id)
+        else {
+            return Ok(());
+        };
+        let Some(image) = world.get_resource::<CompareImage>() else {
+            return Ok(());
+        };
+        let Some(b_view) = world.resource::<RenderAssets<GpuImage>>().get(&image.image) else {
+            return Ok(());
+        };
+        let split_uniforms = world.resource::<ComponentUniforms<CompareSplit>>();
+        let Some(split_binding) = split_uniforms.uniforms().binding() else {
+            return Ok(());
+        };
+
+        let post_process = view_target.post_process_write();
+
+        let bind_group = render_context.render_device().create_bind_group(
+            "ab_compare_bind_group",
+            &compare_pipeline.layout,
+            &BindGroupEntries::sequential((
+                post_process.source,
+                &compare_pipeline.sampler,
+                split_binding.clone(),
+                &b_view.texture_view,
+            )),
+        );
+
+        let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+            label: Some("ab_compare_pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: post_process.destination,
+                resolve_target: None,
+                ops: Operations::default(),
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+
+        render_pass.set_render_pipeline(pipeline);
+        render_pass.set_bind_group(0, &bind_group, &[split_index.index()]);
+        render_pass.draw(0..3, 0..1);
+
+        Ok(())
+    }
+}
+
+#[derive(Resource)]
+struct ComparePipeline {
+    layout: BindGroupLayout,
+    sampler: Sampler,
+    pipeline_id: CachedRenderPipelineId,
+}
+
+impl FromWorld for ComparePipeline {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+
+        let layout = render_device.create_bind_group_layout(
+            "ab_compare_bind_group_layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::FRAGMENT,
+                (
+                    texture_2d(TextureSampleType::Float { filterable: true }),
+                    sampler(SamplerBindingType::Filtering),
+                    uniform_buffer::<CompareSplit>(true),
+                    // Snapshot B
+                    texture_2d(TextureSampleType::Float { filterable: true }),
+                ),
+            ),
+        );
+
+        let sampler = render_device.create_sampler(&SamplerDescriptor::default());
+
+        let shader = world.load_asset(SHADER_ASSET_PATH);
+
+        let pipeline_id =
+            world
+                .resource_mut::<PipelineCache>()
+                .queue_render_pipeline(RenderPipelineDescriptor {
+                    label: Some("ab_compare_pipeline".into()),
+                    layout: vec![layout.clone()],
+                    vertex: fullscreen_shader_vertex_state(),
+                    fragment: Some(FragmentState {
+                        shader,
+                        shader_defs: vec![],
+                        entry_point: "fragment".into(),
+                        targets: vec![Some(ColorTargetState {
+                            format: TextureFormat::bevy_default(),
+                            blend: None,
+                            write_mask: ColorWrites::ALL,
+                        })],
+                    }),
+                    primitive: PrimitiveState::default(),
+                    depth_stencil: None,
+                    multisample: MultisampleState::default(),
+                    push_constant_ranges: vec![],
+                    zero_initialize_workgroup_memory: false,
+                });
+
+        Self {
+            layout,
+            sampler,
+            pipeline_id,
+        }
+    }
+}
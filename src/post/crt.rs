@@ -23,6 +23,9 @@ use bevy::{
     },
 };
 
+use super::CalmEffects;
+use super::governor::{Governable, Governed};
+
 const SHADER_ASSET_PATH: &str = "shaders/crt.wgsl";
 
 pub struct CRTPlugin;
@@ -182,7 +185,7 @@ impl FromWorld for PostProcessPipeline {
     }
 }
 
-#[derive(Component, Default, Clone, Copy, ExtractComponent, ShaderType)]
+#[derive(Component, Default, Clone, Copy, ShaderType)]
 #[repr(C, align(16))]
 pub struct CRTSettings {
     pub enabled: u32, // ShaderType doesn't support bool
@@ -190,3 +193,47 @@ pub struct CRTSettings {
     pub scanline_freq: f32,
     pub line_intensity: f32,
 }
+
+impl ExtractComponent for CRTSettings {
+    type QueryData = (
+        &'static Self,
+        Has<CalmEffects>,
+        Option<&'static Governed<Self>>,
+    );
+    type QueryFilter = ();
+    type Out = Self;
+
+    fn extract_component(
+        (settings, calm, governed): QueryItem<'_, Self::QueryData>,
+    ) -> Option<Self> {
+        let mut out = governed.map_or(*settings, |g| g.apply(settings));
+        if calm {
+            out.enabled = 0;
+        }
+        Some(out)
+    }
+}
+
+impl Governable for CRTSettings {
+    const FULL: [f32; 4] = [0.5, 1.0, 500.0, 0.0];
+    const CAPPED: [bool; 4] = [true, true, false, false];
+
+    fn is_enabled(&self) -> bool {
+        self.enabled != 0
+    }
+
+    fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled as u32;
+    }
+
+    fn strengths(&self) -> [f32; 4] {
+        [self.intensity, self.line_intensity, self.scanline_freq, 0.0]
+    }
+
+    fn set_strengths(&mut self, strengths: [f32; 4]) {
+        let [intensity, line_intensity, scanline_freq, _] = strengths;
+        self.intensity = intensity;
+        self.line_intensity = line_intensity;
+        self.scanline_freq = scanline_freq;
+    }
+}
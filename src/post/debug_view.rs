@@ -0,0 +1,574 @@
+//! Renderer debug views, picked in Effect settings: the main camera's picture swapped for its
+//! depth, its normals, an overdraw heatmap or the shadow cascades, and optionally a wireframe
+//! box around every editable object.
+//!
+//! Depth, normals and cascades come from the depth and normal prepass textures in one extra pass
+//! at the end of the post chain. Overdraw needs every layer of geometry, so a second camera draws
+//! an additive copy of each editable's mesh into an image that the same pass turns into colors.
+
+use std::collections::HashSet;
+
+use bevy::{
+    core_pipeline::{
+        core_3d::graph::{Core3d, Node3d},
+        fullscreen_vertex_shader::fullscreen_shader_vertex_state,
+        prepass::{NormalPrepass, ViewPrepassTextures},
+        tonemapping::Tonemapping,
+    },
+    ecs::query::QueryItem,
+    pbr::{CascadeShadowConfig, NotShadowCaster},
+    prelude::*,
+    render::{
+        RenderApp,
+        camera::RenderTarget,
+        extract_component::{
+            ComponentUniforms, DynamicUniformIndex, ExtractComponent, ExtractComponentPlugin,
+            UniformComponentPlugin,
+        },
+        extract_resource::{ExtractResource, ExtractResourcePlugin},
+        primitives::Aabb,
+        render_asset::{RenderAssetUsages, RenderAssets},
+        render_graph::{
+            NodeRunError, RenderGraphApp, RenderGraphContext, RenderLabel, ViewNode, ViewNodeRunner,
+        },
+        render_resource::{
+            binding_types::{sampler, texture_2d, texture_depth_2d, uniform_buffer},
+            *,
+        },
+        renderer::{RenderContext, RenderDevice},
+        texture::{FallbackImage, GpuImage},
+        view::{RenderLayers, ViewDepthTexture, ViewTarget},
+    },
+    window::PrimaryWindow,
+};
+use bevy_egui::egui;
+
+use super::compare::CompareLabel;
+use crate::camera::OrbitCamera;
+use crate::inspector::{Editable, aabb_world};
+
+const SHADER_ASSET_PATH: &str = "shaders/debug_view.wgsl";
+
+/// Only the overdraw copies are on it
+const OVERDRAW_LAYER: usize = 29;
+/// What each layer of geometry adds to the overdraw image; the shader divides it back out
+const OVERDRAW_STEP: f32 = 0.1;
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum DebugView {
+    #[default]
+    Off,
+    /// Distance from the camera, near bright
+    Depth,
+    /// World-space normals as colors
+    Normals,
+    /// How many surfaces are drawn on top of each other
+    Overdraw,
+    /// The scene tinted by the sun's shadow cascade covering it
+    ShadowCascades,
+}
+
+impl DebugView {
+    pub const ALL: [Self; 5] = [
+        Self::Off,
+        Self::Depth,
+        Self::Normals,
+        Self::Overdraw,
+        Self::ShadowCascades,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Off => "Off",
+            Self::Depth => "Depth",
+            Self::Normals => "Normals",
+            Self::Overdraw => "Overdraw",
+            Self::ShadowCascades => "Shadow cascades",
+        }
+    }
+
+    /// The mode number the shader switches on.
+    fn mode(self) -> u32 {
+        self as u32
+    }
+}
+
+/// The debug view shown, set from the Effect settings.
+#[derive(Resource, Clone, Copy, PartialEq)]
+pub struct DebugViews {
+    pub view: DebugView,
+    /// Distance shown as black in the depth view
+    pub depth_range: f32,
+    /// Wireframe bounds around every editable object
+    pub aabbs: bool,
+}
+
+impl Default for DebugViews {
+    fn default() -> Self {
+        Self {
+            view: DebugView::Off,
+            depth_range: 40.0,
+            aabbs: false,
+        }
+    }
+}
+
+/// On the main camera while a debug view is shown.
+#[derive(Component, Clone, Copy, ExtractComponent, ShaderType)]
+struct DebugViewSettings {
+    mode: u32,
+    depth_range: f32,
+    overdraw_step: f32,
+    _padding: f32,
+    /// Far bounds of the sun's first four shadow cascades (0 where there's none)
+    cascades: Vec4,
+    /// Undoes the projection, to get view distances back from the depth buffer
+    view_from_clip: Mat4,
+}
+
+/// The camera drawing the overdraw copies.
+#[derive(Component)]
+pub struct OverdrawCamera;
+
+/// The additive copy of an editable's mesh drawn by the overdraw camera (a child of it).
+#[derive(Component)]
+pub struct OverdrawProxy;
+
+/// Where the overdraw camera draws, and what its copies are drawn with.
+#[derive(Resource, Clone, ExtractResource)]
+struct OverdrawTarget {
+    image: Handle<Image>,
+    material: Handle<StandardMaterial>,
+}
+
+pub struct DebugViewPlugin;
+impl Plugin for DebugViewPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            ExtractComponentPlugin::<DebugViewSettings>::default(),
+            UniformComponentPlugin::<DebugViewSettings>::default(),
+            ExtractResourcePlugin::<OverdrawTarget>::default(),
+        ))
+        .init_resource::<DebugViews>()
+        .add_systems(Startup, setup_overdraw_target)
+        .add_systems(
+            Update,
+            (
+                sync_debug_view,
+                sync_overdraw_camera,
+                sync_overdraw_proxies,
+                draw_editable_aabbs,
+            ),
+        );
+
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        // Ahead of the A/B compare, whose right half keeps the normal picture
+        render_app
+            .add_render_graph_node::<ViewNodeRunner<DebugViewNode>>(Core3d, DebugViewLabel)
+            .add_render_graph_edges(
+                Core3d,
+                (
+                    Node3d::EndMainPassPostProcessing,
+                    DebugViewLabel,
+                    CompareLabel,
+                ),
+            );
+    }
+
+    fn finish(&self, app: &mut App) {
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app.init_resource::<DebugViewPipeline>();
+    }
+}
+
+fn overdraw_image(size: UVec2) -> Image {
+    let mut image = Image::new_fill(
+        Extent3d {
+            width: size.x.max(1),
+            height: size.y.max(1),
+            ..default()
+        },
+        TextureDimension::D2,
+        &[0; 8],
+        // Float, so layers keep adding up past what 8 bits could count
+        TextureFormat::Rgba16Float,
+        RenderAssetUsages::default(),
+    );
+    image.texture_descriptor.usage =
+        TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST | TextureUsages::RENDER_ATTACHMENT;
+    image
+}
+
+fn setup_overdraw_target(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let image = images.add(overdraw_image(UVec2::ONE));
+    let material = materials.add(StandardMaterial {
+        base_color: Color::linear_rgb(OVERDRAW_STEP, OVERDRAW_STEP, OVERDRAW_STEP),
+        unlit: true,
+        alpha_mode: AlphaMode::Add,
+        ..default()
+    });
+    commands.insert_resource(OverdrawTarget { image, material });
+}
+
+/// Keep the main camera's debug pass settings up to date, and give it the textures the view
+/// reads: a bindable depth texture, and the normal prepass while it's shown.
+fn sync_debug_view(
+    mut commands: Commands,
+    views: Res<DebugViews>,
+    mut q_main: Query<
+        (Entity, &Camera, &mut Camera3d, Has<NormalPrepass>),
+        (With<OrbitCamera>, Without<OverdrawCamera>),
+    >,
+    q_sun: Query<(&DirectionalLight, &CascadeShadowConfig)>,
+    // Whether the normal prepass was added for the view (and so goes again with it)
+    mut added_normals: Local<bool>,
+) {
+    let Ok((main_e, camera, mut camera_3d, has_normals)) = q_main.single_mut() else {
+        return;
+    };
+    let want_normals = views.view == DebugView::Normals;
+    if want_normals && !has_normals {
+        commands.entity(main_e).insert(NormalPrepass);
+        *added_normals = true;
+    } else if !want_normals && *added_normals {
+        commands.entity(main_e).remove::<NormalPrepass>();
+        *added_normals = false;
+    }
+
+    if views.view == DebugView::Off {
+        commands.entity(main_e).remove::<DebugViewSettings>();
+        return;
+    }
+    let usages = TextureUsages::from(camera_3d.depth_texture_usages);
+    if !usages.contains(TextureUsages::TEXTURE_BINDING) {
+        camera_3d.depth_texture_usages = (usages | TextureUsages::TEXTURE_BINDING).into();
+    }
+
+    let mut cascades = [0.0; 4];
+    if let Some((_, config)) = q_sun.iter().find(|(light, _)| light.shadows_enabled) {
+        for (bound, far) in cascades.iter_mut().zip(&config.bounds) {
+            *bound = *far;
+        }
+    }
+    commands.entity(main_e).insert(DebugViewSettings {
+        mode: views.view.mode(),
+        depth_range: views.depth_range.max(0.1),
+        overdraw_step: OVERDRAW_STEP,
+        _padding: 0.0,
+        cascades: Vec4::from_array(cascades),
+        view_from_clip: camera.clip_from_view().inverse(),
+    });
+}
+
+/// While the overdraw view is on, keep a camera drawing the overdraw copies from the main
+/// camera's point of view, at the window's size.
+fn sync_overdraw_camera(
+    mut commands: Commands,
+    views: Res<DebugViews>,
+    target: Res<OverdrawTarget>,
+    mut images: ResMut<Assets<Image>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    q_main: Query<(&Camera, &Transform, &Projection), (With<OrbitCamera>, Without<OverdrawCamera>)>,
+    mut q_overdraw: Query<
+        (Entity, &mut Camera, &mut Transform, &mut Projection),
+        With<OverdrawCamera>,
+    >,
+) {
+    let (Ok(window), Ok((main_cam, main_tf, main_proj)), DebugView::Overdraw) =
+        (windows.single(), q_main.single(), views.view)
+    else {
+        for (e, ..) in &q_overdraw {
+            commands.entity(e).despawn();
+        }
+        return;
+    };
+
+    let size = window.physical_size();
+    let want = Extent3d {
+        width: size.x.max(1),
+        height: size.y.max(1),
+        ..default()
+    };
+    let current = images.get(&target.image).map(|i| i.texture_descriptor.size);
+    if current.is_some_and(|s| s != want)
+        && let Some(image) = images.get_mut(&target.image)
+    {
+        image.resize(want);
+    }
+
+    match q_overdraw.single_mut() {
+        Ok((_, mut camera, mut tf, mut proj)) => {
+            camera.viewport = main_cam.viewport.clone();
+            tf.set_if_neq(*main_tf);
+            *proj = main_proj.clone();
+        }
+        Err(_) => {
+            commands.spawn((
+                Camera3d::default(),
+                Camera {
+                    target: RenderTarget::Image(target.image.clone().into()),
+                    order: -1,
+                    viewport: main_cam.viewport.clone(),
+                    // Layers add up in float, and come out as they went in
+                    hdr: true,
+                    clear_color: ClearColorConfig::Custom(Color::BLACK),
+                    ..default()
+                },
+                Tonemapping::None,
+                *main_tf,
+                main_proj.clone(),
+                Msaa::Off,
+                RenderLayers::layer(OVERDRAW_LAYER),
+                OverdrawCamera,
+                Name::new("OverdrawCamera"),
+            ));
+        }
+    }
+}
+
+/// Give every editable mesh an additive copy for the overdraw camera while the view is on, and
+/// drop them after.
+fn sync_overdraw_proxies(
+    mut commands: Commands,
+    views: Res<DebugViews>,
+    target: Res<OverdrawTarget>,
+    q_editables: Query<(Entity, &Mesh3d), With<Editable>>,
+    mut q_proxies: Query<(Entity, &ChildOf, &mut Mesh3d), (With<OverdrawProxy>, Without<Editable>)>,
+) {
+    if views.view != DebugView::Overdraw {
+        for (e, ..) in &q_proxies {
+            commands.entity(e).despawn();
+        }
+        return;
+    }
+    let mut covered = HashSet::new();
+    for (e, child_of, mut mesh) in &mut q_proxies {
+        match q_editables.get(child_of.parent()) {
+            Ok((parent, parent_mesh)) => {
+                if mesh.0 != parent_mesh.0 {
+                    mesh.0 = parent_mesh.0.clone();
+                }
+                covered.insert(parent);
+            }
+            Err(_) => commands.entity(e).despawn(),
+        }
+    }
+    for (e, mesh) in &q_editables {
+        if covered.contains(&e) {
+            continue;
+        }
+        commands.entity(e).with_child((
+            Mesh3d(mesh.0.clone()),
+            MeshMaterial3d(target.material.clone()),
+            Transform::IDENTITY,
+            NotShadowCaster,
+            RenderLayers::layer(OVERDRAW_LAYER),
+            OverdrawProxy,
+            Name::new("OverdrawProxy"),
+        ));
+    }
+}
+
+fn draw_editable_aabbs(
+    views: Res<DebugViews>,
+    mut gizmos: Gizmos,
+    q_editables: Query<(&GlobalTransform, &Aabb, &InheritedVisibility), With<Editable>>,
+) {
+    if !views.aabbs {
+        return;
+    }
+    let color = Color::srgb(1.0, 0.75, 0.2);
+    for (gt, aabb, visibility) in &q_editables {
+        if !visibility.get() {
+            continue;
+        }
+        let world = aabb_world(*aabb, gt);
+        gizmos.cuboid(
+            Transform::from_translation(world.center.into())
+                .with_scale(Vec3::from(world.half_extents * 2.0).max(Vec3::splat(0.0001))),
+            color,
+        );
+    }
+}
+
+/// Effect settings section.
+pub fn debug_view_ui(ui: &mut egui::Ui, views: &mut DebugViews) {
+    egui::ComboBox::from_label("View")
+        .selected_text(views.view.label())
+        .show_ui(ui, |ui| {
+            for view in DebugView::ALL {
+                ui.selectable_value(&mut views.view, view, view.label());
+            }
+        });
+    match views.view {
+        DebugView::Depth => {
+            ui.add(
+                egui::Slider::new(&mut views.depth_range, 1.0..=200.0)
+                    .logarithmic(true)
+                    .text("Depth range"),
+            );
+        }
+        DebugView::Overdraw => {
+            ui.weak("Blue: drawn once; green, yellow, red: 2, 3, 4 layers; white: 8 or more.");
+        }
+        DebugView::ShadowCascades => {
+            ui.weak("Red, green, blue, yellow: the sun's first to fourth shadow cascade.");
+        }
+        DebugView::Off | DebugView::Normals => {}
+    }
+    ui.checkbox(&mut views.aabbs, "Bounding boxes")
+        .on_hover_text("A wireframe box around every editable object");
+}
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+struct DebugViewLabel;
+
+#[derive(Default)]
+struct DebugViewNode;
+
+impl ViewNode for DebugViewNode {
+    type ViewQuery = (
+        &'static ViewTarget,
+        &'static ViewDepthTexture,
+        Option<&'static ViewPrepassTextures>,
+        &'static DebugViewSettings,
+        &'static DynamicUniformIndex<DebugViewSettings>,
+    );
+
+    fn run(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        (view_target, depth, prepass, _settings, settings_index): QueryItem<Self::ViewQuery>,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let debug_pipeline = world.resource::<DebugViewPipeline>();
+        let pipeline_cache = world.resource::<PipelineCache>();
+        let Some(pipeline) = pipeline_cache.get_render_pipeline(debug_pipeline.pipeline_id) else {
+            return Ok(());
+        };
+        let Some(target) = world.get_resource::<OverdrawTarget>() else {
+            return Ok(());
+        };
+        let Some(overdraw) = world
+            .resource::<RenderAssets<GpuImage>>()
+            .get(&target.image)
+        else {
+            return Ok(());
+        };
+        let settings_uniforms = world.resource::<ComponentUniforms<DebugViewSettings>>();
+        let Some(settings_binding) = settings_uniforms.uniforms().binding() else {
+            return Ok(());
+        };
+        // Without a normal prepass the normals view shows black
+        let normals = prepass
+            .and_then(|p| p.normal_view())
+            .unwrap_or(&world.resource::<FallbackImage>().d2.texture_view);
+
+        let post_process = view_target.post_process_write();
+
+        let bind_group = render_context.render_device().create_bind_group(
+            "debug_view_bind_group",
+            &debug_pipeline.layout,
+            &BindGroupEntries::sequential((
+                post_process.source,
+                &debug_pipeline.sampler,
+                settings_binding.clone(),
+                depth.view(),
+                normals,
+                &overdraw.texture_view,
+            )),
+        );
+
+        let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+            label: Some("debug_view_pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: post_process.destination,
+                resolve_target: None,
+                ops: Operations::default(),
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+
+        render_pass.set_render_pipeline(pipeline);
+        render_pass.set_bind_group(0, &bind_group, &[settings_index.index()]);
+        render_pass.draw(0..3, 0..1);
+
+        Ok(())
+    }
+}
+
+#[derive(Resource)]
+struct DebugViewPipeline {
+    layout: BindGroupLayout,
+    sampler: Sampler,
+    pipeline_id: CachedRenderPipelineId,
+}
+
+impl FromWorld for DebugViewPipeline {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+
+        let layout = render_device.create_bind_group_layout(
+            "debug_view_bind_group_layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::FRAGMENT,
+                (
+                    texture_2d(TextureSampleType::Float { filterable: true }),
+                    sampler(SamplerBindingType::Filtering),
+                    uniform_buffer::<DebugViewSettings>(true),
+                    // Main view depth (reverse-Z)
+                    texture_depth_2d(),
+                    // Normal prepass
+                    texture_2d(TextureSampleType::Float { filterable: true }),
+                    // Overdraw image
+                    texture_2d(TextureSampleType::Float { filterable: true }),
+                ),
+            ),
+        );
+
+        let sampler = render_device.create_sampler(&SamplerDescriptor::default());
+
+        let shader = world.load_asset(SHADER_ASSET_PATH);
+
+        let pipeline_id =
+            world
+                .resource_mut::<PipelineCache>()
+                .queue_render_pipeline(RenderPipelineDescriptor {
+                    label: Some("debug_view_pipeline".into()),
+                    layout: vec![layout.clone()],
+                    vertex: fullscreen_shader_vertex_state(),
+                    fragment: Some(FragmentState {
+                        shader,
+                        shader_defs: vec![],
+                        entry_point: "fragment".into(),
+                        targets: vec![Some(ColorTargetState {
+                            format: TextureFormat::bevy_default(),
+                            blend: None,
+                            write_mask: ColorWrites::ALL,
+                        })],
+                    }),
+                    primitive: PrimitiveState::default(),
+                    depth_stencil: None,
+                    multisample: MultisampleState::default(),
+                    push_constant_ranges: vec![],
+                    zero_initialize_workgroup_memory: false,
+                });
+
+        Self {
+            layout,
+            sampler,
+            pipeline_id,
+        }
+    }
+}
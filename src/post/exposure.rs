@@ -0,0 +1,214 @@
+//! Camera exposure: a fixed EV, or auto exposure that meters the frame and eases toward a
+//! mid-grey average within EV bounds, so bright skyboxes and dark interiors both read.
+//!
+//! Bevy's own auto exposure needs an HDR camera and compute shaders, so instead the meter is
+//! the scopes' downsampled readback of the post chain's output. It measures what's on screen
+//! (after tone mapping, LUT and tint), which is what auto exposure is meant to even out.
+
+use bevy::{prelude::*, render::camera::Exposure};
+use bevy_egui::egui;
+use serde::{Deserialize, Serialize};
+
+use super::scopes::Scopes;
+
+/// Average displayed luminance auto exposure aims for (mid grey)
+const TARGET_LUMA: f32 = 0.18;
+/// Share of the darkest and of the brightest pixels the meter leaves out
+const METER_CLIP: f32 = 0.1;
+
+/// How the cameras are exposed (kept in the app settings).
+#[derive(Resource, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExposureControl {
+    /// Adapt to the frame; otherwise `manual_ev` is used as is
+    pub auto: bool,
+    /// EV100 while auto exposure is off; higher is darker
+    pub manual_ev: f32,
+    /// Bounds auto exposure stays within
+    pub min_ev: f32,
+    pub max_ev: f32,
+    /// How fast auto exposure adapts, in EV per second
+    pub speed: f32,
+}
+
+impl Default for ExposureControl {
+    fn default() -> Self {
+        Self {
+            auto: false,
+            manual_ev: Exposure::EV100_BLENDER,
+            min_ev: 6.0,
+            max_ev: 15.0,
+            speed: 1.5,
+        }
+    }
+}
+
+/// Where auto exposure is, and the newest reading it has acted on.
+#[derive(Default)]
+struct Adaptation {
+    ev: Option<f32>,
+    target: f32,
+    reading: u64,
+}
+
+pub struct ExposurePlugin;
+impl Plugin for ExposurePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ExposureControl>()
+            .init_resource::<Scopes>()
+            .add_systems(Update, adapt_exposure);
+    }
+}
+
+/// Log2 of the mean linear luminance in a luma histogram of sRGB code values, leaving out the
+/// darkest and brightest [`METER_CLIP`] so a black border or a few lamps don't swing it.
+fn metered_log_luma(histogram: &[u32; 256]) -> Option<f32> {
+    let total = histogram.iter().sum::<u32>() as f32;
+    let (low, high) = (total * METER_CLIP, total * (1.0 - METER_CLIP));
+    let (mut seen, mut sum, mut weight) = (0.0, 0.0, 0.0);
+    for (code, &count) in histogram.iter().enumerate() {
+        let (start, end) = (seen, seen + count as f32);
+        seen = end;
+        let kept = end.min(high) - start.max(low);
+        if kept <= 0.0 {
+            continue;
+        }
+        // A 2.2 power is near enough to the sRGB curve for metering
+        let linear = (code as f32 / 255.0).powf(2.2).max(1.0 / 1024.0);
+        sum += kept * linear.log2();
+        weight += kept;
+    }
+    (weight > 0.0).then(|| sum / weight)
+}
+
+/// Meter while auto exposure is on and give every 3D camera the resulting EV, so the
+/// reflection and compare cameras match the main one.
+fn adapt_exposure(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    control: Res<ExposureControl>,
+    mut scopes: ResMut<Scopes>,
+    mut state: Local<Adaptation>,
+    mut q_cams: Query<(Entity, Option<&mut Exposure>), With<Camera3d>>,
+) {
+    if scopes.metering != control.auto {
+        scopes.metering = control.auto;
+    }
+    let (min_ev, max_ev) = (
+        control.min_ev.min(control.max_ev),
+        control.max_ev.max(control.min_ev),
+    );
+
+    let ev = if control.auto {
+        let ev = state.ev.unwrap_or(control.manual_ev);
+        if scopes.readings != state.reading {
+            state.reading = scopes.readings;
+            // Brighter than mid grey by some stops calls for that many more EV
+            if let Some(log_luma) = metered_log_luma(&scopes.histogram) {
+                state.target = ev + log_luma - TARGET_LUMA.log2();
+            }
+        } else if state.ev.is_none() {
+            state.target = ev;
+        }
+        let target = state.target.clamp(min_ev, max_ev);
+        let step = control.speed.max(0.01) * time.delta_secs();
+        ev + (target - ev).clamp(-step, step)
+    } else {
+        control.manual_ev
+    };
+    // Auto exposure picks up from the manual EV whenever it's switched back on
+    state.ev = control.auto.then_some(ev);
+
+    for (e, exposure) in &mut q_cams {
+        match exposure {
+            Some(mut exposure) if exposure.ev100 != ev => exposure.ev100 = ev,
+            Some(_) => {}
+            None => {
+                commands.entity(e).insert(Exposure { ev100: ev });
+            }
+        }
+    }
+}
+
+/// The Exposure section of the Effect settings panel. `current_ev` is the main camera's.
+pub fn exposure_ui(ui: &mut egui::Ui, control: &mut ExposureControl, current_ev: f32) {
+    ui.checkbox(&mut control.auto, "Auto exposure")
+        .on_hover_text("Adapt to how bright the view is, within the bounds below");
+    if control.auto {
+        ui.add(egui::Slider::new(&mut control.min_ev, 0.0..=18.0).text("Min EV"))
+            .on_hover_text("Brightest it gets, for dark interiors");
+        ui.add(egui::Slider::new(&mut control.max_ev, 0.0..=18.0).text("Max EV"))
+            .on_hover_text("Darkest it gets, for bright skies");
+        ui.add(
+            egui::Slider::new(&mut control.speed, 0.1..=8.0)
+                .logarithmic(true)
+                .text("Speed (EV/s)"),
+        );
+        ui.horizontal(|ui| {
+            ui.label(format!("Current: {current_ev:.1} EV"));
+            if ui
+                .button("Hold")
+                .on_hover_text("Switch to manual exposure at the current EV")
+                .clicked()
+            {
+                control.manual_ev = current_ev;
+                control.auto = false;
+            }
+        });
+    } else {
+        ui.add(egui::Slider::new(&mut control.manual_ev, 0.0..=18.0).text("EV"))
+            .on_hover_text("Higher is darker");
+    }
+    if ui.button("Reset exposure").clicked() {
+        *control = ExposureControl::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log_luma(code: usize) -> f32 {
+        (code as f32 / 255.0).powf(2.2).log2()
+    }
+
+    #[test]
+    fn empty_frames_give_no_reading() {
+        assert_eq!(metered_log_luma(&[0; 256]), None);
+    }
+
+    #[test]
+    fn a_flat_frame_meters_its_own_luma() {
+        let mut histogram = [0; 256];
+        histogram[128] = 1000;
+        let metered = metered_log_luma(&histogram).unwrap();
+        assert!((metered - log_luma(128)).abs() < 1e-5, "{metered}");
+    }
+
+    #[test]
+    fn the_darkest_and_brightest_tenth_are_left_out() {
+        let mut histogram = [0; 256];
+        histogram[0] = 100;
+        histogram[128] = 800;
+        histogram[255] = 100;
+        let metered = metered_log_luma(&histogram).unwrap();
+        assert!((metered - log_luma(128)).abs() < 1e-5, "{metered}");
+    }
+
+    #[test]
+    fn black_is_floored_rather_than_infinite() {
+        let mut histogram = [0; 256];
+        histogram[0] = 1000;
+        assert_eq!(metered_log_luma(&histogram), Some(-10.0));
+    }
+
+    #[test]
+    fn brighter_frames_meter_higher() {
+        let (mut dim, mut bright) = ([0; 256], [0; 256]);
+        dim[40] = 500;
+        dim[90] = 500;
+        bright[90] = 500;
+        bright[200] = 500;
+        assert!(metered_log_luma(&bright) > metered_log_luma(&dim));
+    }
+}
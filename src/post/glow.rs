@@ -0,0 +1,117 @@
+//! Glow-only bloom. Turning up the bloom also makes sunlit white surfaces bleed, since bloom
+//! picks up anything bright. With [`BloomIsolation::glow_only`] on, the bloom only takes what's
+//! brighter than a threshold set above any lit surface, and objects flagged [`Glow`] in the
+//! inspector are drawn with their emissive lifted over it (their own material, the one saved,
+//! is left alone; see `stylized`).
+
+use bevy::core_pipeline::bloom::Bloom;
+use bevy::prelude::*;
+use bevy_egui::egui;
+use serde::{Deserialize, Serialize};
+
+/// Set from the Bloom controls in the Effect settings.
+#[derive(Resource, Clone, Copy, PartialEq)]
+pub struct BloomIsolation {
+    pub glow_only: bool,
+    /// Brightness (linear, before tonemapping) below which nothing blooms while `glow_only`
+    /// is on. Full sun on a white surface comes to about 3.
+    pub threshold: f32,
+}
+
+impl Default for BloomIsolation {
+    fn default() -> Self {
+        Self {
+            glow_only: false,
+            threshold: 4.0,
+        }
+    }
+}
+
+/// Persisted on an object whose emissive (or, if it has none, its color) should bloom while the
+/// bloom is glow-only.
+#[derive(Component, Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Glow {
+    /// How far over the threshold its brightest channel is lifted: 1 is twice the threshold
+    pub strength: f32,
+}
+
+impl Default for Glow {
+    fn default() -> Self {
+        Self { strength: 1.0 }
+    }
+}
+
+impl Glow {
+    /// The emissive to draw `material` with, so it clears `threshold`.
+    pub fn emissive(&self, material: &StandardMaterial, threshold: f32) -> LinearRgba {
+        let source = if material.emissive.to_vec3().max_element() > 0.0 {
+            material.emissive
+        } else {
+            material.base_color.to_linear()
+        };
+        let peak = source.to_vec3().max_element().max(1e-4);
+        let gain = threshold.max(0.0) * (1.0 + self.strength.max(0.0)) / peak;
+        LinearRgba::rgb(source.red * gain, source.green * gain, source.blue * gain)
+    }
+}
+
+pub struct GlowPlugin;
+impl Plugin for GlowPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BloomIsolation>()
+            .add_systems(PostUpdate, apply_bloom_threshold);
+    }
+}
+
+/// Keep every camera's bloom threshold in line with the isolation setting.
+fn apply_bloom_threshold(isolation: Res<BloomIsolation>, mut q_bloom: Query<&mut Bloom>) {
+    let (threshold, softness) = if isolation.glow_only {
+        (isolation.threshold.max(0.0), 0.1)
+    } else {
+        (0.0, 0.0)
+    };
+    for mut bloom in &mut q_bloom {
+        if !isolation.is_changed() && !bloom.is_added() {
+            continue;
+        }
+        bloom.prefilter.threshold = threshold;
+        bloom.prefilter.threshold_softness = softness;
+    }
+}
+
+/// Inspector section: flag the object to bloom while the bloom is glow-only.
+pub fn glow_inspector_ui(
+    ui: &mut egui::Ui,
+    commands: &mut Commands,
+    entity: Entity,
+    glow: Option<Mut<Glow>>,
+    glow_only: bool,
+) -> bool {
+    let mut on = glow.is_some();
+    if ui
+        .checkbox(&mut on, "Glow")
+        .on_hover_text("Blooms while Effect settings → Bloom is set to glow objects only")
+        .changed()
+    {
+        if on {
+            commands.entity(entity).insert(Glow::default());
+        } else {
+            commands.entity(entity).remove::<Glow>();
+        }
+        return true;
+    }
+    let Some(mut glow) = glow else {
+        return false;
+    };
+    let mut strength = glow.strength;
+    ui.add(egui::Slider::new(&mut strength, 0.0..=4.0).text("Glow strength"));
+    if !glow_only {
+        ui.weak("Bloom isn't glow-only: every bright object blooms.");
+    }
+    if glow.strength == strength {
+        return false;
+    }
+    glow.strength = strength;
+    true
+}
@@ -0,0 +1,284 @@
+//! Photosensitive-safe effect limiter. While the [`EffectGovernor`] is on, every limited
+//! post effect is shown at no more than a fraction of its full strength, and its strengths
+//! (turning it on or off included) change no faster than `max_flash_hz` full flashes a second,
+//! however fast a tween, script or MIDI knob drives them.
+//!
+//! The eased strengths are kept on the camera as [`Governed<T>`]; each effect's
+//! `ExtractComponent` impl swaps them in and applies the cap, so its shader only ever sees
+//! governed values.
+
+use std::marker::PhantomData;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Limits for every governed post effect (kept in the app settings).
+#[derive(Resource, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EffectGovernor {
+    pub enabled: bool,
+    /// Cap on each strength, as a fraction of its slider's full range
+    pub max_strength: f32,
+    /// Fastest full off-on-off swing a strength can make; 3 is the WCAG flash threshold
+    pub max_flash_hz: f32,
+}
+
+impl Default for EffectGovernor {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_strength: 0.5,
+            max_flash_hz: 2.0,
+        }
+    }
+}
+
+/// A post effect's settings whose strengths the governor limits (up to four per effect).
+pub trait Governable: Component + Copy {
+    /// What each strength counts as at full range; 0 leaves the slot out
+    const FULL: [f32; 4];
+    /// Which strengths are capped; the others (a scanline count) are only slowed
+    const CAPPED: [bool; 4];
+
+    fn is_enabled(&self) -> bool;
+    fn set_enabled(&mut self, enabled: bool);
+    fn strengths(&self) -> [f32; 4];
+    fn set_strengths(&mut self, strengths: [f32; 4]);
+}
+
+/// On a camera while the governor is on: the strengths `T` is shown at.
+#[derive(Component, Clone, Copy)]
+pub struct Governed<T: Governable> {
+    shown: [f32; 4],
+    /// Whether anything is still visible (a disabled effect fades out first)
+    visible: bool,
+    max_strength: f32,
+    _effect: PhantomData<T>,
+}
+
+impl<T: Governable> Governed<T> {
+    fn cap(max_strength: f32, strengths: [f32; 4]) -> [f32; 4] {
+        let mut out = strengths;
+        for ((v, full), capped) in out.iter_mut().zip(T::FULL).zip(T::CAPPED) {
+            if capped {
+                *v = v.clamp(-full * max_strength, full * max_strength);
+            }
+        }
+        out
+    }
+
+    /// `settings` as the shader should get them. Called from the extract phase.
+    pub fn apply(&self, settings: &T) -> T {
+        let mut out = *settings;
+        out.set_enabled(self.visible);
+        out.set_strengths(Self::cap(self.max_strength, self.shown));
+        out
+    }
+}
+
+/// Governs the effects that are compiled in; part of `PostEffectsPlugins`.
+pub struct EffectGovernorPlugin;
+impl Plugin for EffectGovernorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EffectGovernor>();
+        // After whatever drove the settings this frame
+        #[cfg(feature = "chroma")]
+        app.add_systems(
+            PostUpdate,
+            govern::<super::chroma_aberration::ChromaAberrationSettings>,
+        );
+        #[cfg(feature = "crt")]
+        app.add_systems(PostUpdate, govern::<super::crt::CRTSettings>);
+        #[cfg(feature = "gradient")]
+        app.add_systems(
+            PostUpdate,
+            govern::<super::gradient_tint::GradientTintSettings>,
+        );
+        app.add_systems(PostUpdate, govern::<super::lens_flare::LensFlareSettings>);
+    }
+}
+
+/// Ease the shown strengths of `T` toward its settings, within the governor's limits.
+fn govern<T: Governable>(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    governor: Res<EffectGovernor>,
+    mut q: Query<(Entity, &T, Option<&mut Governed<T>>)>,
+) {
+    for (e, settings, governed) in &mut q {
+        if !governor.enabled {
+            if governed.is_some() {
+                commands.entity(e).remove::<Governed<T>>();
+            }
+            continue;
+        }
+        let max_strength = governor.max_strength.clamp(0.0, 1.0);
+        let mut target = Governed::<T>::cap(max_strength, settings.strengths());
+        if !settings.is_enabled() {
+            // Switching off fades out instead
+            for (t, capped) in target.iter_mut().zip(T::CAPPED) {
+                if capped {
+                    *t = 0.0;
+                }
+            }
+        }
+        let Some(mut governed) = governed else {
+            // Turning the governor on mid-effect starts from what's on screen, capped
+            commands.entity(e).insert(Governed::<T> {
+                shown: target,
+                visible: settings.is_enabled(),
+                max_strength,
+                _effect: PhantomData,
+            });
+            continue;
+        };
+
+        // A full swing up and back down takes at least 1 / max_flash_hz seconds
+        let swings = 2.0 * governor.max_flash_hz.max(0.1);
+        let dt = time.delta_secs();
+        let mut shown = governed.shown;
+        for ((s, t), full) in shown.iter_mut().zip(target).zip(T::FULL) {
+            let step = full * max_strength.max(0.05) * swings * dt;
+            *s += (t - *s).clamp(-step, step);
+        }
+        // Off once faded all the way out, so the pass is skipped again
+        let faded = shown
+            .iter()
+            .zip(T::CAPPED)
+            .all(|(s, capped)| !capped || *s == 0.0);
+        let visible = settings.is_enabled() || !faded;
+        if (shown, visible, max_strength)
+            != (governed.shown, governed.visible, governed.max_strength)
+        {
+            governed.shown = shown;
+            governed.visible = visible;
+            governed.max_strength = max_strength;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    /// A strength capped at 1 and a count that's only slowed.
+    #[derive(Component, Clone, Copy)]
+    struct Effect {
+        enabled: bool,
+        strength: f32,
+        count: f32,
+    }
+
+    impl Governable for Effect {
+        const FULL: [f32; 4] = [1.0, 100.0, 0.0, 0.0];
+        const CAPPED: [bool; 4] = [true, false, false, false];
+
+        fn is_enabled(&self) -> bool {
+            self.enabled
+        }
+        fn set_enabled(&mut self, enabled: bool) {
+            self.enabled = enabled;
+        }
+        fn strengths(&self) -> [f32; 4] {
+            [self.strength, self.count, 0.0, 0.0]
+        }
+        fn set_strengths(&mut self, strengths: [f32; 4]) {
+            [self.strength, self.count, ..] = strengths;
+        }
+    }
+
+    /// A world governing one `Effect` at half strength and one flash a second.
+    fn governed_world(effect: Effect) -> (World, Entity) {
+        let mut world = World::new();
+        world.insert_resource(Time::<Real>::default());
+        world.insert_resource(EffectGovernor {
+            enabled: true,
+            max_strength: 0.5,
+            max_flash_hz: 1.0,
+        });
+        let e = world.spawn(effect).id();
+        world.run_system_once(govern::<Effect>).unwrap();
+        (world, e)
+    }
+
+    /// Run the governor `secs` later.
+    fn step(world: &mut World, secs: f32) {
+        world
+            .resource_mut::<Time<Real>>()
+            .advance_by(Duration::from_secs_f32(secs));
+        world.run_system_once(govern::<Effect>).unwrap();
+    }
+
+    fn on_screen(world: &World, e: Entity) -> Effect {
+        let effect = world.get::<Effect>(e).unwrap();
+        world.get::<Governed<Effect>>(e).unwrap().apply(effect)
+    }
+
+    #[test]
+    fn only_capped_strengths_are_capped() {
+        let capped = Governed::<Effect>::cap(0.5, [3.0, 300.0, 0.0, 0.0]);
+        assert_eq!(capped, [0.5, 300.0, 0.0, 0.0]);
+        let capped = Governed::<Effect>::cap(0.5, [-3.0, -300.0, 0.0, 0.0]);
+        assert_eq!(capped, [-0.5, -300.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn turning_on_starts_from_the_capped_settings() {
+        let (world, e) = governed_world(Effect {
+            enabled: true,
+            strength: 1.0,
+            count: 50.0,
+        });
+        let shown = on_screen(&world, e);
+        assert!(shown.enabled);
+        assert_eq!((shown.strength, shown.count), (0.5, 50.0));
+    }
+
+    #[test]
+    fn strengths_change_no_faster_than_the_flash_rate() {
+        let (mut world, e) = governed_world(Effect {
+            enabled: true,
+            strength: 0.0,
+            count: 0.0,
+        });
+        let mut effect = world.get_mut::<Effect>(e).unwrap();
+        effect.strength = 1.0;
+        effect.count = 100.0;
+        // Two half-strength swings a second: 0.1 s moves 0.1 of the full range
+        step(&mut world, 0.1);
+        let shown = on_screen(&world, e);
+        assert!((shown.strength - 0.1).abs() < 1e-5, "{}", shown.strength);
+        assert!((shown.count - 10.0).abs() < 1e-3, "{}", shown.count);
+    }
+
+    #[test]
+    fn switching_off_fades_out_first() {
+        let (mut world, e) = governed_world(Effect {
+            enabled: true,
+            strength: 0.5,
+            count: 0.0,
+        });
+        world.get_mut::<Effect>(e).unwrap().enabled = false;
+        step(&mut world, 0.1);
+        let shown = on_screen(&world, e);
+        assert!(shown.enabled && shown.strength > 0.0);
+        step(&mut world, 1.0);
+        assert!(!on_screen(&world, e).enabled);
+    }
+
+    #[test]
+    fn turning_the_governor_off_lets_go() {
+        let (mut world, e) = governed_world(Effect {
+            enabled: true,
+            strength: 1.0,
+            count: 0.0,
+        });
+        world.resource_mut::<EffectGovernor>().enabled = false;
+        world.run_system_once(govern::<Effect>).unwrap();
+        assert!(world.get::<Governed<Effect>>(e).is_none());
+    }
+}
@@ -23,6 +23,8 @@ use bevy::{
     },
 };
 
+use super::governor::{Governable, Governed};
+
 const SHADER_ASSET_PATH: &str = "shaders/gradient_tint.wgsl";
 
 pub struct GradientTintPlugin;
@@ -182,7 +184,7 @@ impl FromWorld for PostProcessPipeline {
     }
 }
 
-#[derive(Component, Default, Clone, Copy, ExtractComponent, ShaderType)]
+#[derive(Component, Default, Clone, Copy, ShaderType)]
 #[repr(C, align(16))]
 pub struct GradientTintSettings {
     pub enabled: u32, // ShaderType doesn't support bool
@@ -191,3 +193,34 @@ pub struct GradientTintSettings {
     pub color_top_right: Vec4,   // RGBA
     pub color_bottom_left: Vec4, // RGBA
 }
+
+impl ExtractComponent for GradientTintSettings {
+    type QueryData = (&'static Self, Option<&'static Governed<Self>>);
+    type QueryFilter = ();
+    type Out = Self;
+
+    fn extract_component((settings, governed): QueryItem<'_, Self::QueryData>) -> Option<Self> {
+        Some(governed.map_or(*settings, |g| g.apply(settings)))
+    }
+}
+
+impl Governable for GradientTintSettings {
+    const FULL: [f32; 4] = [1.0, 0.0, 0.0, 0.0];
+    const CAPPED: [bool; 4] = [true, false, false, false];
+
+    fn is_enabled(&self) -> bool {
+        self.enabled != 0
+    }
+
+    fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled as u32;
+    }
+
+    fn strengths(&self) -> [f32; 4] {
+        [self.strength, 0.0, 0.0, 0.0]
+    }
+
+    fn set_strengths(&mut self, strengths: [f32; 4]) {
+        self.strength = strengths[0];
+    }
+}
@@ -0,0 +1,321 @@
+use bevy::{
+    core_pipeline::{
+        core_3d::graph::{Core3d, Node3d},
+        fullscreen_vertex_shader::fullscreen_shader_vertex_state,
+    },
+    ecs::query::QueryItem,
+    prelude::*,
+    render::{
+        RenderApp,
+        extract_component::{
+            ComponentUniforms, DynamicUniformIndex, ExtractComponent, ExtractComponentPlugin,
+            UniformComponentPlugin,
+        },
+        render_graph::{
+            NodeRunError, RenderGraphApp, RenderGraphContext, RenderLabel, ViewNode, ViewNodeRunner,
+        },
+        render_resource::{
+            binding_types::{sampler, texture_2d, texture_depth_2d, uniform_buffer},
+            *,
+        },
+        renderer::{RenderContext, RenderDevice},
+        view::{ViewDepthTexture, ViewTarget},
+    },
+};
+
+use super::governor::{Governable, Governed};
+
+const SHADER_ASSET_PATH: &str = "shaders/lens_flare.wgsl";
+
+/// Max flare sources per view (the sun + a few bright emissives).
+pub const MAX_FLARE_SOURCES: usize = 4;
+
+/// How far along the light direction we place the sun when projecting it to the screen.
+const SUN_DISTANCE: f32 = 500.0;
+
+pub struct LensFlarePlugin;
+
+impl Plugin for LensFlarePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            ExtractComponentPlugin::<LensFlareSettings>::default(),
+            UniformComponentPlugin::<LensFlareSettings>::default(),
+        ))
+        .add_systems(
+            Update,
+            (enable_depth_texture_binding, update_lens_flare_sources),
+        );
+
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        render_app
+            .add_render_graph_node::<ViewNodeRunner<PostProcessNode>>(Core3d, PostProcessLabel)
+            .add_render_graph_edges(
+                Core3d,
+                (
+                    Node3d::Tonemapping,
+                    PostProcessLabel,
+                    Node3d::EndMainPassPostProcessing,
+                ),
+            );
+    }
+
+    fn finish(&self, app: &mut App) {
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        render_app.init_resource::<PostProcessPipeline>();
+    }
+}
+
+/// The flare pass samples the main depth texture for occlusion, so it must be bindable.
+fn enable_depth_texture_binding(mut q: Query<&mut Camera3d, Added<LensFlareSettings>>) {
+    for mut camera_3d in &mut q {
+        let usages = TextureUsages::from(camera_3d.depth_texture_usages);
+        camera_3d.depth_texture_usages = (usages | TextureUsages::TEXTURE_BINDING).into();
+    }
+}
+
+/// Project the sun (and optionally bright emissive objects) into screen space for the shader.
+fn update_lens_flare_sources(
+    mut q_cam: Query<(&Camera, &GlobalTransform, &mut LensFlareSettings)>,
+    q_sun: Query<&GlobalTransform, With<DirectionalLight>>,
+    q_emissive: Query<(&GlobalTransform, &MeshMaterial3d<StandardMaterial>)>,
+    materials: Res<Assets<StandardMaterial>>,
+) {
+    for (camera, cam_gt, mut flare) in &mut q_cam {
+        if flare.enabled == 0 {
+            continue;
+        }
+
+        // Screen-space source, faded towards the screen edges so flares don't pop
+        let project = |world: Vec3| -> Option<(Vec2, f32, f32)> {
+            let ndc = camera.world_to_ndc(cam_gt, world)?;
+            if ndc.x.abs() > 1.0 || ndc.y.abs() > 1.0 || ndc.z <= 0.0 || ndc.z > 1.0 {
+                return None;
+            }
+            let edge = ((1.0 - ndc.x.abs()).min(1.0 - ndc.y.abs()) * 4.0).clamp(0.0, 1.0);
+            Some((Vec2::new(ndc.x * 0.5 + 0.5, 0.5 - ndc.y * 0.5), ndc.z, edge))
+        };
+
+        let mut sources: Vec<Vec4> = Vec::with_capacity(MAX_FLARE_SOURCES);
+
+        // Sun: a point far away against the light direction; depth 0 means "sky only"
+        for sun_gt in &q_sun {
+            let towards_sun = -sun_gt.forward();
+            if let Some((uv, _depth, edge)) =
+                project(cam_gt.translation() + towards_sun * SUN_DISTANCE)
+            {
+                sources.push(Vec4::new(uv.x, uv.y, edge, 0.0));
+            }
+        }
+
+        if flare.emissive_sources != 0 {
+            let threshold = flare.emissive_threshold.max(0.01);
+            let mut bright: Vec<Vec4> = q_emissive
+                .iter()
+                .filter_map(|(gt, mat_h)| {
+                    let e = materials.get(&mat_h.0)?.emissive;
+                    let lum = e.red.max(e.green).max(e.blue);
+                    if lum < threshold {
+                        return None;
+                    }
+                    let (uv, depth, edge) = project(gt.translation())?;
+                    let strength = (lum / threshold * 0.5).min(1.0) * edge;
+                    Some(Vec4::new(uv.x, uv.y, strength, depth))
+                })
+                .collect();
+            bright.sort_by(|a, b| b.z.total_cmp(&a.z));
+            sources.extend(bright);
+        }
+
+        sources.truncate(MAX_FLARE_SOURCES);
+        flare.source_count = sources.len() as u32;
+        flare.sources = [Vec4::ZERO; MAX_FLARE_SOURCES];
+        for (slot, src) in flare.sources.iter_mut().zip(sources) {
+            *slot = src;
+        }
+    }
+}
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+struct PostProcessLabel;
+
+#[derive(Default)]
+struct PostProcessNode;
+
+impl ViewNode for PostProcessNode {
+    type ViewQuery = (
+        &'static ViewTarget,
+        &'static ViewDepthTexture,
+        &'static LensFlareSettings,
+        &'static DynamicUniformIndex<LensFlareSettings>,
+    );
+
+    fn run(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        (view_target, depth, _post_process_settings, settings_index): QueryItem<Self::ViewQuery>,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let post_process_pipeline = world.resource::<PostProcessPipeline>();
+        let pipeline_cache = world.resource::<PipelineCache>();
+        let Some(pipeline) = pipeline_cache.get_render_pipeline(post_process_pipeline.pipeline_id)
+        else {
+            return Ok(());
+        };
+
+        let settings_uniforms = world.resource::<ComponentUniforms<LensFlareSettings>>();
+        let Some(settings_binding) = settings_uniforms.uniforms().binding() else {
+            return Ok(());
+        };
+
+        let post_process = view_target.post_process_write();
+
+        let bind_group = render_context.render_device().create_bind_group(
+            "lens_flare_bind_group",
+            &post_process_pipeline.layout,
+            &BindGroupEntries::sequential((
+                post_process.source,
+                &post_process_pipeline.sampler,
+                settings_binding.clone(),
+                depth.view(),
+            )),
+        );
+
+        let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+            label: Some("lens_flare_pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: post_process.destination,
+                resolve_target: None,
+                ops: Operations::default(),
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+
+        render_pass.set_render_pipeline(pipeline);
+        render_pass.set_bind_group(0, &bind_group, &[settings_index.index()]);
+        render_pass.draw(0..3, 0..1);
+
+        Ok(())
+    }
+}
+
+#[derive(Resource)]
+struct PostProcessPipeline {
+    layout: BindGroupLayout,
+    sampler: Sampler,
+    pipeline_id: CachedRenderPipelineId,
+}
+
+impl FromWorld for PostProcessPipeline {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+
+        let layout = render_device.create_bind_group_layout(
+            "lens_flare_bind_group_layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::FRAGMENT,
+                (
+                    texture_2d(TextureSampleType::Float { filterable: true }),
+                    sampler(SamplerBindingType::Filtering),
+                    uniform_buffer::<LensFlareSettings>(true),
+                    // Main view depth (reverse-Z), read with textureLoad for occlusion
+                    texture_depth_2d(),
+                ),
+            ),
+        );
+
+        let sampler = render_device.create_sampler(&SamplerDescriptor::default());
+
+        let shader = world.load_asset(SHADER_ASSET_PATH);
+
+        let pipeline_id =
+            world
+                .resource_mut::<PipelineCache>()
+                .queue_render_pipeline(RenderPipelineDescriptor {
+                    label: Some("lens_flare_pipeline".into()),
+                    layout: vec![layout.clone()],
+                    vertex: fullscreen_shader_vertex_state(),
+                    fragment: Some(FragmentState {
+                        shader,
+                        shader_defs: vec![],
+                        entry_point: "fragment".into(),
+                        targets: vec![Some(ColorTargetState {
+                            format: TextureFormat::bevy_default(),
+                            blend: None,
+                            write_mask: ColorWrites::ALL,
+                        })],
+                    }),
+                    primitive: PrimitiveState::default(),
+                    depth_stencil: None,
+                    multisample: MultisampleState::default(),
+                    push_constant_ranges: vec![],
+                    zero_initialize_workgroup_memory: false,
+                });
+
+        Self {
+            layout,
+            sampler,
+            pipeline_id,
+        }
+    }
+}
+
+#[derive(Component, Default, Clone, Copy, ShaderType)]
+pub struct LensFlareSettings {
+    pub enabled: u32,          // ShaderType doesn't support bool
+    pub emissive_sources: u32, // 1 = bright emissive objects also flare
+    pub intensity: f32,        // glow around the source
+    pub ghost_intensity: f32,
+    pub halo_intensity: f32,
+    pub emissive_threshold: f32, // min emissive channel value to count as a source
+    // Filled every frame by `update_lens_flare_sources`
+    pub source_count: u32,
+    pub sources: [Vec4; MAX_FLARE_SOURCES],
+}
+
+impl ExtractComponent for LensFlareSettings {
+    type QueryData = (&'static Self, Option<&'static Governed<Self>>);
+    type QueryFilter = ();
+    type Out = Self;
+
+    fn extract_component((settings, governed): QueryItem<'_, Self::QueryData>) -> Option<Self> {
+        Some(governed.map_or(*settings, |g| g.apply(settings)))
+    }
+}
+
+impl Governable for LensFlareSettings {
+    const FULL: [f32; 4] = [2.0, 1.0, 1.0, 0.0];
+    const CAPPED: [bool; 4] = [true, true, true, false];
+
+    fn is_enabled(&self) -> bool {
+        self.enabled != 0
+    }
+
+    fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled as u32;
+    }
+
+    fn strengths(&self) -> [f32; 4] {
+        [
+            self.intensity,
+            self.ghost_intensity,
+            self.halo_intensity,
+            0.0,
+        ]
+    }
+
+    fn set_strengths(&mut self, strengths: [f32; 4]) {
+        let [intensity, ghost_intensity, halo_intensity, _] = strengths;
+        self.intensity = intensity;
+        self.ghost_intensity = ghost_intensity;
+        self.halo_intensity = halo_intensity;
+    }
+}
@@ -38,6 +38,9 @@ pub struct LutSettings {
     pub strength: f32,
     /// Size of one axis (e.g. 16 or 32)
     pub lut_size: u32,
+    // WebGL2 wants uniform structs padded to 16 bytes
+    #[cfg(target_arch = "wasm32")]
+    pub _webgl2_padding: f32,
 }
 
 impl Default for LutSettings {
@@ -46,6 +49,8 @@ impl Default for LutSettings {
             enabled: 1,
             strength: 1.0,
             lut_size: 16,
+            #[cfg(target_arch = "wasm32")]
+            _webgl2_padding: 0.0,
         }
     }
 }
@@ -200,7 +205,8 @@ impl Plugin for LutPlugin {
             ExtractResourcePlugin::<LutImages>::default(),
         ));
 
-        app.add_systems(PreStartup, setup);
+        app.add_systems(PreStartup, setup)
+            .add_systems(Update, lut_apply_pending);
 
         app.init_resource::<LutUiState>();
 
@@ -1,6 +1,82 @@
+use bevy::app::{PluginGroup, PluginGroupBuilder};
+use bevy::ecs::system::SystemParam;
+use bevy::pbr::DistanceFog;
+use bevy::prelude::*;
+
+#[cfg(feature = "chroma")]
 pub mod chroma_aberration;
+pub mod compare;
+#[cfg(feature = "crt")]
 pub mod crt;
+pub mod debug_view;
+pub mod exposure;
+pub mod glow;
+pub mod governor;
+#[cfg(feature = "gradient")]
 pub mod gradient_tint;
+pub mod lens_flare;
+#[cfg(feature = "lut")]
 pub mod lut;
 pub mod outlines;
+pub mod scopes;
 pub mod ui;
+
+/// On a camera: the CRT and chromatic aberration passes are extracted switched off, whatever their
+/// settings say (the accessibility "calm effects" option).
+#[derive(Component, Clone, Copy, Default)]
+pub struct CalmEffects;
+
+/// The post-effect settings of the cameras (all but the A/B compare view), as far as the
+/// enabled features go: what the MIDI/OSC controls, the remote API and the compare snapshots
+/// read and write.
+#[derive(SystemParam)]
+pub struct PostEffects<'w, 's> {
+    #[cfg(feature = "chroma")]
+    pub chroma: Query<
+        'w,
+        's,
+        &'static mut chroma_aberration::ChromaAberrationSettings,
+        Without<compare::CompareCamera>,
+    >,
+    #[cfg(feature = "crt")]
+    pub crt: Query<'w, 's, &'static mut crt::CRTSettings, Without<compare::CompareCamera>>,
+    #[cfg(feature = "gradient")]
+    pub gradient: Query<
+        'w,
+        's,
+        &'static mut gradient_tint::GradientTintSettings,
+        Without<compare::CompareCamera>,
+    >,
+    #[cfg(feature = "lut")]
+    pub lut: Query<'w, 's, &'static mut lut::LutSettings, Without<compare::CompareCamera>>,
+    pub flare:
+        Query<'w, 's, &'static mut lens_flare::LensFlareSettings, Without<compare::CompareCamera>>,
+    pub fog: Query<'w, 's, &'static mut DistanceFog, Without<compare::CompareCamera>>,
+}
+
+/// Every post-process pass compiled in (see the Cargo features), their effect governor, glow-only
+/// bloom, the outline shells, the A/B compare, the debug views, the scopes and auto exposure.
+/// The egui panel for them is `ui::PostUiPlugin`.
+pub struct PostEffectsPlugins;
+impl PluginGroup for PostEffectsPlugins {
+    fn build(self) -> PluginGroupBuilder {
+        let group = PluginGroupBuilder::start::<Self>();
+        #[cfg(feature = "chroma")]
+        let group = group.add(chroma_aberration::ChromaAberrationPlugin);
+        #[cfg(feature = "crt")]
+        let group = group.add(crt::CRTPlugin);
+        #[cfg(feature = "gradient")]
+        let group = group.add(gradient_tint::GradientTintPlugin);
+        #[cfg(feature = "lut")]
+        let group = group.add(lut::LutPlugin);
+        group
+            .add(lens_flare::LensFlarePlugin)
+            .add(governor::EffectGovernorPlugin)
+            .add(glow::GlowPlugin)
+            .add(outlines::OutlinesPlugin)
+            .add(compare::PostComparePlugin)
+            .add(debug_view::DebugViewPlugin)
+            .add(scopes::ScopesPlugin)
+            .add(exposure::ExposurePlugin)
+    }
+}
@@ -1,18 +1,45 @@
 use bevy::pbr::NotShadowCaster;
 use bevy::prelude::*;
+use bevy::render::primitives::Aabb;
+use bevy::render::render_resource::Face;
 use bevy_rapier3d::prelude::Collider;
 
+use crate::camera::OrbitCamera;
 use crate::inspector::{Editable, EditableMesh, SpawnKind};
+use crate::power::every_frame;
 
 /// Tag on the outline child entity so we can update it en masse.
 #[derive(Component)]
 pub struct OutlineShell;
 
+pub struct OutlinesPlugin;
+impl Plugin for OutlinesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PreStartup, setup_outlines).add_systems(
+            Update,
+            update_outlines.run_if(every_frame.or(outlines_stale)),
+        );
+    }
+}
+
+/// How thick outlines are drawn.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum OutlineWidthMode {
+    /// `width` is a fraction of each object's size, so big objects get thick outlines
+    #[default]
+    Relative,
+    /// `pixels` wide on screen whatever the object's size or the zoom
+    Screen,
+}
+
 /// Outline settings (shared across all outlines).
 #[derive(Resource)]
 pub struct OutlineParams {
     pub enabled: bool,
-    pub width: f32,   // uniform scale delta (0.0 => off, ~0.02–0.06 good)
+    pub mode: OutlineWidthMode,
+    pub width: f32, // uniform scale delta (0.0 => off, ~0.02–0.06 good)
+    /// Width on screen in `Screen` mode
+    pub pixels: f32,
     pub color: Color, // outline color
     pub material: Handle<StandardMaterial>,
 }
@@ -42,40 +69,154 @@ pub fn spawn_outlined(
         ))
         .id();
 
-    // Outline child: slightly larger backfaces-only, unlit
-    commands.entity(parent).with_children(|c| {
-        c.spawn((
-            Mesh3d(mesh),
-            MeshMaterial3d(outline_mat),
-            Transform::from_scale(Vec3::splat(1.0 + width.max(0.0))),
-            NotShadowCaster,
-            OutlineShell,
-            Name::new(format!("{name}_Outline")),
-            Collider::cuboid(0.5, 0.5, 0.5),
-        ));
-    });
+    commands
+        .entity(parent)
+        .with_child(outline_shell(mesh, outline_mat, width, name));
 
     parent
 }
 
-/// Update all outline shells: scale for width; hide by scaling to zero if disabled.
+/// Outline child for an object drawn with `mesh`, named after it: slightly larger, backfaces
+/// only, unlit. [`update_outlines`] sizes it for the current width once it's spawned.
+pub fn outline_shell(
+    mesh: Handle<Mesh>,
+    outline_mat: Handle<StandardMaterial>,
+    width: f32,
+    name: &str,
+) -> impl Bundle {
+    (
+        Mesh3d(mesh),
+        MeshMaterial3d(outline_mat),
+        Transform::from_scale(Vec3::splat(1.0 + width.max(0.0))),
+        NotShadowCaster,
+        OutlineShell,
+        Name::new(format!("{name}_Outline")),
+        Collider::cuboid(0.5, 0.5, 0.5),
+    )
+}
+
+/// Shared outline material (front-face culled so backfaces show; unlit for flat color).
+fn setup_outlines(mut commands: Commands, mut materials: ResMut<Assets<StandardMaterial>>) {
+    let color = Color::srgb(0.08, 0.10, 0.12);
+    let material = materials.add(StandardMaterial {
+        base_color: color,
+        unlit: true,
+        cull_mode: Some(Face::Front),
+        // keep depth test/write default so it hugs the mesh properly
+        ..default()
+    });
+
+    // Make outline settings globally available (egui will edit these)
+    commands.insert_resource(OutlineParams {
+        enabled: true,
+        mode: OutlineWidthMode::Relative,
+        width: 0.02,
+        pixels: 2.0,
+        color,
+        material,
+    });
+}
+
+/// Whether anything [`update_outlines`] looks at may have changed: the params, a new shell, a
+/// moved object or, for widths in pixels, the camera.
+fn outlines_stale(
+    outline: Res<OutlineParams>,
+    q_added: Query<(), Added<OutlineShell>>,
+    q_moved: Query<
+        (),
+        (
+            Changed<GlobalTransform>,
+            With<Children>,
+            Without<OutlineShell>,
+        ),
+    >,
+    q_cam: Query<
+        (),
+        (
+            With<OrbitCamera>,
+            Or<(Changed<GlobalTransform>, Changed<Projection>)>,
+        ),
+    >,
+) -> bool {
+    outline.is_changed()
+        || !q_added.is_empty()
+        || !q_moved.is_empty()
+        || (outline.mode == OutlineWidthMode::Screen && !q_cam.is_empty())
+}
+
+/// Update all outline shells: show or hide them, and scale them for the width.
+///
+/// Shells are scaled per axis so the outline is equally thick on every side, also under
+/// non-uniformly scaled parents (a 4×0.6×4 terrace). `Relative` widths are a fraction of the
+/// object's largest side; in `Screen` mode the width is the world size of `pixels` at the
+/// object's distance (or from the ortho view's height).
+///
+/// A shell is only revisited when the settings change, it's new, its object moved or (in
+/// `Screen` mode) the camera did.
 pub fn update_outlines(
     outline: Res<OutlineParams>,
-    mut q_shells: Query<&mut Transform, With<OutlineShell>>,
+    q_cam: Query<(&Camera, Ref<GlobalTransform>, Ref<Projection>), With<OrbitCamera>>,
+    q_parents: Query<Ref<GlobalTransform>, Without<OutlineShell>>,
+    mut q_shells: Query<(
+        Ref<OutlineShell>,
+        &mut Transform,
+        &mut Visibility,
+        &GlobalTransform,
+        Option<&Aabb>,
+        Option<&ChildOf>,
+    )>,
 ) {
-    if !outline.is_changed() && q_shells.is_empty() {
-        return;
-    }
-    let scale = if outline.enabled {
-        1.0 + outline.width.max(0.0)
-    } else {
-        0.0 // effectively hides the outline without relying on Visibility API differences
+    let cam = q_cam.single().ok();
+    let camera_moved = outline.mode == OutlineWidthMode::Screen
+        && cam
+            .as_ref()
+            .is_some_and(|(_, gt, proj)| gt.is_changed() || proj.is_changed());
+    // World units one pixel covers at `at`
+    let world_per_pixel = |at: Vec3| -> f32 {
+        let Some((camera, cam_gt, projection)) = &cam else {
+            return 0.0;
+        };
+        let Some(height) = camera
+            .logical_viewport_size()
+            .map(|s| s.y)
+            .filter(|h| *h > 0.0)
+        else {
+            return 0.0;
+        };
+        match &**projection {
+            Projection::Orthographic(ortho) => ortho.area.height() / height,
+            Projection::Perspective(persp) => {
+                2.0 * cam_gt.translation().distance(at) * (persp.fov * 0.5).tan() / height
+            }
+            _ => 0.0,
+        }
     };
-    for mut t in &mut q_shells {
-        // Keep whatever translation/rotation they have; just adjust uniform scale
-        let basis = t.scale.x.max(t.scale.y).max(t.scale.z);
-        // If we previously hid it (0), basis could be 0; just set anew.
-        let _ = basis; // not used further; set directly:
-        t.scale = Vec3::splat(scale);
+    for (shell, mut t, mut visibility, gt, aabb, child_of) in &mut q_shells {
+        let parent = child_of.and_then(|c| q_parents.get(c.parent()).ok());
+        let moved = parent.as_ref().is_some_and(|p| p.is_changed());
+        if !(outline.is_changed() || shell.is_added() || moved || camera_moved) {
+            continue;
+        }
+        visibility.set_if_neq(if outline.enabled {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        });
+        if !outline.enabled {
+            continue;
+        }
+        // The shell's own scale is what's being set, so the object's size comes from the
+        // shell's mesh and its parent's scale, along the parent's axes
+        let parent_scale = parent.map_or(Vec3::ONE, |p| p.to_scale_rotation_translation().0.abs());
+        let size = aabb.map_or(Vec3::ONE, |a| Vec3::from(a.half_extents) * 2.0) * parent_scale;
+        let width = match outline.mode {
+            OutlineWidthMode::Relative => outline.width.max(0.0) * size.max_element() * 0.5,
+            OutlineWidthMode::Screen => outline.pixels.max(0.0) * world_per_pixel(gt.translation()),
+        };
+        // Flat sides (planes) have nothing to thicken
+        let scale = Vec3::ONE + 2.0 * width / size.max(Vec3::splat(0.001));
+        if t.scale != scale {
+            t.scale = scale;
+        }
     }
 }
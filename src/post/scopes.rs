@@ -0,0 +1,448 @@
+//! Scopes window: a luma histogram and an RGB waveform of the final frame, to see clipping and
+//! color balance while grading with the LUT and tint.
+//!
+//! While the window is expanded (or auto exposure is metering), the last pass of the main
+//! camera's post chain draws the view into a small image, which is read back from the GPU every
+//! frame. The egui panels aren't in it, and neither is anything drawn after the post chain.
+
+use bevy::{
+    core_pipeline::{
+        core_3d::graph::{Core3d, Node3d},
+        fullscreen_vertex_shader::fullscreen_shader_vertex_state,
+    },
+    ecs::query::QueryItem,
+    prelude::*,
+    render::{
+        RenderApp,
+        extract_component::{
+            ComponentUniforms, DynamicUniformIndex, ExtractComponent, ExtractComponentPlugin,
+            UniformComponentPlugin,
+        },
+        extract_resource::{ExtractResource, ExtractResourcePlugin},
+        gpu_readback::{Readback, ReadbackComplete},
+        render_asset::{RenderAssetUsages, RenderAssets},
+        render_graph::{
+            NodeRunError, RenderGraphApp, RenderGraphContext, RenderLabel, ViewNode, ViewNodeRunner,
+        },
+        render_resource::{
+            binding_types::{sampler, texture_2d, uniform_buffer},
+            *,
+        },
+        renderer::{RenderContext, RenderDevice},
+        texture::GpuImage,
+        view::ViewTarget,
+    },
+};
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+
+use super::compare::CompareLabel;
+use crate::SceneEditState;
+use crate::camera::OrbitCamera;
+
+const SHADER_ASSET_PATH: &str = "shaders/scope_downsample.wgsl";
+
+/// Size of the frame the scopes are computed from. 256 wide keeps its rows at the 256 byte
+/// alignment of GPU readbacks, so the data comes back unpadded.
+const SCOPE_WIDTH: u32 = 256;
+const SCOPE_HEIGHT: u32 = 144;
+/// Waveform rows (code values are binned in pairs)
+const WAVE_LEVELS: usize = 128;
+
+/// The last frame's scopes, and whether the window wants them.
+#[derive(Resource)]
+pub struct Scopes {
+    /// The window is expanded, so frames are read back
+    pub active: bool,
+    /// Auto exposure wants frames too, window or not
+    pub metering: bool,
+    /// Frames tallied so far, to tell a new reading from the last one
+    pub readings: u64,
+    /// Pixels per luma code value
+    pub histogram: [u32; 256],
+    /// Pixels per column and level (top row brightest), for red, green and blue
+    pub waveform: Vec<[u16; 3]>,
+    /// Fraction of pixels with a channel at 0 and at 255
+    pub clipped: (f32, f32),
+    texture: Option<egui::TextureHandle>,
+    /// New data the waveform texture hasn't been updated with
+    fresh: bool,
+}
+
+impl Default for Scopes {
+    fn default() -> Self {
+        Self {
+            active: false,
+            metering: false,
+            readings: 0,
+            histogram: [0; 256],
+            waveform: vec![[0; 3]; SCOPE_WIDTH as usize * WAVE_LEVELS],
+            clipped: (0.0, 0.0),
+            texture: None,
+            fresh: false,
+        }
+    }
+}
+
+impl Scopes {
+    /// Tally a read back frame (RGBA8, sRGB encoded).
+    fn tally(&mut self, data: &[u8]) {
+        let width = SCOPE_WIDTH as usize;
+        self.histogram = [0; 256];
+        self.waveform.fill([0; 3]);
+        let (mut crushed, mut blown, mut pixels) = (0u32, 0u32, 0u32);
+        for (i, px) in data.chunks_exact(4).enumerate() {
+            let (r, g, b) = (px[0], px[1], px[2]);
+            let luma = 0.2126 * r as f32 + 0.7152 * g as f32 + 0.0722 * b as f32;
+            self.histogram[(luma.round() as usize).min(255)] += 1;
+
+            let x = i % width;
+            for (c, v) in [r, g, b].into_iter().enumerate() {
+                let row = WAVE_LEVELS - 1 - v as usize * WAVE_LEVELS / 256;
+                let count = &mut self.waveform[row * width + x][c];
+                *count = count.saturating_add(1);
+            }
+            crushed += (r == 0 || g == 0 || b == 0) as u32;
+            blown += (r == 255 || g == 255 || b == 255) as u32;
+            pixels += 1;
+        }
+        let pixels = pixels.max(1) as f32;
+        self.clipped = (crushed as f32 / pixels, blown as f32 / pixels);
+        self.readings += 1;
+        self.fresh = true;
+    }
+
+    fn waveform_image(&self) -> egui::ColorImage {
+        // A column holds SCOPE_HEIGHT pixels; a handful at one level already shows clearly
+        let glow = |count: u16| (255.0 * (1.0 - (-(count as f32) * 0.4).exp())) as u8;
+        let pixels = self
+            .waveform
+            .iter()
+            .map(|[r, g, b]| egui::Color32::from_rgb(glow(*r), glow(*g), glow(*b)))
+            .collect();
+        let size = [SCOPE_WIDTH as usize, WAVE_LEVELS];
+        egui::ColorImage {
+            size,
+            source_size: egui::vec2(size[0] as f32, size[1] as f32),
+            pixels,
+        }
+    }
+}
+
+/// The image the scope pass draws into and that's read back.
+#[derive(Resource, Clone, ExtractResource)]
+struct ScopeImage {
+    image: Handle<Image>,
+}
+
+/// The entity carrying the readback while the scopes are active.
+#[derive(Component)]
+struct ScopeReadback;
+
+/// On the main camera while the scopes are active: the view's area in its target.
+#[derive(Component, Clone, Copy, ExtractComponent, ShaderType)]
+struct ScopeSource {
+    /// In UV: min x, min y, max x, max y
+    viewport: Vec4,
+}
+
+pub struct ScopesPlugin;
+impl Plugin for ScopesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            ExtractComponentPlugin::<ScopeSource>::default(),
+            UniformComponentPlugin::<ScopeSource>::default(),
+            ExtractResourcePlugin::<ScopeImage>::default(),
+        ))
+        .init_resource::<Scopes>()
+        .init_resource::<SceneEditState>()
+        .add_systems(Startup, setup_scope_image)
+        .add_systems(Update, sync_scope_readback)
+        .add_systems(EguiPrimaryContextPass, scopes_window);
+
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        // After the A/B compare, so a split view shows in the scopes as it does on screen
+        render_app
+            .add_render_graph_node::<ViewNodeRunner<ScopeNode>>(Core3d, ScopeLabel)
+            .add_render_graph_edges(Core3d, (CompareLabel, ScopeLabel, Node3d::Upscaling));
+    }
+
+    fn finish(&self, app: &mut App) {
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app.init_resource::<ScopePipeline>();
+    }
+}
+
+fn setup_scope_image(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    let mut image = Image::new_fill(
+        Extent3d {
+            width: SCOPE_WIDTH,
+            height: SCOPE_HEIGHT,
+            ..default()
+        },
+        TextureDimension::D2,
+        &[0, 0, 0, 255],
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    image.texture_descriptor.usage = TextureUsages::TEXTURE_BINDING
+        | TextureUsages::COPY_SRC
+        | TextureUsages::COPY_DST
+        | TextureUsages::RENDER_ATTACHMENT;
+    let image = images.add(image);
+    commands.insert_resource(ScopeImage { image });
+}
+
+/// Read frames back, and have the main camera draw them, only while the window is expanded or
+/// auto exposure is on.
+fn sync_scope_readback(
+    mut commands: Commands,
+    scopes: Res<Scopes>,
+    target: Res<ScopeImage>,
+    q_main: Query<(Entity, &Camera), With<OrbitCamera>>,
+    q_readback: Query<Entity, With<ScopeReadback>>,
+) {
+    let viewport = q_main.single().ok().and_then(|(e, camera)| {
+        let rect = camera.physical_viewport_rect()?;
+        let size = camera.physical_target_size()?.as_vec2().max(Vec2::ONE);
+        let (min, max) = (rect.min.as_vec2() / size, rect.max.as_vec2() / size);
+        Some((e, Vec4::new(min.x, min.y, max.x, max.y)))
+    });
+    let (true, Some((main_e, viewport))) = (scopes.active || scopes.metering, viewport) else {
+        for e in &q_readback {
+            commands.entity(e).despawn();
+        }
+        if let Ok((main_e, _)) = q_main.single() {
+            commands.entity(main_e).remove::<ScopeSource>();
+        }
+        return;
+    };
+    commands.entity(main_e).insert(ScopeSource { viewport });
+    if q_readback.is_empty() {
+        commands
+            .spawn((
+                Readback::texture(target.image.clone()),
+                ScopeReadback,
+                Name::new("ScopeReadback"),
+            ))
+            .observe(
+                |trigger: Trigger<ReadbackComplete>, mut scopes: ResMut<Scopes>| {
+                    if scopes.active || scopes.metering {
+                        scopes.tally(&trigger.event().0);
+                    }
+                },
+            );
+    }
+}
+
+fn scopes_window(
+    mut ctxs: EguiContexts,
+    edit_state: Res<SceneEditState>,
+    mut scopes: ResMut<Scopes>,
+) {
+    if !edit_state.open {
+        scopes.active = false;
+        return;
+    }
+    let Ok(ctx) = ctxs.ctx_mut() else {
+        return;
+    };
+    if scopes.fresh || scopes.texture.is_none() {
+        let image = scopes.waveform_image();
+        match &mut scopes.texture {
+            Some(texture) => texture.set(image, egui::TextureOptions::LINEAR),
+            None => {
+                scopes.texture =
+                    Some(ctx.load_texture("scopes_waveform", image, egui::TextureOptions::LINEAR));
+            }
+        }
+        scopes.fresh = false;
+    }
+
+    let shown = egui::Window::new("Scopes")
+        .default_open(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            let size = egui::vec2(SCOPE_WIDTH as f32, 96.0);
+            ui.label("Luma histogram");
+            let (rect, _) = ui.allocate_exact_size(size, egui::Sense::hover());
+            let painter = ui.painter_at(rect);
+            painter.rect_filled(rect, 0.0, egui::Color32::from_gray(20));
+            // Scaled to the tallest bin short of the ends, so clipped spikes don't flatten the rest
+            let tallest = scopes.histogram[1..255]
+                .iter()
+                .copied()
+                .max()
+                .unwrap_or(0)
+                .max(1);
+            for (i, &count) in scopes.histogram.iter().enumerate() {
+                let h = (count as f32 / tallest as f32).min(1.0) * rect.height();
+                let x = rect.left() + (i as f32 + 0.5) * rect.width() / 256.0;
+                let color = if i == 0 || i == 255 {
+                    egui::Color32::from_rgb(230, 80, 60)
+                } else {
+                    egui::Color32::from_gray(200)
+                };
+                painter.line_segment(
+                    [
+                        egui::pos2(x, rect.bottom()),
+                        egui::pos2(x, rect.bottom() - h),
+                    ],
+                    egui::Stroke::new(1.0, color),
+                );
+            }
+            let (crushed, blown) = scopes.clipped;
+            ui.weak(format!(
+                "Clipped: {:.1}% shadows, {:.1}% highlights",
+                crushed * 100.0,
+                blown * 100.0
+            ));
+
+            ui.separator();
+            ui.label("RGB waveform");
+            if let Some(texture) = &scopes.texture {
+                let size = egui::vec2(SCOPE_WIDTH as f32, WAVE_LEVELS as f32);
+                ui.image((texture.id(), size)).on_hover_text(
+                    "Left to right across the view; 0 at the bottom, 255 at the top",
+                );
+            }
+        })
+        .is_some_and(|response| response.inner.is_some());
+    if scopes.active != shown {
+        scopes.active = shown;
+    }
+}
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+struct ScopeLabel;
+
+#[derive(Default)]
+struct ScopeNode;
+
+impl ViewNode for ScopeNode {
+    type ViewQuery = (
+        &'static ViewTarget,
+        &'static ScopeSource,
+        &'static DynamicUniformIndex<ScopeSource>,
+    );
+
+    fn run(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        (view_target, _source, source_index): QueryItem<Self::ViewQuery>,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let scope_pipeline = world.resource::<ScopePipeline>();
+        let pipeline_cache = world.resource::<PipelineCache>();
+        let Some(pipeline) = pipeline_cache.get_render_pipeline(scope_pipeline.pipeline_id) else {
+            return Ok(());
+        };
+        let Some(image) = world.get_resource::<ScopeImage>() else {
+            return Ok(());
+        };
+        let Some(target) = world.resource::<RenderAssets<GpuImage>>().get(&image.image) else {
+            return Ok(());
+        };
+        let source_uniforms = world.resource::<ComponentUniforms<ScopeSource>>();
+        let Some(source_binding) = source_uniforms.uniforms().binding() else {
+            return Ok(());
+        };
+
+        // Only reads the frame, so it stays the view's current main texture
+        let bind_group = render_context.render_device().create_bind_group(
+            "scope_bind_group",
+            &scope_pipeline.layout,
+            &BindGroupEntries::sequential((
+                view_target.main_texture_view(),
+                &scope_pipeline.sampler,
+                source_binding.clone(),
+            )),
+        );
+
+        let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+            label: Some("scope_pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: &target.texture_view,
+                resolve_target: None,
+                ops: Operations::default(),
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+
+        render_pass.set_render_pipeline(pipeline);
+        render_pass.set_bind_group(0, &bind_group, &[source_index.index()]);
+        render_pass.draw(0..3, 0..1);
+
+        Ok(())
+    }
+}
+
+#[derive(Resource)]
+struct ScopePipeline {
+    layout: BindGroupLayout,
+    sampler: Sampler,
+    pipeline_id: CachedRenderPipelineId,
+}
+
+impl FromWorld for ScopePipeline {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+
+        let layout = render_device.create_bind_group_layout(
+            "scope_bind_group_layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::FRAGMENT,
+                (
+                    texture_2d(TextureSampleType::Float { filterable: true }),
+                    sampler(SamplerBindingType::Filtering),
+                    uniform_buffer::<ScopeSource>(true),
+                ),
+            ),
+        );
+
+        let sampler = render_device.create_sampler(&SamplerDescriptor {
+            mag_filter: FilterMode::Linear,
+            min_filter: FilterMode::Linear,
+            ..default()
+        });
+
+        let shader = world.load_asset(SHADER_ASSET_PATH);
+
+        let pipeline_id =
+            world
+                .resource_mut::<PipelineCache>()
+                .queue_render_pipeline(RenderPipelineDescriptor {
+                    label: Some("scope_pipeline".into()),
+                    layout: vec![layout.clone()],
+                    vertex: fullscreen_shader_vertex_state(),
+                    fragment: Some(FragmentState {
+                        shader,
+                        shader_defs: vec![],
+                        entry_point: "fragment".into(),
+                        targets: vec![Some(ColorTargetState {
+                            // The scope image's format, whatever the view's is
+                            format: TextureFormat::Rgba8UnormSrgb,
+                            blend: None,
+                            write_mask: ColorWrites::ALL,
+                        })],
+                    }),
+                    primitive: PrimitiveState::default(),
+                    depth_stencil: None,
+                    multisample: MultisampleState::default(),
+                    push_constant_ranges: vec![],
+                    zero_initialize_workgroup_memory: false,
+                });
+
+        Self {
+            layout,
+            sampler,
+            pipeline_id,
+        }
+    }
+}
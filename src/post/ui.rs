@@ -5,21 +5,49 @@ use bevy::{
         tonemapping::Tonemapping,
     },
     diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin},
+    ecs::system::SystemParam,
     pbr::{DistanceFog, FogFalloff},
     prelude::*,
-    render::render_resource::Face,
+    render::{camera::Exposure, render_resource::Face},
 };
-use bevy_egui::{EguiContexts, egui};
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
 
 use crate::SceneEditState;
-use crate::camera::{FpsText, FpsUpdate};
-use crate::post::{
-    chroma_aberration::ChromaAberrationSettings,
-    crt::CRTSettings,
-    gradient_tint::GradientTintSettings,
-    lut::{LutSettings, LutUiState},
-    outlines::OutlineParams,
-};
+use crate::camera::{FpsText, FpsUpdate, OrbitCamera};
+use crate::diagnostics::{DiagnosticsOverlay, overlay_text};
+use crate::dissolve::{DissolveSettings, dissolve_settings_ui};
+use crate::dock::{DockLayout, DockPanel, DockSet, DockedWindow};
+#[cfg(feature = "chroma")]
+use crate::post::chroma_aberration::ChromaAberrationSettings;
+use crate::post::compare::CompareUi;
+#[cfg(feature = "crt")]
+use crate::post::crt::CRTSettings;
+use crate::post::debug_view::{DebugViews, debug_view_ui};
+use crate::post::exposure::{ExposureControl, exposure_ui};
+use crate::post::glow::BloomIsolation;
+#[cfg(feature = "gradient")]
+use crate::post::gradient_tint::GradientTintSettings;
+use crate::post::lens_flare::LensFlareSettings;
+#[cfg(feature = "lut")]
+use crate::post::lut::{LutSettings, LutUiState};
+use crate::post::outlines::{OutlineParams, OutlineWidthMode};
+use crate::stylized::{ToonShading, toon_ui};
+
+/// Post-processing panel and the FPS counter.
+pub struct PostUiPlugin;
+impl Plugin for PostUiPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ToonShading>()
+            .init_resource::<BloomIsolation>()
+            .init_resource::<DissolveSettings>()
+            .add_systems(PostStartup, setup_fps_text)
+            .add_systems(Update, update_fps_text)
+            .add_systems(
+                EguiPrimaryContextPass,
+                post_process_edit_panel.in_set(DockSet::Effects),
+            );
+    }
+}
 
 fn section(ui: &mut egui::Ui, title: &str, default_open: bool, body: impl FnOnce(&mut egui::Ui)) {
     egui::CollapsingHeader::new(title)
@@ -27,6 +55,15 @@ fn section(ui: &mut egui::Ui, title: &str, default_open: bool, body: impl FnOnce
         .show(ui, |ui| body(ui));
 }
 
+/// The scene-wide look settings in the panel, as one system parameter (the panel has many).
+#[derive(SystemParam)]
+pub struct SceneLook<'w> {
+    toon_shading: ResMut<'w, ToonShading>,
+    bloom_isolation: ResMut<'w, BloomIsolation>,
+    dissolve: ResMut<'w, DissolveSettings>,
+    exposure: ResMut<'w, ExposureControl>,
+}
+
 /// egui panel: tune post-processing effects
 pub fn post_process_edit_panel(
     mut commands: Commands,
@@ -34,78 +71,130 @@ pub fn post_process_edit_panel(
     mut q_cam: Query<
         (
             Entity,
-            &mut DepthOfField,
+            Option<&mut DepthOfField>,
             &mut Tonemapping,
             &mut Bloom,
             Option<&mut DistanceFog>,
             &GlobalTransform,
+            Option<&Exposure>,
         ),
-        With<Camera3d>,
+        With<OrbitCamera>,
     >,
     mut outline: ResMut<OutlineParams>,
+    mut look: SceneLook,
+    mut debug_views: ResMut<DebugViews>,
     mut materials: ResMut<Assets<StandardMaterial>>,
-    (mut chroma_settings, mut crt_settings, mut gradient_tint_settings, mut lut_settings): (
-        Query<&mut ChromaAberrationSettings>,
-        Query<&mut CRTSettings>,
-        Query<&mut GradientTintSettings>,
-        Query<&mut LutSettings>,
-    ),
-
-    mut ui_state: ResMut<LutUiState>,
+    #[cfg(feature = "chroma")] mut chroma_settings: Query<
+        &mut ChromaAberrationSettings,
+        With<OrbitCamera>,
+    >,
+    #[cfg(feature = "crt")] mut crt_settings: Query<&mut CRTSettings, With<OrbitCamera>>,
+    #[cfg(feature = "gradient")] mut gradient_tint_settings: Query<
+        &mut GradientTintSettings,
+        With<OrbitCamera>,
+    >,
+    #[cfg(feature = "lut")] mut lut_settings: Query<&mut LutSettings, With<OrbitCamera>>,
+    #[cfg(feature = "lut")] mut ui_state: ResMut<LutUiState>,
+    mut flare_settings: Query<&mut LensFlareSettings, With<OrbitCamera>>,
     mut edit_state: ResMut<SceneEditState>,
+    dock: Res<DockLayout>,
+    mut compare: CompareUi,
 ) {
-    let Ok((cam_e, mut dof, mut tonemapping, mut bloom, fog_opt, cam_xform)) = q_cam.single_mut()
+    let Ok((cam_e, dof, mut tonemapping, mut bloom, fog_opt, cam_xform, cam_exposure)) =
+        q_cam.single_mut()
     else {
         return;
     };
 
-    // Local copies so sliders can edit smoothly
-    let mut focal_distance = dof.focal_distance;
-    let mut f_stops = dof.aperture_f_stops;
-    let mut bokeh = matches!(dof.mode, DepthOfFieldMode::Bokeh);
+    // Local copies so sliders can edit smoothly (no DoF on WebGL2)
+    let has_dof = dof.is_some();
+    let (mut focal_distance, mut f_stops, mut bokeh) =
+        dof.as_deref().map_or((8.0, 2.0, true), |d| {
+            (
+                d.focal_distance,
+                d.aperture_f_stops,
+                matches!(d.mode, DepthOfFieldMode::Bokeh),
+            )
+        });
 
     let mut enabled = outline.enabled;
     let mut width = outline.width;
+    let mut width_mode = outline.mode;
+    let mut pixels = outline.pixels;
     let mut color = outline.color;
+    let mut toon = *look.toon_shading;
+    let mut isolation = *look.bloom_isolation;
+    let mut dissolve = *look.dissolve;
+    let mut exposure = look.exposure.clone();
+    let current_ev = cam_exposure.copied().unwrap_or_default().ev100;
+    let mut debug = *debug_views;
 
     // --- Effect Settings window (collapsible sections)
-    egui::Window::new("Effect settings")
+    let Ok(ctx) = ctxs.ctx_mut() else {
+        return;
+    };
+    DockedWindow::new(DockPanel::Effects, "Effect settings")
         .open(&mut edit_state.open)
-        .default_width(300.0)
-        .show(ctxs.ctx_mut().expect("single egui context"), |ui| {
+        .scroll(false)
+        .show(ctx, &dock, |ui| {
             egui::ScrollArea::vertical()
                 .auto_shrink([false, false])
                 .show(ui, |ui| {
                     // Depth of Field
-                    section(ui, "Depth of Field", false, |ui| {
-                        ui.add(
-                            egui::Slider::new(&mut focal_distance, 1.0..=40.0)
-                                .text("Focal distance"),
-                        );
-                        ui.add(
-                            egui::Slider::new(&mut f_stops, 0.01..=64.0)
-                                .logarithmic(true)
-                                .text("Aperture (f-stops)"),
-                        );
-                        ui.checkbox(&mut bokeh, "Bokeh mode (prettier)");
-
-                        ui.horizontal(|ui| {
-                            if ui.button("Snap focus to origin").clicked() {
-                                let cam_pos = cam_xform.translation();
-                                focal_distance = cam_pos.length();
-                            }
-                            if ui.button("Reset DoF").clicked() {
-                                focal_distance = 8.0;
-                                f_stops = 2.0;
-                                bokeh = true;
-                            }
+                    if has_dof {
+                        section(ui, "Depth of Field", false, |ui| {
+                            ui.add(
+                                egui::Slider::new(&mut focal_distance, 1.0..=40.0)
+                                    .text("Focal distance"),
+                            );
+                            ui.add(
+                                egui::Slider::new(&mut f_stops, 0.01..=64.0)
+                                    .logarithmic(true)
+                                    .text("Aperture (f-stops)"),
+                            );
+                            ui.checkbox(&mut bokeh, "Bokeh mode (prettier)");
+
+                            ui.horizontal(|ui| {
+                                if ui.button("Snap focus to origin").clicked() {
+                                    let cam_pos = cam_xform.translation();
+                                    focal_distance = cam_pos.length();
+                                }
+                                if ui.button("Reset DoF").clicked() {
+                                    focal_distance = 8.0;
+                                    f_stops = 2.0;
+                                    bokeh = true;
+                                }
+                            });
                         });
-                    });
+                    }
 
                     // Outline
                     section(ui, "Outline", false, |ui| {
                         ui.checkbox(&mut enabled, "Enabled");
-                        ui.add(egui::Slider::new(&mut width, 0.0..=0.10).text("Width"));
+                        ui.horizontal(|ui| {
+                            ui.selectable_value(
+                                &mut width_mode,
+                                OutlineWidthMode::Relative,
+                                "Relative",
+                            )
+                            .on_hover_text("A fraction of each object's size");
+                            ui.selectable_value(
+                                &mut width_mode,
+                                OutlineWidthMode::Screen,
+                                "Screen",
+                            )
+                            .on_hover_text("The same on screen for every object, at any zoom");
+                        });
+                        match width_mode {
+                            OutlineWidthMode::Relative => {
+                                ui.add(egui::Slider::new(&mut width, 0.0..=0.10).text("Width"));
+                            }
+                            OutlineWidthMode::Screen => {
+                                ui.add(
+                                    egui::Slider::new(&mut pixels, 0.0..=10.0).text("Width (px)"),
+                                );
+                            }
+                        }
 
                         // Simple RGB picker (gamma-aware conversions aren’t critical here)
                         let mut rgb = [
@@ -120,11 +209,27 @@ pub fn post_process_edit_panel(
                         if ui.button("Reset Outline").clicked() {
                             enabled = true;
                             width = 0.02;
+                            width_mode = OutlineWidthMode::Relative;
+                            pixels = 2.0;
                             color = Color::srgb(0.08, 0.10, 0.12);
                         }
                     });
 
+                    // Toon shading
+                    section(ui, "Toon Shading", false, |ui| {
+                        ui.checkbox(&mut toon.all, "All objects").on_hover_text(
+                            "Objects with their own toon shading keep their bands and cutoff",
+                        );
+                        ui.add_enabled_ui(toon.all, |ui| toon_ui(ui, &mut toon.toon));
+                    });
+
+                    // Dissolve
+                    section(ui, "Dissolve", false, |ui| {
+                        dissolve_settings_ui(ui, &mut dissolve);
+                    });
+
                     // Chromatic Aberration
+                    #[cfg(feature = "chroma")]
                     section(ui, "Chromatic Aberration", false, |ui| {
                         if let Ok(mut ca) = chroma_settings.single_mut() {
                             let mut on = ca.enabled != 0;
@@ -142,6 +247,7 @@ pub fn post_process_edit_panel(
                     });
 
                     // CRT
+                    #[cfg(feature = "crt")]
                     section(ui, "CRT", false, |ui| {
                         if let Ok(mut crt) = crt_settings.single_mut() {
                             let mut on = crt.enabled != 0;
@@ -169,6 +275,7 @@ pub fn post_process_edit_panel(
                     });
 
                     // Gradient Tint
+                    #[cfg(feature = "gradient")]
                     section(ui, "Gradient Tint", false, |ui| {
                         if let Ok(mut gt) = gradient_tint_settings.single_mut() {
                             let mut on = gt.enabled != 0;
@@ -214,6 +321,7 @@ pub fn post_process_edit_panel(
                     });
 
                     // LUT
+                    #[cfg(feature = "lut")]
                     section(ui, "LUT", false, |ui| {
                         if let Ok(mut lut) = lut_settings.single_mut() {
                             let mut on = lut.enabled != 0;
@@ -234,6 +342,41 @@ pub fn post_process_edit_panel(
                         }
                     });
 
+                    // Lens Flare
+                    section(ui, "Lens Flare", false, |ui| {
+                        if let Ok(mut lf) = flare_settings.single_mut() {
+                            let mut on = lf.enabled != 0;
+                            let mut emissive = lf.emissive_sources != 0;
+
+                            ui.add(egui::Slider::new(&mut lf.intensity, 0.0..=2.0).text("Glow"));
+                            ui.add(
+                                egui::Slider::new(&mut lf.ghost_intensity, 0.0..=1.0)
+                                    .text("Ghosts"),
+                            );
+                            ui.add(
+                                egui::Slider::new(&mut lf.halo_intensity, 0.0..=1.0).text("Halo"),
+                            );
+                            let mut resp = ui.checkbox(&mut on, "Enabled");
+                            if resp.changed() {
+                                lf.enabled = on as u32; // 1 or 0
+                            }
+                            resp = ui.checkbox(&mut emissive, "Bright emissives flare too");
+                            if resp.changed() {
+                                lf.emissive_sources = emissive as u32; // 1 or 0
+                            }
+                            ui.add_enabled(
+                                emissive,
+                                egui::Slider::new(&mut lf.emissive_threshold, 0.1..=10.0)
+                                    .logarithmic(true)
+                                    .text("Emissive threshold"),
+                            );
+                        }
+                    });
+
+                    section(ui, "Exposure", false, |ui| {
+                        exposure_ui(ui, &mut exposure, current_ev);
+                    });
+
                     section(ui, "Renderer Features", false, |ui| {
                         // ---- Bloom ----
                         // TODO: Bloom does nothing since our camera is not HDR
@@ -246,6 +389,27 @@ pub fn post_process_edit_panel(
                                 bloom.intensity = 0.0;
                             }
                         }
+                        ui.add_enabled_ui(bloom_on, |ui| {
+                            let mut intensity = bloom.intensity;
+                            ui.add(
+                                egui::Slider::new(&mut intensity, 0.0..=1.0)
+                                    .text("Bloom intensity"),
+                            );
+                            if intensity != bloom.intensity {
+                                bloom.intensity = intensity;
+                            }
+                            ui.checkbox(&mut isolation.glow_only, "Glow objects only")
+                                .on_hover_text(
+                                    "Only objects flagged Glow in the inspector bloom, \
+                                     not bright lit surfaces",
+                                );
+                            ui.add_enabled(
+                                isolation.glow_only,
+                                egui::Slider::new(&mut isolation.threshold, 1.0..=20.0)
+                                    .logarithmic(true)
+                                    .text("Bloom threshold"),
+                            );
+                        });
 
                         // ---- Fog ----
                         let mut fog_on = fog_opt.is_some();
@@ -272,27 +436,58 @@ pub fn post_process_edit_panel(
                             }
                         }
                     });
+
+                    section(ui, "A/B Compare", false, |ui| {
+                        compare.show(ui);
+                    });
+
+                    section(ui, "Debug View", false, |ui| {
+                        debug_view_ui(ui, &mut debug);
+                    });
                 });
         });
 
     // Apply DoF
-    dof.focal_distance = focal_distance.max(0.1);
-    dof.aperture_f_stops = f_stops.clamp(0.01, 64.0);
-    dof.mode = if bokeh {
-        DepthOfFieldMode::Bokeh
-    } else {
-        DepthOfFieldMode::Gaussian
-    };
+    if let Some(mut dof) = dof {
+        dof.focal_distance = focal_distance.max(0.1);
+        dof.aperture_f_stops = f_stops.clamp(0.01, 64.0);
+        dof.mode = if bokeh {
+            DepthOfFieldMode::Bokeh
+        } else {
+            DepthOfFieldMode::Gaussian
+        };
+    }
 
-    // Apply Outline params (change shared material color)
-    if let Some(mat) = materials.get_mut(&outline.material) {
+    // Apply Outline params (change shared material color). Only on an actual edit: shells
+    // are rescaled whenever the params change
+    if color != outline.color
+        && let Some(mat) = materials.get_mut(&outline.material)
+    {
         mat.base_color = color;
         mat.unlit = true;
         mat.cull_mode = Some(Face::Front);
     }
-    outline.enabled = enabled;
-    outline.width = width.clamp(0.0, 0.25);
-    outline.color = color;
+    let (width, pixels) = (width.clamp(0.0, 0.25), pixels.clamp(0.0, 20.0));
+    if (enabled, width, width_mode, pixels, color)
+        != (
+            outline.enabled,
+            outline.width,
+            outline.mode,
+            outline.pixels,
+            outline.color,
+        )
+    {
+        outline.enabled = enabled;
+        outline.width = width;
+        outline.mode = width_mode;
+        outline.pixels = pixels;
+        outline.color = color;
+    }
+    look.toon_shading.set_if_neq(toon);
+    look.bloom_isolation.set_if_neq(isolation);
+    look.dissolve.set_if_neq(dissolve);
+    look.exposure.set_if_neq(exposure);
+    debug_views.set_if_neq(debug);
 }
 
 pub fn setup_fps_text(mut commands: Commands, asset_server: Res<AssetServer>) {
@@ -320,24 +515,29 @@ pub fn setup_fps_text(mut commands: Commands, asset_server: Res<AssetServer>) {
 }
 
 pub fn update_fps_text(
-    time: Res<Time>,
+    time: Res<Time<Real>>,
     diagnostics: Res<DiagnosticsStore>,
+    overlay: Res<DiagnosticsOverlay>,
     mut q: Query<&mut Text, With<FpsText>>,
     mut upd: ResMut<FpsUpdate>,
 ) {
     upd.timer.tick(time.delta());
 
-    // Only refresh the cached numbers once per second
-    if upd.timer.finished() {
-        if let Ok(mut text) = q.single_mut() {
-            if let Some(fps) = diagnostics
-                .get(&FrameTimeDiagnosticsPlugin::FPS)
-                .and_then(|d| d.smoothed())
-            {
-                upd.cached_fps = fps;
-            }
-
-            text.0 = format!("{:.0}", upd.cached_fps);
+    // Only refresh the cached numbers once per second (or right away when F3 is pressed)
+    if (upd.timer.finished() || overlay.is_changed())
+        && let Ok(mut text) = q.single_mut()
+    {
+        if let Some(fps) = diagnostics
+            .get(&FrameTimeDiagnosticsPlugin::FPS)
+            .and_then(|d| d.smoothed())
+        {
+            upd.cached_fps = fps;
         }
+
+        text.0 = if overlay.enabled {
+            overlay_text(&diagnostics)
+        } else {
+            format!("{:.0}", upd.cached_fps)
+        };
     }
 }
@@ -0,0 +1,112 @@
+//! Power saving: an optional frame rate cap, and an idle throttle that drops to a few frames a
+//! second while the window is unfocused and nothing is animating, so a scene left open in the
+//! background doesn't keep the GPU busy.
+//!
+//! "Animating" means Play mode, timeline playback, a tween in flight or a capture recording.
+//! Ambient motion (light animations, creatures) just runs at the throttled rate.
+//!
+//! With reactive updates on, some heavy per-frame systems (outline sizing, scene change
+//! tracking, selection and collider gizmos, comparing a multi-selection's materials) only run
+//! when what they depend on changed; they're scheduled with [`every_frame`]`.or(...)`. Gizmos
+//! are redrawn every frame they're shown, so those are only skipped while there's nothing to
+//! draw.
+
+use std::time::Duration;
+
+#[cfg(not(target_arch = "wasm32"))]
+use bevy::platform::time::Instant;
+use bevy::prelude::*;
+use bevy::winit::{UpdateMode, WinitSettings};
+use serde::{Deserialize, Serialize};
+
+#[cfg(all(feature = "capture", not(target_arch = "wasm32")))]
+use crate::capture::CaptureState;
+use crate::editor_mode::EditorMode;
+use crate::timeline::TimelinePlayback;
+use crate::tween::AnimateTo;
+
+/// Frame rate limits (kept in the app settings).
+#[derive(Resource, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PowerPrefs {
+    /// Never run faster than `max_fps` (on the web the browser paces frames instead)
+    pub cap_fps: bool,
+    pub max_fps: u32,
+    /// Drop to `idle_fps` while the window is unfocused and nothing is animating
+    pub idle_throttle: bool,
+    pub idle_fps: u32,
+    /// Run heavy systems only on relevant changes instead of every frame
+    pub reactive_updates: bool,
+}
+
+impl Default for PowerPrefs {
+    fn default() -> Self {
+        Self {
+            cap_fps: false,
+            max_fps: 60,
+            idle_throttle: true,
+            idle_fps: 10,
+            reactive_updates: false,
+        }
+    }
+}
+
+pub struct PowerPlugin;
+impl Plugin for PowerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PowerPrefs>()
+            .init_resource::<WinitSettings>()
+            .init_resource::<EditorMode>()
+            .init_resource::<TimelinePlayback>()
+            .add_systems(Update, throttle_when_idle);
+        // Last, so the wait comes after everything else the frame does
+        #[cfg(not(target_arch = "wasm32"))]
+        app.add_systems(Last, cap_frame_rate);
+    }
+}
+
+/// Run condition: reactive updates are off, so heavy systems run every frame as usual.
+pub fn every_frame(prefs: Option<Res<PowerPrefs>>) -> bool {
+    !prefs.is_some_and(|p| p.reactive_updates)
+}
+
+/// While unfocused and idle, only update when the window gets an event or every
+/// `1 / idle_fps` seconds; otherwise keep updating continuously.
+fn throttle_when_idle(
+    prefs: Res<PowerPrefs>,
+    mode: Res<EditorMode>,
+    playback: Res<TimelinePlayback>,
+    q_tweens: Query<(), With<AnimateTo>>,
+    #[cfg(all(feature = "capture", not(target_arch = "wasm32")))] capture: Option<
+        Res<CaptureState>,
+    >,
+    mut winit: ResMut<WinitSettings>,
+) {
+    let busy = *mode == EditorMode::Play || playback.playing || !q_tweens.is_empty();
+    #[cfg(all(feature = "capture", not(target_arch = "wasm32")))]
+    let busy = busy || capture.is_some_and(|c| c.is_recording());
+    let unfocused = if prefs.idle_throttle && !busy {
+        let wait = 1.0 / prefs.idle_fps.max(1) as f32;
+        UpdateMode::reactive_low_power(Duration::from_secs_f32(wait))
+    } else {
+        UpdateMode::Continuous
+    };
+    if winit.unfocused_mode != unfocused {
+        winit.unfocused_mode = unfocused;
+    }
+}
+
+/// Sleep out the rest of the frame's `1 / max_fps` seconds.
+#[cfg(not(target_arch = "wasm32"))]
+fn cap_frame_rate(prefs: Res<PowerPrefs>, mut last_frame: Local<Option<Instant>>) {
+    if prefs.cap_fps
+        && let Some(last) = *last_frame
+    {
+        let due = last + Duration::from_secs_f64(1.0 / prefs.max_fps.max(1) as f64);
+        let now = Instant::now();
+        if due > now {
+            std::thread::sleep(due - now);
+        }
+    }
+    *last_frame = Some(Instant::now());
+}
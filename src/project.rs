@@ -0,0 +1,393 @@
+//! Campaigns: a `project.json` that lists scenes in play order.
+//!
+//! ```json
+//! {
+//!   "title": "Tiny Towers",
+//!   "play_on_load": true,
+//!   "scenes": [
+//!     { "name": "First steps", "file": "levels/01.json" },
+//!     { "name": "The climb", "file": "levels/02.json" }
+//!   ]
+//! }
+//! ```
+//!
+//! When the file exists the app opens on a level select screen. In Play mode, walking the player
+//! into an object named `Goal...` loads the next scene; after the last one the level select
+//! comes back. A scene with objectives keeps its goals shut until they're done, or finishes on
+//! the last one (see [`crate::objectives`]).
+
+use bevy::prelude::*;
+#[cfg(feature = "player")]
+use bevy::render::primitives::Aabb;
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+use serde::{Deserialize, Serialize};
+
+use crate::SceneEditState;
+use crate::editor_mode::EditorMode;
+#[cfg(feature = "player")]
+use crate::inspector::{Editable, aabb_world};
+use crate::inspector::{LoadSceneEvent, SceneIoState};
+use crate::objectives::{ObjectiveCompleted, ObjectiveProgress, Objectives};
+#[cfg(feature = "player")]
+use crate::player::Player;
+use crate::scene_io;
+
+/// Frames between asking for a scene and entering Play mode in it, so the loaded objects
+/// exist (and get colliders) by the time the player drops in.
+const PLAY_DELAY_FRAMES: u8 = 2;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ProjectScene {
+    pub name: String,
+    /// Scene JSON, relative to the working directory
+    pub file: String,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ProjectDoc {
+    #[serde(default)]
+    pub title: String,
+    /// Start Play mode as soon as a level is loaded
+    #[serde(default = "default_true")]
+    pub play_on_load: bool,
+    pub scenes: Vec<ProjectScene>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// The open project and where the player is in it.
+#[derive(Resource, Default)]
+pub struct Project {
+    pub doc: Option<ProjectDoc>,
+    /// Index of the scene being played
+    pub current: Option<usize>,
+    /// Whether the level select screen is up
+    pub show_select: bool,
+    /// Every scene has been completed
+    pub finished: bool,
+    play_in: Option<u8>,
+}
+
+/// Load scene `index` of the project.
+#[derive(Event)]
+pub struct StartLevelEvent(pub usize);
+
+/// Reads `path` at startup; nothing happens when it doesn't exist.
+pub struct ProjectPlugin {
+    pub path: String,
+}
+
+impl Default for ProjectPlugin {
+    fn default() -> Self {
+        Self {
+            path: "project.json".into(),
+        }
+    }
+}
+
+#[derive(Resource)]
+struct ProjectPath(String);
+
+impl Plugin for ProjectPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Project>()
+            .init_resource::<EditorMode>()
+            .init_resource::<Objectives>()
+            .init_resource::<ObjectiveProgress>()
+            .add_event::<ObjectiveCompleted>()
+            .insert_resource(ProjectPath(self.path.clone()))
+            .add_event::<StartLevelEvent>()
+            .add_systems(Startup, load_project)
+            .add_systems(
+                Update,
+                (finish_objectives, start_level, enter_play_when_loaded).chain(),
+            )
+            .add_systems(EguiPrimaryContextPass, level_select_window);
+        #[cfg(feature = "player")]
+        app.add_systems(Update, reach_goal.before(start_level));
+    }
+}
+
+fn load_project(path: Res<ProjectPath>, mut project: ResMut<Project>) {
+    let Some(text) = scene_io::read_scene(&path.0) else {
+        return;
+    };
+    match serde_json::from_str::<ProjectDoc>(&text) {
+        Ok(doc) if doc.scenes.is_empty() => eprintln!("Project: {} lists no scenes", path.0),
+        Ok(doc) => {
+            project.doc = Some(doc);
+            project.show_select = true;
+        }
+        Err(e) => eprintln!("Project: {}: {e}", path.0),
+    }
+}
+
+fn start_level(
+    mut events: EventReader<StartLevelEvent>,
+    mut project: ResMut<Project>,
+    mut io: ResMut<SceneIoState>,
+    mut mode: ResMut<EditorMode>,
+    mut ev_load: EventWriter<LoadSceneEvent>,
+) {
+    let Some(StartLevelEvent(index)) = events.read().last() else {
+        return;
+    };
+    let Some(doc) = &project.doc else {
+        return;
+    };
+    let Some(scene) = doc.scenes.get(*index) else {
+        return;
+    };
+    io.filename = scene.file.clone();
+    let play = doc.play_on_load;
+    ev_load.write(LoadSceneEvent);
+    // Leave Play so the player respawns with the new scene's colliders
+    if *mode == EditorMode::Play {
+        *mode = EditorMode::Select;
+    }
+    project.current = Some(*index);
+    project.show_select = false;
+    project.play_in = play.then_some(PLAY_DELAY_FRAMES);
+}
+
+fn enter_play_when_loaded(mut project: ResMut<Project>, mut mode: ResMut<EditorMode>) {
+    let Some(frames) = project.play_in else {
+        return;
+    };
+    project.play_in = frames.checked_sub(1);
+    if frames == 0 {
+        mode.set_if_neq(EditorMode::Play);
+    }
+}
+
+/// Touching a goal in Play mode moves on to the next scene (or back to the level select after
+/// the last one).
+#[cfg(feature = "player")]
+fn reach_goal(
+    mode: Res<EditorMode>,
+    mut project: ResMut<Project>,
+    mut ev_start: EventWriter<StartLevelEvent>,
+    objectives: Res<Objectives>,
+    progress: Res<ObjectiveProgress>,
+    q_player: Query<&GlobalTransform, With<Player>>,
+    q_goals: Query<(&Name, &GlobalTransform, &Aabb), With<Editable>>,
+) {
+    if *mode != EditorMode::Play
+        || project.current.is_none()
+        || project.play_in.is_some()
+        || !progress.is_complete(&objectives)
+    {
+        return;
+    }
+    // Either player will do in co-op
+    let reached = q_goals
+        .iter()
+        .filter(|(name, ..)| name.as_str().to_ascii_lowercase().starts_with("goal"))
+        .any(|(_, gt, aabb)| {
+            let world = aabb_world(*aabb, gt);
+            // The player's capsule only has to touch it
+            let reach = world.half_extents + Vec3A::splat(0.3);
+            q_player.iter().any(|player| {
+                let p = Vec3A::from(player.translation());
+                ((p - world.center).abs() - reach).max_element() <= 0.0
+            })
+        });
+    if reached {
+        next_level(&mut project, &mut ev_start);
+    }
+}
+
+/// Completing the last objective of a scene that finishes on it counts as reaching the goal.
+fn finish_objectives(
+    mode: Res<EditorMode>,
+    objectives: Res<Objectives>,
+    mut project: ResMut<Project>,
+    mut ev_done: EventReader<ObjectiveCompleted>,
+    mut ev_start: EventWriter<StartLevelEvent>,
+) {
+    let finished = ev_done.read().any(|ev| ev.last);
+    if finished && objectives.finish_level && *mode == EditorMode::Play && project.play_in.is_none()
+    {
+        next_level(&mut project, &mut ev_start);
+    }
+}
+
+/// On to the next scene, or back to the level select after the last one.
+fn next_level(project: &mut Project, ev_start: &mut EventWriter<StartLevelEvent>) {
+    let (Some(current), Some(count)) = (
+        project.current,
+        project.doc.as_ref().map(|d| d.scenes.len()),
+    ) else {
+        return;
+    };
+    if current + 1 < count {
+        ev_start.write(StartLevelEvent(current + 1));
+    } else {
+        project.current = None;
+        project.finished = true;
+        project.show_select = true;
+    }
+}
+
+fn level_select_window(
+    mut ctxs: EguiContexts,
+    edit_state: Res<SceneEditState>,
+    mut project: ResMut<Project>,
+    mut ev_start: EventWriter<StartLevelEvent>,
+) {
+    let Some(doc) = project.doc.clone() else {
+        return;
+    };
+    let Ok(ctx) = ctxs.ctx_mut() else {
+        return;
+    };
+    let title = if doc.title.is_empty() {
+        "Level Select"
+    } else {
+        doc.title.as_str()
+    };
+
+    if project.show_select {
+        let mut close = false;
+        egui::Window::new(title)
+            .id(egui::Id::new("level_select"))
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                if project.finished {
+                    ui.heading("All levels complete!");
+                    ui.separator();
+                }
+                for (i, scene) in doc.scenes.iter().enumerate() {
+                    let current = project.current == Some(i);
+                    if ui
+                        .selectable_label(current, format!("{}. {}", i + 1, scene.name))
+                        .clicked()
+                    {
+                        ev_start.write(StartLevelEvent(i));
+                    }
+                }
+                ui.separator();
+                if ui.button("Close").clicked() {
+                    close = true;
+                }
+            });
+        if close {
+            project.show_select = false;
+        }
+        return;
+    }
+
+    // Small "where am I" box while editing, with a way back to the list
+    if !edit_state.open {
+        return;
+    }
+    egui::Window::new("Project")
+        .default_open(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            match project
+                .current
+                .and_then(|i| doc.scenes.get(i).map(|s| (i, s)))
+            {
+                Some((i, scene)) => {
+                    ui.label(format!(
+                        "Level {} of {}: {}",
+                        i + 1,
+                        doc.scenes.len(),
+                        scene.name
+                    ));
+                }
+                None => {
+                    ui.label(format!("{} levels", doc.scenes.len()));
+                }
+            }
+            if ui.button("Level select").clicked() {
+                project.show_select = true;
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    fn two_levels() -> ProjectDoc {
+        serde_json::from_str(
+            r#"{
+                "title": "Tiny Towers",
+                "scenes": [
+                    { "name": "First steps", "file": "levels/01.json" },
+                    { "name": "The climb", "file": "levels/02.json" }
+                ]
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn missing_fields_take_their_defaults() {
+        let doc = two_levels();
+        assert!(doc.play_on_load);
+        let doc: ProjectDoc = serde_json::from_str(r#"{ "scenes": [] }"#).unwrap();
+        assert!(doc.title.is_empty());
+        assert!(serde_json::from_str::<ProjectDoc>(r#"{ "title": "No scenes" }"#).is_err());
+    }
+
+    #[test]
+    fn round_trips_through_json() {
+        let mut doc = two_levels();
+        doc.play_on_load = false;
+        let back: ProjectDoc = serde_json::from_str(&serde_json::to_string(&doc).unwrap()).unwrap();
+        assert_eq!(back.title, doc.title);
+        assert_eq!(back.play_on_load, doc.play_on_load);
+        let files = |d: &ProjectDoc| {
+            d.scenes
+                .iter()
+                .map(|s| (s.name.clone(), s.file.clone()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(files(&back), files(&doc));
+    }
+
+    fn advance(world: &mut World) {
+        world
+            .run_system_once(
+                |mut project: ResMut<Project>, mut ev_start: EventWriter<StartLevelEvent>| {
+                    next_level(&mut project, &mut ev_start)
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn goals_lead_through_the_levels_and_back_to_the_select() {
+        let mut world = World::new();
+        world.init_resource::<Events<StartLevelEvent>>();
+        world.insert_resource(Project {
+            doc: Some(two_levels()),
+            current: Some(0),
+            ..default()
+        });
+
+        advance(&mut world);
+        let started: Vec<usize> = world
+            .resource_mut::<Events<StartLevelEvent>>()
+            .drain()
+            .map(|ev| ev.0)
+            .collect();
+        assert_eq!(started, [1]);
+
+        world.resource_mut::<Project>().current = Some(1);
+        advance(&mut world);
+        assert!(world.resource::<Events<StartLevelEvent>>().is_empty());
+        let project = world.resource::<Project>();
+        assert!(project.finished && project.show_select);
+        assert_eq!(project.current, None);
+    }
+}
@@ -0,0 +1,185 @@
+//! Randomize panel: bounded rotation, scale and color jitter for the selection, so rows of
+//! duplicated props don't look stamped out. The same seed gives the same result.
+
+use bevy::prelude::*;
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+
+use crate::SceneEditState;
+use crate::asset_cache::AssetCache;
+use crate::inspector::{Editable, SceneIoState, Selected};
+
+#[derive(Resource)]
+struct RandomizeSettings {
+    seed: u64,
+    /// Largest rotation either way, in degrees
+    rotate_deg: f32,
+    y_only: bool,
+    /// Largest scale change, as a fraction of the current scale
+    scale: f32,
+    uniform_scale: bool,
+    /// Largest hue shift, in degrees
+    hue_deg: f32,
+    /// Largest brightness (HSV value) change, as a fraction
+    value: f32,
+}
+
+impl Default for RandomizeSettings {
+    fn default() -> Self {
+        Self {
+            seed: 1,
+            rotate_deg: 15.0,
+            y_only: true,
+            scale: 0.1,
+            uniform_scale: true,
+            hue_deg: 0.0,
+            value: 0.1,
+        }
+    }
+}
+
+pub struct RandomizePlugin;
+impl Plugin for RandomizePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RandomizeSettings>()
+            .init_resource::<AssetCache>()
+            .add_systems(EguiPrimaryContextPass, randomize_window);
+    }
+}
+
+/// SplitMix64: small, seedable and plenty for visual jitter.
+pub(crate) struct Rng(pub(crate) u64);
+
+impl Rng {
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in -1..1
+    pub(crate) fn signed(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 23) as f32 - 1.0
+    }
+
+    /// Uniform in 0..1
+    pub(crate) fn unit(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
+
+fn randomize_window(
+    mut ctxs: EguiContexts,
+    edit_state: Res<SceneEditState>,
+    mut settings: ResMut<RandomizeSettings>,
+    mut commands: Commands,
+    cache: Res<AssetCache>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut io: Option<ResMut<SceneIoState>>,
+    mut q_selected: Query<
+        (
+            Entity,
+            &mut Transform,
+            Option<&MeshMaterial3d<StandardMaterial>>,
+        ),
+        (With<Selected>, With<Editable>),
+    >,
+) {
+    if !edit_state.open {
+        return;
+    }
+    let count = q_selected.iter().count();
+    let mut apply = false;
+    let Ok(ctx) = ctxs.ctx_mut() else {
+        return;
+    };
+    egui::Window::new("Randomize")
+        .default_open(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Seed");
+                ui.add(egui::DragValue::new(&mut settings.seed));
+                if ui.button("New seed").clicked() {
+                    let mut rng = Rng(settings.seed);
+                    settings.seed = rng.next_u64() % 100_000;
+                }
+            });
+            ui.add(egui::Slider::new(&mut settings.rotate_deg, 0.0..=180.0).text("Rotation ±°"));
+            ui.checkbox(&mut settings.y_only, "Around Y only");
+            ui.add(egui::Slider::new(&mut settings.scale, 0.0..=0.5).text("Scale ±"));
+            ui.checkbox(&mut settings.uniform_scale, "Uniform scale");
+            ui.add(egui::Slider::new(&mut settings.hue_deg, 0.0..=60.0).text("Hue ±°"));
+            ui.add(egui::Slider::new(&mut settings.value, 0.0..=0.5).text("Brightness ±"));
+            ui.separator();
+            let label = match count {
+                1 => "Apply to 1 object".to_string(),
+                n => format!("Apply to {n} objects"),
+            };
+            apply = ui
+                .add_enabled(count > 0, egui::Button::new(label))
+                .clicked();
+            ui.small("Applies on top of the current values; each click compounds.");
+        });
+    if !apply {
+        return;
+    }
+
+    // Sorted, so the same seed and selection always give the same result
+    let mut targets: Vec<_> = q_selected.iter_mut().collect();
+    targets.sort_by_key(|(e, ..)| *e);
+    let mut rng = Rng(settings.seed);
+    let mut recolored = false;
+    for (e, mut tf, mat) in targets {
+        let max_angle = settings.rotate_deg.to_radians();
+        let rotation = if settings.y_only {
+            Quat::from_rotation_y(rng.signed() * max_angle)
+        } else {
+            Quat::from_euler(
+                EulerRot::XYZ,
+                rng.signed() * max_angle,
+                rng.signed() * max_angle,
+                rng.signed() * max_angle,
+            )
+        };
+        let scale = if settings.uniform_scale {
+            Vec3::splat(1.0 + rng.signed() * settings.scale)
+        } else {
+            Vec3::new(rng.signed(), rng.signed(), rng.signed()) * settings.scale + Vec3::ONE
+        };
+        tf.rotation = (tf.rotation * rotation).normalize();
+        tf.scale = (tf.scale * scale).max(Vec3::splat(0.001));
+
+        let (dh, dv) = (
+            rng.signed() * settings.hue_deg,
+            rng.signed() * settings.value,
+        );
+        let jitter_color = settings.hue_deg > 0.0 || settings.value > 0.0;
+        let Some(mat) = mat.filter(|_| jitter_color) else {
+            continue;
+        };
+        // Cached materials are shared with other objects: edit a private copy
+        let handle = if cache.is_shared_material(&mat.0) {
+            let Some(copy) = materials.get(&mat.0).cloned() else {
+                continue;
+            };
+            let copy = materials.add(copy);
+            commands.entity(e).insert(MeshMaterial3d(copy.clone()));
+            copy
+        } else {
+            mat.0.clone()
+        };
+        if let Some(m) = materials.get_mut(&handle) {
+            let mut hsva = Hsva::from(m.base_color);
+            hsva.hue = (hsva.hue + dh).rem_euclid(360.0);
+            hsva.value = (hsva.value * (1.0 + dv)).clamp(0.0, 1.0);
+            m.base_color = hsva.into();
+            recolored = true;
+        }
+    }
+    // Transforms flag the scene by themselves; material edits don't
+    if recolored && let Some(io) = io.as_mut() {
+        io.mark_edited();
+    }
+}
@@ -0,0 +1,299 @@
+use bevy::{
+    core_pipeline::tonemapping::Tonemapping,
+    pbr::{Material, MaterialPlugin, NotShadowCaster},
+    prelude::*,
+    render::{
+        camera::RenderTarget,
+        render_asset::RenderAssetUsages,
+        render_resource::{
+            AsBindGroup, Extent3d, ShaderRef, TextureDimension, TextureFormat, TextureUsages,
+        },
+        view::RenderLayers,
+    },
+    window::PrimaryWindow,
+};
+use bevy_egui::egui;
+use serde::{Deserialize, Serialize};
+
+use crate::camera::{OrbitCamera, OrbitSet};
+
+const SHADER_ASSET_PATH: &str = "shaders/planar_reflection.wgsl";
+
+/// Reflective surfaces (and their overlay) live on this layer so the mirror camera,
+/// which only renders layer 0, doesn't see the water from underneath.
+pub const REFLECTIVE_LAYER: usize = 1;
+
+/// Render the reflection at a fraction of the window resolution; water ripples hide the blur.
+const REFLECTION_RESOLUTION_SCALE: u32 = 2;
+
+/// Mark a flat, upward-facing object (the water slab) as a planar mirror.
+/// Only one reflection plane is rendered: the first reflective object's height is used.
+#[derive(Component, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PlanarReflection {
+    pub strength: f32,      // 0..1 opacity of the reflection over the base material
+    pub fresnel_power: f32, // higher = reflections only at grazing angles
+}
+
+impl Default for PlanarReflection {
+    fn default() -> Self {
+        Self {
+            strength: 0.6,
+            fresnel_power: 3.0,
+        }
+    }
+}
+
+/// Screen-space sampled mirror image, drawn as a blended overlay on top of the surface.
+#[derive(Asset, TypePath, AsBindGroup, Clone)]
+pub struct ReflectionMaterial {
+    /// x = strength, y = fresnel power
+    #[uniform(0)]
+    pub params: Vec4,
+    #[texture(1)]
+    #[sampler(2)]
+    pub reflection: Handle<Image>,
+}
+
+impl Material for ReflectionMaterial {
+    fn fragment_shader() -> ShaderRef {
+        SHADER_ASSET_PATH.into()
+    }
+
+    fn alpha_mode(&self) -> AlphaMode {
+        AlphaMode::Blend
+    }
+}
+
+/// Tag for the secondary camera rendering the mirrored scene into an image.
+#[derive(Component)]
+pub struct ReflectionCamera;
+
+/// Overlay child spawned under a `PlanarReflection` surface.
+#[derive(Component)]
+pub struct ReflectionOverlay {
+    surface: Entity,
+}
+
+#[derive(Resource)]
+struct ReflectionTarget {
+    image: Handle<Image>,
+}
+
+pub struct ReflectionPlugin;
+impl Plugin for ReflectionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(MaterialPlugin::<ReflectionMaterial>::default())
+            .add_systems(Startup, setup_reflection_target)
+            .add_systems(
+                Update,
+                (
+                    attach_reflection_overlays,
+                    update_reflection_overlays,
+                    detach_reflection_overlays,
+                    update_reflection_camera.after(OrbitSet::Pose),
+                ),
+            );
+    }
+}
+
+fn reflection_image(width: u32, height: u32) -> Image {
+    let size = Extent3d {
+        width: width.max(1),
+        height: height.max(1),
+        ..default()
+    };
+    let mut image = Image::new_fill(
+        size,
+        TextureDimension::D2,
+        &[0, 0, 0, 255],
+        // BGRA targets aren't renderable on WebGL2
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    image.texture_descriptor.usage =
+        TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST | TextureUsages::RENDER_ATTACHMENT;
+    image
+}
+
+fn setup_reflection_target(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    let image = images.add(reflection_image(640, 360));
+    commands.insert_resource(ReflectionTarget { image });
+}
+
+/// Spawned lazily (after the main camera exists) so egui never picks it as its primary context.
+fn spawn_reflection_camera(commands: &mut Commands, image: Handle<Image>) {
+    commands.spawn((
+        Camera3d::default(),
+        Camera {
+            target: RenderTarget::Image(image.into()),
+            order: -1,
+            // Match the distance-fog tint so empty sky reads as haze, not black
+            clear_color: ClearColorConfig::Custom(Color::srgb(0.86, 0.90, 0.96)),
+            ..default()
+        },
+        // The main camera tonemaps the final frame; don't do it twice
+        Tonemapping::None,
+        RenderLayers::layer(0),
+        ReflectionCamera,
+        Name::new("ReflectionCamera"),
+    ));
+}
+
+fn overlay_params(refl: &PlanarReflection) -> Vec4 {
+    Vec4::new(refl.strength.clamp(0.0, 1.0), refl.fresnel_power, 0.0, 0.0)
+}
+
+/// Give each new reflective surface an overlay child and move both off the mirror camera's layer.
+fn attach_reflection_overlays(
+    mut commands: Commands,
+    target: Res<ReflectionTarget>,
+    mut materials: ResMut<Assets<ReflectionMaterial>>,
+    q_new: Query<(Entity, &Mesh3d, &PlanarReflection), Added<PlanarReflection>>,
+    q_main_cam: Query<Entity, With<OrbitCamera>>,
+    q_refl_cam: Query<(), With<ReflectionCamera>>,
+) {
+    if q_new.is_empty() {
+        return;
+    }
+    if q_refl_cam.is_empty() {
+        spawn_reflection_camera(&mut commands, target.image.clone());
+    }
+    // The main camera has to see the reflective layer as well
+    for cam in &q_main_cam {
+        commands
+            .entity(cam)
+            .insert(RenderLayers::from_layers(&[0, REFLECTIVE_LAYER]));
+    }
+
+    for (e, mesh, refl) in &q_new {
+        let material = materials.add(ReflectionMaterial {
+            params: overlay_params(refl),
+            reflection: target.image.clone(),
+        });
+        commands
+            .entity(e)
+            .insert(RenderLayers::layer(REFLECTIVE_LAYER))
+            .with_children(|c| {
+                c.spawn((
+                    Mesh3d(mesh.0.clone()),
+                    MeshMaterial3d(material),
+                    // Nudge up a hair to avoid z-fighting with the surface
+                    Transform::from_xyz(0.0, 0.001, 0.0),
+                    NotShadowCaster,
+                    RenderLayers::layer(REFLECTIVE_LAYER),
+                    ReflectionOverlay { surface: e },
+                    Name::new("ReflectionOverlay"),
+                ));
+            });
+    }
+}
+
+/// Keep overlay materials in sync with inspector edits.
+fn update_reflection_overlays(
+    q_changed: Query<&PlanarReflection, Changed<PlanarReflection>>,
+    q_overlay: Query<(&ReflectionOverlay, &MeshMaterial3d<ReflectionMaterial>)>,
+    mut materials: ResMut<Assets<ReflectionMaterial>>,
+) {
+    for (overlay, h) in &q_overlay {
+        if let Ok(refl) = q_changed.get(overlay.surface)
+            && let Some(mat) = materials.get_mut(&h.0)
+        {
+            mat.params = overlay_params(refl);
+        }
+    }
+}
+
+/// Remove the overlay when the reflection flag is turned off.
+fn detach_reflection_overlays(
+    mut commands: Commands,
+    mut removed: RemovedComponents<PlanarReflection>,
+    q_overlay: Query<(Entity, &ReflectionOverlay)>,
+) {
+    for e in removed.read() {
+        let Ok(mut ecmd) = commands.get_entity(e) else {
+            continue; // despawned along with its overlay
+        };
+        ecmd.remove::<RenderLayers>();
+        for (overlay_e, overlay) in &q_overlay {
+            if overlay.surface == e {
+                commands.entity(overlay_e).despawn();
+            }
+        }
+    }
+}
+
+/// Mirror the main camera across the reflection plane and keep the render target sized to the
+/// window. There is no oblique near-plane clipping, so geometry just below the plane can leak
+/// into the reflection; with the diorama's flat ground this stays hidden under the surfaces
+/// themselves.
+fn update_reflection_camera(
+    target: Res<ReflectionTarget>,
+    mut images: ResMut<Assets<Image>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    q_surfaces: Query<&GlobalTransform, With<PlanarReflection>>,
+    q_main: Query<(&Transform, &Projection), (With<OrbitCamera>, Without<ReflectionCamera>)>,
+    mut q_refl: Query<(&mut Camera, &mut Transform, &mut Projection), With<ReflectionCamera>>,
+) {
+    let Ok((mut camera, mut tf, mut proj)) = q_refl.single_mut() else {
+        return;
+    };
+    let (Some(surface), Ok((main_tf, main_proj))) = (q_surfaces.iter().next(), q_main.single())
+    else {
+        if camera.is_active {
+            camera.is_active = false;
+        }
+        return;
+    };
+    if !camera.is_active {
+        camera.is_active = true;
+    }
+
+    // Reflect position and basis across the horizontal plane y = h
+    let h = surface.translation().y;
+    let pos = main_tf.translation;
+    let fwd = main_tf.forward().as_vec3();
+    let up = main_tf.up().as_vec3();
+    *tf = Transform::from_xyz(pos.x, 2.0 * h - pos.y, pos.z).looking_to(
+        Vec3::new(fwd.x, -fwd.y, fwd.z),
+        Vec3::new(up.x, -up.y, up.z),
+    );
+    *proj = main_proj.clone();
+
+    // Follow the window size (at reduced resolution)
+    if let Ok(window) = windows.single() {
+        let want = Extent3d {
+            width: (window.physical_width() / REFLECTION_RESOLUTION_SCALE).max(1),
+            height: (window.physical_height() / REFLECTION_RESOLUTION_SCALE).max(1),
+            ..default()
+        };
+        let current = images.get(&target.image).map(|i| i.texture_descriptor.size);
+        if current.is_some_and(|s| s != want)
+            && let Some(image) = images.get_mut(&target.image)
+        {
+            image.resize(want);
+        }
+    }
+}
+
+/// Inspector section to toggle/tune a planar reflection on the selected surface.
+pub fn reflection_inspector_ui(
+    ui: &mut egui::Ui,
+    commands: &mut Commands,
+    entity: Entity,
+    refl: Option<Mut<PlanarReflection>>,
+) {
+    let mut on = refl.is_some();
+    if ui.checkbox(&mut on, "Planar reflection").changed() {
+        if on {
+            commands.entity(entity).insert(PlanarReflection::default());
+        } else {
+            commands.entity(entity).remove::<PlanarReflection>();
+        }
+    }
+    if let Some(mut refl) = refl {
+        let mut r = *refl;
+        ui.add(egui::Slider::new(&mut r.strength, 0.0..=1.0).text("Reflection strength"));
+        ui.add(egui::Slider::new(&mut r.fresnel_power, 0.5..=8.0).text("Fresnel power"));
+        refl.set_if_neq(r);
+    }
+}
@@ -0,0 +1,376 @@
+//! JSON-RPC 2.0 over WebSocket, so external tools (or a browser control surface) can drive the
+//! app live: load scenes, set post-effect parameters, move the camera, spawn objects, take
+//! screenshots and run editor actions (`{"method": "action", "params": {"name": "undo"}}`).
+//!
+//! ```json
+//! {"jsonrpc": "2.0", "id": 1, "method": "set_effect", "params": {"effect": "crt", "intensity": 0.3}}
+//! ```
+//!
+//! The server runs on a background thread; calls are handed to the app through a channel and
+//! answered once a system has applied them.
+
+use std::net::TcpListener;
+use std::sync::Mutex;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::time::Duration;
+
+use bevy::pbr::FogFalloff;
+use bevy::prelude::*;
+use bevy::render::view::screenshot::{Screenshot, save_to_disk};
+use serde_json::{Value, json};
+use tungstenite::Message;
+
+use crate::actions::EditorAction;
+use crate::asset_cache::{AssetCache, PlainMaterial};
+use crate::camera::OrbitCamera;
+use crate::inspector::{Editable, EditableMesh, LoadSceneEvent, SceneIoState, SpawnKind};
+use crate::post::PostEffects;
+
+/// How long a connection waits for the app to answer a call
+const REPLY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Listens on `addr` (localhost only by default).
+pub struct RemotePlugin {
+    pub addr: String,
+}
+
+impl Default for RemotePlugin {
+    fn default() -> Self {
+        Self {
+            addr: "127.0.0.1:9001".into(),
+        }
+    }
+}
+
+impl Plugin for RemotePlugin {
+    fn build(&self, app: &mut App) {
+        let (tx, rx) = mpsc::channel();
+        let addr = self.addr.clone();
+        std::thread::spawn(move || serve(&addr, tx));
+        app.insert_resource(RemoteInbox(Mutex::new(rx)))
+            .add_event::<EditorAction>()
+            .add_systems(Update, handle_remote_calls);
+    }
+}
+
+enum RpcError {
+    MethodNotFound,
+    InvalidParams(String),
+}
+
+impl RpcError {
+    fn to_json(&self) -> Value {
+        match self {
+            RpcError::MethodNotFound => json!({"code": -32601, "message": "Method not found"}),
+            RpcError::InvalidParams(msg) => json!({"code": -32602, "message": msg}),
+        }
+    }
+}
+
+type RpcResult = Result<Value, RpcError>;
+
+struct RemoteCall {
+    method: String,
+    params: Value,
+    reply: Sender<RpcResult>,
+}
+
+#[derive(Resource)]
+struct RemoteInbox(Mutex<Receiver<RemoteCall>>);
+
+fn serve(addr: &str, calls: Sender<RemoteCall>) {
+    let listener = match TcpListener::bind(addr) {
+        Ok(l) => l,
+        Err(e) => {
+            eprintln!("Remote: cannot listen on {addr}: {e}");
+            return;
+        }
+    };
+    eprintln!("Remote: JSON-RPC WebSocket server on ws://{addr}");
+    for stream in listener.incoming().flatten() {
+        let calls = calls.clone();
+        std::thread::spawn(move || {
+            let Ok(mut ws) = tungstenite::accept(stream) else {
+                return;
+            };
+            while let Ok(msg) = ws.read() {
+                let text = match msg {
+                    Message::Text(text) => text.as_str().to_string(),
+                    Message::Close(_) => break,
+                    _ => continue,
+                };
+                let Some(response) = answer(&text, &calls) else {
+                    continue;
+                };
+                if ws.send(Message::text(response.to_string())).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+}
+
+/// Response to one request (`None` for notifications, which have no id).
+fn answer(text: &str, calls: &Sender<RemoteCall>) -> Option<Value> {
+    let Ok(request) = serde_json::from_str::<Value>(text) else {
+        return Some(json!({
+            "jsonrpc": "2.0",
+            "id": null,
+            "error": {"code": -32700, "message": "Parse error"},
+        }));
+    };
+    let id = request.get("id").cloned();
+    let Some(method) = request.get("method").and_then(Value::as_str) else {
+        return Some(json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": {"code": -32600, "message": "Invalid Request"},
+        }));
+    };
+
+    let (reply, result) = mpsc::channel();
+    let call = RemoteCall {
+        method: method.to_string(),
+        params: request.get("params").cloned().unwrap_or(Value::Null),
+        reply,
+    };
+    let outcome = if calls.send(call).is_ok() {
+        result.recv_timeout(REPLY_TIMEOUT).ok()
+    } else {
+        None
+    };
+    let id = id?;
+    Some(match outcome {
+        Some(Ok(value)) => json!({"jsonrpc": "2.0", "id": id, "result": value}),
+        Some(Err(e)) => json!({"jsonrpc": "2.0", "id": id, "error": e.to_json()}),
+        None => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": {"code": -32000, "message": "App did not answer"},
+        }),
+    })
+}
+
+fn f32_param(params: &Value, key: &str) -> Option<f32> {
+    params.get(key).and_then(Value::as_f64).map(|v| v as f32)
+}
+
+fn flag_param(params: &Value, key: &str) -> Option<u32> {
+    params.get(key).and_then(Value::as_bool).map(u32::from)
+}
+
+/// `[x, y, z]` or `[r, g, b, a]`
+fn array_param<const N: usize>(params: &Value, key: &str) -> Result<Option<[f32; N]>, RpcError> {
+    let Some(value) = params.get(key) else {
+        return Ok(None);
+    };
+    let values: Option<Vec<f32>> = value
+        .as_array()
+        .and_then(|a| a.iter().map(|v| v.as_f64().map(|v| v as f32)).collect());
+    values
+        .and_then(|v| v.try_into().ok())
+        .map(Some)
+        .ok_or_else(|| RpcError::InvalidParams(format!("'{key}' must be an array of {N} numbers")))
+}
+
+fn set_if_some<T>(field: &mut T, value: Option<T>) {
+    if let Some(v) = value {
+        *field = v;
+    }
+}
+
+fn handle_remote_calls(
+    mut commands: Commands,
+    inbox: Res<RemoteInbox>,
+    mut io: ResMut<SceneIoState>,
+    mut ev_load: EventWriter<LoadSceneEvent>,
+    mut actions: EventWriter<EditorAction>,
+    mut q_cam: Query<(&mut Transform, &mut OrbitCamera)>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut cache: ResMut<AssetCache>,
+    mut effects: PostEffects,
+) {
+    let inbox = inbox.0.lock().expect("remote inbox");
+    for call in inbox.try_iter() {
+        let params = &call.params;
+        let result = match call.method.as_str() {
+            "load_scene" => match params.get("path").and_then(Value::as_str) {
+                Some(path) => {
+                    io.filename = path.to_string();
+                    ev_load.write(LoadSceneEvent);
+                    Ok(Value::Null)
+                }
+                None => Err(RpcError::InvalidParams("'path' is required".into())),
+            },
+            "action" => match params.get("name").and_then(Value::as_str) {
+                Some(name) => match EditorAction::from_name(name) {
+                    Some(action) => {
+                        actions.write(action);
+                        Ok(Value::Null)
+                    }
+                    None => Err(RpcError::InvalidParams(format!("unknown action '{name}'"))),
+                },
+                None => Err(RpcError::InvalidParams("'name' is required".into())),
+            },
+            "set_effect" => set_effect(&mut effects, params),
+            "move_camera" => move_camera(params, &mut q_cam),
+            "spawn" => spawn(
+                params,
+                &mut commands,
+                &mut meshes,
+                &mut materials,
+                &mut cache,
+            ),
+            "screenshot" => {
+                let path = params
+                    .get("path")
+                    .and_then(Value::as_str)
+                    .map_or_else(|| format!("screenshot-{}.png", unix_secs()), str::to_string);
+                commands
+                    .spawn(Screenshot::primary_window())
+                    .observe(save_to_disk(path.clone()));
+                // Written a frame or two later, once the frame has been rendered
+                Ok(json!({ "path": path }))
+            }
+            _ => Err(RpcError::MethodNotFound),
+        };
+        let _ = call.reply.send(result);
+    }
+}
+
+fn unix_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// `target` `[x, y, z]`, `yaw_deg`, `pitch_deg` and `distance`, each optional.
+fn move_camera(params: &Value, q_cam: &mut Query<(&mut Transform, &mut OrbitCamera)>) -> RpcResult {
+    let target = array_param::<3>(params, "target")?.map(Vec3::from_array);
+    let Some((mut tf, mut orbit)) = q_cam.iter_mut().next() else {
+        return Err(RpcError::InvalidParams("no orbit camera".into()));
+    };
+    let target = target.unwrap_or(orbit.target());
+    let yaw = f32_param(params, "yaw_deg").map_or(orbit.yaw(), f32::to_radians);
+    let pitch = f32_param(params, "pitch_deg").map_or(orbit.pitch(), f32::to_radians);
+    let distance = f32_param(params, "distance")
+        .unwrap_or_else(|| tf.translation.distance(orbit.target()))
+        .max(0.1);
+    orbit.aim(target, yaw, pitch);
+    // The orbit systems re-aim from the current distance to the target
+    let dir = (tf.translation - target).normalize_or(Vec3::Y);
+    tf.translation = target + dir * distance;
+    Ok(Value::Null)
+}
+
+/// `kind` (cuboid, sphere, plane or prism), `name`, `position` `[x, y, z]`, `color` `[r, g, b, a]`.
+fn spawn(
+    params: &Value,
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    cache: &mut AssetCache,
+) -> RpcResult {
+    let kind = match params
+        .get("kind")
+        .and_then(Value::as_str)
+        .unwrap_or("cuboid")
+    {
+        "cuboid" => SpawnKind::Cuboid,
+        "sphere" => SpawnKind::Sphere,
+        "plane" => SpawnKind::Plane,
+        "prism" => SpawnKind::Prism,
+        other => return Err(RpcError::InvalidParams(format!("cannot spawn '{other}'"))),
+    };
+    let position = array_param::<3>(params, "position")?.unwrap_or_default();
+    let [r, g, b, a] = array_param::<4>(params, "color")?.unwrap_or([0.8, 0.8, 0.8, 1.0]);
+    let name = params
+        .get("name")
+        .and_then(Value::as_str)
+        .unwrap_or("Remote object")
+        .to_string();
+
+    let material = cache.material(
+        materials,
+        PlainMaterial {
+            base_color: Color::srgba(r, g, b, a),
+            metallic: 0.0,
+            roughness: 0.6,
+            emissive: LinearRgba::BLACK,
+        },
+    );
+    let entity = commands
+        .spawn((
+            Mesh3d(cache.mesh(meshes, kind)),
+            MeshMaterial3d(material),
+            Transform::from_translation(Vec3::from_array(position)),
+            Editable,
+            EditableMesh {
+                kind,
+                collider: None,
+            },
+            Name::new(name),
+        ))
+        .id();
+    Ok(json!({ "entity": entity.to_bits() }))
+}
+
+/// `effect` names the pass; the other keys are its fields (`enabled` is a bool).
+fn set_effect(effects: &mut PostEffects, params: &Value) -> RpcResult {
+    let effect = params.get("effect").and_then(Value::as_str).unwrap_or("");
+    let p = params;
+    let missing = || RpcError::InvalidParams(format!("effect '{effect}' is not available"));
+    match effect {
+        #[cfg(feature = "chroma")]
+        "chroma" => {
+            let mut s = effects.chroma.iter_mut().next().ok_or_else(missing)?;
+            set_if_some(&mut s.enabled, flag_param(p, "enabled"));
+            set_if_some(&mut s.intensity, f32_param(p, "intensity"));
+        }
+        #[cfg(feature = "crt")]
+        "crt" => {
+            let mut s = effects.crt.iter_mut().next().ok_or_else(missing)?;
+            set_if_some(&mut s.enabled, flag_param(p, "enabled"));
+            set_if_some(&mut s.intensity, f32_param(p, "intensity"));
+            set_if_some(&mut s.scanline_freq, f32_param(p, "scanline_freq"));
+            set_if_some(&mut s.line_intensity, f32_param(p, "line_intensity"));
+        }
+        #[cfg(feature = "gradient")]
+        "gradient" => {
+            let top_right = array_param::<4>(p, "color_top_right")?;
+            let bottom_left = array_param::<4>(p, "color_bottom_left")?;
+            let mut s = effects.gradient.iter_mut().next().ok_or_else(missing)?;
+            set_if_some(&mut s.enabled, flag_param(p, "enabled"));
+            set_if_some(&mut s.additive, flag_param(p, "additive"));
+            set_if_some(&mut s.strength, f32_param(p, "strength"));
+            set_if_some(&mut s.color_top_right, top_right.map(Vec4::from_array));
+            set_if_some(&mut s.color_bottom_left, bottom_left.map(Vec4::from_array));
+        }
+        #[cfg(feature = "lut")]
+        "lut" => {
+            let mut s = effects.lut.iter_mut().next().ok_or_else(missing)?;
+            set_if_some(&mut s.enabled, flag_param(p, "enabled"));
+            set_if_some(&mut s.strength, f32_param(p, "strength"));
+        }
+        "lens_flare" => {
+            let mut s = effects.flare.iter_mut().next().ok_or_else(missing)?;
+            set_if_some(&mut s.enabled, flag_param(p, "enabled"));
+            set_if_some(&mut s.intensity, f32_param(p, "intensity"));
+            set_if_some(&mut s.ghost_intensity, f32_param(p, "ghost_intensity"));
+            set_if_some(&mut s.halo_intensity, f32_param(p, "halo_intensity"));
+        }
+        "fog" => {
+            let color = array_param::<3>(p, "color")?;
+            let mut fog = effects.fog.iter_mut().next().ok_or_else(missing)?;
+            if let Some(density) = f32_param(p, "density") {
+                fog.falloff = FogFalloff::Exponential { density };
+            }
+            if let Some([r, g, b]) = color {
+                fog.color = Color::srgb(r, g, b);
+            }
+        }
+        _ => return Err(missing()),
+    }
+    Ok(Value::Null)
+}
@@ -0,0 +1,231 @@
+//! How an object's rotation is edited in the inspector: Euler angles in either order, an axis
+//! and an angle, or the quaternion itself.
+//!
+//! Euler angles read back from a quaternion aren't the ones that were typed (180° about X comes
+//! back as 180° about Y and Z, say), and compound rotations near ±90° lose an axis. So each
+//! object remembers its mode and the values as last entered, and shows (and saves) those for as
+//! long as they still produce its rotation.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Quaternions whose components differ by less than this count as the same rotation.
+const SAME_ROTATION: f32 = 1e-5;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum RotationMode {
+    /// Degrees about X, then Y, then Z
+    #[default]
+    EulerXyz,
+    /// Degrees about Z, then Y, then X
+    EulerZyx,
+    /// A unit axis and degrees about it
+    AxisAngle,
+    /// x, y, z, w
+    Quaternion,
+}
+
+impl RotationMode {
+    pub const ALL: [Self; 4] = [
+        Self::EulerXyz,
+        Self::EulerZyx,
+        Self::AxisAngle,
+        Self::Quaternion,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::EulerXyz => "Euler XYZ",
+            Self::EulerZyx => "Euler ZYX",
+            Self::AxisAngle => "Axis-angle",
+            Self::Quaternion => "Quaternion",
+        }
+    }
+
+    /// Labels of the fields in use, in order.
+    pub fn fields(self) -> &'static [&'static str] {
+        match self {
+            Self::EulerXyz => &["x", "y", "z"],
+            Self::EulerZyx => &["z", "y", "x"],
+            Self::AxisAngle => &["axis x", "axis y", "axis z", "angle"],
+            Self::Quaternion => &["x", "y", "z", "w"],
+        }
+    }
+
+    /// Whether field `i` is in degrees (and so snaps to the rotate step).
+    pub fn is_angle(self, i: usize) -> bool {
+        match self {
+            Self::EulerXyz | Self::EulerZyx => true,
+            Self::AxisAngle => i == 3,
+            Self::Quaternion => false,
+        }
+    }
+
+    /// `rotation` as this mode's field values (unused ones are zero).
+    pub fn values_of(self, rotation: Quat) -> [f32; 4] {
+        match self {
+            Self::EulerXyz => {
+                let (x, y, z) = rotation.to_euler(EulerRot::XYZ);
+                [x.to_degrees(), y.to_degrees(), z.to_degrees(), 0.0]
+            }
+            Self::EulerZyx => {
+                let (z, y, x) = rotation.to_euler(EulerRot::ZYX);
+                [z.to_degrees(), y.to_degrees(), x.to_degrees(), 0.0]
+            }
+            Self::AxisAngle => {
+                let (axis, angle) = rotation.to_axis_angle();
+                [axis.x, axis.y, axis.z, angle.to_degrees()]
+            }
+            Self::Quaternion => rotation.to_array(),
+        }
+    }
+
+    /// The rotation the field `values` describe. A zero axis or quaternion leaves the object
+    /// unturned (`Quat::IDENTITY`) rather than filling it with NaNs.
+    pub fn rotation_of(self, values: [f32; 4]) -> Quat {
+        let [a, b, c, d] = values;
+        match self {
+            Self::EulerXyz => Quat::from_euler(
+                EulerRot::XYZ,
+                a.to_radians(),
+                b.to_radians(),
+                c.to_radians(),
+            ),
+            Self::EulerZyx => Quat::from_euler(
+                EulerRot::ZYX,
+                a.to_radians(),
+                b.to_radians(),
+                c.to_radians(),
+            ),
+            Self::AxisAngle => Vec3::new(a, b, c)
+                .try_normalize()
+                .map_or(Quat::IDENTITY, |axis| {
+                    Quat::from_axis_angle(axis, d.to_radians())
+                }),
+            Self::Quaternion => Vec4::from_array(values)
+                .try_normalize()
+                .map_or(Quat::IDENTITY, Quat::from_vec4),
+        }
+    }
+}
+
+/// An object's rotation editing mode and the values last entered in it. Saved with the scene.
+#[derive(Component, Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct RotationEdit {
+    pub mode: RotationMode,
+    pub values: [f32; 4],
+}
+
+impl RotationEdit {
+    /// `rotation` in `mode`, freshly converted.
+    pub fn from_rotation(mode: RotationMode, rotation: Quat) -> Self {
+        Self {
+            mode,
+            values: mode.values_of(rotation),
+        }
+    }
+
+    pub fn rotation(&self) -> Quat {
+        self.mode.rotation_of(self.values)
+    }
+
+    /// Whether the values still describe `rotation` (nothing else has turned the object since).
+    pub fn matches(&self, rotation: Quat) -> bool {
+        // q and -q are the same rotation
+        let own = self.rotation();
+        own.abs_diff_eq(rotation, SAME_ROTATION) || own.abs_diff_eq(-rotation, SAME_ROTATION)
+    }
+}
+
+/// What to show for `rotation` in `mode`: the remembered values while they still hold,
+/// otherwise a fresh conversion.
+pub fn displayed_rotation(
+    edit: Option<&RotationEdit>,
+    mode: RotationMode,
+    rotation: Quat,
+) -> [f32; 4] {
+    match edit {
+        Some(edit) if edit.mode == mode && edit.matches(rotation) => edit.values,
+        _ => mode.values_of(rotation),
+    }
+}
+
+/// A scene file's rotation fields for an object: Euler XYZ degrees (read by every version) and,
+/// when the object has a mode of its own, that mode with its values.
+pub fn saved_rotation(
+    rotation: Quat,
+    edit: Option<&RotationEdit>,
+) -> ([f32; 3], Option<RotationEdit>) {
+    let edit = edit.map(|e| {
+        if e.matches(rotation) {
+            *e
+        } else {
+            RotationEdit::from_rotation(e.mode, rotation)
+        }
+    });
+    let [x, y, z, _] = displayed_rotation(edit.as_ref(), RotationMode::EulerXyz, rotation);
+    // Plain Euler XYZ objects are fully described by the angles
+    let edit = edit.filter(|e| e.mode != RotationMode::EulerXyz);
+    ([x, y, z], edit)
+}
+
+/// The rotation stored by [`saved_rotation`], with the mode and values to edit it in (the
+/// angles as written, for files without a mode).
+pub fn loaded_rotation(euler_deg: [f32; 3], edit: Option<RotationEdit>) -> (Quat, RotationEdit) {
+    let [x, y, z] = euler_deg;
+    let edit = edit.unwrap_or(RotationEdit {
+        mode: RotationMode::EulerXyz,
+        values: [x, y, z, 0.0],
+    });
+    (edit.rotation(), edit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn same_rotation(a: Quat, b: Quat) -> bool {
+        a.abs_diff_eq(b, 1e-4) || a.abs_diff_eq(-b, 1e-4)
+    }
+
+    #[test]
+    fn every_mode_round_trips() {
+        let rotation = Quat::from_euler(EulerRot::XYZ, 0.3, -1.1, 2.0);
+        for mode in RotationMode::ALL {
+            let back = mode.rotation_of(mode.values_of(rotation));
+            assert!(same_rotation(back, rotation), "{mode:?}: {back:?}");
+        }
+    }
+
+    #[test]
+    fn degenerate_input_is_identity() {
+        let axis_angle = RotationMode::AxisAngle.rotation_of([0.0, 0.0, 0.0, 90.0]);
+        assert_eq!(axis_angle, Quat::IDENTITY);
+        let quaternion = RotationMode::Quaternion.rotation_of([0.0; 4]);
+        assert_eq!(quaternion, Quat::IDENTITY);
+    }
+
+    #[test]
+    fn unnormalized_input_is_normalized() {
+        let axis_angle = RotationMode::AxisAngle.rotation_of([0.0, 5.0, 0.0, 90.0]);
+        assert!(same_rotation(
+            axis_angle,
+            Quat::from_rotation_y(90f32.to_radians())
+        ));
+        let quaternion = RotationMode::Quaternion.rotation_of([0.0, 0.0, 0.0, 3.0]);
+        assert!(same_rotation(quaternion, Quat::IDENTITY));
+    }
+
+    #[test]
+    fn typed_values_survive_saving() {
+        // 180° about X reads back as 180° about Y and Z; the typed angles must not
+        let edit = RotationEdit {
+            mode: RotationMode::EulerZyx,
+            values: [0.0, 0.0, 180.0, 0.0],
+        };
+        let (euler, saved) = saved_rotation(edit.rotation(), Some(&edit));
+        let (rotation, loaded) = loaded_rotation(euler, saved);
+        assert_eq!(loaded, edit);
+        assert!(same_rotation(rotation, edit.rotation()));
+    }
+}
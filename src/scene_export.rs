@@ -0,0 +1,239 @@
+//! Scene saves run on a worker task: the objects are gathered on the main thread, then the
+//! document is serialized and written in the background, so saving a big scene doesn't stall
+//! the editor. Slow saves show their progress at the bottom of the window with a Cancel button,
+//! and [`SceneExported`] reports how each one went. Quitting waits for a save in progress.
+
+use std::cell::RefCell;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+use bevy::prelude::*;
+use bevy::tasks::{AsyncComputeTaskPool, Task, block_on, poll_once};
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+use serde::Serialize;
+use serde::ser::{Error as _, SerializeSeq, Serializer};
+
+use crate::toasts::Toasts;
+
+/// Saves quicker than this finish without showing their progress.
+const PROGRESS_DELAY_SECS: f32 = 0.3;
+
+/// Objects serialized so far out of `total`, shared with the worker.
+#[derive(Default)]
+struct Progress {
+    done: AtomicUsize,
+    total: usize,
+    cancel: AtomicBool,
+}
+
+thread_local! {
+    /// Progress of the save being serialized on this thread, if any.
+    static CURRENT: RefCell<Option<Arc<Progress>>> = const { RefCell::new(None) };
+}
+
+/// `serialize_with` for lists of saved objects: counts each one towards the running save's
+/// progress, and stops the save once it's cancelled.
+pub(crate) fn serialize_counted<T: Serialize, S: Serializer>(
+    items: &[T],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let progress = CURRENT.with_borrow(Clone::clone);
+    let mut seq = serializer.serialize_seq(Some(items.len()))?;
+    for item in items {
+        if let Some(progress) = &progress {
+            if progress.cancel.load(Ordering::Relaxed) {
+                return Err(S::Error::custom("cancelled"));
+            }
+            progress.done.fetch_add(1, Ordering::Relaxed);
+        }
+        seq.serialize_element(item)?;
+    }
+    seq.end()
+}
+
+/// How a save ended.
+#[derive(Clone, PartialEq, Debug)]
+pub enum ExportOutcome {
+    Written,
+    Cancelled,
+    Failed(String),
+}
+
+/// Sent when a save started through [`SceneExport::start`] is done.
+#[derive(Event, Clone, Debug)]
+pub struct SceneExported {
+    pub path: String,
+    pub outcome: ExportOutcome,
+    /// Whether the user asked for it (browser autosaves don't count)
+    pub user_save: bool,
+}
+
+struct Running {
+    task: Task<ExportOutcome>,
+    progress: Arc<Progress>,
+    path: String,
+    user_save: bool,
+    /// `Time<Real>` seconds it started at
+    started_at: f32,
+}
+
+/// The save running in the background, one at a time.
+#[derive(Resource, Default)]
+pub struct SceneExport {
+    running: Option<Running>,
+}
+
+impl SceneExport {
+    pub fn is_running(&self) -> bool {
+        self.running.is_some()
+    }
+
+    /// Serialize `doc` and hand the JSON to `write` on a worker task; `total` is the objects
+    /// the two serialize between them. `now` is the current `Time<Real>`.
+    pub(crate) fn start<T, W>(
+        &mut self,
+        path: String,
+        doc: T,
+        total: usize,
+        user_save: bool,
+        now: f32,
+        write: W,
+    ) where
+        T: Serialize + Send + 'static,
+        W: FnOnce(&str, &str) -> Result<(), String> + Send + 'static,
+    {
+        let progress = Arc::new(Progress { total, ..default() });
+        let shared = progress.clone();
+        let target = path.clone();
+        let task = AsyncComputeTaskPool::get().spawn(async move {
+            // `write` may serialize more objects of its own, counted the same way
+            CURRENT.set(Some(shared.clone()));
+            let outcome = match serde_json::to_string_pretty(&doc) {
+                _ if shared.cancel.load(Ordering::Relaxed) => ExportOutcome::Cancelled,
+                Ok(json) => match write(&target, &json) {
+                    Ok(()) => ExportOutcome::Written,
+                    Err(_) if shared.cancel.load(Ordering::Relaxed) => ExportOutcome::Cancelled,
+                    Err(e) => ExportOutcome::Failed(e),
+                },
+                Err(e) => ExportOutcome::Failed(e.to_string()),
+            };
+            CURRENT.set(None);
+            outcome
+        });
+        self.running = Some(Running {
+            task,
+            progress,
+            path,
+            user_save,
+            started_at: now,
+        });
+    }
+
+    /// Stop the running save; it ends as [`ExportOutcome::Cancelled`] and writes nothing.
+    pub fn cancel(&self) {
+        if let Some(running) = &self.running {
+            running.progress.cancel.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Share of the running save's objects serialized so far.
+    pub fn progress(&self) -> Option<f32> {
+        let progress = &self.running.as_ref()?.progress;
+        let done = progress.done.load(Ordering::Relaxed);
+        Some(done as f32 / progress.total.max(1) as f32)
+    }
+}
+
+pub struct SceneExportPlugin;
+impl Plugin for SceneExportPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SceneExport>()
+            .add_event::<SceneExported>()
+            .add_systems(Update, finish_export)
+            .add_systems(Last, finish_on_exit)
+            .add_systems(EguiPrimaryContextPass, export_progress);
+    }
+}
+
+fn finish_export(
+    mut export: ResMut<SceneExport>,
+    mut toasts: Option<ResMut<Toasts>>,
+    mut ev_done: EventWriter<SceneExported>,
+) {
+    let Some(running) = export.running.as_mut() else {
+        return;
+    };
+    let Some(outcome) = block_on(poll_once(&mut running.task)) else {
+        return;
+    };
+    let Some(running) = export.running.take() else {
+        return;
+    };
+    if let Some(toasts) = toasts.as_mut() {
+        match &outcome {
+            ExportOutcome::Written if running.user_save => {
+                toasts.info(format!("Saved {}", running.path));
+            }
+            ExportOutcome::Written => {}
+            ExportOutcome::Cancelled => toasts.info("Save cancelled"),
+            ExportOutcome::Failed(e) => toasts.warn(format!("Couldn't save {}: {e}", running.path)),
+        }
+    }
+    ev_done.write(SceneExported {
+        path: running.path,
+        outcome,
+        user_save: running.user_save,
+    });
+}
+
+/// Quitting waits for the running save, rather than dropping it half written.
+fn finish_on_exit(mut export: ResMut<SceneExport>, mut ev_exit: EventReader<AppExit>) {
+    if ev_exit.read().count() == 0 {
+        return;
+    }
+    let Some(running) = export.running.take() else {
+        return;
+    };
+    match block_on(running.task) {
+        ExportOutcome::Written => eprintln!("Scene saved to {}", running.path),
+        ExportOutcome::Cancelled => {}
+        ExportOutcome::Failed(e) => eprintln!("Couldn't save {}: {e}", running.path),
+    }
+}
+
+/// Progress bar and Cancel button for a save that takes a while.
+fn export_progress(mut ctxs: EguiContexts, export: Res<SceneExport>, time: Res<Time<Real>>) {
+    let Some(running) = &export.running else {
+        return;
+    };
+    if time.elapsed_secs() - running.started_at < PROGRESS_DELAY_SECS {
+        return;
+    }
+    let Some(fraction) = export.progress() else {
+        return;
+    };
+    let Ok(ctx) = ctxs.ctx_mut() else {
+        return;
+    };
+
+    egui::Area::new(egui::Id::new("export_progress"))
+        .anchor(egui::Align2::CENTER_BOTTOM, egui::vec2(0.0, -12.0))
+        .order(egui::Order::Foreground)
+        .show(ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(format!("Saving {}", running.path));
+                    ui.add(
+                        egui::ProgressBar::new(fraction)
+                            .desired_width(160.0)
+                            .show_percentage(),
+                    );
+                    if ui.button("Cancel").clicked() {
+                        export.cancel();
+                    }
+                });
+            });
+        });
+    // Keep the bar moving while nothing else asks for a frame
+    ctx.request_repaint();
+}
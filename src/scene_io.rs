@@ -0,0 +1,159 @@
+//! Where scene files live: plain files natively; in the browser (wasm32) a download/upload pair
+//! backed by localStorage, which also holds the periodic autosave.
+//!
+//! `read_text`/`write_text`/`remove_text` are for the tool's own files (like the app settings
+//! and streamed-out cells), which stay in localStorage on the web.
+
+#[cfg(not(target_arch = "wasm32"))]
+mod platform {
+    /// Write to a temporary file beside `path` and move it over, so a save cut short never
+    /// leaves half a scene behind.
+    pub fn write_scene(path: &str, json: &str) -> Result<(), String> {
+        let tmp = format!("{path}.tmp");
+        std::fs::write(&tmp, json).map_err(|e| e.to_string())?;
+        std::fs::rename(&tmp, path).map_err(|e| e.to_string())
+    }
+
+    pub fn read_scene(path: &str) -> Option<String> {
+        std::fs::read_to_string(path).ok()
+    }
+
+    pub fn write_text(path: &str, text: &str) -> Result<(), String> {
+        if let Some(dir) = std::path::Path::new(path).parent() {
+            std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        std::fs::write(path, text).map_err(|e| e.to_string())
+    }
+
+    pub fn read_text(path: &str) -> Option<String> {
+        std::fs::read_to_string(path).ok()
+    }
+
+    pub fn remove_text(path: &str) {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod platform {
+    use std::sync::Mutex;
+
+    use wasm_bindgen::{JsCast, JsValue};
+    use wasm_bindgen_futures::JsFuture;
+    use web_sys::{Blob, BlobPropertyBag, HtmlAnchorElement, HtmlInputElement, Storage, Url};
+
+    /// localStorage name of the periodic autosave.
+    pub const AUTOSAVE: &str = "autosave.json";
+    const KEY_PREFIX: &str = "tunable_world/";
+
+    /// Name of the last uploaded file, set once the browser has read it into localStorage.
+    static UPLOADED: Mutex<Option<String>> = Mutex::new(None);
+
+    fn storage() -> Option<Storage> {
+        web_sys::window()?.local_storage().ok()?
+    }
+
+    fn store(name: &str, json: &str) -> Result<(), String> {
+        storage()
+            .ok_or("localStorage unavailable")?
+            .set_item(&format!("{KEY_PREFIX}{name}"), json)
+            .map_err(|_| "localStorage is full".to_string())
+    }
+
+    fn js_err(e: JsValue) -> String {
+        format!("{e:?}")
+    }
+
+    /// Keep a copy in localStorage and hand the file to the browser as a download.
+    pub fn write_scene(path: &str, json: &str) -> Result<(), String> {
+        store(path, json)?;
+
+        let parts = js_sys::Array::of1(&JsValue::from_str(json));
+        let options = BlobPropertyBag::new();
+        options.set_type("application/json");
+        let blob = Blob::new_with_str_sequence_and_options(&parts, &options).map_err(js_err)?;
+        let url = Url::create_object_url_with_blob(&blob).map_err(js_err)?;
+
+        let document = web_sys::window()
+            .and_then(|w| w.document())
+            .ok_or("no document")?;
+        let anchor: HtmlAnchorElement = document
+            .create_element("a")
+            .map_err(js_err)?
+            .unchecked_into();
+        anchor.set_href(&url);
+        anchor.set_download(path);
+        anchor.click();
+        Url::revoke_object_url(&url).map_err(js_err)
+    }
+
+    pub fn read_scene(path: &str) -> Option<String> {
+        storage()?.get_item(&format!("{KEY_PREFIX}{path}")).ok()?
+    }
+
+    /// Like `write_scene`, without the download.
+    pub fn write_text(path: &str, text: &str) -> Result<(), String> {
+        store(path, text)
+    }
+
+    pub fn read_text(path: &str) -> Option<String> {
+        read_scene(path)
+    }
+
+    pub fn remove_text(path: &str) {
+        if let Some(storage) = storage() {
+            let _ = storage.remove_item(&format!("{KEY_PREFIX}{path}"));
+        }
+    }
+
+    pub fn autosave(json: &str) -> Result<(), String> {
+        store(AUTOSAVE, json)
+    }
+
+    /// Open the browser's file picker; the chosen file is copied into localStorage and its name
+    /// shows up in `take_upload` once it has been read.
+    pub fn pick_upload() {
+        let Some(document) = web_sys::window().and_then(|w| w.document()) else {
+            return;
+        };
+        let Ok(input) = document.create_element("input") else {
+            return;
+        };
+        let input: HtmlInputElement = input.unchecked_into();
+        input.set_type("file");
+        input.set_accept(".json,application/json");
+
+        let on_change = wasm_bindgen::closure::Closure::once_into_js(|event: web_sys::Event| {
+            let Some(input) = event
+                .target()
+                .and_then(|t| t.dyn_into::<HtmlInputElement>().ok())
+            else {
+                return;
+            };
+            let Some(file) = input.files().and_then(|files| files.get(0)) else {
+                return;
+            };
+            wasm_bindgen_futures::spawn_local(async move {
+                let Ok(text) = JsFuture::from(file.text()).await else {
+                    return;
+                };
+                let (Some(text), name) = (text.as_string(), file.name()) else {
+                    return;
+                };
+                if store(&name, &text).is_ok() {
+                    if let Ok(mut uploaded) = UPLOADED.lock() {
+                        *uploaded = Some(name);
+                    }
+                }
+            });
+        });
+        input.set_onchange(Some(on_change.unchecked_ref()));
+        input.click();
+    }
+
+    pub fn take_upload() -> Option<String> {
+        UPLOADED.lock().ok()?.take()
+    }
+}
+
+pub use platform::*;
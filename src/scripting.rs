@@ -0,0 +1,884 @@
+//! Rhai scripts attached to a scene (the `script` field of its JSON), run in Play mode.
+//!
+//! Top-level statements run when Play starts, then `fn start()` if the script defines it, then
+//! `fn update(dt)` every frame. Scripts reach the scene only through the functions registered in
+//! `build_engine` (objects are addressed by name); they have no file or network access.
+//!
+//! ```rhai
+//! on_enter("ElevatorPad", "go_up");
+//!
+//! fn go_up() {
+//!     let p = position("Elevator");
+//!     tween_position("Elevator", p[0], p[1] + 3.0, p[2], 2.0);
+//! }
+//! ```
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use bevy::prelude::*;
+use bevy::render::primitives::Aabb;
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+use bevy_rapier3d::prelude::{ColliderScale, KinematicCharacterController};
+use rhai::{AST, Array, Dynamic, Engine, EvalAltResult, FLOAT, Scope};
+
+use crate::SceneEditState;
+use crate::actions::EditorAction;
+use crate::asset_cache::{AssetCache, PlainMaterial};
+use crate::block_groups::{BlockGroupAction, BlockGroupEvent};
+use crate::bounds::editable_collider;
+use crate::camera::OrbitCamera;
+use crate::custom_props::CustomProperties;
+use crate::dissolve::{DissolveSettings, dissolve_away, dissolve_in};
+use crate::editor_mode::EditorMode;
+use crate::inspector::{
+    Editable, EditableMesh, LoadSceneEvent, SceneIoState, SpawnKind, aabb_world, obb_to_world_aabb,
+};
+use crate::tween::{AnimateTo, Easing, TweenTarget};
+
+/// Operation budget per call, so a runaway loop stalls one frame instead of the app
+const MAX_OPERATIONS: u64 = 500_000;
+
+type ScriptResult<T> = Result<T, Box<EvalAltResult>>;
+
+/// A named scene object as scripts see it, refreshed every frame.
+struct ScriptObject {
+    entity: Entity,
+    transform: Transform,
+    color: Option<Color>,
+    /// World AABB (min, max), for triggers
+    bounds: (Vec3, Vec3),
+    properties: Option<CustomProperties>,
+}
+
+/// Changes requested by a script, applied after it returns.
+enum ScriptCommand {
+    Spawn {
+        kind: SpawnKind,
+        name: String,
+        position: Vec3,
+    },
+    Despawn(Entity),
+    SetTransform(Entity, Transform),
+    SetColor(Entity, Color),
+    SetEmissive(Entity, LinearRgba),
+    Tween(Entity, AnimateTo),
+    /// Post-effect tween, run on the camera
+    TweenEffect(AnimateTo),
+    BlockGroup(String, BlockGroupAction),
+    Action(EditorAction),
+}
+
+/// `on_enter` / `on_exit` subscription: call `callback` when the player crosses into or out of
+/// `object`'s bounds.
+struct TriggerSub {
+    object: String,
+    enter: bool,
+    callback: String,
+    inside: bool,
+}
+
+/// What the registered functions read and write while a script runs.
+#[derive(Default)]
+struct ScriptWorld {
+    objects: HashMap<String, ScriptObject>,
+    player: Option<Vec3>,
+    commands: Vec<ScriptCommand>,
+    triggers: Vec<TriggerSub>,
+}
+
+impl ScriptWorld {
+    fn object(&self, name: &str) -> ScriptResult<&ScriptObject> {
+        self.objects
+            .get(name)
+            .ok_or_else(|| format!("no object named '{name}'").into())
+    }
+
+    fn object_mut(&mut self, name: &str) -> ScriptResult<&mut ScriptObject> {
+        self.objects
+            .get_mut(name)
+            .ok_or_else(|| format!("no object named '{name}'").into())
+    }
+
+    /// Update the snapshot right away, so the script reads back what it just wrote.
+    fn set_transform(&mut self, name: &str, edit: impl FnOnce(&mut Transform)) -> ScriptResult<()> {
+        let obj = self.object_mut(name)?;
+        edit(&mut obj.transform);
+        let command = ScriptCommand::SetTransform(obj.entity, obj.transform);
+        self.commands.push(command);
+        Ok(())
+    }
+}
+
+type ScriptObjects<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static Name,
+        &'static mut Transform,
+        &'static GlobalTransform,
+        Option<&'static Aabb>,
+        Option<&'static MeshMaterial3d<StandardMaterial>>,
+        Option<&'static CustomProperties>,
+    ),
+    With<Editable>,
+>;
+
+/// Compiled scene script and its state while playing.
+#[derive(Resource)]
+pub struct ScriptRuntime {
+    engine: Engine,
+    world: Arc<Mutex<ScriptWorld>>,
+    ast: Option<AST>,
+    scope: Scope<'static>,
+    /// Script file the AST was compiled from
+    loaded: String,
+    /// Last compile or runtime error
+    pub error: Option<String>,
+}
+
+impl Default for ScriptRuntime {
+    fn default() -> Self {
+        let world = Arc::new(Mutex::new(ScriptWorld::default()));
+        Self {
+            engine: build_engine(&world),
+            world,
+            ast: None,
+            scope: Scope::new(),
+            loaded: String::new(),
+            error: None,
+        }
+    }
+}
+
+/// Compile the scene's script again (after editing the file).
+#[derive(Event)]
+pub struct ReloadScriptEvent;
+
+/// Rhai scripting for scene behaviors (elevators, doors, puzzles), plus a small window to pick
+/// the scene's script and see its errors.
+pub struct ScriptingPlugin;
+impl Plugin for ScriptingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ScriptRuntime>()
+            .init_resource::<EditorMode>()
+            .init_resource::<DissolveSettings>()
+            .add_event::<ReloadScriptEvent>()
+            .add_event::<BlockGroupEvent>()
+            .add_event::<EditorAction>()
+            .add_systems(Update, (load_script, run_script).chain())
+            .add_systems(EguiPrimaryContextPass, script_window);
+    }
+}
+
+fn vec3_array(v: Vec3) -> Array {
+    vec![
+        Dynamic::from_float(v.x as FLOAT),
+        Dynamic::from_float(v.y as FLOAT),
+        Dynamic::from_float(v.z as FLOAT),
+    ]
+}
+
+fn parse_kind(kind: &str) -> ScriptResult<SpawnKind> {
+    Ok(match kind.to_ascii_lowercase().as_str() {
+        "cuboid" => SpawnKind::Cuboid,
+        "sphere" => SpawnKind::Sphere,
+        "plane" => SpawnKind::Plane,
+        "prism" => SpawnKind::Prism,
+        _ => return Err(format!("cannot spawn '{kind}' (cuboid, sphere, plane or prism)").into()),
+    })
+}
+
+fn parse_easing(easing: &str) -> ScriptResult<Easing> {
+    Ok(match easing.to_ascii_lowercase().as_str() {
+        "linear" => Easing::Linear,
+        "ease_in" => Easing::EaseIn,
+        "ease_out" => Easing::EaseOut,
+        "ease_in_out" => Easing::EaseInOut,
+        "step" => Easing::Step,
+        _ => {
+            return Err(format!(
+                "unknown easing '{easing}' (linear, ease_in, ease_out, ease_in_out or step)"
+            )
+            .into());
+        }
+    })
+}
+
+/// Post-effect value for `tween_effect`
+fn parse_effect(effect: &str, value: f32) -> ScriptResult<TweenTarget> {
+    Ok(match effect.to_ascii_lowercase().as_str() {
+        "fog_density" => TweenTarget::FogDensity(value),
+        "chroma" => TweenTarget::ChromaIntensity(value),
+        "crt" => TweenTarget::CrtIntensity(value),
+        "lut" => TweenTarget::LutStrength(value),
+        "tint" => TweenTarget::TintStrength(value),
+        _ => {
+            return Err(format!(
+                "unknown effect '{effect}' (fog_density, chroma, crt, lut or tint)"
+            )
+            .into());
+        }
+    })
+}
+
+fn lock(world: &Mutex<ScriptWorld>) -> MutexGuard<'_, ScriptWorld> {
+    world.lock().expect("script world")
+}
+
+/// Engine with the scene API registered; every function goes through `world`.
+fn build_engine(world: &Arc<Mutex<ScriptWorld>>) -> Engine {
+    let mut engine = Engine::new();
+    engine.set_max_operations(MAX_OPERATIONS);
+    engine.set_max_call_levels(32);
+    engine.on_print(|s| println!("[script] {s}"));
+
+    // Queries
+    let w = world.clone();
+    engine.register_fn("exists", move |name: &str| {
+        lock(&w).objects.contains_key(name)
+    });
+    let w = world.clone();
+    engine.register_fn("position", move |name: &str| -> ScriptResult<Array> {
+        Ok(vec3_array(lock(&w).object(name)?.transform.translation))
+    });
+    let w = world.clone();
+    engine.register_fn("rotation", move |name: &str| -> ScriptResult<Array> {
+        let (x, y, z) = lock(&w)
+            .object(name)?
+            .transform
+            .rotation
+            .to_euler(EulerRot::XYZ);
+        Ok(vec3_array(Vec3::new(
+            x.to_degrees(),
+            y.to_degrees(),
+            z.to_degrees(),
+        )))
+    });
+    let w = world.clone();
+    engine.register_fn("scale", move |name: &str| -> ScriptResult<Array> {
+        Ok(vec3_array(lock(&w).object(name)?.transform.scale))
+    });
+    let w = world.clone();
+    engine.register_fn("color", move |name: &str| -> ScriptResult<Array> {
+        let c = lock(&w)
+            .object(name)?
+            .color
+            .ok_or_else(|| format!("'{name}' has no material"))?
+            .to_srgba();
+        Ok([c.red, c.green, c.blue, c.alpha]
+            .map(|v| Dynamic::from_float(v as FLOAT))
+            .to_vec())
+    });
+    // Custom property as a string, or `()` when the object doesn't have it
+    let w = world.clone();
+    engine.register_fn(
+        "property",
+        move |name: &str, key: &str| -> ScriptResult<Dynamic> {
+            let world = lock(&w);
+            let value = world
+                .object(name)?
+                .properties
+                .as_ref()
+                .and_then(|p| p.get(key));
+            Ok(value.map_or(Dynamic::UNIT, |v| Dynamic::from(v.to_string())))
+        },
+    );
+    let w = world.clone();
+    engine.register_fn("player_position", move || -> Array {
+        lock(&w).player.map(vec3_array).unwrap_or_default()
+    });
+
+    // Transforms (rotation in degrees, XYZ order as in the inspector)
+    let w = world.clone();
+    engine.register_fn(
+        "set_position",
+        move |name: &str, x: FLOAT, y: FLOAT, z: FLOAT| -> ScriptResult<()> {
+            lock(&w).set_transform(name, |tf| {
+                tf.translation = Vec3::new(x as f32, y as f32, z as f32);
+            })
+        },
+    );
+    let w = world.clone();
+    engine.register_fn(
+        "set_rotation",
+        move |name: &str, x: FLOAT, y: FLOAT, z: FLOAT| -> ScriptResult<()> {
+            lock(&w).set_transform(name, |tf| {
+                tf.rotation = Quat::from_euler(
+                    EulerRot::XYZ,
+                    (x as f32).to_radians(),
+                    (y as f32).to_radians(),
+                    (z as f32).to_radians(),
+                );
+            })
+        },
+    );
+    let w = world.clone();
+    engine.register_fn(
+        "set_scale",
+        move |name: &str, x: FLOAT, y: FLOAT, z: FLOAT| -> ScriptResult<()> {
+            lock(&w).set_transform(name, |tf| {
+                tf.scale = Vec3::new(x as f32, y as f32, z as f32);
+            })
+        },
+    );
+
+    // Materials (sRGB 0..1; emissive is linear and can go above 1 for bloom)
+    let w = world.clone();
+    engine.register_fn(
+        "set_color",
+        move |name: &str, r: FLOAT, g: FLOAT, b: FLOAT, a: FLOAT| -> ScriptResult<()> {
+            let mut world = lock(&w);
+            let obj = world.object_mut(name)?;
+            let color = Color::srgba(r as f32, g as f32, b as f32, a as f32);
+            obj.color = Some(color);
+            let command = ScriptCommand::SetColor(obj.entity, color);
+            world.commands.push(command);
+            Ok(())
+        },
+    );
+    let w = world.clone();
+    engine.register_fn(
+        "set_emissive",
+        move |name: &str, r: FLOAT, g: FLOAT, b: FLOAT| -> ScriptResult<()> {
+            let mut world = lock(&w);
+            let entity = world.object(name)?.entity;
+            let emissive = LinearRgba::rgb(r as f32, g as f32, b as f32);
+            world
+                .commands
+                .push(ScriptCommand::SetEmissive(entity, emissive));
+            Ok(())
+        },
+    );
+
+    // Spawning
+    let w = world.clone();
+    engine.register_fn(
+        "spawn",
+        move |kind: &str, name: &str, x: FLOAT, y: FLOAT, z: FLOAT| -> ScriptResult<()> {
+            let kind = parse_kind(kind)?;
+            lock(&w).commands.push(ScriptCommand::Spawn {
+                kind,
+                name: name.to_string(),
+                position: Vec3::new(x as f32, y as f32, z as f32),
+            });
+            Ok(())
+        },
+    );
+    let w = world.clone();
+    engine.register_fn("despawn", move |name: &str| -> ScriptResult<()> {
+        let mut world = lock(&w);
+        let entity = world.object(name)?.entity;
+        world.objects.remove(name);
+        world.commands.push(ScriptCommand::Despawn(entity));
+        Ok(())
+    });
+
+    // Tweens, from the current value; linear unless an easing name is passed last
+    type MakeTarget = fn(Vec3) -> TweenTarget;
+    let tweens: [(&str, MakeTarget); 3] = [
+        ("tween_position", TweenTarget::Translation),
+        ("tween_rotation", |deg| {
+            let rad = deg * std::f32::consts::PI / 180.0;
+            TweenTarget::Rotation(Quat::from_euler(EulerRot::XYZ, rad.x, rad.y, rad.z))
+        }),
+        ("tween_scale", TweenTarget::Scale),
+    ];
+    for (fn_name, make) in tweens {
+        let tween = move |w: &Mutex<ScriptWorld>,
+                          name: &str,
+                          to: Vec3,
+                          secs: FLOAT,
+                          easing: Easing|
+              -> ScriptResult<()> {
+            let mut world = lock(w);
+            let entity = world.object(name)?.entity;
+            world.commands.push(ScriptCommand::Tween(
+                entity,
+                AnimateTo {
+                    target: make(to),
+                    duration: secs as f32,
+                    easing,
+                },
+            ));
+            Ok(())
+        };
+        let w = world.clone();
+        engine.register_fn(
+            fn_name,
+            move |name: &str, x: FLOAT, y: FLOAT, z: FLOAT, secs: FLOAT| -> ScriptResult<()> {
+                let to = Vec3::new(x as f32, y as f32, z as f32);
+                tween(&w, name, to, secs, Easing::Linear)
+            },
+        );
+        let w = world.clone();
+        engine.register_fn(
+            fn_name,
+            move |name: &str,
+                  x: FLOAT,
+                  y: FLOAT,
+                  z: FLOAT,
+                  secs: FLOAT,
+                  easing: &str|
+                  -> ScriptResult<()> {
+                let to = Vec3::new(x as f32, y as f32, z as f32);
+                tween(&w, name, to, secs, parse_easing(easing)?)
+            },
+        );
+    }
+    let tween_color = |w: &Mutex<ScriptWorld>,
+                       name: &str,
+                       rgba: [FLOAT; 4],
+                       secs: FLOAT,
+                       easing: Easing|
+     -> ScriptResult<()> {
+        let mut world = lock(w);
+        let entity = world.object(name)?.entity;
+        let [r, g, b, a] = rgba.map(|v| v as f32);
+        world.commands.push(ScriptCommand::Tween(
+            entity,
+            AnimateTo {
+                target: TweenTarget::Color(Color::srgba(r, g, b, a)),
+                duration: secs as f32,
+                easing,
+            },
+        ));
+        Ok(())
+    };
+    let w = world.clone();
+    engine.register_fn(
+        "tween_color",
+        move |name: &str,
+              r: FLOAT,
+              g: FLOAT,
+              b: FLOAT,
+              a: FLOAT,
+              secs: FLOAT|
+              -> ScriptResult<()> {
+            tween_color(&w, name, [r, g, b, a], secs, Easing::Linear)
+        },
+    );
+    let w = world.clone();
+    engine.register_fn(
+        "tween_color",
+        move |name: &str,
+              r: FLOAT,
+              g: FLOAT,
+              b: FLOAT,
+              a: FLOAT,
+              secs: FLOAT,
+              easing: &str|
+              -> ScriptResult<()> {
+            tween_color(&w, name, [r, g, b, a], secs, parse_easing(easing)?)
+        },
+    );
+    let tween_effect = |w: &Mutex<ScriptWorld>,
+                        effect: &str,
+                        value: FLOAT,
+                        secs: FLOAT,
+                        easing: Easing|
+     -> ScriptResult<()> {
+        let target = parse_effect(effect, value as f32)?;
+        lock(w).commands.push(ScriptCommand::TweenEffect(AnimateTo {
+            target,
+            duration: secs as f32,
+            easing,
+        }));
+        Ok(())
+    };
+    let w = world.clone();
+    engine.register_fn(
+        "tween_effect",
+        move |effect: &str, value: FLOAT, secs: FLOAT| -> ScriptResult<()> {
+            tween_effect(&w, effect, value, secs, Easing::Linear)
+        },
+    );
+    let w = world.clone();
+    engine.register_fn(
+        "tween_effect",
+        move |effect: &str, value: FLOAT, secs: FLOAT, easing: &str| -> ScriptResult<()> {
+            tween_effect(&w, effect, value, secs, parse_easing(easing)?)
+        },
+    );
+    let tween_dissolve = |w: &Mutex<ScriptWorld>,
+                          name: &str,
+                          amount: FLOAT,
+                          secs: FLOAT,
+                          easing: Easing|
+     -> ScriptResult<()> {
+        let mut world = lock(w);
+        let entity = world.object(name)?.entity;
+        world.commands.push(ScriptCommand::Tween(
+            entity,
+            AnimateTo {
+                target: TweenTarget::Dissolve(amount as f32),
+                duration: secs as f32,
+                easing,
+            },
+        ));
+        Ok(())
+    };
+    let w = world.clone();
+    engine.register_fn(
+        "tween_dissolve",
+        move |name: &str, amount: FLOAT, secs: FLOAT| -> ScriptResult<()> {
+            tween_dissolve(&w, name, amount, secs, Easing::Linear)
+        },
+    );
+    let w = world.clone();
+    engine.register_fn(
+        "tween_dissolve",
+        move |name: &str, amount: FLOAT, secs: FLOAT, easing: &str| -> ScriptResult<()> {
+            tween_dissolve(&w, name, amount, secs, parse_easing(easing)?)
+        },
+    );
+
+    // Block groups (see `block_groups`)
+    let w = world.clone();
+    engine.register_fn("set_block_group", move |group: &str, solid: bool| {
+        let action = if solid {
+            BlockGroupAction::Solid
+        } else {
+            BlockGroupAction::Ghost
+        };
+        lock(&w)
+            .commands
+            .push(ScriptCommand::BlockGroup(group.to_string(), action));
+    });
+    let w = world.clone();
+    engine.register_fn("toggle_block_group", move |group: &str| {
+        lock(&w).commands.push(ScriptCommand::BlockGroup(
+            group.to_string(),
+            BlockGroupAction::Toggle,
+        ));
+    });
+
+    // Editor actions, as the hotkeys and the remote API run them
+    let w = world.clone();
+    engine.register_fn("editor_action", move |name: &str| -> ScriptResult<()> {
+        let Some(action) = EditorAction::from_name(name) else {
+            return Err(format!("unknown editor action '{name}'").into());
+        };
+        lock(&w).commands.push(ScriptCommand::Action(action));
+        Ok(())
+    });
+
+    // Triggers
+    for (fn_name, enter) in [("on_enter", true), ("on_exit", false)] {
+        let w = world.clone();
+        engine.register_fn(fn_name, move |object: &str, callback: &str| {
+            lock(&w).triggers.push(TriggerSub {
+                object: object.to_string(),
+                enter,
+                callback: callback.to_string(),
+                inside: false,
+            });
+        });
+    }
+
+    engine
+}
+
+/// (Re)compile when the scene's script changes, a scene is loaded or a reload is asked for.
+fn load_script(
+    mut runtime: ResMut<ScriptRuntime>,
+    io: Option<Res<SceneIoState>>,
+    mut ev_load: EventReader<LoadSceneEvent>,
+    mut ev_reload: EventReader<ReloadScriptEvent>,
+    mut mode: ResMut<EditorMode>,
+) {
+    let path = io
+        .map(|io| io.script.trim().to_string())
+        .unwrap_or_default();
+    let requested = ev_load.read().count() + ev_reload.read().count() > 0;
+    if !requested && path == runtime.loaded {
+        return;
+    }
+    runtime.loaded = path.clone();
+    runtime.ast = None;
+    runtime.error = None;
+    if path.is_empty() {
+        return;
+    }
+    let Some(source) = crate::scene_io::read_text(&path) else {
+        runtime.error = Some(format!("cannot read {path}"));
+        return;
+    };
+    match runtime.engine.compile(&source) {
+        Ok(ast) => runtime.ast = Some(ast),
+        Err(e) => runtime.error = Some(format!("{path}: {e}")),
+    }
+    // Start over with the new script if it's running
+    if *mode == EditorMode::Play {
+        mode.set_changed();
+    }
+}
+
+fn run_script(
+    mut commands: Commands,
+    mode: Res<EditorMode>,
+    time: Res<Time>,
+    mut runtime: ResMut<ScriptRuntime>,
+    mut q_objects: ScriptObjects,
+    q_player: Query<&GlobalTransform, With<KinematicCharacterController>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut cache: ResMut<AssetCache>,
+    q_camera: Query<Entity, With<OrbitCamera>>,
+    mut ev_blocks: EventWriter<BlockGroupEvent>,
+    mut ev_actions: EventWriter<EditorAction>,
+    dissolve: Res<DissolveSettings>,
+) {
+    let runtime = &mut *runtime;
+    let starting = mode.is_changed() && *mode == EditorMode::Play;
+    if mode.is_changed() {
+        // Every play session starts from a clean slate
+        runtime.scope = Scope::new();
+        lock(&runtime.world).triggers.clear();
+        for entity in q_objects.iter().map(|(e, ..)| e).chain(&q_camera) {
+            commands.entity(entity).remove::<AnimateTo>();
+        }
+    }
+    if *mode != EditorMode::Play {
+        return;
+    }
+    let Some(ast) = runtime.ast.as_ref() else {
+        return;
+    };
+
+    // Snapshot the named objects for this frame's calls
+    {
+        let mut world = lock(&runtime.world);
+        world.objects.clear();
+        for (entity, name, tf, gt, aabb, mat, props) in &q_objects {
+            let bounds = match aabb {
+                Some(aabb) => aabb_world(*aabb, gt),
+                // Collider boxes have no mesh: a unit cube scaled by the transform
+                None => obb_to_world_aabb(gt.compute_transform(), Vec3::ONE),
+            };
+            world.objects.insert(
+                name.to_string(),
+                ScriptObject {
+                    entity,
+                    transform: *tf,
+                    color: mat.and_then(|m| materials.get(&m.0)).map(|m| m.base_color),
+                    bounds: (
+                        (bounds.center - bounds.half_extents).into(),
+                        (bounds.center + bounds.half_extents).into(),
+                    ),
+                    properties: props.cloned(),
+                },
+            );
+        }
+        world.player = q_player.iter().next().map(|gt| gt.translation());
+    }
+
+    let engine = &runtime.engine;
+    let scope = &mut runtime.scope;
+    let mut result: ScriptResult<()> = Ok(());
+    if starting {
+        result = engine.run_ast_with_scope(scope, ast);
+        if result.is_ok() && has_fn(ast, "start", 0) {
+            result = engine.call_fn::<Dynamic>(scope, ast, "start", ()).map(drop);
+        }
+    }
+
+    // Triggers: compare the player against each watched object's bounds
+    let fired: Vec<String> = {
+        let mut world = lock(&runtime.world);
+        let player = world.player;
+        let ScriptWorld {
+            objects, triggers, ..
+        } = &mut *world;
+        triggers
+            .iter_mut()
+            .filter_map(|t| {
+                let (min, max) = objects.get(&t.object)?.bounds;
+                let inside = player.is_some_and(|p| p.cmpge(min).all() && p.cmple(max).all());
+                let crossed = inside != t.inside && inside == t.enter;
+                t.inside = inside;
+                crossed.then(|| t.callback.clone())
+            })
+            .collect()
+    };
+    for callback in fired {
+        if result.is_err() {
+            break;
+        }
+        result = engine
+            .call_fn::<Dynamic>(scope, ast, &callback, ())
+            .map(drop);
+    }
+
+    if result.is_ok() && has_fn(ast, "update", 1) {
+        let dt = time.delta_secs() as FLOAT;
+        result = engine
+            .call_fn::<Dynamic>(scope, ast, "update", (dt,))
+            .map(drop);
+    }
+    if let Err(e) = result {
+        eprintln!("Script error: {e}");
+        runtime.error = Some(e.to_string());
+        // Stop running a broken script until it's reloaded
+        runtime.ast = None;
+    }
+
+    let pending = std::mem::take(&mut lock(&runtime.world).commands);
+    for command in pending {
+        match command {
+            ScriptCommand::Spawn {
+                kind,
+                name,
+                position,
+            } => {
+                let material = cache.material(
+                    &mut materials,
+                    PlainMaterial {
+                        base_color: Color::srgb(0.8, 0.8, 0.8),
+                        metallic: 0.0,
+                        roughness: 0.8,
+                        emissive: LinearRgba::BLACK,
+                    },
+                );
+                let mut ecmd = commands.spawn((
+                    Mesh3d(cache.mesh(&mut meshes, kind)),
+                    MeshMaterial3d(material),
+                    Transform::from_translation(position),
+                    Editable,
+                    EditableMesh {
+                        kind,
+                        collider: Some(true),
+                    },
+                    Name::new(name),
+                ));
+                // Solid for the player straight away
+                if let Some(collider) = editable_collider(kind, None) {
+                    ecmd.insert((collider, ColliderScale::Absolute(Vec3::ONE)));
+                }
+                let e = ecmd.id();
+                dissolve_in(&mut commands, e, &dissolve);
+            }
+            ScriptCommand::Despawn(entity) => dissolve_away(&mut commands, entity, &dissolve),
+            ScriptCommand::SetTransform(entity, tf) => {
+                if let Ok((_, _, mut current, ..)) = q_objects.get_mut(entity) {
+                    current.set_if_neq(tf);
+                }
+            }
+            ScriptCommand::SetColor(entity, color) => {
+                edit_material(
+                    &mut commands,
+                    &mut materials,
+                    &cache,
+                    &q_objects,
+                    entity,
+                    |m| {
+                        m.base_color = color;
+                    },
+                );
+            }
+            ScriptCommand::SetEmissive(entity, emissive) => {
+                edit_material(
+                    &mut commands,
+                    &mut materials,
+                    &cache,
+                    &q_objects,
+                    entity,
+                    |m| {
+                        m.emissive = emissive;
+                    },
+                );
+            }
+            ScriptCommand::Tween(entity, tween) => {
+                if let Ok(mut ecmd) = commands.get_entity(entity) {
+                    ecmd.insert(tween);
+                }
+            }
+            ScriptCommand::TweenEffect(tween) => {
+                for camera in &q_camera {
+                    commands.entity(camera).insert(tween.clone());
+                }
+            }
+            ScriptCommand::BlockGroup(group, action) => {
+                ev_blocks.write(BlockGroupEvent { group, action });
+            }
+            ScriptCommand::Action(action) => {
+                ev_actions.write(action);
+            }
+        }
+    }
+}
+
+fn has_fn(ast: &AST, name: &str, params: usize) -> bool {
+    ast.iter_functions()
+        .any(|f| f.name == name && f.params.len() == params)
+}
+
+/// Change an object's material, giving it a private copy first if it's shared through the cache.
+fn edit_material(
+    commands: &mut Commands,
+    materials: &mut Assets<StandardMaterial>,
+    cache: &AssetCache,
+    q_objects: &ScriptObjects,
+    entity: Entity,
+    edit: impl FnOnce(&mut StandardMaterial),
+) {
+    let Ok((.., Some(mat))) = q_objects.get(entity) else {
+        return;
+    };
+    let Some(handle) = cache.own_material(commands, materials, entity, &mat.0) else {
+        return;
+    };
+    if let Some(m) = materials.get_mut(&handle) {
+        edit(m);
+    }
+}
+
+/// Pick the scene's script file, reload it and see the last error.
+fn script_window(
+    mut ctxs: EguiContexts,
+    edit_state: Res<SceneEditState>,
+    runtime: Res<ScriptRuntime>,
+    io: Option<ResMut<SceneIoState>>,
+    mut ev_reload: EventWriter<ReloadScriptEvent>,
+) {
+    let Some(mut io) = io else {
+        return;
+    };
+    if !edit_state.open {
+        return;
+    }
+    let Ok(ctx) = ctxs.ctx_mut() else {
+        return;
+    };
+    egui::Window::new("Script")
+        .default_open(false)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("File:");
+                let mut path = io.script.clone();
+                let te = egui::TextEdit::singleline(&mut path)
+                    .hint_text("scripts/scene.rhai")
+                    .desired_width(200.0);
+                // Only touch the resource on edits, it drives reloading and the unsaved marker
+                if ui.add(te).changed() {
+                    io.script = path;
+                    io.mark_edited();
+                }
+                if ui.button("Reload").clicked() {
+                    ev_reload.write(ReloadScriptEvent);
+                }
+            });
+            match (&runtime.error, &runtime.ast) {
+                (Some(e), _) => {
+                    ui.colored_label(ui.visuals().error_fg_color, e);
+                }
+                (None, Some(_)) => {
+                    ui.weak("Compiled; runs in Play mode");
+                }
+                (None, None) => {
+                    ui.weak("No script");
+                }
+            }
+        });
+}
@@ -0,0 +1,101 @@
+//! Back/forward through previously selected objects, like a browser's history: handy when
+//! going back and forth between a trigger and the door it opens. Alt+Left/Right or the
+//! toolbar's arrows.
+
+use bevy::prelude::*;
+
+use crate::editor_mode::EditorMode;
+use crate::inspector::{Editable, SelectObjectEvent, Selected};
+
+/// Entries kept each way; the oldest go first.
+const MAX_HISTORY: usize = 50;
+
+#[derive(Resource, Default)]
+pub struct SelectionHistory {
+    back: Vec<Entity>,
+    forward: Vec<Entity>,
+    current: Option<Entity>,
+}
+
+impl SelectionHistory {
+    pub fn can_go_back(&self) -> bool {
+        !self.back.is_empty()
+    }
+
+    pub fn can_go_forward(&self) -> bool {
+        !self.forward.is_empty()
+    }
+}
+
+#[derive(Event, Clone, Copy, PartialEq, Eq)]
+pub enum SelectionHistoryEvent {
+    Back,
+    Forward,
+}
+
+pub struct SelectionHistoryPlugin;
+impl Plugin for SelectionHistoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SelectionHistory>()
+            .init_resource::<EditorMode>()
+            .add_event::<SelectionHistoryEvent>()
+            .add_systems(Update, (record_selection, navigate_history).chain());
+    }
+}
+
+/// The object most recently added to the selection becomes the current entry.
+fn record_selection(
+    mut history: ResMut<SelectionHistory>,
+    q_added: Query<Entity, Added<Selected>>,
+) {
+    let Some(e) = q_added.iter().last() else {
+        return;
+    };
+    // Selecting what navigation just moved to
+    if history.current == Some(e) {
+        return;
+    }
+    if let Some(prev) = history.current.replace(e) {
+        push_capped(&mut history.back, prev);
+    }
+    history.forward.clear();
+}
+
+fn push_capped(stack: &mut Vec<Entity>, e: Entity) {
+    if stack.last() != Some(&e) {
+        stack.push(e);
+    }
+    if stack.len() > MAX_HISTORY {
+        stack.remove(0);
+    }
+}
+
+fn navigate_history(
+    mut events: EventReader<SelectionHistoryEvent>,
+    mut history: ResMut<SelectionHistory>,
+    mut ev_select: EventWriter<SelectObjectEvent>,
+    q_alive: Query<(), With<Editable>>,
+) {
+    let mut target = None;
+    for ev in events.read() {
+        let history = &mut *history;
+        let (from, to) = match ev {
+            SelectionHistoryEvent::Back => (&mut history.back, &mut history.forward),
+            SelectionHistoryEvent::Forward => (&mut history.forward, &mut history.back),
+        };
+        // Skip objects deleted since (or dropped by a scene load)
+        while let Some(e) = from.pop() {
+            if !q_alive.contains(e) {
+                continue;
+            }
+            if let Some(prev) = history.current.replace(e) {
+                push_capped(to, prev);
+            }
+            target = Some(e);
+            break;
+        }
+    }
+    if let Some(e) = target {
+        ev_select.write(SelectObjectEvent(e));
+    }
+}
@@ -0,0 +1,567 @@
+//! Session edit log: the scene's objects as a session starts (at startup and on every load),
+//! then every change to them once it has settled, appended to a JSON Lines file. Replaying a
+//! session rebuilds its scene from there: all at once, to recover work lost to a crash, or one
+//! change at a time, to see how a build came together (or record a time-lapse of it). The
+//! editor actions run along the way (saves, undo, mode switches, ...) are logged with the
+//! changes they came with.
+//!
+//! Objects are numbered within a session; a change lists the objects it added or edited, as
+//! they'd be saved, and the numbers of the ones it removed:
+//!
+//! ```json
+//! {"start":{"at":1760000000,"scene":"terraces.json","objects":[[0,{...}],[1,{...}]]}}
+//! {"change":{"t":12.5,"set":[[1,{...}]],"removed":[0],"actions":["undo"]}}
+//! ```
+
+use std::collections::{HashMap, VecDeque};
+use std::fs::OpenOptions;
+use std::io::Write;
+
+use bevy::prelude::*;
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+use serde::{Deserialize, Serialize};
+
+use crate::SceneEditState;
+use crate::actions::EditorAction;
+use crate::asset_cache::AssetCache;
+use crate::chunks::SceneChunks;
+use crate::editor_mode::EditorMode;
+use crate::inspector::{
+    EditHistory, Editable, LoadSceneEvent, SceneIoState, SceneObject, SceneObjects,
+    spawn_scene_object,
+};
+use crate::timeline::TimelinePlayback;
+use crate::toasts::Toasts;
+
+/// Seconds between looks for changes, at most.
+const CHECK_SECS: f32 = 1.0;
+
+/// Size the log grows to before a new session moves it aside (to `<path>.1`, replacing the one
+/// there) and starts a fresh one.
+const MAX_LOG_BYTES: u64 = 16 * 1024 * 1024;
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum LogEntry {
+    Start {
+        /// Unix time, in seconds
+        at: u64,
+        scene: String,
+        objects: Vec<(u32, SceneObject)>,
+    },
+    Change(Change),
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct Change {
+    /// Seconds since the session started
+    t: f32,
+    /// Objects added or edited, as they are now
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    set: Vec<(u32, SceneObject)>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    removed: Vec<u32>,
+    /// Names of the editor actions run since the last entry
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    actions: Vec<String>,
+}
+
+/// A session read back from the log.
+struct LoggedSession {
+    at: u64,
+    scene: String,
+    objects: Vec<(u32, SceneObject)>,
+    changes: Vec<Change>,
+}
+
+/// A replay in progress.
+struct Replay {
+    changes: VecDeque<Change>,
+    /// Entities of the replayed objects, by number
+    entities: HashMap<u32, Entity>,
+    total: usize,
+    /// `Time<Real>` seconds the next change is due at (a time-lapse only)
+    next_at: f32,
+    all_at_once: bool,
+}
+
+/// Where the session log goes, and what's been logged so far.
+#[derive(Resource)]
+pub struct SessionLog {
+    /// Log the scene's changes (off until asked for)
+    pub enabled: bool,
+    pub path: String,
+    /// Seconds between the changes of a time-lapse replay
+    pub interval: f32,
+    /// Number and saved JSON of every object in the session
+    known: HashMap<Entity, (u32, String)>,
+    next_id: u32,
+    /// `Time<Real>` seconds the current session started at, once its start is logged
+    started: Option<f32>,
+    /// Actions not logged yet
+    actions: Vec<String>,
+    /// [`SceneIoState::edits`] as of the last look
+    seen_edits: u64,
+    /// Sessions read back with [`SessionLog::read_sessions`]
+    sessions: Vec<LoggedSession>,
+    /// Session to replay next, and whether all at once
+    replay_next: Option<(usize, bool)>,
+    replay: Option<Replay>,
+}
+
+impl Default for SessionLog {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            path: "session-log.jsonl".into(),
+            interval: 0.25,
+            known: HashMap::new(),
+            next_id: 0,
+            started: None,
+            actions: Vec::new(),
+            seen_edits: 0,
+            sessions: Vec::new(),
+            replay_next: None,
+            replay: None,
+        }
+    }
+}
+
+impl SessionLog {
+    fn rotated_path(&self) -> String {
+        format!("{}.1", self.path)
+    }
+
+    fn append(&self, entry: &LogEntry) -> Result<(), String> {
+        let line = serde_json::to_string(entry).map_err(|e| e.to_string())?;
+        // Sessions start a new file once the log is big, so it never has to be read in two
+        if matches!(entry, LogEntry::Start { .. })
+            && std::fs::metadata(&self.path).is_ok_and(|m| m.len() > MAX_LOG_BYTES)
+        {
+            std::fs::rename(&self.path, self.rotated_path()).map_err(|e| e.to_string())?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| e.to_string())?;
+        writeln!(file, "{line}").map_err(|e| e.to_string())
+    }
+
+    /// Read the logged sessions back (the log moved aside too), returning how many there are.
+    /// Lines that don't parse (one cut short by a crash) are skipped.
+    pub fn read_sessions(&mut self) -> Result<usize, String> {
+        let current = std::fs::read_to_string(&self.path).map_err(|e| e.to_string())?;
+        let text = std::fs::read_to_string(self.rotated_path()).unwrap_or_default() + &current;
+        let mut sessions: Vec<LoggedSession> = Vec::new();
+        for entry in text
+            .lines()
+            .filter_map(|line| serde_json::from_str::<LogEntry>(line).ok())
+        {
+            match entry {
+                LogEntry::Start { at, scene, objects } => sessions.push(LoggedSession {
+                    at,
+                    scene,
+                    objects,
+                    changes: Vec::new(),
+                }),
+                LogEntry::Change(change) => {
+                    if let Some(session) = sessions.last_mut() {
+                        session.changes.push(change);
+                    }
+                }
+            }
+        }
+        self.sessions = sessions;
+        Ok(self.sessions.len())
+    }
+
+    /// Replace the scene with the `index`th session read back, and replay its changes: all at
+    /// once, or one every `interval` seconds.
+    pub fn replay(&mut self, index: usize, all_at_once: bool) {
+        self.replay_next = Some((index, all_at_once));
+    }
+
+    pub fn is_replaying(&self) -> bool {
+        self.replay.is_some()
+    }
+
+    /// Stop replaying where it is; the scene as it stands starts a new session.
+    pub fn stop_replay(&mut self) {
+        self.replay = None;
+        self.started = None;
+    }
+}
+
+pub struct SessionLogPlugin;
+impl Plugin for SessionLogPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SessionLog>()
+            .init_resource::<SceneEditState>()
+            .init_resource::<EditorMode>()
+            .add_event::<LoadSceneEvent>()
+            .add_event::<EditorAction>()
+            .add_systems(Update, replay_session)
+            // After Update's commands are applied, so a load's objects are there to start with
+            .add_systems(PostUpdate, log_changes)
+            .add_systems(EguiPrimaryContextPass, session_log_window);
+    }
+}
+
+fn unix_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// Log the session's start, then what changed since the last look, looking once the scene has
+/// been edited (or an action run). Nothing is looked at mid-drag, in Play mode or while a
+/// timeline plays.
+fn log_changes(
+    time: Res<Time<Real>>,
+    mut log: ResMut<SessionLog>,
+    io: Option<Res<SceneIoState>>,
+    mode: Res<EditorMode>,
+    playback: Option<Res<TimelinePlayback>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    scene: SceneObjects,
+    mut ev_load: EventReader<LoadSceneEvent>,
+    mut ev_actions: EventReader<EditorAction>,
+    mut toasts: Option<ResMut<Toasts>>,
+    mut next_check: Local<f32>,
+) {
+    let loaded = ev_load.read().count() > 0;
+    let actions: Vec<String> = ev_actions.read().map(|a| a.name()).collect();
+    if !log.enabled || log.is_replaying() {
+        if log.started.is_some() {
+            log.started = None;
+        }
+        return;
+    }
+    // A loaded scene starts a new session
+    if loaded {
+        log.started = None;
+    }
+    log.actions.extend(actions);
+
+    let now = time.elapsed_secs();
+    let edits = io.as_ref().map_or(0, |io| io.edits());
+    if now < *next_check
+        || (log.started.is_some() && edits == log.seen_edits && log.actions.is_empty())
+        || *mode == EditorMode::Play
+        || playback.is_some_and(|p| p.playing)
+        || mouse.any_pressed([MouseButton::Left, MouseButton::Right])
+    {
+        return;
+    }
+    *next_check = now + CHECK_SECS;
+    log.seen_edits = edits;
+
+    let objects = scene.collect(|_| true);
+    let log = &mut *log;
+    let entry = match log.started {
+        None => {
+            log.started = Some(now);
+            log.actions.clear();
+            log.next_id = 0;
+            log.known.clear();
+            let mut numbered = Vec::with_capacity(objects.len());
+            for (e, obj) in objects {
+                let json = serde_json::to_string(&obj).unwrap_or_default();
+                log.known.insert(e, (log.next_id, json));
+                numbered.push((log.next_id, obj));
+                log.next_id += 1;
+            }
+            LogEntry::Start {
+                at: unix_secs(),
+                scene: io.map(|io| io.filename.clone()).unwrap_or_default(),
+                objects: numbered,
+            }
+        }
+        Some(started) => {
+            let mut known = HashMap::with_capacity(objects.len());
+            let mut set = Vec::new();
+            let mut added = Vec::new();
+            for (e, obj) in objects {
+                let json = serde_json::to_string(&obj).unwrap_or_default();
+                match log.known.remove(&e) {
+                    Some((id, before)) => {
+                        if before != json {
+                            set.push((id, obj));
+                        }
+                        known.insert(e, (id, json));
+                    }
+                    None => added.push((e, obj, json)),
+                }
+            }
+            // An object back as another entity just as it was (put back after Play, streamed
+            // back in) keeps its number
+            let mut vanished: HashMap<String, Vec<u32>> = HashMap::new();
+            for (_, (id, json)) in log.known.drain() {
+                vanished.entry(json).or_default().push(id);
+            }
+            for (e, obj, json) in added {
+                let id = match vanished.get_mut(&json).and_then(Vec::pop) {
+                    Some(id) => id,
+                    None => {
+                        let id = log.next_id;
+                        log.next_id += 1;
+                        set.push((id, obj));
+                        id
+                    }
+                };
+                known.insert(e, (id, json));
+            }
+            log.known = known;
+            let mut removed: Vec<u32> = vanished.into_values().flatten().collect();
+            if set.is_empty() && removed.is_empty() && log.actions.is_empty() {
+                return;
+            }
+            set.sort_by_key(|(id, _)| *id);
+            removed.sort_unstable();
+            LogEntry::Change(Change {
+                t: now - started,
+                set,
+                removed,
+                actions: std::mem::take(&mut log.actions),
+            })
+        }
+    };
+
+    if let Err(e) = log.append(&entry) {
+        log.enabled = false;
+        log.started = None;
+        if let Some(toasts) = toasts.as_mut() {
+            toasts.warn(format!("Couldn't write the session log {}: {e}", log.path));
+        }
+    }
+}
+
+/// Start a replay when one is asked for, then apply its changes as they come due.
+fn replay_session(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    mut log: ResMut<SessionLog>,
+    mut io: Option<ResMut<SceneIoState>>,
+    mut history: Option<ResMut<EditHistory>>,
+    mut chunks: Option<ResMut<SceneChunks>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut cache: ResMut<AssetCache>,
+    mut toasts: Option<ResMut<Toasts>>,
+    q_existing: Query<Entity, With<Editable>>,
+) {
+    let now = time.elapsed_secs();
+    let log = &mut *log;
+    if let Some((index, all_at_once)) = log.replay_next.take()
+        && let Some(session) = log.sessions.get(index)
+    {
+        for e in &q_existing {
+            commands.entity(e).despawn();
+        }
+        // Undo steps and streamed cells belong to the scene being replaced
+        if let Some(history) = history.as_mut() {
+            history.clear(&mut commands);
+        }
+        if let Some(chunks) = chunks.as_mut() {
+            chunks.reset();
+        }
+        let entities = session
+            .objects
+            .iter()
+            .map(|(id, obj)| {
+                let e = spawn_scene_object(
+                    &mut commands,
+                    &mut meshes,
+                    &mut materials,
+                    &mut cache,
+                    obj.clone(),
+                );
+                (*id, e)
+            })
+            .collect();
+        log.replay = Some(Replay {
+            changes: session.changes.iter().cloned().collect(),
+            entities,
+            total: session.changes.len(),
+            next_at: now + log.interval,
+            all_at_once,
+        });
+        if let Some(io) = io.as_mut() {
+            io.skip_change_tracking();
+            // Whatever the replay ends up with isn't saved anywhere yet
+            io.dirty = true;
+        }
+    }
+
+    let interval = log.interval;
+    let Some(replay) = log.replay.as_mut() else {
+        return;
+    };
+    while replay.all_at_once || now >= replay.next_at {
+        let Some(change) = replay.changes.pop_front() else {
+            break;
+        };
+        for id in change.removed {
+            if let Some(e) = replay.entities.remove(&id) {
+                commands.entity(e).despawn();
+            }
+        }
+        // Edited objects are spawned again as they were logged
+        for (id, obj) in change.set {
+            if let Some(e) = replay.entities.remove(&id) {
+                commands.entity(e).despawn();
+            }
+            let e = spawn_scene_object(&mut commands, &mut meshes, &mut materials, &mut cache, obj);
+            replay.entities.insert(id, e);
+        }
+        replay.next_at = now + interval;
+        if let Some(io) = io.as_mut() {
+            io.skip_change_tracking();
+        }
+        if !replay.all_at_once {
+            break;
+        }
+    }
+
+    if replay.changes.is_empty() {
+        let total = replay.total;
+        log.stop_replay();
+        if let Some(toasts) = toasts.as_mut() {
+            toasts.info(format!("Replayed {total} changes"));
+        }
+    }
+}
+
+/// How long ago a Unix time was, roughly.
+fn ago(at: u64) -> String {
+    let secs = unix_secs().saturating_sub(at);
+    match secs {
+        0..60 => "just now".into(),
+        60..3600 => format!("{} min ago", secs / 60),
+        3600..86400 => format!("{} h ago", secs / 3600),
+        _ => format!("{} days ago", secs / 86400),
+    }
+}
+
+fn session_log_window(
+    mut ctxs: EguiContexts,
+    edit_state: Res<SceneEditState>,
+    mode: Res<EditorMode>,
+    mut log: ResMut<SessionLog>,
+    mut toasts: Option<ResMut<Toasts>>,
+) {
+    if !edit_state.open {
+        return;
+    }
+    let Ok(ctx) = ctxs.ctx_mut() else {
+        return;
+    };
+
+    let mut enabled = log.enabled;
+    let mut interval = log.interval;
+    let (mut refresh, mut replay, mut stop) = (false, None, false);
+    egui::Window::new("Session Log")
+        .default_open(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.checkbox(&mut enabled, "Log edits")
+                .on_hover_text("Append every change to the scene to the session log");
+            ui.weak(&log.path);
+            ui.separator();
+
+            if let Some(replay) = &log.replay {
+                let done = replay.total - replay.changes.len();
+                ui.horizontal(|ui| {
+                    ui.label(format!("Replaying {done} / {}", replay.total));
+                    if ui.button("Stop").clicked() {
+                        stop = true;
+                    }
+                });
+                ui.add(egui::ProgressBar::new(
+                    done as f32 / replay.total.max(1) as f32,
+                ));
+                return;
+            }
+
+            if ui
+                .button("Read sessions")
+                .on_hover_text("Read the logged sessions back")
+                .clicked()
+            {
+                refresh = true;
+            }
+            ui.add(
+                egui::Slider::new(&mut interval, 0.02..=2.0)
+                    .logarithmic(true)
+                    .text("Seconds per change"),
+            )
+            .on_hover_text("How fast a time-lapse replays");
+            // Play mode puts its own scene back when it ends
+            ui.add_enabled_ui(*mode != EditorMode::Play, |ui| {
+                egui::ScrollArea::vertical()
+                    .max_height(240.0)
+                    .show(ui, |ui| {
+                        for (i, session) in log.sessions.iter().enumerate().rev() {
+                            ui.horizontal(|ui| {
+                                let scene = match session.scene.trim() {
+                                    "" => "untitled",
+                                    scene => scene,
+                                };
+                                ui.label(format!(
+                                    "{scene}, {}: {} changes",
+                                    ago(session.at),
+                                    session.changes.len()
+                                ));
+                                if ui
+                                    .button("Recover")
+                                    .on_hover_text(
+                                        "Replace the scene with this session's, every change applied",
+                                    )
+                                    .clicked()
+                                {
+                                    replay = Some((i, true));
+                                }
+                                if ui
+                                    .button("Time-lapse")
+                                    .on_hover_text(
+                                        "Replace the scene with this session's start and replay its changes one by one",
+                                    )
+                                    .clicked()
+                                {
+                                    replay = Some((i, false));
+                                }
+                            });
+                        }
+                    });
+            });
+        });
+
+    if enabled != log.enabled {
+        log.enabled = enabled;
+    }
+    if interval != log.interval {
+        log.interval = interval;
+    }
+    if stop {
+        log.stop_replay();
+    }
+    if let Some((i, all_at_once)) = replay {
+        log.replay(i, all_at_once);
+    }
+    if refresh {
+        match log.read_sessions() {
+            Ok(0) => {
+                if let Some(toasts) = toasts.as_mut() {
+                    toasts.info("No sessions logged yet");
+                }
+            }
+            Ok(_) => {}
+            Err(e) => {
+                if let Some(toasts) = toasts.as_mut() {
+                    toasts.warn(format!("Couldn't read {}: {e}", log.path));
+                }
+            }
+        }
+    }
+}
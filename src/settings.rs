@@ -0,0 +1,511 @@
+//! `app_settings.toml`: window size and position, last scene, panel states and egui layout,
+//! key bindings, editor preferences, UI theme, accessibility options, the effect governor,
+//! exposure, frame rate limits and panel docking. Loaded at startup, written on exit.
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::window::{PrimaryWindow, WindowPosition};
+use bevy_egui::{EguiContext, PrimaryEguiContext, egui};
+use serde::{Deserialize, Serialize};
+
+use crate::SceneEditState;
+use crate::accessibility::AccessibilityPrefs;
+use crate::build_mode::BuildMode;
+use crate::camera::CoopCamera;
+use crate::cutaway::CutawayStyle;
+use crate::diagnostics::DiagnosticsOverlay;
+use crate::dock::DockLayout;
+use crate::editor_gizmos::GizmoDepth;
+use crate::editor_mode::TransformSpace;
+#[cfg(feature = "inspector")]
+use crate::inspector::{LoadSceneEvent, SceneIoState};
+use crate::post::exposure::ExposureControl;
+use crate::post::governor::EffectGovernor;
+use crate::power::PowerPrefs;
+use crate::scene_io;
+use crate::ui_theme::UiPrefs;
+
+pub const SETTINGS_FILE: &str = "app_settings.toml";
+
+/// Everything remembered between runs, as written to `app_settings.toml`.
+/// Missing keys fall back to their defaults, so old files keep loading.
+#[derive(Resource, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppSettings {
+    pub window: WindowSettings,
+    /// Scene file name from the inspector's Save/Load row
+    pub last_scene: String,
+    pub panels: PanelSettings,
+    pub keys: KeyBindings,
+    pub editor: EditorPrefs,
+    pub player: PlayerTuning,
+    pub ui: UiPrefs,
+    pub accessibility: AccessibilityPrefs,
+    pub effect_governor: EffectGovernor,
+    pub exposure: ExposureControl,
+    pub power: PowerPrefs,
+    pub dock: DockLayout,
+    /// egui memory (window positions and sizes, collapsed sections) in RON
+    pub ui_layout: String,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WindowSettings {
+    pub width: f32,
+    pub height: f32,
+    /// Left unset until the window has been moved, so the OS picks the first placement
+    pub position: Option<[i32; 2]>,
+}
+
+impl Default for WindowSettings {
+    fn default() -> Self {
+        Self {
+            width: 1280.0,
+            height: 720.0,
+            position: None,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PanelSettings {
+    pub editor_open: bool,
+    pub build_mode: bool,
+    pub diagnostics_overlay: bool,
+}
+
+impl Default for PanelSettings {
+    fn default() -> Self {
+        Self {
+            editor_open: true,
+            build_mode: false,
+            diagnostics_overlay: false,
+        }
+    }
+}
+
+/// Keys for the editor's toggles and actions. Plugins that read one `init_resource` it, so they
+/// work without `SettingsPlugin` too.
+#[derive(Resource, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KeyBindings {
+    pub toggle_editor: KeyCode,
+    pub build_mode: KeyCode,
+    pub diagnostics: KeyCode,
+    pub drop_player: KeyCode,
+    pub orthographic: KeyCode,
+    pub perspective: KeyCode,
+    pub rotate_left: KeyCode,
+    pub rotate_right: KeyCode,
+    /// Start/stop a GIF or video recording
+    pub record: KeyCode,
+    /// Show the key and how-to overlay
+    pub help: KeyCode,
+    /// See through whatever hides the selection
+    pub cutaway: KeyCode,
+    /// Press a call button (or whatever else the player can use) in Play mode
+    pub interact: KeyCode,
+    /// Select the next or previous object in Hierarchy order
+    pub select_next: KeyCode,
+    pub select_previous: KeyCode,
+    /// Flip between post snapshots A and B (Effect settings → A/B Compare, Toggle)
+    pub ab_compare: KeyCode,
+    /// Turn the object being placed a quarter
+    pub turn_placement: KeyCode,
+    /// With Alt: back to earlier selections, or forward again
+    pub selection_back: KeyCode,
+    pub selection_forward: KeyCode,
+    /// With Ctrl+Shift: another copy one step further than the last
+    pub repeat_copy: KeyCode,
+    /// With Ctrl: undo (with Shift too, redo) and redo
+    pub undo: KeyCode,
+    pub redo: KeyCode,
+    /// With Ctrl: copy the selected object beside it
+    pub duplicate: KeyCode,
+    /// With Ctrl: the selection to and from the system clipboard
+    pub copy: KeyCode,
+    pub paste: KeyCode,
+    pub quit: KeyCode,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            toggle_editor: KeyCode::Space,
+            build_mode: KeyCode::KeyB,
+            diagnostics: KeyCode::F3,
+            drop_player: KeyCode::Enter,
+            orthographic: KeyCode::KeyO,
+            perspective: KeyCode::KeyP,
+            rotate_left: KeyCode::KeyQ,
+            rotate_right: KeyCode::KeyE,
+            record: KeyCode::F9,
+            help: KeyCode::F1,
+            cutaway: KeyCode::KeyX,
+            interact: KeyCode::KeyF,
+            select_next: KeyCode::BracketRight,
+            select_previous: KeyCode::BracketLeft,
+            ab_compare: KeyCode::F6,
+            turn_placement: KeyCode::KeyR,
+            selection_back: KeyCode::ArrowLeft,
+            selection_forward: KeyCode::ArrowRight,
+            repeat_copy: KeyCode::KeyD,
+            undo: KeyCode::KeyZ,
+            redo: KeyCode::KeyY,
+            duplicate: KeyCode::KeyD,
+            copy: KeyCode::KeyC,
+            paste: KeyCode::KeyV,
+            quit: KeyCode::Escape,
+        }
+    }
+}
+
+impl KeyBindings {
+    /// Every binding with what it does, for help screens.
+    pub fn actions(&self) -> [(&'static str, KeyCode); 25] {
+        [
+            ("Show/hide the editor", self.toggle_editor),
+            ("Toggle build mode", self.build_mode),
+            ("Frame time and render passes", self.diagnostics),
+            ("Play mode (drop the player)", self.drop_player),
+            ("Orthographic camera", self.orthographic),
+            ("Perspective camera", self.perspective),
+            ("Camera to previous corner", self.rotate_left),
+            ("Camera to next corner", self.rotate_right),
+            ("Start/stop recording", self.record),
+            ("This help", self.help),
+            ("Cutaway (X-ray) view", self.cutaway),
+            ("Interact: call buttons, signs (Play mode)", self.interact),
+            ("Select the next object", self.select_next),
+            ("Select the previous object", self.select_previous),
+            ("Flip A/B post snapshots (Toggle compare)", self.ab_compare),
+            ("Turn the object being placed", self.turn_placement),
+            (
+                "Back to the previous selection (with Alt)",
+                self.selection_back,
+            ),
+            (
+                "Forward to the next selection (with Alt)",
+                self.selection_forward,
+            ),
+            ("Repeat the last copy (with Ctrl+Shift)", self.repeat_copy),
+            ("Undo (with Ctrl; with Ctrl+Shift, redo)", self.undo),
+            ("Redo (with Ctrl)", self.redo),
+            ("Duplicate the selected object (with Ctrl)", self.duplicate),
+            ("Copy the selection to the clipboard (with Ctrl)", self.copy),
+            ("Paste objects from the clipboard (with Ctrl)", self.paste),
+            ("Quit", self.quit),
+        ]
+    }
+}
+
+/// How the Play mode player climbs, from the **Player tuning** window.
+#[derive(Resource, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlayerTuning {
+    /// Steps up to this height are climbed just by walking into them
+    pub step_height: f32,
+    /// Narrower footholds than this aren't stepped onto
+    pub step_min_width: f32,
+    /// Walking into a wall whose top is within reach pulls the player up onto it
+    pub ledge_grab: bool,
+    /// Highest ledge that can be caught, above the feet
+    pub ledge_reach: f32,
+    pub pull_up_secs: f32,
+}
+
+impl Default for PlayerTuning {
+    fn default() -> Self {
+        Self {
+            // Clears the 0.6 terraces
+            step_height: 0.65,
+            step_min_width: 0.2,
+            ledge_grab: true,
+            ledge_reach: 1.3,
+            pull_up_secs: 0.45,
+        }
+    }
+}
+
+/// Short name of a key as printed on the keyboard (`KeyB` is "B", `Digit1` is "1").
+pub fn key_label(key: KeyCode) -> String {
+    match key {
+        KeyCode::BracketLeft => return "[".into(),
+        KeyCode::BracketRight => return "]".into(),
+        KeyCode::ArrowLeft => return "←".into(),
+        KeyCode::ArrowRight => return "→".into(),
+        _ => {}
+    }
+    let name = format!("{key:?}");
+    match name
+        .strip_prefix("Key")
+        .or_else(|| name.strip_prefix("Digit"))
+    {
+        Some(short) => short.to_string(),
+        None => name,
+    }
+}
+
+#[derive(Resource, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EditorPrefs {
+    /// Round the inspector's transform fields to the sizes below
+    pub snap: bool,
+    pub move_snap: f32,
+    pub rotate_snap_deg: f32,
+    pub scale_snap: f32,
+    /// Ground grid at Y=0; its cells are `move_snap` wide
+    pub show_grid: bool,
+    /// Cells between the grid's major lines
+    pub grid_major_every: u32,
+    /// Axes widget in the viewport corner
+    pub show_axes: bool,
+    /// Load `last_scene` at startup instead of the built-in one
+    pub reopen_last_scene: bool,
+    /// See-through or outline-only drawing of whatever is between the camera and the selection
+    pub cutaway: bool,
+    pub cutaway_style: CutawayStyle,
+    /// Opacity of see-through objects, 0..1
+    pub cutaway_opacity: f32,
+    /// Moving the selection also drags nearby objects, less the farther away they are
+    pub proportional: bool,
+    /// How far proportional moves reach
+    pub proportional_radius: f32,
+    /// Axes of the inspector's transform fields
+    pub transform_space: TransformSpace,
+    /// Whether the selection box and other editor gizmos show through objects in front
+    pub gizmo_depth: GizmoDepth,
+    /// Selection box turned with the object instead of its axis-aligned world bounds
+    pub oriented_selection_box: bool,
+    /// Editor gizmo line width, in pixels
+    pub gizmo_line_width: f32,
+    /// Smooth jagged edges (of gizmo lines and everything else) with FXAA
+    pub gizmo_smoothing: bool,
+    /// Play mode also drops a second player, driven by the first gamepad
+    pub second_player: bool,
+    /// How the view covers both players
+    pub coop_camera: CoopCamera,
+}
+
+impl Default for EditorPrefs {
+    fn default() -> Self {
+        Self {
+            snap: false,
+            move_snap: 0.25,
+            rotate_snap_deg: 15.0,
+            scale_snap: 0.1,
+            show_grid: true,
+            grid_major_every: 4,
+            show_axes: true,
+            reopen_last_scene: true,
+            cutaway: false,
+            cutaway_style: CutawayStyle::Transparent,
+            cutaway_opacity: 0.25,
+            proportional: false,
+            proportional_radius: 3.0,
+            transform_space: TransformSpace::Local,
+            gizmo_depth: GizmoDepth::OnTop,
+            oriented_selection_box: true,
+            gizmo_line_width: 3.0,
+            gizmo_smoothing: true,
+            second_player: false,
+            coop_camera: CoopCamera::Shared,
+        }
+    }
+}
+
+/// Where settings are read from and written back to; `None` keeps them in memory only
+/// (the benchmark runs that way, so it neither uses nor clobbers the user's file).
+#[derive(Resource, Clone)]
+pub struct SettingsFile(pub Option<String>);
+
+pub struct SettingsPlugin;
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        let settings = load_settings(SETTINGS_FILE);
+        app.insert_resource(settings.keys.clone())
+            .insert_resource(settings.editor.clone())
+            .insert_resource(settings.player.clone())
+            .insert_resource(settings.ui.clone())
+            .insert_resource(settings.accessibility.clone())
+            .insert_resource(settings.effect_governor.clone())
+            .insert_resource(settings.exposure.clone())
+            .insert_resource(settings.power.clone())
+            .insert_resource(settings.dock.clone())
+            .insert_resource(settings)
+            .insert_resource(SettingsFile(Some(SETTINGS_FILE.into())))
+            .add_systems(Startup, apply_settings)
+            .add_systems(PreUpdate, restore_ui_layout)
+            .add_systems(Update, track_window)
+            .add_systems(Last, save_settings_on_exit);
+
+        #[cfg(feature = "inspector")]
+        app.add_systems(PostStartup, reopen_last_scene);
+    }
+}
+
+fn load_settings(path: &str) -> AppSettings {
+    let Some(text) = scene_io::read_text(path) else {
+        return AppSettings::default();
+    };
+    toml::from_str(&text).unwrap_or_else(|e| {
+        eprintln!("Settings: ignoring invalid {path}: {e}");
+        AppSettings::default()
+    })
+}
+
+fn apply_settings(
+    settings: Res<AppSettings>,
+    mut q_window: Query<&mut Window, With<PrimaryWindow>>,
+    mut edit_state: ResMut<SceneEditState>,
+    build: Option<ResMut<BuildMode>>,
+    overlay: Option<ResMut<DiagnosticsOverlay>>,
+) {
+    if let Ok(mut window) = q_window.single_mut() {
+        let w = &settings.window;
+        window
+            .resolution
+            .set(w.width.max(320.0), w.height.max(240.0));
+        if let Some([x, y]) = w.position {
+            window.position = WindowPosition::At(IVec2::new(x, y));
+        }
+    }
+
+    edit_state.open = settings.panels.editor_open;
+    if let Some(mut build) = build {
+        build.enabled = settings.panels.build_mode;
+    }
+    if let Some(mut overlay) = overlay {
+        overlay.enabled = settings.panels.diagnostics_overlay;
+    }
+}
+
+#[cfg(feature = "inspector")]
+fn reopen_last_scene(
+    settings: Res<AppSettings>,
+    prefs: Res<EditorPrefs>,
+    mut io: ResMut<SceneIoState>,
+    mut ev_load: EventWriter<LoadSceneEvent>,
+) {
+    let scene = settings.last_scene.trim();
+    if scene.is_empty() {
+        return;
+    }
+    io.filename = scene.into();
+    if prefs.reopen_last_scene && scene_io::read_scene(scene).is_some() {
+        ev_load.write(LoadSceneEvent);
+    }
+}
+
+/// Put the saved egui memory back once the primary context exists.
+fn restore_ui_layout(
+    settings: Res<AppSettings>,
+    mut q_ctx: Query<&mut EguiContext, With<PrimaryEguiContext>>,
+    mut done: Local<bool>,
+) {
+    if *done {
+        return;
+    }
+    let Ok(mut ctx) = q_ctx.single_mut() else {
+        return;
+    };
+    *done = true;
+    if settings.ui_layout.is_empty() {
+        return;
+    }
+    match ron::from_str::<egui::Memory>(&settings.ui_layout) {
+        Ok(memory) => ctx.get_mut().memory_mut(|m| *m = memory),
+        Err(e) => eprintln!("Settings: ignoring saved UI layout: {e}"),
+    }
+}
+
+/// The window is already gone by the time `AppExit` arrives when it's closed with the title bar
+/// button, so its state is copied whenever it changes.
+fn track_window(
+    q_window: Query<&Window, (With<PrimaryWindow>, Changed<Window>)>,
+    mut settings: ResMut<AppSettings>,
+) {
+    let Ok(window) = q_window.single() else {
+        return;
+    };
+    settings.window.width = window.resolution.width();
+    settings.window.height = window.resolution.height();
+    if let WindowPosition::At(pos) = window.position {
+        settings.window.position = Some([pos.x, pos.y]);
+    }
+}
+
+/// The resources saved to the settings file as they are, as one system parameter.
+#[derive(SystemParam)]
+struct SavedPrefs<'w> {
+    keys: Res<'w, KeyBindings>,
+    editor: Res<'w, EditorPrefs>,
+    player: Res<'w, PlayerTuning>,
+    ui: Res<'w, UiPrefs>,
+    accessibility: Res<'w, AccessibilityPrefs>,
+    effect_governor: Res<'w, EffectGovernor>,
+    exposure: Res<'w, ExposureControl>,
+    power: Res<'w, PowerPrefs>,
+    dock: Res<'w, DockLayout>,
+}
+
+impl SavedPrefs<'_> {
+    fn store(&self, settings: &mut AppSettings) {
+        settings.keys = self.keys.clone();
+        settings.editor = self.editor.clone();
+        settings.player = self.player.clone();
+        settings.ui = self.ui.clone();
+        settings.accessibility = self.accessibility.clone();
+        settings.effect_governor = self.effect_governor.clone();
+        settings.exposure = self.exposure.clone();
+        settings.power = self.power.clone();
+        settings.dock = self.dock.clone();
+    }
+}
+
+fn save_settings_on_exit(
+    mut ev_exit: EventReader<AppExit>,
+    file: Res<SettingsFile>,
+    mut settings: ResMut<AppSettings>,
+    prefs: SavedPrefs,
+    edit_state: Res<SceneEditState>,
+    build: Option<Res<BuildMode>>,
+    overlay: Option<Res<DiagnosticsOverlay>>,
+    #[cfg(feature = "inspector")] io: Option<Res<SceneIoState>>,
+    mut q_ctx: Query<&mut EguiContext, With<PrimaryEguiContext>>,
+) {
+    if ev_exit.read().last().is_none() {
+        return;
+    }
+    let Some(path) = &file.0 else {
+        return;
+    };
+
+    prefs.store(&mut settings);
+    settings.panels = PanelSettings {
+        editor_open: edit_state.open,
+        build_mode: build.is_some_and(|b| b.enabled),
+        diagnostics_overlay: overlay.is_some_and(|o| o.enabled),
+    };
+    #[cfg(feature = "inspector")]
+    if let Some(io) = io {
+        settings.last_scene = io.filename.clone();
+    }
+    if let Ok(mut ctx) = q_ctx.single_mut() {
+        match ctx.get_mut().memory(ron::to_string) {
+            Ok(layout) => settings.ui_layout = layout,
+            Err(e) => eprintln!("Settings: cannot save UI layout: {e}"),
+        }
+    }
+
+    match toml::to_string_pretty(&*settings) {
+        Ok(text) => {
+            if let Err(e) = scene_io::write_text(path, &text) {
+                eprintln!("Settings: cannot write {path}: {e}");
+            }
+        }
+        Err(e) => eprintln!("Settings: serialize error: {e}"),
+    }
+}
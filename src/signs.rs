@@ -0,0 +1,250 @@
+//! Signs: any object can carry pages of text for tutorials and storytelling. In Play mode a
+//! player standing next to one sees a prompt; the interact key (or a gamepad's south button)
+//! opens a speech box above the sign and turns its pages, closing after the last one. Walking
+//! away closes it too. The text is saved with the scene.
+
+use bevy::prelude::*;
+use bevy::render::primitives::Aabb;
+use bevy::window::PrimaryWindow;
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+use bevy_rapier3d::prelude::KinematicCharacterController;
+use serde::{Deserialize, Serialize};
+
+use crate::accessibility::Described;
+use crate::camera::OrbitCamera;
+use crate::editor_mode::EditorMode;
+use crate::inspector::{Editable, aabb_world, obb_to_world_aabb};
+use crate::settings::{KeyBindings, key_label};
+
+/// How far from a sign's bounds a player can read it
+const READ_REACH: f32 = 1.2;
+/// Widest a page gets on screen before wrapping, in points
+const BOX_WIDTH: f32 = 320.0;
+const BOX_FILL: egui::Color32 = egui::Color32::from_rgba_premultiplied(18, 20, 28, 235);
+const BOX_ACCENT: egui::Color32 = egui::Color32::from_rgb(235, 200, 120);
+
+/// Persisted on the object that reads as a sign.
+#[derive(Component, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Sign {
+    /// Shown one at a time, in order; blank pages are skipped
+    pub pages: Vec<String>,
+}
+
+impl Default for Sign {
+    fn default() -> Self {
+        Self {
+            pages: vec!["Welcome!".to_string()],
+        }
+    }
+}
+
+impl Sign {
+    fn page(&self, i: usize) -> Option<&str> {
+        self.pages
+            .iter()
+            .map(|p| p.trim())
+            .filter(|p| !p.is_empty())
+            .nth(i)
+    }
+
+    fn page_count(&self) -> usize {
+        self.pages.iter().filter(|p| !p.trim().is_empty()).count()
+    }
+}
+
+/// The sign in reach and the one being read, while playing.
+#[derive(Resource, Default)]
+struct SignReader {
+    near: Option<Entity>,
+    /// Sign and page on show
+    open: Option<(Entity, usize)>,
+}
+
+pub struct SignsPlugin;
+impl Plugin for SignsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EditorMode>()
+            .init_resource::<KeyBindings>()
+            .init_resource::<SignReader>()
+            .add_systems(Update, read_signs)
+            .add_systems(EguiPrimaryContextPass, sign_box);
+    }
+}
+
+/// World bounds of a sign; collider boxes have no mesh, so a unit cube scaled by the transform.
+fn sign_bounds(gt: &GlobalTransform, aabb: Option<&Aabb>) -> (Vec3, Vec3) {
+    let bounds = match aabb {
+        Some(aabb) => aabb_world(*aabb, gt),
+        None => obb_to_world_aabb(gt.compute_transform(), Vec3::ONE),
+    };
+    (
+        Vec3::from(bounds.center - bounds.half_extents),
+        Vec3::from(bounds.center + bounds.half_extents),
+    )
+}
+
+/// Find the sign in reach and open it or turn its page on interact.
+fn read_signs(
+    mode: Res<EditorMode>,
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    q_pads: Query<&Gamepad>,
+    mut reader: ResMut<SignReader>,
+    q_players: Query<&GlobalTransform, With<KinematicCharacterController>>,
+    q_signs: Query<(Entity, &Sign, &GlobalTransform, Option<&Aabb>), With<Editable>>,
+) {
+    if *mode != EditorMode::Play {
+        *reader = SignReader::default();
+        return;
+    }
+    reader.near = q_signs
+        .iter()
+        .filter(|(_, sign, ..)| sign.page_count() > 0)
+        .filter_map(|(e, _, gt, aabb)| {
+            let (min, max) = sign_bounds(gt, aabb);
+            let d = q_players
+                .iter()
+                .map(|p| p.translation().clamp(min, max).distance(p.translation()))
+                .min_by(f32::total_cmp)?;
+            (d <= READ_REACH).then_some((e, d))
+        })
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(e, _)| e);
+    // Walking off (or the sign going away) closes it
+    if reader.open.is_some_and(|(e, _)| Some(e) != reader.near) {
+        reader.open = None;
+    }
+
+    let pressed = keys.just_pressed(bindings.interact)
+        || q_pads
+            .iter()
+            .any(|pad| pad.just_pressed(GamepadButton::South));
+    if !pressed {
+        return;
+    }
+    reader.open = match (reader.open, reader.near) {
+        (Some((e, page)), _) => {
+            let pages = q_signs.get(e).map_or(0, |(_, sign, ..)| sign.page_count());
+            (page + 1 < pages).then_some((e, page + 1))
+        }
+        (None, Some(e)) => Some((e, 0)),
+        (None, None) => None,
+    };
+}
+
+/// The prompt over a sign in reach, or the page being read in a speech box above it.
+fn sign_box(
+    mut ctxs: EguiContexts,
+    reader: Res<SignReader>,
+    bindings: Res<KeyBindings>,
+    q_window: Query<&Window, With<PrimaryWindow>>,
+    q_cam: Query<(&Camera, &GlobalTransform), With<OrbitCamera>>,
+    q_signs: Query<(&Sign, &GlobalTransform, Option<&Aabb>, Option<&Name>)>,
+) {
+    let Some(e) = reader.near else {
+        return;
+    };
+    let (Ok(ctx), Ok(window), Ok((camera, cam_gt)), Ok((sign, gt, aabb, name))) = (
+        ctxs.ctx_mut(),
+        q_window.single(),
+        q_cam.single(),
+        q_signs.get(e),
+    ) else {
+        return;
+    };
+    let (min, max) = sign_bounds(gt, aabb);
+    let top = Vec3::new((min.x + max.x) * 0.5, max.y + 0.2, (min.z + max.z) * 0.5);
+    let Ok(pos) = camera.world_to_viewport(cam_gt, top) else {
+        return;
+    };
+    // Viewport positions are in logical window pixels; egui points also include the UI scale
+    let pos = pos * window.scale_factor() / ctx.pixels_per_point();
+    let key = key_label(bindings.interact);
+
+    egui::Area::new(egui::Id::new("sign_box"))
+        .fixed_pos(egui::pos2(pos.x, pos.y))
+        .pivot(egui::Align2::CENTER_BOTTOM)
+        .order(egui::Order::Foreground)
+        .interactable(false)
+        .show(ctx, |ui| {
+            let frame = egui::Frame::new()
+                .fill(BOX_FILL)
+                .stroke(egui::Stroke::new(1.5, BOX_ACCENT))
+                .corner_radius(8.0)
+                .inner_margin(egui::Margin::same(12));
+            let Some((_, page)) = reader.open else {
+                frame.show(ui, |ui| {
+                    ui.colored_label(BOX_ACCENT, format!("[{key}] Read"));
+                });
+                return;
+            };
+            frame.show(ui, |ui| {
+                ui.set_max_width(BOX_WIDTH);
+                if let Some(name) = name {
+                    ui.colored_label(BOX_ACCENT, egui::RichText::new(name.as_str()).strong());
+                }
+                ui.label(
+                    egui::RichText::new(sign.page(page).unwrap_or_default())
+                        .size(16.0)
+                        .color(egui::Color32::from_gray(235)),
+                );
+                ui.add_space(4.0);
+                let pages = sign.page_count();
+                let next = if page + 1 < pages { "▸" } else { "✖" };
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    ui.weak(format!("{}/{pages}  [{key}] {next}", page + 1));
+                });
+            });
+        });
+}
+
+/// Inspector section: whether the object is a sign, and its pages.
+pub fn sign_inspector_ui(
+    ui: &mut egui::Ui,
+    commands: &mut Commands,
+    entity: Entity,
+    sign: Option<Mut<Sign>>,
+) -> bool {
+    let mut on = sign.is_some();
+    if ui
+        .checkbox(&mut on, "Sign")
+        .on_hover_text("Players read it with the interact key in Play mode")
+        .changed()
+    {
+        if on {
+            commands.entity(entity).insert(Sign::default());
+        } else {
+            commands.entity(entity).remove::<Sign>();
+        }
+        return true;
+    }
+    let Some(mut current) = sign else {
+        return false;
+    };
+    let mut s = current.clone();
+
+    let mut remove = None;
+    for (i, page) in s.pages.iter_mut().enumerate() {
+        ui.horizontal(|ui| {
+            ui.label(format!("Page {}", i + 1));
+            if ui.small_button("✖").described("Remove page").clicked() {
+                remove = Some(i);
+            }
+        });
+        ui.add(
+            egui::TextEdit::multiline(page)
+                .desired_rows(2)
+                .desired_width(f32::INFINITY),
+        );
+    }
+    if let Some(i) = remove
+        && s.pages.len() > 1
+    {
+        s.pages.remove(i);
+    }
+    if ui.button("Add page").clicked() {
+        s.pages.push(String::new());
+    }
+
+    current.set_if_neq(s)
+}
@@ -0,0 +1,517 @@
+use bevy::{
+    prelude::*,
+    render::{mesh::Indices, render_asset::RenderAssetUsages, render_resource::PrimitiveTopology},
+    window::PrimaryWindow,
+};
+use bevy_egui::egui;
+use bevy_rapier3d::prelude::Collider;
+use serde::{Deserialize, Serialize};
+
+use crate::SceneEditState;
+use crate::camera::{OrbitCamera, UiPointer};
+use crate::inspector::{Editable, EditableMesh, Selected, SpawnKind};
+
+/// Polyline samples per spline segment used for walls and arc-length spacing.
+const SAMPLES_PER_SEGMENT: usize = 16;
+
+/// How close (in pixels) the cursor must be to grab a control point.
+const HANDLE_PICK_RADIUS_PX: f32 = 12.0;
+
+/// What a spline generates along its curve.
+#[derive(Copy, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub enum SplineMode {
+    #[default]
+    Path, // flat tiles laid along the curve
+    Wall,  // one extruded wall mesh (with a collider)
+    Props, // copies of a primitive spaced along the curve
+    Guide, // nothing; the curve only guides (camera rails)
+}
+
+/// Catmull-Rom spline through `points` (local to the entity), saved as a first-class scene object.
+#[derive(Component, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SplinePath {
+    pub points: Vec<[f32; 3]>,
+    pub mode: SplineMode,
+    pub width: f32,   // path tile size / wall thickness
+    pub height: f32,  // wall height
+    pub spacing: f32, // distance between path tiles / props
+    pub prop: SpawnKind,
+    pub prop_scale: f32,
+    pub color: [f32; 3], // sRGB
+}
+
+impl Default for SplinePath {
+    fn default() -> Self {
+        Self {
+            points: vec![
+                [0.0, 0.0, 0.0],
+                [1.5, 0.0, 0.6],
+                [3.0, 0.0, -0.6],
+                [4.5, 0.0, 0.0],
+            ],
+            mode: SplineMode::Path,
+            width: 0.6,
+            height: 1.0,
+            spacing: 0.7,
+            prop: SpawnKind::Sphere,
+            prop_scale: 0.4,
+            color: [0.66, 0.62, 0.56],
+        }
+    }
+}
+
+/// Content spawned under a spline (tiles, wall, props); rebuilt whenever the spline changes.
+#[derive(Component)]
+pub struct SplineGenerated {
+    spline: Entity,
+}
+
+/// Viewport handle dragging for the selected spline.
+#[derive(Resource, Default)]
+pub struct SplineEditState {
+    hovered: Option<usize>,
+    dragging: Option<usize>,
+}
+
+impl SplineEditState {
+    /// The cursor is on (or dragging) a control point, so clicks shouldn't pick objects.
+    pub fn captures_pointer(&self) -> bool {
+        self.hovered.is_some() || self.dragging.is_some()
+    }
+}
+
+pub struct SplinesPlugin;
+impl Plugin for SplinesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SplineEditState>().add_systems(
+            Update,
+            (
+                drag_spline_points,
+                rebuild_spline_content,
+                draw_spline_gizmos,
+            )
+                .chain(),
+        );
+    }
+}
+
+/// Spawn an editable spline object (the entity itself has no mesh; content is generated).
+pub fn spawn_spline_object(commands: &mut Commands, tf: Transform, spline: SplinePath) -> Entity {
+    commands
+        .spawn((
+            Editable,
+            Name::new("Spline"),
+            tf,
+            Visibility::default(),
+            EditableMesh {
+                kind: SpawnKind::Spline,
+                collider: None,
+            },
+            spline,
+        ))
+        .id()
+}
+
+/// Dense polyline along the curve (local space).
+pub(crate) fn polyline(points: &[[f32; 3]]) -> Vec<Vec3> {
+    let pts: Vec<Vec3> = points.iter().map(|p| Vec3::from_array(*p)).collect();
+    let Ok(curve) = CubicCardinalSpline::new_catmull_rom(pts.iter().copied()).to_curve() else {
+        return pts;
+    };
+    let segments = curve.segments().len();
+    let n = segments * SAMPLES_PER_SEGMENT;
+    (0..=n)
+        .map(|i| curve.position(i as f32 / SAMPLES_PER_SEGMENT as f32))
+        .collect()
+}
+
+/// Horizontal direction of travel at each polyline vertex.
+fn tangents(line: &[Vec3]) -> Vec<Vec3> {
+    (0..line.len())
+        .map(|i| {
+            let a = line[i.saturating_sub(1)];
+            let b = line[(i + 1).min(line.len() - 1)];
+            let d = Vec3::new(b.x - a.x, 0.0, b.z - a.z);
+            d.try_normalize().unwrap_or(Vec3::X)
+        })
+        .collect()
+}
+
+/// Evenly spaced (by arc length) positions and directions along the polyline.
+fn spaced_samples(line: &[Vec3], spacing: f32) -> Vec<(Vec3, Vec3)> {
+    let spacing = spacing.max(0.05);
+    let dirs = tangents(line);
+    let mut out = Vec::new();
+    let mut next = 0.0;
+    let mut walked = 0.0;
+    for i in 0..line.len().saturating_sub(1) {
+        let (a, b) = (line[i], line[i + 1]);
+        let len = a.distance(b);
+        while next <= walked + len {
+            let t = if len > 0.0 {
+                (next - walked) / len
+            } else {
+                0.0
+            };
+            out.push((
+                a.lerp(b, t),
+                dirs[i].lerp(dirs[i + 1], t).normalize_or(Vec3::X),
+            ));
+            next += spacing;
+        }
+        walked += len;
+    }
+    out
+}
+
+/// Triangle-list mesh builder; quads are wound to face along `normal`.
+#[derive(Default)]
+struct MeshBuilder {
+    positions: Vec<[f32; 3]>,
+    normals: Vec<[f32; 3]>,
+    uvs: Vec<[f32; 2]>,
+    indices: Vec<u32>,
+}
+
+impl MeshBuilder {
+    fn quad(&mut self, q: [Vec3; 4], normal: Vec3) {
+        let base = self.positions.len() as u32;
+        for (p, uv) in q
+            .iter()
+            .zip([[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]])
+        {
+            self.positions.push(p.to_array());
+            self.normals.push(normal.to_array());
+            self.uvs.push(uv);
+        }
+        let facing = (q[1] - q[0]).cross(q[2] - q[0]).dot(normal) >= 0.0;
+        let tris: [u32; 6] = if facing {
+            [0, 1, 2, 0, 2, 3]
+        } else {
+            [0, 2, 1, 0, 3, 2]
+        };
+        self.indices.extend(tris.iter().map(|i| base + i));
+    }
+
+    fn collider(&self) -> Option<Collider> {
+        let verts = self
+            .positions
+            .iter()
+            .map(|p| Vec3::from_array(*p))
+            .collect();
+        let tris = self
+            .indices
+            .chunks_exact(3)
+            .map(|t| [t[0], t[1], t[2]])
+            .collect();
+        Collider::trimesh(verts, tris).ok()
+    }
+
+    fn build(self) -> Mesh {
+        Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::default(),
+        )
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, self.positions)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, self.normals)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, self.uvs)
+        .with_inserted_indices(Indices::U32(self.indices))
+    }
+}
+
+/// Extrude a `width` x `height` wall along the polyline (sides, top and end caps).
+fn wall_mesh(line: &[Vec3], width: f32, height: f32) -> MeshBuilder {
+    let mut b = MeshBuilder::default();
+    let dirs = tangents(line);
+    let hw = 0.5 * width;
+    let up = Vec3::Y * height;
+    let side = |i: usize| Vec3::Y.cross(dirs[i]).normalize_or(Vec3::Z) * hw;
+
+    for i in 0..line.len().saturating_sub(1) {
+        let (p0, p1) = (line[i], line[i + 1]);
+        let (s0, s1) = (side(i), side(i + 1));
+        let n = (s0 + s1).normalize_or(Vec3::Z);
+        // Left and right faces
+        b.quad([p0 + s0, p1 + s1, p1 + s1 + up, p0 + s0 + up], n);
+        b.quad([p0 - s0, p1 - s1, p1 - s1 + up, p0 - s0 + up], -n);
+        // Top
+        b.quad(
+            [p0 - s0 + up, p1 - s1 + up, p1 + s1 + up, p0 + s0 + up],
+            Vec3::Y,
+        );
+    }
+    // End caps
+    if let (Some(&first), Some(&last)) = (line.first(), line.last()) {
+        let (s0, s1) = (side(0), side(line.len() - 1));
+        b.quad(
+            [first - s0, first + s0, first + s0 + up, first - s0 + up],
+            -dirs[0],
+        );
+        b.quad(
+            [last - s1, last + s1, last + s1 + up, last - s1 + up],
+            dirs[line.len() - 1],
+        );
+    }
+    b
+}
+
+fn prop_mesh(kind: SpawnKind) -> Mesh {
+    match kind {
+        SpawnKind::Sphere => Mesh::from(Sphere::new(0.5)),
+        SpawnKind::Prism => Extrusion::new(
+            Triangle2d::new(
+                Vec2::new(0.0, 1.0),
+                Vec2::new(0.0, 0.0),
+                Vec2::new(1.0, 0.0),
+            ),
+            1.0,
+        )
+        .into(),
+        _ => Mesh::from(Cuboid::new(1.0, 1.0, 1.0)),
+    }
+}
+
+/// Respawn the generated content of changed splines. Tiles/props of one spline share a
+/// single mesh and material so they batch.
+fn rebuild_spline_content(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    q_changed: Query<(Entity, &SplinePath), Changed<SplinePath>>,
+    q_generated: Query<(Entity, &SplineGenerated)>,
+) {
+    for (e, spline) in &q_changed {
+        for (child, generated) in &q_generated {
+            if generated.spline == e {
+                commands.entity(child).despawn();
+            }
+        }
+        if spline.points.len() < 2 {
+            continue;
+        }
+
+        let line = polyline(&spline.points);
+        let [r, g, b] = spline.color;
+        let material = materials.add(StandardMaterial {
+            base_color: Color::srgb(r, g, b),
+            perceptual_roughness: 0.8,
+            ..default()
+        });
+
+        commands.entity(e).with_children(|c| match spline.mode {
+            SplineMode::Wall => {
+                let builder = wall_mesh(&line, spline.width, spline.height);
+                let collider = builder.collider();
+                let mut wall = c.spawn((
+                    Mesh3d(meshes.add(builder.build())),
+                    MeshMaterial3d(material),
+                    Transform::IDENTITY,
+                    SplineGenerated { spline: e },
+                    Name::new("SplineWall"),
+                ));
+                if let Some(collider) = collider {
+                    wall.insert(collider);
+                }
+            }
+            SplineMode::Path => {
+                let tile = meshes.add(Mesh::from(Cuboid::new(
+                    spline.width,
+                    0.05,
+                    spline.spacing * 0.9,
+                )));
+                for (p, dir) in spaced_samples(&line, spline.spacing) {
+                    c.spawn((
+                        Mesh3d(tile.clone()),
+                        MeshMaterial3d(material.clone()),
+                        Transform::from_translation(p + Vec3::Y * 0.025).looking_to(dir, Vec3::Y),
+                        SplineGenerated { spline: e },
+                    ));
+                }
+            }
+            SplineMode::Props => {
+                let prop = meshes.add(prop_mesh(spline.prop));
+                for (p, dir) in spaced_samples(&line, spline.spacing) {
+                    c.spawn((
+                        Mesh3d(prop.clone()),
+                        MeshMaterial3d(material.clone()),
+                        Transform::from_translation(p + Vec3::Y * 0.5 * spline.prop_scale)
+                            .looking_to(dir, Vec3::Y)
+                            .with_scale(Vec3::splat(spline.prop_scale)),
+                        SplineGenerated { spline: e },
+                    ));
+                }
+            }
+            SplineMode::Guide => {}
+        });
+    }
+}
+
+/// Drag the selected spline's control points in the viewport (on a horizontal plane
+/// at the point's height).
+fn drag_spline_points(
+    mouse: Res<ButtonInput<MouseButton>>,
+    mut edit: ResMut<SplineEditState>,
+    edit_state: Res<SceneEditState>,
+    mut ui_pointer: UiPointer,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    q_cam: Query<(&Camera, &GlobalTransform), With<OrbitCamera>>,
+    mut q_spline: Query<(&GlobalTransform, &mut SplinePath), With<Selected>>,
+) {
+    let cursor = windows.single().ok().and_then(|w| w.cursor_position());
+    let (Some(cursor), Ok((camera, cam_gt)), Ok((gt, mut spline))) =
+        (cursor, q_cam.single(), q_spline.single_mut())
+    else {
+        *edit = SplineEditState::default();
+        return;
+    };
+    if !edit_state.open {
+        *edit = SplineEditState::default();
+        return;
+    }
+
+    if let Some(i) = edit.dragging {
+        if !mouse.pressed(MouseButton::Left) || i >= spline.points.len() {
+            edit.dragging = None;
+            return;
+        }
+        let world = gt.transform_point(Vec3::from_array(spline.points[i]));
+        let Ok(ray) = camera.viewport_to_world(cam_gt, cursor) else {
+            return;
+        };
+        if let Some(d) = ray.intersect_plane(world, InfinitePlane3d::new(Vec3::Y)) {
+            let local = gt.affine().inverse().transform_point3(ray.get_point(d));
+            spline.points[i] = local.to_array();
+        }
+        return;
+    }
+
+    // Hover: nearest control point on screen within the pick radius
+    edit.hovered = spline
+        .points
+        .iter()
+        .enumerate()
+        .filter_map(|(i, p)| {
+            let world = gt.transform_point(Vec3::from_array(*p));
+            let screen = camera.world_to_viewport(cam_gt, world).ok()?;
+            let d = screen.distance(cursor);
+            (d < HANDLE_PICK_RADIUS_PX).then_some((i, d))
+        })
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(i, _)| i);
+
+    let over_ui = ui_pointer.wants_pointer_in_primary();
+    if mouse.just_pressed(MouseButton::Left) && !over_ui {
+        edit.dragging = edit.hovered;
+    }
+}
+
+/// Show every spline's curve in the editor, plus draggable handles on the selected one.
+fn draw_spline_gizmos(
+    mut gizmos: Gizmos,
+    edit_state: Res<SceneEditState>,
+    edit: Res<SplineEditState>,
+    q: Query<(&GlobalTransform, &SplinePath, Has<Selected>), With<Editable>>,
+) {
+    if !edit_state.open {
+        return;
+    }
+    for (gt, spline, selected) in &q {
+        let color = if selected {
+            Color::srgb(1.0, 0.85, 0.2)
+        } else {
+            Color::srgba(0.9, 0.9, 0.9, 0.5)
+        };
+        let line = polyline(&spline.points);
+        gizmos.linestrip(line.iter().map(|p| gt.transform_point(*p)), color);
+        // Click target for selecting the spline itself
+        gizmos.cuboid(
+            Transform::from_translation(gt.translation()).with_scale(Vec3::splat(0.25)),
+            color,
+        );
+        if !selected {
+            continue;
+        }
+        for (i, p) in spline.points.iter().enumerate() {
+            let active = edit.dragging == Some(i) || edit.hovered == Some(i);
+            let (radius, c) = if active {
+                (0.16, Color::srgb(1.0, 0.4, 0.1))
+            } else {
+                (0.1, color)
+            };
+            let world = gt.transform_point(Vec3::from_array(*p));
+            gizmos.sphere(Isometry3d::from_translation(world), radius, c);
+        }
+    }
+}
+
+/// Inspector section for spline generation settings and control points.
+pub fn spline_inspector_ui(ui: &mut egui::Ui, mut spline: Mut<SplinePath>) {
+    ui.heading("Spline");
+
+    let mut s = spline.clone();
+    ui.horizontal(|ui| {
+        ui.label("Mode:");
+        ui.selectable_value(&mut s.mode, SplineMode::Path, "Path");
+        ui.selectable_value(&mut s.mode, SplineMode::Wall, "Wall");
+        ui.selectable_value(&mut s.mode, SplineMode::Props, "Props");
+        ui.selectable_value(&mut s.mode, SplineMode::Guide, "Guide");
+    });
+    ui.horizontal(|ui| {
+        ui.label("Color");
+        ui.color_edit_button_rgb(&mut s.color);
+    });
+    match s.mode {
+        SplineMode::Path => {
+            ui.add(egui::Slider::new(&mut s.width, 0.1..=4.0).text("Tile width"));
+            ui.add(egui::Slider::new(&mut s.spacing, 0.1..=4.0).text("Spacing"));
+        }
+        SplineMode::Wall => {
+            ui.add(egui::Slider::new(&mut s.width, 0.05..=2.0).text("Thickness"));
+            ui.add(egui::Slider::new(&mut s.height, 0.1..=6.0).text("Height"));
+        }
+        SplineMode::Props => {
+            ui.horizontal(|ui| {
+                ui.label("Prop:");
+                ui.selectable_value(&mut s.prop, SpawnKind::Cuboid, "Cuboid");
+                ui.selectable_value(&mut s.prop, SpawnKind::Sphere, "Sphere");
+                ui.selectable_value(&mut s.prop, SpawnKind::Prism, "Prism");
+            });
+            ui.add(egui::Slider::new(&mut s.prop_scale, 0.05..=3.0).text("Prop scale"));
+            ui.add(egui::Slider::new(&mut s.spacing, 0.1..=4.0).text("Spacing"));
+        }
+        SplineMode::Guide => {
+            ui.weak("Nothing is generated; the curve only shows while editing.");
+        }
+    }
+
+    ui.label(format!(
+        "{} control points (drag the handles in the viewport)",
+        s.points.len()
+    ));
+    ui.horizontal(|ui| {
+        if ui.button("Add point").clicked() {
+            // Continue in the direction of the last segment; a spline with no points starts
+            // again at its origin
+            let next = match s.points.len() {
+                0 => Vec3::ZERO,
+                n => {
+                    let last = Vec3::from_array(s.points[n - 1]);
+                    let prev = Vec3::from_array(s.points[n.saturating_sub(2)]);
+                    last + (last - prev).try_normalize().unwrap_or(Vec3::X) * 1.5
+                }
+            };
+            s.points.push(next.to_array());
+        }
+        if ui
+            .add_enabled(s.points.len() > 2, egui::Button::new("Remove last"))
+            .clicked()
+        {
+            s.points.pop();
+        }
+    });
+
+    // Only touch the component on edits so content isn't regenerated every frame
+    if s != *spline {
+        *spline = s;
+    }
+}
@@ -0,0 +1,135 @@
+use bevy::prelude::*;
+use bevy_egui::egui;
+use bevy_rapier3d::prelude::Collider;
+use serde::{Deserialize, Serialize};
+
+/// Parametric staircase for `SpawnKind::Stairs`. The mesh sits on the entity's origin
+/// (bottom at y = 0, centered in x/z) and climbs toward -Z (the entity's forward).
+#[derive(Component, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct StairsParams {
+    pub steps: u32,
+    pub rise: f32,  // height of one step
+    pub run: f32,   // depth of one step
+    pub width: f32, // side to side
+    /// Collide as a smooth ramp (easy to walk up) instead of individual steps
+    #[serde(default)]
+    pub ramp_collider: bool,
+}
+
+impl Default for StairsParams {
+    fn default() -> Self {
+        Self {
+            steps: 5,
+            rise: 0.2,
+            run: 0.3,
+            width: 1.0,
+            ramp_collider: true,
+        }
+    }
+}
+
+impl StairsParams {
+    fn total_run(&self) -> f32 {
+        self.steps as f32 * self.run
+    }
+
+    /// Center and size of step `i` (0 = bottom), each a solid block down to the floor.
+    fn step_box(&self, i: u32) -> (Vec3, Vec3) {
+        let h = (i + 1) as f32 * self.rise;
+        let z = 0.5 * self.total_run() - (i as f32 + 0.5) * self.run;
+        (
+            Vec3::new(0.0, 0.5 * h, z),
+            Vec3::new(self.width, h, self.run),
+        )
+    }
+}
+
+pub struct StairsPlugin;
+impl Plugin for StairsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, rebuild_stairs_meshes);
+    }
+}
+
+/// Build the staircase mesh by merging one cuboid per step.
+pub fn stairs_mesh(params: &StairsParams) -> Mesh {
+    let mut mesh: Option<Mesh> = None;
+    for i in 0..params.steps.max(1) {
+        let (center, size) = params.step_box(i);
+        let step =
+            Mesh::from(Cuboid::from_size(size)).transformed_by(Transform::from_translation(center));
+        match mesh.as_mut() {
+            Some(m) => m
+                .merge(&step)
+                .expect("cuboid meshes share the same attributes"),
+            None => mesh = Some(step),
+        }
+    }
+    mesh.expect("at least one step")
+}
+
+/// Ramp (wedge) or stepped collider matching `stairs_mesh`.
+pub fn stairs_collider(params: &StairsParams) -> Collider {
+    if params.ramp_collider {
+        let (hw, hl) = (0.5 * params.width, 0.5 * params.total_run());
+        let top = params.steps.max(1) as f32 * params.rise;
+        let pts = [
+            Vec3::new(-hw, 0.0, hl),
+            Vec3::new(hw, 0.0, hl),
+            Vec3::new(-hw, 0.0, -hl),
+            Vec3::new(hw, 0.0, -hl),
+            Vec3::new(-hw, top, -hl),
+            Vec3::new(hw, top, -hl),
+        ];
+        if let Some(c) = Collider::convex_hull(&pts) {
+            return c;
+        }
+    }
+    Collider::compound(
+        (0..params.steps.max(1))
+            .map(|i| {
+                let (center, size) = params.step_box(i);
+                (
+                    center,
+                    Quat::IDENTITY,
+                    Collider::cuboid(0.5 * size.x, 0.5 * size.y, 0.5 * size.z),
+                )
+            })
+            .collect(),
+    )
+}
+
+/// Regenerate the mesh when the parameters are edited (the old mesh asset is dropped with its
+/// handle); the `BoundsPlugin` picks up the new mesh for bounds and collider. Freshly spawned
+/// stairs already got a matching mesh from the `AssetCache`.
+fn rebuild_stairs_meshes(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    q: Query<(Entity, Ref<StairsParams>), Changed<StairsParams>>,
+) {
+    for (e, params) in &q {
+        if params.is_added() {
+            continue;
+        }
+        commands
+            .entity(e)
+            .insert(Mesh3d(meshes.add(stairs_mesh(&params))));
+    }
+}
+
+/// Inspector section for stairs parameters.
+pub fn stairs_inspector_ui(ui: &mut egui::Ui, mut params: Mut<StairsParams>) {
+    ui.heading("Stairs");
+
+    let mut p = *params;
+    ui.add(egui::Slider::new(&mut p.steps, 1..=32).text("Steps"));
+    ui.add(egui::Slider::new(&mut p.rise, 0.05..=1.0).text("Rise"));
+    ui.add(egui::Slider::new(&mut p.run, 0.05..=1.0).text("Run"));
+    ui.add(egui::Slider::new(&mut p.width, 0.2..=8.0).text("Width"));
+    ui.checkbox(&mut p.ramp_collider, "Ramp collider");
+
+    // Only touch the component on edits so the mesh isn't rebuilt every frame
+    if p != *params {
+        *params = p;
+    }
+}
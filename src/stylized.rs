@@ -0,0 +1,481 @@
+//! Stylized materials for mesh objects, set per object in the inspector:
+//!
+//! - **Triplanar textures** for blocky terrain: textures are projected along the world axes
+//!   instead of following the mesh UVs, so stretched cuboids keep square texels and
+//!   neighbouring blocks line up. Upward faces get the top texture and everything else the side
+//!   one, blended across edges by the sharpness.
+//! - **Toon shading**: light falls off in a few flat bands instead of smoothly, with a hard-edged
+//!   highlight, for an illustrated look that goes with the outlines. It can also be turned on
+//!   for every object from the Effect settings ([`ToonShading`]).
+//!
+//! Objects flagged to glow (`post::glow`) get a skin too while the bloom is glow-only, one whose
+//! emissive is lifted over the bloom threshold, and so does the painted ground
+//! ([`crate::ground_paint`]), whose skin mixes the paint layers by the splat map.
+//!
+//! The object keeps its standard material (what the inspector edits and the scene saves) on a
+//! render layer no camera draws; a child "skin" with the same mesh draws it through the
+//! stylized shader, picking up every change to that material.
+
+use std::collections::HashSet;
+
+use bevy::{
+    image::{ImageAddressMode, ImageLoaderSettings, ImageSampler, ImageSamplerDescriptor},
+    pbr::{ExtendedMaterial, MaterialExtension},
+    prelude::*,
+    render::{
+        render_resource::{AsBindGroup, ShaderRef},
+        view::RenderLayers,
+    },
+};
+use bevy_egui::egui;
+use serde::{Deserialize, Serialize};
+
+use crate::ground_paint::{GroundPaint, MAX_LAYERS, Splat};
+use crate::inspector::Editable;
+use crate::instancing::InstancedTile;
+use crate::post::glow::{BloomIsolation, Glow};
+
+const SHADER_ASSET_PATH: &str = "shaders/stylized.wgsl";
+
+/// Render layer the stylized objects' own meshes move to: nothing renders it, the skin draws
+/// them instead.
+const HIDDEN_LAYER: usize = 30;
+
+pub type StylizedMaterial = ExtendedMaterial<StandardMaterial, StylizedExtension>;
+
+/// Triplanar textures tinting the base color, toon lighting, and ground paint layers replacing
+/// it.
+#[derive(Asset, AsBindGroup, Reflect, Debug, Clone)]
+pub struct StylizedExtension {
+    /// x = texture repeats per meter, y = blend sharpness, z = 1 when textured
+    #[uniform(100)]
+    pub triplanar: Vec4,
+    /// x = light bands (0 for smooth shading), y = specular cutoff
+    #[uniform(101)]
+    pub toon: Vec4,
+    #[texture(102)]
+    #[sampler(103)]
+    pub top: Option<Handle<Image>>,
+    #[texture(104)]
+    #[sampler(105)]
+    pub side: Option<Handle<Image>>,
+    /// x = paint layers (0 when unpainted), y = layer texture repeats per meter
+    #[uniform(106)]
+    pub splat: Vec4,
+    /// Each layer's tint
+    #[uniform(107)]
+    pub splat_tints: [Vec4; MAX_LAYERS],
+    /// The layers' shares over the mesh UVs, one per channel
+    #[texture(108)]
+    #[sampler(109)]
+    pub splat_map: Option<Handle<Image>>,
+    #[texture(110)]
+    #[sampler(111)]
+    pub layer0: Option<Handle<Image>>,
+    #[texture(112)]
+    #[sampler(113)]
+    pub layer1: Option<Handle<Image>>,
+    #[texture(114)]
+    #[sampler(115)]
+    pub layer2: Option<Handle<Image>>,
+    #[texture(116)]
+    #[sampler(117)]
+    pub layer3: Option<Handle<Image>>,
+}
+
+impl MaterialExtension for StylizedExtension {
+    fn fragment_shader() -> ShaderRef {
+        SHADER_ASSET_PATH.into()
+    }
+
+    fn deferred_fragment_shader() -> ShaderRef {
+        SHADER_ASSET_PATH.into()
+    }
+}
+
+/// Persisted on a mesh object drawn with triplanar textures.
+#[derive(Component, Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Triplanar {
+    /// Texture on upward faces, relative to `assets/` (empty for none)
+    pub top: String,
+    /// Texture on the sides and underneath
+    pub side: String,
+    /// Texture repeats per meter
+    pub scale: f32,
+    /// How quickly the textures give way to each other around edges; higher is crisper
+    pub sharpness: f32,
+}
+
+impl Default for Triplanar {
+    fn default() -> Self {
+        Self {
+            top: "textures/grass_top.png".into(),
+            side: "textures/dirt_side.png".into(),
+            scale: 1.0,
+            sharpness: 4.0,
+        }
+    }
+}
+
+/// Persisted on a mesh object drawn with toon shading.
+#[derive(Component, Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Toon {
+    /// Flat light bands between shadow and full light
+    pub steps: u32,
+    /// How bright the specular reflection must be to show as a highlight; 1 for none
+    pub specular_cutoff: f32,
+}
+
+impl Default for Toon {
+    fn default() -> Self {
+        Self {
+            steps: 3,
+            specular_cutoff: 0.5,
+        }
+    }
+}
+
+/// Toon shading for every mesh object, set in the Effect settings. Objects with their own
+/// [`Toon`] keep its bands and cutoff.
+#[derive(Resource, Clone, Copy, PartialEq, Default)]
+pub struct ToonShading {
+    pub all: bool,
+    pub toon: Toon,
+}
+
+/// What a skin draws differently from the object's own material.
+#[derive(Clone, PartialEq)]
+struct Look {
+    triplanar: Option<Triplanar>,
+    toon: Option<Toon>,
+    /// Emissive lifted over this bloom threshold
+    glow: Option<(Glow, f32)>,
+    /// Ground paint, on the painted ground
+    splat: Option<Splat>,
+}
+
+impl Look {
+    fn is_plain(&self) -> bool {
+        self.triplanar.is_none()
+            && self.toon.is_none()
+            && self.glow.is_none()
+            && self.splat.is_none()
+    }
+
+    /// The skin's copy of the object's material.
+    fn base(&self, material: &StandardMaterial) -> StandardMaterial {
+        let mut base = material.clone();
+        if let Some((glow, threshold)) = self.glow {
+            base.emissive = glow.emissive(material, threshold);
+        }
+        base
+    }
+}
+
+/// On a stylized object: the child drawing it, that child's material and what it was made for.
+#[derive(Component)]
+struct StylizedSkin {
+    skin: Entity,
+    material: Handle<StylizedMaterial>,
+    applied: Look,
+    /// The object's own render layers (a reflective surface's), which the skin is drawn on and
+    /// the object gets back when the skin comes off
+    layers: Option<RenderLayers>,
+}
+
+pub struct StylizedPlugin;
+impl Plugin for StylizedPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(MaterialPlugin::<StylizedMaterial>::default())
+            .init_resource::<ToonShading>()
+            .add_systems(Update, update_skins);
+    }
+}
+
+/// Load a texture that tiles (the default sampler clamps).
+fn load_tiling(asset_server: &AssetServer, path: &str) -> Option<Handle<Image>> {
+    if path.trim().is_empty() {
+        return None;
+    }
+    Some(
+        asset_server.load_with_settings(path.to_string(), |s: &mut ImageLoaderSettings| {
+            s.sampler = ImageSampler::Descriptor(ImageSamplerDescriptor {
+                address_mode_u: ImageAddressMode::Repeat,
+                address_mode_v: ImageAddressMode::Repeat,
+                address_mode_w: ImageAddressMode::Repeat,
+                ..ImageSamplerDescriptor::linear()
+            });
+        }),
+    )
+}
+
+fn extension(asset_server: &AssetServer, look: &Look) -> StylizedExtension {
+    let (triplanar, toon, splat) = (look.triplanar.as_ref(), look.toon, look.splat.as_ref());
+    let layer = |i: usize| {
+        splat
+            .and_then(|s| s.layers.get(i))
+            .and_then(|l| load_tiling(asset_server, &l.texture))
+    };
+    let mut splat_tints = [Vec4::ZERO; MAX_LAYERS];
+    for (tint, layer) in splat_tints
+        .iter_mut()
+        .zip(splat.iter().flat_map(|s| &s.layers))
+    {
+        *tint = Vec3::from_array(layer.tint).extend(1.0);
+    }
+    StylizedExtension {
+        triplanar: triplanar.map_or(Vec4::ZERO, |t| {
+            Vec4::new(t.scale.max(0.01), t.sharpness.clamp(1.0, 32.0), 1.0, 0.0)
+        }),
+        toon: toon.map_or(Vec4::ZERO, |t| {
+            Vec4::new(t.steps.clamp(1, 16) as f32, t.specular_cutoff, 0.0, 0.0)
+        }),
+        top: triplanar.and_then(|t| load_tiling(asset_server, &t.top)),
+        side: triplanar.and_then(|t| load_tiling(asset_server, &t.side)),
+        splat: splat.map_or(Vec4::ZERO, |s| {
+            let layers = s.layers.len().min(MAX_LAYERS) as f32;
+            Vec4::new(layers, s.repeats.max(0.01), 0.0, 0.0)
+        }),
+        splat_tints,
+        splat_map: splat.map(|s| s.map.clone()),
+        layer0: layer(0),
+        layer1: layer(1),
+        layer2: layer(2),
+        layer3: layer(3),
+    }
+}
+
+/// Give objects that want a stylized look a skin (hiding them from the cameras), keep the skin
+/// following the object's settings, (regenerated) mesh, standard material and paint, and take it
+/// away again when the look is turned off. Instanced tiles have their own draw path and are left
+/// alone.
+fn update_skins(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    toon_shading: Res<ToonShading>,
+    isolation: Res<BloomIsolation>,
+    ground_paint: Option<Res<GroundPaint>>,
+    mut material_events: EventReader<AssetEvent<StandardMaterial>>,
+    std_materials: Res<Assets<StandardMaterial>>,
+    mut materials: ResMut<Assets<StylizedMaterial>>,
+    mut q_objects: Query<
+        (
+            Entity,
+            (
+                Option<&Triplanar>,
+                Option<&Toon>,
+                Option<&Glow>,
+                Option<&Name>,
+                Option<&RenderLayers>,
+            ),
+            Ref<Mesh3d>,
+            Ref<MeshMaterial3d<StandardMaterial>>,
+            Option<&mut StylizedSkin>,
+            Has<InstancedTile>,
+        ),
+        With<Editable>,
+    >,
+    mut q_skin_meshes: Query<&mut Mesh3d, Without<Editable>>,
+) {
+    let modified: HashSet<AssetId<StandardMaterial>> = material_events
+        .read()
+        .filter_map(|ev| match ev {
+            AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+    let splat = ground_paint.as_deref().and_then(GroundPaint::splat);
+    // Painting rewrites the splat map in place; the skin's material has to hear of it
+    let painted = ground_paint.as_ref().is_some_and(|p| p.is_changed());
+    let hidden = RenderLayers::layer(HIDDEN_LAYER);
+    for (e, (triplanar, toon, glow, name, layers), mesh, mat, skin, tile) in &mut q_objects {
+        let ground = ground_paint
+            .as_deref()
+            .is_some_and(|p| name.is_some_and(|n| n.as_str() == p.ground));
+        let look = Look {
+            triplanar: triplanar.cloned(),
+            toon: toon
+                .copied()
+                .or(toon_shading.all.then_some(toon_shading.toon)),
+            glow: glow
+                .filter(|_| isolation.glow_only)
+                .map(|g| (*g, isolation.threshold)),
+            splat: splat.as_ref().filter(|_| ground).cloned(),
+        };
+        let wanted = !tile && !look.is_plain();
+        let Some(mut skin) = skin else {
+            if wanted {
+                let base = std_materials.get(&mat.0).cloned().unwrap_or_default();
+                let material = materials.add(StylizedMaterial {
+                    base: look.base(&base),
+                    extension: extension(&asset_server, &look),
+                });
+                let skin = commands
+                    .spawn((
+                        Mesh3d(mesh.0.clone()),
+                        MeshMaterial3d(material.clone()),
+                        Transform::IDENTITY,
+                        layers.cloned().unwrap_or_default(),
+                        Name::new("Stylized skin"),
+                        ChildOf(e),
+                    ))
+                    .id();
+                commands.entity(e).insert((
+                    hidden.clone(),
+                    StylizedSkin {
+                        skin,
+                        material,
+                        applied: look,
+                        layers: layers.cloned(),
+                    },
+                ));
+            }
+            continue;
+        };
+        if !wanted {
+            commands.entity(skin.skin).despawn();
+            commands.entity(e).remove::<StylizedSkin>();
+            match skin.layers.take() {
+                Some(layers) => commands.entity(e).insert(layers),
+                None => commands.entity(e).remove::<RenderLayers>(),
+            };
+            continue;
+        }
+        // Layers set on the object since (it became a reflective surface, or stopped being
+        // one) are the skin's to draw on
+        if layers != Some(&hidden) {
+            skin.layers = layers.cloned();
+            commands
+                .entity(skin.skin)
+                .insert(layers.cloned().unwrap_or_default());
+            commands.entity(e).insert(hidden.clone());
+        }
+
+        if mesh.is_changed()
+            && let Ok(mut skin_mesh) = q_skin_meshes.get_mut(skin.skin)
+        {
+            skin_mesh.set_if_neq(Mesh3d(mesh.0.clone()));
+        }
+        let base_changed = mat.is_changed() || modified.contains(&mat.0.id());
+        let look_changed = skin.applied != look;
+        let repainted = painted && look.splat.is_some();
+        if !base_changed && !look_changed && !repainted {
+            continue;
+        }
+        let Some(skin_mat) = materials.get_mut(&skin.material) else {
+            continue;
+        };
+        // The glow depends on the base, so a new look recopies it too
+        if let Some(base) = std_materials.get(&mat.0) {
+            skin_mat.base = look.base(base);
+        }
+        if look_changed {
+            skin_mat.extension = extension(&asset_server, &look);
+            skin.applied = look;
+        }
+    }
+}
+
+/// Inspector section: switch triplanar textures on and set them up.
+pub fn triplanar_inspector_ui(
+    ui: &mut egui::Ui,
+    commands: &mut Commands,
+    entity: Entity,
+    triplanar: Option<Mut<Triplanar>>,
+) -> bool {
+    let mut on = triplanar.is_some();
+    if ui
+        .checkbox(&mut on, "Triplanar textures")
+        .on_hover_text("Project textures along the world axes instead of the mesh UVs")
+        .changed()
+    {
+        if on {
+            commands.entity(entity).insert(Triplanar::default());
+        } else {
+            commands.entity(entity).remove::<Triplanar>();
+        }
+        return true;
+    }
+    let Some(mut triplanar) = triplanar else {
+        return false;
+    };
+
+    let mut edited = triplanar.clone();
+    for (label, path) in [("Top", &mut edited.top), ("Side", &mut edited.side)] {
+        // Edit the path in a scratch buffer so the texture only reloads once editing is done
+        let id = ui.make_persistent_id(("triplanar_texture", label, entity));
+        let mut text = ui
+            .data_mut(|d| d.get_temp::<String>(id))
+            .unwrap_or_else(|| path.clone());
+        ui.horizontal(|ui| {
+            ui.label(label);
+            let resp = ui.text_edit_singleline(&mut text);
+            if resp.lost_focus() {
+                ui.data_mut(|d| d.remove::<String>(id));
+                *path = text;
+            } else if resp.has_focus() {
+                ui.data_mut(|d| d.insert_temp(id, text));
+            }
+        });
+    }
+    ui.add(
+        egui::Slider::new(&mut edited.scale, 0.1..=4.0)
+            .logarithmic(true)
+            .text("Repeats per m"),
+    );
+    ui.add(egui::Slider::new(&mut edited.sharpness, 1.0..=16.0).text("Blend sharpness"));
+    ui.weak("The object's color tints both textures.");
+    triplanar.set_if_neq(edited)
+}
+
+/// Inspector section: switch toon shading on for the object and set its bands.
+pub fn toon_inspector_ui(
+    ui: &mut egui::Ui,
+    commands: &mut Commands,
+    entity: Entity,
+    toon: Option<Mut<Toon>>,
+    all: bool,
+) -> bool {
+    let mut on = toon.is_some();
+    let label = if all {
+        "Own toon shading"
+    } else {
+        "Toon shading"
+    };
+    if ui
+        .checkbox(&mut on, label)
+        .on_hover_text("Flat bands of light and a hard highlight")
+        .changed()
+    {
+        if on {
+            commands.entity(entity).insert(Toon::default());
+        } else {
+            commands.entity(entity).remove::<Toon>();
+        }
+        return true;
+    }
+    let Some(mut toon) = toon else {
+        return false;
+    };
+    let mut edited = *toon;
+    toon_ui(ui, &mut edited);
+    toon.set_if_neq(edited)
+}
+
+/// Band and highlight sliders, for an object or the whole scene.
+pub fn toon_ui(ui: &mut egui::Ui, toon: &mut Toon) {
+    ui.add(egui::Slider::new(&mut toon.steps, 1..=8).text("Light bands"));
+    ui.add(
+        egui::Slider::new(&mut toon.specular_cutoff, 0.0..=1.0)
+            .text("Specular cutoff")
+            .custom_formatter(|v, _| {
+                if v >= 1.0 {
+                    "no highlight".into()
+                } else {
+                    format!("{v:.2}")
+                }
+            }),
+    );
+}
@@ -0,0 +1,120 @@
+//! Surface behaviors for walkable objects: conveyors that carry the player along, ice that keeps
+//! them sliding and bounce pads that throw them up. The player's controller applies them while
+//! standing on the object (see `player::player_motion_with_gravity`).
+
+use bevy::prelude::*;
+use bevy::render::primitives::Aabb;
+use bevy_egui::egui;
+use serde::{Deserialize, Serialize};
+
+use crate::SceneEditState;
+use crate::editor_mode::EditorMode;
+use crate::inspector::{Editable, aabb_world};
+
+/// Persisted on the object walked on.
+#[derive(Component, Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub enum Surface {
+    /// Carries whoever stands on it along its own forward (-Z) axis, in meters per second
+    Conveyor { speed: f32 },
+    /// How quickly walking changes speed (per second); low values keep the player sliding
+    Ice { grip: f32 },
+    /// Throws the player up this many meters on landing
+    Bounce { height: f32 },
+}
+
+impl Surface {
+    const CONVEYOR: Self = Self::Conveyor { speed: 1.5 };
+    const ICE: Self = Self::Ice { grip: 1.5 };
+    const BOUNCE: Self = Self::Bounce { height: 2.5 };
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Conveyor { .. } => "Conveyor",
+            Self::Ice { .. } => "Ice",
+            Self::Bounce { .. } => "Bounce pad",
+        }
+    }
+}
+
+pub struct SurfacesPlugin;
+impl Plugin for SurfacesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EditorMode>()
+            .add_systems(Update, draw_conveyor_gizmos);
+    }
+}
+
+/// Conveyors show which way they run while editing.
+fn draw_conveyor_gizmos(
+    mut gizmos: Gizmos,
+    edit_state: Res<SceneEditState>,
+    mode: Res<EditorMode>,
+    q: Query<(&GlobalTransform, &Aabb, &Surface), With<Editable>>,
+) {
+    if !edit_state.open || *mode == EditorMode::Play {
+        return;
+    }
+    let color = Color::srgb(0.95, 0.8, 0.2);
+    for (gt, aabb, surface) in &q {
+        let Surface::Conveyor { speed } = *surface else {
+            continue;
+        };
+        let bounds = aabb_world(*aabb, gt);
+        let top = Vec3::from(bounds.center) + Vec3::Y * (bounds.half_extents.y + 0.02);
+        let dir = (gt.forward().as_vec3() * Vec3::new(1.0, 0.0, 1.0)).normalize_or_zero()
+            * speed.signum();
+        let reach = bounds.half_extents.x.min(bounds.half_extents.z).max(0.2);
+        gizmos.arrow(top - dir * reach, top + dir * reach, color);
+    }
+}
+
+/// Inspector section: what walking on the object does.
+pub fn surface_inspector_ui(
+    ui: &mut egui::Ui,
+    commands: &mut Commands,
+    entity: Entity,
+    surface: Option<Mut<Surface>>,
+) -> bool {
+    let current = surface.as_deref().copied();
+    let mut picked = current;
+    egui::ComboBox::from_label("Surface")
+        .selected_text(picked.map_or("Normal", Surface::label))
+        .show_ui(ui, |ui| {
+            ui.selectable_value(&mut picked, None, "Normal");
+            for kind in [Surface::CONVEYOR, Surface::ICE, Surface::BOUNCE] {
+                let same = picked.is_some_and(|p| p.label() == kind.label());
+                if ui.selectable_label(same, kind.label()).clicked() && !same {
+                    picked = Some(kind);
+                }
+            }
+        });
+    if picked.map(Surface::label) != current.map(Surface::label) {
+        match picked {
+            Some(s) => commands.entity(entity).insert(s),
+            None => commands.entity(entity).remove::<Surface>(),
+        };
+        return true;
+    }
+
+    let Some(mut surface) = surface else {
+        return false;
+    };
+    let mut s = *surface;
+    match &mut s {
+        Surface::Conveyor { speed } => {
+            ui.add(egui::Slider::new(speed, -6.0..=6.0).text("Speed (m/s)"));
+            ui.weak("Runs along the object's forward axis; rotate it to turn the belt.");
+        }
+        Surface::Ice { grip } => {
+            ui.add(
+                egui::Slider::new(grip, 0.1..=10.0)
+                    .logarithmic(true)
+                    .text("Grip"),
+            );
+        }
+        Surface::Bounce { height } => {
+            ui.add(egui::Slider::new(height, 0.5..=10.0).text("Height (m)"));
+        }
+    }
+    surface.set_if_neq(s)
+}
@@ -0,0 +1,693 @@
+//! Import block layouts sketched in the Tiled map editor (<https://www.mapeditor.org>).
+//!
+//! Every non-empty cell of a tile layer becomes a column of unit cuboids on the build grid,
+//! the same tiles build mode places. Tiles are configured with custom properties in the tileset:
+//!
+//! - `height` (int, default 1): blocks stacked in the column
+//! - `material` (string): a build palette name (`Stone`, `Grass`, `Dirt`, `Sand`, `Brick`, `Wood`)
+//! - `color` (color): any other color; tiles with neither get a palette entry by tile ID
+//!
+//! and a layer's `elevation` property (int, default 0) lifts all of its columns, so layers can
+//! be stacked. Maps are read from JSON (`.tmj`/`.json`) or TMX with CSV or XML layer data;
+//! external tilesets are resolved next to the map.
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+use serde::Deserialize;
+
+use crate::SceneEditState;
+use crate::asset_cache::{AssetCache, PlainMaterial};
+use crate::build_mode::TILE_PALETTE;
+use crate::inspector::{Editable, EditableMesh, SpawnKind};
+use crate::instancing::InstancedTile;
+use crate::scene_io;
+
+/// Tiled keeps flip/rotation flags in the top bits of a global tile ID.
+const GID_MASK: u32 = 0x0FFF_FFFF;
+
+/// Import the map at `path` into the scene.
+#[derive(Event)]
+pub struct ImportTiledEvent {
+    pub path: String,
+}
+
+#[derive(Resource, Default)]
+struct TiledImportState {
+    path: String,
+    status: Option<String>,
+}
+
+pub struct TiledPlugin;
+impl Plugin for TiledPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AssetCache>()
+            .init_resource::<TiledImportState>()
+            .add_event::<ImportTiledEvent>()
+            .add_systems(Update, import_tiled)
+            .add_systems(EguiPrimaryContextPass, tiled_window);
+    }
+}
+
+/// Per-tile settings from the tileset's custom properties.
+#[derive(Default, Clone)]
+struct TileProps {
+    height: Option<u32>,
+    material: Option<String>,
+    color: Option<Color>,
+}
+
+struct Tileset {
+    first_gid: u32,
+    tiles: HashMap<u32, TileProps>,
+}
+
+struct TileLayer {
+    name: String,
+    width: u32,
+    elevation: i32,
+    /// Global tile IDs, row by row (0 = empty)
+    data: Vec<u32>,
+}
+
+/// What the importer needs from a map, whichever format it came in.
+struct TiledMap {
+    width: u32,
+    height: u32,
+    layers: Vec<TileLayer>,
+    tilesets: Vec<Tileset>,
+}
+
+impl TiledMap {
+    fn load(path: &str) -> Result<TiledMap, String> {
+        let text = scene_io::read_text(path).ok_or_else(|| format!("cannot read {path}"))?;
+        Self::parse(&text, path)
+    }
+
+    fn parse(text: &str, path: &str) -> Result<TiledMap, String> {
+        let map = if path.ends_with(".tmx") {
+            tmx::parse(text, path)?
+        } else {
+            json::parse(text, path)?
+        };
+        // Layers without a width of their own are laid out by the map's
+        if map.width == 0 && map.layers.iter().any(|l| l.width == 0) {
+            return Err("map has zero width".into());
+        }
+        Ok(map)
+    }
+
+    /// Tileset and local tile ID of a global tile ID.
+    fn tile(&self, gid: u32) -> Option<(&Tileset, u32)> {
+        self.tilesets
+            .iter()
+            .filter(|ts| ts.first_gid <= gid)
+            .max_by_key(|ts| ts.first_gid)
+            .map(|ts| (ts, gid - ts.first_gid))
+    }
+}
+
+/// Resolve an external tileset reference against the map's directory.
+fn sibling_path(map_path: &str, source: &str) -> String {
+    match map_path.rfind(['/', '\\']) {
+        Some(i) => format!("{}/{source}", &map_path[..i]),
+        None => source.to_string(),
+    }
+}
+
+/// Tiled colors are `#RRGGBB` or `#AARRGGBB`.
+fn parse_color(s: &str) -> Option<Color> {
+    let hex = s.trim_start_matches('#');
+    let v = u32::from_str_radix(hex, 16).ok()?;
+    let (a, rgb) = match hex.len() {
+        6 => (0xFF, v),
+        8 => (v >> 24, v & 0xFF_FFFF),
+        _ => return None,
+    };
+    let byte = |shift: u32| ((rgb >> shift) & 0xFF) as u8;
+    Some(Color::srgba_u8(byte(16), byte(8), byte(0), a as u8))
+}
+
+mod json {
+    //! The JSON map format (`.tmj`, `.json`; tilesets `.tsj`).
+
+    use super::*;
+
+    #[derive(Deserialize)]
+    struct Map {
+        width: u32,
+        height: u32,
+        #[serde(default)]
+        layers: Vec<Layer>,
+        #[serde(default)]
+        tilesets: Vec<TilesetRef>,
+    }
+
+    #[derive(Deserialize)]
+    struct Layer {
+        #[serde(rename = "type")]
+        kind: String,
+        #[serde(default)]
+        name: String,
+        #[serde(default)]
+        width: u32,
+        #[serde(default)]
+        data: Option<serde_json::Value>,
+        #[serde(default)]
+        properties: Vec<Property>,
+        /// Children of a group layer
+        #[serde(default)]
+        layers: Vec<Layer>,
+    }
+
+    #[derive(Deserialize)]
+    struct TilesetRef {
+        firstgid: u32,
+        source: Option<String>,
+        #[serde(default)]
+        tiles: Vec<Tile>,
+    }
+
+    #[derive(Deserialize)]
+    struct TilesetFile {
+        #[serde(default)]
+        tiles: Vec<Tile>,
+    }
+
+    #[derive(Deserialize)]
+    struct Tile {
+        id: u32,
+        #[serde(default)]
+        properties: Vec<Property>,
+    }
+
+    #[derive(Deserialize)]
+    struct Property {
+        name: String,
+        value: serde_json::Value,
+    }
+
+    fn tile_props(properties: &[Property]) -> TileProps {
+        let mut props = TileProps::default();
+        for p in properties {
+            match p.name.as_str() {
+                "height" => props.height = p.value.as_u64().map(|h| h as u32),
+                "material" => props.material = p.value.as_str().map(str::to_string),
+                "color" => props.color = p.value.as_str().and_then(parse_color),
+                _ => {}
+            }
+        }
+        props
+    }
+
+    fn flatten(layers: Vec<Layer>, out: &mut Vec<TileLayer>) -> Result<(), String> {
+        for layer in layers {
+            match layer.kind.as_str() {
+                "group" => flatten(layer.layers, out)?,
+                "tilelayer" => {
+                    let data = match layer.data {
+                        Some(serde_json::Value::Array(cells)) => cells
+                            .iter()
+                            .map(|c| c.as_u64().unwrap_or(0) as u32)
+                            .collect(),
+                        Some(_) => {
+                            return Err(format!(
+                                "layer \"{}\" is base64-encoded; set its format to CSV",
+                                layer.name
+                            ));
+                        }
+                        // Infinite maps keep their tiles in chunks
+                        None => {
+                            return Err(format!(
+                                "layer \"{}\" has no tile data (infinite maps aren't supported)",
+                                layer.name
+                            ));
+                        }
+                    };
+                    let elevation = layer
+                        .properties
+                        .iter()
+                        .find(|p| p.name == "elevation")
+                        .and_then(|p| p.value.as_i64())
+                        .unwrap_or(0) as i32;
+                    out.push(TileLayer {
+                        name: layer.name,
+                        width: layer.width,
+                        elevation,
+                        data,
+                    });
+                }
+                // Object and image layers have nothing to build
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    pub(super) fn parse(text: &str, path: &str) -> Result<TiledMap, String> {
+        let map: Map = serde_json::from_str(text).map_err(|e| e.to_string())?;
+        let mut layers = Vec::new();
+        flatten(map.layers, &mut layers)?;
+
+        let mut tilesets = Vec::new();
+        for ts in map.tilesets {
+            let tiles = match ts.source {
+                Some(source) => {
+                    let ts_path = sibling_path(path, &source);
+                    let text = scene_io::read_text(&ts_path)
+                        .ok_or_else(|| format!("cannot read tileset {ts_path}"))?;
+                    let file: TilesetFile = serde_json::from_str(&text)
+                        .map_err(|e| format!("tileset {ts_path}: {e}"))?;
+                    file.tiles
+                }
+                None => ts.tiles,
+            };
+            tilesets.push(Tileset {
+                first_gid: ts.firstgid,
+                tiles: tiles
+                    .iter()
+                    .map(|t| (t.id, tile_props(&t.properties)))
+                    .collect(),
+            });
+        }
+
+        Ok(TiledMap {
+            width: map.width,
+            height: map.height,
+            layers,
+            tilesets,
+        })
+    }
+}
+
+mod tmx {
+    //! The XML map format (`.tmx`; tilesets `.tsx`).
+
+    use roxmltree::{Document, Node};
+
+    use super::*;
+
+    fn attr<T: std::str::FromStr>(node: Node, name: &str) -> Option<T> {
+        node.attribute(name)?.parse().ok()
+    }
+
+    fn properties<'a>(node: Node<'a, '_>) -> impl Iterator<Item = (&'a str, &'a str)> {
+        node.children()
+            .filter(|n| n.has_tag_name("properties"))
+            .flat_map(|n| n.children().filter(|p| p.has_tag_name("property")))
+            .filter_map(|p| {
+                // Multi-line string properties keep their value as text
+                let value = p.attribute("value").or_else(|| p.text())?;
+                Some((p.attribute("name")?, value))
+            })
+    }
+
+    fn tile_props(tile: Node) -> TileProps {
+        let mut props = TileProps::default();
+        for (name, value) in properties(tile) {
+            match name {
+                "height" => props.height = value.parse().ok(),
+                "material" => props.material = Some(value.to_string()),
+                "color" => props.color = parse_color(value),
+                _ => {}
+            }
+        }
+        props
+    }
+
+    fn tiles(tileset: Node) -> HashMap<u32, TileProps> {
+        tileset
+            .children()
+            .filter(|n| n.has_tag_name("tile"))
+            .filter_map(|t| Some((attr(t, "id")?, tile_props(t))))
+            .collect()
+    }
+
+    fn layer_data(layer: Node, name: &str) -> Result<Vec<u32>, String> {
+        let data = layer
+            .children()
+            .find(|n| n.has_tag_name("data"))
+            .ok_or_else(|| format!("layer \"{name}\" has no data"))?;
+        match data.attribute("encoding") {
+            Some("csv") => data
+                .text()
+                .unwrap_or("")
+                .split(',')
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(|s| s.parse().map_err(|_| format!("bad tile ID {s:?}")))
+                .collect(),
+            // Deprecated per-tile elements
+            None if data.children().any(|n| n.has_tag_name("tile")) => Ok(data
+                .children()
+                .filter(|n| n.has_tag_name("tile"))
+                .map(|t| attr(t, "gid").unwrap_or(0))
+                .collect()),
+            None => Err(format!(
+                "layer \"{name}\" has no tile data (infinite maps aren't supported)"
+            )),
+            Some(_) => Err(format!(
+                "layer \"{name}\" is base64-encoded; set its format to CSV"
+            )),
+        }
+    }
+
+    fn flatten(parent: Node, out: &mut Vec<TileLayer>) -> Result<(), String> {
+        for node in parent.children() {
+            if node.has_tag_name("group") {
+                flatten(node, out)?;
+            } else if node.has_tag_name("layer") {
+                let name = node.attribute("name").unwrap_or("").to_string();
+                let elevation = properties(node)
+                    .find(|(n, _)| *n == "elevation")
+                    .and_then(|(_, v)| v.parse().ok())
+                    .unwrap_or(0);
+                out.push(TileLayer {
+                    data: layer_data(node, &name)?,
+                    width: attr(node, "width").unwrap_or(0),
+                    elevation,
+                    name,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    pub(super) fn parse(text: &str, path: &str) -> Result<TiledMap, String> {
+        let doc = Document::parse(text).map_err(|e| e.to_string())?;
+        let map = doc.root_element();
+        if !map.has_tag_name("map") {
+            return Err("not a Tiled map".into());
+        }
+        let mut layers = Vec::new();
+        flatten(map, &mut layers)?;
+
+        let mut tilesets = Vec::new();
+        for ts in map.children().filter(|n| n.has_tag_name("tileset")) {
+            let first_gid = attr(ts, "firstgid").ok_or("tileset without firstgid")?;
+            let tiles = match ts.attribute("source") {
+                Some(source) => {
+                    let ts_path = sibling_path(path, source);
+                    let text = scene_io::read_text(&ts_path)
+                        .ok_or_else(|| format!("cannot read tileset {ts_path}"))?;
+                    let doc =
+                        Document::parse(&text).map_err(|e| format!("tileset {ts_path}: {e}"))?;
+                    tiles(doc.root_element())
+                }
+                None => tiles(ts),
+            };
+            tilesets.push(Tileset { first_gid, tiles });
+        }
+
+        Ok(TiledMap {
+            width: attr(map, "width").ok_or("map without width")?,
+            height: attr(map, "height").ok_or("map without height")?,
+            layers,
+            tilesets,
+        })
+    }
+}
+
+/// Material for a tile: its `material` or `color` property, else a palette entry by ID.
+fn tile_material(
+    props: &TileProps,
+    local_id: u32,
+    cache: &mut AssetCache,
+    materials: &mut Assets<StandardMaterial>,
+) -> Handle<StandardMaterial> {
+    let palette = props
+        .material
+        .as_deref()
+        .and_then(|m| {
+            TILE_PALETTE
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(m))
+        })
+        .unwrap_or(&TILE_PALETTE[local_id as usize % TILE_PALETTE.len()]);
+    let [r, g, b] = palette.1;
+    let base_color = match (&props.material, props.color) {
+        (None, Some(color)) => color,
+        _ => Color::srgb(r, g, b),
+    };
+    // Same properties as build mode's tiles, so imported and hand-placed tiles share materials
+    cache.material(
+        materials,
+        PlainMaterial {
+            base_color,
+            metallic: 0.0,
+            roughness: 0.8,
+            emissive: LinearRgba::BLACK,
+        },
+    )
+}
+
+fn import_tiled(
+    mut commands: Commands,
+    mut events: EventReader<ImportTiledEvent>,
+    mut state: ResMut<TiledImportState>,
+    mut cache: ResMut<AssetCache>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    for ev in events.read() {
+        let map = match TiledMap::load(&ev.path) {
+            Ok(map) => map,
+            Err(e) => {
+                eprintln!("Tiled import: {e}");
+                state.status = Some(format!("Import failed: {e}"));
+                continue;
+            }
+        };
+
+        let mesh = cache.mesh(&mut meshes, SpawnKind::Cuboid);
+        let mut tile_materials: HashMap<u32, Handle<StandardMaterial>> = HashMap::new();
+        // Centered on the origin, with tile centers on the build grid
+        let offset = Vec2::new((map.width / 2) as f32, (map.height / 2) as f32);
+        let mut count = 0;
+        let mut unknown = 0;
+        for layer in &map.layers {
+            let width = if layer.width > 0 {
+                layer.width
+            } else {
+                map.width
+            };
+            let unknown_before = unknown;
+            for (i, &raw) in layer.data.iter().enumerate() {
+                let gid = raw & GID_MASK;
+                if gid == 0 {
+                    continue;
+                }
+                let Some((tileset, local_id)) = map.tile(gid) else {
+                    unknown += 1;
+                    continue;
+                };
+                let props = tileset.tiles.get(&local_id).cloned().unwrap_or_default();
+                let material = tile_materials
+                    .entry(gid)
+                    .or_insert_with(|| tile_material(&props, local_id, &mut cache, &mut materials))
+                    .clone();
+
+                let (col, row) = (i as u32 % width, i as u32 / width);
+                let x = col as f32 - offset.x + 0.5;
+                let z = row as f32 - offset.y + 0.5;
+                for level in 0..props.height.unwrap_or(1) {
+                    let y = (layer.elevation + level as i32) as f32 + 0.5;
+                    commands.spawn((
+                        Mesh3d(mesh.clone()),
+                        MeshMaterial3d(material.clone()),
+                        Transform::from_xyz(x, y, z),
+                        Editable,
+                        EditableMesh {
+                            kind: SpawnKind::Cuboid,
+                            collider: Some(true),
+                        },
+                        Name::new("Tile"),
+                        InstancedTile,
+                    ));
+                    count += 1;
+                }
+            }
+            if unknown > unknown_before {
+                eprintln!(
+                    "Tiled import: layer \"{}\": {} cell(s) with tiles from no tileset",
+                    layer.name,
+                    unknown - unknown_before
+                );
+            }
+        }
+        eprintln!("Tiled import: {count} tiles from {}", ev.path);
+        let mut status = format!("Imported {count} tiles from {} layer(s)", map.layers.len());
+        if unknown > 0 {
+            status += &format!("; skipped {unknown} cell(s) with unknown tiles");
+        }
+        state.status = Some(status);
+    }
+}
+
+fn tiled_window(
+    mut ctxs: EguiContexts,
+    edit_state: Res<SceneEditState>,
+    mut state: ResMut<TiledImportState>,
+    mut ev_import: EventWriter<ImportTiledEvent>,
+) {
+    if !edit_state.open {
+        return;
+    }
+    let Ok(ctx) = ctxs.ctx_mut() else {
+        return;
+    };
+    egui::Window::new("Tiled Import")
+        .default_open(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Map:");
+                ui.add(
+                    egui::TextEdit::singleline(&mut state.path)
+                        .hint_text("level.tmx")
+                        .desired_width(200.0),
+                );
+                let can_import = !state.path.trim().is_empty();
+                if ui
+                    .add_enabled(can_import, egui::Button::new("Import"))
+                    .clicked()
+                {
+                    ev_import.write(ImportTiledEvent {
+                        path: state.path.trim().to_string(),
+                    });
+                }
+            });
+            ui.small("Tile properties: height (int), material (palette name) or color.");
+            ui.small("Layer property: elevation (int).");
+            if let Some(status) = &state.status {
+                ui.label(status);
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const JSON_MAP: &str = r##"{
+        "width": 2, "height": 2,
+        "layers": [
+            {"type": "tilelayer", "name": "ground", "width": 2, "data": [1, 0, 2, 1]},
+            {"type": "group", "layers": [
+                {"type": "tilelayer", "name": "walls", "width": 2, "data": [0, 2, 0, 0],
+                 "properties": [{"name": "elevation", "type": "int", "value": 1}]}
+            ]},
+            {"type": "objectgroup", "name": "spawns"}
+        ],
+        "tilesets": [{"firstgid": 1, "tiles": [
+            {"id": 1, "properties": [
+                {"name": "height", "type": "int", "value": 3},
+                {"name": "color", "type": "color", "value": "#ff00ff00"}
+            ]}
+        ]}]
+    }"##;
+
+    const TMX_MAP: &str = r##"<?xml version="1.0" encoding="UTF-8"?>
+        <map width="3" height="1">
+            <tileset firstgid="1">
+                <tile id="0">
+                    <properties><property name="material" value="Brick"/></properties>
+                </tile>
+            </tileset>
+            <tileset firstgid="5"/>
+            <layer name="ground" width="3" height="1">
+                <properties><property name="elevation" type="int" value="-2"/></properties>
+                <data encoding="csv">
+                    1,0,
+                    6
+                </data>
+            </layer>
+        </map>"##;
+
+    #[test]
+    fn colors_with_and_without_alpha() {
+        let rgb = parse_color("#ff8000").unwrap().to_srgba();
+        assert_eq!(rgb.to_u8_array(), [255, 128, 0, 255]);
+        let argb = parse_color("#80ff0000").unwrap().to_srgba();
+        assert_eq!(argb.to_u8_array(), [255, 0, 0, 128]);
+        assert!(parse_color("#fff").is_none());
+        assert!(parse_color("#gg0000").is_none());
+    }
+
+    #[test]
+    fn tilesets_resolve_next_to_the_map() {
+        assert_eq!(
+            sibling_path("maps/level.tmx", "tiles.tsx"),
+            "maps/tiles.tsx"
+        );
+        assert_eq!(
+            sibling_path(r"maps\level.tmx", "tiles.tsx"),
+            "maps/tiles.tsx"
+        );
+        assert_eq!(sibling_path("level.tmx", "tiles.tsx"), "tiles.tsx");
+    }
+
+    #[test]
+    fn json_layers_and_properties() {
+        let map = TiledMap::parse(JSON_MAP, "level.tmj").unwrap();
+        assert_eq!((map.width, map.height), (2, 2));
+        // The group's layer is flattened, the object layer skipped
+        let names: Vec<_> = map.layers.iter().map(|l| l.name.as_str()).collect();
+        assert_eq!(names, ["ground", "walls"]);
+        assert_eq!(map.layers[0].data, [1, 0, 2, 1]);
+        assert_eq!(map.layers[1].elevation, 1);
+
+        let (tileset, local_id) = map.tile(2).unwrap();
+        assert_eq!(local_id, 1);
+        let props = &tileset.tiles[&local_id];
+        assert_eq!(props.height, Some(3));
+        assert_eq!(
+            props.color.unwrap().to_srgba().to_u8_array(),
+            [0, 255, 0, 255]
+        );
+    }
+
+    #[test]
+    fn tmx_csv_layers_and_properties() {
+        let map = TiledMap::parse(TMX_MAP, "level.tmx").unwrap();
+        assert_eq!((map.width, map.height), (3, 1));
+        let layer = &map.layers[0];
+        assert_eq!((layer.name.as_str(), layer.width), ("ground", 3));
+        assert_eq!(layer.data, [1, 0, 6]);
+        assert_eq!(layer.elevation, -2);
+
+        let (tileset, local_id) = map.tile(1).unwrap();
+        let material = tileset.tiles[&local_id].material.as_deref();
+        assert_eq!(material, Some("Brick"));
+        // The second tileset takes over from its first ID
+        let (tileset, local_id) = map.tile(6).unwrap();
+        assert_eq!((tileset.first_gid, local_id), (5, 1));
+    }
+
+    #[test]
+    fn flip_flags_are_masked_off() {
+        let map = TiledMap::parse(JSON_MAP, "level.tmj").unwrap();
+        let flipped = 0x8000_0000 | 0x4000_0000 | 2;
+        let (tileset, local_id) = map.tile(flipped & GID_MASK).unwrap();
+        assert_eq!((tileset.first_gid, local_id), (1, 1));
+    }
+
+    #[test]
+    fn zero_width_maps_are_rejected() {
+        let json = r#"{"width": 0, "height": 1, "layers": [
+            {"type": "tilelayer", "data": [1]}
+        ]}"#;
+        assert!(TiledMap::parse(json, "level.tmj").is_err());
+        let tmx = r#"<map width="0" height="1">
+            <layer name="a"><data encoding="csv">1</data></layer>
+        </map>"#;
+        assert!(TiledMap::parse(tmx, "level.tmx").is_err());
+    }
+
+    #[test]
+    fn garbage_is_an_error() {
+        assert!(TiledMap::parse("{", "level.tmj").is_err());
+        assert!(TiledMap::parse("<tileset/>", "level.tmx").is_err());
+    }
+}
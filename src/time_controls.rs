@@ -0,0 +1,53 @@
+//! Pause, slow motion and frame stepping for everything that runs on game time
+//! (`Time<Virtual>`): physics, the player, scripts, tweens, the timeline, light animations and
+//! wind sway. The camera and the editor's own feedback run on real time and keep going.
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::RapierConfiguration;
+
+/// Game time one press of the step button advances.
+pub const STEP_SECS: f32 = 1.0 / 60.0;
+/// Slowest and fastest time scale offered.
+pub const MIN_SPEED: f32 = 0.1;
+pub const MAX_SPEED: f32 = 4.0;
+
+/// Requests from the time bar; pause and speed live in `Time<Virtual>` itself.
+#[derive(Resource, Default)]
+pub struct TimeControls {
+    /// Advance one step on the next frame (only while paused)
+    pub step: bool,
+}
+
+pub struct TimeControlsPlugin;
+impl Plugin for TimeControlsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TimeControls>()
+            // After `First` has advanced the clocks, before anything reads them
+            .add_systems(PreUpdate, apply_time_controls);
+    }
+}
+
+fn apply_time_controls(
+    mut controls: ResMut<TimeControls>,
+    mut virt: ResMut<Time<Virtual>>,
+    mut time: ResMut<Time>,
+    mut q_physics: Query<&mut RapierConfiguration>,
+) {
+    let step = std::mem::take(&mut controls.step) && virt.is_paused();
+    if step {
+        // `Time` is this frame's copy of the virtual clock, so both move
+        let dt = Duration::from_secs_f32(STEP_SECS);
+        virt.advance_by(dt);
+        time.advance_by(dt);
+    }
+
+    // A paused frame has no time to integrate; keep the physics world still instead
+    let active = !virt.is_paused() || step;
+    for mut config in &mut q_physics {
+        if config.physics_pipeline_active != active {
+            config.physics_pipeline_active = active;
+        }
+    }
+}
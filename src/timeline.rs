@@ -0,0 +1,850 @@
+//! Keyframe animation of object transforms and material colors, plus fog and gradient tint.
+//!
+//! Tracks name their object (so they survive a save and load) and are stored in the scene file.
+//! The timeline plays from the **Timeline** window while editing, and from the start whenever
+//! Play mode is entered.
+
+use std::collections::HashMap;
+
+use bevy::diagnostic::FrameCount;
+use bevy::pbr::{DistanceFog, FogFalloff};
+use bevy::prelude::*;
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+use serde::{Deserialize, Serialize};
+
+use crate::SceneEditState;
+use crate::accessibility::Described;
+use crate::asset_cache::AssetCache;
+use crate::editor_mode::EditorMode;
+use crate::inspector::{Editable, Selected};
+use crate::post::compare::CompareCamera;
+#[cfg(feature = "gradient")]
+use crate::post::gradient_tint::GradientTintSettings;
+
+/// Keys closer together than this are the same key
+const KEY_EPSILON: f32 = 1.0 / 120.0;
+const LABEL_WIDTH: f32 = 170.0;
+const ROW_HEIGHT: f32 = 18.0;
+const KEY_RADIUS: f32 = 5.0;
+
+/// Shape of the motion from one key to the next.
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Easing {
+    #[default]
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+    /// Hold the value, then jump at the next key
+    Step,
+}
+
+impl Easing {
+    pub const ALL: [Easing; 5] = [
+        Easing::Linear,
+        Easing::EaseIn,
+        Easing::EaseOut,
+        Easing::EaseInOut,
+        Easing::Step,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Easing::Linear => "Linear",
+            Easing::EaseIn => "Ease in",
+            Easing::EaseOut => "Ease out",
+            Easing::EaseInOut => "Ease in-out",
+            Easing::Step => "Step",
+        }
+    }
+
+    /// Eased progress for `t` in 0..1
+    pub fn ease(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t,
+            Easing::EaseOut => 1.0 - (1.0 - t) * (1.0 - t),
+            Easing::EaseInOut => t * t * (3.0 - 2.0 * t),
+            Easing::Step => {
+                if t < 1.0 {
+                    0.0
+                } else {
+                    1.0
+                }
+            }
+        }
+    }
+}
+
+/// What a track animates.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum TrackTarget {
+    /// Position, rotation and scale of the named object
+    Transform(String),
+    /// Base color of the named object's material
+    Color(String),
+    FogDensity,
+    FogColor,
+    TintStrength,
+    TintTopRight,
+    TintBottomLeft,
+}
+
+impl TrackTarget {
+    pub fn label(&self) -> String {
+        match self {
+            TrackTarget::Transform(name) => format!("{name}: transform"),
+            TrackTarget::Color(name) => format!("{name}: color"),
+            TrackTarget::FogDensity => "Fog density".into(),
+            TrackTarget::FogColor => "Fog color".into(),
+            TrackTarget::TintStrength => "Tint strength".into(),
+            TrackTarget::TintTopRight => "Tint top-right".into(),
+            TrackTarget::TintBottomLeft => "Tint bottom-left".into(),
+        }
+    }
+
+    /// Scene-wide targets, as far as the enabled features go
+    fn globals() -> Vec<TrackTarget> {
+        vec![
+            TrackTarget::FogDensity,
+            TrackTarget::FogColor,
+            #[cfg(feature = "gradient")]
+            TrackTarget::TintStrength,
+            #[cfg(feature = "gradient")]
+            TrackTarget::TintTopRight,
+            #[cfg(feature = "gradient")]
+            TrackTarget::TintBottomLeft,
+        ]
+    }
+}
+
+/// A keyed value; colors are sRGBA.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum KeyValue {
+    Transform {
+        position: [f32; 3],
+        rotation_euler_deg: [f32; 3],
+        scale: [f32; 3],
+    },
+    Color([f32; 4]),
+    Scalar(f32),
+}
+
+impl KeyValue {
+    pub fn from_transform(tf: &Transform) -> Self {
+        let (rx, ry, rz) = tf.rotation.to_euler(EulerRot::XYZ);
+        KeyValue::Transform {
+            position: tf.translation.to_array(),
+            rotation_euler_deg: [rx.to_degrees(), ry.to_degrees(), rz.to_degrees()],
+            scale: tf.scale.to_array(),
+        }
+    }
+
+    fn rotation(euler_deg: [f32; 3]) -> Quat {
+        let [x, y, z] = euler_deg.map(f32::to_radians);
+        Quat::from_euler(EulerRot::XYZ, x, y, z)
+    }
+
+    fn to_transform(self) -> Option<Transform> {
+        let KeyValue::Transform {
+            position,
+            rotation_euler_deg,
+            scale,
+        } = self
+        else {
+            return None;
+        };
+        Some(Transform {
+            translation: Vec3::from_array(position),
+            rotation: Self::rotation(rotation_euler_deg),
+            scale: Vec3::from_array(scale),
+        })
+    }
+
+    /// `self` at `t = 0`, `to` at `t = 1`; rotations take the short way round.
+    fn lerp(self, to: KeyValue, t: f32) -> KeyValue {
+        match (self, to) {
+            (KeyValue::Transform { .. }, KeyValue::Transform { .. }) => {
+                let (Some(a), Some(b)) = (self.to_transform(), to.to_transform()) else {
+                    return self;
+                };
+                KeyValue::from_transform(&Transform {
+                    translation: a.translation.lerp(b.translation, t),
+                    rotation: a.rotation.slerp(b.rotation, t),
+                    scale: a.scale.lerp(b.scale, t),
+                })
+            }
+            (KeyValue::Color(a), KeyValue::Color(b)) => {
+                KeyValue::Color(Vec4::from_array(a).lerp(Vec4::from_array(b), t).to_array())
+            }
+            (KeyValue::Scalar(a), KeyValue::Scalar(b)) => KeyValue::Scalar(a + (b - a) * t),
+            // Mismatched keys (hand-edited files): hold
+            _ => self,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Keyframe {
+    /// Seconds from the start of the timeline
+    pub time: f32,
+    pub value: KeyValue,
+    /// Easing towards the next key
+    #[serde(default)]
+    pub easing: Easing,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Track {
+    pub target: TrackTarget,
+    /// Sorted by time
+    pub keys: Vec<Keyframe>,
+}
+
+impl Track {
+    /// Value at `time`; before the first key and after the last the nearest key holds.
+    pub fn sample(&self, time: f32) -> Option<KeyValue> {
+        let next = self.keys.iter().position(|k| k.time > time);
+        match next {
+            None => self.keys.last().map(|k| k.value),
+            Some(0) => self.keys.first().map(|k| k.value),
+            Some(i) => {
+                let (a, b) = (&self.keys[i - 1], &self.keys[i]);
+                let span = (b.time - a.time).max(f32::EPSILON);
+                Some(a.value.lerp(b.value, a.easing.ease((time - a.time) / span)))
+            }
+        }
+    }
+
+    /// Move key `i` to `time`, keeping the keys sorted; returns its new index.
+    fn move_key(&mut self, i: usize, time: f32) -> usize {
+        let mut key = self.keys.remove(i);
+        key.time = time;
+        let at = self.keys.partition_point(|k| k.time <= time);
+        self.keys.insert(at, key);
+        at
+    }
+}
+
+/// The scene's animation, saved with the scene.
+#[derive(Resource, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Timeline {
+    /// Seconds
+    pub length: f32,
+    pub looping: bool,
+    pub tracks: Vec<Track>,
+}
+
+impl Default for Timeline {
+    fn default() -> Self {
+        Self {
+            length: 10.0,
+            looping: true,
+            tracks: Vec::new(),
+        }
+    }
+}
+
+impl Timeline {
+    pub fn is_empty(&self) -> bool {
+        self.tracks.iter().all(|t| t.keys.is_empty())
+    }
+
+    /// Key `value` at `time`, replacing a key already there; the track is created if needed.
+    pub fn set_key(&mut self, target: TrackTarget, time: f32, value: KeyValue) {
+        let track = match self.tracks.iter().position(|t| t.target == target) {
+            Some(i) => &mut self.tracks[i],
+            None => {
+                self.tracks.push(Track {
+                    target,
+                    keys: Vec::new(),
+                });
+                self.tracks.last_mut().expect("just pushed")
+            }
+        };
+        if let Some(key) = track
+            .keys
+            .iter_mut()
+            .find(|k| (k.time - time).abs() < KEY_EPSILON)
+        {
+            key.value = value;
+            return;
+        }
+        let at = track.keys.partition_point(|k| k.time <= time);
+        track.keys.insert(
+            at,
+            Keyframe {
+                time,
+                value,
+                easing: Easing::default(),
+            },
+        );
+    }
+}
+
+/// Playhead, shared by the Timeline window and Play mode.
+#[derive(Resource, Default)]
+pub struct TimelinePlayback {
+    pub playing: bool,
+    /// Seconds
+    pub time: f32,
+    /// Frame the scene was last posed at the playhead
+    pub posed_at: Option<u32>,
+}
+
+impl TimelinePlayback {
+    /// Whether the timeline moves objects this frame (playing, or posing after a scrub), which
+    /// isn't an edit.
+    pub fn posing(&self, frame: &FrameCount) -> bool {
+        self.playing || self.posed_at == Some(frame.0)
+    }
+}
+
+/// Which key the Timeline window has selected, and what it's dragging.
+#[derive(Default)]
+struct TimelineUi {
+    selected: Option<(usize, usize)>,
+    dragging_key: bool,
+    global: usize,
+}
+
+pub struct TimelinePlugin;
+impl Plugin for TimelinePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SceneEditState>()
+            .init_resource::<EditorMode>()
+            .init_resource::<Timeline>()
+            .init_resource::<TimelinePlayback>()
+            .add_systems(
+                Update,
+                (play_in_play_mode, advance_playhead, apply_timeline).chain(),
+            )
+            .add_systems(EguiPrimaryContextPass, timeline_window);
+    }
+}
+
+/// Entering Play mode starts the timeline from zero; leaving it stops.
+fn play_in_play_mode(
+    mode: Res<EditorMode>,
+    timeline: Res<Timeline>,
+    mut playback: ResMut<TimelinePlayback>,
+    mut was_play: Local<bool>,
+) {
+    if !mode.is_changed() {
+        return;
+    }
+    let play = *mode == EditorMode::Play;
+    if play && !*was_play && !timeline.is_empty() {
+        *playback = TimelinePlayback {
+            playing: true,
+            time: 0.0,
+            ..default()
+        };
+    } else if !play && *was_play {
+        playback.playing = false;
+    }
+    *was_play = play;
+}
+
+fn advance_playhead(
+    time: Res<Time>,
+    timeline: Res<Timeline>,
+    mut playback: ResMut<TimelinePlayback>,
+) {
+    if !playback.playing {
+        return;
+    }
+    let length = timeline.length.max(KEY_EPSILON);
+    playback.time += time.delta_secs();
+    if playback.time >= length {
+        if timeline.looping {
+            playback.time %= length;
+        } else {
+            playback.time = length;
+            playback.playing = false;
+        }
+    }
+}
+
+/// Pose the scene at the playhead. Only while playing or after the playhead or keys change, so
+/// the inspector can still move animated objects while the timeline is paused.
+fn apply_timeline(
+    mut commands: Commands,
+    timeline: Res<Timeline>,
+    mut playback: ResMut<TimelinePlayback>,
+    frame: Res<FrameCount>,
+    cache: Res<AssetCache>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut q_objects: Query<
+        (
+            Entity,
+            &Name,
+            &mut Transform,
+            Option<&MeshMaterial3d<StandardMaterial>>,
+        ),
+        With<Editable>,
+    >,
+    mut q_fog: Query<&mut DistanceFog, Without<CompareCamera>>,
+    #[cfg(feature = "gradient")] mut q_tint: Query<
+        &mut GradientTintSettings,
+        Without<CompareCamera>,
+    >,
+) {
+    if !playback.is_changed() && !timeline.is_changed() {
+        return;
+    }
+    playback.bypass_change_detection().posed_at = Some(frame.0);
+    let by_name: HashMap<String, Entity> = q_objects
+        .iter()
+        .map(|(e, name, ..)| (name.as_str().to_string(), e))
+        .collect();
+
+    for track in &timeline.tracks {
+        let Some(value) = track.sample(playback.time) else {
+            continue;
+        };
+        match (&track.target, value) {
+            (TrackTarget::Transform(name), _) => {
+                let Some(tf) = value.to_transform() else {
+                    continue;
+                };
+                if let Some(&e) = by_name.get(name)
+                    && let Ok((_, _, mut current, _)) = q_objects.get_mut(e)
+                {
+                    current.set_if_neq(tf);
+                }
+            }
+            (TrackTarget::Color(name), KeyValue::Color([r, g, b, a])) => {
+                let Some(&e) = by_name.get(name) else {
+                    continue;
+                };
+                let Ok((_, _, _, Some(mat))) = q_objects.get(e) else {
+                    continue;
+                };
+                // Cached materials are shared with other objects: edit a private copy
+                let Some(handle) = cache.own_material(&mut commands, &mut materials, e, &mat.0)
+                else {
+                    continue;
+                };
+                if let Some(m) = materials.get_mut(&handle) {
+                    m.base_color = Color::srgba(r, g, b, a);
+                }
+            }
+            (TrackTarget::FogDensity, KeyValue::Scalar(density)) => {
+                for mut fog in &mut q_fog {
+                    fog.falloff = FogFalloff::Exponential {
+                        density: density.max(0.0),
+                    };
+                }
+            }
+            (TrackTarget::FogColor, KeyValue::Color([r, g, b, a])) => {
+                for mut fog in &mut q_fog {
+                    fog.color = Color::srgba(r, g, b, a);
+                }
+            }
+            #[cfg(feature = "gradient")]
+            (TrackTarget::TintStrength, KeyValue::Scalar(strength)) => {
+                for mut gt in &mut q_tint {
+                    gt.strength = strength.clamp(0.0, 1.0);
+                }
+            }
+            #[cfg(feature = "gradient")]
+            (TrackTarget::TintTopRight, KeyValue::Color(c)) => {
+                for mut gt in &mut q_tint {
+                    gt.color_top_right = Vec4::from_array(c);
+                }
+            }
+            #[cfg(feature = "gradient")]
+            (TrackTarget::TintBottomLeft, KeyValue::Color(c)) => {
+                for mut gt in &mut q_tint {
+                    gt.color_bottom_left = Vec4::from_array(c);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Current value of a scene-wide target, for keying it.
+fn global_value(
+    target: &TrackTarget,
+    fog: Option<&DistanceFog>,
+    #[cfg(feature = "gradient")] tint: Option<&GradientTintSettings>,
+) -> Option<KeyValue> {
+    match target {
+        TrackTarget::FogDensity => match fog?.falloff {
+            FogFalloff::Exponential { density } => Some(KeyValue::Scalar(density)),
+            _ => None,
+        },
+        TrackTarget::FogColor => Some(KeyValue::Color(fog?.color.to_srgba().to_f32_array())),
+        #[cfg(feature = "gradient")]
+        TrackTarget::TintStrength => Some(KeyValue::Scalar(tint?.strength)),
+        #[cfg(feature = "gradient")]
+        TrackTarget::TintTopRight => Some(KeyValue::Color(tint?.color_top_right.to_array())),
+        #[cfg(feature = "gradient")]
+        TrackTarget::TintBottomLeft => Some(KeyValue::Color(tint?.color_bottom_left.to_array())),
+        _ => None,
+    }
+}
+
+fn timeline_window(
+    mut ctxs: EguiContexts,
+    edit_state: Res<SceneEditState>,
+    mut timeline: ResMut<Timeline>,
+    mut playback: ResMut<TimelinePlayback>,
+    mut ui_state: Local<TimelineUi>,
+    materials: Res<Assets<StandardMaterial>>,
+    q_selected: Query<
+        (
+            Option<&Name>,
+            &Transform,
+            Option<&MeshMaterial3d<StandardMaterial>>,
+        ),
+        (With<Selected>, With<Editable>),
+    >,
+    q_fog: Query<&DistanceFog, Without<CompareCamera>>,
+    #[cfg(feature = "gradient")] q_tint: Query<&GradientTintSettings, Without<CompareCamera>>,
+) {
+    if !edit_state.open {
+        return;
+    }
+    // Keys may have gone with a scene load
+    if let Some((t, k)) = ui_state.selected
+        && timeline
+            .tracks
+            .get(t)
+            .is_none_or(|track| k >= track.keys.len())
+    {
+        ui_state.selected = None;
+    }
+
+    let Ok(ctx) = ctxs.ctx_mut() else {
+        return;
+    };
+    egui::Window::new("Timeline")
+        .default_open(false)
+        .default_width(640.0)
+        .show(ctx, |ui| {
+            // Transport. Resources are only written on edits: a change re-poses the scene
+            ui.horizontal(|ui| {
+                if ui.button("⏮").described("Back to start").clicked() {
+                    playback.time = 0.0;
+                }
+                let (label, name) = if playback.playing {
+                    ("⏸", "Pause")
+                } else {
+                    ("▶", "Play")
+                };
+                if ui.button(label).described(name).clicked() {
+                    // Replay a finished one-shot timeline from the start
+                    if !playback.playing && playback.time >= timeline.length {
+                        playback.time = 0.0;
+                    }
+                    playback.playing = !playback.playing;
+                }
+                let mut time = playback.time;
+                let length = timeline.length;
+                if ui
+                    .add(
+                        egui::DragValue::new(&mut time)
+                            .speed(0.02)
+                            .range(0.0..=length)
+                            .suffix(" s"),
+                    )
+                    .changed()
+                {
+                    playback.time = time;
+                }
+                ui.separator();
+                let mut length = timeline.length;
+                ui.label("Length");
+                if ui
+                    .add(
+                        egui::DragValue::new(&mut length)
+                            .speed(0.1)
+                            .range(0.5..=600.0)
+                            .suffix(" s"),
+                    )
+                    .changed()
+                {
+                    timeline.length = length;
+                }
+                let mut looping = timeline.looping;
+                if ui.checkbox(&mut looping, "Loop").changed() {
+                    timeline.looping = looping;
+                }
+            });
+
+            match tracks_view(ui, &timeline, playback.time, &mut ui_state) {
+                Some(TracksEdit::Scrub(time)) => playback.time = time,
+                Some(TracksEdit::MoveKey { track, key, time }) => {
+                    let key = timeline.tracks[track].move_key(key, time);
+                    ui_state.selected = Some((track, key));
+                }
+                None => {}
+            }
+
+            // Keying at the playhead
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.label("Key selection:");
+                let selected = q_selected.single().ok();
+                let name = selected.and_then(|(name, ..)| name).map(|n| n.to_string());
+                let can_key = name.is_some();
+                if ui
+                    .add_enabled(can_key, egui::Button::new("Transform"))
+                    .clicked()
+                    && let (Some(name), Some((_, tf, _))) = (name.clone(), selected)
+                {
+                    let time = playback.time;
+                    timeline.set_key(
+                        TrackTarget::Transform(name),
+                        time,
+                        KeyValue::from_transform(tf),
+                    );
+                }
+                let color = selected
+                    .and_then(|(_, _, mat)| mat)
+                    .and_then(|mat| materials.get(&mat.0))
+                    .map(|m| m.base_color.to_srgba().to_f32_array());
+                if ui
+                    .add_enabled(can_key && color.is_some(), egui::Button::new("Color"))
+                    .clicked()
+                    && let (Some(name), Some(color)) = (name, color)
+                {
+                    let time = playback.time;
+                    timeline.set_key(TrackTarget::Color(name), time, KeyValue::Color(color));
+                }
+                if selected.is_some() && !can_key {
+                    ui.weak("Name the object to animate it");
+                }
+            });
+            ui.horizontal(|ui| {
+                let globals = TrackTarget::globals();
+                ui_state.global = ui_state.global.min(globals.len() - 1);
+                egui::ComboBox::from_id_salt("timeline_global")
+                    .selected_text(globals[ui_state.global].label())
+                    .show_ui(ui, |ui| {
+                        for (i, target) in globals.iter().enumerate() {
+                            ui.selectable_value(&mut ui_state.global, i, target.label());
+                        }
+                    });
+                let target = &globals[ui_state.global];
+                let value = global_value(
+                    target,
+                    q_fog.iter().next(),
+                    #[cfg(feature = "gradient")]
+                    q_tint.iter().next(),
+                );
+                if ui
+                    .add_enabled(value.is_some(), egui::Button::new("Key"))
+                    .on_disabled_hover_text("Turn fog on first")
+                    .clicked()
+                    && let Some(value) = value
+                {
+                    let time = playback.time;
+                    timeline.set_key(target.clone(), time, value);
+                }
+            });
+
+            // Selected key
+            let Some((t, k)) = ui_state.selected else {
+                return;
+            };
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.label(timeline.tracks[t].target.label());
+                let key = &timeline.tracks[t].keys[k];
+                let (mut time, mut easing) = (key.time, key.easing);
+                let length = timeline.length;
+                if ui
+                    .add(
+                        egui::DragValue::new(&mut time)
+                            .speed(0.02)
+                            .range(0.0..=length)
+                            .suffix(" s"),
+                    )
+                    .changed()
+                {
+                    let k = timeline.tracks[t].move_key(k, time);
+                    ui_state.selected = Some((t, k));
+                }
+                egui::ComboBox::from_id_salt("timeline_easing")
+                    .selected_text(easing.label())
+                    .show_ui(ui, |ui| {
+                        for e in Easing::ALL {
+                            ui.selectable_value(&mut easing, e, e.label());
+                        }
+                    });
+                if let Some((t, k)) = ui_state.selected
+                    && timeline.tracks[t].keys[k].easing != easing
+                {
+                    timeline.tracks[t].keys[k].easing = easing;
+                }
+                if ui.button("Delete key").clicked() {
+                    let (t, k) = ui_state.selected.take().expect("a key is selected");
+                    timeline.tracks[t].keys.remove(k);
+                    if timeline.tracks[t].keys.is_empty() {
+                        timeline.tracks.remove(t);
+                    }
+                } else if ui.button("Delete track").clicked() {
+                    ui_state.selected = None;
+                    timeline.tracks.remove(t);
+                }
+            });
+        });
+}
+
+/// An edit made in the tracks view, applied by the caller.
+enum TracksEdit {
+    Scrub(f32),
+    MoveKey { track: usize, key: usize, time: f32 },
+}
+
+/// Ruler plus one row of key diamonds per track. Drag on the ruler (or empty space) to scrub,
+/// drag a key to retime it.
+fn tracks_view(
+    ui: &mut egui::Ui,
+    timeline: &Timeline,
+    playhead: f32,
+    ui_state: &mut TimelineUi,
+) -> Option<TracksEdit> {
+    let rows = timeline.tracks.len() + 1;
+    let width = ui.available_width().max(LABEL_WIDTH + 120.0);
+    let (rect, response) = ui.allocate_exact_size(
+        egui::vec2(width, ROW_HEIGHT * rows as f32),
+        egui::Sense::click_and_drag(),
+    );
+    let lane = egui::Rect::from_min_max(egui::pos2(rect.min.x + LABEL_WIDTH, rect.min.y), rect.max);
+    let length = timeline.length.max(KEY_EPSILON);
+    let x_of = |time: f32| lane.min.x + time / length * lane.width();
+    let time_of = |x: f32| ((x - lane.min.x) / lane.width() * length).clamp(0.0, length);
+    let row_y = |row: usize| rect.min.y + ROW_HEIGHT * (row as f32 + 0.5);
+
+    // Interaction
+    let hit_key = |p: egui::Pos2| -> Option<(usize, usize)> {
+        timeline.tracks.iter().enumerate().find_map(|(t, track)| {
+            track
+                .keys
+                .iter()
+                .position(|key| {
+                    (x_of(key.time) - p.x).abs() <= KEY_RADIUS + 1.0
+                        && (row_y(t + 1) - p.y).abs() <= ROW_HEIGHT * 0.5
+                })
+                .map(|k| (t, k))
+        })
+    };
+    let mut edit = None;
+    if let Some(p) = response.interact_pointer_pos() {
+        if response.drag_started() || response.clicked() {
+            // Drags start a few pixels away from where the button went down
+            let origin = ui.input(|i| i.pointer.press_origin()).unwrap_or(p);
+            let hit = hit_key(origin);
+            ui_state.dragging_key = hit.is_some() && response.drag_started();
+            if hit.is_some() {
+                ui_state.selected = hit;
+            } else if origin.x >= lane.min.x {
+                edit = Some(TracksEdit::Scrub(time_of(p.x)));
+            }
+        } else if response.dragged() {
+            match ui_state.selected {
+                Some((track, key)) if ui_state.dragging_key => {
+                    edit = Some(TracksEdit::MoveKey {
+                        track,
+                        key,
+                        time: time_of(p.x),
+                    });
+                }
+                _ if p.x >= lane.min.x => edit = Some(TracksEdit::Scrub(time_of(p.x))),
+                _ => {}
+            }
+        }
+    }
+    if response.drag_stopped() {
+        ui_state.dragging_key = false;
+    }
+
+    // Drawing
+    let painter = ui.painter_at(rect);
+    let visuals = ui.visuals();
+    let text_color = visuals.text_color();
+    let weak = visuals.weak_text_color();
+    let font = egui::FontId::proportional(11.0);
+    painter.rect_filled(lane, 2.0, visuals.extreme_bg_color);
+    for row in (2..rows).step_by(2) {
+        let y = rect.min.y + ROW_HEIGHT * row as f32;
+        painter.rect_filled(
+            egui::Rect::from_min_max(
+                egui::pos2(rect.min.x, y),
+                egui::pos2(rect.max.x, y + ROW_HEIGHT),
+            ),
+            0.0,
+            visuals.faint_bg_color,
+        );
+    }
+
+    // Ruler: a tick per second (fewer on long timelines), labelled
+    let step = match length {
+        l if l <= 20.0 => 1.0,
+        l if l <= 120.0 => 5.0,
+        _ => 30.0,
+    };
+    let mut s = 0.0;
+    while s <= length + f32::EPSILON {
+        let x = x_of(s);
+        painter.line_segment(
+            [
+                egui::pos2(x, rect.min.y + ROW_HEIGHT * 0.6),
+                egui::pos2(x, rect.max.y),
+            ],
+            egui::Stroke::new(1.0, weak.gamma_multiply(0.3)),
+        );
+        painter.text(
+            egui::pos2(x + 2.0, rect.min.y + 1.0),
+            egui::Align2::LEFT_TOP,
+            format!("{s:.0}"),
+            font.clone(),
+            weak,
+        );
+        s += step;
+    }
+
+    for (t, track) in timeline.tracks.iter().enumerate() {
+        let y = row_y(t + 1);
+        painter.text(
+            egui::pos2(rect.min.x + 4.0, y),
+            egui::Align2::LEFT_CENTER,
+            track.target.label(),
+            font.clone(),
+            text_color,
+        );
+        for (k, key) in track.keys.iter().enumerate() {
+            let c = egui::pos2(x_of(key.time), y);
+            let selected = ui_state.selected == Some((t, k));
+            let fill = if selected {
+                visuals.selection.bg_fill
+            } else {
+                text_color
+            };
+            painter.add(egui::Shape::convex_polygon(
+                vec![
+                    c + egui::vec2(0.0, -KEY_RADIUS),
+                    c + egui::vec2(KEY_RADIUS, 0.0),
+                    c + egui::vec2(0.0, KEY_RADIUS),
+                    c + egui::vec2(-KEY_RADIUS, 0.0),
+                ],
+                fill,
+                egui::Stroke::NONE,
+            ));
+        }
+    }
+
+    let x = x_of(playhead.min(length));
+    painter.line_segment(
+        [egui::pos2(x, rect.min.y), egui::pos2(x, rect.max.y)],
+        egui::Stroke::new(2.0, egui::Color32::from_rgb(230, 80, 60)),
+    );
+    edit
+}
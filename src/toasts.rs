@@ -0,0 +1,85 @@
+//! Short notices in the bottom-right corner for things worth knowing that don't need a window
+//! (an edit that had to be corrected, say). Each one fades out after a few seconds.
+
+use bevy::prelude::*;
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+
+/// How long a toast stays up.
+const TOAST_SECS: f32 = 4.0;
+
+struct Toast {
+    text: String,
+    warning: bool,
+    /// `Time<Real>` seconds when it was (last) posted
+    posted_at: f32,
+}
+
+/// Post with [`Toasts::info`] or [`Toasts::warn`] from any system.
+#[derive(Resource, Default)]
+pub struct Toasts {
+    toasts: Vec<Toast>,
+    /// Posted since the last frame drawn; stamped when drawn
+    pending: Vec<(String, bool)>,
+}
+
+impl Toasts {
+    pub fn info(&mut self, text: impl Into<String>) {
+        self.pending.push((text.into(), false));
+    }
+
+    pub fn warn(&mut self, text: impl Into<String>) {
+        self.pending.push((text.into(), true));
+    }
+}
+
+pub struct ToastsPlugin;
+impl Plugin for ToastsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Toasts>()
+            .add_systems(EguiPrimaryContextPass, draw_toasts);
+    }
+}
+
+fn draw_toasts(mut ctxs: EguiContexts, mut toasts: ResMut<Toasts>, time: Res<Time<Real>>) {
+    let now = time.elapsed_secs();
+    let toasts = &mut *toasts;
+    for (text, warning) in toasts.pending.drain(..) {
+        // A repeat just stays up longer instead of stacking
+        match toasts.toasts.iter_mut().find(|t| t.text == text) {
+            Some(toast) => toast.posted_at = now,
+            None => toasts.toasts.push(Toast {
+                text,
+                warning,
+                posted_at: now,
+            }),
+        }
+    }
+    toasts.toasts.retain(|t| now - t.posted_at < TOAST_SECS);
+    if toasts.toasts.is_empty() {
+        return;
+    }
+
+    let Ok(ctx) = ctxs.ctx_mut() else {
+        return;
+    };
+    egui::Area::new(egui::Id::new("toasts"))
+        .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-12.0, -12.0))
+        .order(egui::Order::Foreground)
+        .interactable(false)
+        .show(ctx, |ui| {
+            for toast in &toasts.toasts {
+                // Fade over the last second
+                let alpha = (TOAST_SECS - (now - toast.posted_at)).clamp(0.0, 1.0);
+                ui.set_opacity(alpha);
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    if toast.warning {
+                        ui.colored_label(ui.visuals().warn_fg_color, format!("⚠ {}", toast.text));
+                    } else {
+                        ui.label(&toast.text);
+                    }
+                });
+            }
+        });
+    // Keep repainting while they fade
+    ctx.request_repaint();
+}
@@ -0,0 +1,273 @@
+use bevy::prelude::*;
+use bevy::render::primitives::Aabb;
+use bevy::window::PrimaryWindow;
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+
+use crate::SceneEditState;
+use crate::accessibility::Described;
+use crate::actions::EditorAction;
+use crate::bake::BakedMember;
+use crate::camera::OrbitCamera;
+use crate::dock::DockSet;
+use crate::editor_mode::EditorMode;
+#[cfg(feature = "inspector")]
+use crate::inspector::{EditHistory, SceneIoState};
+use crate::inspector::{Editable, Selected, aabb_world, ray_aabb_intersection};
+#[cfg(feature = "inspector")]
+use crate::selection_history::SelectionHistory;
+use crate::settings::EditorPrefs;
+use crate::time_controls::{MAX_SPEED, MIN_SPEED, TimeControls};
+
+/// How long the toolbar says "Saved" after a save
+#[cfg(feature = "inspector")]
+const SAVED_FLASH_SECS: f32 = 2.0;
+
+/// Mode buttons and scene name along the top, cursor position and selection along the bottom.
+pub struct ToolbarPlugin;
+impl Plugin for ToolbarPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EditorMode>()
+            .init_resource::<EditorPrefs>()
+            .init_resource::<TimeControls>()
+            .add_event::<EditorAction>()
+            .add_systems(
+                EguiPrimaryContextPass,
+                // Before the docked panels, so both bars span the whole window
+                (toolbar, status_bar).chain().before(DockSet::Hierarchy),
+            );
+    }
+}
+
+fn toolbar(
+    mut ctxs: EguiContexts,
+    edit_state: Res<SceneEditState>,
+    mode: Res<EditorMode>,
+    mut actions: EventWriter<EditorAction>,
+    mut prefs: ResMut<EditorPrefs>,
+    mut virt: ResMut<Time<Virtual>>,
+    mut time_controls: ResMut<TimeControls>,
+    #[cfg(feature = "inspector")] time: Res<Time<Real>>,
+    #[cfg(feature = "inspector")] io: Option<Res<SceneIoState>>,
+    #[cfg(feature = "inspector")] history: Option<Res<SelectionHistory>>,
+    #[cfg(feature = "inspector")] edits: Option<Res<EditHistory>>,
+) {
+    if !edit_state.open {
+        return;
+    }
+
+    let mut selected = *mode;
+    let mut cutaway = prefs.cutaway;
+    let (mut proportional, mut radius) = (prefs.proportional, prefs.proportional_radius);
+    let (mut paused, mut speed) = (virt.is_paused(), virt.relative_speed());
+    let Ok(ctx) = ctxs.ctx_mut() else {
+        return;
+    };
+    egui::TopBottomPanel::top("toolbar").show(ctx, |ui| {
+        ui.horizontal(|ui| {
+            for m in EditorMode::ALL {
+                // Play needs the player plugin
+                if m == EditorMode::Play && !cfg!(feature = "player") {
+                    continue;
+                }
+                ui.selectable_value(&mut selected, m, m.label());
+            }
+            #[cfg(feature = "inspector")]
+            if let Some(history) = &history {
+                ui.separator();
+                if ui
+                    .add_enabled(history.can_go_back(), egui::Button::new("◀"))
+                    .described("Previous selection (Alt+Left)")
+                    .clicked()
+                {
+                    actions.write(EditorAction::SelectionBack);
+                }
+                if ui
+                    .add_enabled(history.can_go_forward(), egui::Button::new("▶"))
+                    .described("Next selection (Alt+Right)")
+                    .clicked()
+                {
+                    actions.write(EditorAction::SelectionForward);
+                }
+            }
+            #[cfg(feature = "inspector")]
+            if let Some(edits) = &edits {
+                ui.separator();
+                if ui
+                    .add_enabled(edits.can_undo(), egui::Button::new("Undo"))
+                    .described("Undo the last edit (Ctrl+Z)")
+                    .clicked()
+                {
+                    actions.write(EditorAction::Undo);
+                }
+                if ui
+                    .add_enabled(edits.can_redo(), egui::Button::new("Redo"))
+                    .described("Redo the last undone edit (Ctrl+Shift+Z)")
+                    .clicked()
+                {
+                    actions.write(EditorAction::Redo);
+                }
+            }
+
+            ui.separator();
+            ui.toggle_value(&mut cutaway, "Cutaway")
+                .on_hover_text("See through whatever hides the selection (or the player)");
+            ui.toggle_value(&mut proportional, "Proportional")
+                .on_hover_text("Moving the selection drags nearby objects along, with falloff");
+            if proportional {
+                ui.add(
+                    egui::DragValue::new(&mut radius)
+                        .speed(0.1)
+                        .range(0.1..=100.0)
+                        .prefix("r "),
+                )
+                .on_hover_text("How far the pull reaches");
+            }
+
+            ui.separator();
+            let pause_label = if paused { "▶" } else { "⏸" };
+            if ui
+                .button(pause_label)
+                .described("Pause or resume game time (physics, animations, scripts)")
+                .clicked()
+            {
+                paused = !paused;
+            }
+            if ui
+                .add_enabled(paused, egui::Button::new("⏭"))
+                .described("Advance one frame")
+                .clicked()
+            {
+                time_controls.step = true;
+            }
+            ui.add(
+                egui::Slider::new(&mut speed, MIN_SPEED..=MAX_SPEED)
+                    .logarithmic(true)
+                    .max_decimals(2)
+                    .suffix("×"),
+            )
+            .on_hover_text("Time scale");
+            if ui.button("1×").on_hover_text("Normal speed").clicked() {
+                speed = 1.0;
+            }
+
+            #[cfg(feature = "inspector")]
+            if let Some(io) = io {
+                ui.separator();
+                let name = match io.filename.trim() {
+                    "" => "untitled",
+                    name => name,
+                };
+                let asterisk = if io.dirty { "*" } else { "" };
+                ui.label(format!("{name}{asterisk}"));
+
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if ui.button("Save").clicked() {
+                        actions.write(EditorAction::Save);
+                    }
+                    let just_saved = io
+                        .saved_at
+                        .is_some_and(|t| time.elapsed_secs() - t < SAVED_FLASH_SECS);
+                    if io.dirty {
+                        ui.colored_label(ui.visuals().warn_fg_color, "● Unsaved");
+                    } else if just_saved {
+                        ui.colored_label(egui::Color32::LIGHT_GREEN, "✔ Saved");
+                    } else {
+                        ui.weak("Saved");
+                    }
+                });
+            }
+        });
+    });
+
+    if selected != *mode {
+        actions.write(EditorAction::SetMode(selected));
+    }
+    if paused != virt.is_paused() {
+        if paused {
+            virt.pause();
+        } else {
+            virt.unpause();
+        }
+    }
+    if speed != virt.relative_speed() {
+        virt.set_relative_speed(speed);
+    }
+    if cutaway != prefs.cutaway {
+        prefs.cutaway = cutaway;
+    }
+    if (proportional, radius) != (prefs.proportional, prefs.proportional_radius) {
+        prefs.proportional = proportional;
+        prefs.proportional_radius = radius;
+    }
+}
+
+fn status_bar(
+    mut ctxs: EguiContexts,
+    edit_state: Res<SceneEditState>,
+    prefs: Res<EditorPrefs>,
+    q_window: Query<&Window, With<PrimaryWindow>>,
+    q_cam: Query<(&Camera, &GlobalTransform), With<OrbitCamera>>,
+    q_editables: Query<(&GlobalTransform, &Aabb), (With<Editable>, Without<BakedMember>)>,
+    q_selected: Query<(Entity, Option<&Name>), With<Selected>>,
+) {
+    if !edit_state.open {
+        return;
+    }
+
+    let cursor_world = q_window
+        .single()
+        .ok()
+        .and_then(|w| w.cursor_position())
+        .zip(q_cam.single().ok())
+        .and_then(|(cursor, (camera, cam_gt))| {
+            let ray = camera.viewport_to_world(cam_gt, cursor).ok()?;
+            // Nearest object under the cursor, else the ground plane
+            let nearest = q_editables
+                .iter()
+                .filter_map(|(gt, aabb)| {
+                    let world = aabb_world(*aabb, gt);
+                    ray_aabb_intersection(
+                        ray.origin,
+                        *ray.direction,
+                        (world.center - world.half_extents).into(),
+                        (world.center + world.half_extents).into(),
+                    )
+                })
+                .min_by(f32::total_cmp);
+            let t = nearest
+                .or_else(|| ray.intersect_plane(Vec3::ZERO, InfinitePlane3d::new(Vec3::Y)))?;
+            Some(ray.get_point(t))
+        });
+
+    let selection = match q_selected.single() {
+        Ok((_, Some(name))) => name.to_string(),
+        Ok((e, None)) => format!("Entity {e}"),
+        Err(_) if q_selected.is_empty() => "Nothing selected".to_string(),
+        Err(_) => format!("{} objects", q_selected.iter().count()),
+    };
+    let snap = if prefs.snap {
+        format!(
+            "Snap: on ({} / {}° / {})",
+            prefs.move_snap, prefs.rotate_snap_deg, prefs.scale_snap
+        )
+    } else {
+        "Snap: off".to_string()
+    };
+
+    let Ok(ctx) = ctxs.ctx_mut() else {
+        return;
+    };
+    egui::TopBottomPanel::bottom("status_bar").show(ctx, |ui| {
+        ui.horizontal(|ui| {
+            match cursor_world {
+                Some(p) => ui.monospace(format!("{:7.2} {:7.2} {:7.2}", p.x, p.y, p.z)),
+                None => ui.monospace(format!("{:>23}", "-")),
+            };
+            ui.separator();
+            ui.label(selection);
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                ui.label(snap);
+            });
+        });
+    });
+}
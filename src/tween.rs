@@ -0,0 +1,284 @@
+//! `AnimateTo`: ease one property of an entity to a new value over a few seconds.
+//!
+//! ```ignore
+//! commands.entity(door).insert(AnimateTo {
+//!     target: TweenTarget::Translation(open_position),
+//!     duration: 1.5,
+//!     easing: Easing::EaseInOut,
+//! });
+//! ```
+//!
+//! The tween starts from the entity's current value and the component removes itself when done.
+//! An entity runs one tween at a time: inserting another replaces it, starting from wherever the
+//! first one got to. Post-effect targets go on the camera that carries the effect's settings.
+
+use bevy::ecs::system::SystemParam;
+use bevy::pbr::{DistanceFog, FogFalloff};
+use bevy::prelude::*;
+
+use crate::asset_cache::AssetCache;
+use crate::dissolve::DissolveAmount;
+#[cfg(feature = "chroma")]
+use crate::post::chroma_aberration::ChromaAberrationSettings;
+#[cfg(feature = "crt")]
+use crate::post::crt::CRTSettings;
+#[cfg(feature = "gradient")]
+use crate::post::gradient_tint::GradientTintSettings;
+#[cfg(feature = "lut")]
+use crate::post::lut::LutSettings;
+pub use crate::timeline::Easing;
+
+/// The property to animate and the value it ends at.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TweenTarget {
+    Translation(Vec3),
+    Rotation(Quat),
+    Scale(Vec3),
+    /// Base color of the entity's material
+    Color(Color),
+    /// How far the entity is dissolved, 0 whole .. 1 gone (see `dissolve`)
+    Dissolve(f32),
+    FogDensity(f32),
+    ChromaIntensity(f32),
+    CrtIntensity(f32),
+    LutStrength(f32),
+    TintStrength(f32),
+    TintTopRight(Vec4),
+    TintBottomLeft(Vec4),
+}
+
+impl TweenTarget {
+    /// Blend from `self` towards `to` (of the same kind)
+    fn lerp(self, to: TweenTarget, t: f32) -> Option<TweenTarget> {
+        use TweenTarget::*;
+        Some(match (self, to) {
+            (Translation(a), Translation(b)) => Translation(a.lerp(b, t)),
+            (Rotation(a), Rotation(b)) => Rotation(a.slerp(b, t)),
+            (Scale(a), Scale(b)) => Scale(a.lerp(b, t)),
+            (Color(a), Color(b)) => Color(a.mix(&b, t)),
+            (Dissolve(a), Dissolve(b)) => Dissolve(a + (b - a) * t),
+            (FogDensity(a), FogDensity(b)) => FogDensity(a + (b - a) * t),
+            (ChromaIntensity(a), ChromaIntensity(b)) => ChromaIntensity(a + (b - a) * t),
+            (CrtIntensity(a), CrtIntensity(b)) => CrtIntensity(a + (b - a) * t),
+            (LutStrength(a), LutStrength(b)) => LutStrength(a + (b - a) * t),
+            (TintStrength(a), TintStrength(b)) => TintStrength(a + (b - a) * t),
+            (TintTopRight(a), TintTopRight(b)) => TintTopRight(a.lerp(b, t)),
+            (TintBottomLeft(a), TintBottomLeft(b)) => TintBottomLeft(a.lerp(b, t)),
+            _ => return None,
+        })
+    }
+}
+
+/// Animate `target`'s property from its current value over `duration` seconds.
+#[derive(Component, Clone, Debug)]
+#[require(TweenProgress)]
+pub struct AnimateTo {
+    pub target: TweenTarget,
+    pub duration: f32,
+    pub easing: Easing,
+}
+
+/// Start value and time so far of the entity's `AnimateTo`.
+#[derive(Component, Default)]
+struct TweenProgress {
+    from: Option<TweenTarget>,
+    elapsed: f32,
+}
+
+pub struct TweenPlugin;
+impl Plugin for TweenPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AssetCache>()
+            .add_systems(Update, run_tweens);
+    }
+}
+
+/// Everything a tween can write to.
+#[derive(SystemParam)]
+struct TweenProperties<'w, 's> {
+    commands: Commands<'w, 's>,
+    cache: Res<'w, AssetCache>,
+    materials: ResMut<'w, Assets<StandardMaterial>>,
+    transforms: Query<'w, 's, &'static mut Transform>,
+    mesh_materials: Query<'w, 's, &'static MeshMaterial3d<StandardMaterial>>,
+    dissolves: Query<'w, 's, &'static mut DissolveAmount>,
+    fog: Query<'w, 's, &'static mut DistanceFog>,
+    #[cfg(feature = "chroma")]
+    chroma: Query<'w, 's, &'static mut ChromaAberrationSettings>,
+    #[cfg(feature = "crt")]
+    crt: Query<'w, 's, &'static mut CRTSettings>,
+    #[cfg(feature = "lut")]
+    lut: Query<'w, 's, &'static mut LutSettings>,
+    #[cfg(feature = "gradient")]
+    tint: Query<'w, 's, &'static mut GradientTintSettings>,
+}
+
+impl TweenProperties<'_, '_> {
+    /// Current value of `like`'s property on `e`, if it has one
+    #[allow(unreachable_patterns)]
+    fn get(&self, e: Entity, like: TweenTarget) -> Option<TweenTarget> {
+        use TweenTarget::*;
+        Some(match like {
+            Translation(_) => Translation(self.transforms.get(e).ok()?.translation),
+            Rotation(_) => Rotation(self.transforms.get(e).ok()?.rotation),
+            Scale(_) => Scale(self.transforms.get(e).ok()?.scale),
+            Color(_) => {
+                let handle = &self.mesh_materials.get(e).ok()?.0;
+                Color(self.materials.get(handle)?.base_color)
+            }
+            // Not dissolving is whole
+            Dissolve(_) => Dissolve(self.dissolves.get(e).map_or(0.0, |d| d.0)),
+            FogDensity(_) => match self.fog.get(e).ok()?.falloff {
+                FogFalloff::Exponential { density } => FogDensity(density),
+                _ => return None,
+            },
+            #[cfg(feature = "chroma")]
+            ChromaIntensity(_) => ChromaIntensity(self.chroma.get(e).ok()?.intensity),
+            #[cfg(feature = "crt")]
+            CrtIntensity(_) => CrtIntensity(self.crt.get(e).ok()?.intensity),
+            #[cfg(feature = "lut")]
+            LutStrength(_) => LutStrength(self.lut.get(e).ok()?.strength),
+            #[cfg(feature = "gradient")]
+            TintStrength(_) => TintStrength(self.tint.get(e).ok()?.strength),
+            #[cfg(feature = "gradient")]
+            TintTopRight(_) => TintTopRight(self.tint.get(e).ok()?.color_top_right),
+            #[cfg(feature = "gradient")]
+            TintBottomLeft(_) => TintBottomLeft(self.tint.get(e).ok()?.color_bottom_left),
+            _ => return None,
+        })
+    }
+
+    #[allow(unreachable_patterns)]
+    fn set(&mut self, e: Entity, value: TweenTarget) {
+        use TweenTarget::*;
+        match value {
+            Translation(v) => {
+                if let Ok(mut tf) = self.transforms.get_mut(e) {
+                    tf.translation = v;
+                }
+            }
+            Rotation(q) => {
+                if let Ok(mut tf) = self.transforms.get_mut(e) {
+                    tf.rotation = q;
+                }
+            }
+            Scale(v) => {
+                if let Ok(mut tf) = self.transforms.get_mut(e) {
+                    tf.scale = v;
+                }
+            }
+            Color(color) => {
+                let Ok(mat) = self.mesh_materials.get(e) else {
+                    return;
+                };
+                // Cached materials are shared with other objects: edit a private copy
+                let Some(handle) =
+                    self.cache
+                        .own_material(&mut self.commands, &mut self.materials, e, &mat.0)
+                else {
+                    return;
+                };
+                if let Some(m) = self.materials.get_mut(&handle) {
+                    m.base_color = color;
+                }
+            }
+            Dissolve(amount) => {
+                let amount = amount.clamp(0.0, 1.0);
+                match self.dissolves.get_mut(e) {
+                    Ok(mut d) => d.0 = amount,
+                    Err(_) if amount > 0.0 => {
+                        self.commands.entity(e).insert(DissolveAmount(amount));
+                    }
+                    Err(_) => {}
+                }
+            }
+            FogDensity(density) => {
+                if let Ok(mut fog) = self.fog.get_mut(e) {
+                    fog.falloff = FogFalloff::Exponential {
+                        density: density.max(0.0),
+                    };
+                }
+            }
+            #[cfg(feature = "chroma")]
+            ChromaIntensity(v) => {
+                if let Ok(mut s) = self.chroma.get_mut(e) {
+                    s.intensity = v;
+                }
+            }
+            #[cfg(feature = "crt")]
+            CrtIntensity(v) => {
+                if let Ok(mut s) = self.crt.get_mut(e) {
+                    s.intensity = v;
+                }
+            }
+            #[cfg(feature = "lut")]
+            LutStrength(v) => {
+                if let Ok(mut s) = self.lut.get_mut(e) {
+                    s.strength = v;
+                }
+            }
+            #[cfg(feature = "gradient")]
+            TintStrength(v) => {
+                if let Ok(mut s) = self.tint.get_mut(e) {
+                    s.strength = v;
+                }
+            }
+            #[cfg(feature = "gradient")]
+            TintTopRight(c) => {
+                if let Ok(mut s) = self.tint.get_mut(e) {
+                    s.color_top_right = c;
+                }
+            }
+            #[cfg(feature = "gradient")]
+            TintBottomLeft(c) => {
+                if let Ok(mut s) = self.tint.get_mut(e) {
+                    s.color_bottom_left = c;
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn run_tweens(
+    time: Res<Time>,
+    mut q_tweens: Query<(Entity, Ref<AnimateTo>, &mut TweenProgress)>,
+    mut props: TweenProperties,
+) {
+    let dt = time.delta_secs();
+    for (e, tween, mut progress) in &mut q_tweens {
+        // A replaced tween starts over from the current value
+        if tween.is_changed() {
+            *progress = TweenProgress::default();
+        }
+        let from = match progress.from {
+            Some(from) => from,
+            None => match props.get(e, tween.target) {
+                Some(from) => *progress.from.insert(from),
+                // Nothing to animate (no material, fog is off, pass compiled out)
+                None => {
+                    props
+                        .commands
+                        .entity(e)
+                        .remove::<(AnimateTo, TweenProgress)>();
+                    continue;
+                }
+            },
+        };
+        progress.elapsed += dt;
+        let t = if tween.duration > 0.0 {
+            (progress.elapsed / tween.duration).min(1.0)
+        } else {
+            1.0
+        };
+        if let Some(value) = from.lerp(tween.target, tween.easing.ease(t)) {
+            props.set(e, value);
+        }
+        if t >= 1.0 {
+            props
+                .commands
+                .entity(e)
+                .remove::<(AnimateTo, TweenProgress)>();
+        }
+    }
+}
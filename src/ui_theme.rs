@@ -0,0 +1,285 @@
+use bevy::prelude::*;
+use bevy_egui::{
+    EguiContext, EguiContextSettings, EguiContexts, EguiPrimaryContextPass, PrimaryEguiContext,
+    egui,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::SceneEditState;
+#[cfg(feature = "player")]
+use crate::camera::CoopCamera;
+use crate::cutaway::CutawayStyle;
+use crate::dock::{DockLayout, DockPanel, DockSlot};
+use crate::editor_gizmos::GizmoDepth;
+use crate::power::PowerPrefs;
+use crate::settings::EditorPrefs;
+
+#[derive(Copy, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub enum UiTheme {
+    #[default]
+    Dark,
+    Light,
+}
+
+/// egui look: theme, optional accent color and UI scale (kept in the app settings).
+#[derive(Resource, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UiPrefs {
+    pub theme: UiTheme,
+    /// Tint selections, highlights and links with `accent` instead of the theme's blue
+    pub custom_accent: bool,
+    pub accent: [u8; 3],
+    /// Multiplies the window's own DPI scale, for high-DPI displays or a roomier UI
+    pub scale: f32,
+}
+
+impl Default for UiPrefs {
+    fn default() -> Self {
+        Self {
+            theme: UiTheme::Dark,
+            custom_accent: false,
+            accent: [232, 140, 90],
+            scale: 1.0,
+        }
+    }
+}
+
+impl UiPrefs {
+    fn visuals(&self) -> egui::Visuals {
+        let mut visuals = match self.theme {
+            UiTheme::Dark => egui::Visuals::dark(),
+            UiTheme::Light => egui::Visuals::light(),
+        };
+        if self.custom_accent {
+            let [r, g, b] = self.accent;
+            let accent = egui::Color32::from_rgb(r, g, b);
+            visuals.selection.bg_fill = accent.gamma_multiply(0.6);
+            visuals.selection.stroke.color = accent;
+            visuals.hyperlink_color = accent;
+            visuals.widgets.hovered.bg_stroke.color = accent;
+            visuals.widgets.active.bg_stroke.color = accent;
+        }
+        visuals
+    }
+}
+
+/// Theme and UI scale for every egui panel, plus the Preferences window to change them (and
+/// where the editor panels are docked).
+pub struct UiThemePlugin;
+impl Plugin for UiThemePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<UiPrefs>()
+            .init_resource::<EditorPrefs>()
+            .add_systems(Update, apply_ui_prefs)
+            .add_systems(EguiPrimaryContextPass, preferences_window);
+    }
+}
+
+/// Push the prefs into the primary egui context whenever they change (and once it exists).
+fn apply_ui_prefs(
+    prefs: Res<UiPrefs>,
+    mut q_ctx: Query<(&mut EguiContext, &mut EguiContextSettings), With<PrimaryEguiContext>>,
+    mut applied: Local<bool>,
+) {
+    if *applied && !prefs.is_changed() {
+        return;
+    }
+    let Ok((mut ctx, mut ctx_settings)) = q_ctx.single_mut() else {
+        return;
+    };
+    *applied = true;
+
+    let (preference, theme) = match prefs.theme {
+        UiTheme::Dark => (egui::ThemePreference::Dark, egui::Theme::Dark),
+        UiTheme::Light => (egui::ThemePreference::Light, egui::Theme::Light),
+    };
+    let ctx = ctx.get_mut();
+    ctx.set_theme(preference);
+    ctx.set_visuals_of(theme, prefs.visuals());
+    ctx_settings.scale_factor = prefs.scale.clamp(0.5, 3.0);
+}
+
+fn preferences_window(
+    mut ctxs: EguiContexts,
+    edit_state: Res<SceneEditState>,
+    mut prefs: ResMut<UiPrefs>,
+    mut editor_prefs: ResMut<EditorPrefs>,
+    mut dock_layout: ResMut<DockLayout>,
+    mut power_prefs: ResMut<PowerPrefs>,
+    mut dragged_scale: Local<Option<f32>>,
+) {
+    if !edit_state.open {
+        return;
+    }
+    let Ok(ctx) = ctxs.ctx_mut() else {
+        return;
+    };
+
+    // Widgets edit copies, written back below
+    let mut edited = prefs.clone();
+    let mut editor = editor_prefs.clone();
+    let mut dock = dock_layout.clone();
+    let mut power = power_prefs.clone();
+    egui::Window::new("Preferences")
+        .default_open(false)
+        .show(ctx, |ui| {
+            ui.heading("Theme");
+            ui.horizontal(|ui| {
+                ui.selectable_value(&mut edited.theme, UiTheme::Dark, "Dark");
+                ui.selectable_value(&mut edited.theme, UiTheme::Light, "Light");
+            });
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut edited.custom_accent, "Custom accent");
+                ui.add_enabled_ui(edited.custom_accent, |ui| {
+                    ui.color_edit_button_srgb(&mut edited.accent);
+                });
+            });
+
+            ui.separator();
+            // Rescaling mid-drag would move the slider out from under the cursor, so the new
+            // scale applies on release
+            let mut scale = dragged_scale.unwrap_or(edited.scale);
+            let response = ui.add(
+                egui::Slider::new(&mut scale, 0.5..=3.0)
+                    .step_by(0.05)
+                    .text("UI scale"),
+            );
+            if response.dragged() {
+                *dragged_scale = Some(scale);
+            } else {
+                *dragged_scale = None;
+                edited.scale = scale;
+            }
+            if ui.button("Reset").clicked() {
+                edited = UiPrefs::default();
+            }
+
+            ui.separator();
+            ui.heading("Layout");
+            egui::Grid::new("dock_slots").show(ui, |ui| {
+                for (panel, name) in [
+                    (DockPanel::Hierarchy, "Hierarchy"),
+                    (DockPanel::Inspector, "Inspector"),
+                    (DockPanel::Effects, "Effects"),
+                ] {
+                    ui.label(name);
+                    let slot = dock.slot_mut(panel);
+                    egui::ComboBox::from_id_salt(name)
+                        .selected_text(slot.label())
+                        .show_ui(ui, |ui| {
+                            for s in DockSlot::ALL {
+                                ui.selectable_value(slot, s, s.label());
+                            }
+                        });
+                    ui.end_row();
+                }
+            });
+            if ui.button("Reset layout").clicked() {
+                dock.reset(ui.ctx());
+            }
+
+            ui.separator();
+            ui.heading("Grid");
+            ui.checkbox(&mut editor.show_grid, "Show ground grid");
+            ui.add_enabled_ui(editor.show_grid, |ui| {
+                ui.add(
+                    egui::Slider::new(&mut editor.grid_major_every, 1..=20)
+                        .text("Cells per major line"),
+                );
+                ui.weak(format!(
+                    "Cell size follows the move snap ({})",
+                    editor.move_snap
+                ));
+            });
+            ui.checkbox(&mut editor.show_axes, "Show axes widget");
+
+            ui.separator();
+            ui.heading("Cutaway");
+            ui.checkbox(&mut editor.cutaway, "See through what hides the selection");
+            ui.add_enabled_ui(editor.cutaway, |ui| {
+                ui.horizontal(|ui| {
+                    for style in CutawayStyle::ALL {
+                        ui.selectable_value(&mut editor.cutaway_style, style, style.label());
+                    }
+                });
+                ui.add_enabled(
+                    editor.cutaway_style == CutawayStyle::Transparent,
+                    egui::Slider::new(&mut editor.cutaway_opacity, 0.0..=1.0).text("Opacity"),
+                );
+            });
+
+            ui.separator();
+            ui.heading("Proportional editing");
+            ui.checkbox(&mut editor.proportional, "Moves drag nearby objects along");
+            ui.add_enabled(
+                editor.proportional,
+                egui::Slider::new(&mut editor.proportional_radius, 0.1..=20.0).text("Radius"),
+            );
+
+            ui.separator();
+            ui.heading("Gizmos");
+            ui.horizontal(|ui| {
+                for depth in GizmoDepth::ALL {
+                    ui.selectable_value(&mut editor.gizmo_depth, depth, depth.label());
+                }
+            });
+            ui.checkbox(
+                &mut editor.oriented_selection_box,
+                "Selection box turns with the object",
+            );
+            ui.add(egui::Slider::new(&mut editor.gizmo_line_width, 1.0..=8.0).text("Line width"));
+            ui.checkbox(&mut editor.gizmo_smoothing, "Smooth edges (FXAA)");
+
+            #[cfg(feature = "player")]
+            {
+                ui.separator();
+                ui.heading("Play");
+                ui.checkbox(&mut editor.second_player, "Second player (gamepad)");
+                ui.add_enabled_ui(editor.second_player, |ui| {
+                    ui.horizontal(|ui| {
+                        for camera in CoopCamera::ALL {
+                            ui.selectable_value(&mut editor.coop_camera, camera, camera.label());
+                        }
+                    });
+                });
+            }
+
+            ui.separator();
+            ui.heading("Performance");
+            // On the web the browser paces frames
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                ui.checkbox(&mut power.cap_fps, "Cap frame rate");
+                ui.add_enabled(
+                    power.cap_fps,
+                    egui::Slider::new(&mut power.max_fps, 15..=240).text("Max FPS"),
+                );
+            }
+            ui.checkbox(&mut power.idle_throttle, "Throttle in the background")
+                .on_hover_text(
+                    "While the window is unfocused and nothing is animating \
+                     (Play, timeline, tweens, capture)",
+                );
+            ui.add_enabled(
+                power.idle_throttle,
+                egui::Slider::new(&mut power.idle_fps, 1..=30).text("Background FPS"),
+            );
+            ui.checkbox(&mut power.reactive_updates, "Reactive updates")
+                .on_hover_text(
+                    "Update outlines, scene change tracking and selection gizmos only when \
+                     something they depend on changes, instead of every frame",
+                );
+
+            ui.separator();
+            ui.checkbox(
+                &mut editor.reopen_last_scene,
+                "Reopen last scene at startup",
+            );
+        });
+
+    // Restyling, the gizmo config and the frame pacer all react to changes, so set only on an edit
+    prefs.set_if_neq(edited);
+    editor_prefs.set_if_neq(editor);
+    dock_layout.set_if_neq(dock);
+    power_prefs.set_if_neq(power);
+}
@@ -0,0 +1,116 @@
+//! Catches broken transforms on editable objects before they spread: a single NaN position
+//! poisons AABB math, picking and the physics broadphase without any visible error.
+//!
+//! Whatever wrote the value (an inspector field, a loaded file, a script, the physics step),
+//! non-finite components go back to the object's last good value, zero or huge scales and
+//! far-off positions are clamped, and a toast says what was fixed.
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use bevy::transform::TransformSystem;
+use bevy_rapier3d::prelude::PhysicsSet;
+
+use crate::inspector::Editable;
+use crate::toasts::Toasts;
+
+/// Farthest an object may sit from the origin on any axis.
+const MAX_COORD: f32 = 100_000.0;
+/// Scale range per axis (by magnitude; mirrored axes keep their sign), as in the inspector.
+const MIN_SCALE: f32 = 0.001;
+const MAX_SCALE: f32 = 1000.0;
+
+pub struct ValidationPlugin;
+impl Plugin for ValidationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Toasts>().add_systems(
+            PostUpdate,
+            sanitize_transforms
+                .after(PhysicsSet::Writeback)
+                .before(TransformSystem::TransformPropagate),
+        );
+    }
+}
+
+/// What was wrong with a transform, for the toast.
+#[derive(Default)]
+struct Fixes {
+    non_finite: usize,
+    scale: usize,
+    far: usize,
+}
+
+/// The corrected transform, or `None` if `tf` was fine.
+fn sanitize(tf: &Transform, last_good: Option<&Transform>, fixes: &mut Fixes) -> Option<Transform> {
+    let mut out = *tf;
+
+    if !out.translation.is_finite() {
+        out.translation = last_good.map_or(Vec3::ZERO, |g| g.translation);
+        fixes.non_finite += 1;
+    } else if out.translation.abs().max_element() > MAX_COORD {
+        out.translation = out
+            .translation
+            .clamp(Vec3::splat(-MAX_COORD), Vec3::splat(MAX_COORD));
+        fixes.far += 1;
+    }
+
+    if !out.rotation.is_finite() || out.rotation.length_squared() < 1e-6 {
+        out.rotation = last_good.map_or(Quat::IDENTITY, |g| g.rotation);
+        fixes.non_finite += 1;
+    } else if !out.rotation.is_normalized() {
+        // Drift from repeated composition; not worth a toast
+        out.rotation = out.rotation.normalize();
+    }
+
+    if !out.scale.is_finite() {
+        out.scale = last_good.map_or(Vec3::ONE, |g| g.scale);
+        fixes.non_finite += 1;
+    } else {
+        let clamped = out
+            .scale
+            .map(|s| s.abs().clamp(MIN_SCALE, MAX_SCALE).copysign(s));
+        if clamped != out.scale {
+            out.scale = clamped;
+            fixes.scale += 1;
+        }
+    }
+
+    (out != *tf).then_some(out)
+}
+
+fn sanitize_transforms(
+    mut q_changed: Query<(Entity, &mut Transform), (With<Editable>, Changed<Transform>)>,
+    mut removed: RemovedComponents<Editable>,
+    mut last_good: Local<HashMap<Entity, Transform>>,
+    mut toasts: ResMut<Toasts>,
+) {
+    for e in removed.read() {
+        last_good.remove(&e);
+    }
+
+    let mut fixes = Fixes::default();
+    for (e, mut tf) in &mut q_changed {
+        if let Some(fixed) = sanitize(&tf, last_good.get(&e), &mut fixes) {
+            warn!(
+                "Corrected invalid transform on {e}: {:?} -> {:?}",
+                *tf, fixed
+            );
+            *tf = fixed;
+        }
+        last_good.insert(e, *tf);
+    }
+
+    let mut what = Vec::new();
+    if fixes.non_finite > 0 {
+        what.push("NaN/infinite values reset".to_string());
+    }
+    if fixes.scale > 0 {
+        what.push("zero or huge scale clamped".to_string());
+    }
+    if fixes.far > 0 {
+        what.push(format!("position clamped to ±{MAX_COORD}"));
+    }
+    if !what.is_empty() {
+        toasts.warn(format!("Invalid transform corrected: {}", what.join(", ")));
+    }
+}
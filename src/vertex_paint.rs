@@ -0,0 +1,462 @@
+//! Vertex painting: a brush that tints the vertices of the selected mesh objects, for shading
+//! that varies across a big terrace or a wall without splitting it up or adding textures. The
+//! standard material multiplies its color by the painted one, so white leaves an object as it
+//! was and greys and hues shade it.
+//!
+//! An object's paint is a [`VertexPaint`], saved with it in the scene. Meshes are shared
+//! between identical objects (see [`crate::asset_cache`]), so a painted object draws with its
+//! own copy of its mesh, carrying the colors. Blocks only have vertices at their corners, so the
+//! copy is subdivided until its edges are about [`PAINT_SPACING`] long, and a brush in the
+//! middle of a big face still has vertices to paint.
+
+use std::collections::HashMap;
+
+use bevy::picking::mesh_picking::ray_cast::{MeshRayCast, MeshRayCastSettings, RayCastVisibility};
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, PrimitiveTopology, VertexAttributeValues};
+use bevy::window::PrimaryWindow;
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+use serde::{Deserialize, Serialize};
+
+use crate::SceneEditState;
+use crate::camera::{OrbitCamera, UiPointer};
+use crate::editor_gizmos::EditorGizmos;
+use crate::editor_mode::EditorMode;
+use crate::ground_paint::{GroundBrush, deserialize_rgba_runs, serialize_rgba_runs};
+use crate::inspector::{Editable, Selected};
+
+/// At full intensity, how many times over a second of painting covers the vertices under the
+/// brush center.
+const PAINT_RATE: f32 = 4.0;
+
+/// Meters between the vertices of a painted mesh, give or take a factor of two.
+pub const PAINT_SPACING: f32 = 0.5;
+
+/// Most times a painted mesh's triangles are split in four (a 20 m face gets 0.6 m steps).
+const MAX_SUBDIVISIONS: u8 = 5;
+
+/// An object's painted vertex colors.
+#[derive(Component, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct VertexPaint {
+    /// Times each triangle of the object's mesh is split in four before it's painted
+    #[serde(default)]
+    pub subdivisions: u8,
+    /// sRGB color of each vertex of the subdivided mesh, in its order. Vertices past the end
+    /// (the mesh was rebuilt bigger) stay white.
+    #[serde(
+        serialize_with = "serialize_rgba_runs",
+        deserialize_with = "deserialize_rgba_runs"
+    )]
+    pub colors: Vec<[u8; 4]>,
+}
+
+/// On a painted object: the copy of its mesh it made to carry the colors.
+#[derive(Component)]
+struct PaintedMesh(AssetId<Mesh>);
+
+/// The vertex brush, set in the **Vertex Paint** window.
+#[derive(Resource)]
+pub struct VertexBrush {
+    /// Left drags on the selected objects paint them
+    pub active: bool,
+    /// sRGB
+    pub color: [u8; 3],
+    /// How quickly a stroke covers what's under it, 0..=1
+    pub intensity: f32,
+    /// Meters
+    pub radius: f32,
+}
+
+impl Default for VertexBrush {
+    fn default() -> Self {
+        Self {
+            active: false,
+            color: [150, 140, 130],
+            intensity: 0.3,
+            radius: 1.0,
+        }
+    }
+}
+
+impl VertexBrush {
+    /// Viewport clicks paint rather than pick objects.
+    pub fn captures_pointer(&self) -> bool {
+        self.active
+    }
+}
+
+pub struct VertexPaintPlugin;
+impl Plugin for VertexPaintPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<VertexBrush>()
+            .init_resource::<SceneEditState>()
+            .init_resource::<EditorMode>()
+            .init_gizmo_group::<EditorGizmos>()
+            .add_systems(Update, (paint_vertices, apply_vertex_paint).chain())
+            .add_systems(EguiPrimaryContextPass, vertex_paint_window);
+    }
+}
+
+/// Paint the selected object under the cursor while the left button is held (the press has to
+/// start in the viewport), and outline the brush around the spot.
+fn paint_vertices(
+    mut commands: Commands,
+    mut brush: ResMut<VertexBrush>,
+    edit_state: Res<SceneEditState>,
+    mode: Res<EditorMode>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    time: Res<Time<Real>>,
+    meshes: Res<Assets<Mesh>>,
+    mut ui_pointer: UiPointer,
+    mut ray_cast: MeshRayCast,
+    q_window: Query<&Window, With<PrimaryWindow>>,
+    q_cam: Query<(&Camera, &GlobalTransform), With<OrbitCamera>>,
+    mut q_selected: Query<
+        (
+            &GlobalTransform,
+            &Mesh3d,
+            Option<&mut VertexPaint>,
+            Option<&PaintedMesh>,
+        ),
+        (With<Selected>, With<Editable>),
+    >,
+    mut gizmos: Gizmos<EditorGizmos>,
+    mut stroke: Local<bool>,
+) {
+    if !brush.active {
+        *stroke = false;
+        return;
+    }
+    // Other modes own left drags (and Play the whole scene)
+    if !edit_state.open || *mode != EditorMode::Select {
+        brush.active = false;
+        return;
+    }
+    if mouse.just_pressed(MouseButton::Left) {
+        *stroke = !ui_pointer.wants_pointer_in_primary();
+    } else if !mouse.pressed(MouseButton::Left) {
+        *stroke = false;
+    }
+
+    let Some(cursor) = q_window.single().ok().and_then(|w| w.cursor_position()) else {
+        return;
+    };
+    let Ok((camera, cam_gt)) = q_cam.single() else {
+        return;
+    };
+    let Ok(ray) = camera.viewport_to_world(cam_gt, cursor) else {
+        return;
+    };
+    // Stylized objects are drawn by a skin and hidden from the cameras themselves, so
+    // visibility can't be asked for
+    let Some((target, hit)) = ray_cast
+        .cast_ray(
+            ray,
+            &MeshRayCastSettings::default()
+                .with_visibility(RayCastVisibility::Any)
+                .with_filter(&|e| q_selected.contains(e)),
+        )
+        .first()
+        .map(|(e, hit)| (*e, hit.point))
+    else {
+        return;
+    };
+    gizmos.sphere(
+        Isometry3d::from_translation(hit),
+        brush.radius,
+        Color::srgba(1.0, 0.8, 0.3, 0.8),
+    );
+    if !*stroke {
+        return;
+    }
+
+    let Ok((gt, mesh3d, paint, own)) = q_selected.get_mut(target) else {
+        return;
+    };
+    let Some(mesh) = meshes.get(&mesh3d.0) else {
+        return;
+    };
+    let Some(mut paint) = paint else {
+        // Its subdivided copy of the mesh gets made first; painting starts on the next frame
+        commands.entity(target).insert(VertexPaint {
+            subdivisions: subdivisions_for(mesh, gt),
+            colors: Vec::new(),
+        });
+        return;
+    };
+    if own.is_none_or(|own| own.0 != mesh3d.id()) {
+        return;
+    }
+    let Some(positions) = mesh
+        .attribute(Mesh::ATTRIBUTE_POSITION)
+        .and_then(|a| a.as_float3())
+    else {
+        return;
+    };
+    let amount = brush.intensity * time.delta_secs() * PAINT_RATE;
+    let color = [brush.color[0], brush.color[1], brush.color[2], 255];
+    let radius = brush.radius.max(0.01);
+    let mut colors = paint.colors.clone();
+    colors.resize(positions.len(), [255; 4]);
+    let mut painted = false;
+    for (value, position) in colors.iter_mut().zip(positions) {
+        let d = gt
+            .transform_point(Vec3::from_array(*position))
+            .distance(hit)
+            / radius;
+        if d >= 1.0 {
+            continue;
+        }
+        let share = (amount * (1.0 - d * d * (3.0 - 2.0 * d))).clamp(0.0, 1.0);
+        for (channel, target) in value.iter_mut().zip(color) {
+            let step = (target as f32 - *channel as f32) * share;
+            // At least a step each stroke, so colors get all the way there
+            let step = if step > 0.0 {
+                step.ceil()
+            } else {
+                step.floor()
+            };
+            *channel = (*channel as f32 + step).clamp(0.0, 255.0) as u8;
+        }
+        painted = true;
+    }
+    if painted && paint.colors != colors {
+        paint.colors = colors;
+    }
+}
+
+/// Vertex indices of `mesh`'s triangles, three by three.
+fn triangle_indices(mesh: &Mesh) -> Vec<u32> {
+    match mesh.indices() {
+        Some(indices) => indices.iter().map(|i| i as u32).collect(),
+        None => (0..mesh.count_vertices() as u32).collect(),
+    }
+}
+
+/// How many times `mesh`'s triangles have to be split for its edges to come down to
+/// [`PAINT_SPACING`] on the object.
+fn subdivisions_for(mesh: &Mesh, gt: &GlobalTransform) -> u8 {
+    let Some(positions) = mesh
+        .attribute(Mesh::ATTRIBUTE_POSITION)
+        .and_then(|a| a.as_float3())
+    else {
+        return 0;
+    };
+    let affine = gt.affine();
+    let mut longest = 0.0f32;
+    for tri in triangle_indices(mesh).chunks_exact(3) {
+        for (a, b) in [(tri[0], tri[1]), (tri[1], tri[2]), (tri[2], tri[0])] {
+            let (Some(a), Some(b)) = (positions.get(a as usize), positions.get(b as usize)) else {
+                continue;
+            };
+            let edge = affine.transform_vector3(Vec3::from_array(*b) - Vec3::from_array(*a));
+            longest = longest.max(edge.length());
+        }
+    }
+    let mut levels = 0;
+    while longest > PAINT_SPACING && levels < MAX_SUBDIVISIONS {
+        longest /= 2.0;
+        levels += 1;
+    }
+    levels
+}
+
+/// `mesh` with every triangle split in four, `levels` times over; the vertices added along an
+/// edge are shared by the triangles on both sides. Float attributes are interpolated (normals
+/// renormalized) and any others left out. Meshes that aren't triangle lists are copied as they
+/// are.
+fn subdivide(mesh: &Mesh, levels: u8) -> Mesh {
+    if levels == 0 || mesh.primitive_topology() != PrimitiveTopology::TriangleList {
+        return mesh.clone();
+    }
+    // Every attribute as four floats a vertex, and how many of them it uses
+    let mut attributes: Vec<_> = mesh
+        .attributes()
+        .filter_map(|(attribute, values)| {
+            let (width, values): (usize, Vec<Vec4>) = match values {
+                VertexAttributeValues::Float32x2(v) => (
+                    2,
+                    v.iter()
+                        .map(|v| Vec2::from(*v).extend(0.0).extend(0.0))
+                        .collect(),
+                ),
+                VertexAttributeValues::Float32x3(v) => {
+                    (3, v.iter().map(|v| Vec3::from(*v).extend(0.0)).collect())
+                }
+                VertexAttributeValues::Float32x4(v) => {
+                    (4, v.iter().map(|v| Vec4::from(*v)).collect())
+                }
+                _ => return None,
+            };
+            Some((*attribute, width, values))
+        })
+        .collect();
+    let mut indices = triangle_indices(mesh);
+
+    for _ in 0..levels {
+        let mut midpoints: HashMap<(u32, u32), u32> = HashMap::new();
+        let mut split = Vec::with_capacity(indices.len() * 4);
+        for tri in indices.chunks_exact(3) {
+            let mut midpoint = |a: u32, b: u32| {
+                *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                    let mut new = 0;
+                    for (_, _, values) in &mut attributes {
+                        new = values.len() as u32;
+                        let mid = (values[a as usize] + values[b as usize]) * 0.5;
+                        values.push(mid);
+                    }
+                    new
+                })
+            };
+            let (a, b, c) = (tri[0], tri[1], tri[2]);
+            let (ab, bc, ca) = (midpoint(a, b), midpoint(b, c), midpoint(c, a));
+            split.extend_from_slice(&[a, ab, ca, ab, b, bc, ca, bc, c, ab, bc, ca]);
+        }
+        indices = split;
+    }
+
+    let mut out = Mesh::new(PrimitiveTopology::TriangleList, mesh.asset_usage);
+    for (attribute, width, values) in attributes {
+        let normal = attribute.id == Mesh::ATTRIBUTE_NORMAL.id;
+        let values = match width {
+            2 => VertexAttributeValues::Float32x2(
+                values
+                    .iter()
+                    .map(|v| v.truncate().truncate().to_array())
+                    .collect(),
+            ),
+            3 => VertexAttributeValues::Float32x3(
+                values
+                    .iter()
+                    .map(|v| {
+                        let v = v.truncate();
+                        if normal { v.normalize_or_zero() } else { v }.to_array()
+                    })
+                    .collect(),
+            ),
+            _ => VertexAttributeValues::Float32x4(values.iter().map(|v| v.to_array()).collect()),
+        };
+        out.insert_attribute(attribute, values);
+    }
+    out.insert_indices(Indices::U32(indices));
+    out
+}
+
+/// Give painted objects their own (subdivided) mesh and keep its colors following their paint
+/// (and the mesh, when it's rebuilt); take the colors off again when the paint is cleared.
+fn apply_vertex_paint(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    q_painted: Query<(Entity, Ref<VertexPaint>, Ref<Mesh3d>, Option<&PaintedMesh>), With<Editable>>,
+    mut removed: RemovedComponents<VertexPaint>,
+    q_unpainted: Query<(&Mesh3d, &PaintedMesh), Without<VertexPaint>>,
+) {
+    for (e, paint, mesh3d, own) in &q_painted {
+        if !paint.is_changed() && !mesh3d.is_changed() {
+            continue;
+        }
+        let handle = if own.is_some_and(|own| own.0 == mesh3d.id()) {
+            mesh3d.0.clone()
+        } else {
+            let Some(copy) = meshes
+                .get(&mesh3d.0)
+                .map(|m| subdivide(m, paint.subdivisions))
+            else {
+                continue;
+            };
+            let handle = meshes.add(copy);
+            commands
+                .entity(e)
+                .insert((Mesh3d(handle.clone()), PaintedMesh(handle.id())));
+            handle
+        };
+        let Some(mesh) = meshes.get_mut(&handle) else {
+            continue;
+        };
+        let colors: Vec<[f32; 4]> = (0..mesh.count_vertices())
+            .map(|i| {
+                let [r, g, b, a] = paint.colors.get(i).copied().unwrap_or([255; 4]);
+                Color::srgba_u8(r, g, b, a).to_linear().to_f32_array()
+            })
+            .collect();
+        mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+    }
+    for e in removed.read() {
+        if let Ok((mesh3d, own)) = q_unpainted.get(e)
+            && own.0 == mesh3d.id()
+            && let Some(mesh) = meshes.get_mut(&mesh3d.0)
+        {
+            mesh.remove_attribute(Mesh::ATTRIBUTE_COLOR);
+        }
+    }
+}
+
+fn vertex_paint_window(
+    mut ctxs: EguiContexts,
+    mut commands: Commands,
+    edit_state: Res<SceneEditState>,
+    mut mode: ResMut<EditorMode>,
+    mut brush: ResMut<VertexBrush>,
+    ground_brush: Option<ResMut<GroundBrush>>,
+    q_selected: Query<(Entity, Has<VertexPaint>), (With<Selected>, With<Editable>, With<Mesh3d>)>,
+) {
+    if !edit_state.open {
+        return;
+    }
+    let Ok(ctx) = ctxs.ctx_mut() else {
+        return;
+    };
+
+    let mut active = brush.active;
+    let (mut color, mut intensity, mut radius) = (brush.color, brush.intensity, brush.radius);
+    let mut clear = false;
+    let playing = *mode == EditorMode::Play;
+    let painted = q_selected.iter().filter(|(_, painted)| *painted).count();
+    egui::Window::new("Vertex Paint")
+        .default_open(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.add_enabled(!playing, egui::Checkbox::new(&mut active, "Paint"))
+                .on_hover_text("Drag on the selected objects to tint their vertices");
+            ui.horizontal(|ui| {
+                ui.label("Color");
+                ui.color_edit_button_srgb(&mut color);
+            });
+            ui.add(egui::Slider::new(&mut intensity, 0.05..=1.0).text("Intensity"));
+            ui.add(
+                egui::Slider::new(&mut radius, 0.1..=10.0)
+                    .logarithmic(true)
+                    .text("Brush radius (m)"),
+            );
+            if q_selected.is_empty() {
+                ui.weak("Select the objects to paint.");
+            }
+            ui.weak("White leaves the object's color as it is.");
+            clear = ui
+                .add_enabled(painted > 0, egui::Button::new("Clear selected"))
+                .on_hover_text("Take the paint off the selected objects")
+                .clicked();
+        });
+
+    if active != brush.active {
+        brush.active = active;
+        if active {
+            // The brush owns left drags, which Move, Rotate and Scale (and the ground brush)
+            // would take too
+            mode.set_if_neq(EditorMode::Select);
+            if let Some(mut ground_brush) = ground_brush {
+                ground_brush.active = false;
+            }
+        }
+    }
+    if (color, intensity, radius) != (brush.color, brush.intensity, brush.radius) {
+        brush.color = color;
+        brush.intensity = intensity;
+        brush.radius = radius;
+    }
+    if clear {
+        for (e, _) in &q_selected {
+            commands.entity(e).remove::<VertexPaint>();
+        }
+    }
+}
@@ -0,0 +1,226 @@
+//! Objects that only show from some of the four orbit views (keys 1–4), for reveals and for
+//! cutaway walls that would block the current view. Tagged objects fade out and in as the
+//! camera snaps between views; selected ones always stay visible so they can be edited.
+//!
+//! Hiding goes through `Visibility`, shared with LOD culling: LOD keeps view-hidden objects
+//! hidden, and an object coming back into view stays hidden if LOD has culled it.
+//! Instanced tiles switch without fading (their shader ignores material alpha).
+
+use bevy::prelude::*;
+use bevy_egui::egui;
+use serde::{Deserialize, Serialize};
+
+use crate::asset_cache::AssetCache;
+use crate::camera::{OrbitCamera, OrbitSet};
+use crate::inspector::Selected;
+use crate::lod::Lod;
+
+/// Seconds for a full fade in or out.
+const FADE_SECS: f32 = 0.35;
+
+/// Which orbit views (1–4, by index) the object can be seen from. Objects without it show
+/// from every view.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct ViewLayers(pub [bool; 4]);
+
+impl Default for ViewLayers {
+    fn default() -> Self {
+        Self([true; 4])
+    }
+}
+
+/// Hidden because the camera is at a view the object isn't on.
+#[derive(Component, Clone)]
+pub struct ViewHidden;
+
+/// Mid-fade: the material's alpha is scaled by `alpha`, the authored look is kept here.
+#[derive(Component)]
+struct ViewFade {
+    alpha: f32,
+    base_alpha: f32,
+    base_mode: AlphaMode,
+    /// The cached material the object showed before fading on a copy, put back when done
+    shared: Option<Handle<StandardMaterial>>,
+}
+
+pub struct ViewLayersPlugin;
+impl Plugin for ViewLayersPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AssetCache>().add_systems(
+            Update,
+            (release_untagged, fade_view_layers)
+                .chain()
+                .after(OrbitSet::Pose),
+        );
+    }
+}
+
+/// Give `e` a material of its own (if it shares a cached one) set up for blending.
+fn start_fade(
+    commands: &mut Commands,
+    cache: &AssetCache,
+    materials: &mut Assets<StandardMaterial>,
+    e: Entity,
+    handle: &Handle<StandardMaterial>,
+    alpha: f32,
+) -> Option<ViewFade> {
+    let shared = cache.is_shared_material(handle).then(|| handle.clone());
+    let handle = cache.own_material(commands, materials, e, handle)?;
+    let mat = materials.get_mut(&handle)?;
+    let fade = ViewFade {
+        alpha,
+        base_alpha: mat.base_color.alpha(),
+        base_mode: mat.alpha_mode,
+        shared,
+    };
+    mat.alpha_mode = AlphaMode::Blend;
+    mat.base_color.set_alpha(fade.base_alpha * alpha);
+    Some(fade)
+}
+
+/// Back to the authored look: the shared material again, or the object's own one unfaded.
+fn end_fade(
+    commands: &mut Commands,
+    materials: &mut Assets<StandardMaterial>,
+    e: Entity,
+    handle: &Handle<StandardMaterial>,
+    fade: &ViewFade,
+) {
+    if let Some(shared) = &fade.shared {
+        commands.entity(e).insert(MeshMaterial3d(shared.clone()));
+    } else if let Some(mat) = materials.get_mut(handle) {
+        mat.alpha_mode = fade.base_mode;
+        mat.base_color.set_alpha(fade.base_alpha);
+    }
+}
+
+fn fade_view_layers(
+    mut commands: Commands,
+    // Follows the camera, so it keeps fading while the scene is paused
+    time: Res<Time<Real>>,
+    cache: Res<AssetCache>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    q_cam: Query<&OrbitCamera>,
+    mut q_objects: Query<(
+        Entity,
+        &ViewLayers,
+        &mut Visibility,
+        Option<&mut ViewFade>,
+        Option<&MeshMaterial3d<StandardMaterial>>,
+        Option<&Lod>,
+        Has<ViewHidden>,
+        Has<Selected>,
+    )>,
+) {
+    let Ok(cam) = q_cam.single() else {
+        return;
+    };
+    let view = cam.view();
+    let step = time.delta_secs() / FADE_SECS;
+
+    for (e, layers, mut vis, fade, mat, lod, hidden, selected) in &mut q_objects {
+        let wanted = selected || layers.0[view];
+        let Some(mut fade) = fade else {
+            if wanted != hidden {
+                continue;
+            }
+            // Start moving towards `wanted`
+            if wanted {
+                commands.entity(e).remove::<ViewHidden>();
+                if !lod.is_some_and(|l| l.is_culled()) {
+                    *vis = Visibility::Inherited;
+                }
+            }
+            let from = if wanted { 0.0 } else { 1.0 };
+            match mat.and_then(|m| start_fade(&mut commands, &cache, &mut materials, e, &m.0, from))
+            {
+                Some(fade) => {
+                    commands.entity(e).insert(fade);
+                }
+                // Nothing to fade (lights, decals): switch right away
+                None if !wanted => {
+                    commands.entity(e).insert(ViewHidden);
+                    *vis = Visibility::Hidden;
+                }
+                None => {}
+            }
+            continue;
+        };
+
+        let target = if wanted { 1.0 } else { 0.0 };
+        fade.alpha = if wanted {
+            (fade.alpha + step).min(target)
+        } else {
+            (fade.alpha - step).max(target)
+        };
+        let Some(mat) = mat else {
+            commands.entity(e).remove::<ViewFade>();
+            continue;
+        };
+        if fade.alpha != target {
+            if let Some(m) = materials.get_mut(&mat.0) {
+                m.base_color.set_alpha(fade.base_alpha * fade.alpha);
+            }
+            continue;
+        }
+        // Done: back to the authored material, hidden or shown for good
+        end_fade(&mut commands, &mut materials, e, &mat.0, &fade);
+        commands.entity(e).remove::<ViewFade>();
+        if !wanted {
+            commands.entity(e).insert(ViewHidden);
+            *vis = Visibility::Hidden;
+        }
+    }
+}
+
+/// Show objects again (and finish their fades) once their `ViewLayers` is removed.
+fn release_untagged(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut q_untagged: Query<
+        (
+            Entity,
+            &mut Visibility,
+            Option<&ViewFade>,
+            Option<&MeshMaterial3d<StandardMaterial>>,
+            Option<&Lod>,
+            Has<ViewHidden>,
+        ),
+        (Without<ViewLayers>, Or<(With<ViewHidden>, With<ViewFade>)>),
+    >,
+) {
+    for (e, mut vis, fade, mat, lod, hidden) in &mut q_untagged {
+        if let (Some(fade), Some(mat)) = (fade, mat) {
+            end_fade(&mut commands, &mut materials, e, &mat.0, fade);
+        }
+        if hidden && !lod.is_some_and(|l| l.is_culled()) {
+            *vis = Visibility::Inherited;
+        }
+        commands.entity(e).remove::<(ViewFade, ViewHidden)>();
+    }
+}
+
+/// Inspector section: the views an object shows from.
+pub fn view_layers_inspector_ui(
+    ui: &mut egui::Ui,
+    commands: &mut Commands,
+    entity: Entity,
+    layers: Option<&ViewLayers>,
+) -> bool {
+    let mut views = layers.copied().unwrap_or_default();
+    let mut changed = false;
+    ui.horizontal(|ui| {
+        ui.label("Visible from views");
+        for (i, on) in views.0.iter_mut().enumerate() {
+            changed |= ui.checkbox(on, (i + 1).to_string()).changed();
+        }
+    });
+    if changed {
+        if views == ViewLayers::default() {
+            commands.entity(entity).remove::<ViewLayers>();
+        } else {
+            commands.entity(entity).insert(views);
+        }
+    }
+    changed
+}